@@ -1,27 +1,227 @@
 //! CLI argument parsing and help text
 
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+/// How subcommand results are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+/// A standalone subcommand that runs against the index without an MCP client.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Index the whole repository, or only `paths`
+    Index { paths: Vec<String> },
+    Search {
+        query: String,
+        limit: Option<u32>,
+        exported_only: bool,
+    },
+    Definition {
+        name: String,
+        file: Option<String>,
+        limit: Option<u32>,
+    },
+    References {
+        name: String,
+        file: Option<String>,
+        reference_type: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Check the public API of each package against a revision or stored surface
+    /// files, failing on breaking changes
+    ApiCheck {
+        package: Option<String>,
+        base: Option<String>,
+        baseline: Option<String>,
+        head: Option<String>,
+        write_to: Option<String>,
+    },
+    /// Invoke any MCP tool by name with JSON arguments
+    Call {
+        tool: String,
+        arguments: Map<String, Value>,
+    },
+    /// Serve the Language Server Protocol over stdio
+    Lsp,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub command: Command,
+    pub output: OutputFormat,
+}
+
+/// Only the first argument counts, so subcommand arguments such as the query of
+/// `search help` are left alone.
 pub fn wants_help(args: &[String]) -> bool {
-    args.iter()
-        .skip(1)
-        .any(|a| a == "-h" || a == "--help" || a == "help")
+    args.get(1)
+        .is_some_and(|a| a == "-h" || a == "--help" || a == "help")
 }
 
 pub fn wants_version(args: &[String]) -> bool {
-    args.iter()
-        .skip(1)
-        .any(|a| a == "-V" || a == "--version" || a == "version")
+    args.get(1)
+        .is_some_and(|a| a == "-V" || a == "--version" || a == "version")
+}
+
+/// Parse a subcommand invocation. Returns `None` when the server should run
+/// (no arguments, or `serve`).
+pub fn parse_command(args: &[String]) -> Result<Option<Invocation>> {
+    let Some(name) = args.get(1) else {
+        return Ok(None);
+    };
+    if name == "serve" {
+        return Ok(None);
+    }
+
+    let mut positional = Vec::new();
+    let mut output = OutputFormat::Human;
+    let mut limit = None;
+    let mut file = None;
+    let mut reference_type = None;
+    let mut exported_only = false;
+    let mut json_args = None;
+    let mut package = None;
+    let mut base = None;
+    let mut baseline = None;
+    let mut head = None;
+    let mut write_to = None;
+
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        let mut value = |flag: &str| {
+            rest.next()
+                .cloned()
+                .ok_or_else(|| anyhow!("Missing value for {flag}"))
+        };
+        match arg.as_str() {
+            "--output" | "-o" => {
+                output = match value(arg)?.as_str() {
+                    "json" => OutputFormat::Json,
+                    "human" | "text" => OutputFormat::Human,
+                    other => {
+                        return Err(anyhow!("Invalid --output: {other} (expected json|human)"))
+                    }
+                }
+            }
+            "--limit" | "-n" => {
+                let raw = value(arg)?;
+                limit = Some(
+                    raw.parse::<u32>()
+                        .map_err(|err| anyhow!("Invalid --limit '{raw}': {err}"))?,
+                );
+            }
+            "--file" => file = Some(value(arg)?),
+            "--type" => reference_type = Some(value(arg)?),
+            "--exported-only" => exported_only = true,
+            "--package" => package = Some(value(arg)?),
+            "--base" => base = Some(value(arg)?),
+            "--baseline" => baseline = Some(value(arg)?),
+            "--head" => head = Some(value(arg)?),
+            "--write" => write_to = Some(value(arg)?),
+            "--json" if name == "call" => json_args = Some(value(arg)?),
+            "--json" => output = OutputFormat::Json,
+            flag if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(anyhow!("Unknown option for {name}: {flag}"))
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let single = |what: &str| match positional.as_slice() {
+        [one] => Ok(one.clone()),
+        [] => Err(anyhow!("{name} requires a {what}")),
+        _ => Err(anyhow!("{name} takes a single {what}")),
+    };
+
+    let command = match name.as_str() {
+        "index" => Command::Index {
+            paths: positional.clone(),
+        },
+        "search" => {
+            if positional.is_empty() {
+                return Err(anyhow!("search requires a query"));
+            }
+            Command::Search {
+                query: positional.join(" "),
+                limit,
+                exported_only,
+            }
+        }
+        "definition" => Command::Definition {
+            name: single("symbol name")?,
+            file,
+            limit,
+        },
+        "references" => Command::References {
+            name: single("symbol name")?,
+            file,
+            reference_type,
+            limit,
+        },
+        "api-check" => {
+            if !positional.is_empty() {
+                return Err(anyhow!("api-check takes no arguments, only options"));
+            }
+            if base.is_some() && baseline.is_some() {
+                return Err(anyhow!("api-check takes --base or --baseline, not both"));
+            }
+            Command::ApiCheck {
+                package,
+                base,
+                baseline,
+                head,
+                write_to,
+            }
+        }
+        "call" => {
+            let tool = single("tool name")?;
+            let arguments = match json_args {
+                Some(raw) => match serde_json::from_str::<Value>(&raw) {
+                    Ok(Value::Object(map)) => map,
+                    Ok(_) => return Err(anyhow!("--json must be a JSON object")),
+                    Err(err) => return Err(anyhow!("Invalid --json: {err}")),
+                },
+                None => Map::new(),
+            };
+            Command::Call { tool, arguments }
+        }
+        "lsp" => Command::Lsp,
+        other => return Err(anyhow!("Unknown command: {other} (see --help)")),
+    };
+
+    Ok(Some(Invocation { command, output }))
 }
 
 pub fn print_help() {
     println!("code-intelligence-mcp-server");
     println!();
-    println!("MCP server over stdio for local code intelligence (index + search + context).");
+    println!(
+        "MCP server over stdio or HTTP for local code intelligence (index + search + context)."
+    );
     println!();
     println!("Usage:");
-    println!("  code-intelligence-mcp-server");
+    println!("  code-intelligence-mcp-server [serve]");
+    println!("  code-intelligence-mcp-server index [PATH...]");
+    println!("  code-intelligence-mcp-server search <QUERY> [--limit N] [--exported-only]");
+    println!("  code-intelligence-mcp-server definition <NAME> [--file PATH] [--limit N]");
+    println!(
+        "  code-intelligence-mcp-server references <NAME> [--file PATH] [--type TYPE] [--limit N]"
+    );
+    println!("  code-intelligence-mcp-server api-check [--base REV | --baseline DIR] [--head REV]");
+    println!("                                         [--package NAME] [--write DIR]");
+    println!("  code-intelligence-mcp-server call <TOOL> [--json '{{...}}']");
+    println!("  code-intelligence-mcp-server lsp     (language server over stdio)");
     println!("  code-intelligence-mcp-server --help");
     println!("  code-intelligence-mcp-server --version");
     println!();
+    println!("Subcommands run against the index in DB_PATH and exit. Add --output json");
+    println!("(or --json, except for call) for machine-readable output. api-check exits");
+    println!("with status 1 when it finds breaking API changes.");
+    println!();
     println!("Required env:");
     println!("  BASE_DIR=/absolute/path/to/repo");
     println!();
@@ -38,15 +238,37 @@ pub fn print_help() {
     println!("  TANTIVY_INDEX_PATH=~/.cimcp/tantivy-index (default: global ~/.cimcp directory)");
     println!("  MAX_CONTEXT_BYTES=200000");
     println!("  WATCH_MODE=true|false                (default: true)");
+    println!(
+        "  WATCH_BACKEND=auto|events|poll       (default: auto; events with polling fallback)"
+    );
     println!("  REPO_ROOTS=/path/a,/path/b           (default: BASE_DIR only)");
+    println!("  MCP_TRANSPORT=stdio|http             (default: stdio; http serves /mcp and /sse)");
+    println!("  MCP_HTTP_BIND=host:port              (default: 127.0.0.1:8765)");
+    println!(
+        "  MCP_HTTP_AUTH_TOKEN=secret           (optional; require Authorization: Bearer <token>)"
+    );
     println!();
     println!("Embeddings auto-detection:");
     println!("  - Defaults to fastembed (using BGE Base v1.5).");
     println!("  - Set EMBEDDINGS_BACKEND=hash to use deterministic hashing (no model).");
     println!();
     println!("Tools:");
-    println!("  search_code, refresh_index, get_definition, find_references, get_file_symbols,");
-    println!("  get_call_hierarchy, get_type_graph, get_usage_examples, get_index_stats, get_similarity_cluster");
+    let names = code_intelligence_mcp_server::server::tool_list()
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect::<Vec<_>>();
+    let mut line = String::new();
+    for (i, name) in names.iter().enumerate() {
+        let sep = if i + 1 < names.len() { "," } else { "" };
+        if !line.is_empty() && line.len() + name.len() + sep.len() > 88 {
+            println!("  {}", line.trim_end());
+            line.clear();
+        }
+        line.push_str(name);
+        line.push_str(sep);
+        line.push(' ');
+    }
+    println!("  {}", line.trim_end());
 }
 
 pub fn print_version() {
@@ -65,5 +287,133 @@ mod tests {
         assert!(wants_version(&["bin".to_string(), "-V".to_string()]));
         assert!(!wants_help(&["bin".to_string()]));
         assert!(!wants_version(&["bin".to_string()]));
+        let search_help = ["bin", "search", "help"].map(String::from);
+        assert!(!wants_help(&search_help));
+        let search_version = ["bin", "search", "--version"].map(String::from);
+        assert!(!wants_version(&search_version));
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        std::iter::once("bin")
+            .chain(list.iter().copied())
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn parse_command_runs_server_without_subcommand() {
+        assert_eq!(parse_command(&args(&[])).unwrap(), None);
+        assert_eq!(parse_command(&args(&["serve"])).unwrap(), None);
+        assert_eq!(
+            parse_command(&args(&["lsp"])).unwrap().unwrap().command,
+            Command::Lsp
+        );
+        assert!(parse_command(&args(&["frobnicate"])).is_err());
+    }
+
+    #[test]
+    fn parse_command_reads_subcommands_and_options() {
+        let inv = parse_command(&args(&[
+            "search", "auth", "token", "--limit", "3", "--json",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(inv.output, OutputFormat::Json);
+        assert_eq!(
+            inv.command,
+            Command::Search {
+                query: "auth token".to_string(),
+                limit: Some(3),
+                exported_only: false,
+            }
+        );
+
+        let inv = parse_command(&args(&[
+            "references",
+            "Foo",
+            "--type",
+            "call",
+            "-o",
+            "human",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(inv.output, OutputFormat::Human);
+        assert_eq!(
+            inv.command,
+            Command::References {
+                name: "Foo".to_string(),
+                file: None,
+                reference_type: Some("call".to_string()),
+                limit: None,
+            }
+        );
+
+        let inv = parse_command(&args(&["index", "src/a.ts", "src/b.ts"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            inv.command,
+            Command::Index {
+                paths: vec!["src/a.ts".to_string(), "src/b.ts".to_string()],
+            }
+        );
+
+        assert!(parse_command(&args(&["definition"])).is_err());
+        assert!(parse_command(&args(&["definition", "A", "B"])).is_err());
+        assert!(parse_command(&args(&["search", "x", "--limit"])).is_err());
+    }
+
+    #[test]
+    fn parse_command_reads_api_check_options() {
+        let inv = parse_command(&args(&[
+            "api-check",
+            "--base",
+            "origin/main",
+            "--package",
+            "@acme/core",
+            "--write",
+            "api",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            inv.command,
+            Command::ApiCheck {
+                package: Some("@acme/core".to_string()),
+                base: Some("origin/main".to_string()),
+                baseline: None,
+                head: None,
+                write_to: Some("api".to_string()),
+            }
+        );
+
+        assert!(parse_command(&args(&["api-check", "main"])).is_err());
+        assert!(
+            parse_command(&args(&["api-check", "--base", "main", "--baseline", "api"])).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_command_call_takes_json_arguments() {
+        let inv = parse_command(&args(&[
+            "call",
+            "get_file_symbols",
+            "--json",
+            r#"{"file_path":"src/a.ts"}"#,
+            "--output",
+            "json",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(inv.output, OutputFormat::Json);
+        let Command::Call { tool, arguments } = inv.command else {
+            panic!("expected call");
+        };
+        assert_eq!(tool, "get_file_symbols");
+        assert_eq!(arguments["file_path"], "src/a.ts");
+
+        assert!(parse_command(&args(&["call", "x", "--json", "[1]"])).is_err());
+        assert!(parse_command(&args(&["call", "x", "--json", "{"])).is_err());
     }
 }
@@ -0,0 +1,394 @@
+//! Standalone CLI subcommands
+//!
+//! Runs the same `handlers::handle_*` functions the MCP tools use, directly against
+//! `AppState`, and prints the result as JSON or as plain text.
+
+use std::io::Write;
+
+use anyhow::{anyhow, Result};
+use rust_mcp_sdk::schema::{CallToolRequestParams, ContentBlock};
+use serde_json::Value;
+
+use code_intelligence_mcp_server::handlers::{
+    handle_check_api_surface, handle_find_references, handle_get_definition, handle_refresh_index,
+    handle_search_code, AppState,
+};
+use code_intelligence_mcp_server::path::Utf8PathBuf;
+use code_intelligence_mcp_server::server::call_tool;
+use code_intelligence_mcp_server::tools::{
+    CheckApiSurfaceTool, FindReferencesTool, GetDefinitionTool, RefreshIndexTool, SearchCodeTool,
+};
+
+use crate::cli::{Command, Invocation, OutputFormat};
+
+pub async fn run(state: &AppState, invocation: Invocation) -> Result<()> {
+    let Invocation { command, output } = invocation;
+    let api_check = matches!(command, Command::ApiCheck { .. });
+    let (result, human): (Value, fn(&Value) -> String) = match command {
+        Command::Index { paths } => {
+            let files = if paths.is_empty() {
+                None
+            } else {
+                Some(absolute_paths(&paths)?)
+            };
+            let tool = RefreshIndexTool {
+                files,
+                revision: None,
+            };
+            let result = handle_refresh_index(state, tool).await?;
+            (result, format_index)
+        }
+        Command::Search {
+            query,
+            limit,
+            exported_only,
+        } => {
+            let tool = SearchCodeTool {
+                query,
+                limit,
+                exported_only: Some(exported_only),
+                revision: None,
+            };
+            (
+                handle_search_code(&state.retriever, tool).await?,
+                format_search,
+            )
+        }
+        Command::Definition { name, file, limit } => {
+            let tool = GetDefinitionTool {
+                symbol_name: name,
+                file,
+                limit,
+                revision: None,
+            };
+            (
+                handle_get_definition(state, tool).await?,
+                format_definitions,
+            )
+        }
+        Command::References {
+            name,
+            file,
+            reference_type,
+            limit,
+        } => {
+            let tool = FindReferencesTool {
+                symbol_name: name,
+                file,
+                reference_type,
+                limit,
+                revision: None,
+            };
+            (handle_find_references(state, tool)?, format_references)
+        }
+        Command::ApiCheck {
+            package,
+            base,
+            baseline,
+            head,
+            write_to,
+        } => {
+            let tool = CheckApiSurfaceTool {
+                package,
+                base,
+                baseline,
+                head,
+                write_to,
+                limit: Some(u32::MAX),
+            };
+            (
+                handle_check_api_surface(state, tool).await?,
+                format_api_check,
+            )
+        }
+        Command::Call { tool, arguments } => {
+            return run_call(state, tool, arguments, output).await;
+        }
+        Command::Lsp => return Err(anyhow!("lsp is a long-running mode, not a command")),
+    };
+
+    match output {
+        OutputFormat::Json => emit(&serde_json::to_string_pretty(&result)?)?,
+        OutputFormat::Human => emit(human(&result).trim_end())?,
+    }
+
+    // Breaking API changes fail the check, for CI
+    let breaking = u64_field(&result["summary"], "breaking");
+    if api_check && breaking > 0 {
+        return Err(anyhow!("{breaking} breaking API changes"));
+    }
+    Ok(())
+}
+
+async fn run_call(
+    state: &AppState,
+    tool: String,
+    arguments: serde_json::Map<String, Value>,
+    output: OutputFormat,
+) -> Result<()> {
+    let params = CallToolRequestParams {
+        name: tool.clone(),
+        arguments: Some(arguments),
+        meta: None,
+        task: None,
+    };
+    let result = call_tool(state, params)
+        .await
+        .map_err(|err| anyhow!("{tool} failed: {err}"))?;
+
+    let text = result
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::TextContent(text) => Some(text.text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match output {
+        // Tools return pretty-printed JSON text; re-emit it as a single JSON document
+        OutputFormat::Json => match serde_json::from_str::<Value>(&text) {
+            Ok(value) => emit(&serde_json::to_string_pretty(&value)?)?,
+            Err(_) => emit(&Value::String(text).to_string())?,
+        },
+        OutputFormat::Human => emit(&text)?,
+    }
+
+    if result.is_error == Some(true) {
+        return Err(anyhow!("{tool} returned an error"));
+    }
+    Ok(())
+}
+
+/// Print to stdout; a closed pipe (e.g. `| head`) is not an error.
+fn emit(text: &str) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    match writeln!(stdout, "{text}").and_then(|_| stdout.flush()) {
+        Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        other => Ok(other?),
+    }
+}
+
+/// Resolve CLI paths against the working directory, as a shell user expects.
+fn absolute_paths(paths: &[String]) -> Result<Vec<String>> {
+    let cwd = std::env::current_dir()?;
+    paths
+        .iter()
+        .map(|p| {
+            let abs = cwd.join(p);
+            Utf8PathBuf::from_path_buf(abs)
+                .map(|p| p.to_string())
+                .map_err(|p| anyhow!("Path is not valid UTF-8: {}", p.display()))
+        })
+        .collect()
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+fn u64_field(value: &Value, key: &str) -> u64 {
+    value.get(key).and_then(Value::as_u64).unwrap_or(0)
+}
+
+fn array_field<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+fn format_disambiguation(out: &mut String, result: &Value) {
+    if let Some(hint) = result
+        .pointer("/disambiguation/hint")
+        .and_then(Value::as_str)
+    {
+        out.push_str(&format!("\nnote: {hint}\n"));
+    }
+}
+
+fn format_index(result: &Value) -> String {
+    let stats = &result["stats"];
+    format!(
+        "Indexed {} files ({} symbols); {} unchanged, {} skipped, {} deleted, {} re-resolved ({} scanned)\n",
+        u64_field(stats, "files_indexed"),
+        u64_field(stats, "symbols_indexed"),
+        u64_field(stats, "files_unchanged"),
+        u64_field(stats, "files_skipped"),
+        u64_field(stats, "files_deleted"),
+        u64_field(stats, "files_reresolved"),
+        u64_field(stats, "files_scanned"),
+    )
+}
+
+fn format_search(result: &Value) -> String {
+    let hits = array_field(result, "hits");
+    if hits.is_empty() {
+        return format!("No results for '{}'\n", str_field(result, "query"));
+    }
+    let mut out = String::new();
+    for (i, hit) in hits.iter().enumerate() {
+        out.push_str(&format!(
+            "{:>2}. {:>7.3}  {} {}  {}\n",
+            i + 1,
+            hit.get("score").and_then(Value::as_f64).unwrap_or(0.0),
+            str_field(hit, "kind"),
+            str_field(hit, "name"),
+            str_field(hit, "file_path"),
+        ));
+    }
+    out
+}
+
+fn format_definitions(result: &Value) -> String {
+    let definitions = array_field(result, "definitions");
+    if definitions.is_empty() {
+        return format!(
+            "No definition found for '{}'\n",
+            str_field(result, "symbol_name")
+        );
+    }
+    let mut out = String::new();
+    for def in definitions {
+        out.push_str(&format!(
+            "{}:{}-{}  {} {}\n",
+            str_field(def, "file_path"),
+            u64_field(def, "start_line"),
+            u64_field(def, "end_line"),
+            str_field(def, "kind"),
+            str_field(def, "name"),
+        ));
+        for line in str_field(def, "text").lines() {
+            out.push_str(&format!("    {line}\n"));
+        }
+        out.push('\n');
+    }
+    format_disambiguation(&mut out, result);
+    out
+}
+
+fn format_references(result: &Value) -> String {
+    let references = array_field(result, "references");
+    if references.is_empty() {
+        return format!(
+            "No references found for '{}'\n",
+            str_field(result, "symbol_name")
+        );
+    }
+    let mut out = String::new();
+    for r in references {
+        out.push_str(&format!(
+            "{}:{}  {} -[{}]-> {}\n",
+            str_field(r, "at_file"),
+            u64_field(r, "at_line"),
+            str_field(r, "from_symbol_name"),
+            str_field(r, "reference_type"),
+            str_field(r, "to_symbol_name"),
+        ));
+    }
+    format_disambiguation(&mut out, result);
+    out
+}
+
+fn format_api_check(result: &Value) -> String {
+    let mut out = String::new();
+    for package in array_field(result, "packages") {
+        out.push_str(&format!(
+            "{}  {} exported symbols\n",
+            str_field(package, "name"),
+            u64_field(package, "symbols"),
+        ));
+    }
+    for path in array_field(result, "written") {
+        out.push_str(&format!("wrote {}\n", path.as_str().unwrap_or("")));
+    }
+    let Some(against) = result.get("against").and_then(Value::as_str) else {
+        return out;
+    };
+    for change in array_field(result, "changes") {
+        let symbol = match str_field(change, "symbol") {
+            "" => String::new(),
+            symbol => format!(" {symbol}"),
+        };
+        out.push_str(&format!(
+            "{:<12}  {}{}  {}: {}\n",
+            if change["breaking"] == true {
+                "breaking"
+            } else {
+                "non-breaking"
+            },
+            str_field(change, "package"),
+            symbol,
+            str_field(change, "change").replace('_', " "),
+            str_field(change, "detail"),
+        ));
+    }
+    out.push_str(&format!(
+        "{} breaking, {} non-breaking changes against {}\n",
+        u64_field(&result["summary"], "breaking"),
+        u64_field(&result["summary"], "non_breaking"),
+        against,
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn human_output_lists_hits_and_references() {
+        let search = json!({
+            "query": "auth",
+            "hits": [
+                { "score": 0.91234, "kind": "function", "name": "login", "file_path": "src/auth.ts" }
+            ]
+        });
+        assert_eq!(
+            format_search(&search),
+            " 1.   0.912  function login  src/auth.ts\n"
+        );
+        assert_eq!(
+            format_search(&json!({ "query": "nope", "hits": [] })),
+            "No results for 'nope'\n"
+        );
+
+        let refs = json!({
+            "symbol_name": "login",
+            "references": [{
+                "at_file": "src/app.ts", "at_line": 12,
+                "from_symbol_name": "main", "reference_type": "call", "to_symbol_name": "login"
+            }],
+            "disambiguation": { "hint": "Multiple 'login' symbols found in 2 files." }
+        });
+        let out = format_references(&refs);
+        assert!(out.starts_with("src/app.ts:12  main -[call]-> login\n"));
+        assert!(out.contains("note: Multiple 'login' symbols"));
+    }
+
+    #[test]
+    fn human_api_check_lists_changes_by_severity() {
+        let check = json!({
+            "against": "main",
+            "packages": [{ "name": "core", "symbols": 12 }],
+            "summary": { "breaking": 1, "non_breaking": 1 },
+            "changes": [
+                { "package": "core", "symbol": "parse", "change": "parameter_added", "breaking": true,
+                  "detail": "required parameter `strict` added" },
+                { "package": "cli", "symbol": "", "change": "package_added", "breaking": false,
+                  "detail": "3 exported symbols" }
+            ],
+            "written": []
+        });
+        assert_eq!(
+            format_api_check(&check),
+            "core  12 exported symbols\n\
+             breaking      core parse  parameter added: required parameter `strict` added\n\
+             non-breaking  cli  package added: 3 exported symbols\n\
+             1 breaking, 1 non-breaking changes against main\n"
+        );
+    }
+}
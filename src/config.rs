@@ -52,6 +52,26 @@ pub enum EmbeddingsBackend {
     JinaCode,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchBackend {
+    /// Filesystem events, falling back to polling when the watcher cannot start
+    Auto,
+    /// Filesystem events only
+    Events,
+    /// Periodic fingerprint scan of all repo roots
+    Poll,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum McpTransport {
+    /// One client per process over stdin/stdout
+    Stdio,
+    /// Streamable HTTP (and legacy SSE) for many concurrent clients
+    Http,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub base_dir: Utf8PathBuf,
@@ -78,11 +98,14 @@ pub struct Config {
     pub rank_test_penalty: f32,
     pub rank_popularity_weight: f32,
     pub rank_popularity_cap: u64,
+    pub rank_history_weight: f32,
+    pub history_max_commits: usize, // Commits mined for history signals (0 = off)
     pub index_patterns: Vec<String>,
     pub exclude_patterns: Vec<String>,
     pub watch_mode: bool,
     pub watch_debounce_ms: u64,
     pub watch_min_index_interval_ms: u64, // Minimum time between index runs in watch mode
+    pub watch_backend: WatchBackend,
     pub max_context_bytes: usize,
     pub index_node_modules: bool,
     pub repo_roots: Vec<Utf8PathBuf>,
@@ -133,6 +156,11 @@ pub struct Config {
 
     // Package detection config (09-04)
     pub package_detection_enabled: bool,
+
+    // Transport config
+    pub mcp_transport: McpTransport,
+    pub http_bind_addr: String, // host:port for the HTTP transport
+    pub http_auth_token: Option<String>,
 }
 
 impl Config {
@@ -279,6 +307,16 @@ impl Config {
             .map(parse_u64)
             .transpose()?
             .unwrap_or(50);
+        let rank_history_weight = optional_env("RANK_HISTORY_WEIGHT")
+            .as_deref()
+            .map(parse_any_f32)
+            .transpose()?
+            .unwrap_or(0.05);
+        let history_max_commits = optional_env("HISTORY_MAX_COMMITS")
+            .as_deref()
+            .map(parse_usize)
+            .transpose()?
+            .unwrap_or(1000);
 
         let index_patterns = parse_csv_or_default(
             optional_env("INDEX_PATTERNS").as_deref(),
@@ -314,6 +352,12 @@ impl Config {
             .transpose()?
             .unwrap_or(5000); // Default 5 seconds between index runs
 
+        let watch_backend = optional_env("WATCH_BACKEND")
+            .as_deref()
+            .map(parse_watch_backend)
+            .transpose()?
+            .unwrap_or(WatchBackend::Auto);
+
         let max_context_bytes = optional_env("MAX_CONTEXT_BYTES")
             .as_deref()
             .map(parse_usize)
@@ -482,6 +526,19 @@ impl Config {
             .transpose()?
             .unwrap_or(true); // Default enabled
 
+        // Transport config
+        let mcp_transport = optional_env("MCP_TRANSPORT")
+            .as_deref()
+            .map(parse_mcp_transport)
+            .transpose()?
+            .unwrap_or(McpTransport::Stdio);
+        let http_bind_addr = optional_env("MCP_HTTP_BIND")
+            .as_deref()
+            .map(parse_bind_addr)
+            .transpose()?
+            .unwrap_or_else(|| "127.0.0.1:8765".to_string());
+        let http_auth_token = optional_env("MCP_HTTP_AUTH_TOKEN");
+
         Ok(Self {
             base_dir,
             db_path,
@@ -508,11 +565,14 @@ impl Config {
             rank_test_penalty,
             rank_popularity_weight,
             rank_popularity_cap,
+            rank_history_weight,
+            history_max_commits,
             index_patterns,
             exclude_patterns,
             watch_mode,
             watch_debounce_ms,
             watch_min_index_interval_ms,
+            watch_backend,
             max_context_bytes,
             index_node_modules,
             repo_roots,
@@ -563,6 +623,11 @@ impl Config {
 
             // Package detection config (09-04)
             package_detection_enabled,
+
+            // Transport config
+            mcp_transport,
+            http_bind_addr,
+            http_auth_token,
         })
     }
 
@@ -583,6 +648,14 @@ impl Config {
         Ok(relative.as_str().to_string())
     }
 
+    /// Directory holding the indexes of past revisions, next to the database.
+    pub fn snapshot_dir(&self) -> Utf8PathBuf {
+        self.db_path
+            .parent()
+            .map(|dir| dir.join("snapshots"))
+            .unwrap_or_else(|| Utf8PathBuf::from("snapshots"))
+    }
+
     /// Get the relative path from base to the given path (PathBuf version for compatibility).
     pub fn path_relative_to_base_path(&self, path: &Path) -> Result<String> {
         let utf8_path = to_utf8_pathbuf(path)?;
@@ -664,6 +737,45 @@ fn parse_embeddings_backend(value: &str) -> Result<EmbeddingsBackend> {
     }
 }
 
+fn parse_watch_backend(value: &str) -> Result<WatchBackend> {
+    match value.trim().to_lowercase().as_str() {
+        "auto" => Ok(WatchBackend::Auto),
+        "events" | "event" | "notify" => Ok(WatchBackend::Events),
+        "poll" | "polling" => Ok(WatchBackend::Poll),
+        other => Err(anyhow!("Invalid WATCH_BACKEND: {other}")),
+    }
+}
+
+fn parse_mcp_transport(value: &str) -> Result<McpTransport> {
+    match value.trim().to_lowercase().as_str() {
+        "stdio" => Ok(McpTransport::Stdio),
+        "http" | "streamable-http" | "sse" => Ok(McpTransport::Http),
+        other => Err(anyhow!("Invalid MCP_TRANSPORT: {other}")),
+    }
+}
+
+fn parse_bind_addr(value: &str) -> Result<String> {
+    let value = value.trim();
+    split_bind_addr(value).map_err(|err| anyhow!("Invalid MCP_HTTP_BIND '{value}': {err}"))?;
+    Ok(value.to_string())
+}
+
+/// Split a `host:port` bind address into its parts. IPv6 hosts may be bracketed
+/// (`[::1]:8765`); the brackets are stripped.
+pub fn split_bind_addr(value: &str) -> Result<(String, u16)> {
+    let (host, port) = value
+        .rsplit_once(':')
+        .ok_or_else(|| anyhow!("expected host:port"))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return Err(anyhow!("missing host"));
+    }
+    let port = port
+        .parse::<u16>()
+        .map_err(|err| anyhow!("invalid port '{port}': {err}"))?;
+    Ok((host.to_string(), port))
+}
+
 fn parse_usize(value: &str) -> Result<usize> {
     value
         .trim()
@@ -772,10 +884,13 @@ mod tests {
             "RANK_TEST_PENALTY",
             "RANK_POPULARITY_WEIGHT",
             "RANK_POPULARITY_CAP",
+            "RANK_HISTORY_WEIGHT",
+            "HISTORY_MAX_COMMITS",
             "INDEX_PATTERNS",
             "EXCLUDE_PATTERNS",
             "WATCH_MODE",
             "WATCH_DEBOUNCE_MS",
+            "WATCH_BACKEND",
             "MAX_CONTEXT_BYTES",
             "INDEX_NODE_MODULES",
             "REPO_ROOTS",
@@ -816,6 +931,10 @@ mod tests {
             "METRICS_PORT",
             // Package detection config (09-04)
             "PACKAGE_DETECTION_ENABLED",
+            // Transport config
+            "MCP_TRANSPORT",
+            "MCP_HTTP_BIND",
+            "MCP_HTTP_AUTH_TOKEN",
         ] {
             std::env::remove_var(k);
         }
@@ -835,8 +954,8 @@ mod tests {
         clear_env();
         let base = tmp_dir();
         let home = tmp_home_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
-        std::env::set_var("HOME", home.to_string());
+        std::env::set_var("BASE_DIR", &base);
+        std::env::set_var("HOME", &home);
 
         let cfg = Config::from_env().unwrap();
         assert_eq!(cfg.embeddings_backend, EmbeddingsBackend::JinaCode);
@@ -846,6 +965,7 @@ mod tests {
         let expected_global = home.join(".cimcp");
         assert_eq!(cfg.db_path, expected_global.join("code-intelligence.db"));
         assert_eq!(cfg.vector_db_path, expected_global.join("vectors"));
+        assert_eq!(cfg.snapshot_dir(), expected_global.join("snapshots"));
         assert_eq!(cfg.tantivy_index_path, expected_global.join("tantivy-index"));
         assert_eq!(cfg.repo_roots, vec![cfg.base_dir.clone()]);
     }
@@ -856,8 +976,8 @@ mod tests {
         clear_env();
         let base = tmp_dir();
         let home = tmp_home_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
-        std::env::set_var("HOME", home.to_string());
+        std::env::set_var("BASE_DIR", &base);
+        std::env::set_var("HOME", &home);
 
         // Default should be JinaCode
         let cfg = Config::from_env().unwrap();
@@ -878,9 +998,9 @@ mod tests {
         clear_env();
         let base = tmp_dir();
         let custom = tmp_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
+        std::env::set_var("BASE_DIR", &base);
         std::env::set_var("EMBEDDINGS_BACKEND", "fastembed");
-        std::env::set_var("EMBEDDINGS_MODEL_DIR", custom.to_string());
+        std::env::set_var("EMBEDDINGS_MODEL_DIR", &custom);
 
         let cfg = Config::from_env().unwrap();
         assert_eq!(cfg.embeddings_backend, EmbeddingsBackend::FastEmbed);
@@ -892,7 +1012,7 @@ mod tests {
         let _g = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         clear_env();
         let base = tmp_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
+        std::env::set_var("BASE_DIR", &base);
         // No backend set, should default to JinaCode
 
         let cfg = Config::from_env().unwrap();
@@ -908,7 +1028,7 @@ mod tests {
         let extra = tmp_dir();
         // Canonicalize the extra path for comparison since repo_roots contains canonicalized paths
         let extra_canonical = to_utf8_pathbuf(&std::fs::canonicalize(&extra).unwrap()).unwrap();
-        std::env::set_var("BASE_DIR", base.to_string());
+        std::env::set_var("BASE_DIR", &base);
         std::env::set_var(
             "REPO_ROOTS",
             format!(
@@ -930,7 +1050,7 @@ mod tests {
         let _g = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         clear_env();
         let base = tmp_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
+        std::env::set_var("BASE_DIR", &base);
         std::env::set_var("HYBRID_ALPHA", "2");
         assert!(Config::from_env().is_err());
 
@@ -946,17 +1066,63 @@ mod tests {
         let _g = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         clear_env();
         let base = tmp_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
+        std::env::set_var("BASE_DIR", &base);
         let cfg = Config::from_env().unwrap();
         assert!(cfg.watch_mode);
     }
 
+    #[test]
+    fn watch_backend_defaults_to_auto_and_parses() {
+        let _g = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        clear_env();
+        let base = tmp_dir();
+        std::env::set_var("BASE_DIR", &base);
+        let cfg = Config::from_env().unwrap();
+        assert_eq!(cfg.watch_backend, WatchBackend::Auto);
+
+        std::env::set_var("WATCH_BACKEND", "poll");
+        let cfg = Config::from_env().unwrap();
+        assert_eq!(cfg.watch_backend, WatchBackend::Poll);
+
+        std::env::set_var("WATCH_BACKEND", "inotify-ish");
+        assert!(Config::from_env().is_err());
+    }
+
+    #[test]
+    fn transport_defaults_to_stdio_and_parses_http_settings() {
+        let _g = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        clear_env();
+        let base = tmp_dir();
+        std::env::set_var("BASE_DIR", &base);
+        let cfg = Config::from_env().unwrap();
+        assert_eq!(cfg.mcp_transport, McpTransport::Stdio);
+        assert_eq!(cfg.http_bind_addr, "127.0.0.1:8765");
+        assert!(cfg.http_auth_token.is_none());
+
+        std::env::set_var("MCP_TRANSPORT", "http");
+        std::env::set_var("MCP_HTTP_BIND", "[::1]:9000");
+        std::env::set_var("MCP_HTTP_AUTH_TOKEN", "s3cret");
+        let cfg = Config::from_env().unwrap();
+        assert_eq!(cfg.mcp_transport, McpTransport::Http);
+        assert_eq!(
+            split_bind_addr(&cfg.http_bind_addr).unwrap(),
+            ("::1".to_string(), 9000)
+        );
+        assert_eq!(cfg.http_auth_token.as_deref(), Some("s3cret"));
+
+        std::env::set_var("MCP_HTTP_BIND", "localhost");
+        assert!(Config::from_env().is_err());
+        std::env::set_var("MCP_HTTP_BIND", "0.0.0.0:8765");
+        std::env::set_var("MCP_TRANSPORT", "websocket");
+        assert!(Config::from_env().is_err());
+    }
+
     #[test]
     fn bool_parsing_accepts_multiple_spellings() {
         let _g = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         clear_env();
         let base = tmp_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
+        std::env::set_var("BASE_DIR", &base);
         std::env::set_var("WATCH_MODE", "yes");
         std::env::set_var("INDEX_NODE_MODULES", "1");
         let cfg = Config::from_env().unwrap();
@@ -969,7 +1135,7 @@ mod tests {
         let _g = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         clear_env();
         let base = tmp_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
+        std::env::set_var("BASE_DIR", &base);
 
         let cfg = Config::from_env().unwrap();
 
@@ -991,6 +1157,10 @@ mod tests {
         assert!(cfg.parallel_workers >= 1);
         assert!(cfg.embedding_cache_enabled);
 
+        // History defaults
+        assert!((cfg.rank_history_weight - 0.05).abs() < f32::EPSILON);
+        assert_eq!(cfg.history_max_commits, 1000);
+
         // PageRank defaults
         assert!((cfg.pagerank_damping - 0.85).abs() < f32::EPSILON);
         assert_eq!(cfg.pagerank_iterations, 20);
@@ -1005,7 +1175,7 @@ mod tests {
         let _g = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         clear_env();
         let base = tmp_dir();
-        std::env::set_var("BASE_DIR", base.to_string());
+        std::env::set_var("BASE_DIR", &base);
         std::env::set_var("LEARNING_ENABLED", "true");
         std::env::set_var("MAX_CONTEXT_TOKENS", "16384");
         std::env::set_var("PAGERANK_DAMPING", "0.9");
@@ -0,0 +1,336 @@
+//! Changed lines between two revisions.
+//!
+//! `diff_revisions` takes a range the way `git diff` does: `A..B` compares two
+//! commits, `A...B` compares `B` with the commit it forked from `A` at, a single
+//! revision compares it with the working tree, and no revision compares `HEAD` with
+//! the working tree, staged, unstaged and untracked changes alike. Each changed file
+//! comes with the lines of its new side the changes touch.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions, Patch, Repository, RevparseMode, Tree};
+
+use super::open_repository;
+use crate::config::Config;
+
+/// Lines of the new side of a file a change touches, 1-based. A change that only
+/// removes lines touches none of the new side: `count` is 0 and `start` is the line
+/// the removed ones stood before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: u32,
+    pub count: u32,
+}
+
+/// A file a diff changes, with paths relative to the indexed base directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedFile {
+    /// The new path, or the old one for a deleted file
+    pub path: String,
+    /// The path before a rename
+    pub old_path: Option<String>,
+    /// `added`, `deleted`, `modified` or `renamed`
+    pub status: &'static str,
+    pub hunks: Vec<LineRange>,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// The files changed by `range` in the repository holding the base directory,
+/// leaving out those outside it.
+pub fn diff_revisions(config: &Config, range: Option<&str>) -> Result<Vec<ChangedFile>> {
+    let repo = open_repository(config)?;
+
+    let mut options = diff_options();
+    let mut diff = match range.map(str::trim).filter(|r| !r.is_empty()) {
+        None => {
+            let head = repo.repo.head().and_then(|head| head.peel_to_tree()).ok();
+            repo.repo
+                .diff_tree_to_workdir_with_index(head.as_ref(), Some(&mut options))?
+        }
+        Some(range) => {
+            let (old, new) = range_trees(&repo.repo, range)?;
+            match new {
+                Some(new) => {
+                    repo.repo
+                        .diff_tree_to_tree(Some(&old), Some(&new), Some(&mut options))?
+                }
+                None => repo
+                    .repo
+                    .diff_tree_to_workdir_with_index(Some(&old), Some(&mut options))?,
+            }
+        }
+    };
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    changed_files(&diff, |path| repo.indexed_path(config, path))
+}
+
+/// The revision the index must be built from to answer for the new side of `range`:
+/// `None` when that side is the working tree the index describes, or the commit
+/// checked out with `files` left as it has them; otherwise the revision naming it,
+/// whose files the lines of the diff are numbered in.
+pub fn new_side_revision(
+    config: &Config,
+    range: Option<&str>,
+    files: &[ChangedFile],
+) -> Result<Option<String>> {
+    let Some(range) = range.map(str::trim).filter(|r| !r.is_empty()) else {
+        return Ok(None);
+    };
+    let repo = open_repository(config)?;
+    let spec = repo
+        .repo
+        .revparse(range)
+        .with_context(|| format!("Invalid revision range: range={range}"))?;
+    if spec.mode().contains(RevparseMode::SINGLE) {
+        return Ok(None);
+    }
+    let new = spec
+        .to()
+        .ok_or_else(|| anyhow!("Revision range names no commit: {range}"))?
+        .peel_to_commit()
+        .with_context(|| format!("Revision is not a commit: range={range}"))?
+        .id();
+    let head = repo
+        .repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .ok()
+        .map(|commit| commit.id());
+    if head == Some(new) {
+        let uncommitted = diff_revisions(config, None)?;
+        if !uncommitted
+            .iter()
+            .any(|u| files.iter().any(|f| f.path == u.path))
+        {
+            return Ok(None);
+        }
+    }
+    Ok(Some(range_new_side(range).to_string()))
+}
+
+/// The revision on the new side of a range of two: what follows `...` or `..`,
+/// `HEAD` when nothing does.
+fn range_new_side(range: &str) -> &str {
+    let new = range
+        .split_once("...")
+        .or_else(|| range.split_once(".."))
+        .map_or(range, |(_, new)| new)
+        .trim();
+    if new.is_empty() {
+        "HEAD"
+    } else {
+        new
+    }
+}
+
+/// Changed lines only, untracked files included with their content.
+fn diff_options() -> DiffOptions {
+    let mut options = DiffOptions::new();
+    options
+        .context_lines(0)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    options
+}
+
+/// The trees a range compares; the new one is the working tree when it is `None`.
+fn range_trees<'r>(repo: &'r Repository, range: &str) -> Result<(Tree<'r>, Option<Tree<'r>>)> {
+    let spec = repo
+        .revparse(range)
+        .with_context(|| format!("Invalid revision range: range={range}"))?;
+    let tree = |object: Option<&git2::Object<'r>>| -> Result<Tree<'r>> {
+        let object = object.ok_or_else(|| anyhow!("Revision range names no commit: {range}"))?;
+        object
+            .peel_to_tree()
+            .with_context(|| format!("Revision is not a commit: range={range}"))
+    };
+    if spec.mode().contains(RevparseMode::SINGLE) {
+        return Ok((tree(spec.from())?, None));
+    }
+    let new = tree(spec.to())?;
+    let old = if spec.mode().contains(RevparseMode::MERGE_BASE) {
+        let (Some(from), Some(to)) = (spec.from(), spec.to()) else {
+            return Err(anyhow!("Revision range names no commit: {range}"));
+        };
+        let base = repo
+            .merge_base(from.peel_to_commit()?.id(), to.peel_to_commit()?.id())
+            .with_context(|| format!("Revisions share no history: range={range}"))?;
+        repo.find_commit(base)?.tree()?
+    } else {
+        tree(spec.from())?
+    };
+    Ok((old, Some(new)))
+}
+
+fn changed_files(
+    diff: &Diff,
+    relative: impl Fn(&Path) -> Option<String>,
+) -> Result<Vec<ChangedFile>> {
+    let mut files = Vec::new();
+    for index in 0..diff.deltas().len() {
+        let Some(delta) = diff.get_delta(index) else {
+            continue;
+        };
+        let status = match delta.status() {
+            Delta::Added | Delta::Untracked | Delta::Copied => "added",
+            Delta::Deleted => "deleted",
+            Delta::Modified | Delta::Typechange => "modified",
+            Delta::Renamed => "renamed",
+            _ => continue,
+        };
+        let new_path = delta.new_file().path().and_then(&relative);
+        let old_path = delta.old_file().path().and_then(&relative);
+        let Some(path) = (if status == "deleted" {
+            old_path.clone()
+        } else {
+            new_path
+        }) else {
+            continue;
+        };
+
+        let mut file = ChangedFile {
+            old_path: old_path.filter(|old| status == "renamed" && *old != path),
+            path,
+            status,
+            hunks: Vec::new(),
+            additions: 0,
+            deletions: 0,
+        };
+        // Binary files have no patch
+        if let Some(patch) = Patch::from_diff(diff, index)? {
+            let (_, additions, deletions) = patch.line_stats()?;
+            file.additions = additions;
+            file.deletions = deletions;
+            for hunk in 0..patch.num_hunks() {
+                let (hunk, _) = patch.hunk(hunk)?;
+                file.hunks.push(if hunk.new_lines() == 0 {
+                    // `git diff` numbers a removal by the line before it
+                    LineRange {
+                        start: hunk.new_start() + 1,
+                        count: 0,
+                    }
+                } else {
+                    LineRange {
+                        start: hunk.new_start(),
+                        count: hunk.new_lines(),
+                    }
+                });
+            }
+        }
+        files.push(file);
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::fs;
+
+    fn commit(repo: &Repository, message: &str) -> git2::Oid {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test User", "test@example.com").unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    fn diff(repo: &Repository, old: git2::Oid, new: Option<git2::Oid>) -> Vec<ChangedFile> {
+        let old = repo.find_commit(old).unwrap().tree().unwrap();
+        let mut options = diff_options();
+        let mut diff = match new {
+            Some(new) => {
+                let new = repo.find_commit(new).unwrap().tree().unwrap();
+                repo.diff_tree_to_tree(Some(&old), Some(&new), Some(&mut options))
+            }
+            None => repo.diff_tree_to_workdir_with_index(Some(&old), Some(&mut options)),
+        }
+        .unwrap();
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+            .unwrap();
+        let mut files =
+            changed_files(&diff, |path| Some(path.to_string_lossy().into_owned())).unwrap();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+
+    #[test]
+    fn reports_the_new_side_lines_each_change_touches() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let lines: Vec<String> = (1..=10).map(|i| format!("line {i}")).collect();
+        fs::write(dir.path().join("a.txt"), lines.join("\n") + "\n").unwrap();
+        fs::write(dir.path().join("old.txt"), "moved\nunchanged\ncontent\n").unwrap();
+        fs::write(dir.path().join("gone.txt"), "bye\n").unwrap();
+        let first = commit(&repo, "first");
+
+        let mut changed = lines.clone();
+        changed[1] = "line two".to_string();
+        changed.remove(6);
+        fs::write(dir.path().join("a.txt"), changed.join("\n") + "\n").unwrap();
+        fs::rename(dir.path().join("old.txt"), dir.path().join("new.txt")).unwrap();
+        fs::remove_file(dir.path().join("gone.txt")).unwrap();
+        let second = commit(&repo, "second");
+
+        let files = diff(&repo, first, Some(second));
+        let summary: Vec<_> = files
+            .iter()
+            .map(|f| (f.path.as_str(), f.old_path.as_deref(), f.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a.txt", None, "modified"),
+                ("gone.txt", None, "deleted"),
+                ("new.txt", Some("old.txt"), "renamed"),
+            ]
+        );
+        assert_eq!(
+            files[0].hunks,
+            vec![
+                LineRange { start: 2, count: 1 },
+                LineRange { start: 7, count: 0 },
+            ]
+        );
+        assert_eq!((files[0].additions, files[0].deletions), (1, 2));
+
+        // Uncommitted files count as added
+        fs::write(dir.path().join("draft.txt"), "one\ntwo\n").unwrap();
+        let files = diff(&repo, second, None);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].status, "added");
+        assert_eq!(files[0].hunks, vec![LineRange { start: 1, count: 2 }]);
+    }
+
+    #[test]
+    fn names_the_new_side_of_a_range() {
+        assert_eq!(range_new_side("main..feature"), "feature");
+        assert_eq!(range_new_side("main...feature"), "feature");
+        assert_eq!(range_new_side("origin/main.."), "HEAD");
+        assert_eq!(range_new_side("v1.2...HEAD~1"), "HEAD~1");
+    }
+}
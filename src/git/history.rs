@@ -0,0 +1,504 @@
+//! Commit history of the files in the working tree.
+//!
+//! `mine_history` walks back from `HEAD` along first parents, as `git log
+//! --first-parent` does, and records for each commit the files it changed under
+//! their current paths, following renames. It also records where the lines each
+//! change touched are at `HEAD`: every commit's hunks are mapped through the ones of
+//! the commits after it, the way blame carries lines back, except that lines a later
+//! commit rewrote go to the lines it put in their place. A change can so be
+//! attributed to the symbols holding its code today, even once it was edited again.
+//! `LineMap` does the mapping, and also carries lines from `HEAD` to the uncommitted
+//! working tree.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::Result;
+use git2::{Delta, DiffFindOptions, DiffOptions, Patch, Repository};
+
+/// A hunk of a diff, lines 1-based as git numbers them: a side without lines starts
+/// at the line before the change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+}
+
+impl Hunk {
+    /// Lines of the new side the hunk touches, half-open. A removal touches the line
+    /// it stood before.
+    fn new_range(&self) -> (u32, u32) {
+        if self.new_lines == 0 {
+            (self.new_start + 1, self.new_start + 2)
+        } else {
+            (self.new_start, self.new_start + self.new_lines)
+        }
+    }
+}
+
+/// Lines of one version of a file mapped to the lines of a later one. Lines a later
+/// change replaced map to the lines replacing them, and removed lines to the line
+/// they stood before, so a change is placed where its code lives on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMap {
+    segments: Vec<Segment>,
+}
+
+/// Half-open lines `start..end` mapping to the lines from `target` on one to one, or
+/// each to all of `target..end` when `target_end` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Segment {
+    start: u32,
+    end: u32,
+    target: u32,
+    target_end: Option<u32>,
+}
+
+impl Segment {
+    /// Where lines `a..b`, which must lie within the segment, map to.
+    fn target_range(&self, a: u32, b: u32) -> (u32, u32) {
+        match self.target_end {
+            Some(end) => (self.target, end),
+            None => (
+                self.target + (a - self.start),
+                self.target + (b - self.start),
+            ),
+        }
+    }
+}
+
+impl LineMap {
+    pub fn identity() -> Self {
+        Self {
+            segments: vec![Segment {
+                start: 1,
+                end: u32::MAX,
+                target: 1,
+                target_end: None,
+            }],
+        }
+    }
+
+    /// The later lines `start..end` map to, as sorted half-open ranges.
+    pub fn map_range(&self, start: u32, end: u32) -> Vec<(u32, u32)> {
+        merge_ranges(
+            self.segments
+                .iter()
+                .filter_map(|segment| {
+                    let (a, b) = (start.max(segment.start), end.min(segment.end));
+                    (a < b).then(|| segment.target_range(a, b))
+                })
+                .collect(),
+        )
+    }
+
+    /// Map one version earlier: given the hunks of the diff from the earlier version
+    /// to the one the map starts at, map the earlier version's lines instead.
+    pub fn step_back(&mut self, hunks: &[Hunk]) {
+        // Lines of the newer version outside any hunk, and how far back they move
+        let mut unchanged: Vec<(u32, u32, i64)> = Vec::new();
+        // Lines of the older version a hunk replaces, and the newer lines it touches
+        let mut replaced: Vec<(u32, u32, (u32, u32))> = Vec::new();
+        let mut cursor = 1;
+        let mut shift: i64 = 0;
+        for hunk in hunks {
+            let start = if hunk.new_lines == 0 {
+                hunk.new_start + 1
+            } else {
+                hunk.new_start
+            };
+            if cursor < start {
+                unchanged.push((cursor, start, shift));
+            }
+            cursor = cursor.max(start + hunk.new_lines);
+            shift += hunk.old_lines as i64 - hunk.new_lines as i64;
+            if hunk.old_lines > 0 {
+                replaced.push((
+                    hunk.old_start,
+                    hunk.old_start + hunk.old_lines,
+                    hunk.new_range(),
+                ));
+            }
+        }
+        unchanged.push((cursor, u32::MAX, shift));
+
+        let mut segments = Vec::new();
+        for segment in &self.segments {
+            for &(us, ue, shift) in &unchanged {
+                let (a, b) = (segment.start.max(us), segment.end.min(ue));
+                if a >= b {
+                    continue;
+                }
+                let old = |line: u32| (line as i64 + shift).clamp(1, u32::MAX as i64) as u32;
+                let (target, target_end) = segment.target_range(a, b);
+                segments.push(Segment {
+                    start: old(a),
+                    end: old(b),
+                    target,
+                    target_end: segment.target_end.map(|_| target_end),
+                });
+            }
+            for &(os, oe, (ns, ne)) in &replaced {
+                let (a, b) = (segment.start.max(ns), segment.end.min(ne));
+                if a >= b {
+                    continue;
+                }
+                let (target, target_end) = segment.target_range(a, b);
+                segments.push(Segment {
+                    start: os,
+                    end: oe,
+                    target,
+                    target_end: Some(target_end),
+                });
+            }
+        }
+        segments.sort_unstable();
+        self.segments = segments;
+    }
+}
+
+/// A commit and the files it changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinedCommit {
+    pub id: String,
+    pub author: String,
+    pub email: String,
+    /// Seconds since the epoch
+    pub time: i64,
+    pub summary: String,
+    pub files: Vec<MinedFile>,
+}
+
+/// A file a commit changed, under its path at `HEAD`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinedFile {
+    pub path: String,
+    pub added: usize,
+    pub deleted: usize,
+    /// Where the lines the change touched are at `HEAD`, half-open and sorted
+    pub lines: Vec<(u32, u32)>,
+}
+
+/// The history of the files at `HEAD`, newest commit first, at most `max_commits`
+/// commits. Paths are those `path_of` gives for paths relative to the working
+/// directory; files it returns `None` for are left out.
+pub fn mine_history(
+    repo: &Repository,
+    max_commits: usize,
+    path_of: impl Fn(&Path) -> Option<String>,
+) -> Result<Vec<MinedCommit>> {
+    let mut walk = repo.revwalk()?;
+    if walk.push_head().is_err() {
+        // No commits yet
+        return Ok(Vec::new());
+    }
+    walk.simplify_first_parent()?;
+
+    let mut tracker = PathTracker::default();
+    let mut maps: HashMap<String, LineMap> = HashMap::new();
+    let mut commits = Vec::new();
+    for oid in walk.take(max_commits) {
+        let commit = repo.find_commit(oid?)?;
+        let tree = commit.tree()?;
+        let parent = commit.parent(0).ok().map(|p| p.tree()).transpose()?;
+        let mut options = DiffOptions::new();
+        options.context_lines(0);
+        let mut diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&tree), Some(&mut options))?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+        let mut files = Vec::new();
+        for index in 0..diff.deltas().len() {
+            let Some(delta) = diff.get_delta(index) else {
+                continue;
+            };
+            let new_path = delta.new_file().path().map(path_key);
+            let old_path = delta.old_file().path().map(path_key);
+            let current = match delta.status() {
+                Delta::Deleted => {
+                    if let Some(old) = &old_path {
+                        tracker.forget(old);
+                    }
+                    continue;
+                }
+                Delta::Added | Delta::Copied | Delta::Modified | Delta::Renamed => {
+                    let Some(new) = &new_path else {
+                        continue;
+                    };
+                    let current = tracker.current(new);
+                    match delta.status() {
+                        Delta::Added | Delta::Copied => tracker.forget(new),
+                        Delta::Renamed => tracker.rename(old_path.as_deref(), new, &current),
+                        _ => {}
+                    }
+                    current
+                }
+                _ => continue,
+            };
+            let Some(current) = current else {
+                continue;
+            };
+
+            let mut file = MinedFile {
+                path: current.clone(),
+                added: 0,
+                deleted: 0,
+                lines: Vec::new(),
+            };
+            if let Some(patch) = Patch::from_diff(&diff, index)? {
+                let (_, added, deleted) = patch.line_stats()?;
+                file.added = added;
+                file.deleted = deleted;
+                let hunks = patch_hunks(&patch)?;
+                let map = maps.entry(current).or_insert_with(LineMap::identity);
+                for hunk in &hunks {
+                    let (start, end) = hunk.new_range();
+                    file.lines.extend(map.map_range(start, end));
+                }
+                map.step_back(&hunks);
+                file.lines = merge_ranges(file.lines);
+            }
+            files.push(file);
+        }
+
+        let files: Vec<MinedFile> = files
+            .into_iter()
+            .filter_map(|mut file| {
+                file.path = path_of(Path::new(&file.path))?;
+                Some(file)
+            })
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        let author = commit.author();
+        commits.push(MinedCommit {
+            id: commit.id().to_string(),
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            time: commit.time().seconds(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            files,
+        });
+    }
+    Ok(commits)
+}
+
+/// Maps from the lines of each file at `HEAD` to its lines in the working tree, for
+/// the files among `paths` (relative to the working directory) with uncommitted
+/// changes.
+pub fn working_tree_maps(repo: &Repository, paths: &[String]) -> Result<HashMap<String, LineMap>> {
+    let mut maps = HashMap::new();
+    let Ok(head) = repo.head().and_then(|head| head.peel_to_tree()) else {
+        return Ok(maps);
+    };
+    let mut options = DiffOptions::new();
+    options.context_lines(0).disable_pathspec_match(true);
+    for path in paths {
+        options.pathspec(path);
+    }
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&head), Some(&mut options))?;
+    for index in 0..diff.deltas().len() {
+        let Some(delta) = diff.get_delta(index) else {
+            continue;
+        };
+        if delta.status() != Delta::Modified {
+            continue;
+        }
+        let (Some(path), Some(patch)) = (
+            delta.new_file().path().map(path_key),
+            Patch::from_diff(&diff, index)?,
+        ) else {
+            continue;
+        };
+        let mut map = LineMap::identity();
+        map.step_back(&patch_hunks(&patch)?);
+        maps.insert(path, map);
+    }
+    Ok(maps)
+}
+
+/// Which current file a path names, going back through the history: renames point
+/// it at the file's later path, and once a path was deleted, or added, earlier
+/// changes to it are to a file that is not there today.
+#[derive(Default)]
+struct PathTracker {
+    renamed: HashMap<String, String>,
+    gone: HashSet<String>,
+}
+
+impl PathTracker {
+    fn current(&self, path: &str) -> Option<String> {
+        match self.renamed.get(path) {
+            Some(current) => Some(current.clone()),
+            None if self.gone.contains(path) => None,
+            None => Some(path.to_string()),
+        }
+    }
+
+    fn forget(&mut self, path: &str) {
+        self.renamed.remove(path);
+        self.gone.insert(path.to_string());
+    }
+
+    fn rename(&mut self, old: Option<&str>, new: &str, current: &Option<String>) {
+        self.forget(new);
+        if let (Some(old), Some(current)) = (old, current) {
+            self.gone.remove(old);
+            self.renamed.insert(old.to_string(), current.clone());
+        }
+    }
+}
+
+fn patch_hunks(patch: &Patch) -> Result<Vec<Hunk>> {
+    (0..patch.num_hunks())
+        .map(|i| {
+            let (hunk, _) = patch.hunk(i)?;
+            Ok(Hunk {
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+            })
+        })
+        .collect()
+}
+
+/// Sorted ranges, overlapping and adjacent ones joined.
+pub fn merge_ranges(mut ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u32, u32)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::fs;
+
+    fn hunk(old_start: u32, old_lines: u32, new_start: u32, new_lines: u32) -> Hunk {
+        Hunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+        }
+    }
+
+    #[test]
+    fn line_maps_carry_unchanged_lines_back() {
+        let mut map = LineMap::identity();
+        // Lines 3-4 added after line 2, and old line 6 removed (before new line 8)
+        map.step_back(&[hunk(2, 0, 3, 2), hunk(6, 1, 7, 0)]);
+        assert_eq!(map.map_range(1, 3), vec![(1, 3)]);
+        assert_eq!(map.map_range(3, 5), vec![(5, 7)]);
+        assert_eq!(map.map_range(5, 6), vec![(7, 8)]);
+        // The removed line goes to the line it stood before
+        assert_eq!(map.map_range(6, 7), vec![(8, 9)]);
+        assert_eq!(map.map_range(7, 8), vec![(8, 9)]);
+
+        // Lines rewritten later go to the lines replacing them
+        map.step_back(&[hunk(4, 2, 4, 1)]);
+        assert_eq!(map.map_range(4, 5), vec![(6, 7)]);
+        assert_eq!(map.map_range(5, 6), vec![(6, 7)]);
+        assert_eq!(map.map_range(6, 7), vec![(7, 8)]);
+        assert_eq!(map.map_range(3, 4), vec![(5, 6)]);
+    }
+
+    fn commit(repo: &Repository, dir: &Path, files: &[(&str, Option<&str>)]) {
+        for (path, content) in files {
+            match content {
+                Some(content) => fs::write(dir.join(path), content).unwrap(),
+                None => fs::remove_file(dir.join(path)).unwrap(),
+            }
+        }
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Ada", "ada@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "change",
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn mines_changes_under_current_paths_and_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit(
+            &repo,
+            dir.path(),
+            &[
+                ("old.txt", Some("a\nb\nc\nd\ne\nf\ng\nh\n")),
+                ("gone.txt", Some("x\n")),
+            ],
+        );
+        commit(
+            &repo,
+            dir.path(),
+            &[
+                ("old.txt", Some("a\nB\nc\nd\ne\nf\ng\nh\n")),
+                ("gone.txt", None),
+            ],
+        );
+        fs::rename(dir.path().join("old.txt"), dir.path().join("new.txt")).unwrap();
+        commit(
+            &repo,
+            dir.path(),
+            &[
+                ("new.txt", Some("new\na\nB\nc\nd\ne\nf\ng\nh\n")),
+                ("gone.txt", Some("back\n")),
+            ],
+        );
+
+        let commits = mine_history(&repo, 10, |p| Some(path_key(p))).unwrap();
+        // Per commit, the files it changed and their lines
+        type Files<'a> = Vec<(&'a str, Vec<(u32, u32)>)>;
+        let summary: Vec<Files> = commits
+            .iter()
+            .map(|c| {
+                c.files
+                    .iter()
+                    .map(|f| (f.path.as_str(), f.lines.clone()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                vec![("gone.txt", vec![(1, 2)]), ("new.txt", vec![(1, 2)])],
+                // `B` is line 3 now; the removed `gone.txt` is not today's
+                vec![("new.txt", vec![(3, 4)])],
+                // `B` replaced `b`, so it counts too
+                vec![("new.txt", vec![(2, 10)])],
+            ]
+        );
+        assert_eq!(commits[0].author, "Ada");
+        assert_eq!(commits[1].files[0].added, 1);
+        assert_eq!(commits[1].files[0].deleted, 1);
+    }
+}
@@ -0,0 +1,83 @@
+//! Reading the git repository the indexed code lives in.
+//!
+//! The index describes the files as they are on disk; this module tells which of
+//! them a commit range or the uncommitted work changed, and where, what their
+//! history has been, and what they held at a past revision.
+
+pub mod diff;
+pub mod history;
+pub mod tree;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use git2::Repository;
+
+use crate::config::Config;
+
+pub use diff::{diff_revisions, new_side_revision, ChangedFile, LineRange};
+
+/// The repository holding the base directory, with its working directory.
+pub struct GitRepository {
+    pub repo: Repository,
+    pub workdir: PathBuf,
+}
+
+/// Open the repository holding the base directory.
+pub fn open_repository(config: &Config) -> Result<GitRepository> {
+    let repo = Repository::discover(config.base_dir.as_std_path()).with_context(|| {
+        format!(
+            "No git repository holds the base directory: base_dir={}",
+            config.base_dir
+        )
+    })?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Repository is bare: path={}", repo.path().display()))?
+        .to_path_buf();
+    Ok(GitRepository { repo, workdir })
+}
+
+impl GitRepository {
+    /// The path relative to the base directory of a path relative to the working
+    /// directory, or `None` when it lies outside the base directory or holds the index
+    /// itself, which may be stored under it untracked.
+    pub fn indexed_path(&self, config: &Config, path: &Path) -> Option<String> {
+        let absolute = self.workdir.join(path);
+        let index_paths = [
+            &config.db_path,
+            &config.vector_db_path,
+            &config.tantivy_index_path,
+            &config.snapshot_dir(),
+        ];
+        if index_paths
+            .iter()
+            .any(|index| absolute.to_string_lossy().starts_with(index.as_str()))
+        {
+            return None;
+        }
+        config
+            .path_relative_to_base_path(&absolute)
+            .ok()
+            .filter(|relative| !relative.starts_with(".."))
+    }
+
+    /// The path relative to the working directory of an indexed path.
+    pub fn repo_path(&self, config: &Config, indexed: &str) -> Option<String> {
+        let absolute = config.base_dir.as_std_path().join(indexed);
+        let relative = match absolute.strip_prefix(&self.workdir) {
+            Ok(relative) => relative.to_path_buf(),
+            // The working directory may be reached through a symlink
+            Err(_) => {
+                let workdir = dunce::canonicalize(&self.workdir).ok()?;
+                let base_dir = dunce::canonicalize(config.base_dir.as_std_path()).ok()?;
+                base_dir
+                    .join(indexed)
+                    .strip_prefix(workdir)
+                    .ok()?
+                    .to_path_buf()
+            }
+        };
+        Some(relative.to_string_lossy().replace('\\', "/"))
+    }
+}
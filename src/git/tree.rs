@@ -0,0 +1,193 @@
+//! Files of a revision, read from the object database.
+//!
+//! A past revision is indexed without checking it out: its tree lists each file with
+//! the id of its blob, the hash of its content. Blobs are copied out into a store
+//! keyed by that id, so a file left unchanged from one revision to the next is the
+//! same blob and is stored once however many revisions hold it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Context, Result};
+use git2::{ObjectType, Oid, TreeWalkMode, TreeWalkResult};
+
+use super::open_repository;
+use crate::config::Config;
+
+/// Mode git records for symbolic links, which are not indexed.
+const SYMLINK_MODE: i32 = 0o120000;
+
+/// Id of the commit `revision` (a branch, tag, commit or any expression
+/// `git rev-parse` takes) names.
+pub fn resolve_revision(config: &Config, revision: &str) -> Result<String> {
+    let repo = open_repository(config)?;
+    let commit = repo
+        .repo
+        .revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("Unknown revision: revision={revision}"))?;
+    Ok(commit.id().to_string())
+}
+
+/// The blob ids, by path relative to the base directory, of the files `commit` holds
+/// under the base directory that `keep` accepts, leaving out the directories whose
+/// name `skip_dir` rejects.
+pub fn revision_files(
+    config: &Config,
+    commit: &str,
+    skip_dir: impl Fn(&str) -> bool,
+    keep: impl Fn(&str) -> bool,
+) -> Result<BTreeMap<String, String>> {
+    let repo = open_repository(config)?;
+    let oid = Oid::from_str(commit).map_err(|_| anyhow!("Invalid commit id: {commit}"))?;
+    let tree = repo
+        .repo
+        .find_commit(oid)
+        .and_then(|commit| commit.tree())
+        .with_context(|| format!("Commit missing from the repository: id={commit}"))?;
+
+    let mut files = BTreeMap::new();
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        let Some(name) = entry.name() else {
+            return TreeWalkResult::Skip;
+        };
+        match entry.kind() {
+            Some(ObjectType::Tree) if skip_dir(name) => TreeWalkResult::Skip,
+            Some(ObjectType::Blob) if entry.filemode() != SYMLINK_MODE => {
+                let path = Path::new(dir).join(name);
+                if let Some(indexed) = repo.indexed_path(config, &path).filter(|p| keep(p)) {
+                    files.insert(indexed, entry.id().to_string());
+                }
+                TreeWalkResult::Ok
+            }
+            _ => TreeWalkResult::Ok,
+        }
+    })?;
+    Ok(files)
+}
+
+/// Where the blob `id` is stored under `store`.
+pub fn blob_path(store: &Path, id: &str) -> PathBuf {
+    let (dir, rest) = id.split_at(2.min(id.len()));
+    store.join(dir).join(rest)
+}
+
+/// Copy the blobs `ids` into `store`, skipping those it holds already. Returns how
+/// many were copied.
+pub fn store_blobs<'a>(
+    config: &Config,
+    ids: impl IntoIterator<Item = &'a str>,
+    store: &Path,
+) -> Result<usize> {
+    let repo = open_repository(config)?;
+    let mut copied = 0;
+    for id in ids {
+        let path = blob_path(store, id);
+        if path.exists() {
+            continue;
+        }
+        let oid = Oid::from_str(id).map_err(|_| anyhow!("Invalid blob id: {id}"))?;
+        let blob = repo
+            .repo
+            .find_blob(oid)
+            .with_context(|| format!("Blob missing from the repository: id={id}"))?;
+        let dir = path.parent().unwrap_or(store);
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create blob directory: {}", dir.display()))?;
+        // Written aside first, so a blob in the store is always complete
+        let partial = path.with_extension("partial");
+        fs::write(&partial, blob.content())
+            .with_context(|| format!("Failed to write blob: {}", partial.display()))?;
+        fs::rename(&partial, &path)
+            .with_context(|| format!("Failed to store blob: {}", path.display()))?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+/// Remove the blobs of `store` that `keep` rejects, once they have been stored for
+/// `grace`: a younger one may be on its way into a tree that doesn't record it yet.
+/// Returns how many were removed.
+pub fn remove_blobs(store: &Path, keep: impl Fn(&str) -> bool, grace: Duration) -> Result<usize> {
+    let Ok(dirs) = fs::read_dir(store) else {
+        return Ok(0);
+    };
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for dir in dirs {
+        let dir = dir?.path();
+        let Some(prefix) = dir.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !dir.is_dir() {
+            continue;
+        }
+        for blob in fs::read_dir(&dir)
+            .with_context(|| format!("Failed to list blobs: {}", dir.display()))?
+        {
+            let blob = blob?;
+            let Some(rest) = blob.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            // Blobs being written
+            if rest.ends_with(".partial") || keep(&format!("{prefix}{rest}")) {
+                continue;
+            }
+            let stored = blob.metadata()?.modified()?;
+            if now.duration_since(stored).unwrap_or_default() < grace {
+                continue;
+            }
+            fs::remove_file(blob.path())
+                .with_context(|| format!("Failed to remove blob: {}", blob.path().display()))?;
+            removed += 1;
+        }
+        // Left empty
+        let _ = fs::remove_dir(&dir);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blobs_are_stored_under_their_id_prefix() {
+        let store = Path::new("/snapshots/blobs");
+        assert_eq!(
+            blob_path(store, "3b18e512dba79e4c8300dd08aeb37f8e728b8dad"),
+            store
+                .join("3b")
+                .join("18e512dba79e4c8300dd08aeb37f8e728b8dad")
+        );
+    }
+
+    #[test]
+    fn removes_only_the_blobs_not_kept() {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let store = std::env::temp_dir().join(format!("code-intel-blobs-test-{nanos}"));
+        for id in ["aa01", "aa02", "bb01"] {
+            let path = blob_path(&store, id);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, id).unwrap();
+        }
+        fs::write(store.join("aa").join("03.partial"), "").unwrap();
+
+        let keep = |id: &str| id == "aa01";
+        assert_eq!(
+            remove_blobs(&store, keep, Duration::from_secs(3600)).unwrap(),
+            0
+        );
+        assert_eq!(remove_blobs(&store, keep, Duration::ZERO).unwrap(), 2);
+        assert!(blob_path(&store, "aa01").exists());
+        assert!(!blob_path(&store, "aa02").exists());
+        assert!(store.join("aa").join("03.partial").exists());
+        assert!(!store.join("bb").exists());
+        let _ = fs::remove_dir_all(&store);
+    }
+}
@@ -0,0 +1,933 @@
+//! Public API surface of each package, and how it changed.
+//!
+//! The surface of a package is what its code exports: every exported symbol, the
+//! members of exported types that are not private, each with its declaration
+//! signature and the types it extends or implements. It is kept in a canonical form,
+//! sorted and free of positions, so a surface written to a file changes only when the
+//! API does and can be committed as a baseline.
+//!
+//! Two surfaces are compared symbol by symbol, and each change is classified as
+//! breaking for code using the package or not. Parameters are told apart by position:
+//! one added that callers must pass, one removed, one whose type no longer accepts
+//! all it did, or one no longer optional is breaking; one widened to a union taking
+//! more, made optional or added as optional is not. A return type is breaking to
+//! change unless it was narrowed to some of the types it had. Any other change to a
+//! declaration counts as breaking, since it cannot be told safe.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::compare::declaration_signature;
+use crate::indexer::pipeline::edges::RESOURCE_LANGUAGES;
+use crate::storage::sqlite::{PackageRow, SqliteStore, SymbolRow};
+
+/// Ending of the name of a surface file.
+const SURFACE_FILE_SUFFIX: &str = ".api.json";
+
+/// Kinds of symbols declaring a type, whose signature has no parameters of its own.
+const TYPE_KINDS: &[&str] = &["class", "interface", "struct", "trait", "enum"];
+
+/// Characters of a declaration's text loaded, enough for its signature.
+const SIGNATURE_SPAN: usize = 2048;
+
+/// Edges naming the types a symbol extends or implements.
+const RELATION_EDGES: &[&str] = &["extends", "implements"];
+
+/// The API surfaces of the packages of an index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiSurface {
+    pub packages: Vec<PackageSurface>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageSurface {
+    pub name: String,
+    /// Directory of the package relative to the base directory, `""` for the root
+    pub path: String,
+    pub package_type: String,
+    pub symbols: Vec<ApiSymbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiSymbol {
+    /// Qualified name
+    pub name: String,
+    pub kind: String,
+    /// File declaring it, relative to the package directory
+    pub file: String,
+    pub signature: String,
+    /// `extends Base`, `implements Handler`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<String>,
+}
+
+/// A difference between two surfaces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiChange {
+    pub package: String,
+    /// Qualified name of the symbol changed, `""` for the package as a whole
+    pub symbol: String,
+    pub kind: String,
+    /// `package_added`, `package_removed`, `added`, `removed`, `kind_changed`,
+    /// `parameter_added`, `parameter_removed`, `parameter_narrowed`,
+    /// `parameter_widened`, `parameter_made_required`, `parameter_made_optional`,
+    /// `parameter_renamed`, `return_type_changed`, `return_type_narrowed`,
+    /// `relation_added`, `relation_removed` or `declaration_changed`
+    pub change: &'static str,
+    pub breaking: bool,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_signature: Option<String>,
+}
+
+/// The API surface of every package of an index. Code outside any package is
+/// the surface of a package named `root_name` at the root.
+pub fn api_surface(sqlite: &SqliteStore, root_name: &str) -> Result<ApiSurface> {
+    // Rows without a root_path cannot hold files until packages are detected again
+    let mut packages: Vec<(PackageRow, String)> = sqlite
+        .list_all_packages()?
+        .into_iter()
+        .filter_map(|p| p.root_path.clone().map(|root| (p, root)))
+        .collect();
+    // Deepest first, so a file goes to the innermost package holding it
+    packages.sort_by_key(|(_, root)| std::cmp::Reverse(root.len()));
+
+    let symbols = sqlite.list_exported_declarations(RESOURCE_LANGUAGES, SIGNATURE_SPAN)?;
+    let mut relations: HashMap<String, Vec<String>> = HashMap::new();
+    for (from, edge_type, to) in sqlite.list_edge_targets_by_types(RELATION_EDGES)? {
+        relations
+            .entry(from)
+            .or_default()
+            .push(format!("{edge_type} {to}"));
+    }
+
+    let mut surfaces: BTreeMap<String, PackageSurface> = BTreeMap::new();
+    for symbol in &symbols {
+        // Members of exported symbols come along unless marked private
+        if !symbol.exported && is_private(symbol) {
+            continue;
+        }
+        let package = packages
+            .iter()
+            .find(|(_, root)| symbol.file_path.starts_with(root.as_str()));
+        let (name, path, package_type) = match package {
+            Some((p, root)) => (p.name.as_str(), root.as_str(), p.package_type.as_str()),
+            None => (root_name, "", ""),
+        };
+        let mut symbol_relations = relations.remove(symbol.id.as_str()).unwrap_or_default();
+        symbol_relations.sort();
+        symbol_relations.dedup();
+        surfaces
+            .entry(path.to_string())
+            .or_insert_with(|| PackageSurface {
+                name: name.to_string(),
+                path: path.to_string(),
+                package_type: package_type.to_string(),
+                symbols: Vec::new(),
+            })
+            .symbols
+            .push(ApiSymbol {
+                name: symbol.qualified_name.clone(),
+                kind: symbol.kind.clone(),
+                file: symbol.file_path[path.len()..].to_string(),
+                signature: declaration_signature(symbol),
+                relations: symbol_relations,
+            });
+    }
+
+    let mut packages: Vec<PackageSurface> = surfaces.into_values().collect();
+    for package in &mut packages {
+        package.symbols.sort_by(|a, b| {
+            (&a.name, &a.kind, &a.file, &a.signature).cmp(&(
+                &b.name,
+                &b.kind,
+                &b.file,
+                &b.signature,
+            ))
+        });
+    }
+    Ok(ApiSurface { packages })
+}
+
+fn is_private(symbol: &SymbolRow) -> bool {
+    let name = symbol.name.as_str();
+    if name.starts_with('#') {
+        return true;
+    }
+    if symbol.language == "python" {
+        return name.starts_with('_') && !(name.starts_with("__") && name.ends_with("__"));
+    }
+    let head = symbol.text.split(name).next().unwrap_or("");
+    head.split_whitespace()
+        .any(|word| word == "private" || word == "private:")
+}
+
+/// How `new` differs from `old`, package by package.
+pub fn diff_surfaces(old: &ApiSurface, new: &ApiSurface) -> Vec<ApiChange> {
+    let new_packages: HashMap<&str, &PackageSurface> =
+        new.packages.iter().map(|p| (p.name.as_str(), p)).collect();
+    let old_names: Vec<&str> = old.packages.iter().map(|p| p.name.as_str()).collect();
+
+    let mut changes = Vec::new();
+    for old_package in &old.packages {
+        match new_packages.get(old_package.name.as_str()) {
+            Some(new_package) => diff_package(old_package, new_package, &mut changes),
+            None => changes.push(ApiChange {
+                package: old_package.name.clone(),
+                symbol: String::new(),
+                kind: "package".to_string(),
+                change: "package_removed",
+                breaking: true,
+                detail: format!("{} exported symbols", old_package.symbols.len()),
+                old_signature: None,
+                new_signature: None,
+            }),
+        }
+    }
+    for new_package in &new.packages {
+        if !old_names.contains(&new_package.name.as_str()) {
+            changes.push(ApiChange {
+                package: new_package.name.clone(),
+                symbol: String::new(),
+                kind: "package".to_string(),
+                change: "package_added",
+                breaking: false,
+                detail: format!("{} exported symbols", new_package.symbols.len()),
+                old_signature: None,
+                new_signature: None,
+            });
+        }
+    }
+    changes
+}
+
+fn diff_package(old: &PackageSurface, new: &PackageSurface, changes: &mut Vec<ApiChange>) {
+    let mut old_by_name: BTreeMap<&str, Vec<&ApiSymbol>> = BTreeMap::new();
+    for symbol in &old.symbols {
+        old_by_name.entry(&symbol.name).or_default().push(symbol);
+    }
+    let mut new_by_name: BTreeMap<&str, Vec<&ApiSymbol>> = BTreeMap::new();
+    for symbol in &new.symbols {
+        new_by_name.entry(&symbol.name).or_default().push(symbol);
+    }
+
+    let change = |symbol: &ApiSymbol, change, breaking, detail: String| ApiChange {
+        package: new.name.clone(),
+        symbol: symbol.name.clone(),
+        kind: symbol.kind.clone(),
+        change,
+        breaking,
+        detail,
+        old_signature: None,
+        new_signature: None,
+    };
+    for (name, mut old_symbols) in old_by_name {
+        let mut new_symbols = new_by_name.remove(name).unwrap_or_default();
+        // Overloads and declarations sharing a name: unchanged ones first, then by kind
+        old_symbols.retain(|o| {
+            match new_symbols
+                .iter()
+                .position(|n| n.kind == o.kind && n.signature == o.signature)
+            {
+                Some(at) => {
+                    let n = new_symbols.remove(at);
+                    diff_relations(o, n, &new.name, changes);
+                    false
+                }
+                None => true,
+            }
+        });
+        let mut pairs = Vec::new();
+        old_symbols.retain(
+            |o| match new_symbols.iter().position(|n| n.kind == o.kind) {
+                Some(at) => {
+                    pairs.push((*o, new_symbols.remove(at)));
+                    false
+                }
+                None => true,
+            },
+        );
+        let mut old_symbols = old_symbols.into_iter();
+        for n in std::mem::take(&mut new_symbols) {
+            match old_symbols.next() {
+                Some(o) => pairs.push((o, n)),
+                None => new_symbols.push(n),
+            }
+        }
+        for o in old_symbols {
+            changes.push(ApiChange {
+                old_signature: Some(o.signature.clone()),
+                ..change(
+                    o,
+                    "removed",
+                    true,
+                    format!("{} removed from the API", o.kind),
+                )
+            });
+        }
+        for n in new_symbols {
+            changes.push(ApiChange {
+                new_signature: Some(n.signature.clone()),
+                ..change(n, "added", false, format!("{} added to the API", n.kind))
+            });
+        }
+        for (o, n) in pairs {
+            let at = changes.len();
+            if o.kind != n.kind {
+                changes.push(change(
+                    n,
+                    "kind_changed",
+                    true,
+                    format!("{} became {}", o.kind, n.kind),
+                ));
+            } else {
+                for (kind, breaking, detail) in classify_signature_change(o, n) {
+                    changes.push(change(n, kind, breaking, detail));
+                }
+            }
+            for c in &mut changes[at..] {
+                c.old_signature = Some(o.signature.clone());
+                c.new_signature = Some(n.signature.clone());
+            }
+            diff_relations(o, n, &new.name, changes);
+        }
+    }
+    for n in new_by_name.into_values().flatten() {
+        changes.push(ApiChange {
+            new_signature: Some(n.signature.clone()),
+            ..change(n, "added", false, format!("{} added to the API", n.kind))
+        });
+    }
+}
+
+fn diff_relations(old: &ApiSymbol, new: &ApiSymbol, package: &str, changes: &mut Vec<ApiChange>) {
+    let relation = |relation: &String, change, breaking| ApiChange {
+        package: package.to_string(),
+        symbol: new.name.clone(),
+        kind: new.kind.clone(),
+        change,
+        breaking,
+        detail: relation.clone(),
+        old_signature: None,
+        new_signature: None,
+    };
+    for removed in old.relations.iter().filter(|r| !new.relations.contains(r)) {
+        changes.push(relation(removed, "relation_removed", true));
+    }
+    for added in new.relations.iter().filter(|r| !old.relations.contains(r)) {
+        changes.push(relation(added, "relation_added", false));
+    }
+}
+
+/// A declaration split into its parameters, its return type and the rest.
+#[derive(Debug, PartialEq, Eq)]
+struct Callable<'a> {
+    /// What comes before the parameters and after the return type
+    rest: (&'a str, &'a str),
+    params: Vec<Param<'a>>,
+    returns: Option<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Param<'a> {
+    /// The name, or for a parameter without a `name: type` annotation its whole
+    /// declaration
+    name: &'a str,
+    ty: Option<&'a str>,
+    optional: bool,
+}
+
+/// The changes between two signatures of a symbol, as `(change, breaking, detail)`.
+fn classify_signature_change(
+    old: &ApiSymbol,
+    new: &ApiSymbol,
+) -> Vec<(&'static str, bool, String)> {
+    if old.signature == new.signature {
+        return Vec::new();
+    }
+    if TYPE_KINDS.contains(&new.kind.as_str()) {
+        // A change to the types it extends or implements is told by its relations,
+        // unless they are not in the index
+        if old.relations != new.relations
+            && without_relations(&old.signature) == without_relations(&new.signature)
+        {
+            return Vec::new();
+        }
+        return vec![(
+            "declaration_changed",
+            true,
+            "declaration changed".to_string(),
+        )];
+    }
+    let short = short_name(&new.name);
+    let (Some(o), Some(n)) = (
+        parse_callable(&old.signature, short),
+        parse_callable(&new.signature, short),
+    ) else {
+        return vec![(
+            "declaration_changed",
+            true,
+            "declaration changed".to_string(),
+        )];
+    };
+    // Python callers may pass any parameter by name
+    let by_name = new.signature.starts_with("def ") || new.signature.starts_with("async def ");
+    let label = |i: usize, p: &Param| match p.ty {
+        Some(_) => format!("`{}`", p.name),
+        None => format!("#{}", i + 1),
+    };
+
+    let mut changes = Vec::new();
+    for (i, (op, np)) in o.params.iter().zip(&n.params).enumerate() {
+        let (old_type, new_type) = match (op.ty, np.ty) {
+            (Some(a), Some(b)) => (a, b),
+            // Bare names, as in Python without annotations
+            (None, None) if is_identifier(op.name) && is_identifier(np.name) => ("", ""),
+            _ => (op.ty.unwrap_or(op.name), np.ty.unwrap_or(np.name)),
+        };
+        let renamed = match (op.ty, np.ty) {
+            (Some(_), Some(_)) => op.name != np.name,
+            (None, None) => old_type.is_empty() && op.name != np.name,
+            _ => false,
+        };
+        if renamed {
+            changes.push((
+                "parameter_renamed",
+                by_name,
+                format!("parameter `{}` renamed to `{}`", op.name, np.name),
+            ));
+        }
+        if old_type != new_type {
+            if is_subset(old_type, new_type) {
+                changes.push((
+                    "parameter_widened",
+                    false,
+                    format!(
+                        "parameter {} widened from `{old_type}` to `{new_type}`",
+                        label(i, np)
+                    ),
+                ));
+            } else {
+                changes.push((
+                    "parameter_narrowed",
+                    true,
+                    format!(
+                        "parameter {} changed from `{old_type}` to `{new_type}`",
+                        label(i, np)
+                    ),
+                ));
+            }
+        }
+        if op.optional && !np.optional {
+            changes.push((
+                "parameter_made_required",
+                true,
+                format!("parameter {} is now required", label(i, np)),
+            ));
+        } else if !op.optional && np.optional {
+            changes.push((
+                "parameter_made_optional",
+                false,
+                format!("parameter {} is now optional", label(i, np)),
+            ));
+        }
+    }
+    for (i, np) in n.params.iter().enumerate().skip(o.params.len()) {
+        let (breaking, which) = if np.optional {
+            (false, "optional")
+        } else {
+            (true, "required")
+        };
+        changes.push((
+            "parameter_added",
+            breaking,
+            format!("{which} parameter {} added", label(i, np)),
+        ));
+    }
+    for (i, op) in o.params.iter().enumerate().skip(n.params.len()) {
+        changes.push((
+            "parameter_removed",
+            true,
+            format!("parameter {} removed", label(i, op)),
+        ));
+    }
+
+    match (o.returns, n.returns) {
+        (Some(old_return), Some(new_return)) if old_return != new_return => {
+            if is_subset(new_return, old_return) {
+                changes.push((
+                    "return_type_narrowed",
+                    false,
+                    format!("return type narrowed from `{old_return}` to `{new_return}`"),
+                ));
+            } else {
+                changes.push((
+                    "return_type_changed",
+                    true,
+                    format!("return type changed from `{old_return}` to `{new_return}`"),
+                ));
+            }
+        }
+        (Some(_), None) | (None, Some(_)) => changes.push((
+            "return_type_changed",
+            true,
+            format!(
+                "return type changed from `{}` to `{}`",
+                o.returns.unwrap_or("unspecified"),
+                n.returns.unwrap_or("unspecified")
+            ),
+        )),
+        _ => {}
+    }
+    if o.rest != n.rest {
+        changes.push((
+            "declaration_changed",
+            true,
+            "declaration changed".to_string(),
+        ));
+    }
+    if changes.is_empty() {
+        // Only the spelling of the parameters changed, such as their spacing
+        changes.push((
+            "declaration_changed",
+            false,
+            "declaration reformatted".to_string(),
+        ));
+    }
+    changes
+}
+
+/// A type declaration without the types it extends or implements.
+fn without_relations(signature: &str) -> &str {
+    let end = [" extends ", " implements ", "("]
+        .iter()
+        .filter_map(|clause| signature.find(clause))
+        .min()
+        .unwrap_or(signature.len());
+    signature[..end].trim_end()
+}
+
+/// Last segment of a qualified name.
+fn short_name(name: &str) -> &str {
+    name.rsplit(['.', ':']).next().unwrap_or(name)
+}
+
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The parameters of a declaration: those of the first parameter list after its
+/// name (a Go receiver comes before it), which for a function assigned to it
+/// follows `=`.
+fn parse_callable<'a>(signature: &'a str, name: &str) -> Option<Callable<'a>> {
+    let after_name = signature
+        .match_indices(name)
+        .map(|(at, _)| at + name.len())
+        .find(|&end| {
+            let rest = signature[end..].trim_start();
+            rest.starts_with('(') || rest.starts_with('<') || rest.starts_with('=')
+        })?;
+    let open = after_name + signature[after_name..].find('(')?;
+    let close = matching_paren(signature, open)?;
+    let params = split_top_level(&signature[open + 1..close], ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(parse_param)
+        .collect();
+
+    let tail = signature[close + 1..].trim();
+    let tail = tail.strip_suffix("=>").unwrap_or(tail).trim();
+    // `where` and `throws` clauses are not part of the return type
+    let clause = ["where ", "throws "]
+        .iter()
+        .filter_map(|clause| tail.find(clause))
+        .min()
+        .unwrap_or(tail.len());
+    let (returns, clause) = tail.split_at(clause);
+    let returns = returns.trim();
+    let returns = returns
+        .strip_prefix("->")
+        .or_else(|| returns.strip_prefix(':'))
+        .unwrap_or(returns)
+        .trim();
+    Some(Callable {
+        rest: (signature[..open].trim(), clause.trim()),
+        params,
+        returns: (!returns.is_empty()).then_some(returns),
+    })
+}
+
+fn parse_param(param: &str) -> Param<'_> {
+    let parts = split_top_level(param, '=');
+    let declaration = parts[0].trim();
+    let default = parts.len() > 1;
+    let variadic = declaration.starts_with("...") || declaration.starts_with('*');
+    match split_top_level(declaration, ':').as_slice() {
+        [name, _, ..] if !name.trim().is_empty() => {
+            let ty = declaration[name.len() + 1..].trim();
+            // Modifiers such as `readonly` or `mut` come before the name
+            let name = name.split_whitespace().last().unwrap_or(name);
+            let (name, optional_name) = match name.strip_suffix('?') {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            Param {
+                name: name.trim_start_matches(['.', '*']),
+                ty: (!ty.is_empty()).then_some(ty),
+                optional: default || variadic || optional_name,
+            }
+        }
+        _ => Param {
+            name: declaration,
+            ty: None,
+            optional: default || variadic,
+        },
+    }
+}
+
+/// Whether every member of the union `part` is a member of the union `whole`.
+fn is_subset(part: &str, whole: &str) -> bool {
+    let whole: Vec<&str> = split_top_level(whole, '|')
+        .into_iter()
+        .map(str::trim)
+        .collect();
+    split_top_level(part, '|')
+        .into_iter()
+        .all(|member| whole.contains(&member.trim()))
+}
+
+fn matching_paren(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `text` split at each `separator` outside brackets and strings. `=>` and `->`
+/// close no bracket, and neither `==`, `=>` nor `::` is a separator.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let byte_at = |at: Option<usize>| at.and_then(|at| bytes.get(at)).copied();
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        let before = byte_at(i.checked_sub(1));
+        let after = byte_at(Some(i + 1));
+        match c {
+            '"' | '\'' | '`' => quote = Some(c),
+            '(' | '[' | '{' | '<' => depth += 1,
+            '>' if matches!(before, Some(b'=' | b'-')) => {}
+            ')' | ']' | '}' | '>' => depth -= 1,
+            '=' if matches!(before, Some(b'=' | b'!' | b'<' | b'>'))
+                || matches!(after, Some(b'=' | b'>')) => {}
+            ':' if before == Some(b':') || after == Some(b':') => {}
+            c if c == separator && depth <= 0 => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// The file name a package's surface is written to.
+pub fn surface_file_name(package: &str) -> String {
+    let name: String = package
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{SURFACE_FILE_SUFFIX}", name.trim_matches('.'))
+}
+
+/// Write the surface of each package to its own file in `dir`, removing the surface
+/// files of packages no longer there when `prune` is set. Returns the paths written.
+pub fn write_surface(dir: &Path, surface: &ApiSurface, prune: bool) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create surface directory: {}", dir.display()))?;
+    let mut written = Vec::new();
+    for package in &surface.packages {
+        let path = dir.join(surface_file_name(&package.name));
+        let mut text = serde_json::to_string_pretty(package)?;
+        text.push('\n');
+        fs::write(&path, text)
+            .with_context(|| format!("Failed to write surface file: {}", path.display()))?;
+        written.push(path);
+    }
+    if prune {
+        for path in surface_files(dir)? {
+            if !written.contains(&path) {
+                fs::remove_file(&path).with_context(|| {
+                    format!("Failed to remove surface file: {}", path.display())
+                })?;
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// Read the surface files in `dir` written by `write_surface`.
+pub fn read_surface(dir: &Path) -> Result<ApiSurface> {
+    let mut packages = Vec::new();
+    for path in surface_files(dir)? {
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read surface file: {}", path.display()))?;
+        let package: PackageSurface = serde_json::from_str(&text)
+            .with_context(|| format!("Invalid surface file: {}", path.display()))?;
+        packages.push(package);
+    }
+    packages.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(ApiSurface { packages })
+}
+
+fn surface_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read surface directory: {}", dir.display()))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(SURFACE_FILE_SUFFIX))
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, kind: &str, signature: &str) -> ApiSymbol {
+        ApiSymbol {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            file: "src/index.ts".to_string(),
+            signature: signature.to_string(),
+            relations: Vec::new(),
+        }
+    }
+
+    fn classify(name: &str, old: &str, new: &str) -> Vec<(&'static str, bool)> {
+        classify_signature_change(
+            &symbol(name, "function", old),
+            &symbol(name, "function", new),
+        )
+        .into_iter()
+        .map(|(change, breaking, _)| (change, breaking))
+        .collect()
+    }
+
+    #[test]
+    fn parameters_are_compared_by_position() {
+        assert_eq!(
+            classify(
+                "save",
+                "export function save(user: User)",
+                "export function save(user: User, force: boolean)"
+            ),
+            vec![("parameter_added", true)]
+        );
+        assert_eq!(
+            classify(
+                "save",
+                "export function save(user: User)",
+                "export function save(user: User, force?: boolean, ...rest: string[])"
+            ),
+            vec![("parameter_added", false), ("parameter_added", false)]
+        );
+        assert_eq!(
+            classify(
+                "save",
+                "export function save(id: string | number)",
+                "export function save(id: string)"
+            ),
+            vec![("parameter_narrowed", true)]
+        );
+        assert_eq!(
+            classify(
+                "save",
+                "export function save(id: string)",
+                "export function save(id: string | number = 0)"
+            ),
+            vec![
+                ("parameter_widened", false),
+                ("parameter_made_optional", false)
+            ]
+        );
+        assert_eq!(
+            classify(
+                "Orders.find",
+                "find(id: string, opts?: Options)",
+                "find(key: string)"
+            ),
+            vec![("parameter_renamed", false), ("parameter_removed", true)]
+        );
+        assert_eq!(
+            classify("load", "def load(path, mode=\"r\")", "def load(file, mode)"),
+            vec![
+                ("parameter_renamed", true),
+                ("parameter_made_required", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn return_types_may_narrow_but_not_change() {
+        assert_eq!(
+            classify(
+                "find",
+                "export function find(id: string): User | null",
+                "export function find(id: string): User"
+            ),
+            vec![("return_type_narrowed", false)]
+        );
+        assert_eq!(
+            classify(
+                "find",
+                "pub fn find(&self, id: &str) -> Option<User>",
+                "pub fn find(&self, id: &str) -> Result<User>"
+            ),
+            vec![("return_type_changed", true)]
+        );
+        assert_eq!(
+            classify(
+                "Name",
+                "func (s *Store) Name(id int) (string, error)",
+                "func (s *Store) Name(id int64) (string, error)"
+            ),
+            vec![("parameter_narrowed", true)]
+        );
+        assert_eq!(
+            classify(
+                "fetch",
+                "export const fetch = async (url: string): Promise<Response> =>",
+                "export const fetch = async (url: string, init?: RequestInit): Promise<Response> =>"
+            ),
+            vec![("parameter_added", false)]
+        );
+        assert_eq!(
+            classify(
+                "fetch",
+                "export function fetch(url: string)",
+                "export async function fetch(url: string)"
+            ),
+            vec![("declaration_changed", true)]
+        );
+        assert_eq!(
+            classify(
+                "LIMIT",
+                "export const LIMIT: number",
+                "export const LIMIT: bigint"
+            ),
+            vec![("declaration_changed", true)]
+        );
+    }
+
+    #[test]
+    fn surfaces_are_diffed_by_package_and_name() {
+        let package = |name: &str, symbols: Vec<ApiSymbol>| PackageSurface {
+            name: name.to_string(),
+            path: format!("packages/{name}/"),
+            package_type: "npm".to_string(),
+            symbols,
+        };
+        let mut admin = symbol("Admin", "class", "export class Admin extends User");
+        admin.relations = vec!["extends User".to_string()];
+        let old = ApiSurface {
+            packages: vec![
+                package(
+                    "core",
+                    vec![
+                        admin.clone(),
+                        symbol("Config", "interface", "export interface Config"),
+                        symbol("parse", "function", "export function parse(text: string)"),
+                    ],
+                ),
+                package(
+                    "legacy",
+                    vec![symbol("old", "function", "export function old()")],
+                ),
+            ],
+        };
+        admin.relations.clear();
+        let new = ApiSurface {
+            packages: vec![
+                package(
+                    "core",
+                    vec![
+                        admin,
+                        symbol("Config", "type", "export type Config = { debug: boolean }"),
+                        symbol(
+                            "format",
+                            "function",
+                            "export function format(value: unknown)",
+                        ),
+                    ],
+                ),
+                package(
+                    "cli",
+                    vec![symbol("main", "function", "export function main()")],
+                ),
+            ],
+        };
+
+        let changes = diff_surfaces(&old, &new);
+        let changes: Vec<(&str, &str, &str, bool)> = changes
+            .iter()
+            .map(|c| (c.package.as_str(), c.symbol.as_str(), c.change, c.breaking))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("core", "Admin", "relation_removed", true),
+                ("core", "Config", "kind_changed", true),
+                ("core", "parse", "removed", true),
+                ("core", "format", "added", false),
+                ("legacy", "", "package_removed", true),
+                ("cli", "", "package_added", false),
+            ]
+        );
+        assert!(diff_surfaces(&old, &old).is_empty());
+        assert_eq!(surface_file_name("@acme/core"), "@acme_core.api.json");
+    }
+}
@@ -0,0 +1,740 @@
+//! Symbol-level differences between two indexes of the same code, usually the
+//! snapshots of two revisions.
+//!
+//! Symbols are matched by file, kind and qualified name, through files that moved
+//! and containers that were renamed. Those left over on either side are paired up as
+//! renamed when they sit under the same container of the same file and their text,
+//! their own name left out, is mostly the same. Matched symbols are compared by
+//! signature (the declaration up to its body, whitespace collapsed) and by whether
+//! they are exported. Edges are compared through the same matching, so an edge
+//! between two renamed symbols is neither new nor broken.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::storage::sqlite::{SqliteStore, SymbolRow};
+
+/// Share of their lines two symbols need in common to be one renamed.
+const RENAME_SIMILARITY: f64 = 0.6;
+
+/// Share of their symbols two files need in common to be one moved.
+const MOVE_SIMILARITY: f64 = 0.5;
+
+/// Longest signature kept, in bytes.
+const MAX_SIGNATURE_LEN: usize = 300;
+
+/// How the symbols, edges and files of a new index differ from an old one.
+#[derive(Debug, Default)]
+pub struct RevisionComparison {
+    pub added: Vec<SymbolRow>,
+    pub removed: Vec<SymbolRow>,
+    /// The old symbol and the new one
+    pub renamed: Vec<(SymbolRow, SymbolRow)>,
+    pub signature_changes: Vec<SignatureChange>,
+    /// The old symbol and the new one, exported on one side only
+    pub export_changes: Vec<(SymbolRow, SymbolRow)>,
+    pub new_edges: Vec<EdgeChange>,
+    pub broken_edges: Vec<EdgeChange>,
+    pub moved_files: Vec<MovedFile>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SignatureChange {
+    pub old: SymbolRow,
+    pub new: SymbolRow,
+    pub old_signature: String,
+    pub new_signature: String,
+}
+
+/// An edge found on one side only, between symbols named as on that side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeChange {
+    pub from: String,
+    pub from_file: String,
+    pub to: String,
+    pub to_file: String,
+    pub edge_type: String,
+    /// `source_added`, `target_added` or `added` for a new edge, `source_removed`,
+    /// `target_removed` or `removed` for a broken one
+    pub reason: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovedFile {
+    pub old_path: String,
+    pub new_path: String,
+    /// Whether the content is unchanged
+    pub identical: bool,
+}
+
+/// Compare the symbols, edges and files of two indexes.
+pub fn compare_indexes(old: &SqliteStore, new: &SqliteStore) -> Result<RevisionComparison> {
+    let old_symbols = load_symbols(old)?;
+    let new_symbols = load_symbols(new)?;
+    let moved_files = find_moved_files(old, new, &old_symbols, &new_symbols)?;
+    let moves: HashMap<&str, &str> = moved_files
+        .iter()
+        .map(|m| (m.old_path.as_str(), m.new_path.as_str()))
+        .collect();
+    let matching = match_symbols(&old_symbols, &new_symbols, &moves);
+
+    let mut comparison = RevisionComparison {
+        moved_files: moved_files.clone(),
+        ..Default::default()
+    };
+    for (i, symbol) in old_symbols.iter().enumerate() {
+        if symbol.kind == "file" {
+            continue;
+        }
+        let Some(&j) = matching.new_of_old.get(&i) else {
+            comparison.removed.push(symbol.clone());
+            continue;
+        };
+        let new_symbol = &new_symbols[j];
+        if matching.renamed.contains(&i) {
+            comparison
+                .renamed
+                .push((symbol.clone(), new_symbol.clone()));
+        } else {
+            let old_signature = declaration_signature(symbol);
+            let new_signature = declaration_signature(new_symbol);
+            if old_signature != new_signature {
+                comparison.signature_changes.push(SignatureChange {
+                    old: symbol.clone(),
+                    new: new_symbol.clone(),
+                    old_signature,
+                    new_signature,
+                });
+            }
+        }
+        if symbol.exported != new_symbol.exported {
+            comparison
+                .export_changes
+                .push((symbol.clone(), new_symbol.clone()));
+        }
+    }
+    let matched_new: HashSet<usize> = matching.new_of_old.values().copied().collect();
+    comparison.added = new_symbols
+        .iter()
+        .enumerate()
+        .filter(|(j, s)| s.kind != "file" && !matched_new.contains(j))
+        .map(|(_, s)| s.clone())
+        .collect();
+
+    // Edges keyed by their ends as they are on the new side
+    let new_keys: HashMap<&str, SymbolKey> = new_symbols
+        .iter()
+        .map(|s| {
+            (
+                s.id.as_str(),
+                SymbolKey::of(s, &s.file_path, &s.qualified_name),
+            )
+        })
+        .collect();
+    let old_keys: HashMap<&str, SymbolKey> = old_symbols
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let key = match matching.new_of_old.get(&i) {
+                Some(&j) => new_keys[new_symbols[j].id.as_str()].clone(),
+                None => SymbolKey::of(
+                    s,
+                    moves
+                        .get(s.file_path.as_str())
+                        .copied()
+                        .unwrap_or(s.file_path.as_str()),
+                    &matching.qualified_names[i],
+                ),
+            };
+            (s.id.as_str(), key)
+        })
+        .collect();
+    let old_index: HashMap<&str, usize> = old_symbols
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id.as_str(), i))
+        .collect();
+    let new_index: HashMap<&str, usize> = new_symbols
+        .iter()
+        .enumerate()
+        .map(|(j, s)| (s.id.as_str(), j))
+        .collect();
+
+    let old_edges = keyed_edges(old, &old_keys)?;
+    let new_edges = keyed_edges(new, &new_keys)?;
+    for (key, (from, to)) in &old_edges {
+        if new_edges.contains_key(key) {
+            continue;
+        }
+        let (from, to) = (old_index[from], old_index[to]);
+        let reason = if !matching.new_of_old.contains_key(&from) {
+            "source_removed"
+        } else if !matching.new_of_old.contains_key(&to) {
+            "target_removed"
+        } else {
+            "removed"
+        };
+        comparison.broken_edges.push(edge_change(
+            &old_symbols[from],
+            &old_symbols[to],
+            &key.2,
+            reason,
+        ));
+    }
+    for (key, (from, to)) in &new_edges {
+        if old_edges.contains_key(key) {
+            continue;
+        }
+        let (from, to) = (new_index[from], new_index[to]);
+        let reason = if !matched_new.contains(&from) {
+            "source_added"
+        } else if !matched_new.contains(&to) {
+            "target_added"
+        } else {
+            "added"
+        };
+        comparison.new_edges.push(edge_change(
+            &new_symbols[from],
+            &new_symbols[to],
+            &key.2,
+            reason,
+        ));
+    }
+    comparison.broken_edges.sort_by(edge_order);
+    comparison.new_edges.sort_by(edge_order);
+    Ok(comparison)
+}
+
+/// The declaration of a symbol without its body or initial value, whitespace
+/// collapsed: `function save(user: User): Promise<void>`, `pub struct Config`,
+/// `def load(path: str) -> bytes`. Type aliases are kept whole, as their value is
+/// the type.
+pub fn declaration_signature(symbol: &SymbolRow) -> String {
+    let text = symbol.text.as_str();
+    let whole = matches!(symbol.kind.as_str(), "type" | "type_alias" | "typedef");
+    let end = if whole {
+        text.len()
+    } else {
+        declaration_end(text, symbol.language == "python")
+    };
+    let mut signature = text[..end].split_whitespace().collect::<Vec<_>>().join(" ");
+    if !whole {
+        if let Some(at) = initializer_start(&signature) {
+            signature.truncate(at);
+            signature.truncate(signature.trim_end().len());
+        }
+    }
+    if signature.len() > MAX_SIGNATURE_LEN {
+        let mut at = MAX_SIGNATURE_LEN;
+        while !signature.is_char_boundary(at) {
+            at -= 1;
+        }
+        signature.truncate(at);
+        signature.push_str("...");
+    }
+    signature
+}
+
+/// Where the body of a declaration starts: its first brace or semicolon outside
+/// parentheses, or for Python the colon ending its first line.
+fn declaration_end(text: &str, python: bool) -> usize {
+    let mut depth = 0i32;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            '{' | ';' if depth <= 0 => return i,
+            ':' if python && depth <= 0 => {
+                let rest = text[i + 1..].trim_start_matches([' ', '\t']);
+                if rest.is_empty() || rest.starts_with(['\n', '\r', '#']) {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+/// Where the value assigned in a declaration starts, unless the value is a
+/// function, whose parameters belong to the signature.
+fn initializer_start(signature: &str) -> Option<usize> {
+    let bytes = signature.as_bytes();
+    let mut depth = 0i32;
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'(' | b'[' | b'<' => depth += 1,
+            // `=>` and `->` close nothing
+            b')' | b']' | b'>' if !(b == b'>' && i > 0 && matches!(bytes[i - 1], b'=' | b'-')) => {
+                depth -= 1
+            }
+            b'=' if depth <= 0 => {
+                let before = i.checked_sub(1).map(|p| bytes[p]);
+                let after = bytes.get(i + 1).copied();
+                if matches!(before, Some(b'=' | b'!' | b'<' | b'>'))
+                    || matches!(after, Some(b'=' | b'>'))
+                {
+                    continue;
+                }
+                let value = signature[i + 1..].trim_start();
+                let is_function = value.starts_with('(')
+                    || value.starts_with("async")
+                    || value.starts_with("function")
+                    || value.contains("=>");
+                return (!is_function).then_some(i);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Symbols of an index, files included, in file order.
+fn load_symbols(sqlite: &SqliteStore) -> Result<Vec<SymbolRow>> {
+    let files: BTreeSet<String> = sqlite
+        .list_all_symbol_headers()?
+        .into_iter()
+        .map(|header| header.file_path)
+        .collect();
+    let mut symbols = Vec::new();
+    for file in &files {
+        symbols.extend(sqlite.list_symbols_by_file(file)?);
+    }
+    Ok(symbols)
+}
+
+/// Files of the old index only paired with files of the new one only that have the
+/// same content or mostly the same symbols.
+fn find_moved_files(
+    old: &SqliteStore,
+    new: &SqliteStore,
+    old_symbols: &[SymbolRow],
+    new_symbols: &[SymbolRow],
+) -> Result<Vec<MovedFile>> {
+    let old_names = names_by_file(old_symbols);
+    let new_names = names_by_file(new_symbols);
+    let gone: Vec<&str> = old_names
+        .keys()
+        .filter(|file| !new_names.contains_key(*file))
+        .copied()
+        .collect();
+    let came: Vec<&str> = new_names
+        .keys()
+        .filter(|file| !old_names.contains_key(*file))
+        .copied()
+        .collect();
+    if gone.is_empty() || came.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut came_contents = HashMap::new();
+    for file in &came {
+        came_contents.insert(*file, new.get_file_content(file)?);
+    }
+    let mut candidates = Vec::new();
+    for old_file in &gone {
+        let content = old.get_file_content(old_file)?;
+        for new_file in &came {
+            let identical = content.is_some() && content == came_contents[new_file];
+            let similarity = jaccard(&old_names[old_file], &new_names[new_file]);
+            if identical || similarity >= MOVE_SIMILARITY {
+                let score = if identical { 2.0 } else { similarity };
+                candidates.push((score, identical, *old_file, *new_file));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.2.cmp(b.2)).then(a.3.cmp(b.3)));
+
+    let mut used = HashSet::new();
+    let mut moved = Vec::new();
+    for (_, identical, old_file, new_file) in candidates {
+        if used.contains(old_file) || used.contains(new_file) {
+            continue;
+        }
+        used.insert(old_file);
+        used.insert(new_file);
+        moved.push(MovedFile {
+            old_path: old_file.to_string(),
+            new_path: new_file.to_string(),
+            identical,
+        });
+    }
+    moved.sort_by(|a, b| a.old_path.cmp(&b.old_path));
+    Ok(moved)
+}
+
+/// Kinds and qualified names of the symbols of each file, the files themselves left
+/// out.
+fn names_by_file(symbols: &[SymbolRow]) -> HashMap<&str, HashSet<(&str, &str)>> {
+    let mut names: HashMap<&str, HashSet<(&str, &str)>> = HashMap::new();
+    for symbol in symbols {
+        let file = names.entry(symbol.file_path.as_str()).or_default();
+        if symbol.kind != "file" {
+            file.insert((symbol.kind.as_str(), symbol.qualified_name.as_str()));
+        }
+    }
+    names
+}
+
+fn jaccard<T: Eq + std::hash::Hash>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Which new symbol each old one became.
+struct Matching {
+    /// Index of the new symbol by index of the old one
+    new_of_old: HashMap<usize, usize>,
+    /// Old symbols whose new one has another name
+    renamed: HashSet<usize>,
+    /// Qualified name of each old symbol with its containers renamed as on the new side
+    qualified_names: Vec<String>,
+}
+
+/// Match the old symbols to the new ones, containers first so that their members
+/// are looked up under the containers' new names.
+fn match_symbols(old: &[SymbolRow], new: &[SymbolRow], moves: &HashMap<&str, &str>) -> Matching {
+    let old_index: HashMap<&str, usize> = old
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id.as_str(), i))
+        .collect();
+    let new_by_key: HashMap<(&str, &str, &str), usize> = new
+        .iter()
+        .enumerate()
+        .map(|(j, s)| {
+            (
+                (
+                    s.file_path.as_str(),
+                    s.kind.as_str(),
+                    s.qualified_name.as_str(),
+                ),
+                j,
+            )
+        })
+        .collect();
+    let mut new_by_place: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+    for (j, symbol) in new.iter().enumerate() {
+        new_by_place
+            .entry((symbol.file_path.as_str(), symbol.kind.as_str()))
+            .or_default()
+            .push(j);
+    }
+
+    let parent = |i: usize| {
+        old[i]
+            .parent_id
+            .as_deref()
+            .and_then(|id| old_index.get(id).copied())
+    };
+    let depth = |mut i: usize| {
+        let mut depth = 0;
+        while let Some(p) = parent(i) {
+            depth += 1;
+            i = p;
+            if depth > 32 {
+                break;
+            }
+        }
+        depth
+    };
+    let mut levels: Vec<Vec<usize>> = Vec::new();
+    for i in 0..old.len() {
+        let d = depth(i);
+        if levels.len() <= d {
+            levels.resize(d + 1, Vec::new());
+        }
+        levels[d].push(i);
+    }
+
+    let mut matching = Matching {
+        new_of_old: HashMap::new(),
+        renamed: HashSet::new(),
+        qualified_names: old.iter().map(|s| s.qualified_name.clone()).collect(),
+    };
+    let mut new_used = vec![false; new.len()];
+    for level in levels {
+        let mut unmatched = Vec::new();
+        for i in level {
+            let symbol = &old[i];
+            // Members are named after their container, which may have been renamed
+            if let Some(p) = parent(i) {
+                if let (Some(&pj), Some(rest)) = (
+                    matching.new_of_old.get(&p),
+                    symbol
+                        .qualified_name
+                        .strip_prefix(old[p].qualified_name.as_str()),
+                ) {
+                    matching.qualified_names[i] = format!("{}{}", new[pj].qualified_name, rest);
+                }
+            }
+            let file = moves
+                .get(symbol.file_path.as_str())
+                .copied()
+                .unwrap_or(symbol.file_path.as_str());
+            let key = (
+                file,
+                symbol.kind.as_str(),
+                matching.qualified_names[i].as_str(),
+            );
+            match new_by_key.get(&key) {
+                Some(&j) if !new_used[j] => {
+                    new_used[j] = true;
+                    matching.new_of_old.insert(i, j);
+                }
+                _ => unmatched.push(i),
+            }
+        }
+
+        let mut candidates = Vec::new();
+        for i in unmatched {
+            let symbol = &old[i];
+            if symbol.kind == "file" {
+                continue;
+            }
+            let new_parent = match parent(i) {
+                Some(p) => match matching.new_of_old.get(&p) {
+                    Some(&pj) => Some(new[pj].id.as_str()),
+                    // Gone with its container
+                    None => continue,
+                },
+                None => None,
+            };
+            let file = moves
+                .get(symbol.file_path.as_str())
+                .copied()
+                .unwrap_or(symbol.file_path.as_str());
+            let Some(places) = new_by_place.get(&(file, symbol.kind.as_str())) else {
+                continue;
+            };
+            for &j in places {
+                if new_used[j] || new[j].parent_id.as_deref() != new_parent {
+                    continue;
+                }
+                let similarity = text_similarity(symbol, &new[j]);
+                if similarity >= RENAME_SIMILARITY {
+                    candidates.push((similarity, i, j));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+        for (_, i, j) in candidates {
+            if new_used[j] || matching.new_of_old.contains_key(&i) {
+                continue;
+            }
+            new_used[j] = true;
+            matching.new_of_old.insert(i, j);
+            if old[i].name != new[j].name {
+                matching.renamed.insert(i);
+            }
+        }
+    }
+    matching
+}
+
+/// Share of their lines two symbols have in common, their own names left out.
+fn text_similarity(a: &SymbolRow, b: &SymbolRow) -> f64 {
+    let lines = |symbol: &SymbolRow| -> Vec<String> {
+        without_identifier(&symbol.text, &symbol.name)
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect()
+    };
+    let (a, b) = (lines(a), lines(b));
+    if a == b {
+        return 1.0;
+    }
+    jaccard(&a.into_iter().collect(), &b.into_iter().collect())
+}
+
+/// `text` with every whole-word occurrence of `name` blanked out.
+fn without_identifier(text: &str, name: &str) -> String {
+    if name.is_empty() {
+        return text.to_string();
+    }
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (at, _) in text.match_indices(name) {
+        let end = at + name.len();
+        let bounded = !text[..at].chars().next_back().is_some_and(is_word)
+            && !text[end..].chars().next().is_some_and(is_word);
+        if bounded && at >= last {
+            out.push_str(&text[last..at]);
+            out.push('_');
+            last = end;
+        }
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// A symbol as edges are told apart by: file, kind and qualified name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SymbolKey(String, String, String);
+
+impl SymbolKey {
+    fn of(symbol: &SymbolRow, file: &str, qualified_name: &str) -> Self {
+        Self(
+            file.to_string(),
+            symbol.kind.clone(),
+            qualified_name.to_string(),
+        )
+    }
+}
+
+/// An edge as told apart across indexes: the keys of its ends and its type.
+type EdgeKey = (SymbolKey, SymbolKey, String);
+
+/// The edges of an index by their keys, with the ids of their ends.
+fn keyed_edges<'a>(
+    sqlite: &SqliteStore,
+    keys: &HashMap<&'a str, SymbolKey>,
+) -> Result<HashMap<EdgeKey, (&'a str, &'a str)>> {
+    let mut edges = HashMap::new();
+    for edge in sqlite.list_all_edge_rows()? {
+        let (Some((&from_id, from)), Some((&to_id, to))) = (
+            keys.get_key_value(edge.from_symbol_id.as_str()),
+            keys.get_key_value(edge.to_symbol_id.as_str()),
+        ) else {
+            continue;
+        };
+        edges
+            .entry((from.clone(), to.clone(), edge.edge_type))
+            .or_insert((from_id, to_id));
+    }
+    Ok(edges)
+}
+
+fn edge_change(
+    from: &SymbolRow,
+    to: &SymbolRow,
+    edge_type: &str,
+    reason: &'static str,
+) -> EdgeChange {
+    EdgeChange {
+        from: from.qualified_name.clone(),
+        from_file: from.file_path.clone(),
+        to: to.qualified_name.clone(),
+        to_file: to.file_path.clone(),
+        edge_type: edge_type.to_string(),
+        reason,
+    }
+}
+
+fn edge_order(a: &EdgeChange, b: &EdgeChange) -> std::cmp::Ordering {
+    (&a.from_file, &a.from, &a.to, &a.edge_type).cmp(&(&b.from_file, &b.from, &b.to, &b.edge_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(id: &str, kind: &str, qualified_name: &str, text: &str) -> SymbolRow {
+        SymbolRow {
+            id: id.to_string(),
+            file_path: "src/auth.ts".to_string(),
+            language: "typescript".to_string(),
+            kind: kind.to_string(),
+            name: qualified_name.rsplit('.').next().unwrap().to_string(),
+            exported: true,
+            start_byte: 0,
+            end_byte: text.len() as u32,
+            start_line: 1,
+            end_line: 1,
+            text: text.to_string(),
+            qualified_name: qualified_name.to_string(),
+            parent_id: None,
+        }
+    }
+
+    fn member(id: &str, parent: &str, qualified_name: &str, text: &str) -> SymbolRow {
+        SymbolRow {
+            parent_id: Some(parent.to_string()),
+            ..symbol(id, "method", qualified_name, text)
+        }
+    }
+
+    #[test]
+    fn signatures_leave_out_bodies_and_values() {
+        let signature = |kind: &str, language: &str, text: &str| {
+            declaration_signature(&SymbolRow {
+                language: language.to_string(),
+                ..symbol("x", kind, "x", text)
+            })
+        };
+        assert_eq!(
+            signature(
+                "function",
+                "typescript",
+                "export function login(user: string,\n    opts: { remember: boolean }): Promise<Session> {\n  return start(user);\n}"
+            ),
+            "export function login(user: string, opts: { remember: boolean }): Promise<Session>"
+        );
+        assert_eq!(
+            signature("const", "typescript", "export const LIMIT: number = 10;"),
+            "export const LIMIT: number"
+        );
+        assert_eq!(
+            signature(
+                "const",
+                "typescript",
+                "export const check = (token: string): boolean => {\n  return !!token;\n}"
+            ),
+            "export const check = (token: string): boolean =>"
+        );
+        assert_eq!(
+            signature("type", "typescript", "export type Id = { value: string };"),
+            "export type Id = { value: string };"
+        );
+        assert_eq!(
+            signature(
+                "function",
+                "python",
+                "def load(path: str, mode: str = \"r\") -> bytes:\n    return open(path).read()"
+            ),
+            "def load(path: str, mode: str = \"r\") -> bytes"
+        );
+        assert_eq!(
+            signature("function", "rust", "pub fn parse<T: FromStr>(input: &str) -> Result<T> where T::Err: Display {\n    todo!()\n}"),
+            "pub fn parse<T: FromStr>(input: &str) -> Result<T> where T::Err: Display"
+        );
+    }
+
+    #[test]
+    fn renamed_symbols_and_their_members_are_matched() {
+        let old = vec![
+            symbol("c1", "class", "AuthService", "class AuthService {\n  login() {}\n}"),
+            member("m1", "c1", "AuthService.login", "login() {\n  return check(AuthService.token);\n}"),
+            symbol("f1", "function", "verify", "function verify(token) {\n  const parts = token.split('.');\n  return parts.length === 3;\n}"),
+            symbol("f2", "function", "legacy", "function legacy() {\n  return 1;\n}"),
+        ];
+        let new = vec![
+            symbol("c2", "class", "Authenticator", "class Authenticator {\n  login() {}\n}"),
+            member("m2", "c2", "Authenticator.login", "login() {\n  return check(Authenticator.token);\n}"),
+            symbol("f3", "function", "isValidToken", "function isValidToken(token) {\n  const parts = token.split('.');\n  return parts.length === 3;\n}"),
+            symbol("f4", "function", "fresh", "function fresh() {\n  return 2;\n}"),
+        ];
+        let matching = match_symbols(&old, &new, &HashMap::new());
+        assert_eq!(matching.new_of_old.get(&0), Some(&0));
+        assert_eq!(matching.new_of_old.get(&1), Some(&1));
+        assert_eq!(matching.new_of_old.get(&2), Some(&2));
+        assert_eq!(matching.new_of_old.get(&3), None);
+        // The member kept its name; only its container was renamed
+        assert_eq!(matching.renamed, HashSet::from([0, 2]));
+        assert_eq!(matching.qualified_names[1], "Authenticator.login");
+    }
+
+    #[test]
+    fn names_are_blanked_as_whole_words_only() {
+        assert_eq!(
+            without_identifier("save(saveAll, save_x, $save, save)", "save"),
+            "_(saveAll, save_x, $save, _)"
+        );
+    }
+}
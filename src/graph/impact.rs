@@ -0,0 +1,208 @@
+//! Impact of a change: the symbols a diff touches and the code depending on them.
+//!
+//! A hunk is mapped to symbols by the bytes of the file it covers, each changed line
+//! going to the innermost symbol around it, so a change inside a method names the
+//! method rather than its class. The callers are then followed upstream over the same
+//! edges as `build_dependency_graph`, each recorded at the depth it is first reached.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+
+use super::is_call_edge;
+use crate::git::LineRange;
+use crate::storage::sqlite::{SqliteStore, SymbolRow};
+
+/// A symbol depending on a changed one.
+#[derive(Debug, Clone)]
+pub struct ImpactedSymbol {
+    pub symbol: SymbolRow,
+    /// Edges between it and the nearest changed symbol
+    pub depth: usize,
+    /// The symbol it depends on one step closer to the change
+    pub via: String,
+    pub edge_type: String,
+}
+
+/// The symbols of a file the hunks touch, innermost first for each changed line.
+/// `content` is the file's text the symbols' byte spans refer to.
+pub fn touched_symbols<'a>(
+    symbols: &'a [SymbolRow],
+    content: &str,
+    hunks: &[LineRange],
+) -> Vec<&'a SymbolRow> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_start = |line: u32| {
+        line_starts
+            .get(line.saturating_sub(1) as usize)
+            .copied()
+            .unwrap_or(content.len())
+    };
+
+    let mut seen = HashSet::new();
+    let mut touched = Vec::new();
+    for hunk in hunks {
+        // A removal touches the line it stood before
+        let lines = hunk.start..hunk.start + hunk.count.max(1);
+        for line in lines {
+            let (start, end) = (line_start(line), line_start(line + 1));
+            if start >= content.len() {
+                break;
+            }
+            let innermost = symbols
+                .iter()
+                .filter(|s| s.kind != "file")
+                .filter(|s| (s.start_byte as usize) < end && start < s.end_byte as usize)
+                .min_by_key(|s| s.end_byte - s.start_byte);
+            if let Some(symbol) = innermost {
+                if seen.insert(symbol.id.as_str()) {
+                    touched.push(symbol);
+                }
+            }
+        }
+    }
+    touched
+}
+
+/// The symbols depending on `changed`, up to `depth` edges away and `limit` of them,
+/// nearest first.
+pub fn trace_callers(
+    sqlite: &SqliteStore,
+    changed: &[SymbolRow],
+    depth: usize,
+    limit: usize,
+) -> Result<Vec<ImpactedSymbol>> {
+    let mut visited: HashSet<String> = changed.iter().map(|s| s.id.clone()).collect();
+    let mut frontier: Vec<String> = changed.iter().map(|s| s.id.clone()).collect();
+    let mut impacted = Vec::new();
+    let mut symbols: HashMap<String, Option<SymbolRow>> = HashMap::new();
+
+    for level in 1..=depth {
+        let mut next = Vec::new();
+        for current in &frontier {
+            for edge in sqlite.list_edges_to(current, limit)? {
+                if impacted.len() >= limit {
+                    return Ok(impacted);
+                }
+                if !is_call_edge(&edge.edge_type) && edge.edge_type != "reference" {
+                    continue;
+                }
+                if visited.contains(&edge.from_symbol_id) {
+                    continue;
+                }
+                let caller = match symbols.get(&edge.from_symbol_id) {
+                    Some(symbol) => symbol.clone(),
+                    None => {
+                        let symbol = sqlite.get_symbol_by_id(&edge.from_symbol_id)?;
+                        symbols.insert(edge.from_symbol_id.clone(), symbol.clone());
+                        symbol
+                    }
+                };
+                let Some(caller) = caller else {
+                    continue;
+                };
+                visited.insert(caller.id.clone());
+                // Top-level code is reported but nothing depends on a file itself
+                if caller.kind != "file" {
+                    next.push(caller.id.clone());
+                }
+                impacted.push(ImpactedSymbol {
+                    symbol: caller,
+                    depth: level,
+                    via: current.clone(),
+                    edge_type: edge.edge_type,
+                });
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    Ok(impacted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(id: &str, kind: &str, content: &str, text: &str) -> SymbolRow {
+        let start = content.find(text).unwrap();
+        SymbolRow {
+            id: id.to_string(),
+            file_path: "src/users.ts".to_string(),
+            language: "typescript".to_string(),
+            kind: kind.to_string(),
+            name: id.to_string(),
+            exported: false,
+            start_byte: start as u32,
+            end_byte: (start + text.len()) as u32,
+            start_line: 0,
+            end_line: 0,
+            text: text.to_string(),
+            qualified_name: id.to_string(),
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn maps_changed_lines_to_the_innermost_symbols() {
+        let content = "import { db } from './db';\n\
+                       export class Users {\n\
+                       \x20 table = 'users';\n\
+                       \x20 find(id) {\n\
+                       \x20   return db.get(id);\n\
+                       \x20 }\n\
+                       }\n\
+                       export function save(user) {\n\
+                       \x20 db.put(user);\n\
+                       }\n";
+        let class_text = &content[content.find("export class").unwrap()
+            ..content.find("}\nexport function").unwrap() + 1];
+        let symbols = vec![
+            symbol("file", "file", content, content),
+            symbol("Users", "class", content, class_text),
+            symbol(
+                "find",
+                "method",
+                content,
+                "find(id) {\n    return db.get(id);\n  }",
+            ),
+            symbol(
+                "save",
+                "function",
+                content,
+                "export function save(user) {\n  db.put(user);\n}",
+            ),
+        ];
+        let touched = |hunks: &[LineRange]| -> Vec<String> {
+            touched_symbols(&symbols, content, hunks)
+                .into_iter()
+                .map(|s| s.id.clone())
+                .collect()
+        };
+
+        assert_eq!(touched(&[LineRange { start: 5, count: 1 }]), vec!["find"]);
+        assert_eq!(
+            touched(&[LineRange { start: 3, count: 2 }]),
+            vec!["Users", "find"]
+        );
+        // Removing the last statement of `save` leaves its closing brace
+        assert_eq!(
+            touched(&[LineRange {
+                start: 10,
+                count: 0
+            }]),
+            vec!["save"]
+        );
+        // Imports and lines past the end belong to no symbol
+        assert!(touched(&[LineRange { start: 1, count: 1 }]).is_empty());
+        assert!(touched(&[LineRange {
+            start: 11,
+            count: 0
+        }])
+        .is_empty());
+    }
+}
@@ -1,10 +1,20 @@
 //! Graph building functions for call hierarchies, type graphs, and dependency graphs
 
+pub mod api_surface;
+pub mod compare;
+pub mod impact;
 pub mod pagerank;
 
+use crate::indexer::pipeline::http_calls::HTTP_CALL_EDGE;
 use crate::storage::sqlite::{SqliteStore, SymbolRow};
 use serde_json::json;
 
+/// Edges a call hierarchy follows: calls within a service and HTTP calls between
+/// services.
+fn is_call_edge(edge_type: &str) -> bool {
+    edge_type == "call" || edge_type == HTTP_CALL_EDGE
+}
+
 /// Build a dependency graph starting from a root symbol
 pub fn build_dependency_graph(
     sqlite: &SqliteStore,
@@ -58,7 +68,7 @@ pub fn build_dependency_graph(
                     }
 
                     // Filter edge types? "call" is primary. "reference" maybe?
-                    if e.edge_type != "call" && e.edge_type != "reference" {
+                    if !is_call_edge(&e.edge_type) && e.edge_type != "reference" {
                         continue;
                     }
 
@@ -115,7 +125,7 @@ pub fn build_dependency_graph(
                         break;
                     }
 
-                    if e.edge_type != "call" && e.edge_type != "reference" {
+                    if !is_call_edge(&e.edge_type) && e.edge_type != "reference" {
                         continue;
                     }
 
@@ -218,7 +228,7 @@ pub fn build_call_hierarchy(
                     if edges.len() >= limit {
                         break;
                     }
-                    if e.edge_type != "call" {
+                    if !is_call_edge(&e.edge_type) {
                         continue;
                     }
                     let Some(caller) = sqlite.get_symbol_by_id(&e.from_symbol_id)? else {
@@ -238,13 +248,13 @@ pub fn build_call_hierarchy(
                     edges.push(json!({
                         "from": e.from_symbol_id,
                         "to": e.to_symbol_id,
-                        "edge_type": "call",
+                        "edge_type": e.edge_type,
                         "at_file": e.at_file,
                         "at_line": e.at_line,
                         "evidence_count": e.evidence_count,
                         "resolution": e.resolution,
                         "evidence": sqlite
-                            .list_edge_evidence(&e.from_symbol_id, &e.to_symbol_id, "call", 3)
+                            .list_edge_evidence(&e.from_symbol_id, &e.to_symbol_id, &e.edge_type, 3)
                             .unwrap_or_default()
                             .into_iter()
                             .map(|ev| json!({
@@ -264,7 +274,7 @@ pub fn build_call_hierarchy(
                     if edges.len() >= limit {
                         break;
                     }
-                    if e.edge_type != "call" {
+                    if !is_call_edge(&e.edge_type) {
                         continue;
                     }
                     let Some(callee) = sqlite.get_symbol_by_id(&e.to_symbol_id)? else {
@@ -284,13 +294,13 @@ pub fn build_call_hierarchy(
                     edges.push(json!({
                         "from": e.from_symbol_id,
                         "to": e.to_symbol_id,
-                        "edge_type": "call",
+                        "edge_type": e.edge_type,
                         "at_file": e.at_file,
                         "at_line": e.at_line,
                         "evidence_count": e.evidence_count,
                         "resolution": e.resolution,
                         "evidence": sqlite
-                            .list_edge_evidence(&e.from_symbol_id, &e.to_symbol_id, "call", 3)
+                            .list_edge_evidence(&e.from_symbol_id, &e.to_symbol_id, &e.edge_type, 3)
                             .unwrap_or_default()
                             .into_iter()
                             .map(|ev| json!({
@@ -429,6 +439,8 @@ mod tests {
             start_line: 1,
             end_line: 1,
             text: format!("export function {name}() {{}}"),
+            qualified_name: name.to_string(),
+            parent_id: None,
         }
     }
 
@@ -5,7 +5,7 @@ use crate::{
     storage::sqlite::{SqliteStore, SymbolMetricsRow},
 };
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Compute and store PageRank scores for all symbols in the graph.
 ///
@@ -145,6 +145,127 @@ pub fn compute_and_store_pagerank(sqlite: &SqliteStore, config: &Config) -> Resu
     Ok(())
 }
 
+/// Upper bound on node updates before an incremental run gives up and recomputes
+/// PageRank for the whole graph.
+const INCREMENTAL_MAX_UPDATES: usize = 50_000;
+
+/// Update stored PageRank scores after a local graph change.
+///
+/// `seeds` are the symbols whose score may have moved: symbols of re-indexed files
+/// and the targets of edges that were added or removed. Starting from the stored
+/// scores, each seed is recomputed from its in-neighbors and any change larger than
+/// the tolerance is propagated to its successors (asynchronous Gauss-Seidel
+/// iteration), so only the affected part of the graph is visited.
+///
+/// Falls back to [`compute_and_store_pagerank`] when no scores are stored yet or the
+/// change spreads too far. Scores of untouched symbols keep the base term of the last
+/// full run, which drifts slightly as the symbol count changes until the next one.
+///
+/// # Returns
+/// The number of symbols whose score was written
+pub fn update_pagerank_incremental(
+    sqlite: &SqliteStore,
+    config: &Config,
+    seeds: &[String],
+) -> Result<usize> {
+    let num_symbols = sqlite
+        .count_rankable_symbols()
+        .context("Failed to count symbols")?;
+    if num_symbols == 0 {
+        return Ok(0);
+    }
+
+    let has_scores = !sqlite
+        .get_top_symbols_by_pagerank(1)
+        .context("Failed to load stored PageRank scores")?
+        .is_empty();
+    if !has_scores {
+        compute_and_store_pagerank(sqlite, config)?;
+        return Ok(num_symbols as usize);
+    }
+
+    let damping = config.pagerank_damping as f64;
+    let base_score = (1.0 - damping) / num_symbols as f64;
+    let tolerance = base_score * 1e-3;
+
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    let score_of = |id: &str, scores: &mut HashMap<String, f64>| -> Result<f64> {
+        if let Some(score) = scores.get(id) {
+            return Ok(*score);
+        }
+        let score = sqlite
+            .get_symbol_metrics(id)?
+            .map(|m| m.pagerank)
+            .unwrap_or(base_score);
+        scores.insert(id.to_string(), score);
+        Ok(score)
+    };
+
+    let mut queue: VecDeque<String> = VecDeque::new();
+    let mut queued: HashSet<String> = HashSet::new();
+    let mut changed: HashSet<String> = HashSet::new();
+    for id in seeds {
+        if sqlite.is_rankable_symbol(id)? && queued.insert(id.clone()) {
+            queue.push_back(id.clone());
+            // Seeds are always written, so new symbols get a stored score
+            changed.insert(id.clone());
+        }
+    }
+
+    let mut updates = 0usize;
+    while let Some(id) = queue.pop_front() {
+        queued.remove(&id);
+        updates += 1;
+        if updates > INCREMENTAL_MAX_UPDATES {
+            tracing::debug!(
+                seeds = seeds.len(),
+                "Incremental PageRank exceeded update budget, recomputing"
+            );
+            compute_and_store_pagerank(sqlite, config)?;
+            return Ok(num_symbols as usize);
+        }
+
+        let mut score = base_score;
+        for (from_id, out_degree) in sqlite.list_rankable_predecessors(&id)? {
+            if out_degree > 0 {
+                score += damping * score_of(&from_id, &mut scores)? / out_degree as f64;
+            }
+        }
+
+        let previous = score_of(&id, &mut scores)?;
+        scores.insert(id.clone(), score);
+        if (score - previous).abs() <= tolerance {
+            continue;
+        }
+
+        changed.insert(id.clone());
+        for next in sqlite.list_rankable_successors(&id)? {
+            if queued.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let updated_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    for symbol_id in &changed {
+        let metrics = SymbolMetricsRow {
+            symbol_id: symbol_id.clone(),
+            pagerank: scores[symbol_id],
+            in_degree: 0,
+            out_degree: 0,
+            updated_at,
+        };
+        sqlite
+            .upsert_symbol_metrics(&metrics)
+            .with_context(|| format!("Failed to store PageRank for {}", symbol_id))?;
+    }
+
+    Ok(changed.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -183,11 +304,14 @@ mod tests {
             rank_test_penalty: 0.1,
             rank_popularity_weight: 0.05,
             rank_popularity_cap: 50,
+            rank_history_weight: 0.05,
+            history_max_commits: 1000,
             index_patterns: vec!["**/*.ts".to_string()],
             exclude_patterns: vec!["**/node_modules/**".to_string()],
             watch_mode: false,
             watch_debounce_ms: 250,
             watch_min_index_interval_ms: 5000,
+            watch_backend: crate::config::WatchBackend::Auto,
             max_context_bytes: 200_000,
             index_node_modules: false,
             repo_roots: vec!["/tmp/test".into()],
@@ -218,6 +342,9 @@ mod tests {
             metrics_enabled: true,
             metrics_port: 9090,
             package_detection_enabled: true,
+            mcp_transport: crate::config::McpTransport::Stdio,
+            http_bind_addr: "127.0.0.1:8765".to_string(),
+            http_auth_token: None,
         }
     }
 
@@ -256,6 +383,8 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
                 text: "function A() {}".to_string(),
+                qualified_name: "A".to_string(),
+                parent_id: None,
             },
             SymbolRow {
                 id: "sym_b".to_string(),
@@ -269,6 +398,8 @@ mod tests {
                 start_line: 2,
                 end_line: 2,
                 text: "function B() {}".to_string(),
+                qualified_name: "B".to_string(),
+                parent_id: None,
             },
             SymbolRow {
                 id: "sym_c".to_string(),
@@ -282,6 +413,8 @@ mod tests {
                 start_line: 3,
                 end_line: 3,
                 text: "function C() {}".to_string(),
+                qualified_name: "C".to_string(),
+                parent_id: None,
             },
         ];
 
@@ -366,6 +499,8 @@ mod tests {
                 start_line: 1,
                 end_line: 10,
                 text: "function f() {}".to_string(),
+                qualified_name: "test.ts".to_string(),
+                parent_id: None,
             },
             SymbolRow {
                 id: "sym_f".to_string(),
@@ -379,6 +514,8 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
                 text: "function f() {}".to_string(),
+                qualified_name: "f".to_string(),
+                parent_id: None,
             },
         ];
 
@@ -418,4 +555,80 @@ mod tests {
             .expect("function symbol should have metrics");
         assert!(func_metrics.pagerank > 0.0);
     }
+
+    #[test]
+    fn incremental_update_matches_full_recompute() {
+        use crate::storage::sqlite::{EdgeRow, SymbolRow};
+
+        let sqlite = setup_test_store();
+        let mut config = create_test_config();
+        config.pagerank_iterations = 200;
+
+        let add_symbol = |id: &str| {
+            sqlite
+                .upsert_symbol(&SymbolRow {
+                    id: id.to_string(),
+                    file_path: format!("{id}.ts"),
+                    language: "typescript".to_string(),
+                    kind: "function".to_string(),
+                    name: id.to_string(),
+                    exported: true,
+                    start_byte: 0,
+                    end_byte: 10,
+                    start_line: 1,
+                    end_line: 1,
+                    text: format!("function {id}() {{}}"),
+                    qualified_name: id.to_string(),
+                    parent_id: None,
+                })
+                .unwrap();
+        };
+        let add_edge = |from: &str, to: &str| {
+            sqlite
+                .upsert_edge(&EdgeRow {
+                    from_symbol_id: from.to_string(),
+                    to_symbol_id: to.to_string(),
+                    edge_type: "call".to_string(),
+                    at_file: None,
+                    at_line: None,
+                    confidence: 1.0,
+                    evidence_count: 1,
+                    resolution: "local".to_string(),
+                })
+                .unwrap();
+        };
+
+        for id in ["a", "b", "c", "d"] {
+            add_symbol(id);
+        }
+        add_edge("a", "b");
+        add_edge("b", "c");
+        add_edge("d", "c");
+        compute_and_store_pagerank(&sqlite, &config).unwrap();
+
+        // New symbol e calls a, and a now also calls d
+        add_symbol("e");
+        add_edge("e", "a");
+        add_edge("a", "d");
+        let seeds = ["e", "a", "b", "d"].map(String::from);
+        let updated = update_pagerank_incremental(&sqlite, &config, &seeds).unwrap();
+        assert!(updated > 0);
+
+        let ids = ["a", "b", "c", "d", "e"].map(String::from);
+        let incremental = sqlite.batch_get_symbol_metrics(&ids).unwrap();
+
+        compute_and_store_pagerank(&sqlite, &config).unwrap();
+        let full = sqlite.batch_get_symbol_metrics(&ids).unwrap();
+
+        let base = (1.0 - config.pagerank_damping as f64) / ids.len() as f64;
+        for id in &ids {
+            let diff = (incremental[id] - full[id]).abs();
+            assert!(
+                diff < base * 1e-2,
+                "{id}: incremental={} full={}",
+                incremental[id],
+                full[id]
+            );
+        }
+    }
 }
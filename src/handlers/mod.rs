@@ -1,17 +1,32 @@
 //! MCP tool handlers
 
+use crate::git::{diff_revisions, new_side_revision, ChangedFile};
+use crate::graph::api_surface::{
+    api_surface, diff_surfaces, read_surface, write_surface, ApiChange, ApiSurface,
+};
+use crate::graph::compare::{compare_indexes, declaration_signature, EdgeChange};
+use crate::graph::impact::{touched_symbols, trace_callers, ImpactedSymbol};
 use crate::graph::{build_call_hierarchy, build_dependency_graph, build_type_graph};
+use crate::indexer::pipeline::history::MAX_CO_CHANGE_FILES;
+use crate::lsp::position::{Position, TextDocument};
 use crate::path::{PathError, PathNormalizer, Utf8PathBuf};
 use crate::retrieval::assembler::FormatMode;
+use crate::retrieval::content::{search_content, ContentSearch};
 use crate::retrieval::Retriever;
-use crate::storage::sqlite::{SqliteStore, SymbolRow};
+use crate::storage::sqlite::{
+    AuthorStatsRow, ChangeStatsRow, CoChangeRow, GitCommitRow, HistoryTarget, SqliteStore,
+    SymbolHeaderRow, SymbolRow,
+};
 use crate::tools::*;
+use anyhow::Context;
 use rust_mcp_sdk::schema::{CallToolError, CallToolRequestParams};
 use serde::de::DeserializeOwned;
 use serde_json::json;
 
+pub use snapshots::RevisionSnapshots;
 pub use state::AppState;
 
+mod snapshots;
 mod state;
 
 /// Type alias for data flow trace results
@@ -66,6 +81,25 @@ pub fn extract_usage_line(text: &str, symbol_name: &str) -> Option<String> {
     None
 }
 
+/// Convert a tool's `file_path` argument to the index's key (path relative to
+/// `BASE_DIR`). Paths outside the base directory are passed through unchanged.
+fn normalize_tool_file_path(state: &AppState, file_path: &str) -> Result<String, anyhow::Error> {
+    // Create path normalizer for validation
+    let normalizer = PathNormalizer::new(state.config.base_dir.clone());
+
+    // Convert to Utf8Path and validate
+    let path_buf = std::path::PathBuf::from(file_path);
+    let utf8_path = Utf8PathBuf::from_path_buf(path_buf).map_err(|_| PathError::NonUtf8 {
+        path: std::path::PathBuf::from(file_path),
+    })?;
+
+    // Get relative path to base (for database lookup)
+    Ok(normalizer
+        .relative_to_base(&utf8_path)
+        .map(|p| p.to_string())
+        .unwrap_or_else(|_| file_path.to_string()))
+}
+
 /// Handle refresh_index tool
 pub async fn handle_refresh_index(
     state: &AppState,
@@ -111,7 +145,11 @@ pub async fn handle_search_code(
 
     let result = retriever.search(&tool.query, limit, exported_only).await?;
     // Return only the SearchResponse (without hit_signals) to reduce response size
-    Ok(serde_json::to_value(result.response)?)
+    let mut response = serde_json::to_value(result.response)?;
+    if !result.query_errors.is_empty() {
+        response["query_errors"] = json!(result.query_errors);
+    }
+    Ok(response)
 }
 
 /// Handle get_definition tool
@@ -169,21 +207,7 @@ pub fn handle_get_file_symbols(
         "get_file_symbols called"
     );
 
-    // Create path normalizer for validation
-    let normalizer = PathNormalizer::new(state.config.base_dir.clone());
-
-    // Convert to Utf8Path and validate
-    let path_buf = std::path::PathBuf::from(&tool.file_path);
-    let utf8_path = Utf8PathBuf::from_path_buf(path_buf)
-        .map_err(|_| PathError::NonUtf8 {
-            path: std::path::PathBuf::from(&tool.file_path),
-        })?;
-
-    // Get relative path to base (for database lookup)
-    let file_path_normalized = normalizer
-        .relative_to_base(&utf8_path)
-        .map(|p| p.to_string())
-        .unwrap_or_else(|_| tool.file_path.clone());
+    let file_path_normalized = normalize_tool_file_path(state, &tool.file_path)?;
 
     tracing::debug!(
         original_path = %tool.file_path,
@@ -193,6 +217,18 @@ pub fn handle_get_file_symbols(
 
     let sqlite = &state.sqlite;
 
+    if tool.tree.unwrap_or(false) {
+        // Containers are needed to place their members even when they are filtered out
+        let rows = sqlite.list_symbol_headers_by_file(&file_path_normalized, false)?;
+        let (count, symbols) = symbol_tree(&rows, |row| row.exported || !exported_only);
+        return Ok(json!({
+            "file_path": tool.file_path,
+            "file_path_normalized": file_path_normalized,
+            "count": count,
+            "symbols": symbols,
+        }));
+    }
+
     let rows = sqlite.list_symbol_headers_by_file(&file_path_normalized, exported_only)?;
 
     if rows.is_empty() {
@@ -211,6 +247,241 @@ pub fn handle_get_file_symbols(
     }))
 }
 
+/// Nest a file's symbols under their containers, returning how many symbols were
+/// placed and the top-level ones. Only rows accepted by `keep` are listed (the
+/// `file` row never is); a symbol whose container is not listed hangs from its
+/// nearest listed ancestor.
+fn symbol_tree(
+    rows: &[SymbolHeaderRow],
+    keep: impl Fn(&SymbolHeaderRow) -> bool,
+) -> (usize, Vec<serde_json::Value>) {
+    use std::collections::{HashMap, HashSet};
+
+    let by_id: HashMap<&str, &SymbolHeaderRow> =
+        rows.iter().map(|r| (r.id.as_str(), r)).collect();
+    let kept: Vec<&SymbolHeaderRow> = rows
+        .iter()
+        .filter(|row| row.kind != "file" && keep(row))
+        .collect();
+    let kept_ids: HashSet<&str> = kept.iter().map(|r| r.id.as_str()).collect();
+
+    let mut children: HashMap<Option<&str>, Vec<&SymbolHeaderRow>> = HashMap::new();
+    for row in &kept {
+        let mut parent = row.parent_id.as_deref();
+        for _ in 0..rows.len() {
+            match parent {
+                Some(id) if !kept_ids.contains(id) => {
+                    parent = by_id.get(id).and_then(|p| p.parent_id.as_deref());
+                }
+                _ => break,
+            }
+        }
+        children.entry(parent).or_default().push(row);
+    }
+
+    fn node(
+        row: &SymbolHeaderRow,
+        children: &std::collections::HashMap<Option<&str>, Vec<&SymbolHeaderRow>>,
+    ) -> serde_json::Value {
+        let mut value = json!(row);
+        value["children"] = children
+            .get(&Some(row.id.as_str()))
+            .map(|members| members.iter().map(|m| node(m, children)).collect())
+            .unwrap_or_default();
+        value
+    }
+
+    let roots = children
+        .get(&None)
+        .map(|top| top.iter().map(|row| node(row, &children)).collect())
+        .unwrap_or_default();
+    (kept.len(), roots)
+}
+
+/// Targets considered when resolving an identifier; edges to other symbols are ignored
+const RESOLVE_TARGET_LIMIT: usize = 500;
+/// Outgoing edges scanned per enclosing symbol
+const RESOLVE_EDGE_LIMIT: usize = 1000;
+/// Candidates returned alongside the chosen target
+const RESOLVE_CANDIDATE_LIMIT: usize = 10;
+
+/// A possible target for the identifier under the cursor.
+struct PositionTarget {
+    symbol_id: String,
+    /// Evidence places the reference on the cursor's line
+    at_cursor: bool,
+    /// Index of the enclosing symbol the edge starts from (0 = innermost)
+    depth: usize,
+    confidence: f32,
+    value: serde_json::Value,
+}
+
+fn symbol_summary(row: &SymbolRow) -> serde_json::Value {
+    json!({
+        "id": row.id,
+        "name": row.name,
+        "qualified_name": row.qualified_name,
+        "parent_id": row.parent_id,
+        "kind": row.kind,
+        "file_path": row.file_path,
+        "language": row.language,
+        "exported": row.exported,
+        "start_line": row.start_line,
+        "end_line": row.end_line,
+    })
+}
+
+/// Handle resolve_position tool
+///
+/// Finds the symbols enclosing the cursor, then resolves the identifier under it:
+/// a declaration name resolves to its own symbol; otherwise the outgoing edges of
+/// the enclosing symbols (innermost first) are matched by target name, preferring
+/// edges whose evidence is on the cursor's line. Only when no edge matches does it
+/// fall back to a plain name lookup.
+pub fn handle_resolve_position(
+    state: &AppState,
+    tool: ResolvePositionTool,
+) -> Result<serde_json::Value, anyhow::Error> {
+    if tool.line == 0 || tool.column == 0 {
+        anyhow::bail!("line and column are 1-based");
+    }
+    let file_path_normalized = normalize_tool_file_path(state, &tool.file_path)?;
+    let text = std::fs::read_to_string(state.config.base_dir.join(&file_path_normalized))
+        .with_context(|| format!("Failed to read {}", tool.file_path))?;
+    let doc = TextDocument::new(text);
+    if doc.line(tool.line as usize - 1).is_none() {
+        anyhow::bail!("Line {} is past the end of {}", tool.line, tool.file_path);
+    }
+    let offset = doc.offset(Position {
+        line: tool.line - 1,
+        character: tool.column - 1,
+    });
+
+    let sqlite = &state.sqlite;
+    let headers = sqlite.list_symbol_headers_by_file(&file_path_normalized, false)?;
+
+    // Enclosing symbols, innermost first; the file symbol (if any) comes last
+    let mut enclosing: Vec<&SymbolHeaderRow> = headers
+        .iter()
+        .filter(|h| (h.start_byte as usize) <= offset && offset < h.end_byte as usize)
+        .collect();
+    enclosing.sort_by_key(|h| (h.kind == "file", h.end_byte - h.start_byte));
+
+    let identifier = doc.identifier_at(offset);
+    let mut targets: Vec<PositionTarget> = Vec::new();
+
+    if let Some((start, end, name)) = identifier {
+        let ident_range = doc.range(start, end);
+
+        // The cursor is on a declaration's own name
+        if let Some(decl) = enclosing.iter().find(|h| {
+            h.kind != "file"
+                && h.name == name
+                && doc.name_range(h.start_byte as usize, h.end_byte as usize, name) == ident_range
+        }) {
+            targets.push(PositionTarget {
+                symbol_id: decl.id.clone(),
+                at_cursor: true,
+                depth: 0,
+                confidence: 1.0,
+                value: json!({ "via": "declaration", "symbol": decl }),
+            });
+        }
+
+        let by_id: std::collections::HashMap<String, SymbolRow> = sqlite
+            .search_symbols_by_exact_name(name, None, RESOLVE_TARGET_LIMIT)?
+            .into_iter()
+            .map(|row| (row.id.clone(), row))
+            .collect();
+
+        if targets.is_empty() {
+            for (depth, scope) in enclosing.iter().enumerate() {
+                for edge in sqlite.list_edges_from(&scope.id, RESOLVE_EDGE_LIMIT)? {
+                    let Some(target) = by_id.get(&edge.to_symbol_id) else {
+                        continue;
+                    };
+                    let evidence = sqlite.list_edge_evidence(
+                        &edge.from_symbol_id,
+                        &edge.to_symbol_id,
+                        &edge.edge_type,
+                        50,
+                    )?;
+                    let evidence_lines: Vec<u32> = evidence.iter().map(|e| e.at_line).collect();
+                    let at_cursor = evidence_lines.contains(&tool.line)
+                        || (evidence_lines.is_empty() && edge.at_line == Some(tool.line));
+                    targets.push(PositionTarget {
+                        symbol_id: target.id.clone(),
+                        at_cursor,
+                        depth,
+                        confidence: edge.confidence,
+                        value: json!({
+                            "via": "edge",
+                            "symbol": symbol_summary(target),
+                            "edge_type": edge.edge_type,
+                            "resolution": edge.resolution,
+                            "confidence": edge.confidence,
+                            "from_symbol_id": edge.from_symbol_id,
+                            "evidence_lines": evidence_lines,
+                            "at_cursor": at_cursor,
+                        }),
+                    });
+                }
+                // Outer symbols repeat the edges of the symbols they contain
+                if targets.iter().any(|t| t.at_cursor) {
+                    break;
+                }
+            }
+        }
+
+        if targets.is_empty() {
+            for row in by_id.values().filter(|row| row.kind != "file") {
+                targets.push(PositionTarget {
+                    symbol_id: row.id.clone(),
+                    at_cursor: false,
+                    depth: usize::from(row.file_path != file_path_normalized),
+                    confidence: 0.0,
+                    value: json!({ "via": "name_match", "symbol": symbol_summary(row) }),
+                });
+            }
+        }
+    }
+
+    targets.sort_by(|a, b| {
+        b.at_cursor
+            .cmp(&a.at_cursor)
+            .then(a.depth.cmp(&b.depth))
+            .then(b.confidence.total_cmp(&a.confidence))
+            .then(a.symbol_id.cmp(&b.symbol_id))
+    });
+    let mut seen = std::collections::HashSet::new();
+    targets.retain(|t| seen.insert(t.symbol_id.clone()));
+    targets.truncate(RESOLVE_CANDIDATE_LIMIT);
+
+    let mut targets = targets.into_iter().map(|t| t.value);
+    let resolved = targets.next();
+    let candidates: Vec<serde_json::Value> = targets.collect();
+
+    Ok(json!({
+        "file_path": tool.file_path,
+        "file_path_normalized": file_path_normalized,
+        "line": tool.line,
+        "column": tool.column,
+        "identifier": identifier.map(|(start, end, name)| json!({
+            "name": name,
+            "line": doc.position(start).line + 1,
+            "start_column": doc.position(start).character + 1,
+            "end_column": doc.position(end).character + 1,
+        })),
+        "enclosing_symbol": enclosing.first(),
+        "enclosing_symbols": enclosing
+            .iter()
+            .map(|h| json!({ "id": h.id, "name": h.name, "kind": h.kind }))
+            .collect::<Vec<_>>(),
+        "resolved": resolved,
+        "candidates": candidates,
+    }))
+}
+
 /// Handle get_index_stats tool
 pub fn handle_get_index_stats(state: &AppState) -> Result<serde_json::Value, anyhow::Error> {
     let sqlite = &state.sqlite;
@@ -594,6 +865,7 @@ pub async fn handle_explain_search(
                 "popularity_boost": sig.popularity_boost,
                 "learning_boost": sig.learning_boost,
                 "affinity_boost": sig.affinity_boost,
+                "history_boost": sig.history_boost,
             });
         }
 
@@ -618,6 +890,7 @@ pub async fn handle_explain_search(
         "limit": resp.limit,
         "count": results.len(),
         "results": results,
+        "query_errors": result.query_errors,
         "display": display,
     }))
 }
@@ -1043,21 +1316,7 @@ pub fn handle_get_module_summary(
         "get_module_summary called"
     );
 
-    // Create path normalizer for validation
-    let normalizer = PathNormalizer::new(state.config.base_dir.clone());
-
-    // Convert to Utf8Path and validate
-    let path_buf = std::path::PathBuf::from(&tool.file_path);
-    let utf8_path = Utf8PathBuf::from_path_buf(path_buf)
-        .map_err(|_| PathError::NonUtf8 {
-            path: std::path::PathBuf::from(&tool.file_path),
-        })?;
-
-    // Get relative path to base (for database lookup)
-    let file_path_normalized = normalizer
-        .relative_to_base(&utf8_path)
-        .map(|p| p.to_string())
-        .unwrap_or_else(|_| tool.file_path.clone());
+    let file_path_normalized = normalize_tool_file_path(state, &tool.file_path)?;
 
     tracing::debug!(
         original_path = %tool.file_path,
@@ -1492,8 +1751,30 @@ pub fn handle_find_affected_code(
         .collect::<std::collections::HashSet<_>>()
         .len();
 
+    // Files that tend to change along with the symbol's file without depending on it
+    let affected_paths = affected
+        .iter()
+        .filter_map(|f| f.get("file_path").and_then(|v| v.as_str()))
+        .collect::<std::collections::HashSet<_>>();
+    let co_changed = sqlite
+        .list_co_changed_files(&root.file_path, MAX_CO_CHANGE_FILES, limit)?
+        .into_iter()
+        .filter(|other| !affected_paths.contains(other.file_path.as_str()))
+        .filter(|other| include_tests || !is_test_file_for_affected(&other.file_path))
+        .take(10)
+        .collect::<Vec<_>>();
+
     // Build display
-    let display = format_affected_code(root, &affected, affected_files);
+    let mut display = format_affected_code(root, &affected, affected_files);
+    if !co_changed.is_empty() {
+        display.push_str("\n## Usually Changed Together\n\n");
+        for other in &co_changed {
+            display.push_str(&format!(
+                "- `{}` - {} shared commits\n",
+                other.file_path, other.commit_count
+            ));
+        }
+    }
 
     Ok(json!({
         "symbol_name": root.name,
@@ -1503,6 +1784,7 @@ pub fn handle_find_affected_code(
         "affected_count": affected.len(),
         "affected_files": affected_files,
         "affected": affected,
+        "co_changed_files": co_changed,
         "warning": warning,
         "display": display,
     }))
@@ -1586,6 +1868,864 @@ fn format_affected_code(
     out
 }
 
+/// Handle analyze_diff tool - find the symbols a git diff changes and the code and
+/// tests depending on them. A range whose new side the working tree does not hold is
+/// answered from the snapshot of that side, whose files its lines are numbered in.
+pub async fn handle_analyze_diff(
+    state: &AppState,
+    tool: AnalyzeDiffTool,
+) -> Result<serde_json::Value, anyhow::Error> {
+    let depth = tool.depth.unwrap_or(3) as usize;
+    let limit = tool.limit.unwrap_or(200).max(1) as usize;
+
+    let files = diff_revisions(&state.config, tool.range.as_deref())?;
+    let snapshot = match new_side_revision(&state.config, tool.range.as_deref(), &files)? {
+        Some(revision) => Some(state.snapshots.open(state, &revision).await?),
+        None => None,
+    };
+    let state = snapshot.as_deref().unwrap_or(state);
+    let sqlite = &state.sqlite;
+
+    let mut changed: Vec<SymbolRow> = Vec::new();
+    let mut file_list = Vec::new();
+    for file in &files {
+        let symbols = sqlite.list_symbols_by_file(&file.path)?;
+        let touched: Vec<&SymbolRow> = if file.status == "deleted" {
+            // Still indexed until the next refresh
+            symbols.iter().filter(|s| s.kind != "file").collect()
+        } else {
+            let content = match sqlite.get_file_content(&file.path)? {
+                Some(content) => Some(content),
+                None => std::fs::read_to_string(state.config.base_dir.join(&file.path)).ok(),
+            };
+            content
+                .map(|content| touched_symbols(&symbols, &content, &file.hunks))
+                .unwrap_or_default()
+        };
+        file_list.push(json!({
+            "path": file.path,
+            "old_path": file.old_path,
+            "status": file.status,
+            "additions": file.additions,
+            "deletions": file.deletions,
+            "changed_symbols": touched.len(),
+        }));
+        changed.extend(touched.into_iter().cloned());
+    }
+
+    let affected = trace_callers(sqlite, &changed, depth, limit)?;
+
+    let symbol_json = |s: &SymbolRow| {
+        json!({
+            "symbol_id": s.id,
+            "symbol_name": s.name,
+            "kind": s.kind,
+            "file_path": s.file_path,
+            "exported": s.exported,
+            "line_range": [s.start_line, s.end_line],
+        })
+    };
+    let changed_symbols: Vec<serde_json::Value> = changed.iter().map(symbol_json).collect();
+    let affected_list: Vec<serde_json::Value> = affected
+        .iter()
+        .map(|a| {
+            let mut value = symbol_json(&a.symbol);
+            value["depth"] = json!(a.depth);
+            value["via"] = json!(a.via);
+            value["edge_type"] = json!(a.edge_type);
+            value
+        })
+        .collect();
+    let affected_exports: Vec<serde_json::Value> = changed
+        .iter()
+        .map(|s| (s, 0))
+        .chain(affected.iter().map(|a| (&a.symbol, a.depth)))
+        .filter(|(s, _)| s.exported)
+        .map(|(s, depth)| {
+            json!({
+                "symbol_id": s.id,
+                "symbol_name": s.name,
+                "kind": s.kind,
+                "file_path": s.file_path,
+                "depth": depth,
+            })
+        })
+        .collect();
+
+    // Tests linked to every file holding a changed or affected symbol, and tests
+    // the diff or the callers reach directly
+    let mut tests: std::collections::BTreeMap<String, std::collections::BTreeSet<String>> =
+        std::collections::BTreeMap::new();
+    let mut sources: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    sources.extend(affected.iter().map(|a| a.symbol.file_path.as_str()));
+    let mut seen = std::collections::HashSet::new();
+    for source in sources {
+        if !seen.insert(source) {
+            continue;
+        }
+        if sqlite.is_test_file(source) {
+            tests
+                .entry(source.to_string())
+                .or_default()
+                .insert(source.to_string());
+            continue;
+        }
+        for test in sqlite.get_tests_for_source(source)? {
+            tests.entry(test).or_default().insert(source.to_string());
+        }
+    }
+    let tests: Vec<serde_json::Value> = tests
+        .into_iter()
+        .map(|(test_file, sources)| json!({ "test_file": test_file, "reached_from": sources }))
+        .collect();
+
+    let display = format_diff_analysis(
+        tool.range.as_deref(),
+        &files,
+        &changed,
+        &affected,
+        &affected_exports,
+        &tests,
+    );
+
+    Ok(json!({
+        "range": tool.range,
+        "depth": depth,
+        "files": file_list,
+        "changed_symbols": changed_symbols,
+        "affected_count": affected_list.len(),
+        "affected": affected_list,
+        "affected_exports": affected_exports,
+        "tests": tests,
+        "display": display,
+    }))
+}
+
+/// Format diff analysis results as markdown
+fn format_diff_analysis(
+    range: Option<&str>,
+    files: &[ChangedFile],
+    changed: &[SymbolRow],
+    affected: &[ImpactedSymbol],
+    affected_exports: &[serde_json::Value],
+    tests: &[serde_json::Value],
+) -> String {
+    let mut out = format!(
+        "# Diff Impact: {}\n\n",
+        range.unwrap_or("HEAD..working tree")
+    );
+    out.push_str(&format!(
+        "**Changed:** {} symbols in {} files\n",
+        changed.len(),
+        files.len()
+    ));
+    out.push_str(&format!(
+        "**Affected:** {} symbols, {} exports, {} test files\n\n",
+        affected.len(),
+        affected_exports.len(),
+        tests.len()
+    ));
+
+    if files.is_empty() {
+        out.push_str("*No changes found*\n");
+        return out;
+    }
+
+    out.push_str("## Changed Files\n\n");
+    for file in files.iter().take(30) {
+        let path = match &file.old_path {
+            Some(old) => format!("{} -> {}", old, file.path),
+            None => file.path.clone(),
+        };
+        out.push_str(&format!(
+            "- `{}` ({}, +{} -{})\n",
+            path, file.status, file.additions, file.deletions
+        ));
+    }
+    if files.len() > 30 {
+        out.push_str(&format!("*... and {} more*\n", files.len() - 30));
+    }
+
+    if !changed.is_empty() {
+        out.push_str("\n## Changed Symbols\n\n");
+        for s in changed.iter().take(30) {
+            out.push_str(&format!(
+                "- **{}** ({}) - `{}`\n",
+                s.name, s.kind, s.file_path
+            ));
+        }
+        if changed.len() > 30 {
+            out.push_str(&format!("*... and {} more*\n", changed.len() - 30));
+        }
+    }
+
+    if !affected.is_empty() {
+        out.push_str("\n## Affected Callers\n\n");
+        for a in affected.iter().take(30) {
+            out.push_str(&format!(
+                "- **{}** ({}) - `{}` (depth {})\n",
+                a.symbol.name, a.symbol.kind, a.symbol.file_path, a.depth
+            ));
+        }
+        if affected.len() > 30 {
+            out.push_str(&format!("*... and {} more*\n", affected.len() - 30));
+        }
+    }
+
+    if !affected_exports.is_empty() {
+        out.push_str("\n## [!] Affected Exports\n\n");
+        for e in affected_exports.iter().take(20) {
+            let name = e.get("symbol_name").and_then(|v| v.as_str()).unwrap_or("?");
+            let file = e.get("file_path").and_then(|v| v.as_str()).unwrap_or("");
+            out.push_str(&format!("- **{}** - `{}`\n", name, file));
+        }
+    }
+
+    if !tests.is_empty() {
+        out.push_str("\n## Tests to Run\n\n");
+        for t in tests {
+            let file = t.get("test_file").and_then(|v| v.as_str()).unwrap_or("");
+            out.push_str(&format!("- `{}`\n", file));
+        }
+    }
+
+    out
+}
+
+/// Handle compare_revisions tool
+pub async fn handle_compare_revisions(
+    state: &AppState,
+    tool: CompareRevisionsTool,
+) -> Result<serde_json::Value, anyhow::Error> {
+    let limit = tool.limit.unwrap_or(50).max(1) as usize;
+    let base = state.snapshots.open(state, &tool.base).await?;
+    let head = match tool.head.as_deref() {
+        Some(head) => Some(state.snapshots.open(state, head).await?),
+        None => None,
+    };
+    let head_state = head.as_deref().unwrap_or(state);
+    let comparison = compare_indexes(&base.sqlite, &head_state.sqlite)?;
+
+    let in_scope = |file: &str| tool.path.as_deref().is_none_or(|path| file.contains(path));
+    let symbol_json = |s: &SymbolRow| {
+        json!({
+            "symbol_name": s.name,
+            "qualified_name": s.qualified_name,
+            "kind": s.kind,
+            "file_path": s.file_path,
+            "exported": s.exported,
+            "line_range": [s.start_line, s.end_line],
+            "signature": declaration_signature(s),
+        })
+    };
+    let added: Vec<serde_json::Value> = comparison
+        .added
+        .iter()
+        .filter(|s| in_scope(&s.file_path))
+        .map(symbol_json)
+        .collect();
+    let removed: Vec<serde_json::Value> = comparison
+        .removed
+        .iter()
+        .filter(|s| in_scope(&s.file_path))
+        .map(symbol_json)
+        .collect();
+    let renamed: Vec<serde_json::Value> = comparison
+        .renamed
+        .iter()
+        .filter(|(old, new)| in_scope(&old.file_path) || in_scope(&new.file_path))
+        .map(|(old, new)| {
+            json!({
+                "old_name": old.qualified_name,
+                "new_name": new.qualified_name,
+                "kind": new.kind,
+                "old_file_path": old.file_path,
+                "file_path": new.file_path,
+                "exported": new.exported,
+            })
+        })
+        .collect();
+    let signature_changes: Vec<serde_json::Value> = comparison
+        .signature_changes
+        .iter()
+        .filter(|c| in_scope(&c.new.file_path))
+        .map(|c| {
+            json!({
+                "symbol_name": c.new.qualified_name,
+                "kind": c.new.kind,
+                "file_path": c.new.file_path,
+                "exported": c.new.exported,
+                "old_signature": c.old_signature,
+                "new_signature": c.new_signature,
+            })
+        })
+        .collect();
+    let export_changes: Vec<serde_json::Value> = comparison
+        .export_changes
+        .iter()
+        .filter(|(_, new)| in_scope(&new.file_path))
+        .map(|(_, new)| {
+            json!({
+                "symbol_name": new.qualified_name,
+                "kind": new.kind,
+                "file_path": new.file_path,
+                "exported": new.exported,
+            })
+        })
+        .collect();
+    let edge_json = |edges: &[EdgeChange]| -> Vec<serde_json::Value> {
+        edges
+            .iter()
+            .filter(|e| in_scope(&e.from_file) || in_scope(&e.to_file))
+            .map(|e| {
+                json!({
+                    "from": e.from,
+                    "from_file": e.from_file,
+                    "to": e.to,
+                    "to_file": e.to_file,
+                    "edge_type": e.edge_type,
+                    "reason": e.reason,
+                })
+            })
+            .collect()
+    };
+    let new_edges = edge_json(&comparison.new_edges);
+    let broken_edges = edge_json(&comparison.broken_edges);
+    let moved_files: Vec<serde_json::Value> = comparison
+        .moved_files
+        .iter()
+        .filter(|m| in_scope(&m.old_path) || in_scope(&m.new_path))
+        .map(|m| {
+            json!({
+                "old_path": m.old_path,
+                "new_path": m.new_path,
+                "identical": m.identical,
+            })
+        })
+        .collect();
+
+    let head_name = tool.head.as_deref().unwrap_or("working tree");
+    let summary = json!({
+        "added": added.len(),
+        "removed": removed.len(),
+        "renamed": renamed.len(),
+        "signature_changes": signature_changes.len(),
+        "export_changes": export_changes.len(),
+        "new_edges": new_edges.len(),
+        "broken_edges": broken_edges.len(),
+        "moved_files": moved_files.len(),
+    });
+    let display = format_revision_comparison(
+        &tool.base,
+        head_name,
+        &[
+            ("Added", &added),
+            ("Removed", &removed),
+            ("Renamed", &renamed),
+            ("Signature Changes", &signature_changes),
+            ("Export Changes", &export_changes),
+            ("New Edges", &new_edges),
+            ("Broken Edges", &broken_edges),
+            ("Moved Files", &moved_files),
+        ],
+        limit,
+    );
+    let take = |items: Vec<serde_json::Value>| items.into_iter().take(limit).collect::<Vec<_>>();
+
+    Ok(json!({
+        "base": tool.base,
+        "head": head_name,
+        "summary": summary,
+        "added": take(added),
+        "removed": take(removed),
+        "renamed": take(renamed),
+        "signature_changes": take(signature_changes),
+        "export_changes": take(export_changes),
+        "new_edges": take(new_edges),
+        "broken_edges": take(broken_edges),
+        "moved_files": take(moved_files),
+        "display": display,
+    }))
+}
+
+/// Format a revision comparison as markdown
+fn format_revision_comparison(
+    base: &str,
+    head: &str,
+    sections: &[(&str, &Vec<serde_json::Value>)],
+    limit: usize,
+) -> String {
+    let mut out = format!("# Changes: {} -> {}\n\n", base, head);
+    if sections.iter().all(|(_, items)| items.is_empty()) {
+        out.push_str("*No structural changes found*\n");
+        return out;
+    }
+    let text = |value: &serde_json::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    for (title, items) in sections {
+        if items.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("## {} ({})\n\n", title, items.len()));
+        for item in items.iter().take(limit) {
+            let line = match *title {
+                "Renamed" => format!(
+                    "- **{}** -> **{}** ({}) - `{}`",
+                    text(item, "old_name"),
+                    text(item, "new_name"),
+                    text(item, "kind"),
+                    text(item, "file_path")
+                ),
+                "Signature Changes" => format!(
+                    "- **{}** - `{}`\n  - old: `{}`\n  - new: `{}`",
+                    text(item, "symbol_name"),
+                    text(item, "file_path"),
+                    text(item, "old_signature"),
+                    text(item, "new_signature")
+                ),
+                "Export Changes" => format!(
+                    "- **{}** ({}) - `{}`: {}",
+                    text(item, "symbol_name"),
+                    text(item, "kind"),
+                    text(item, "file_path"),
+                    if item["exported"] == true {
+                        "now exported"
+                    } else {
+                        "no longer exported"
+                    }
+                ),
+                "New Edges" | "Broken Edges" => format!(
+                    "- {} -[{}]-> {} ({})",
+                    text(item, "from"),
+                    text(item, "edge_type"),
+                    text(item, "to"),
+                    text(item, "reason").replace('_', " ")
+                ),
+                "Moved Files" => format!(
+                    "- `{}` -> `{}`{}",
+                    text(item, "old_path"),
+                    text(item, "new_path"),
+                    if item["identical"] == true {
+                        ""
+                    } else {
+                        " (modified)"
+                    }
+                ),
+                _ => format!(
+                    "- **{}** ({}) - `{}`",
+                    text(item, "qualified_name"),
+                    text(item, "kind"),
+                    text(item, "file_path")
+                ),
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+        if items.len() > limit {
+            out.push_str(&format!("*... and {} more*\n", items.len() - limit));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Handle check_api_surface tool
+pub async fn handle_check_api_surface(
+    state: &AppState,
+    tool: CheckApiSurfaceTool,
+) -> Result<serde_json::Value, anyhow::Error> {
+    if tool.base.is_some() && tool.baseline.is_some() {
+        return Err(anyhow::anyhow!(
+            "Pass either a base revision or a baseline directory, not both"
+        ));
+    }
+    let limit = tool.limit.unwrap_or(100).max(1) as usize;
+    let root_name = state.config.base_dir.file_name().unwrap_or("root");
+    let in_scope = |surface: &mut ApiSurface| {
+        if let Some(package) = &tool.package {
+            surface.packages.retain(|p| &p.name == package);
+        }
+    };
+
+    let head = match tool.head.as_deref() {
+        Some(head) => Some(state.snapshots.open(state, head).await?),
+        None => None,
+    };
+    let head_state = head.as_deref().unwrap_or(state);
+    let mut surface = api_surface(&head_state.sqlite, root_name)?;
+    in_scope(&mut surface);
+    let (against, mut baseline) = match (&tool.base, &tool.baseline) {
+        (Some(base), _) => {
+            let base_state = state.snapshots.open(state, base).await?;
+            (
+                Some(base.clone()),
+                Some(api_surface(&base_state.sqlite, root_name)?),
+            )
+        }
+        (_, Some(dir)) => {
+            let dir = state.config.base_dir.join(dir);
+            (
+                Some(dir.to_string()),
+                Some(read_surface(dir.as_std_path())?),
+            )
+        }
+        _ => (None, None),
+    };
+    if let Some(baseline) = &mut baseline {
+        in_scope(baseline);
+    }
+    if let Some(package) = &tool.package {
+        let known = |surface: &ApiSurface| surface.packages.iter().any(|p| &p.name == package);
+        if !known(&surface) && !baseline.as_ref().is_some_and(known) {
+            return Err(anyhow::anyhow!("Unknown package: {package}"));
+        }
+    }
+
+    let written: Vec<String> = match &tool.write_to {
+        Some(dir) => write_surface(
+            state.config.base_dir.join(dir).as_std_path(),
+            &surface,
+            tool.package.is_none(),
+        )?
+        .into_iter()
+        .map(|path| path.display().to_string())
+        .collect(),
+        None => Vec::new(),
+    };
+    let changes = baseline
+        .as_ref()
+        .map(|baseline| diff_surfaces(baseline, &surface))
+        .unwrap_or_default();
+    let breaking = changes.iter().filter(|c| c.breaking).count();
+
+    let head_name = tool.head.as_deref().unwrap_or("working tree");
+    let packages: Vec<serde_json::Value> = surface
+        .packages
+        .iter()
+        .map(|p| {
+            json!({
+                "name": p.name,
+                "path": p.path,
+                "package_type": p.package_type,
+                "symbols": p.symbols.len(),
+            })
+        })
+        .collect();
+    let display = format_api_surface(&surface, against.as_deref(), head_name, &changes, limit);
+    let mut result = json!({
+        "head": head_name,
+        "against": against,
+        "packages": packages,
+        "summary": {
+            "breaking": breaking,
+            "non_breaking": changes.len() - breaking,
+        },
+        "changes": changes.iter().take(limit).collect::<Vec<_>>(),
+        "written": written,
+        "display": display,
+    });
+    // The surface itself, unless it went to files or only the changes were asked for
+    if against.is_none() && tool.write_to.is_none() {
+        result["surface"] = serde_json::to_value(&surface)?;
+    }
+    Ok(result)
+}
+
+/// Format an API surface, and its changes when checked against a baseline, as markdown
+fn format_api_surface(
+    surface: &ApiSurface,
+    against: Option<&str>,
+    head: &str,
+    changes: &[ApiChange],
+    limit: usize,
+) -> String {
+    let mut out = match against {
+        Some(against) => format!("# API Changes: {} -> {}\n\n", against, head),
+        None => format!("# API Surface: {}\n\n", head),
+    };
+    for package in &surface.packages {
+        let path = if package.path.is_empty() {
+            "."
+        } else {
+            package.path.as_str()
+        };
+        out.push_str(&format!(
+            "- **{}** (`{}`) - {} exported symbols\n",
+            package.name,
+            path,
+            package.symbols.len()
+        ));
+    }
+    out.push('\n');
+
+    if against.is_none() {
+        for package in &surface.packages {
+            out.push_str(&format!("## {}\n\n", package.name));
+            for symbol in package.symbols.iter().take(limit) {
+                out.push_str(&format!("- `{}` ({})\n", symbol.signature, symbol.kind));
+            }
+            if package.symbols.len() > limit {
+                out.push_str(&format!(
+                    "*... and {} more*\n",
+                    package.symbols.len() - limit
+                ));
+            }
+            out.push('\n');
+        }
+        return out;
+    }
+    if changes.is_empty() {
+        out.push_str("*No API changes found*\n");
+        return out;
+    }
+    for (title, breaking) in [("Breaking Changes", true), ("Non-breaking Changes", false)] {
+        let items: Vec<&ApiChange> = changes.iter().filter(|c| c.breaking == breaking).collect();
+        if items.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("## {} ({})\n\n", title, items.len()));
+        for change in items.iter().take(limit) {
+            if change.symbol.is_empty() {
+                out.push_str(&format!(
+                    "- **{}**: {} ({})\n",
+                    change.package,
+                    change.change.replace('_', " "),
+                    change.detail
+                ));
+            } else {
+                out.push_str(&format!(
+                    "- **{}** `{}` ({}): {}\n",
+                    change.package, change.symbol, change.kind, change.detail
+                ));
+            }
+        }
+        if items.len() > limit {
+            out.push_str(&format!("*... and {} more*\n", items.len() - limit));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Handle get_change_history tool
+pub fn handle_get_change_history(
+    state: &AppState,
+    tool: GetChangeHistoryTool,
+) -> Result<serde_json::Value, anyhow::Error> {
+    let limit = tool.limit.unwrap_or(10).max(1) as usize;
+    let sqlite = &state.sqlite;
+
+    let (file_path, symbol) = match (&tool.symbol_name, &tool.file_path) {
+        (Some(name), file_path) => {
+            let roots = sqlite.search_symbols_by_exact_name(name, file_path.as_deref(), 1)?;
+            let Some(root) = roots.into_iter().next() else {
+                return Ok(json!({
+                    "symbol_name": name,
+                    "error": "SYMBOL_NOT_FOUND",
+                    "message": format!("Symbol '{}' not found", name),
+                    "commits": [],
+                }));
+            };
+            (root.file_path.clone(), Some(root))
+        }
+        (None, Some(file_path)) => (file_path.clone(), None),
+        (None, None) => {
+            return Ok(json!({
+                "error": "INVALID_INPUT",
+                "message": "Either file_path or symbol_name must be provided",
+                "commits": [],
+            }));
+        }
+    };
+    let target = match &symbol {
+        Some(symbol) => HistoryTarget::Symbol(&symbol.id),
+        None => HistoryTarget::File(&file_path),
+    };
+
+    let stats = sqlite.get_change_stats(target)?;
+    let authors = sqlite.list_authors(target, limit)?;
+    let commits = sqlite.list_commits(target, limit)?;
+    let (co_changed, hot_symbols) = if symbol.is_none() {
+        let mut hot_symbols = Vec::new();
+        for (symbol_id, commit_count, last_commit_at) in
+            sqlite.list_symbols_by_churn(&file_path, limit)?
+        {
+            if let Some(row) = sqlite.get_symbol_by_id(&symbol_id)? {
+                hot_symbols.push(json!({
+                    "symbol_id": symbol_id,
+                    "symbol_name": row.name,
+                    "kind": row.kind,
+                    "commit_count": commit_count,
+                    "last_commit_at": last_commit_at,
+                }));
+            }
+        }
+        let co_changed = sqlite.list_co_changed_files(&file_path, MAX_CO_CHANGE_FILES, limit)?;
+        (co_changed, hot_symbols)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    let display = format_change_history(
+        &file_path,
+        symbol.as_ref(),
+        stats.as_ref(),
+        &authors,
+        &commits,
+        &co_changed,
+        &hot_symbols,
+    );
+
+    Ok(json!({
+        "file_path": file_path,
+        "symbol_name": symbol.as_ref().map(|s| s.name.as_str()),
+        "symbol_kind": symbol.as_ref().map(|s| s.kind.as_str()),
+        "stats": stats,
+        "authors": authors,
+        "commits": commits,
+        "co_changed": co_changed,
+        "hot_symbols": hot_symbols,
+        "display": display,
+    }))
+}
+
+/// Format the history of a file or symbol as markdown
+fn format_change_history(
+    file_path: &str,
+    symbol: Option<&SymbolRow>,
+    stats: Option<&ChangeStatsRow>,
+    authors: &[AuthorStatsRow],
+    commits: &[GitCommitRow],
+    co_changed: &[CoChangeRow],
+    hot_symbols: &[serde_json::Value],
+) -> String {
+    let mut out = match symbol {
+        Some(symbol) => format!(
+            "# Change History: {}\n\n**Kind:** {}\n**File:** `{}`\n\n",
+            symbol.name, symbol.kind, file_path
+        ),
+        None => format!("# Change History: `{}`\n\n", file_path),
+    };
+
+    let Some(stats) = stats else {
+        out.push_str("*No commits found. Is the code in a git repository, and committed?*\n");
+        return out;
+    };
+    out.push_str(&format!(
+        "**Commits:** {} (first {}, last {})\n",
+        stats.commit_count,
+        format_commit_date(stats.first_commit_at),
+        format_commit_date(stats.last_commit_at)
+    ));
+    if let (Some(added), Some(deleted)) = (stats.lines_added, stats.lines_deleted) {
+        out.push_str(&format!("**Lines:** +{} / -{}\n", added, deleted));
+    }
+    out.push('\n');
+
+    if !authors.is_empty() {
+        out.push_str("## Authors\n\n");
+        for author in authors {
+            out.push_str(&format!(
+                "- **{}** - {} commits, last {}\n",
+                author.author,
+                author.commit_count,
+                format_commit_date(author.last_commit_at)
+            ));
+        }
+        out.push('\n');
+    }
+
+    if !commits.is_empty() {
+        out.push_str("## Recent Commits\n\n");
+        for commit in commits {
+            out.push_str(&format!(
+                "- `{}` {} {} - {}\n",
+                &commit.id[..commit.id.len().min(8)],
+                format_commit_date(commit.committed_at),
+                commit.author,
+                commit.summary
+            ));
+        }
+        out.push('\n');
+    }
+
+    if !hot_symbols.is_empty() {
+        out.push_str("## Most Changed Symbols\n\n");
+        for hot in hot_symbols {
+            let name = hot
+                .get("symbol_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("?");
+            let kind = hot.get("kind").and_then(|v| v.as_str()).unwrap_or("");
+            let count = hot
+                .get("commit_count")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            out.push_str(&format!("- **{}** ({}) - {} commits\n", name, kind, count));
+        }
+        out.push('\n');
+    }
+
+    if !co_changed.is_empty() {
+        out.push_str("## Usually Changed Together\n\n");
+        for other in co_changed {
+            out.push_str(&format!(
+                "- `{}` - {} shared commits\n",
+                other.file_path, other.commit_count
+            ));
+        }
+    }
+    out
+}
+
+/// A commit time as `YYYY-MM-DD` (UTC)
+fn format_commit_date(seconds: i64) -> String {
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let days = seconds.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Handle search_content tool
+pub fn handle_search_content(
+    state: &AppState,
+    tool: SearchContentTool,
+) -> Result<serde_json::Value, anyhow::Error> {
+    let limit = tool.limit.unwrap_or(100).max(1) as usize;
+
+    let result = search_content(
+        &state.sqlite,
+        &ContentSearch {
+            pattern: &tool.pattern,
+            literal: tool.literal.unwrap_or(false),
+            case_insensitive: tool.case_insensitive.unwrap_or(false),
+            path: tool.path.as_deref(),
+            limit,
+        },
+    )?;
+
+    Ok(json!({
+        "pattern": tool.pattern,
+        "count": result.matches.len(),
+        "truncated": result.truncated,
+        "candidate_files": result.candidate_files,
+        "matches": result.matches,
+    }))
+}
+
 /// Handle search_todos tool
 pub fn handle_search_todos(
     state: &AppState,
@@ -1855,6 +2995,7 @@ pub fn handle_search_framework_patterns(
             "path": pattern.path,
             "name": pattern.name,
             "handler": pattern.handler,
+            "symbol_id": pattern.symbol_id,
             "arguments": pattern.arguments,
             "parent_chain": pattern.parent_chain,
         }));
@@ -1989,6 +3130,8 @@ mod tests {
                 start_line: 1,
                 end_line: 2,
                 text: "export function exportedFunc() {}".to_string(),
+                qualified_name: "exportedFunc".to_string(),
+                parent_id: None,
             },
             SymbolRow {
                 id: "2".to_string(),
@@ -2002,6 +3145,8 @@ mod tests {
                 start_line: 2,
                 end_line: 3,
                 text: "export function anotherExportedFunc() {}".to_string(),
+                qualified_name: "anotherExportedFunc".to_string(),
+                parent_id: None,
             },
         ];
         let purpose = infer_file_purpose_for_summary(&symbols);
@@ -2023,6 +3168,8 @@ mod tests {
             start_line: 1,
             end_line: 2,
             text: "function internalFunc() {}".to_string(),
+            qualified_name: "internalFunc".to_string(),
+            parent_id: None,
         }];
         let purpose = infer_file_purpose_for_summary(&symbols);
         assert!(purpose.contains("internal"));
@@ -2042,9 +3189,19 @@ mod tests {
             start_line: 1,
             end_line: 2,
             text: "export class MyClass {}".to_string(),
+            qualified_name: "MyClass".to_string(),
+            parent_id: None,
         }];
         let purpose = infer_file_purpose_for_summary(&symbols);
         assert!(purpose.contains("module"));
         assert!(purpose.contains("classes"));
     }
+
+    #[test]
+    fn test_format_commit_date() {
+        assert_eq!(format_commit_date(0), "1970-01-01");
+        assert_eq!(format_commit_date(951_782_400), "2000-02-29");
+        assert_eq!(format_commit_date(1_700_000_000), "2023-11-14");
+        assert_eq!(format_commit_date(-86_400), "1969-12-31");
+    }
 }
@@ -0,0 +1,392 @@
+//! Indexes of past revisions.
+//!
+//! A tool asked about a revision answers from a snapshot: an index built like the
+//! main one, over the files the revision holds. They are read from the object
+//! database into the snapshot's own directory, so the working tree is never touched,
+//! and are hard links into a blob store every snapshot shares, keyed by blob id.
+//! Snapshots are kept by revision name; when a branch has moved on, only the files
+//! whose blob changed are linked and indexed again, and blobs no snapshot holds any
+//! more are removed from the store. Symbol texts the main index or another snapshot
+//! has embedded come from the embedding cache they share.
+//!
+//! Each revision is built under its own lock, so tools asking about different
+//! revisions don't wait for each other. Only the most recently used snapshots are
+//! kept open; the others stay on disk and are opened again when asked for.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex as SyncMutex, PoisonError};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use super::AppState;
+use crate::config::Config;
+use crate::git::tree::{blob_path, remove_blobs, resolve_revision, revision_files, store_blobs};
+use crate::indexer::package::detector::is_manifest_filename;
+use crate::indexer::pipeline::scan::{should_index_file, should_skip_dir};
+use crate::indexer::pipeline::utils::unix_now_s;
+use crate::indexer::pipeline::IndexPipeline;
+use crate::metrics::MetricsRegistry;
+use crate::path::{Utf8Path, Utf8PathBuf};
+use crate::retrieval::Retriever;
+use crate::storage::sqlite::SqliteStore;
+use crate::storage::tantivy::TantivyIndex;
+use crate::storage::vector::LanceDbStore;
+
+/// Record of what a snapshot holds, kept in its directory.
+const MANIFEST_FILE: &str = "snapshot.json";
+
+/// Most snapshots kept open at once.
+const MAX_OPEN_SNAPSHOTS: usize = 4;
+
+/// How long a blob no snapshot holds is kept, in case a snapshot being updated is
+/// about to record it.
+const BLOB_GRACE: Duration = Duration::from_secs(10 * 60);
+
+/// Open snapshots by revision name, the least recently used first.
+#[derive(Default)]
+pub struct RevisionSnapshots {
+    open: SyncMutex<Vec<(String, Arc<Slot>)>>,
+}
+
+/// The snapshot of one revision, once built.
+type Slot = Mutex<Option<Snapshot>>;
+
+struct Snapshot {
+    commit: String,
+    state: Arc<AppState>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SnapshotManifest {
+    revision: String,
+    commit: String,
+    base_dir: String,
+    built_at: i64,
+    /// Blob ids by path relative to the base directory
+    files: BTreeMap<String, String>,
+}
+
+impl RevisionSnapshots {
+    /// The state answering for `revision` of the repository holding `main`'s base
+    /// directory, built first or brought up to date with where the revision points.
+    pub async fn open(&self, main: &AppState, revision: &str) -> Result<Arc<AppState>> {
+        let revision = revision.trim();
+        let commit = resolve_revision(&main.config, revision)?;
+        let slot = self.slot(revision);
+        let mut snapshot = slot.lock().await;
+        if let Some(snapshot) = snapshot.as_ref().filter(|s| s.commit == commit) {
+            return Ok(snapshot.state.clone());
+        }
+
+        let dir = main
+            .config
+            .snapshot_dir()
+            .join(snapshot_name(main.config.base_dir.as_str(), revision));
+        let state = match snapshot.take() {
+            Some(snapshot) => snapshot.state,
+            None => Arc::new(snapshot_state(main, &dir).await?),
+        };
+        let manifest = update_snapshot(&main.config, &state, &dir, revision, &commit).await?;
+        tracing::info!(
+            revision = %revision,
+            commit = %commit,
+            files = manifest.files.len(),
+            "Revision snapshot ready"
+        );
+        *snapshot = Some(Snapshot {
+            commit,
+            state: state.clone(),
+        });
+        Ok(state)
+    }
+
+    /// The slot of `revision`, made the most recently used. The least recently used
+    /// snapshots past `MAX_OPEN_SNAPSHOTS` are closed, save those still being built
+    /// or answering a tool, since a second state over the same directory can't take
+    /// the locks of its indexes.
+    fn slot(&self, revision: &str) -> Arc<Slot> {
+        let mut open = self.open.lock().unwrap_or_else(PoisonError::into_inner);
+        let slot = match open.iter().position(|(name, _)| name == revision) {
+            Some(index) => open.remove(index).1,
+            None => Arc::default(),
+        };
+        open.push((revision.to_string(), slot.clone()));
+
+        let mut index = 0;
+        while open.len() > MAX_OPEN_SNAPSHOTS && index + 1 < open.len() {
+            let idle = Arc::strong_count(&open[index].1) == 1
+                && open[index].1.try_lock().is_ok_and(|snapshot| {
+                    snapshot
+                        .as_ref()
+                        .is_none_or(|s| Arc::strong_count(&s.state) == 1)
+                });
+            if idle {
+                let (name, _) = open.remove(index);
+                tracing::debug!(revision = %name, "Closed revision snapshot");
+            } else {
+                index += 1;
+            }
+        }
+        slot
+    }
+}
+
+/// Name of the directory of the snapshot of `revision` of the code in `base_dir`:
+/// the revision made safe for a path, with a hash telling apart revisions differing
+/// only in the characters replaced, and those of different repositories.
+fn snapshot_name(base_dir: &str, revision: &str) -> String {
+    let name: String = revision
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let mut hasher = Sha256::new();
+    hasher.update(base_dir);
+    hasher.update([0]);
+    hasher.update(revision);
+    let hash = format!("{:x}", hasher.finalize());
+    format!("{}-{}", name.trim_matches('.'), &hash[..12])
+}
+
+/// A state indexing the tree of the snapshot in `dir`, sharing the embedder and
+/// embedding cache of `main`.
+async fn snapshot_state(main: &AppState, dir: &Utf8PathBuf) -> Result<AppState> {
+    let tree = dir.join("tree");
+    fs::create_dir_all(&tree)
+        .with_context(|| format!("Failed to create snapshot directory: {tree}"))?;
+
+    let mut config = (*main.config).clone();
+    config.base_dir = tree.clone();
+    config.repo_roots = vec![tree];
+    config.db_path = dir.join("index.db");
+    config.vector_db_path = dir.join("vectors");
+    config.tantivy_index_path = dir.join("tantivy-index");
+    config.watch_mode = false;
+    // The history of a revision is that of the main index, up to it
+    config.history_max_commits = 0;
+    let config = Arc::new(config);
+
+    let sqlite = SqliteStore::open(&config.db_path)?;
+    sqlite.init()?;
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path)?);
+    let embedder = main.indexer.embedder().clone();
+    let vector_dim = embedder.lock().await.dim();
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await?;
+    lancedb.migrate_vector_table("symbols", vector_dim).await?;
+    let vectors = Arc::new(lancedb.open_or_create_table("symbols", vector_dim).await?);
+    let metrics = Arc::new(MetricsRegistry::new()?);
+
+    let indexer = IndexPipeline::new(
+        config.clone(),
+        tantivy.clone(),
+        vectors.clone(),
+        embedder.clone(),
+        metrics.clone(),
+    )
+    .with_embedding_cache(main.indexer.embedding_cache().clone());
+    let retriever = Retriever::new(
+        config.clone(),
+        tantivy,
+        vectors,
+        embedder,
+        None,
+        None,
+        metrics,
+    );
+    Ok(AppState {
+        config,
+        indexer,
+        retriever,
+        sqlite: Arc::new(sqlite),
+        snapshots: Default::default(),
+    })
+}
+
+/// Bring the tree of the snapshot in `dir` to `commit` and index what changed.
+async fn update_snapshot(
+    main: &Config,
+    state: &AppState,
+    dir: &Utf8PathBuf,
+    revision: &str,
+    commit: &str,
+) -> Result<SnapshotManifest> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let previous = read_manifest(&manifest_path);
+    let tree = state.config.base_dir.as_std_path();
+
+    let files = revision_files(
+        main,
+        commit,
+        |name| should_skip_dir(main, Path::new(name)),
+        |path| {
+            let is_manifest = Path::new(path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_manifest_filename);
+            is_manifest || should_index_file(main, &tree.join(path))
+        },
+    )?;
+    let changed: Vec<(&String, &String)> = files
+        .iter()
+        .filter(|(path, id)| previous.files.get(*path) != Some(id) || !tree.join(path).exists())
+        .collect();
+
+    let store = main.snapshot_dir().join("blobs");
+    store_blobs(
+        main,
+        changed.iter().map(|(_, id)| id.as_str()),
+        store.as_std_path(),
+    )?;
+    for (path, id) in &changed {
+        let target = tree.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        let _ = fs::remove_file(&target);
+        let blob = blob_path(store.as_std_path(), id);
+        fs::hard_link(&blob, &target)
+            .or_else(|_| fs::copy(&blob, &target).map(|_| ()))
+            .with_context(|| format!("Failed to link snapshot file: {}", target.display()))?;
+        // A linked blob carries the time it was stored, which may match the old one's
+        state.sqlite.delete_file_fingerprint(path)?;
+    }
+    for path in previous
+        .files
+        .keys()
+        .filter(|path| !files.contains_key(*path))
+    {
+        let _ = fs::remove_file(tree.join(path));
+    }
+
+    state.indexer.index_all().await?;
+
+    let manifest = SnapshotManifest {
+        revision: revision.to_string(),
+        commit: commit.to_string(),
+        base_dir: main.base_dir.to_string(),
+        built_at: unix_now_s(),
+        files,
+    };
+    fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)
+        .with_context(|| format!("Failed to write snapshot manifest: {manifest_path}"))?;
+
+    let dropped = previous
+        .files
+        .iter()
+        .any(|(path, id)| manifest.files.get(path) != Some(id));
+    if dropped {
+        match remove_unheld_blobs(&main.snapshot_dir(), BLOB_GRACE) {
+            Ok(removed) => tracing::debug!(removed = removed, "Removed snapshot blobs"),
+            Err(err) => tracing::warn!(error = %err, "Failed to remove snapshot blobs"),
+        }
+    }
+    Ok(manifest)
+}
+
+fn read_manifest(path: &Utf8Path) -> SnapshotManifest {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+/// Remove the blobs of the store in `snapshot_dir` that no snapshot there holds,
+/// whichever repository it is of. Returns how many were removed.
+fn remove_unheld_blobs(snapshot_dir: &Utf8Path, grace: Duration) -> Result<usize> {
+    let mut held = HashSet::new();
+    for entry in fs::read_dir(snapshot_dir)
+        .with_context(|| format!("Failed to list snapshots: {snapshot_dir}"))?
+    {
+        let Ok(dir) = Utf8PathBuf::from_path_buf(entry?.path()) else {
+            continue;
+        };
+        held.extend(read_manifest(&dir.join(MANIFEST_FILE)).files.into_values());
+    }
+    remove_blobs(
+        snapshot_dir.join("blobs").as_std_path(),
+        |id| held.contains(id),
+        grace,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_names_tell_revisions_and_repositories_apart() {
+        let release = snapshot_name("/work/shop", "release/1.2");
+        assert!(release.starts_with("release-1.2-"));
+        assert_ne!(release, snapshot_name("/work/shop", "release-1.2"));
+        assert_ne!(release, snapshot_name("/work/blog", "release/1.2"));
+        assert_eq!(release, snapshot_name("/work/shop", "release/1.2"));
+        assert!(snapshot_name("/work/shop", "../HEAD~1").starts_with("-HEAD-1-"));
+    }
+
+    #[test]
+    fn least_recently_used_idle_snapshots_are_closed() {
+        let snapshots = RevisionSnapshots::default();
+        let in_use = snapshots.slot("main");
+        for n in 0..5 {
+            snapshots.slot(&format!("r{n}"));
+        }
+        snapshots.slot("r1");
+        let open: Vec<String> = snapshots
+            .open
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        assert_eq!(open, ["main", "r3", "r4", "r1"]);
+        drop(in_use);
+    }
+
+    #[test]
+    fn blobs_no_snapshot_holds_are_removed() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = Utf8PathBuf::from_path_buf(
+            std::env::temp_dir().join(format!("code-intel-snapshots-test-{nanos}")),
+        )
+        .unwrap();
+        for (name, id) in [("main-1", "aa01"), ("other-repo", "bb01")] {
+            let manifest = SnapshotManifest {
+                files: BTreeMap::from([("src/a.ts".to_string(), id.to_string())]),
+                ..Default::default()
+            };
+            fs::create_dir_all(dir.join(name)).unwrap();
+            fs::write(
+                dir.join(name).join(MANIFEST_FILE),
+                serde_json::to_vec(&manifest).unwrap(),
+            )
+            .unwrap();
+        }
+        let store = dir.join("blobs");
+        for id in ["aa01", "aa02", "bb01"] {
+            let path = blob_path(store.as_std_path(), id);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, id).unwrap();
+        }
+
+        assert_eq!(remove_unheld_blobs(&dir, Duration::ZERO).unwrap(), 1);
+        assert!(blob_path(store.as_std_path(), "aa01").exists());
+        assert!(!blob_path(store.as_std_path(), "aa02").exists());
+        assert!(blob_path(store.as_std_path(), "bb01").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,5 +1,6 @@
 //! Application state
 
+use super::snapshots::RevisionSnapshots;
 use crate::config::Config;
 use crate::indexer::pipeline::IndexPipeline;
 use crate::retrieval::Retriever;
@@ -12,4 +13,6 @@ pub struct AppState {
     pub indexer: IndexPipeline,
     pub retriever: Retriever,
     pub sqlite: Arc<SqliteStore>,
+    /// Indexes of past revisions, built as tools ask about them
+    pub snapshots: Arc<RevisionSnapshots>,
 }
@@ -0,0 +1,434 @@
+//! YAML, TOML and JSON key extraction
+//!
+//! Config files are reduced to their keys: every top-level key becomes a `config_key`
+//! spanning its value, and so does each key directly below it, so that
+//! `services.web` in a compose file or `compilerOptions.strict` in a tsconfig can be
+//! found by name. Deeper keys stay in the text of their parent. None of the formats
+//! is fully parsed; the scanners only track enough (indentation, table headers,
+//! strings, brackets and comments) to find the keys.
+//!
+//! YAML and JSON documents that turn out to be OpenAPI specs also get their
+//! operations, see [`super::openapi`].
+
+use anyhow::Result;
+
+use super::openapi::{extract_openapi_operations, SpecFormat};
+use super::symbol::{ExtractedFile, ExtractedSymbol, LineIndex, SymbolKind};
+
+/// Levels of JSON keys extracted: the top level and its children.
+const MAX_KEY_DEPTH: usize = 2;
+
+pub fn extract_yaml_symbols(source: &str) -> Result<ExtractedFile> {
+    let lines = LineIndex::new(source);
+    let keys = yaml_keys(source);
+    let symbols = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            // A key's value runs up to the next key at the same or a lower indent
+            let next = keys[i + 1..]
+                .iter()
+                .find(|next| next.indent <= key.indent || next.document != key.document);
+            let end = match next {
+                Some(next) if next.document == key.document => next.start,
+                _ => key.document_end,
+            };
+            lines.symbol(source, &key.name, SymbolKind::ConfigKey, key.start, end)
+        })
+        .collect();
+    Ok(with_operations(symbols, source, SpecFormat::Yaml))
+}
+
+pub fn extract_toml_symbols(source: &str) -> Result<ExtractedFile> {
+    Ok(ExtractedFile::with_symbols(toml_keys(source), Vec::new()))
+}
+
+pub fn extract_json_symbols(source: &str) -> Result<ExtractedFile> {
+    let lines = LineIndex::new(source);
+    let symbols = json_keys(source, MAX_KEY_DEPTH)
+        .into_iter()
+        .map(|(mut path, start, end)| {
+            let name = path.pop().unwrap_or_default();
+            lines.symbol(source, name, SymbolKind::ConfigKey, start, end)
+        })
+        .collect();
+    Ok(with_operations(symbols, source, SpecFormat::Json))
+}
+
+fn with_operations(
+    symbols: Vec<ExtractedSymbol>,
+    source: &str,
+    format: SpecFormat,
+) -> ExtractedFile {
+    let mut file = ExtractedFile::with_symbols(symbols, Vec::new());
+    let (operations, routes) = extract_openapi_operations(source, format);
+    file.symbols.extend(operations);
+    file.framework_patterns = routes;
+    file
+}
+
+/// The `(path, start, end)` of every key of a JSON (or JSONC) document down to
+/// `max_depth` levels, in document order.
+pub(super) fn json_keys(source: &str, max_depth: usize) -> Vec<(Vec<String>, usize, usize)> {
+    let mut scanner = JsonScanner {
+        bytes: source.as_bytes(),
+        pos: 0,
+        max_depth,
+        path: Vec::new(),
+        keys: Vec::new(),
+    };
+    scanner.value(0);
+    scanner.keys
+}
+
+struct YamlKey {
+    name: String,
+    indent: usize,
+    start: usize,
+    /// Index of the `---`-separated document holding the key
+    document: usize,
+    document_end: usize,
+}
+
+fn yaml_keys(source: &str) -> Vec<YamlKey> {
+    let mut out: Vec<YamlKey> = Vec::new();
+    let mut document = 0;
+    // Indent of the children of the current top-level key, once seen; deeper keys
+    // are left in the text of their parent
+    let mut child_indent: Option<usize> = None;
+    // Indent below which lines are the content of a block scalar (`key: |`)
+    let mut block_scalar: Option<usize> = None;
+    let mut offset = 0;
+
+    for raw in source.split_inclusive('\n') {
+        let start = offset;
+        offset += raw.len();
+        let line = raw.trim_end();
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(scalar_indent) = block_scalar {
+            if indent > scalar_indent {
+                continue;
+            }
+            block_scalar = None;
+        }
+        if indent == 0 && (trimmed.starts_with("---") || trimmed.starts_with("...")) {
+            for key in out.iter_mut().filter(|k| k.document == document) {
+                key.document_end = start;
+            }
+            document += 1;
+            child_indent = None;
+            continue;
+        }
+
+        // The first line below a top-level key sets the indent of its children; when
+        // that is a sequence entry, the key has none
+        if indent > 0 && child_indent.is_none() {
+            child_indent = Some(indent);
+        }
+
+        let Some((name, value)) = yaml_key(trimmed) else {
+            continue;
+        };
+        if value.starts_with(['|', '>']) {
+            block_scalar = Some(indent);
+        }
+        if indent == 0 {
+            child_indent = None;
+        } else if child_indent != Some(indent) {
+            continue;
+        }
+        out.push(YamlKey {
+            name,
+            indent,
+            start: start + indent,
+            document,
+            document_end: source.len(),
+        });
+    }
+    out
+}
+
+/// The key and the rest of a `key: value` line.
+pub(super) fn yaml_key(line: &str) -> Option<(String, &str)> {
+    if line.starts_with(['-', '[', '{', '!', '&', '*', '?', '%', '@', '|', '>']) {
+        return None;
+    }
+    let (name, rest) = match line.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let close = line[1..].find(quote)? + 1;
+            (&line[1..close], line[close + 1..].trim_start())
+        }
+        _ => {
+            let colon = line
+                .match_indices(':')
+                .map(|(at, _)| at)
+                .find(|&at| line[at + 1..].is_empty() || line[at + 1..].starts_with([' ', '\t']))?;
+            (line[..colon].trim_end(), &line[colon..])
+        }
+    };
+    let value = rest.strip_prefix(':')?;
+    if !(value.is_empty() || value.starts_with([' ', '\t'])) || name.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), value.trim_start()))
+}
+
+fn toml_keys(source: &str) -> Vec<ExtractedSymbol> {
+    let lines = LineIndex::new(source);
+    // Start and name of each table header and key line, with whether it is a header
+    let mut entries: Vec<(usize, String, bool)> = Vec::new();
+    let mut multiline: Option<&str> = None;
+    let mut offset = 0;
+    for raw in source.split_inclusive('\n') {
+        let start = offset;
+        offset += raw.len();
+        let trimmed = raw.trim();
+        if let Some(delimiter) = multiline {
+            if trimmed.contains(delimiter) {
+                multiline = None;
+            }
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let start = start + (raw.len() - raw.trim_start().len());
+        if let Some(header) = trimmed.strip_prefix('[') {
+            let header = header.trim_start_matches('[');
+            if let Some(close) = header.find(']') {
+                entries.push((start, toml_key_name(&header[..close]), true));
+            }
+            continue;
+        }
+        let Some(eq) = trimmed.find('=') else {
+            continue;
+        };
+        let key = trimmed[..eq].trim();
+        let is_key = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_alphanumeric() || "_-.\"' ".contains(c));
+        if !is_key {
+            continue;
+        }
+        let value = trimmed[eq + 1..].trim_start();
+        for delimiter in ["\"\"\"", "'''"] {
+            if value.starts_with(delimiter) && !value[3..].contains(delimiter) {
+                multiline = Some(delimiter);
+            }
+        }
+        entries.push((start, toml_key_name(key), false));
+    }
+
+    // A table runs up to the next header, a key up to the next key or header
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, (start, name, is_header))| {
+            let end = entries[i + 1..]
+                .iter()
+                .find(|(_, _, next_is_header)| *next_is_header || !*is_header)
+                .map_or(source.len(), |(next, _, _)| *next);
+            lines.symbol(source, name.clone(), SymbolKind::ConfigKey, *start, end)
+        })
+        .collect()
+}
+
+/// A (possibly dotted) TOML key with quotes removed: `tool."black"` is `tool.black`.
+fn toml_key_name(key: &str) -> String {
+    key.split('.')
+        .map(|part| part.trim().trim_matches(|c| c == '"' || c == '\''))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Finds the keys of a JSON (or JSONC) document, recording `(path, start, end)` of
+/// each key down to `max_depth`.
+struct JsonScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    max_depth: usize,
+    /// Keys of the objects the scanner is in
+    path: Vec<String>,
+    keys: Vec<(Vec<String>, usize, usize)>,
+}
+
+impl JsonScanner<'_> {
+    /// Skip whitespace and comments, returning the next byte.
+    fn peek(&mut self) -> Option<u8> {
+        loop {
+            match self.bytes.get(self.pos)? {
+                b if b.is_ascii_whitespace() || *b == b',' => self.pos += 1,
+                b'/' if self.bytes.get(self.pos + 1) == Some(&b'/') => {
+                    while self.bytes.get(self.pos).is_some_and(|&b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b'/' if self.bytes.get(self.pos + 1) == Some(&b'*') => {
+                    self.pos += 2;
+                    while self.pos < self.bytes.len() && !self.bytes[self.pos..].starts_with(b"*/")
+                    {
+                        self.pos += 1;
+                    }
+                    self.pos = (self.pos + 2).min(self.bytes.len());
+                }
+                b => return Some(*b),
+            }
+        }
+    }
+
+    /// Skip one value; keys of objects at `depth` < `max_depth` are recorded.
+    fn value(&mut self, depth: usize) {
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                while let Some(b) = self.peek() {
+                    if b == b'}' {
+                        self.pos += 1;
+                        return;
+                    }
+                    let start = self.pos;
+                    let Some(name) = self.string() else {
+                        // Not a key: give up on this object
+                        self.pos += 1;
+                        continue;
+                    };
+                    if self.peek() == Some(b':') {
+                        self.pos += 1;
+                    }
+                    let index = self.keys.len();
+                    let recorded = depth < self.max_depth;
+                    self.path.push(name);
+                    if recorded {
+                        self.keys.push((self.path.clone(), start, start));
+                    }
+                    self.value(depth + 1);
+                    if recorded {
+                        self.keys[index].2 = self.pos;
+                    }
+                    self.path.pop();
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                while let Some(b) = self.peek() {
+                    if b == b']' {
+                        self.pos += 1;
+                        return;
+                    }
+                    // Keys inside arrays are not recorded
+                    self.value(self.max_depth);
+                }
+            }
+            Some(b'"') => {
+                self.string();
+            }
+            Some(b'}' | b']') => self.pos += 1,
+            Some(_) => {
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|b| !b",}] \t\r\n".contains(b))
+                {
+                    self.pos += 1;
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Consume a string literal, returning its (unescaped only for `\"`) contents.
+    fn string(&mut self) -> Option<String> {
+        if self.bytes.get(self.pos) != Some(&b'"') {
+            return None;
+        }
+        let start = self.pos + 1;
+        let mut pos = start;
+        while pos < self.bytes.len() && self.bytes[pos] != b'"' {
+            pos += if self.bytes[pos] == b'\\' { 2 } else { 1 };
+        }
+        let end = pos.min(self.bytes.len());
+        self.pos = (pos + 1).min(self.bytes.len());
+        Some(String::from_utf8_lossy(&self.bytes[start..end]).replace("\\\"", "\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(file: &ExtractedFile) -> Vec<&str> {
+        file.symbols.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    fn text<'a>(source: &'a str, file: &ExtractedFile, name: &str) -> &'a str {
+        let symbol = file.symbols.iter().find(|s| s.name == name).unwrap();
+        &source[symbol.bytes.start..symbol.bytes.end]
+    }
+
+    #[test]
+    fn extracts_yaml_keys_and_their_children() {
+        let source = "# compose\nversion: \"3.8\"\nservices:\n  web:\n    image: nginx\n    ports:\n      - \"80:80\"\n  db:\n    command: |\n      run:\n        now\n\"x-shared\": true\nsteps:\n- run: a\n---\nsecond: 1\n";
+        let file = extract_yaml_symbols(source).unwrap();
+        assert_eq!(
+            names(&file),
+            vec!["version", "services", "web", "db", "x-shared", "steps", "second"]
+        );
+        assert_eq!(
+            text(source, &file, "web"),
+            "web:\n    image: nginx\n    ports:\n      - \"80:80\""
+        );
+        assert!(text(source, &file, "services").ends_with("now"));
+        assert_eq!(text(source, &file, "steps"), "steps:\n- run: a");
+        let db = file.symbols.iter().find(|s| s.name == "db").unwrap();
+        assert_eq!((db.lines.start, db.lines.end), (8, 11));
+    }
+
+    #[test]
+    fn extracts_toml_tables_and_keys() {
+        let source = "name = \"demo\"\n\n[package]\nversion = \"0.1.0\"\ndescription = \"\"\"\nkey = not a key\n\"\"\"\n\n[tool.\"black\"]\nline-length = 100\n\n[[bin]]\npath = \"src/main.rs\"\n";
+        let file = extract_toml_symbols(source).unwrap();
+        assert_eq!(
+            names(&file),
+            vec![
+                "name",
+                "package",
+                "version",
+                "description",
+                "tool.black",
+                "line-length",
+                "bin",
+                "path"
+            ]
+        );
+        assert!(text(source, &file, "package").ends_with("\"\"\""));
+        assert_eq!(text(source, &file, "line-length"), "line-length = 100");
+        let package = file.symbols.iter().find(|s| s.name == "package").unwrap();
+        let version = file.symbols.iter().find(|s| s.name == "version").unwrap();
+        assert!(
+            package.bytes.start < version.bytes.start && version.bytes.end <= package.bytes.end
+        );
+    }
+
+    #[test]
+    fn extracts_json_keys_two_levels_deep() {
+        let source = "{\n  // tsconfig allows comments\n  \"compilerOptions\": {\n    \"strict\": true,\n    \"paths\": { \"@/*\": [\"src/*\"] }\n  },\n  \"include\": [{ \"nested\": 1 }, \"src\"],\n  \"name\": \"a \\\"quoted\\\" }\"\n}\n";
+        let file = extract_json_symbols(source).unwrap();
+        assert_eq!(
+            names(&file),
+            vec!["compilerOptions", "strict", "paths", "include", "name"]
+        );
+        assert_eq!(
+            text(source, &file, "paths"),
+            "\"paths\": { \"@/*\": [\"src/*\"] }"
+        );
+        assert_eq!(
+            text(source, &file, "name"),
+            "\"name\": \"a \\\"quoted\\\" }\""
+        );
+    }
+}
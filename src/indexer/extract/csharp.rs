@@ -0,0 +1,784 @@
+//! C# symbol extraction
+//!
+//! Namespaces, types and their members are read from the tree-sitter syntax tree;
+//! method and property bodies are not descended into, except that the local
+//! functions of a top-level program are its functions. A file-scoped namespace runs
+//! to the end of the file. Attributes become decorator entries and `///` XML doc
+//! comments become docstrings.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tree_sitter::Node;
+
+use super::symbol::{
+    ByteSpan, DecoratorEntry, DecoratorType, ExtractedFile, ExtractedSymbol, Import, JSDocEntry,
+    JSDocParam, LineSpan, SymbolKind,
+};
+use crate::indexer::parser::{parser_for_id, LanguageId};
+
+pub fn extract_csharp_symbols(source: &str) -> Result<ExtractedFile> {
+    let parsed = parse(source)?;
+    Ok(ExtractedFile {
+        symbols: parsed.symbols,
+        imports: parsed.imports,
+        type_edges: Vec::new(),
+        dataflow_edges: Vec::new(),
+        todos: Vec::new(),
+        jsdoc_entries: parsed.docs,
+        decorators: parsed.attributes,
+        framework_patterns: Vec::new(),
+    })
+}
+
+/// `(extends, implements, aliases)` of the type declared at the start of `text`, in
+/// the shape of `parse_type_relations`. C# doesn't mark which base is a class, so a
+/// class's first base counts as its superclass unless it is named like an interface
+/// (`IFoo`); interfaces extend their bases and structs implement theirs.
+pub fn csharp_type_relations(text: &str) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let mut extends = Vec::new();
+    let mut implements = Vec::new();
+    let Ok(parsed) = parse(text) else {
+        return (extends, implements, Vec::new());
+    };
+    for relation in parsed.relations.iter().filter(|r| r.symbol == 0) {
+        match relation.kind {
+            RelationKind::Extends => extends.push(relation.target.clone()),
+            RelationKind::Implements => implements.push(relation.target.clone()),
+        }
+    }
+    (extends, implements, Vec::new())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelationKind {
+    Extends,
+    Implements,
+}
+
+#[derive(Debug, Clone)]
+struct Relation {
+    symbol: usize,
+    kind: RelationKind,
+    target: String,
+}
+
+#[derive(Default)]
+struct ParsedCSharp {
+    symbols: Vec<ExtractedSymbol>,
+    imports: Vec<Import>,
+    relations: Vec<Relation>,
+    docs: Vec<JSDocEntry>,
+    attributes: Vec<DecoratorEntry>,
+}
+
+/// What holds the declarations being read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    File,
+    Namespace,
+    Type(SymbolKind),
+}
+
+fn parse(source: &str) -> Result<ParsedCSharp> {
+    let mut parser = parser_for_id(LanguageId::CSharp)?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| anyhow!("Failed to parse source"))?;
+    let mut out = ParsedCSharp::default();
+    read_declarations(source, tree.root_node(), Container::File, &mut out);
+    Ok(out)
+}
+
+fn read_declarations(source: &str, node: Node, container: Container, out: &mut ParsedCSharp) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        read_declaration(source, child, container, out);
+    }
+}
+
+/// Record what `node` declares, and the declarations in its body.
+fn read_declaration(source: &str, node: Node, container: Container, out: &mut ParsedCSharp) {
+    let in_type = matches!(container, Container::Type(_));
+    let kind = match node.kind() {
+        "using_directive" => {
+            if !in_type {
+                out.imports.extend(read_using(source, node));
+            }
+            return;
+        }
+        // Declarations under `#if`, and those the parser could not make sense of
+        // the surroundings of
+        "preproc_if" | "preproc_elif" | "preproc_else" | "ERROR" => {
+            read_declarations(source, node, container, out);
+            return;
+        }
+        // Statements of a top-level program, of which only local functions declare
+        "global_statement" => {
+            read_declarations(source, node, container, out);
+            return;
+        }
+        "namespace_declaration" | "file_scoped_namespace_declaration" if !in_type => {
+            SymbolKind::Module
+        }
+        "class_declaration" => SymbolKind::Class,
+        "record_declaration" if has_child(node, "struct") => SymbolKind::Struct,
+        "record_declaration" => SymbolKind::Class,
+        "struct_declaration" => SymbolKind::Struct,
+        "interface_declaration" => SymbolKind::Interface,
+        "enum_declaration" => SymbolKind::Enum,
+        "delegate_declaration" => SymbolKind::TypeAlias,
+        "method_declaration" | "constructor_declaration" if in_type => SymbolKind::Function,
+        "local_function_statement" if container == Container::File => SymbolKind::Function,
+        "property_declaration" | "indexer_declaration" if in_type => SymbolKind::Property,
+        "field_declaration" if in_type && has_modifier(source, node, "const") => SymbolKind::Const,
+        _ => return,
+    };
+
+    let name = match node.kind() {
+        "indexer_declaration" => Some("this".to_string()),
+        "field_declaration" => node
+            .named_children(&mut node.walk())
+            .find(|c| c.kind() == "variable_declaration")
+            .and_then(|declaration| {
+                declaration
+                    .named_children(&mut declaration.walk())
+                    .find(|c| c.kind() == "variable_declarator")
+            })
+            .and_then(|declarator| declarator.child_by_field_name("name"))
+            .map(|name| text(source, name)),
+        _ => node
+            .child_by_field_name("name")
+            .map(|name| text(source, name).split_whitespace().collect()),
+    };
+    let Some(name) = name.filter(|n| !n.is_empty()) else {
+        return;
+    };
+    let exported = kind == SymbolKind::Module
+        || container == Container::Type(SymbolKind::Interface)
+        || has_modifier(source, node, "public");
+
+    let start_line = node.start_position().row as u32 + 1;
+    let (end, end_line) = if node.kind() == "file_scoped_namespace_declaration" {
+        // Runs to the end of the file
+        let code = source.trim_end();
+        (source.len(), code.matches('\n').count() as u32 + 1)
+    } else {
+        (node.end_byte(), node.end_position().row as u32 + 1)
+    };
+    let symbol = out.symbols.len();
+    let symbol_id = format!("<unknown>:{start_line}:{name}");
+    out.symbols.push(ExtractedSymbol {
+        name: name.clone(),
+        kind,
+        exported,
+        bytes: ByteSpan {
+            start: node.start_byte(),
+            end,
+        },
+        lines: LineSpan {
+            start: start_line,
+            end: end_line,
+        },
+    });
+
+    if let Some(bases) = node
+        .named_children(&mut node.walk())
+        .find(|c| c.kind() == "base_list")
+    {
+        let names: Vec<String> = bases
+            .named_children(&mut bases.walk())
+            .filter_map(|base| base_type_name(source, base))
+            .collect();
+        for (n, base) in names.into_iter().enumerate() {
+            let relation_kind = match kind {
+                SymbolKind::Interface => RelationKind::Extends,
+                SymbolKind::Class if n == 0 && !looks_like_interface(&base) => {
+                    RelationKind::Extends
+                }
+                _ => RelationKind::Implements,
+            };
+            out.relations.push(Relation {
+                symbol,
+                kind: relation_kind,
+                target: base,
+            });
+        }
+    }
+
+    let attributes = read_attributes(source, node);
+    let deprecated = attributes
+        .iter()
+        .any(|a| attribute_base_name(&a.name) == "Obsolete");
+    for attribute in attributes {
+        out.attributes.push(DecoratorEntry {
+            symbol_id: symbol_id.clone(),
+            decorator_type: classify_attribute(&attribute.name, kind),
+            name: attribute.name,
+            arguments: attribute.arguments,
+            target_line: start_line,
+        });
+    }
+    let doc = doc_lines(source, node);
+    if !doc.is_empty() {
+        let mut entry = parse_xml_doc(&doc, &symbol_id);
+        entry.deprecated |= deprecated;
+        out.docs.push(entry);
+    }
+
+    let body = node.child_by_field_name("body");
+    match (kind, body) {
+        (SymbolKind::Module, Some(body)) => {
+            read_declarations(source, body, Container::Namespace, out)
+        }
+        (SymbolKind::Class | SymbolKind::Struct | SymbolKind::Interface, Some(body))
+            if body.kind() == "declaration_list" =>
+        {
+            read_declarations(source, body, Container::Type(kind), out)
+        }
+        _ => {}
+    }
+}
+
+fn text(source: &str, node: Node) -> String {
+    source[node.byte_range()].to_string()
+}
+
+fn has_child(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|c| c.kind() == kind);
+    found
+}
+
+fn has_modifier(source: &str, node: Node, modifier: &str) -> bool {
+    let mut cursor = node.walk();
+    let found = node
+        .children(&mut cursor)
+        .any(|c| c.kind() == "modifier" && &source[c.byte_range()] == modifier);
+    found
+}
+
+/// `using [static] Namespace.Type;` or `using Alias = Namespace.Type;`
+fn read_using(source: &str, node: Node) -> Option<Import> {
+    let alias = node.child_by_field_name("name");
+    let mut cursor = node.walk();
+    let target = node
+        .named_children(&mut cursor)
+        .find(|c| Some(*c) != alias)?;
+    let path: String = text(source, target).split_whitespace().collect();
+    let path = path.strip_prefix("global::").unwrap_or(&path).to_string();
+    let name = path
+        .rsplit('.')
+        .next()
+        .filter(|n| !n.is_empty())?
+        .to_string();
+    Some(Import {
+        name,
+        source: path,
+        alias: alias.map(|alias| text(source, alias)),
+    })
+}
+
+/// Name of a base type, its last segment without type arguments: `Entity<int>` and
+/// `Shop.Entity` are `Entity`.
+fn base_type_name(source: &str, node: Node) -> Option<String> {
+    match node.kind() {
+        "identifier" => Some(text(source, node)),
+        "generic_name" => node
+            .named_child(0)
+            .filter(|n| n.kind() == "identifier")
+            .map(|n| text(source, n)),
+        "qualified_name" | "alias_qualified_name" => {
+            base_type_name(source, node.child_by_field_name("name")?)
+        }
+        // A record passing arguments to its base: `: Animal(Name)`
+        "primary_constructor_base_type" => {
+            base_type_name(source, node.child_by_field_name("type")?)
+        }
+        _ => None,
+    }
+}
+
+struct Attribute {
+    name: String,
+    arguments: Option<String>,
+}
+
+/// The attributes of a declaration, save assembly- or module-wide ones.
+fn read_attributes(source: &str, node: Node) -> Vec<Attribute> {
+    let mut attributes = Vec::new();
+    let mut cursor = node.walk();
+    for list in node
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "attribute_list")
+    {
+        let mut list_cursor = list.walk();
+        let children: Vec<Node> = list.named_children(&mut list_cursor).collect();
+        let global = children.iter().any(|c| {
+            c.kind() == "attribute_target_specifier"
+                && matches!(
+                    text(source, *c).trim_end_matches(':').trim(),
+                    "assembly" | "module"
+                )
+        });
+        if global {
+            continue;
+        }
+        for attribute in children.iter().filter(|c| c.kind() == "attribute") {
+            let Some(name) = attribute.child_by_field_name("name") else {
+                continue;
+            };
+            let arguments = attribute
+                .named_children(&mut attribute.walk())
+                .find(|c| c.kind() == "attribute_argument_list")
+                .map(|arguments| text(source, arguments).trim().to_string())
+                .filter(|arguments| arguments != "()");
+            attributes.push(Attribute {
+                name: text(source, name).split_whitespace().collect(),
+                arguments,
+            });
+        }
+    }
+    attributes
+}
+
+/// The `///` comments right before a declaration, without their slashes. Other
+/// comments between them are passed over.
+fn doc_lines<'s>(source: &'s str, node: Node) -> Vec<&'s str> {
+    let mut lines = Vec::new();
+    let mut previous = node.prev_sibling();
+    while let Some(comment) = previous.filter(|p| p.kind() == "comment") {
+        let text = &source[comment.byte_range()];
+        if let Some(line) = text.strip_prefix("///").filter(|l| !l.starts_with('/')) {
+            lines.push(line.trim_end_matches('\r'));
+        }
+        previous = comment.prev_sibling();
+    }
+    lines.reverse();
+    lines
+}
+
+fn looks_like_interface(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next() == Some('I') && chars.next().is_some_and(|c| c.is_ascii_uppercase())
+}
+
+/// `Http.HttpGetAttribute` -> `HttpGet`
+fn attribute_base_name(name: &str) -> &str {
+    let last = name.rsplit(['.', ':']).next().unwrap_or(name);
+    last.strip_suffix("Attribute")
+        .filter(|s| !s.is_empty())
+        .unwrap_or(last)
+}
+
+/// Recognize well-known ASP.NET attributes; others are typed by what they annotate.
+fn classify_attribute(name: &str, target: SymbolKind) -> DecoratorType {
+    match attribute_base_name(name) {
+        "ApiController" | "Controller" | "Route" => DecoratorType::Controller,
+        "HttpGet" => DecoratorType::Get,
+        "HttpPost" => DecoratorType::Post,
+        "HttpPut" => DecoratorType::Put,
+        "HttpDelete" => DecoratorType::Delete,
+        "HttpPatch" => DecoratorType::Patch,
+        _ => match target {
+            SymbolKind::Function => DecoratorType::MethodDecorator,
+            SymbolKind::Property | SymbolKind::Const => DecoratorType::PropertyDecorator,
+            _ => DecoratorType::ClassDecorator,
+        },
+    }
+}
+
+static SUMMARY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<summary>(.*?)</summary>").expect("valid regex"));
+static PARAM_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)<param\s+name\s*=\s*"([^"]*)"\s*>(.*?)</param>"#).expect("valid regex")
+});
+static RETURNS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<returns>(.*?)</returns>").expect("valid regex"));
+static EXAMPLE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<example>(.*?)</example>").expect("valid regex"));
+static EXCEPTION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)<exception\s+cref\s*=\s*"([^"]*)"\s*(?:/>|>(.*?)</exception>)"#)
+        .expect("valid regex")
+});
+static SEE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<see(?:also)?\s+(?:cref|href)\s*=\s*"([^"]*)"\s*/?>"#).expect("valid regex")
+});
+/// Inline references whose target stands in for the element in plain text
+static INLINE_REF_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<(?:see|paramref|typeparamref)\s+\w+\s*=\s*"([^"]*)"\s*/>"#).expect("valid regex")
+});
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"</?[^>]*>").expect("valid regex"));
+
+/// Plain text of an XML doc fragment: inline references become their target and
+/// other tags are dropped.
+fn doc_text(xml: &str) -> String {
+    let text = INLINE_REF_RE.replace_all(xml, |c: &regex::Captures| {
+        let target = &c[1];
+        // `T:System.String` -> `System.String`
+        match target.split_once(':') {
+            Some((prefix, rest)) if prefix.len() == 1 => rest.to_string(),
+            _ => target.to_string(),
+        }
+    });
+    let text = TAG_RE.replace_all(&text, "");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn non_empty(text: String) -> Option<String> {
+    (!text.is_empty()).then_some(text)
+}
+
+fn parse_xml_doc(lines: &[&str], symbol_id: &str) -> JSDocEntry {
+    let raw = lines
+        .iter()
+        .map(|l| l.strip_prefix(' ').unwrap_or(l))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let summary = match SUMMARY_RE.captures(&raw) {
+        Some(c) => non_empty(doc_text(&c[1])),
+        // Docs without a <summary> are plain text
+        None if !raw.trim_start().starts_with('<') => non_empty(doc_text(&raw)),
+        None => None,
+    };
+    let params = PARAM_RE
+        .captures_iter(&raw)
+        .map(|c| JSDocParam {
+            name: c[1].to_string(),
+            type_annotation: None,
+            description: non_empty(doc_text(&c[2])),
+        })
+        .collect();
+    let returns = RETURNS_RE
+        .captures(&raw)
+        .and_then(|c| non_empty(doc_text(&c[1])));
+    let examples = EXAMPLE_RE
+        .captures_iter(&raw)
+        .map(|c| TAG_RE.replace_all(&c[1], "").trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect();
+    let throws = EXCEPTION_RE
+        .captures_iter(&raw)
+        .map(|c| {
+            let ty = doc_text(&format!("<see cref=\"{}\"/>", &c[1]));
+            match c
+                .get(2)
+                .map(|d| doc_text(d.as_str()))
+                .filter(|d| !d.is_empty())
+            {
+                Some(desc) => format!("{ty} {desc}"),
+                None => ty,
+            }
+        })
+        .collect();
+    let see_also = SEE_RE
+        .captures_iter(&raw)
+        .map(|c| doc_text(&c[0]))
+        .collect();
+
+    JSDocEntry {
+        symbol_id: symbol_id.to_string(),
+        raw_text: raw,
+        summary,
+        params,
+        returns,
+        examples,
+        deprecated: false,
+        throws,
+        see_also,
+        since: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(extracted: &'a ExtractedFile, name: &str) -> &'a ExtractedSymbol {
+        extracted
+            .symbols
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("{name} should be extracted"))
+    }
+
+    #[test]
+    fn extracts_types_members_and_usings() {
+        let source = r#"using System;
+using System.Collections.Generic;
+using Json = System.Text.Json.JsonSerializer;
+
+namespace Acme.Billing
+{
+    public interface IInvoiceStore
+    {
+        Invoice Find(int id);
+        int Count { get; }
+    }
+
+    [Serializable]
+    public sealed class Invoice : Entity, IComparable<Invoice>
+    {
+        public const decimal TaxRate = 0.2m;
+        private readonly List<Line> _lines = new() { };
+
+        public Invoice(int id) : base(id) { }
+
+        public decimal Total => _lines.Sum(l => l.Amount);
+
+        public string Label { get; set; } = "draft";
+
+        public Line this[int index] => _lines[index];
+
+        internal static Task<T> LoadAsync<T>(string path) where T : class
+        {
+            var text = $"{path}: {"}"}";
+            if (text.Length > 0) { return null; }
+            return null;
+        }
+
+        int IComparable<Invoice>.CompareTo(Invoice other) => 0;
+
+        public static Invoice operator +(Invoice a, Invoice b) => a;
+    }
+
+    public record Line(string Sku, decimal Amount);
+
+    public readonly record struct Money(decimal Value) : IEquatable<Money>;
+
+    public enum Status { Draft, Paid = 2 }
+
+    public delegate void InvoiceHandler(Invoice invoice);
+}
+"#;
+        let extracted = extract_csharp_symbols(source).unwrap();
+        let names: Vec<(&str, SymbolKind)> = extracted
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Acme.Billing", SymbolKind::Module),
+                ("IInvoiceStore", SymbolKind::Interface),
+                ("Find", SymbolKind::Function),
+                ("Count", SymbolKind::Property),
+                ("Invoice", SymbolKind::Class),
+                ("TaxRate", SymbolKind::Const),
+                ("Invoice", SymbolKind::Function),
+                ("Total", SymbolKind::Property),
+                ("Label", SymbolKind::Property),
+                ("this", SymbolKind::Property),
+                ("LoadAsync", SymbolKind::Function),
+                ("CompareTo", SymbolKind::Function),
+                ("Line", SymbolKind::Class),
+                ("Money", SymbolKind::Struct),
+                ("Status", SymbolKind::Enum),
+                ("InvoiceHandler", SymbolKind::TypeAlias),
+            ]
+        );
+
+        let namespace = find(&extracted, "Acme.Billing");
+        assert_eq!((namespace.lines.start, namespace.lines.end), (5, 46));
+        let store = find(&extracted, "IInvoiceStore");
+        assert_eq!((store.lines.start, store.lines.end), (7, 11));
+        assert!(
+            find(&extracted, "Find").exported,
+            "interface members are public"
+        );
+
+        let invoice = find(&extracted, "Invoice");
+        assert_eq!((invoice.lines.start, invoice.lines.end), (13, 37));
+        assert!(source[invoice.bytes.start..].starts_with("[Serializable]"));
+        assert!(source[..invoice.bytes.end].ends_with("=> a;\n    }"));
+
+        let load = find(&extracted, "LoadAsync");
+        assert!(!load.exported);
+        assert_eq!((load.lines.start, load.lines.end), (27, 32));
+        let total = find(&extracted, "Total");
+        assert!(source[total.bytes.start..total.bytes.end].ends_with("l.Amount);"));
+        let line = find(&extracted, "Line");
+        assert_eq!(
+            &source[line.bytes.start..line.bytes.end],
+            "public record Line(string Sku, decimal Amount);"
+        );
+
+        let imports: Vec<(&str, &str, Option<&str>)> = extracted
+            .imports
+            .iter()
+            .map(|i| (i.name.as_str(), i.source.as_str(), i.alias.as_deref()))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("System", "System", None),
+                ("Generic", "System.Collections.Generic", None),
+                (
+                    "JsonSerializer",
+                    "System.Text.Json.JsonSerializer",
+                    Some("Json")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn type_relations_split_superclass_and_interfaces() {
+        assert_eq!(
+            csharp_type_relations(
+                "public class Invoice : Entity<int>, IComparable<Invoice>, IDisposable { }"
+            ),
+            (
+                vec!["Entity".to_string()],
+                vec!["IComparable".to_string(), "IDisposable".to_string()],
+                vec![]
+            )
+        );
+        assert_eq!(
+            csharp_type_relations("class Repo : IRepo where T : class, new() { }").1,
+            vec!["IRepo".to_string()]
+        );
+        assert_eq!(
+            csharp_type_relations("public interface IRepo<T> : IReader<T>, IWriter { }").0,
+            vec!["IReader".to_string(), "IWriter".to_string()]
+        );
+        assert_eq!(
+            csharp_type_relations("public record Dog(string Name) : Animal(Name);").0,
+            vec!["Animal".to_string()]
+        );
+    }
+
+    #[test]
+    fn attributes_and_xml_docs_attach_to_declarations() {
+        let source = r#"namespace Shop.Api;
+
+/// <summary>
+/// Manages <see cref="T:Shop.Order"/> resources.
+/// </summary>
+[ApiController]
+[Route("api/[controller]")]
+public class OrdersController : ControllerBase
+{
+    /// <summary>Gets one order.</summary>
+    /// <param name="id">The order id.</param>
+    /// <returns>The order, if found.</returns>
+    /// <exception cref="System.ArgumentException">When <paramref name="id"/> is negative.</exception>
+    [HttpGet("{id}"), Obsolete]
+    public Order Get(int id) => Load(id);
+
+    [HttpPost]
+    public IActionResult Create([FromBody] Order order)
+    {
+        return Ok();
+    }
+}
+"#;
+        let extracted = extract_csharp_symbols(source).unwrap();
+        let namespace = find(&extracted, "Shop.Api");
+        assert_eq!((namespace.lines.start, namespace.lines.end), (1, 22));
+        let controller = find(&extracted, "OrdersController");
+        assert_eq!(controller.lines.start, 6);
+        assert_eq!(controller.lines.end, 22);
+
+        let attributes: Vec<(&str, Option<&str>, &str, u32, DecoratorType)> = extracted
+            .decorators
+            .iter()
+            .map(|d| {
+                (
+                    d.name.as_str(),
+                    d.arguments.as_deref(),
+                    d.symbol_id.as_str(),
+                    d.target_line,
+                    d.decorator_type.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                (
+                    "ApiController",
+                    None,
+                    "<unknown>:6:OrdersController",
+                    6,
+                    DecoratorType::Controller
+                ),
+                (
+                    "Route",
+                    Some("(\"api/[controller]\")"),
+                    "<unknown>:6:OrdersController",
+                    6,
+                    DecoratorType::Controller
+                ),
+                (
+                    "HttpGet",
+                    Some("(\"{id}\")"),
+                    "<unknown>:14:Get",
+                    14,
+                    DecoratorType::Get
+                ),
+                (
+                    "Obsolete",
+                    None,
+                    "<unknown>:14:Get",
+                    14,
+                    DecoratorType::MethodDecorator
+                ),
+                (
+                    "HttpPost",
+                    None,
+                    "<unknown>:17:Create",
+                    17,
+                    DecoratorType::Post
+                ),
+            ]
+        );
+
+        assert_eq!(extracted.jsdoc_entries.len(), 2);
+        let class_doc = &extracted.jsdoc_entries[0];
+        assert_eq!(class_doc.symbol_id, "<unknown>:6:OrdersController");
+        assert_eq!(
+            class_doc.summary.as_deref(),
+            Some("Manages Shop.Order resources.")
+        );
+        assert_eq!(class_doc.see_also, vec!["Shop.Order".to_string()]);
+
+        let get_doc = &extracted.jsdoc_entries[1];
+        assert_eq!(get_doc.symbol_id, "<unknown>:14:Get");
+        assert_eq!(get_doc.summary.as_deref(), Some("Gets one order."));
+        assert_eq!(get_doc.params.len(), 1);
+        assert_eq!(get_doc.params[0].name, "id");
+        assert_eq!(
+            get_doc.params[0].description.as_deref(),
+            Some("The order id.")
+        );
+        assert_eq!(get_doc.returns.as_deref(), Some("The order, if found."));
+        assert_eq!(
+            get_doc.throws,
+            vec!["System.ArgumentException When id is negative.".to_string()]
+        );
+        assert!(get_doc.deprecated, "[Obsolete] marks the doc deprecated");
+    }
+
+    #[test]
+    fn top_level_statements_are_not_declarations() {
+        let source = r#"using Microsoft.AspNetCore.Builder;
+
+var builder = WebApplication.CreateBuilder(args);
+var app = builder.Build();
+app.MapGet("/", () => "Hello {");
+if (app.Environment.IsDevelopment()) { app.UseDeveloperExceptionPage(); }
+await app.RunAsync();
+
+static string Greet(string name) => $"Hello {name}";
+
+record Greeting(string Text);
+"#;
+        let extracted = extract_csharp_symbols(source).unwrap();
+        let names: Vec<&str> = extracted.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Greet", "Greeting"]);
+        assert_eq!(extracted.imports.len(), 1);
+    }
+}
@@ -158,7 +158,7 @@ fn extract_pattern_details(
                 }
             }
         }
-        FrameworkPatternKind::Plugin => {
+        FrameworkPatternKind::Plugin | FrameworkPatternKind::Mount => {
             // Argument is the plugin (identifier or call)
             if let Some(first) = children.iter().find(|n| n.is_named()) {
                 name = extract_plugin_name(*first, source);
@@ -176,13 +176,15 @@ fn extract_pattern_details(
                 arguments = Some(truncate_text(&text_for_node(*first, source), 200));
             }
         }
+        // Client calls are not Elysia methods
+        FrameworkPatternKind::HttpCall => {}
     }
 
     (path, name, handler, arguments)
 }
 
 /// Extract string content without quotes
-fn extract_string_value(node: Node, source: &str) -> String {
+pub(super) fn extract_string_value(node: Node, source: &str) -> String {
     let text = text_for_node(node, source);
     text.trim_matches(|c| c == '"' || c == '\'' || c == '`')
         .to_string()
@@ -244,7 +246,7 @@ fn extract_plugin_name(node: Node, source: &str) -> Option<String> {
 }
 
 /// Find the root variable of a method chain
-fn find_chain_root(member_expr: Node, source: &str) -> Option<String> {
+pub(super) fn find_chain_root(member_expr: Node, source: &str) -> Option<String> {
     let object = member_expr.child_by_field_name("object")?;
 
     match object.kind() {
@@ -272,7 +274,7 @@ fn find_chain_root(member_expr: Node, source: &str) -> Option<String> {
     }
 }
 
-fn text_for_node(node: Node, source: &str) -> String {
+pub(super) fn text_for_node(node: Node, source: &str) -> String {
     source
         .get(node.start_byte()..node.end_byte())
         .unwrap_or("")
@@ -2,6 +2,8 @@ use crate::indexer::parser::{parser_for_id, LanguageId};
 use anyhow::{anyhow, Result};
 use tree_sitter::{Node, Parser, TreeCursor};
 
+use super::go_routes::extract_go_routes;
+use super::http_clients::extract_go_http_calls;
 use super::symbol::{ByteSpan, ExtractedFile, ExtractedSymbol, Import, LineSpan, SymbolKind};
 
 pub fn extract_go_symbols(source: &str) -> Result<ExtractedFile> {
@@ -69,6 +71,9 @@ fn extract_symbols_with_parser(parser: &mut Parser, source: &str) -> Result<Extr
     });
 
     symbols.sort_by_key(|s| s.bytes.start);
+    let mut framework_patterns = extract_go_routes(root, source, &imports);
+    framework_patterns.extend(extract_go_http_calls(root, source, &imports));
+    framework_patterns.sort_by_key(|p| (p.line, p.column));
     Ok(ExtractedFile {
         symbols,
         imports,
@@ -77,7 +82,7 @@ fn extract_symbols_with_parser(parser: &mut Parser, source: &str) -> Result<Extr
         todos: Vec::new(),
         jsdoc_entries: Vec::new(),
         decorators: Vec::new(),
-        framework_patterns: Vec::new(),
+        framework_patterns,
     })
 }
 
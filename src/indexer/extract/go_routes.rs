@@ -0,0 +1,501 @@
+//! net/http, chi and gin route extraction
+//!
+//! Go services register routes as calls on a mux or router: `mux.HandleFunc("GET
+//! /users/{id}", h)` with the standard library, `r.Get("/users/{id}", h)` with chi and
+//! `r.GET("/users/:id", h)` with gin. Prefixes come from nesting: chi's
+//! `r.Route("/users", func(r chi.Router) { ... })` and `r.Group(...)` closures, gin's
+//! `v1 := r.Group("/v1")` groups, and chi's `r.Mount("/admin", adminRouter())`, which
+//! prefixes the routes registered by the function building the mounted router.
+
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+
+use super::routers::join_paths;
+use super::symbol::{ExtractedFrameworkPattern, FrameworkPatternKind, Import};
+
+/// chi's route methods.
+const CHI_METHODS: &[&str] = &[
+    "Get", "Post", "Put", "Delete", "Patch", "Head", "Options", "Connect", "Trace",
+];
+
+/// gin's route methods.
+const GIN_METHODS: &[&str] = &["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
+
+/// Most levels of mounted functions followed when resolving a prefix.
+const MAX_MOUNT_DEPTH: usize = 8;
+
+/// Framework patterns of a Go file, for the router package it imports.
+pub fn extract_go_routes(
+    root: Node,
+    source: &str,
+    imports: &[Import],
+) -> Vec<ExtractedFrameworkPattern> {
+    let Some(framework) = framework(imports) else {
+        return Vec::new();
+    };
+    let mut walker = RouteWalker {
+        source,
+        framework,
+        registrations: Vec::new(),
+        mounted_functions: HashMap::new(),
+    };
+    walker.walk(root, &HashMap::new(), None);
+
+    // Routes of a function whose router is mounted get the mount's prefix
+    let RouteWalker {
+        registrations,
+        mounted_functions,
+        ..
+    } = walker;
+    let function_prefix = |function: Option<&str>| {
+        let mut prefix = String::new();
+        let mut current = function;
+        for _ in 0..MAX_MOUNT_DEPTH {
+            let Some((parent, path)) = current.and_then(|f| mounted_functions.get(f)) else {
+                break;
+            };
+            prefix = join_paths(path, &prefix);
+            current = parent.as_deref();
+        }
+        prefix
+    };
+    let mut patterns: Vec<ExtractedFrameworkPattern> = registrations
+        .into_iter()
+        .map(|(function, mut pattern)| {
+            let prefix = function_prefix(function.as_deref());
+            if !prefix.is_empty() {
+                pattern.path = pattern.path.map(|path| join_paths(&prefix, &path));
+            }
+            pattern
+        })
+        .collect();
+    patterns.sort_by_key(|p| (p.line, p.column));
+    patterns
+}
+
+fn framework(imports: &[Import]) -> Option<&'static str> {
+    let imported = |prefix: &str| imports.iter().any(|i| i.source.starts_with(prefix));
+    if imported("github.com/go-chi/chi") {
+        Some("chi")
+    } else if imported("github.com/gin-gonic/gin") {
+        Some("gin")
+    } else if imports.iter().any(|i| i.source == "net/http") {
+        Some("net/http")
+    } else {
+        None
+    }
+}
+
+struct RouteWalker<'s> {
+    source: &'s str,
+    framework: &'static str,
+    /// Patterns, with the function they are registered in
+    registrations: Vec<(Option<String>, ExtractedFrameworkPattern)>,
+    /// Function building a mounted router to the function mounting it and the path
+    mounted_functions: HashMap<String, (Option<String>, String)>,
+}
+
+impl RouteWalker<'_> {
+    /// Walk `node` with the prefixes of the routers in scope, by variable name.
+    fn walk(&mut self, node: Node, prefixes: &HashMap<String, String>, function: Option<&str>) {
+        match node.kind() {
+            "function_declaration" | "method_declaration" => {
+                let name = node.child_by_field_name("name").map(|n| self.text(n));
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.walk(body, &HashMap::new(), name.as_deref());
+                }
+                return;
+            }
+            "block" | "statement_list" => {
+                // Groups declared in a block are scoped to it
+                let mut scoped = prefixes.clone();
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    self.walk(child, &scoped, function);
+                    self.declare_group(child, &mut scoped);
+                }
+                return;
+            }
+            "call_expression" if self.call(node, prefixes, function) => return,
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.walk(child, prefixes, function);
+        }
+    }
+
+    /// gin's `v1 := r.Group("/v1")`: `v1` registers routes under the prefix.
+    fn declare_group(&self, statement: Node, prefixes: &mut HashMap<String, String>) {
+        if !matches!(
+            statement.kind(),
+            "short_var_declaration" | "assignment_statement"
+        ) {
+            return;
+        }
+        let (Some(left), Some(right)) = (
+            statement
+                .child_by_field_name("left")
+                .and_then(|l| l.named_child(0)),
+            statement
+                .child_by_field_name("right")
+                .and_then(|r| r.named_child(0)),
+        ) else {
+            return;
+        };
+        let Some((router, method, arguments)) = self.method_call(right) else {
+            return;
+        };
+        if method != "Group" {
+            return;
+        }
+        let Some(path) = arguments.first().and_then(|a| self.string(*a)) else {
+            return;
+        };
+        let prefix = join_paths(prefixes.get(&router).map_or("", String::as_str), &path);
+        prefixes.insert(self.text(left), prefix);
+    }
+
+    /// Record the route or mount `call` registers. Returns whether its arguments were
+    /// walked already.
+    fn call(
+        &mut self,
+        call: Node,
+        prefixes: &HashMap<String, String>,
+        function: Option<&str>,
+    ) -> bool {
+        let Some((router, method, arguments)) = self.method_call(call) else {
+            return false;
+        };
+        let prefix = prefixes.get(&router).cloned().unwrap_or_default();
+
+        // chi's r.Route("/users", func(r chi.Router) { ... }) and r.Group(func(r chi.Router) { ... })
+        if self.framework == "chi" && matches!(method.as_str(), "Route" | "Group") {
+            let (path, closure) = match (method.as_str(), arguments.as_slice()) {
+                ("Route", [path, closure, ..]) => (self.string(*path), *closure),
+                ("Group", [closure, ..]) => (Some(String::new()), *closure),
+                _ => return false,
+            };
+            let (Some(path), "func_literal") = (path, closure.kind()) else {
+                return false;
+            };
+            let mut scoped = prefixes.clone();
+            if let Some(parameter) = self.first_parameter(closure) {
+                scoped.insert(parameter, join_paths(&prefix, &path));
+            }
+            if let Some(body) = closure.child_by_field_name("body") {
+                self.walk(body, &scoped, function);
+            }
+            return true;
+        }
+
+        // chi's r.Mount("/admin", adminRouter())
+        if self.framework == "chi" && method == "Mount" {
+            let (Some(path), Some(mounted)) = (
+                arguments.first().and_then(|a| self.string(*a)),
+                arguments.get(1),
+            ) else {
+                return false;
+            };
+            let full = join_paths(&prefix, &path);
+            let mounted_function = (mounted.kind() == "call_expression")
+                .then(|| mounted.child_by_field_name("function"))
+                .flatten()
+                .filter(|f| f.kind() == "identifier")
+                .map(|f| self.text(f));
+            if let Some(mounted_function) = &mounted_function {
+                self.mounted_functions.insert(
+                    mounted_function.clone(),
+                    (function.map(str::to_string), full.clone()),
+                );
+            }
+            let name = mounted_function.unwrap_or_else(|| self.text(*mounted));
+            self.push(
+                function,
+                call,
+                FrameworkPatternKind::Mount,
+                None,
+                full,
+                Some(name),
+                None,
+                &router,
+            );
+            return false;
+        }
+
+        let (http_method, path, handler) = match self.route(&method, &arguments) {
+            Some(route) => route,
+            None => return false,
+        };
+        let handler = handler.and_then(|h| self.handler_name(h));
+        self.push(
+            function,
+            call,
+            FrameworkPatternKind::Route,
+            Some(http_method),
+            join_paths(&prefix, &path),
+            None,
+            handler,
+            &router,
+        );
+        false
+    }
+
+    /// Method, path and handler of a route registration.
+    fn route<'a>(
+        &self,
+        method: &str,
+        arguments: &[Node<'a>],
+    ) -> Option<(String, String, Option<Node<'a>>)> {
+        let handler = arguments.last().copied();
+        match (self.framework, method) {
+            // mux.HandleFunc("GET /users/{id}", h), r.Handle("/x", h)
+            (_, "HandleFunc" | "Handle") if !(self.framework == "gin" && method == "Handle") => {
+                let pattern = self.string(*arguments.first()?)?;
+                let (http_method, path) = match pattern.split_once(' ') {
+                    Some((m, p)) if m.chars().all(|c| c.is_ascii_uppercase()) => {
+                        (m.to_string(), p.trim().to_string())
+                    }
+                    _ => ("ALL".to_string(), pattern),
+                };
+                is_route_path(&path).then_some((http_method, path, handler))
+            }
+            ("chi", m) if CHI_METHODS.contains(&m) => {
+                let path = self.string(*arguments.first()?)?;
+                Some((m.to_ascii_uppercase(), path, handler))
+            }
+            // r.Method("GET", "/x", h)
+            ("chi", "Method" | "MethodFunc") | ("gin", "Handle") => {
+                let http_method = self.string(*arguments.first()?)?;
+                let path = self.string(*arguments.get(1)?)?;
+                Some((http_method.to_ascii_uppercase(), path, handler))
+            }
+            ("gin", m) if GIN_METHODS.contains(&m) || m == "Any" => {
+                let path = self.string(*arguments.first()?)?;
+                let http_method = if m == "Any" { "ALL" } else { m };
+                Some((http_method.to_string(), path, handler))
+            }
+            _ => None,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        function: Option<&str>,
+        call: Node,
+        kind: FrameworkPatternKind,
+        http_method: Option<String>,
+        path: String,
+        name: Option<String>,
+        handler: Option<String>,
+        router: &str,
+    ) {
+        let position = call.start_position();
+        self.registrations.push((
+            function.map(str::to_string),
+            ExtractedFrameworkPattern {
+                line: position.row as u32 + 1,
+                column: position.column as u32,
+                framework: self.framework.to_string(),
+                kind,
+                http_method,
+                path: Some(path),
+                name,
+                handler,
+                arguments: None,
+                parent_chain: Some(router.to_string()),
+            },
+        ));
+    }
+
+    /// The handler a route names: `listUsers`, `h.ListUsers`, or the function wrapped
+    /// by `http.HandlerFunc(listUsers)`.
+    fn handler_name(&self, node: Node) -> Option<String> {
+        match node.kind() {
+            "identifier" | "selector_expression" => Some(self.text(node)),
+            "func_literal" => Some("<anonymous>".to_string()),
+            "call_expression" | "type_conversion_expression" => {
+                let arguments = node.child_by_field_name("arguments")?;
+                let mut cursor = arguments.walk();
+                let last = arguments.named_children(&mut cursor).last();
+                match last {
+                    Some(last) => self.handler_name(last),
+                    None => Some(self.text(node.child_by_field_name("function")?)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The router, method and arguments of a call such as `r.Get("/x", h)`.
+    fn method_call<'a>(&self, call: Node<'a>) -> Option<(String, String, Vec<Node<'a>>)> {
+        if call.kind() != "call_expression" {
+            return None;
+        }
+        let function = call
+            .child_by_field_name("function")
+            .filter(|f| f.kind() == "selector_expression")?;
+        let router = self.text(function.child_by_field_name("operand")?);
+        let method = self.text(function.child_by_field_name("field")?);
+        let arguments = call.child_by_field_name("arguments")?;
+        let mut cursor = arguments.walk();
+        let arguments = arguments
+            .named_children(&mut cursor)
+            .filter(|a| a.kind() != "comment")
+            .collect();
+        Some((router, method, arguments))
+    }
+
+    fn first_parameter(&self, closure: Node) -> Option<String> {
+        let parameters = closure.child_by_field_name("parameters")?;
+        let declaration = parameters.named_child(0)?;
+        Some(self.text(declaration.child_by_field_name("name")?))
+    }
+
+    fn string(&self, node: Node) -> Option<String> {
+        match node.kind() {
+            "interpreted_string_literal" => Some(self.text(node).trim_matches('"').to_string()),
+            "raw_string_literal" => Some(self.text(node).trim_matches('`').to_string()),
+            _ => None,
+        }
+    }
+
+    fn text(&self, node: Node) -> String {
+        self.source[node.start_byte()..node.end_byte()].to_string()
+    }
+}
+
+fn is_route_path(path: &str) -> bool {
+    path.starts_with('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::indexer::extract::go::extract_go_symbols;
+    use crate::indexer::extract::symbol::FrameworkPatternKind;
+
+    fn routes(source: &str) -> Vec<(String, String, String, String)> {
+        extract_go_symbols(source)
+            .unwrap()
+            .framework_patterns
+            .into_iter()
+            .filter(|p| p.kind == FrameworkPatternKind::Route)
+            .map(|p| {
+                (
+                    p.framework,
+                    p.http_method.unwrap(),
+                    p.path.unwrap(),
+                    p.handler.unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn route(
+        framework: &str,
+        method: &str,
+        path: &str,
+        handler: &str,
+    ) -> (String, String, String, String) {
+        (framework.into(), method.into(), path.into(), handler.into())
+    }
+
+    #[test]
+    fn net_http_patterns() {
+        let source = r#"
+package main
+
+import "net/http"
+
+func main() {
+	mux := http.NewServeMux()
+	mux.HandleFunc("GET /users/{id}", getUser)
+	mux.Handle("/static/", http.StripPrefix("/static/", files))
+	http.HandleFunc("/health", func(w http.ResponseWriter, r *http.Request) {})
+	resp, _ := http.Get("https://example.com/x")
+	_ = resp
+}
+"#;
+        assert_eq!(
+            routes(source),
+            vec![
+                route("net/http", "GET", "/users/{id}", "getUser"),
+                route("net/http", "ALL", "/static", "files"),
+                route("net/http", "ALL", "/health", "<anonymous>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn chi_routes_nest_under_route_group_and_mount() {
+        let source = r#"
+package api
+
+import (
+	"net/http"
+
+	"github.com/go-chi/chi/v5"
+)
+
+func Router(h *Handlers) http.Handler {
+	r := chi.NewRouter()
+	r.Route("/users", func(r chi.Router) {
+		r.Get("/", h.ListUsers)
+		r.Group(func(r chi.Router) {
+			r.Use(auth)
+			r.Post("/{id}/ban", h.BanUser)
+		})
+	})
+	r.Mount("/admin", adminRouter())
+	r.Method("DELETE", "/cache", http.HandlerFunc(clearCache))
+	return r
+}
+
+func adminRouter() http.Handler {
+	r := chi.NewRouter()
+	r.Get("/stats", stats)
+	return r
+}
+"#;
+        assert_eq!(
+            routes(source),
+            vec![
+                route("chi", "GET", "/users", "h.ListUsers"),
+                route("chi", "POST", "/users/{id}/ban", "h.BanUser"),
+                route("chi", "DELETE", "/cache", "clearCache"),
+                route("chi", "GET", "/admin/stats", "stats"),
+            ]
+        );
+    }
+
+    #[test]
+    fn gin_groups() {
+        let source = r#"
+package main
+
+import "github.com/gin-gonic/gin"
+
+func setup(r *gin.Engine) {
+	v1 := r.Group("/v1")
+	{
+		users := v1.Group("/users")
+		users.GET("/:id", getUser)
+		users.POST("", createUser)
+	}
+	r.Any("/ping", ping)
+	r.Handle("PUT", "/items/:id", putItem)
+}
+"#;
+        assert_eq!(
+            routes(source),
+            vec![
+                route("gin", "GET", "/v1/users/:id", "getUser"),
+                route("gin", "POST", "/v1/users", "createUser"),
+                route("gin", "ALL", "/ping", "ping"),
+                route("gin", "PUT", "/items/:id", "putItem"),
+            ]
+        );
+    }
+}
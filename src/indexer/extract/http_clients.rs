@@ -0,0 +1,870 @@
+//! HTTP client call extraction
+//!
+//! Client code calls other services with `fetch('/api/users')`, axios, Angular's
+//! `HttpClient`, `requests.get(...)` and `httpx`, or Go's `http.NewRequest("GET", url,
+//! nil)`. Each such call is recorded as an `http_call` pattern with its method and the
+//! path it requests, so it can be matched against the routes services register.
+//!
+//! URLs are usually built rather than written out: `` `${API_URL}/users/${id}` ``,
+//! `f"{base}/users/{user_id}"`, `fmt.Sprintf("%s/users/%d", base, id)`. The parts
+//! spliced in become path parameters, except for a leading one, which is taken to be
+//! the base URL of the service and left out of the path. So is the scheme and host of
+//! an absolute URL.
+
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+
+use super::routers::ModuleBindings;
+use super::symbol::{ExtractedFrameworkPattern, FrameworkPatternKind, Import};
+
+/// Methods named after the HTTP method they send, in client libraries.
+const METHOD_CALLS: &[&str] = &["get", "post", "put", "delete", "patch", "head", "options"];
+
+/// Endings of receiver names taken to be HTTP clients when their type is not known
+/// from the file: `api`, `apiClient`, `this.http`, `usersClient`.
+const CLIENT_NAME_ENDINGS: &[&str] = &["api", "client", "http"];
+
+/// Part of a URL as code builds it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum UrlPart {
+    Literal(String),
+    /// An expression spliced in, named by its text when that is a plain name
+    Dynamic(String),
+}
+
+/// The path a URL built from `parts` requests, or `None` when it is not a path on
+/// some service.
+fn url_path(parts: &[UrlPart]) -> Option<String> {
+    let mut url = String::new();
+    for part in parts {
+        match part {
+            UrlPart::Literal(text) => url.push_str(text),
+            // The base URL the path is appended to
+            UrlPart::Dynamic(_) if url.is_empty() => {}
+            UrlPart::Dynamic(name) => {
+                url.push('{');
+                url.push_str(name);
+                url.push('}');
+            }
+        }
+    }
+
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let path = match ["https://", "http://", "//"]
+        .iter()
+        .find_map(|scheme| url.strip_prefix(scheme))
+    {
+        Some(rest) => rest.find('/').map_or("/", |slash| &rest[slash..]),
+        None => url,
+    };
+    if !path.starts_with('/') || path.starts_with("//") {
+        return None;
+    }
+    let trimmed = path.trim_end_matches('/');
+    Some(if trimmed.is_empty() { "/" } else { trimmed }.to_string())
+}
+
+/// The parts of a template with `{expression}` (or `${expression}` when `dollar`)
+/// interpolations.
+fn interpolated_parts(template: &str, dollar: bool) -> Vec<UrlPart> {
+    let opener = if dollar { "${" } else { "{" };
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(opener) {
+        let inner = &rest[start + opener.len()..];
+        let mut depth = 1;
+        let Some(end) = inner.char_indices().find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(i)
+        }) else {
+            break;
+        };
+        if start > 0 {
+            parts.push(UrlPart::Literal(rest[..start].to_string()));
+        }
+        parts.push(UrlPart::Dynamic(parameter_name(&inner[..end])));
+        rest = &inner[end + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(UrlPart::Literal(rest.to_string()));
+    }
+    parts
+}
+
+/// The name of a path parameter spliced in as `expression`: the expression itself
+/// when it is a plain (possibly dotted) name, or else nothing.
+fn parameter_name(expression: &str) -> String {
+    let expression = expression.trim();
+    let plain = expression
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+    if plain {
+        expression.to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn http_call(
+    node: Node,
+    framework: &str,
+    http_method: &str,
+    path: String,
+    receiver: Option<String>,
+) -> ExtractedFrameworkPattern {
+    let position = node.start_position();
+    ExtractedFrameworkPattern {
+        line: position.row as u32 + 1,
+        column: position.column as u32,
+        framework: framework.to_string(),
+        kind: FrameworkPatternKind::HttpCall,
+        http_method: Some(http_method.to_ascii_uppercase()),
+        path: Some(path),
+        name: None,
+        handler: None,
+        arguments: None,
+        parent_chain: receiver,
+    }
+}
+
+/// Whether a receiver not otherwise known to be an HTTP client is named like one.
+fn is_client_name(receiver: &str) -> bool {
+    let name = receiver.rsplit('.').next().unwrap_or(receiver);
+    let name = name.to_ascii_lowercase();
+    CLIENT_NAME_ENDINGS
+        .iter()
+        .any(|ending| name.ends_with(ending))
+}
+
+fn text(node: Node, source: &str) -> String {
+    source[node.start_byte()..node.end_byte()].to_string()
+}
+
+fn walk<'a>(node: Node<'a>, f: &mut impl FnMut(Node<'a>)) {
+    f(node);
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk(child, f);
+    }
+}
+
+fn named_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|child| child.kind() != "comment")
+        .collect()
+}
+
+/// Extract the HTTP calls of a JavaScript or TypeScript AST: `fetch`, axios and its
+/// instances, Angular's `HttpClient`, and clients named like one (`api.get('/users')`).
+pub fn extract_js_http_calls(
+    root: Node,
+    source: &str,
+    imports: &[Import],
+) -> Vec<ExtractedFrameworkPattern> {
+    let bindings = ModuleBindings::collect(root, source, imports);
+    let axios: Vec<&str> = bindings
+        .locals
+        .iter()
+        .filter(|(_, module)| *module == "axios")
+        .map(|(local, _)| local.as_str())
+        .collect();
+    let angular = imports
+        .iter()
+        .any(|import| import.source == "@angular/common/http");
+
+    // const api = axios.create({ baseURL: '/api' })
+    let mut instances: HashMap<String, String> = HashMap::new();
+    walk(root, &mut |node| {
+        if node.kind() != "variable_declarator" {
+            return;
+        }
+        let (Some(name), Some(value)) = (
+            node.child_by_field_name("name"),
+            node.child_by_field_name("value"),
+        ) else {
+            return;
+        };
+        let Some((receiver, method, arguments)) = js_method_call(value, source) else {
+            return;
+        };
+        if method == "create" && axios.contains(&receiver.as_str()) {
+            let base = arguments
+                .first()
+                .and_then(|config| js_object_value(*config, "baseURL", source))
+                .and_then(|url| url_path(&js_url_parts(url, source)))
+                .unwrap_or_default();
+            instances.insert(text(name, source), base);
+        }
+    });
+
+    let mut calls = Vec::new();
+    walk(root, &mut |node| {
+        if node.kind() != "call_expression" {
+            return;
+        }
+        let Some(function) = node.child_by_field_name("function") else {
+            return;
+        };
+        let arguments = node
+            .child_by_field_name("arguments")
+            .map(named_children)
+            .unwrap_or_default();
+        let function_name = text(function, source);
+
+        // fetch(url, { method }) and axios(url, config) or axios(config)
+        let is_fetch = matches!(
+            function_name.as_str(),
+            "fetch" | "window.fetch" | "globalThis.fetch" | "self.fetch"
+        );
+        if is_fetch || axios.contains(&function_name.as_str()) {
+            let framework = if is_fetch { "fetch" } else { "axios" };
+            let (url, options) = match arguments.first() {
+                Some(config) if !is_fetch && config.kind() == "object" => {
+                    (js_object_value(*config, "url", source), Some(*config))
+                }
+                first => (first.copied(), arguments.get(1).copied()),
+            };
+            let method = options
+                .and_then(|options| js_object_value(options, "method", source))
+                .and_then(|method| js_string(method, source))
+                .unwrap_or_else(|| "GET".to_string());
+            if let Some(path) = url.and_then(|url| url_path(&js_url_parts(url, source))) {
+                calls.push(http_call(node, framework, &method, path, None));
+            }
+            return;
+        }
+
+        // axios.get(url), api.post(url, body), this.http.get<User>(url)
+        let Some((receiver, method, _)) = js_method_call(node, source) else {
+            return;
+        };
+        let (framework, base) = if axios.contains(&receiver.as_str()) {
+            ("axios", String::new())
+        } else if let Some(base) = instances.get(&receiver) {
+            ("axios", base.clone())
+        } else if angular && is_client_name(&receiver) {
+            ("angular", String::new())
+        } else if is_client_name(&receiver) {
+            ("http", String::new())
+        } else {
+            return;
+        };
+        let (http_method, url) = if METHOD_CALLS.contains(&method.as_str()) {
+            (method.clone(), arguments.first().copied())
+        } else if method == "request" {
+            let config = arguments.first().copied().filter(|c| c.kind() == "object");
+            let method = config
+                .and_then(|config| js_object_value(config, "method", source))
+                .and_then(|method| js_string(method, source))
+                .unwrap_or_else(|| "GET".to_string());
+            (
+                method,
+                config.and_then(|c| js_object_value(c, "url", source)),
+            )
+        } else {
+            return;
+        };
+        let Some(path) = url.and_then(|url| url_path(&js_url_parts(url, source))) else {
+            return;
+        };
+        let path = if base.is_empty() {
+            path
+        } else {
+            super::routers::join_paths(&base, &path)
+        };
+        calls.push(http_call(
+            node,
+            framework,
+            &http_method,
+            path,
+            Some(receiver),
+        ));
+    });
+    calls.sort_by_key(|c| (c.line, c.column));
+    calls
+}
+
+/// The receiver, method and arguments of a call such as `api.get('/x')`.
+fn js_method_call<'a>(call: Node<'a>, source: &str) -> Option<(String, String, Vec<Node<'a>>)> {
+    if call.kind() != "call_expression" {
+        return None;
+    }
+    let function = call
+        .child_by_field_name("function")
+        .filter(|f| f.kind() == "member_expression")?;
+    let receiver = text(function.child_by_field_name("object")?, source);
+    let method = text(function.child_by_field_name("property")?, source);
+    let arguments = call
+        .child_by_field_name("arguments")
+        .map(named_children)
+        .unwrap_or_default();
+    Some((receiver, method, arguments))
+}
+
+/// The value of `key` in an object literal.
+fn js_object_value<'a>(object: Node<'a>, key: &str, source: &str) -> Option<Node<'a>> {
+    if object.kind() != "object" {
+        return None;
+    }
+    named_children(object).into_iter().find_map(|pair| {
+        let name = pair.child_by_field_name("key")?;
+        let name = text(name, source);
+        (pair.kind() == "pair" && name.trim_matches(['"', '\'']) == key)
+            .then(|| pair.child_by_field_name("value"))
+            .flatten()
+    })
+}
+
+fn js_string(node: Node, source: &str) -> Option<String> {
+    matches!(node.kind(), "string" | "template_string").then(|| {
+        text(node, source)
+            .trim_matches(['"', '\'', '`'])
+            .to_string()
+    })
+}
+
+fn js_url_parts(node: Node, source: &str) -> Vec<UrlPart> {
+    match node.kind() {
+        "string" => vec![UrlPart::Literal(
+            text(node, source).trim_matches(['"', '\'']).to_string(),
+        )],
+        "template_string" => interpolated_parts(text(node, source).trim_matches('`'), true),
+        "binary_expression" => {
+            let (Some(left), Some(right)) = (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ) else {
+                return Vec::new();
+            };
+            let mut parts = js_url_parts(left, source);
+            parts.extend(js_url_parts(right, source));
+            parts
+        }
+        "parenthesized_expression" => named_children(node)
+            .first()
+            .map(|inner| js_url_parts(*inner, source))
+            .unwrap_or_default(),
+        _ => vec![UrlPart::Dynamic(parameter_name(&text(node, source)))],
+    }
+}
+
+/// Extract the HTTP calls of a Python AST: `requests` and `httpx`, their sessions and
+/// clients (with their `base_url`), and clients named like one.
+pub fn extract_python_http_calls(
+    root: Node,
+    source: &str,
+    imports: &[Import],
+) -> Vec<ExtractedFrameworkPattern> {
+    let modules: HashMap<&str, &str> = imports
+        .iter()
+        .filter(|import| import.name == import.source)
+        .filter(|import| matches!(import.source.as_str(), "requests" | "httpx"))
+        .map(|import| {
+            let local = import.alias.as_deref().unwrap_or(&import.name);
+            (local, import.source.as_str())
+        })
+        .collect();
+    if modules.is_empty() {
+        return Vec::new();
+    }
+
+    // session = requests.Session(), with httpx.Client(base_url=...) as client
+    let mut clients: HashMap<String, (&str, String)> = HashMap::new();
+    walk(root, &mut |node| {
+        let (name, value) = match node.kind() {
+            "assignment" => (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ),
+            "as_pattern" => (
+                node.child_by_field_name("alias"),
+                named_children(node).first().copied(),
+            ),
+            _ => return,
+        };
+        let (Some(name), Some(value)) = (name, value) else {
+            return;
+        };
+        let Some((receiver, constructor)) = python_method_call(value, source) else {
+            return;
+        };
+        let Some(&module) = modules.get(receiver.as_str()) else {
+            return;
+        };
+        if !matches!(constructor.as_str(), "Session" | "Client" | "AsyncClient") {
+            return;
+        }
+        let base = python_keyword(value, "base_url", source)
+            .and_then(|url| url_path(&python_url_parts(url, source)))
+            .unwrap_or_default();
+        clients.insert(text(name, source), (module, base));
+    });
+
+    let mut calls = Vec::new();
+    walk(root, &mut |node| {
+        if node.kind() != "call" {
+            return;
+        }
+        let Some((receiver, method)) = python_method_call(node, source) else {
+            return;
+        };
+        let (framework, base) = if let Some(&module) = modules.get(receiver.as_str()) {
+            (module, String::new())
+        } else if let Some((module, base)) = clients.get(&receiver) {
+            (*module, base.clone())
+        } else if is_client_name(&receiver) {
+            ("http", String::new())
+        } else {
+            return;
+        };
+        let arguments = node
+            .child_by_field_name("arguments")
+            .map(named_children)
+            .unwrap_or_default();
+        let positional: Vec<Node> = arguments
+            .iter()
+            .copied()
+            .filter(|a| a.kind() != "keyword_argument")
+            .collect();
+        let (http_method, url) = if METHOD_CALLS.contains(&method.as_str()) {
+            let url = positional
+                .first()
+                .copied()
+                .or_else(|| python_keyword(node, "url", source));
+            (method, url)
+        } else if method == "request" {
+            let Some(http_method) = positional
+                .first()
+                .copied()
+                .or_else(|| python_keyword(node, "method", source))
+                .and_then(|m| python_string(m, source))
+            else {
+                return;
+            };
+            let url = positional
+                .get(1)
+                .copied()
+                .or_else(|| python_keyword(node, "url", source));
+            (http_method, url)
+        } else {
+            return;
+        };
+        let Some(path) = url.and_then(|url| url_path(&python_url_parts(url, source))) else {
+            return;
+        };
+        let path = if base.is_empty() {
+            path
+        } else {
+            super::routers::join_paths(&base, &path)
+        };
+        calls.push(http_call(
+            node,
+            framework,
+            &http_method,
+            path,
+            Some(receiver),
+        ));
+    });
+    calls.sort_by_key(|c| (c.line, c.column));
+    calls
+}
+
+fn python_method_call(call: Node, source: &str) -> Option<(String, String)> {
+    if call.kind() != "call" {
+        return None;
+    }
+    let function = call
+        .child_by_field_name("function")
+        .filter(|f| f.kind() == "attribute")?;
+    Some((
+        text(function.child_by_field_name("object")?, source),
+        text(function.child_by_field_name("attribute")?, source),
+    ))
+}
+
+fn python_keyword<'a>(call: Node<'a>, name: &str, source: &str) -> Option<Node<'a>> {
+    let arguments = call.child_by_field_name("arguments")?;
+    named_children(arguments).into_iter().find_map(|argument| {
+        (argument.kind() == "keyword_argument"
+            && argument
+                .child_by_field_name("name")
+                .is_some_and(|n| text(n, source) == name))
+        .then(|| argument.child_by_field_name("value"))
+        .flatten()
+    })
+}
+
+/// The value of a string literal without its prefix and quotes, and whether it is an
+/// f-string.
+fn python_literal(node: Node, source: &str) -> Option<(String, bool)> {
+    if node.kind() != "string" {
+        return None;
+    }
+    let literal = text(node, source);
+    let unprefixed = literal.trim_start_matches(|c: char| "rRbBuUfF".contains(c));
+    let prefix = &literal[..literal.len() - unprefixed.len()];
+    let formatted = prefix.contains(['f', 'F']);
+    ["\"\"\"", "'''", "\"", "'"].iter().find_map(|quote| {
+        unprefixed
+            .strip_prefix(quote)
+            .and_then(|l| l.strip_suffix(quote))
+            .map(|inner| (inner.to_string(), formatted))
+    })
+}
+
+fn python_string(node: Node, source: &str) -> Option<String> {
+    python_literal(node, source).map(|(value, _)| value)
+}
+
+fn python_url_parts(node: Node, source: &str) -> Vec<UrlPart> {
+    match node.kind() {
+        // "/users/{}".format(id) fills its placeholders the way an f-string does
+        "string" => match python_literal(node, source) {
+            Some((value, _)) => interpolated_parts(&value, false),
+            None => Vec::new(),
+        },
+        "call" => match python_method_call(node, source) {
+            Some((_, method)) if method == "format" => node
+                .child_by_field_name("function")
+                .and_then(|f| f.child_by_field_name("object"))
+                .map(|template| python_url_parts(template, source))
+                .unwrap_or_default(),
+            _ => vec![UrlPart::Dynamic(String::new())],
+        },
+        "binary_operator" => {
+            let (Some(left), Some(right)) = (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ) else {
+                return Vec::new();
+            };
+            let mut parts = python_url_parts(left, source);
+            parts.extend(python_url_parts(right, source));
+            parts
+        }
+        "parenthesized_expression" => named_children(node)
+            .first()
+            .map(|inner| python_url_parts(*inner, source))
+            .unwrap_or_default(),
+        _ => vec![UrlPart::Dynamic(parameter_name(&text(node, source)))],
+    }
+}
+
+/// Extract the HTTP calls of a Go AST: requests built with `http.NewRequest` and
+/// `http.NewRequestWithContext`, and `http.Get`, `http.Post` and the like, on the
+/// package or on a client.
+pub fn extract_go_http_calls(
+    root: Node,
+    source: &str,
+    imports: &[Import],
+) -> Vec<ExtractedFrameworkPattern> {
+    let Some(http) = imports.iter().find(|import| import.source == "net/http") else {
+        return Vec::new();
+    };
+    let http = http.alias.as_deref().unwrap_or(&http.name);
+
+    let mut calls = Vec::new();
+    walk(root, &mut |node| {
+        if node.kind() != "call_expression" {
+            return;
+        }
+        let Some(function) = node
+            .child_by_field_name("function")
+            .filter(|f| f.kind() == "selector_expression")
+        else {
+            return;
+        };
+        let (Some(receiver), Some(method)) = (
+            function.child_by_field_name("operand"),
+            function.child_by_field_name("field"),
+        ) else {
+            return;
+        };
+        let receiver = text(receiver, source);
+        let method = text(method, source);
+        let arguments = node
+            .child_by_field_name("arguments")
+            .map(named_children)
+            .unwrap_or_default();
+        let on_package = receiver == http;
+        let on_client = receiver.starts_with(&format!("{http}.")) || is_client_name(&receiver);
+
+        let (http_method, url) = match method.as_str() {
+            "NewRequest" if on_package => (arguments.first(), arguments.get(1)),
+            "NewRequestWithContext" if on_package => (arguments.get(1), arguments.get(2)),
+            "Get" | "Head" | "Post" | "PostForm" if on_package || on_client => {
+                (None, arguments.first())
+            }
+            _ => return,
+        };
+        let http_method = match http_method {
+            Some(method) => go_method(*method, source, http),
+            None => Some(method.trim_end_matches("Form").to_ascii_uppercase()),
+        };
+        let (Some(http_method), Some(url)) = (http_method, url) else {
+            return;
+        };
+        if let Some(path) = url_path(&go_url_parts(*url, source)) {
+            calls.push(http_call(
+                node,
+                "net/http",
+                &http_method,
+                path,
+                Some(receiver),
+            ));
+        }
+    });
+    calls.sort_by_key(|c| (c.line, c.column));
+    calls
+}
+
+/// The method a request is built with: `"GET"` or `http.MethodGet`, and any method
+/// when it is not spelled out.
+fn go_method(node: Node, source: &str, http: &str) -> Option<String> {
+    let method = text(node, source);
+    if let Some(literal) = go_string(node, source) {
+        return Some(literal.to_ascii_uppercase());
+    }
+    Some(
+        method
+            .strip_prefix(&format!("{http}.Method"))
+            .map_or_else(|| "ALL".to_string(), str::to_ascii_uppercase),
+    )
+}
+
+fn go_string(node: Node, source: &str) -> Option<String> {
+    match node.kind() {
+        "interpreted_string_literal" => Some(text(node, source).trim_matches('"').to_string()),
+        "raw_string_literal" => Some(text(node, source).trim_matches('`').to_string()),
+        _ => None,
+    }
+}
+
+fn go_url_parts(node: Node, source: &str) -> Vec<UrlPart> {
+    if let Some(literal) = go_string(node, source) {
+        return vec![UrlPart::Literal(literal)];
+    }
+    match node.kind() {
+        "binary_expression" => {
+            let (Some(left), Some(right)) = (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ) else {
+                return Vec::new();
+            };
+            let mut parts = go_url_parts(left, source);
+            parts.extend(go_url_parts(right, source));
+            parts
+        }
+        // fmt.Sprintf("%s/users/%d", base, id)
+        "call_expression"
+            if node
+                .child_by_field_name("function")
+                .is_some_and(|f| text(f, source).ends_with(".Sprintf")) =>
+        {
+            node.child_by_field_name("arguments")
+                .and_then(|arguments| named_children(arguments).first().copied())
+                .and_then(|format| go_string(format, source))
+                .map(|format| format_parts(&format))
+                .unwrap_or_default()
+        }
+        "parenthesized_expression" => named_children(node)
+            .first()
+            .map(|inner| go_url_parts(*inner, source))
+            .unwrap_or_default(),
+        _ => vec![UrlPart::Dynamic(parameter_name(&text(node, source)))],
+    }
+}
+
+/// The parts of a `Printf` format, with each verb spliced in.
+fn format_parts(format: &str) -> Vec<UrlPart> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            literal.push('%');
+            continue;
+        }
+        // Flags, width and precision, then the verb
+        while chars
+            .peek()
+            .is_some_and(|c| !c.is_ascii_alphabetic() && *c != '%')
+        {
+            chars.next();
+        }
+        chars.next();
+        if !literal.is_empty() {
+            parts.push(UrlPart::Literal(std::mem::take(&mut literal)));
+        }
+        parts.push(UrlPart::Dynamic(String::new()));
+    }
+    if !literal.is_empty() {
+        parts.push(UrlPart::Literal(literal));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::extract::go::extract_go_symbols;
+    use crate::indexer::extract::python::extract_python_symbols;
+    use crate::indexer::extract::typescript::extract_typescript_symbols;
+    use crate::indexer::parser::LanguageId;
+
+    fn calls(patterns: Vec<ExtractedFrameworkPattern>) -> Vec<(String, String, String)> {
+        patterns
+            .into_iter()
+            .filter(|p| p.kind == FrameworkPatternKind::HttpCall)
+            .map(|p| (p.framework, p.http_method.unwrap(), p.path.unwrap()))
+            .collect()
+    }
+
+    fn call(framework: &str, method: &str, path: &str) -> (String, String, String) {
+        (framework.into(), method.into(), path.into())
+    }
+
+    #[test]
+    fn url_paths_leave_out_base_urls_and_splice_in_parameters() {
+        let literal = |text: &str| UrlPart::Literal(text.to_string());
+        let dynamic = |name: &str| UrlPart::Dynamic(name.to_string());
+        assert_eq!(
+            url_path(&[literal("/api/users/")]),
+            Some("/api/users".into())
+        );
+        assert_eq!(
+            url_path(&[dynamic("API_URL"), literal("/users/"), dynamic("id")]),
+            Some("/users/{id}".into())
+        );
+        assert_eq!(
+            url_path(&[literal(
+                "https://billing.internal:8080/v1/invoices?paid=true"
+            )]),
+            Some("/v1/invoices".into())
+        );
+        assert_eq!(
+            url_path(&[literal("/users/"), dynamic(""), literal("/posts")]),
+            Some("/users/{}/posts".into())
+        );
+        assert_eq!(url_path(&[literal("users")]), None);
+        assert_eq!(url_path(&[dynamic("url")]), None);
+        assert_eq!(
+            interpolated_parts("${base}/users/${user.id}", true),
+            vec![dynamic("base"), literal("/users/"), dynamic("user.id")]
+        );
+        assert_eq!(
+            format_parts("%s/users/%d/100%%"),
+            vec![
+                dynamic(""),
+                literal("/users/"),
+                dynamic(""),
+                literal("/100%")
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_fetch_axios_and_named_clients() {
+        let source = r#"
+import axios from 'axios';
+import { api } from './api';
+
+const billing = axios.create({ baseURL: 'https://billing.internal/v1' });
+
+export async function loadUser(id: string) {
+  await fetch(`${API_URL}/api/users/${id}`);
+  await fetch('/api/users', { method: 'POST', body: '{}' });
+  await axios.delete('/api/users/' + id);
+  await axios({ method: 'put', url: `/api/users/${id}` });
+  await billing.get('/invoices');
+  await api.patch(`/api/users/${id}/settings`);
+  await router.get('/not/a/call');
+  await fetch(url);
+}
+"#;
+        assert_eq!(
+            calls(
+                extract_typescript_symbols(LanguageId::Typescript, source)
+                    .unwrap()
+                    .framework_patterns
+            ),
+            vec![
+                call("fetch", "GET", "/api/users/{id}"),
+                call("fetch", "POST", "/api/users"),
+                call("axios", "DELETE", "/api/users/{id}"),
+                call("axios", "PUT", "/api/users/{id}"),
+                call("axios", "GET", "/v1/invoices"),
+                call("http", "PATCH", "/api/users/{id}/settings"),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_requests_and_httpx_calls() {
+        let source = r#"
+import requests
+import httpx
+
+BASE = "http://users:8000"
+
+def invite(user_id):
+    requests.post(f"{BASE}/api/users/{user_id}/invite", json={})
+    requests.request("DELETE", BASE + "/api/users/" + str(user_id))
+    with httpx.Client(base_url="http://billing/v1") as client:
+        client.get("/invoices/{}".format(user_id))
+    session = requests.Session()
+    session.put(url=f"{BASE}/api/users/{user_id}")
+    cache.get("/not/a/call")
+"#;
+        assert_eq!(
+            calls(extract_python_symbols(source).unwrap().framework_patterns),
+            vec![
+                call("requests", "POST", "/api/users/{user_id}/invite"),
+                call("requests", "DELETE", "/api/users/{}"),
+                call("httpx", "GET", "/v1/invoices/{}"),
+                call("requests", "PUT", "/api/users/{user_id}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_go_requests() {
+        let source = r#"
+package client
+
+import (
+	"fmt"
+	"net/http"
+)
+
+func (c *Client) GetUser(ctx context.Context, id int) error {
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, fmt.Sprintf("%s/api/users/%d", c.baseURL, id), nil)
+	_, _ = http.NewRequest("DELETE", c.baseURL+"/api/users/"+strconv.Itoa(id), nil)
+	_, _ = c.httpClient.Post(c.baseURL+"/api/users", "application/json", nil)
+	_, _ = http.Get("https://example.com/health")
+	return err
+}
+"#;
+        assert_eq!(
+            calls(extract_go_symbols(source).unwrap().framework_patterns),
+            vec![
+                call("net/http", "GET", "/api/users/{}"),
+                call("net/http", "DELETE", "/api/users/{}"),
+                call("net/http", "POST", "/api/users"),
+                call("net/http", "GET", "/health"),
+            ]
+        );
+    }
+}
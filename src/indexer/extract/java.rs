@@ -2,6 +2,7 @@ use crate::indexer::parser::{parser_for_id, LanguageId};
 use anyhow::{anyhow, Result};
 use tree_sitter::{Node, Parser, TreeCursor};
 
+use super::spring::extract_spring_routes;
 use super::symbol::{ByteSpan, ExtractedFile, ExtractedSymbol, Import, LineSpan, SymbolKind};
 
 pub fn extract_java_symbols(source: &str) -> Result<ExtractedFile> {
@@ -67,6 +68,7 @@ fn extract_symbols_with_parser(parser: &mut Parser, source: &str) -> Result<Extr
     });
 
     symbols.sort_by_key(|s| s.bytes.start);
+    let framework_patterns = extract_spring_routes(root, source);
     Ok(ExtractedFile {
         symbols,
         imports,
@@ -75,7 +77,7 @@ fn extract_symbols_with_parser(parser: &mut Parser, source: &str) -> Result<Extr
         todos: Vec::new(),
         jsdoc_entries: Vec::new(),
         decorators: Vec::new(),
-        framework_patterns: Vec::new(),
+        framework_patterns,
     })
 }
 
@@ -137,21 +139,45 @@ fn extract_import(node: Node, source: &str, imports: &mut Vec<Import>) {
     // import_declaration: import (static)? name ;
     // name is usually a scoped_identifier or identifier
     let mut cursor = node.walk();
-    for child in node.children(&mut cursor) {
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+    let wildcard = children.iter().any(|c| c.kind() == "asterisk");
+    for child in children {
         if child.kind() == "scoped_identifier" || child.kind() == "identifier" {
             let name = child.utf8_text(source.as_bytes()).unwrap().to_string();
             // Java imports are usually full package paths
             // We can treat the full path as source
             // And the last part as name (unless it's import static or *)
+            if wildcard {
+                imports.push(jvm_wildcard_import(&name));
+            } else {
+                imports.push(jvm_import(&name, None));
+            }
+        }
+    }
+}
 
-            let last_part = name.split('.').next_back().unwrap_or(&name).to_string();
+/// An import of the fully qualified `path`, bound to its last segment. Kotlin and
+/// Scala imports are recorded the same way, so they resolve against Java symbols and
+/// the other way around.
+pub(crate) fn jvm_import(path: &str, alias: Option<String>) -> Import {
+    let name = path.split('.').next_back().unwrap_or(path).to_string();
+    Import {
+        name,
+        source: path.to_string(),
+        alias,
+    }
+}
 
-            imports.push(Import {
-                name: last_part,
-                source: name,
-                alias: None,
-            });
-        }
+/// Name of the import of everything in a package (`import a.b.*`), whose source
+/// is the package.
+pub(crate) const WILDCARD_IMPORT: &str = "*";
+
+/// An import of everything in `package`.
+pub(crate) fn jvm_wildcard_import(package: &str) -> Import {
+    Import {
+        name: WILDCARD_IMPORT.to_string(),
+        source: package.trim_end_matches('.').to_string(),
+        alias: None,
     }
 }
 
@@ -159,6 +185,18 @@ fn extract_import(node: Node, source: &str, imports: &mut Vec<Import>) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_extract_java_wildcard_import() {
+        let source = "package com.example;\n\nimport java.util.*;\nimport java.io.File;\n";
+        let extracted = extract_java_symbols(source).unwrap();
+        let imports: Vec<(&str, &str)> = extracted
+            .imports
+            .iter()
+            .map(|i| (i.name.as_str(), i.source.as_str()))
+            .collect();
+        assert_eq!(imports, [("*", "java.util"), ("File", "java.io.File")]);
+    }
+
     #[test]
     fn test_extract_java_symbols() {
         let source = r#"
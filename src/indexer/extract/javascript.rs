@@ -2,7 +2,8 @@ use crate::indexer::parser::{parser_for_id, LanguageId};
 use anyhow::{anyhow, Result};
 use tree_sitter::{Node, Parser, TreeCursor};
 
-use super::elysia::extract_elysia_patterns;
+use super::http_clients::extract_js_http_calls;
+use super::routers::extract_web_patterns;
 use super::symbol::{ByteSpan, ExtractedFile, ExtractedSymbol, Import, LineSpan, SymbolKind};
 
 pub fn extract_javascript_symbols(source: &str) -> Result<ExtractedFile> {
@@ -64,8 +65,10 @@ fn extract_symbols_with_parser(parser: &mut Parser, source: &str) -> Result<Extr
 
     symbols.sort_by_key(|s| s.bytes.start);
 
-    // Extract Elysia framework patterns
-    let framework_patterns = extract_elysia_patterns(root, source);
+    // Extract web framework routes
+    let mut framework_patterns = extract_web_patterns(root, source, &imports);
+    framework_patterns.extend(extract_js_http_calls(root, source, &imports));
+    framework_patterns.sort_by_key(|p| (p.line, p.column));
 
     Ok(ExtractedFile {
         symbols,
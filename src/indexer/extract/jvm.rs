@@ -0,0 +1,1224 @@
+//! Kotlin and Scala symbol extraction
+//!
+//! Neither language has a tree-sitter grammar bundled, so both share one lexer and a
+//! pass over brace nesting (plus indentation, for Scala 3 bodies opened by `:`).
+//! Declarations map onto the kinds `java.rs` emits: classes and objects are classes,
+//! Kotlin interfaces and Scala traits are interfaces, and `fun`/`def` are functions.
+//! Imports are recorded the way Java's are, so names resolve across all three.
+
+use anyhow::Result;
+
+use super::java::{jvm_import, jvm_wildcard_import};
+use super::symbol::{ByteSpan, ExtractedFile, ExtractedSymbol, Import, LineSpan, SymbolKind};
+
+pub fn extract_kotlin_symbols(source: &str) -> Result<ExtractedFile> {
+    Ok(extracted_file(parse(source, Dialect::Kotlin)))
+}
+
+pub fn extract_scala_symbols(source: &str) -> Result<ExtractedFile> {
+    Ok(extracted_file(parse(source, Dialect::Scala)))
+}
+
+/// `(extends, implements, aliases)` of the Kotlin type declared at the start of
+/// `text`. A base with constructor arguments (`: Base(), Listener`) is the
+/// superclass; the others are interfaces.
+pub fn kotlin_type_relations(text: &str) -> (Vec<String>, Vec<String>, Vec<String>) {
+    type_relations(parse(text, Dialect::Kotlin))
+}
+
+/// `(extends, implements, aliases)` of the Scala type declared at the start of
+/// `text`: the first parent after `extends` is extended and `with` mixins are
+/// implemented.
+pub fn scala_type_relations(text: &str) -> (Vec<String>, Vec<String>, Vec<String>) {
+    type_relations(parse(text, Dialect::Scala))
+}
+
+fn extracted_file(parsed: ParsedJvm) -> ExtractedFile {
+    ExtractedFile {
+        symbols: parsed.symbols,
+        imports: parsed.imports,
+        type_edges: Vec::new(),
+        dataflow_edges: Vec::new(),
+        todos: Vec::new(),
+        jsdoc_entries: Vec::new(),
+        decorators: Vec::new(),
+        framework_patterns: Vec::new(),
+    }
+}
+
+fn type_relations(parsed: ParsedJvm) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let mut extends = Vec::new();
+    let mut implements = Vec::new();
+    for relation in parsed.relations.iter().filter(|r| r.symbol == 0) {
+        match relation.kind {
+            RelationKind::Extends => extends.push(relation.target.clone()),
+            RelationKind::Implements => implements.push(relation.target.clone()),
+        }
+    }
+    (extends, implements, Vec::new())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dialect {
+    Kotlin,
+    Scala,
+}
+
+impl Dialect {
+    fn is_modifier(self, word: &str) -> bool {
+        match self {
+            Dialect::Kotlin => KOTLIN_MODIFIERS.contains(&word),
+            Dialect::Scala => SCALA_MODIFIERS.contains(&word),
+        }
+    }
+}
+
+const KOTLIN_MODIFIERS: &[&str] = &[
+    "public",
+    "private",
+    "protected",
+    "internal",
+    "open",
+    "final",
+    "abstract",
+    "sealed",
+    "data",
+    "value",
+    "inner",
+    "enum",
+    "annotation",
+    "companion",
+    "override",
+    "inline",
+    "infix",
+    "operator",
+    "suspend",
+    "tailrec",
+    "external",
+    "const",
+    "lateinit",
+    "expect",
+    "actual",
+];
+
+const SCALA_MODIFIERS: &[&str] = &[
+    "private",
+    "protected",
+    "final",
+    "abstract",
+    "sealed",
+    "case",
+    "implicit",
+    "lazy",
+    "override",
+    "open",
+    "inline",
+    "transparent",
+    "opaque",
+    "infix",
+];
+
+const PUNCTUATION: &[&str] = &[
+    "=>", "->", "<-", "::", "?.", "?:", "!!", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=",
+    "-=", "*=", "/=", "..",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Ident,
+    Str,
+    Num,
+    Punct,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+    line: u32,
+    /// Indentation of the line the token is on
+    indent: u32,
+    first_on_line: bool,
+}
+
+impl Token<'_> {
+    fn is_punct(&self, text: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == text
+    }
+
+    fn is_ident(&self, text: &str) -> bool {
+        self.kind == TokenKind::Ident && self.text == text
+    }
+}
+
+fn is_ident_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b >= 0x80
+}
+
+fn is_ident_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+/// Index just past the `"` string starting at `i`, including `${...}` templates.
+fn skip_string(bytes: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            b'$' if bytes.get(i + 1) == Some(&b'{') => i = skip_template(bytes, i + 2),
+            b'\n' => return i,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Index just past the `"""` string starting at `i`. Extra quotes before the
+/// closing three belong to the string.
+fn skip_raw_string(bytes: &[u8], i: usize) -> usize {
+    let mut j = i + 3;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'"' => {
+                let run = bytes[j..].iter().take_while(|&&b| b == b'"').count();
+                if run >= 3 {
+                    return j + run;
+                }
+                j += run;
+            }
+            b'$' if bytes.get(j + 1) == Some(&b'{') => j = skip_template(bytes, j + 2),
+            _ => j += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Index just past the `}` closing a template whose body starts at `i`.
+fn skip_template(bytes: &[u8], mut i: usize) -> usize {
+    let mut depth = 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => {
+                depth += 1;
+                i += 1;
+            }
+            b'}' => {
+                depth -= 1;
+                i += 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            b'"' if bytes[i..].starts_with(b"\"\"\"") => i = skip_raw_string(bytes, i),
+            b'"' => i = skip_string(bytes, i),
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Index just past the character literal at `i`, or `None` when the quote starts
+/// something else (a Scala symbol literal or quote).
+fn skip_char(source: &str, i: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    if bytes.get(i + 1) == Some(&b'\\') {
+        let close = source[i + 2..].find(['\'', '\n'])? + i + 2;
+        return (bytes[close] == b'\'').then_some(close + 1);
+    }
+    let len = source[i + 1..].chars().next()?.len_utf8();
+    (bytes.get(i + 1 + len) == Some(&b'\'')).then_some(i + 2 + len)
+}
+
+/// Index just past the (possibly nested) block comment starting at `i`.
+fn skip_block_comment(bytes: &[u8], i: usize) -> usize {
+    let mut depth = 0;
+    let mut j = i;
+    while j < bytes.len() {
+        if bytes[j..].starts_with(b"/*") {
+            depth += 1;
+            j += 2;
+        } else if bytes[j..].starts_with(b"*/") {
+            depth -= 1;
+            j += 2;
+            if depth == 0 {
+                return j;
+            }
+        } else {
+            j += 1;
+        }
+    }
+    bytes.len()
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1u32;
+    let mut indent = 0u32;
+    // Whether nothing but whitespace precedes `i` on its line
+    let mut at_line_start = true;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        let start = i;
+
+        if b == b'\n' {
+            line += 1;
+            indent = 0;
+            at_line_start = true;
+            i += 1;
+            continue;
+        }
+        if b == b' ' || b == b'\t' {
+            if at_line_start {
+                indent += 1;
+            }
+            i += 1;
+            continue;
+        }
+        if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if source[i..].starts_with("//") {
+            i = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+            continue;
+        }
+        if source[i..].starts_with("/*") {
+            let end = skip_block_comment(bytes, i);
+            let newlines = source[i..end].matches('\n').count() as u32;
+            if newlines > 0 {
+                line += newlines;
+                at_line_start = false;
+            }
+            i = end;
+            continue;
+        }
+
+        let token_line = line;
+        let (kind, text, end) = if b == b'"' {
+            let end = if source[i..].starts_with("\"\"\"") {
+                skip_raw_string(bytes, i)
+            } else {
+                skip_string(bytes, i)
+            };
+            line += source[i..end].matches('\n').count() as u32;
+            (TokenKind::Str, &source[i..end], end)
+        } else if let Some(end) = (b == b'\'').then(|| skip_char(source, i)).flatten() {
+            (TokenKind::Str, &source[i..end], end)
+        } else if b == b'`' {
+            // Backquoted identifiers
+            let end = source[i + 1..]
+                .find(['`', '\n'])
+                .map_or(bytes.len(), |n| i + 1 + n);
+            let text = &source[i + 1..end];
+            (TokenKind::Ident, text, (end + 1).min(bytes.len()))
+        } else if is_ident_start(b) {
+            let mut end = i + 1;
+            while end < bytes.len() && is_ident_char(bytes[end]) {
+                end += 1;
+            }
+            (TokenKind::Ident, &source[i..end], end)
+        } else if b.is_ascii_digit() {
+            let mut end = i + 1;
+            while end < bytes.len()
+                && (is_ident_char(bytes[end])
+                    || (bytes[end] == b'.' && bytes.get(end + 1).is_some_and(u8::is_ascii_digit)))
+            {
+                end += 1;
+            }
+            (TokenKind::Num, &source[i..end], end)
+        } else {
+            let len = PUNCTUATION
+                .iter()
+                .find(|p| source[i..].starts_with(**p))
+                .map_or_else(
+                    || source[i..].chars().next().map_or(1, char::len_utf8),
+                    |p| p.len(),
+                );
+            (TokenKind::Punct, &source[i..i + len], i + len)
+        };
+
+        tokens.push(Token {
+            kind,
+            text,
+            start,
+            end,
+            line: token_line,
+            indent,
+            first_on_line: at_line_start,
+        });
+        at_line_start = false;
+        i = end;
+    }
+
+    tokens
+}
+
+/// Whether the line starting at `tokens[i]` carries on the statement before it,
+/// because that one ends or this one starts with an operator or connective.
+fn continues_line(tokens: &[Token], i: usize) -> bool {
+    let token = &tokens[i];
+    let Some(prev) = i.checked_sub(1).map(|p| &tokens[p]) else {
+        return false;
+    };
+    let prev_open = match prev.kind {
+        TokenKind::Punct => !matches!(prev.text, ")" | "]" | ">" | "}" | "?" | "!!" | "*"),
+        TokenKind::Ident => matches!(
+            prev.text,
+            "with" | "extends" | "by" | "where" | "else" | "as"
+        ),
+        TokenKind::Str | TokenKind::Num => false,
+    };
+    let leading = match token.kind {
+        TokenKind::Punct => matches!(
+            token.text,
+            "." | "?." | "?:" | ":" | "=" | "=>" | "->" | "&&" | "||" | "," | ")" | "]" | ">"
+        ),
+        TokenKind::Ident => matches!(token.text, "with" | "extends" | "where" | "by"),
+        TokenKind::Str | TokenKind::Num => false,
+    };
+    prev_open || leading
+}
+
+/// Whether `tokens[i]` begins a statement (ignoring bracket nesting, which the
+/// caller tracks).
+fn starts_statement(tokens: &[Token], i: usize) -> bool {
+    let Some(prev) = i.checked_sub(1).map(|p| &tokens[p]) else {
+        return true;
+    };
+    if prev.kind == TokenKind::Punct && matches!(prev.text, "{" | "}" | ";") {
+        return true;
+    }
+    tokens[i].first_on_line && !continues_line(tokens, i)
+}
+
+/// Index of the token closing the bracket opened at `open_at`.
+fn matching(tokens: &[Token], open_at: usize, open: &str, close: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, t) in tokens.iter().enumerate().skip(open_at) {
+        if t.is_punct(open) {
+            depth += 1;
+        } else if t.is_punct(close) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelationKind {
+    Extends,
+    Implements,
+}
+
+#[derive(Debug, Clone)]
+struct Relation {
+    symbol: usize,
+    kind: RelationKind,
+    target: String,
+}
+
+#[derive(Default)]
+struct ParsedJvm {
+    symbols: Vec<ExtractedSymbol>,
+    imports: Vec<Import>,
+    relations: Vec<Relation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameKind {
+    /// File, package block or type body: statements are declarations
+    Declarations,
+    /// Function bodies, lambdas, initializers
+    Block,
+}
+
+struct Frame {
+    kind: FrameKind,
+    /// Symbol whose body this is
+    symbol: Option<usize>,
+    /// For a Scala 3 body opened by `:`, the indentation of its declaration
+    indent: Option<u32>,
+    /// `(`/`[` nesting in the current statement
+    depth: usize,
+    /// Declaration without a braced body and the indentation of its line; it runs
+    /// until a statement that is not indented past it
+    open: Option<(usize, u32)>,
+}
+
+impl Frame {
+    fn new(kind: FrameKind, symbol: Option<usize>, indent: Option<u32>) -> Self {
+        Self {
+            kind,
+            symbol,
+            indent,
+            depth: 0,
+            open: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Body {
+    /// `{` at the header's end index
+    Braces,
+    /// Scala 3 `:` followed by an indented block
+    Indented,
+    /// `= expression`
+    Expression,
+    None,
+}
+
+struct Declared {
+    name: String,
+    kind: SymbolKind,
+    exported: bool,
+    bases: Vec<(RelationKind, String)>,
+}
+
+struct Decl {
+    symbol: Option<Declared>,
+    /// Index of the `{` for braced bodies, else of the first token after the header
+    end: usize,
+    body: Body,
+    /// Whether statements in the body are declarations
+    body_kind: FrameKind,
+}
+
+fn parse(source: &str, dialect: Dialect) -> ParsedJvm {
+    let tokens = tokenize(source);
+    let mut out = ParsedJvm::default();
+    let mut stack = vec![Frame::new(FrameKind::Declarations, None, None)];
+    // First token of an indented body, which follows its `:` without a break
+    let mut body_start = None;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+
+        // Indented bodies end at the first line indented no further than their
+        // declaration, or with an `end` marker at the same indentation
+        if token.first_on_line {
+            let end_marker =
+                token.is_ident("end") && tokens.get(i + 1).is_some_and(|t| !t.first_on_line);
+            while stack.len() > 1
+                && stack
+                    .last()
+                    .and_then(|f| f.indent)
+                    .is_some_and(|indent| token.indent <= indent)
+                && !continues_line(&tokens, i)
+            {
+                let frame = stack.pop().expect("checked length");
+                let last = if end_marker && frame.indent == Some(token.indent) {
+                    let on_line = tokens[i..].iter().take_while(|t| t.line == token.line);
+                    on_line.last().expect("the marker is on its line")
+                } else {
+                    &tokens[i - 1]
+                };
+                close_frame(&mut out, frame, last);
+            }
+        }
+
+        let frame = stack.last_mut().expect("file frame is never popped");
+        if frame.depth == 0 && (starts_statement(&tokens, i) || body_start == Some(i)) {
+            if let Some((symbol, indent)) = frame.open {
+                if !token.first_on_line || token.indent <= indent {
+                    close_symbol(&mut out, symbol, &tokens[i - 1]);
+                    frame.open = None;
+                }
+            }
+            if frame.kind == FrameKind::Declarations && frame.open.is_none() {
+                if let Some(decl) = read_declaration(&tokens, i, dialect, &mut out.imports) {
+                    let symbol = decl
+                        .symbol
+                        .map(|declared| push_symbol(&mut out, source, token, declared));
+                    match decl.body {
+                        Body::Braces => {
+                            stack.push(Frame::new(decl.body_kind, symbol, None));
+                            i = decl.end + 1;
+                        }
+                        Body::Indented => {
+                            stack.push(Frame::new(decl.body_kind, symbol, Some(token.indent)));
+                            body_start = Some(decl.end);
+                            i = decl.end;
+                        }
+                        Body::Expression | Body::None => {
+                            if let Some(symbol) = symbol {
+                                frame.open = Some((symbol, token.indent));
+                                // Until something follows, the header is all there is
+                                close_symbol(&mut out, symbol, &tokens[decl.end - 1]);
+                            }
+                            i = decl.end;
+                        }
+                    }
+                    continue;
+                }
+            }
+        }
+
+        let frame = stack.last_mut().expect("file frame is never popped");
+        if token.kind == TokenKind::Punct {
+            match token.text {
+                "{" => stack.push(Frame::new(FrameKind::Block, None, None)),
+                "}" => {
+                    // Indented bodies inside the braces end with them
+                    while stack.len() > 1 && stack.last().is_some_and(|f| f.indent.is_some()) {
+                        let frame = stack.pop().expect("checked length");
+                        close_frame(&mut out, frame, &tokens[i - 1]);
+                    }
+                    if stack.len() > 1 {
+                        let frame = stack.pop().expect("checked length");
+                        if let Some((symbol, _)) = frame.open {
+                            close_symbol(&mut out, symbol, &tokens[i - 1]);
+                        }
+                        if let Some(symbol) = frame.symbol {
+                            close_symbol(&mut out, symbol, token);
+                        }
+                    }
+                }
+                "(" | "[" => frame.depth += 1,
+                ")" | "]" => frame.depth = frame.depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+
+    if let Some(last) = tokens.last() {
+        while let Some(frame) = stack.pop() {
+            close_frame(&mut out, frame, last);
+        }
+    }
+    out
+}
+
+fn push_symbol(out: &mut ParsedJvm, source: &str, first: &Token, declared: Declared) -> usize {
+    let symbol = out.symbols.len();
+    out.symbols.push(ExtractedSymbol {
+        name: declared.name,
+        kind: declared.kind,
+        exported: declared.exported,
+        bytes: ByteSpan {
+            start: first.start,
+            end: source.len(),
+        },
+        lines: LineSpan {
+            start: first.line,
+            end: first.line,
+        },
+    });
+    for (kind, target) in declared.bases {
+        out.relations.push(Relation {
+            symbol,
+            kind,
+            target,
+        });
+    }
+    symbol
+}
+
+fn close_symbol(out: &mut ParsedJvm, symbol: usize, token: &Token) {
+    let sym = &mut out.symbols[symbol];
+    sym.bytes.end = token.end;
+    sym.lines.end = token.line;
+}
+
+/// Close the symbol of a frame that ends just after `last`, and its open declaration.
+fn close_frame(out: &mut ParsedJvm, frame: Frame, last: &Token) {
+    if let Some((symbol, _)) = frame.open {
+        close_symbol(out, symbol, last);
+    }
+    if let Some(symbol) = frame.symbol {
+        close_symbol(out, symbol, last);
+    }
+}
+
+/// Read the declaration starting at `tokens[i]`, if the statement is one. Imports
+/// are added to `imports` directly.
+fn read_declaration(
+    tokens: &[Token],
+    i: usize,
+    dialect: Dialect,
+    imports: &mut Vec<Import>,
+) -> Option<Decl> {
+    let mut j = skip_annotations(tokens, i);
+    let mut modifiers = Vec::new();
+    while let Some(t) = tokens.get(j) {
+        if t.kind != TokenKind::Ident || !dialect.is_modifier(t.text) {
+            break;
+        }
+        // A modifier word used as a name (`val data = ...`, `case Red`)
+        if !tokens
+            .get(j + 1)
+            .is_some_and(|n| n.kind == TokenKind::Ident || n.is_punct("["))
+        {
+            break;
+        }
+        modifiers.push(t.text);
+        j += 1;
+        // Scala qualified access: `private[billing]`
+        if tokens.get(j).is_some_and(|t| t.is_punct("[")) {
+            j = matching(tokens, j, "[", "]")? + 1;
+        }
+        j = skip_annotations(tokens, j);
+    }
+    let keyword = tokens.get(j)?;
+    if keyword.kind != TokenKind::Ident {
+        return None;
+    }
+    let exported = !modifiers
+        .iter()
+        .any(|m| matches!(*m, "private" | "protected" | "internal"));
+
+    let kind = match (dialect, keyword.text) {
+        (_, "import") => {
+            let end = read_imports(tokens, j + 1, dialect, imports);
+            return Some(Decl {
+                symbol: None,
+                end,
+                body: Body::None,
+                body_kind: FrameKind::Declarations,
+            });
+        }
+        (_, "package") => {
+            // `package object billing`, `package billing { ... }` or a package clause
+            let mut k = j + 1;
+            if tokens.get(k).is_some_and(|t| t.is_ident("object")) {
+                k += 1;
+            }
+            let mut decl = read_header(tokens, k, dialect);
+            decl.body_kind = FrameKind::Declarations;
+            return Some(decl);
+        }
+        (Dialect::Kotlin, "class") if modifiers.contains(&"enum") => SymbolKind::Enum,
+        (_, "class") => SymbolKind::Class,
+        (Dialect::Kotlin, "interface") | (Dialect::Scala, "trait") => SymbolKind::Interface,
+        (_, "object") => SymbolKind::Class,
+        (Dialect::Scala, "enum") => SymbolKind::Enum,
+        (Dialect::Kotlin, "fun") if tokens.get(j + 1).is_some_and(|t| t.is_ident("interface")) => {
+            j += 1;
+            SymbolKind::Interface
+        }
+        (Dialect::Kotlin, "fun") | (Dialect::Scala, "def") => SymbolKind::Function,
+        (Dialect::Kotlin, "typealias") | (Dialect::Scala, "type") => SymbolKind::TypeAlias,
+        (Dialect::Kotlin, "val") if modifiers.contains(&"const") => SymbolKind::Const,
+        _ => return None,
+    };
+
+    let (name_at, body_kind) = match kind {
+        SymbolKind::Function => (function_name(tokens, j, dialect)?, FrameKind::Block),
+        SymbolKind::Const => (j + 1, FrameKind::Block),
+        _ => (j + 1, FrameKind::Declarations),
+    };
+    let name = tokens.get(name_at).filter(|t| {
+        t.kind == TokenKind::Ident && !t.first_on_line && !matches!(t.text, "this" | "extends")
+    });
+    let Some(name) = name else {
+        // `companion object { ... }`: its members belong to the enclosing type
+        if keyword.is_ident("object") {
+            let mut decl = read_header(tokens, j + 1, dialect);
+            decl.body_kind = body_kind;
+            return Some(decl);
+        }
+        return None;
+    };
+
+    let mut decl = read_header(tokens, name_at + 1, dialect);
+    if modifiers.contains(&"companion") {
+        decl.body_kind = body_kind;
+        return Some(decl);
+    }
+    let bases = match kind {
+        SymbolKind::Class | SymbolKind::Interface | SymbolKind::Enum => {
+            read_bases(&tokens[name_at + 1..decl.end], dialect, kind)
+        }
+        _ => Vec::new(),
+    };
+    decl.symbol = Some(Declared {
+        name: name.text.to_string(),
+        kind,
+        exported,
+        bases,
+    });
+    decl.body_kind = body_kind;
+    Some(decl)
+}
+
+/// Index of the first token after any annotations starting at `i`.
+fn skip_annotations(tokens: &[Token], mut i: usize) -> usize {
+    while tokens.get(i).is_some_and(|t| t.is_punct("@")) {
+        let mut j = i + 1;
+        // Use-site targets: `@field:Json`, `@file:JvmName`
+        if tokens.get(j + 1).is_some_and(|t| t.is_punct(":")) {
+            j += 2;
+        }
+        // A qualified name: identifiers joined by dots
+        while tokens.get(j).is_some_and(|t| t.kind == TokenKind::Ident) {
+            j += 1;
+            if !tokens.get(j).is_some_and(|t| t.is_punct(".")) {
+                break;
+            }
+            j += 1;
+        }
+        if tokens.get(j).is_some_and(|t| t.is_punct("[")) {
+            j = matching(tokens, j, "[", "]").map_or(j, |close| close + 1);
+        }
+        if tokens
+            .get(j)
+            .is_some_and(|t| t.is_punct("(") && !t.first_on_line)
+        {
+            j = matching(tokens, j, "(", ")").map_or(j, |close| close + 1);
+        }
+        if j == i + 1 {
+            break;
+        }
+        i = j;
+    }
+    i
+}
+
+/// Index of a function's name, given the index of `fun`/`def`. Kotlin names come
+/// after type parameters and an extension receiver (`fun <T> List<T>.second()`).
+fn function_name(tokens: &[Token], keyword: usize, dialect: Dialect) -> Option<usize> {
+    if dialect == Dialect::Scala {
+        return Some(keyword + 1);
+    }
+    let mut depth = 0i32;
+    for (n, t) in tokens.iter().enumerate().skip(keyword + 1) {
+        match t.text {
+            "<" => depth += 1,
+            ">" => depth -= 1,
+            "(" if depth == 0 => return n.checked_sub(1).filter(|&p| p > keyword),
+            "{" | "}" | "=" | ";" => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Find where a declaration header starting at `i` ends and what body follows.
+fn read_header(tokens: &[Token], i: usize, dialect: Dialect) -> Decl {
+    let header = |end: usize, body: Body| Decl {
+        symbol: None,
+        end,
+        body,
+        body_kind: FrameKind::Block,
+    };
+    let mut depth = 0usize;
+    for k in i..tokens.len() {
+        let t = &tokens[k];
+        if depth == 0 {
+            if t.first_on_line && k > i && starts_statement(tokens, k) {
+                return header(k, Body::None);
+            }
+            match t.text {
+                "{" if t.kind == TokenKind::Punct => return header(k, Body::Braces),
+                "=" if t.kind == TokenKind::Punct => return header(k + 1, Body::Expression),
+                ";" if t.kind == TokenKind::Punct => return header(k + 1, Body::None),
+                "}" if t.kind == TokenKind::Punct => return header(k, Body::None),
+                ":" if dialect == Dialect::Scala
+                    && t.kind == TokenKind::Punct
+                    && tokens.get(k + 1).is_none_or(|n| n.first_on_line) =>
+                {
+                    return header(k + 1, Body::Indented);
+                }
+                _ => {}
+            }
+        }
+        if t.kind == TokenKind::Punct {
+            match t.text {
+                "(" | "[" | "<" => depth += 1,
+                ")" | "]" | ">" => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+    header(tokens.len(), Body::None)
+}
+
+/// Supertypes in a type header (the tokens after its name). Only the last segment
+/// of each name is kept, without type arguments.
+fn read_bases(header: &[Token], dialect: Dialect, kind: SymbolKind) -> Vec<(RelationKind, String)> {
+    // The supertype list starts at a top-level `:` (Kotlin) or `extends` (Scala)
+    let mut depth = 0i32;
+    let mut start = None;
+    for (n, t) in header.iter().enumerate() {
+        match t.text {
+            "(" | "[" | "<" if t.kind == TokenKind::Punct => depth += 1,
+            ")" | "]" | ">" if t.kind == TokenKind::Punct => depth -= 1,
+            ":" if depth == 0 && dialect == Dialect::Kotlin && t.kind == TokenKind::Punct => {
+                start = Some(n + 1);
+                break;
+            }
+            "extends" if depth == 0 && dialect == Dialect::Scala && t.kind == TokenKind::Ident => {
+                start = Some(n + 1);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let Some(start) = start else {
+        return Vec::new();
+    };
+
+    // (name, has constructor arguments)
+    let mut parents: Vec<(String, bool)> = Vec::new();
+    let mut current: Option<(String, bool)> = None;
+    // Skipping a Kotlin `by delegate` expression
+    let mut delegating = false;
+    let mut depth = 0i32;
+    for t in &header[start..] {
+        match (t.kind, t.text) {
+            (TokenKind::Punct, "(") if depth == 0 => {
+                if let Some(current) = current.as_mut() {
+                    current.1 = true;
+                }
+                depth += 1;
+            }
+            (TokenKind::Punct, "(" | "[" | "<" | "{") => depth += 1,
+            (TokenKind::Punct, ")" | "]" | ">" | "}") => depth -= 1,
+            (TokenKind::Punct, ",") if depth == 0 => {
+                parents.extend(current.take());
+                delegating = false;
+            }
+            (TokenKind::Ident, "with") if depth == 0 => parents.extend(current.take()),
+            (TokenKind::Ident, "by") if depth == 0 => delegating = true,
+            (TokenKind::Ident, "where" | "derives") if depth == 0 => break,
+            (TokenKind::Ident, name) if depth == 0 && !delegating => {
+                // Qualified names keep their last segment
+                current = Some((name.to_string(), false));
+            }
+            _ => {}
+        }
+    }
+    parents.extend(current);
+
+    parents
+        .into_iter()
+        .enumerate()
+        .map(|(n, (name, called))| {
+            let relation = match (dialect, kind) {
+                (_, SymbolKind::Interface) => RelationKind::Extends,
+                (Dialect::Kotlin, SymbolKind::Class) if called => RelationKind::Extends,
+                (Dialect::Scala, SymbolKind::Class | SymbolKind::Enum) if n == 0 => {
+                    RelationKind::Extends
+                }
+                _ => RelationKind::Implements,
+            };
+            (relation, name)
+        })
+        .collect()
+}
+
+/// Read the import clauses from `i`: Kotlin's `a.b.C [as D]`, and Scala's
+/// comma-separated `a.b.C`, `a.b.{C, D => E}` and `a.b.C as D`. Wildcards
+/// (`a.b.*`, `a.b._`) import the package `a.b`. Returns the index after the
+/// statement.
+fn read_imports(
+    tokens: &[Token],
+    mut i: usize,
+    dialect: Dialect,
+    imports: &mut Vec<Import>,
+) -> usize {
+    let on_same_line = |k: usize| tokens.get(k).filter(|t| !t.first_on_line);
+    loop {
+        let start = i;
+        // Identifiers joined by dots, possibly ending in a dot before `{` or `*`
+        while tokens.get(i).is_some_and(|t| {
+            let follows_dot = i == start || tokens[i - 1].is_punct(".");
+            if t.kind == TokenKind::Ident {
+                follows_dot
+            } else {
+                t.is_punct(".") && !follows_dot
+            }
+        }) {
+            i += 1;
+        }
+        let path: String = tokens[start..i].iter().map(|t| t.text).collect();
+
+        match on_same_line(i) {
+            Some(t) if t.is_punct("{") => {
+                let Some(close) = matching(tokens, i, "{", "}") else {
+                    return tokens.len();
+                };
+                for selector in tokens[i + 1..close].split(|t| t.is_punct(",")) {
+                    let (name, alias) = match selector {
+                        [name] => (name.text, None),
+                        [name, arrow, alias] if arrow.is_punct("=>") || arrow.is_ident("as") => {
+                            (name.text, Some(alias.text))
+                        }
+                        _ => continue,
+                    };
+                    if matches!(name, "_" | "*") && alias.is_none() {
+                        imports.push(jvm_wildcard_import(&path));
+                        continue;
+                    }
+                    if matches!(name, "_" | "*" | "given") || alias == Some("_") {
+                        continue;
+                    }
+                    imports.push(jvm_import(
+                        &format!("{path}{name}"),
+                        alias.map(str::to_string),
+                    ));
+                }
+                i = close + 1;
+            }
+            Some(t) if t.is_punct("*") => {
+                imports.push(jvm_wildcard_import(&path));
+                i += 1;
+            }
+            Some(t) if t.is_ident("as") => {
+                let alias = on_same_line(i + 1).filter(|t| t.kind == TokenKind::Ident);
+                imports.push(jvm_import(&path, alias.map(|t| t.text.to_string())));
+                i += 2;
+            }
+            _ => {
+                let last = path.rsplit('.').next().unwrap_or_default();
+                if last == "_" {
+                    imports.push(jvm_wildcard_import(&path[..path.len() - 1]));
+                } else if !matches!(last, "" | "given") {
+                    imports.push(jvm_import(&path, None));
+                }
+            }
+        }
+
+        if dialect == Dialect::Scala && on_same_line(i).is_some_and(|t| t.is_punct(",")) {
+            i += 1;
+            continue;
+        }
+        return i.min(tokens.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(extracted: &'a ExtractedFile, name: &str) -> &'a ExtractedSymbol {
+        extracted
+            .symbols
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("missing symbol {name}"))
+    }
+
+    #[test]
+    fn extracts_kotlin_declarations_and_imports() {
+        let source = r#"@file:JvmName("Billing")
+package com.acme.billing
+
+import com.acme.pricing.Pricing
+import com.acme.money.Money as Cash
+import kotlinx.coroutines.*
+
+const val MAX_LINES = 100
+
+typealias Lines = List<Line>
+
+/** A billable document. */
+@Serializable
+data class Invoice(
+    val id: String,
+    val lines: Lines,
+) : Document(id), Comparable<Invoice> {
+    fun total(): Cash = Pricing.sum(lines.map { it.amount })
+
+    private fun validate() {
+        fun check(line: Line) = require(line.amount > 0)
+        lines.forEach(::check)
+    }
+
+    companion object {
+        fun empty(id: String) = Invoice(id, emptyList())
+    }
+}
+
+internal interface Sink : Closeable {
+    suspend fun write(invoice: Invoice)
+}
+
+enum class Status { DRAFT, SENT; fun isOpen() = this == DRAFT }
+
+fun <T> List<T>.second(): T = this[1]
+
+object Registry {
+    val defaultSink = object : Sink {
+        override suspend fun write(invoice: Invoice) {}
+        override fun close() {}
+    }
+}
+"#;
+        let extracted = extract_kotlin_symbols(source).unwrap();
+        let names: Vec<&str> = extracted.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "MAX_LINES",
+                "Lines",
+                "Invoice",
+                "total",
+                "validate",
+                "empty",
+                "Sink",
+                "write",
+                "Status",
+                "isOpen",
+                "second",
+                "Registry",
+            ]
+        );
+
+        assert_eq!(find(&extracted, "MAX_LINES").kind, SymbolKind::Const);
+        assert_eq!(find(&extracted, "Lines").kind, SymbolKind::TypeAlias);
+        assert_eq!(find(&extracted, "Status").kind, SymbolKind::Enum);
+        assert_eq!(find(&extracted, "Registry").kind, SymbolKind::Class);
+
+        let invoice = find(&extracted, "Invoice");
+        assert_eq!(invoice.kind, SymbolKind::Class);
+        assert!(invoice.exported);
+        assert_eq!((invoice.lines.start, invoice.lines.end), (13, 28));
+        assert!(source[invoice.bytes.start..].starts_with("@Serializable"));
+
+        let total = find(&extracted, "total");
+        assert_eq!(total.kind, SymbolKind::Function);
+        assert_eq!((total.lines.start, total.lines.end), (18, 18));
+        assert!(!find(&extracted, "validate").exported);
+        assert!(!find(&extracted, "Sink").exported);
+        assert_eq!(find(&extracted, "Sink").kind, SymbolKind::Interface);
+
+        let imports: Vec<(&str, &str, Option<&str>)> = extracted
+            .imports
+            .iter()
+            .map(|i| (i.name.as_str(), i.source.as_str(), i.alias.as_deref()))
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                ("Pricing", "com.acme.pricing.Pricing", None),
+                ("Money", "com.acme.money.Money", Some("Cash")),
+                ("*", "kotlinx.coroutines", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn kotlin_type_relations_split_superclass_and_interfaces() {
+        let (extends, implements, _) = kotlin_type_relations(
+            "data class Invoice(val id: String) : Document(id), Comparable<Invoice>, Auditable by audit {}",
+        );
+        assert_eq!(extends, ["Document"]);
+        assert_eq!(implements, ["Comparable", "Auditable"]);
+
+        let (extends, implements, _) =
+            kotlin_type_relations("interface Sink<T : Any> : java.io.Closeable, Flushable");
+        assert_eq!(extends, ["Closeable", "Flushable"]);
+        assert!(implements.is_empty());
+    }
+
+    #[test]
+    fn extracts_scala_declarations_and_imports() {
+        let source = r#"package com.acme.billing
+
+import com.acme.pricing.{Pricing, Rounding => Round, _}
+import scala.concurrent.Future, scala.util.Try
+import com.acme.money._
+
+sealed trait Document extends Product with Serializable
+
+case class Invoice(id: String, lines: Seq[Line]) extends Document {
+  def total: BigDecimal = Pricing.sum(lines.map(_.amount))
+
+  private[billing] def validate(): Unit = {
+    def check(line: Line) = require(line.amount > 0)
+    lines.foreach(check)
+  }
+}
+
+object Invoice {
+  type Id = String
+  def empty(id: Id): Invoice = Invoice(id, Nil)
+}
+
+enum Status:
+  case Draft, Sent
+
+  def isOpen: Boolean = this == Draft
+
+class Ledger extends Base(1), Auditable:
+  def post(invoice: Invoice): Unit =
+    val line = invoice.lines.head
+    record(line)
+
+  def close(): Unit = ()
+
+end Ledger
+
+def main(args: Array[String]): Unit = println("ok")
+"#;
+        let extracted = extract_scala_symbols(source).unwrap();
+        let names: Vec<&str> = extracted.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "Document", "Invoice", "total", "validate", "Invoice", "Id", "empty", "Status",
+                "isOpen", "Ledger", "post", "close", "main",
+            ]
+        );
+
+        assert_eq!(find(&extracted, "Document").kind, SymbolKind::Interface);
+        assert_eq!(find(&extracted, "Id").kind, SymbolKind::TypeAlias);
+        assert_eq!(find(&extracted, "Status").kind, SymbolKind::Enum);
+        assert!(!find(&extracted, "validate").exported);
+
+        let invoice = &extracted.symbols[1];
+        assert_eq!(invoice.kind, SymbolKind::Class);
+        assert_eq!((invoice.lines.start, invoice.lines.end), (9, 16));
+
+        let status = find(&extracted, "Status");
+        assert_eq!((status.lines.start, status.lines.end), (23, 26));
+        let ledger = find(&extracted, "Ledger");
+        assert_eq!((ledger.lines.start, ledger.lines.end), (28, 35));
+        let post = find(&extracted, "post");
+        assert_eq!((post.lines.start, post.lines.end), (29, 31));
+
+        let imports: Vec<(&str, &str, Option<&str>)> = extracted
+            .imports
+            .iter()
+            .map(|i| (i.name.as_str(), i.source.as_str(), i.alias.as_deref()))
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                ("Pricing", "com.acme.pricing.Pricing", None),
+                ("Rounding", "com.acme.pricing.Rounding", Some("Round")),
+                ("*", "com.acme.pricing", None),
+                ("Future", "scala.concurrent.Future", None),
+                ("Try", "scala.util.Try", None),
+                ("*", "com.acme.money", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn scala_type_relations_split_superclass_and_mixins() {
+        let (extends, implements, _) = scala_type_relations(
+            "class Ledger(id: Int) extends Base[Int](id) with Auditable with java.io.Closeable {",
+        );
+        assert_eq!(extends, ["Base"]);
+        assert_eq!(implements, ["Auditable", "Closeable"]);
+
+        let (extends, implements, _) =
+            scala_type_relations("sealed trait Document extends Product with Serializable");
+        assert_eq!(extends, ["Product", "Serializable"]);
+        assert!(implements.is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+//! Markdown section extraction
+//!
+//! Every ATX (`## Title`) and setext (`Title` underlined with `===` or `---`) heading
+//! becomes a `section` running up to the next heading of the same or a higher level,
+//! so subsections nest inside their sections. Headings inside fenced code blocks and
+//! YAML front matter are ignored.
+
+use anyhow::Result;
+
+use super::symbol::{ExtractedFile, LineIndex, SymbolKind};
+
+pub fn extract_markdown_symbols(source: &str) -> Result<ExtractedFile> {
+    let lines = LineIndex::new(source);
+    let headings = headings(source);
+    let symbols = headings
+        .iter()
+        .enumerate()
+        .map(|(i, heading)| {
+            let end = headings[i + 1..]
+                .iter()
+                .find(|next| next.level <= heading.level)
+                .map_or(source.len(), |next| next.start);
+            lines.symbol(
+                source,
+                &heading.title,
+                SymbolKind::Section,
+                heading.start,
+                end,
+            )
+        })
+        .collect();
+    Ok(ExtractedFile::with_symbols(symbols, Vec::new()))
+}
+
+#[derive(Debug)]
+struct Heading {
+    level: usize,
+    title: String,
+    start: usize,
+}
+
+fn headings(source: &str) -> Vec<Heading> {
+    let mut out = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    // Start and text of the previous line when it is a one-line paragraph, which a
+    // setext underline turns into a heading
+    let mut paragraph: Option<(usize, &str)> = None;
+    let mut in_paragraph = false;
+    let mut offset = 0;
+    let mut in_front_matter = source.starts_with("---\n") || source.starts_with("---\r\n");
+
+    for (index, raw) in source.split_inclusive('\n').enumerate() {
+        let start = offset;
+        offset += raw.len();
+        let line = raw.trim_end();
+
+        if in_front_matter {
+            if index > 0 && (line == "---" || line == "...") {
+                in_front_matter = false;
+            }
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim_start();
+        if let Some((marker, len)) = fence {
+            let closes = indent < 4
+                && trimmed.chars().take_while(|&c| c == marker).count() >= len
+                && trimmed.trim_start_matches(marker).trim().is_empty();
+            if closes {
+                fence = None;
+            }
+            continue;
+        }
+        if indent < 4 {
+            if let Some(marker) = ['`', '~'].into_iter().find(|&m| trimmed.starts_with(m)) {
+                let len = trimmed.chars().take_while(|&c| c == marker).count();
+                if len >= 3 {
+                    fence = Some((marker, len));
+                    (paragraph, in_paragraph) = (None, false);
+                    continue;
+                }
+            }
+        }
+
+        if indent < 4 {
+            if let Some(heading) = atx_heading(trimmed, start) {
+                out.push(heading);
+                (paragraph, in_paragraph) = (None, false);
+                continue;
+            }
+            let underline = trimmed.trim_end();
+            let setext_level = if !underline.is_empty() && underline.chars().all(|c| c == '=') {
+                Some(1)
+            } else if !underline.is_empty() && underline.chars().all(|c| c == '-') {
+                Some(2)
+            } else {
+                None
+            };
+            if let (Some(level), Some((title_start, title))) = (setext_level, paragraph) {
+                out.push(Heading {
+                    level,
+                    title: clean_title(title),
+                    start: title_start,
+                });
+                (paragraph, in_paragraph) = (None, false);
+                continue;
+            }
+        }
+
+        let is_block_start = trimmed.is_empty()
+            || indent >= 4
+            || trimmed.starts_with(['>', '-', '*', '+', '|', '<'])
+            || trimmed.starts_with(|c: char| c.is_ascii_digit());
+        if is_block_start {
+            (paragraph, in_paragraph) = (None, false);
+        } else if in_paragraph {
+            paragraph = None;
+        } else {
+            (paragraph, in_paragraph) = (Some((start, trimmed)), true);
+        }
+    }
+    out
+}
+
+fn atx_heading(line: &str, start: usize) -> Option<Heading> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    // Closing sequence: `## Title ##`
+    let rest = rest.trim();
+    let rest = match rest.trim_end_matches('#') {
+        stripped if stripped.is_empty() || stripped.ends_with([' ', '\t']) => stripped,
+        _ => rest,
+    };
+    let title = clean_title(rest);
+    if title.is_empty() {
+        return None;
+    }
+    Some(Heading {
+        level,
+        title,
+        start,
+    })
+}
+
+/// Heading text without emphasis, code spans or link targets.
+fn clean_title(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text.trim();
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '*' | '`' | '[' => {}
+            ']' => {
+                // `[text](target)`: drop the target
+                if let Some(after) = rest.strip_prefix('(') {
+                    rest = after.find(')').map_or("", |at| &after[at + 1..]);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_nested_sections() {
+        let source = "---\ntitle: Guide\n# not a heading\n---\n# Payments Guide\n\nIntro.\n\n## Setup\n\n```sh\n# install\n```\n\n### Keys ###\n\nText.\n\nRefunds\n-------\n\nMore.\n\n# [API](api.md) `v2`\n";
+        let file = extract_markdown_symbols(source).unwrap();
+        let names: Vec<&str> = file.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Payments Guide", "Setup", "Keys", "Refunds", "API v2"]
+        );
+        assert!(file.symbols.iter().all(|s| s.kind == SymbolKind::Section));
+
+        let guide = &file.symbols[0];
+        let setup = &file.symbols[1];
+        let keys = &file.symbols[2];
+        let refunds = &file.symbols[3];
+        let api = &file.symbols[4];
+        assert_eq!(guide.lines.start, 5);
+        assert!(source[guide.bytes.start..guide.bytes.end].ends_with("More."));
+        assert!(setup.bytes.start > guide.bytes.start && setup.bytes.end <= guide.bytes.end);
+        assert!(keys.bytes.end <= setup.bytes.end);
+        assert_eq!(refunds.lines.start, 19);
+        assert!(refunds.bytes.start >= setup.bytes.end);
+        assert_eq!(api.lines.start, api.lines.end);
+    }
+}
@@ -1,10 +1,24 @@
 pub mod c;
+pub mod config;
 pub mod cpp;
+pub mod csharp;
 pub mod elysia;
 pub mod go;
+pub mod go_routes;
+pub mod http_clients;
 pub mod java;
 pub mod javascript;
+pub mod jvm;
+pub mod markdown;
+pub mod nestjs;
+pub mod openapi;
+pub mod protobuf;
 pub mod python;
+pub mod python_routes;
+pub mod routers;
+pub mod ruby;
 pub mod rust;
+pub mod spring;
+pub mod sql;
 pub mod symbol;
 pub mod typescript;
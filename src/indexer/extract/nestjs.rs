@@ -0,0 +1,197 @@
+//! NestJS route extraction
+//!
+//! A NestJS controller declares its routes with decorators: `@Controller('users')` on
+//! the class gives the prefix and `@Get(':id')`, `@Post()` and the other method
+//! decorators on its methods give each route's method and path.
+
+use tree_sitter::Node;
+
+use super::elysia::{extract_string_value, text_for_node};
+use super::routers::join_paths;
+use super::symbol::{ExtractedFrameworkPattern, FrameworkPatternKind};
+
+/// Method decorators and the HTTP method of the routes they declare.
+const ROUTE_DECORATORS: &[(&str, &str)] = &[
+    ("Get", "GET"),
+    ("Post", "POST"),
+    ("Put", "PUT"),
+    ("Delete", "DELETE"),
+    ("Patch", "PATCH"),
+    ("Options", "OPTIONS"),
+    ("Head", "HEAD"),
+    ("All", "ALL"),
+];
+
+/// Extract the routes of the NestJS controllers in a TypeScript AST
+pub fn extract_nestjs_patterns(root: Node, source: &str) -> Vec<ExtractedFrameworkPattern> {
+    let mut patterns = Vec::new();
+    collect_controllers(root, source, &mut patterns);
+    patterns.sort_by_key(|p| (p.line, p.column));
+    patterns
+}
+
+fn collect_controllers(node: Node, source: &str, patterns: &mut Vec<ExtractedFrameworkPattern>) {
+    if node.kind() == "class_declaration" {
+        // An exported class carries its decorators on the export statement
+        let mut decorators = decorators_of(node);
+        if let Some(export) = node.parent().filter(|p| p.kind() == "export_statement") {
+            decorators.extend(decorators_of(export));
+        }
+        let prefix = decorators.iter().find_map(|decorator| {
+            let (name, path) = decorator_call(*decorator, source)?;
+            (name == "Controller").then(|| path.unwrap_or_default())
+        });
+        if let (Some(prefix), Some(class_name), Some(body)) = (
+            prefix,
+            node.child_by_field_name("name"),
+            node.child_by_field_name("body"),
+        ) {
+            let class_name = text_for_node(class_name, source);
+            collect_routes(body, source, &class_name, &prefix, patterns);
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_controllers(child, source, patterns);
+    }
+}
+
+fn collect_routes(
+    body: Node,
+    source: &str,
+    class_name: &str,
+    prefix: &str,
+    patterns: &mut Vec<ExtractedFrameworkPattern>,
+) {
+    // Decorators of a method come before it in the class body, or inside it
+    let mut pending: Vec<Node> = Vec::new();
+    let mut cursor = body.walk();
+    for member in body.named_children(&mut cursor) {
+        if member.kind() == "decorator" {
+            pending.push(member);
+            continue;
+        }
+        let mut decorators = std::mem::take(&mut pending);
+        if member.kind() != "method_definition" {
+            continue;
+        }
+        decorators.extend(decorators_of(member));
+        let Some(method_name) = member.child_by_field_name("name") else {
+            continue;
+        };
+        let handler = format!("{class_name}.{}", text_for_node(method_name, source));
+        for decorator in decorators {
+            let Some((name, path)) = decorator_call(decorator, source) else {
+                continue;
+            };
+            let Some(&(_, http_method)) = ROUTE_DECORATORS.iter().find(|(d, _)| *d == name) else {
+                continue;
+            };
+            let position = decorator.start_position();
+            patterns.push(ExtractedFrameworkPattern {
+                line: position.row as u32 + 1,
+                column: position.column as u32,
+                framework: "nestjs".to_string(),
+                kind: FrameworkPatternKind::Route,
+                http_method: Some(http_method.to_string()),
+                path: Some(join_paths(
+                    &join_paths("/", prefix),
+                    path.as_deref().unwrap_or(""),
+                )),
+                name: None,
+                handler: Some(handler.clone()),
+                arguments: None,
+                parent_chain: Some(class_name.to_string()),
+            });
+        }
+    }
+}
+
+fn decorators_of(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| child.kind() == "decorator")
+        .collect()
+}
+
+/// The name of a decorator and its first argument when that is a string:
+/// `@Get(':id')` is `("Get", Some(":id"))` and `@Post()` is `("Post", None)`.
+fn decorator_call(decorator: Node, source: &str) -> Option<(String, Option<String>)> {
+    let mut cursor = decorator.walk();
+    let call = decorator
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "call_expression")?;
+    let name = text_for_node(call.child_by_field_name("function")?, source);
+    let arguments = call.child_by_field_name("arguments")?;
+    let mut cursor = arguments.walk();
+    let path = arguments
+        .named_children(&mut cursor)
+        .next()
+        .filter(|arg| matches!(arg.kind(), "string" | "template_string"))
+        .map(|arg| extract_string_value(arg, source));
+    Some((name, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::parser::{parser_for_id, LanguageId};
+
+    #[test]
+    fn extracts_controller_routes_under_the_prefix() {
+        let source = r#"
+import { Controller, Get, Post, Body } from '@nestjs/common';
+
+@Controller('users')
+export class UsersController {
+  @Get()
+  findAll() {}
+
+  @Get(':id')
+  @UseGuards(AuthGuard)
+  findOne(@Param('id') id: string) {}
+
+  @Post('/')
+  create(@Body() dto: CreateUserDto) {}
+
+  helper() {}
+}
+
+export class NotAController {
+  @Get('x')
+  x() {}
+}
+"#;
+        let mut parser = parser_for_id(LanguageId::Typescript).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let routes: Vec<_> = extract_nestjs_patterns(tree.root_node(), source)
+            .into_iter()
+            .map(|p| {
+                assert_eq!(p.framework, "nestjs");
+                assert_eq!(p.parent_chain.as_deref(), Some("UsersController"));
+                (p.http_method.unwrap(), p.path.unwrap(), p.handler.unwrap())
+            })
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                (
+                    "GET".into(),
+                    "/users".into(),
+                    "UsersController.findAll".into()
+                ),
+                (
+                    "GET".into(),
+                    "/users/:id".into(),
+                    "UsersController.findOne".into()
+                ),
+                (
+                    "POST".into(),
+                    "/users".into(),
+                    "UsersController.create".into()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! OpenAPI operation extraction
+//!
+//! A YAML or JSON document with a top-level `openapi` (or Swagger 2 `swagger`) key is
+//! an API contract: each operation under `paths` becomes an `operation` symbol, named
+//! by its `operationId` when it has one (that is the name generated clients and
+//! server stubs use) and by method and path otherwise. Each is also recorded as a
+//! `route` framework pattern of the `openapi` framework, so the contract can be
+//! matched against the routes the code serves.
+
+use super::config::{json_keys, yaml_key};
+use super::symbol::{
+    ExtractedFrameworkPattern, ExtractedSymbol, FrameworkPatternKind, LineIndex, SymbolKind,
+};
+
+/// Framework recorded on the route patterns of operations.
+pub const OPENAPI_FRAMEWORK: &str = "openapi";
+
+/// Keys of a path item that are operations.
+const OPERATION_METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Levels of keys an operation's `operationId` lies at.
+const OPERATION_ID_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecFormat {
+    Yaml,
+    Json,
+}
+
+/// The operations of `source` and their routes; both are empty when `source` is
+/// not an OpenAPI document.
+pub fn extract_openapi_operations(
+    source: &str,
+    format: SpecFormat,
+) -> (Vec<ExtractedSymbol>, Vec<ExtractedFrameworkPattern>) {
+    let keys = match format {
+        SpecFormat::Yaml => yaml_keys(source),
+        SpecFormat::Json => json_keys(source, OPERATION_ID_DEPTH),
+    };
+    let is_spec = keys.iter().any(
+        |(path, _, _)| matches!(path.as_slice(), [key] if key == "openapi" || key == "swagger"),
+    );
+    if !is_spec {
+        return (Vec::new(), Vec::new());
+    }
+
+    let value_of = |wanted: &[&str]| {
+        keys.iter()
+            .find(|(path, _, _)| path.iter().map(String::as_str).eq(wanted.iter().copied()))
+            .and_then(|&(_, start, end)| scalar_value(&source[start..end]))
+    };
+    // Swagger 2 prefixes every path with its `basePath`
+    let base_path = value_of(&["basePath"]).unwrap_or_default();
+
+    let lines = LineIndex::new(source);
+    let mut symbols = Vec::new();
+    let mut routes = Vec::new();
+    for (path, start, end) in &keys {
+        let [paths, route, method] = path.as_slice() else {
+            continue;
+        };
+        let method = method.to_ascii_lowercase();
+        if paths != "paths" || !OPERATION_METHODS.contains(&method.as_str()) {
+            continue;
+        }
+        let method = method.to_ascii_uppercase();
+        let operation_id = value_of(&["paths", route.as_str(), path[2].as_str(), "operationId"]);
+        let name = operation_id
+            .clone()
+            .unwrap_or_else(|| format!("{method} {route}"));
+        let symbol = lines.symbol(source, name, SymbolKind::Operation, *start, *end);
+        routes.push(ExtractedFrameworkPattern {
+            line: symbol.lines.start,
+            column: 0,
+            framework: OPENAPI_FRAMEWORK.to_string(),
+            kind: FrameworkPatternKind::Route,
+            http_method: Some(method),
+            path: Some(format!("{}{}", base_path.trim_end_matches('/'), route)),
+            name: operation_id,
+            handler: None,
+            arguments: None,
+            parent_chain: None,
+        });
+        symbols.push(symbol);
+    }
+    (symbols, routes)
+}
+
+/// The `(path, start, end)` of every key of a YAML document, the first document of a
+/// stream only. A key runs up to the next key at the same or a lower indent.
+fn yaml_keys(source: &str) -> Vec<(Vec<String>, usize, usize)> {
+    let mut keys: Vec<(Vec<String>, usize, usize)> = Vec::new();
+    let mut indents: Vec<usize> = Vec::new();
+    // Open keys, innermost last: their indent and index in `keys`
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let mut block_scalar: Option<usize> = None;
+    let mut document_end = source.len();
+    let mut offset = 0;
+
+    for raw in source.split_inclusive('\n') {
+        let start = offset;
+        offset += raw.len();
+        let line = raw.trim_end();
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(scalar_indent) = block_scalar {
+            if indent > scalar_indent {
+                continue;
+            }
+            block_scalar = None;
+        }
+        if indent == 0 && (trimmed.starts_with("---") || trimmed.starts_with("...")) {
+            if keys.is_empty() {
+                continue;
+            }
+            document_end = start;
+            break;
+        }
+        let Some((name, value)) = yaml_key(trimmed) else {
+            continue;
+        };
+        if value.starts_with(['|', '>']) {
+            block_scalar = Some(indent);
+        }
+        while open
+            .last()
+            .is_some_and(|&(open_indent, _)| open_indent >= indent)
+        {
+            open.pop();
+        }
+        let mut path: Vec<String> = open
+            .iter()
+            .map(|&(_, i)| keys[i].0.last().cloned().unwrap_or_default())
+            .collect();
+        path.push(name);
+        open.push((indent, keys.len()));
+        indents.push(indent);
+        keys.push((path, start + indent, start + indent));
+    }
+
+    for i in 0..keys.len() {
+        keys[i].2 = (i + 1..keys.len())
+            .find(|&next| indents[next] <= indents[i])
+            .map_or(document_end, |next| keys[next].1 - indents[next]);
+    }
+    keys
+}
+
+/// The scalar value of a `key: value` entry, without quotes.
+fn scalar_value(entry: &str) -> Option<String> {
+    let first_line = entry.lines().next()?;
+    // Past a quoted key, which may hold a colon
+    let after_key = match first_line.chars().next()? {
+        quote @ ('"' | '\'') => &first_line[first_line[1..].find(quote)? + 2..],
+        _ => first_line,
+    };
+    let value = after_key[after_key.find(':')? + 1..].trim();
+    let value = match value.chars().next() {
+        Some('"' | '\'') => value.trim_end_matches(','),
+        // A YAML comment may follow a plain scalar
+        _ => value.split(" #").next().unwrap_or_default().trim_end(),
+    };
+    let value = value.trim_matches(|c| c == '"' || c == '\'');
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operations(source: &str, format: SpecFormat) -> Vec<(String, String, String)> {
+        let (symbols, routes) = extract_openapi_operations(source, format);
+        assert_eq!(symbols.len(), routes.len());
+        symbols
+            .iter()
+            .zip(&routes)
+            .map(|(symbol, route)| {
+                assert_eq!(symbol.lines.start, route.line);
+                (
+                    symbol.name.clone(),
+                    route.http_method.clone().unwrap(),
+                    route.path.clone().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extracts_yaml_operations() {
+        let source = "openapi: 3.0.3\ninfo:\n  title: Billing\n  description: |\n    paths:\n      get: no\npaths:\n  /invoices:\n    get:\n      operationId: listInvoices\n      parameters:\n        - name: limit\n          in: query\n    post:\n      summary: Create\n  \"/invoices/{id}\":\n    get:\n      operationId: 'getInvoice'\ncomponents:\n  schemas: {}\n";
+        assert_eq!(
+            operations(source, SpecFormat::Yaml),
+            vec![
+                ("listInvoices".into(), "GET".into(), "/invoices".into()),
+                ("POST /invoices".into(), "POST".into(), "/invoices".into()),
+                ("getInvoice".into(), "GET".into(), "/invoices/{id}".into()),
+            ]
+        );
+        let (symbols, _) = extract_openapi_operations(source, SpecFormat::Yaml);
+        let list = &symbols[0];
+        assert_eq!((list.lines.start, list.lines.end), (9, 13));
+        let get = &symbols[2];
+        assert_eq!(
+            &source[get.bytes.start..get.bytes.end],
+            "get:\n      operationId: 'getInvoice'"
+        );
+    }
+
+    #[test]
+    fn extracts_swagger_json_operations_under_the_base_path() {
+        let source = r#"{
+  "swagger": "2.0",
+  "basePath": "/api/",
+  "paths": {
+    "/users/{id}": {
+      "parameters": [],
+      "delete": { "operationId": "deleteUser", "tags": ["users"] }
+    }
+  }
+}"#;
+        assert_eq!(
+            operations(source, SpecFormat::Json),
+            vec![(
+                "deleteUser".into(),
+                "DELETE".into(),
+                "/api/users/{id}".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn ignores_other_documents() {
+        let source = "paths:\n  /x:\n    get:\n      operationId: x\n";
+        assert!(extract_openapi_operations(source, SpecFormat::Yaml)
+            .0
+            .is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! Protocol Buffers extraction
+//!
+//! Messages (nested ones included), enums, services and their rpcs are extracted
+//! from `.proto` files, and `import` statements become imports of the file they
+//! name. The `package` declaration is picked up as the module path when qualified
+//! names are assigned. Comments and string literals are skipped by the lexer.
+
+use anyhow::Result;
+
+use super::symbol::{ExtractedFile, Import, LineIndex, SymbolKind};
+
+pub fn extract_protobuf_symbols(source: &str) -> Result<ExtractedFile> {
+    let lines = LineIndex::new(source);
+    let tokens = tokenize(source);
+    let closing = matching_braces(&tokens);
+    let mut symbols = Vec::new();
+    let mut imports = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Word {
+            continue;
+        }
+        let next = tokens.get(i + 1);
+        match token.text {
+            "message" | "enum" | "service" => {
+                let Some(name) = next.filter(|t| t.kind == TokenKind::Word) else {
+                    continue;
+                };
+                if !tokens.get(i + 2).is_some_and(|t| t.is("{")) {
+                    continue;
+                }
+                let kind = match token.text {
+                    "message" => SymbolKind::Message,
+                    "enum" => SymbolKind::Enum,
+                    _ => SymbolKind::Service,
+                };
+                let end = closing[i + 2].map_or(source.len(), |close| tokens[close].end);
+                symbols.push(lines.symbol(source, name.text, kind, token.start, end));
+            }
+            "rpc" => {
+                let Some(name) = next.filter(|t| t.kind == TokenKind::Word) else {
+                    continue;
+                };
+                // Up to the `;` or the end of the options block, whichever is first
+                let end = tokens[i + 1..]
+                    .iter()
+                    .enumerate()
+                    .find_map(|(offset, t)| match t.text {
+                        ";" => Some(t.end),
+                        "{" => closing[i + 1 + offset].map(|close| tokens[close].end),
+                        "}" => Some(t.start),
+                        _ => None,
+                    })
+                    .unwrap_or(source.len());
+                symbols.push(lines.symbol(source, name.text, SymbolKind::Rpc, token.start, end));
+            }
+            "import" => {
+                let path = tokens[i + 1..]
+                    .iter()
+                    .take(2)
+                    .find(|t| t.kind == TokenKind::Str)
+                    .and_then(|t| t.text.get(1..t.text.len() - 1));
+                if let Some(path) = path.filter(|p| !p.is_empty()) {
+                    let stem = path.rsplit('/').next().unwrap_or(path);
+                    imports.push(Import {
+                        name: stem.strip_suffix(".proto").unwrap_or(stem).to_string(),
+                        source: path.to_string(),
+                        alias: None,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(ExtractedFile::with_symbols(symbols, imports))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Str,
+    Punct,
+}
+
+#[derive(Debug)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl Token<'_> {
+    fn is(&self, punct: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == punct
+    }
+}
+
+/// For each `{` token, the index of the `}` closing it.
+fn matching_braces(tokens: &[Token]) -> Vec<Option<usize>> {
+    let mut out = vec![None; tokens.len()];
+    let mut open = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is("{") {
+            open.push(i);
+        } else if token.is("}") {
+            if let Some(start) = open.pop() {
+                out[start] = Some(i);
+            }
+        }
+    }
+    out
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let start = i;
+        let kind = match b {
+            _ if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = source[i..].find('\n').map_or(bytes.len(), |at| i + at);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = source[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |at| i + 2 + at + 2);
+                continue;
+            }
+            b'"' | b'\'' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+                TokenKind::Str
+            }
+            _ if b.is_ascii_alphanumeric() || b == b'_' || b == b'.' => {
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'.')
+                {
+                    i += 1;
+                }
+                TokenKind::Word
+            }
+            _ => {
+                // Whole characters, so slices stay on boundaries
+                i += source[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Punct
+            }
+        };
+        out.push(Token {
+            kind,
+            text: &source[start..i],
+            start,
+            end: i,
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_messages_enums_services_and_rpcs() {
+        let source = r#"syntax = "proto3";
+package acme.billing.v1;
+
+import "google/protobuf/timestamp.proto";
+import public "acme/common/money.proto";
+
+// message Commented {}
+message Invoice {
+  string id = 1;
+  string message = 2;
+  message Line {
+    int64 amount = 1;
+  }
+  enum Status {
+    STATUS_UNSPECIFIED = 0;
+  }
+}
+
+service InvoiceService {
+  rpc GetInvoice(GetInvoiceRequest) returns (Invoice);
+  rpc StreamInvoices(stream Query) returns (stream Invoice) {
+    option (google.api.http) = { get: "/v1/invoices" };
+  }
+}
+"#;
+        let file = extract_protobuf_symbols(source).unwrap();
+        let names: Vec<(&str, SymbolKind)> = file
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Invoice", SymbolKind::Message),
+                ("Line", SymbolKind::Message),
+                ("Status", SymbolKind::Enum),
+                ("InvoiceService", SymbolKind::Service),
+                ("GetInvoice", SymbolKind::Rpc),
+                ("StreamInvoices", SymbolKind::Rpc),
+            ]
+        );
+        let invoice = &file.symbols[0];
+        assert_eq!((invoice.lines.start, invoice.lines.end), (8, 17));
+        let line = &file.symbols[1];
+        assert!(invoice.bytes.start < line.bytes.start && line.bytes.end < invoice.bytes.end);
+        let stream = &file.symbols[5];
+        assert_eq!((stream.lines.start, stream.lines.end), (21, 23));
+
+        let imports: Vec<(&str, &str)> = file
+            .imports
+            .iter()
+            .map(|i| (i.name.as_str(), i.source.as_str()))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("timestamp", "google/protobuf/timestamp.proto"),
+                ("money", "acme/common/money.proto")
+            ]
+        );
+    }
+}
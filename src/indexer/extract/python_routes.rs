@@ -0,0 +1,539 @@
+//! FastAPI, Flask and Django route extraction
+//!
+//! FastAPI and Flask register routes with decorators on the view function:
+//! `@app.get("/items")`, `@router.post(...)`, `@bp.route("/x", methods=["GET", "POST"])`.
+//! Routers and blueprints carry their own prefix (`APIRouter(prefix=...)`,
+//! `Blueprint(..., url_prefix=...)`) and are nested with `include_router` and
+//! `register_blueprint`. Django lists its routes in `urlpatterns` as `path()` and
+//! `re_path()` entries, nesting other URLconfs with `include()`.
+//!
+//! Routes get the prefixes known in the file, like the JavaScript routers (see
+//! `routers`). Mounts of routers from other modules, and Django views imported from
+//! another module, keep that module in `arguments` so that they can be followed once
+//! the whole repository is indexed.
+
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+
+use super::routers::{apply_prefixes, Registration};
+use super::symbol::{ExtractedFrameworkPattern, FrameworkPatternKind, Import};
+
+/// Decorator methods registering a route for one HTTP method.
+const ROUTE_METHODS: &[&str] = &[
+    "get", "post", "put", "delete", "patch", "options", "head", "trace",
+];
+
+/// Framework patterns of a Python file, for the web framework it imports.
+pub fn extract_python_routes(
+    root: Node,
+    source: &str,
+    imports: &[Import],
+) -> Vec<ExtractedFrameworkPattern> {
+    let modules = PythonModules::new(imports);
+    let Some(framework) = modules.framework() else {
+        return Vec::new();
+    };
+    let mut base_prefixes = HashMap::new();
+    let mut registrations = Vec::new();
+    walk(root, &mut |node| match node.kind() {
+        // router = APIRouter(prefix="/users"), bp = Blueprint("bp", __name__, url_prefix="/bp")
+        "assignment" => {
+            let (Some(left), Some(right)) = (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ) else {
+                return;
+            };
+            if right.kind() != "call" {
+                return;
+            }
+            let prefix = call_arguments(right, source)
+                .keyword("prefix")
+                .or_else(|| call_arguments(right, source).keyword("url_prefix"))
+                .and_then(|value| string_literal(value, source));
+            if let Some(prefix) = prefix {
+                base_prefixes.insert(text(left, source), prefix);
+            }
+        }
+        "decorated_definition" if framework != "django" => {
+            registrations.extend(decorated_routes(node, source));
+        }
+        "call" if framework == "django" => {
+            registrations.extend(url_pattern(node, source, &modules));
+        }
+        "call" => registrations.extend(mount(node, source, &modules)),
+        _ => {}
+    });
+    apply_prefixes(framework, registrations, &base_prefixes)
+}
+
+/// Modules imported by the file, by the name they are bound to.
+struct PythonModules<'a> {
+    imports: &'a [Import],
+    bound: HashMap<&'a str, String>,
+}
+
+impl<'a> PythonModules<'a> {
+    fn new(imports: &'a [Import]) -> Self {
+        let bound = imports
+            .iter()
+            .map(|import| {
+                let local = import.alias.as_deref().unwrap_or(&import.name);
+                // `import a.b` binds the module itself; `from a import b` binds `b`, a
+                // submodule or an attribute of `a`
+                let module = if import.name == import.source {
+                    import.source.clone()
+                } else if import.source.ends_with('.') {
+                    format!("{}{}", import.source, import.name)
+                } else {
+                    format!("{}.{}", import.source, import.name)
+                };
+                (local, module)
+            })
+            .collect();
+        Self { imports, bound }
+    }
+
+    fn framework(&self) -> Option<&'static str> {
+        self.imports.iter().find_map(|import| {
+            let root = import.source.split('.').next().unwrap_or_default();
+            match root {
+                "fastapi" => Some("fastapi"),
+                "flask" => Some("flask"),
+                "django" => Some("django"),
+                _ => None,
+            }
+        })
+    }
+
+    /// The module an expression such as `users.router` or `views.detail` comes from,
+    /// going by the name its qualifier (or the whole expression) is bound to.
+    fn module_of(&self, expression: &str) -> Option<String> {
+        let mut qualifier = expression;
+        loop {
+            if let Some(module) = self.bound.get(qualifier) {
+                return Some(module.clone());
+            }
+            qualifier = &qualifier[..qualifier.rfind('.')?];
+        }
+    }
+}
+
+/// Routes registered by the decorators of a function.
+fn decorated_routes(node: Node, source: &str) -> Vec<Registration> {
+    let Some(function) = node
+        .child_by_field_name("definition")
+        .filter(|definition| definition.kind() == "function_definition")
+    else {
+        return Vec::new();
+    };
+    let Some(handler) = function
+        .child_by_field_name("name")
+        .map(|n| text(n, source))
+    else {
+        return Vec::new();
+    };
+    let mut routes = Vec::new();
+    let mut cursor = node.walk();
+    for decorator in node.named_children(&mut cursor) {
+        if decorator.kind() != "decorator" {
+            continue;
+        }
+        let Some(call) = decorator.named_child(0).filter(|c| c.kind() == "call") else {
+            continue;
+        };
+        let Some((object, method)) = method_call(call, source) else {
+            continue;
+        };
+        let arguments = call_arguments(call, source);
+        let Some(path) = arguments
+            .positional(0)
+            .or_else(|| arguments.keyword("path"))
+            .or_else(|| arguments.keyword("rule"))
+            .and_then(|path| string_literal(path, source))
+        else {
+            continue;
+        };
+        let (kind, methods) = match method.as_str() {
+            m if ROUTE_METHODS.contains(&m) => {
+                (FrameworkPatternKind::Route, vec![m.to_ascii_uppercase()])
+            }
+            // Flask's route() and FastAPI's api_route() default to GET
+            "route" | "api_route" => {
+                let methods = arguments
+                    .keyword("methods")
+                    .map(|list| string_list(list, source))
+                    .unwrap_or_else(|| vec!["GET".to_string()]);
+                (FrameworkPatternKind::Route, methods)
+            }
+            "websocket" => (FrameworkPatternKind::WebSocket, vec![String::new()]),
+            _ => continue,
+        };
+        let position = decorator.start_position();
+        for http_method in methods {
+            routes.push(Registration {
+                line: position.row as u32 + 1,
+                column: position.column as u32,
+                kind: kind.clone(),
+                http_method: (!http_method.is_empty()).then(|| http_method.to_ascii_uppercase()),
+                path: path.clone(),
+                name: None,
+                handler: Some(handler.clone()),
+                arguments: None,
+                parent_chain: Some(object.clone()),
+            });
+        }
+    }
+    routes
+}
+
+/// A router nested under another: `app.include_router(users.router, prefix="/users")`,
+/// `app.register_blueprint(bp, url_prefix="/bp")`, `app.mount("/admin", admin_app)`,
+/// and Flask's `app.add_url_rule("/x", view_func=view)`, which is a route.
+fn mount(node: Node, source: &str, modules: &PythonModules) -> Option<Registration> {
+    let (object, method) = method_call(node, source)?;
+    let arguments = call_arguments(node, source);
+    let position = node.start_position();
+    let (kind, path, mounted, handler, http_method) = match method.as_str() {
+        "include_router" | "register_blueprint" => {
+            let prefix = arguments
+                .keyword("prefix")
+                .or_else(|| arguments.keyword("url_prefix"))
+                .and_then(|prefix| string_literal(prefix, source))
+                .unwrap_or_default();
+            let router = text(arguments.positional(0)?, source);
+            (
+                FrameworkPatternKind::Mount,
+                prefix,
+                Some(router),
+                None,
+                None,
+            )
+        }
+        "mount" => {
+            let path = string_literal(arguments.positional(0)?, source)?;
+            let app = arguments
+                .positional(1)
+                .or_else(|| arguments.keyword("app"))?;
+            let app = (app.kind() != "call").then(|| text(app, source))?;
+            (FrameworkPatternKind::Mount, path, Some(app), None, None)
+        }
+        "add_url_rule" => {
+            let path = string_literal(arguments.positional(0)?, source)?;
+            let view = arguments
+                .keyword("view_func")
+                .or_else(|| arguments.positional(2))?;
+            let methods = arguments
+                .keyword("methods")
+                .map(|list| string_list(list, source))
+                .and_then(|methods| methods.into_iter().next());
+            let handler = view_name(view, source)?;
+            let method = methods.unwrap_or_else(|| "GET".to_string());
+            (
+                FrameworkPatternKind::Route,
+                path,
+                None,
+                Some(handler),
+                Some(method),
+            )
+        }
+        _ => return None,
+    };
+    let module = mounted.as_deref().and_then(|m| modules.module_of(m));
+    Some(Registration {
+        line: position.row as u32 + 1,
+        column: position.column as u32,
+        kind,
+        http_method,
+        path,
+        name: mounted,
+        handler,
+        arguments: module,
+        parent_chain: Some(object),
+    })
+}
+
+/// A Django URL pattern: `path("users/<int:pk>/", views.detail)`,
+/// `re_path(r"^archive/$", archive)` or `path("blog/", include("blog.urls"))`.
+fn url_pattern(node: Node, source: &str, modules: &PythonModules) -> Option<Registration> {
+    let function = text(node.child_by_field_name("function")?, source);
+    let function = function.rsplit('.').next().unwrap_or(&function);
+    if !matches!(function, "path" | "re_path" | "url") {
+        return None;
+    }
+    let arguments = call_arguments(node, source);
+    let route = string_literal(arguments.positional(0)?, source)?;
+    // Django routes are relative to where their URLconf is included
+    let route = match function {
+        "path" => format!("/{route}"),
+        _ => format!("/{}", route.trim_start_matches('^').trim_end_matches('$')),
+    };
+    let target = arguments
+        .positional(1)
+        .or_else(|| arguments.keyword("view"))?;
+    let position = node.start_position();
+    let mut registration = Registration {
+        line: position.row as u32 + 1,
+        column: position.column as u32,
+        kind: FrameworkPatternKind::Route,
+        http_method: Some("ALL".to_string()),
+        path: route,
+        name: None,
+        handler: None,
+        arguments: None,
+        parent_chain: None,
+    };
+
+    let included = (target.kind() == "call")
+        .then(|| target.child_by_field_name("function"))
+        .flatten()
+        .filter(|function| text(*function, source) == "include");
+    if included.is_some() {
+        // include("blog.urls") or include(("blog.urls", "blog"))
+        let urlconf = call_arguments(target, source).positional(0)?;
+        let urlconf = match urlconf.kind() {
+            "tuple" => urlconf.named_child(0)?,
+            _ => urlconf,
+        };
+        let module = string_literal(urlconf, source)?;
+        registration.kind = FrameworkPatternKind::Mount;
+        registration.http_method = None;
+        registration.name = Some(module.clone());
+        registration.arguments = Some(module);
+    } else {
+        let view = view_name(target, source)?;
+        registration.arguments = modules.module_of(&view);
+        registration.handler = Some(view);
+    }
+    Some(registration)
+}
+
+/// The view a route is served by: `detail`, `views.detail`, or the class of
+/// `views.DetailView.as_view()`.
+fn view_name(node: Node, source: &str) -> Option<String> {
+    match node.kind() {
+        "identifier" | "attribute" => Some(text(node, source)),
+        "call" => match method_call(node, source) {
+            Some((object, method)) if method == "as_view" => Some(object),
+            _ => Some(text(node.child_by_field_name("function")?, source)),
+        },
+        _ => None,
+    }
+}
+
+/// The object and method of a call such as `app.get(...)`.
+fn method_call(call: Node, source: &str) -> Option<(String, String)> {
+    let function = call
+        .child_by_field_name("function")
+        .filter(|f| f.kind() == "attribute")?;
+    Some((
+        text(function.child_by_field_name("object")?, source),
+        text(function.child_by_field_name("attribute")?, source),
+    ))
+}
+
+struct CallArguments<'a> {
+    positional: Vec<Node<'a>>,
+    keywords: HashMap<String, Node<'a>>,
+}
+
+impl<'a> CallArguments<'a> {
+    fn positional(&self, index: usize) -> Option<Node<'a>> {
+        self.positional.get(index).copied()
+    }
+
+    fn keyword(&self, name: &str) -> Option<Node<'a>> {
+        self.keywords.get(name).copied()
+    }
+}
+
+fn call_arguments<'a>(call: Node<'a>, source: &str) -> CallArguments<'a> {
+    let mut positional = Vec::new();
+    let mut keywords = HashMap::new();
+    if let Some(arguments) = call.child_by_field_name("arguments") {
+        let mut cursor = arguments.walk();
+        for argument in arguments.named_children(&mut cursor) {
+            match argument.kind() {
+                "keyword_argument" => {
+                    let name = argument.child_by_field_name("name");
+                    let value = argument.child_by_field_name("value");
+                    if let (Some(name), Some(value)) = (name, value) {
+                        keywords.insert(text(name, source), value);
+                    }
+                }
+                "comment" => {}
+                _ => positional.push(argument),
+            }
+        }
+    }
+    CallArguments {
+        positional,
+        keywords,
+    }
+}
+
+/// The value of a string literal, without its prefix and quotes.
+fn string_literal(node: Node, source: &str) -> Option<String> {
+    if node.kind() != "string" {
+        return None;
+    }
+    let literal = text(node, source);
+    let literal = literal.trim_start_matches(|c: char| "rRbBuUfF".contains(c));
+    for quote in ["\"\"\"", "'''", "\"", "'"] {
+        if let Some(inner) = literal
+            .strip_prefix(quote)
+            .and_then(|l| l.strip_suffix(quote))
+        {
+            return Some(inner.to_string());
+        }
+    }
+    None
+}
+
+/// The strings of a list or tuple literal such as `["GET", "POST"]`, upper-cased.
+fn string_list(node: Node, source: &str) -> Vec<String> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter_map(|item| string_literal(item, source))
+        .map(|item| item.to_ascii_uppercase())
+        .collect()
+}
+
+fn text(node: Node, source: &str) -> String {
+    source[node.start_byte()..node.end_byte()].to_string()
+}
+
+fn walk<'a>(node: Node<'a>, f: &mut impl FnMut(Node<'a>)) {
+    f(node);
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk(child, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::indexer::extract::python::extract_python_symbols;
+    use crate::indexer::extract::symbol::{ExtractedFrameworkPattern, FrameworkPatternKind};
+
+    fn patterns(source: &str) -> Vec<ExtractedFrameworkPattern> {
+        extract_python_symbols(source).unwrap().framework_patterns
+    }
+
+    fn routes(patterns: &[ExtractedFrameworkPattern]) -> Vec<(String, String, String)> {
+        patterns
+            .iter()
+            .filter(|p| p.kind == FrameworkPatternKind::Route)
+            .map(|p| {
+                (
+                    p.http_method.clone().unwrap(),
+                    p.path.clone().unwrap(),
+                    p.handler.clone().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn route(method: &str, path: &str, handler: &str) -> (String, String, String) {
+        (method.into(), path.into(), handler.into())
+    }
+
+    #[test]
+    fn fastapi_routes_get_router_prefixes() {
+        let source = r#"
+from fastapi import APIRouter, FastAPI
+from .routers import users
+
+app = FastAPI()
+items = APIRouter(prefix="/items")
+
+@items.get("/{item_id}")
+async def read_item(item_id: int):
+    return {}
+
+@app.api_route("/ping", methods=["GET", "HEAD"])
+def ping():
+    return "pong"
+
+app.include_router(items, prefix="/api")
+app.include_router(users.router, prefix="/users")
+"#;
+        let found = patterns(source);
+        assert!(found.iter().all(|p| p.framework == "fastapi"));
+        assert_eq!(
+            routes(&found),
+            vec![
+                route("GET", "/api/items/{item_id}", "read_item"),
+                route("GET", "/ping", "ping"),
+                route("HEAD", "/ping", "ping"),
+            ]
+        );
+        let users = found.last().unwrap();
+        assert_eq!(users.kind, FrameworkPatternKind::Mount);
+        assert_eq!(users.path.as_deref(), Some("/users"));
+        assert_eq!(users.arguments.as_deref(), Some(".routers.users"));
+    }
+
+    #[test]
+    fn flask_blueprint_routes_and_methods() {
+        let source = r#"
+from flask import Blueprint, Flask
+
+bp = Blueprint("accounts", __name__, url_prefix="/accounts")
+
+@bp.route("/<int:id>", methods=["GET", "post"])
+@login_required
+def account(id):
+    pass
+
+@bp.delete("/<int:id>")
+def delete_account(id):
+    pass
+
+app = Flask(__name__)
+app.register_blueprint(bp, url_prefix="/v1")
+app.add_url_rule("/health", view_func=health)
+"#;
+        assert_eq!(
+            routes(&patterns(source)),
+            vec![
+                route("GET", "/v1/accounts/<int:id>", "account"),
+                route("POST", "/v1/accounts/<int:id>", "account"),
+                route("DELETE", "/v1/accounts/<int:id>", "delete_account"),
+                route("GET", "/health", "health"),
+            ]
+        );
+    }
+
+    #[test]
+    fn django_urlpatterns() {
+        let source = r#"
+from django.urls import include, path, re_path
+from . import views
+from .views import ArchiveView
+
+urlpatterns = [
+    path("", views.index, name="index"),
+    path("users/<int:pk>/", views.user_detail),
+    re_path(r"^archive/(?P<year>[0-9]{4})/$", ArchiveView.as_view()),
+    path("blog/", include("blog.urls")),
+]
+"#;
+        let found = patterns(source);
+        assert_eq!(
+            routes(&found),
+            vec![
+                route("ALL", "/", "views.index"),
+                route("ALL", "/users/<int:pk>", "views.user_detail"),
+                route("ALL", "/archive/(?P<year>[0-9]{4})", "ArchiveView"),
+            ]
+        );
+        assert_eq!(found[1].arguments.as_deref(), Some(".views"));
+        assert_eq!(found[2].arguments.as_deref(), Some(".views.ArchiveView"));
+        let blog = &found[3];
+        assert_eq!(blog.kind, FrameworkPatternKind::Mount);
+        assert_eq!(blog.path.as_deref(), Some("/blog"));
+        assert_eq!(blog.arguments.as_deref(), Some("blog.urls"));
+    }
+}
@@ -0,0 +1,649 @@
+//! Express, Koa, Hono and Fastify route extraction
+//!
+//! The four frameworks register routes the same way, as `router.get('/path', ...)`
+//! calls on an app or router object, and nest routers under prefixes:
+//! `app.use('/api', router)` in Express and Koa, `app.route('/api', sub)` in Hono and
+//! `fastify.register(plugin, { prefix: '/api' })` in Fastify. Each route becomes a
+//! `route` pattern whose path includes every prefix known in the file, including
+//! `new Router({ prefix })` and `new Hono().basePath()`. Each nesting becomes a
+//! `mount` pattern; when the mounted router is imported, its module specifier is
+//! kept in `arguments` so that prefixes can be carried into the file defining it
+//! once the whole repository is indexed.
+//!
+//! Which extractor handles a file is decided by the web framework it imports, see
+//! [`extract_web_patterns`].
+
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+
+use super::elysia::{
+    extract_elysia_patterns, extract_string_value, find_chain_root, text_for_node,
+};
+use super::nestjs::extract_nestjs_patterns;
+use super::symbol::{ExtractedFrameworkPattern, FrameworkPatternKind, Import};
+
+/// Route registration methods shared by the four frameworks.
+const ROUTE_METHODS: &[&str] = &[
+    "get", "post", "put", "delete", "patch", "options", "head", "all",
+];
+
+/// Most levels of in-file mounts followed when resolving a router's prefix.
+const MAX_MOUNT_DEPTH: usize = 8;
+
+/// Framework patterns of a JavaScript or TypeScript file, extracted as the web
+/// framework it imports (or `require`s) registers them. Files importing none of the
+/// known frameworks keep the Elysia extraction.
+pub fn extract_web_patterns(
+    root: Node,
+    source: &str,
+    imports: &[Import],
+) -> Vec<ExtractedFrameworkPattern> {
+    let modules = ModuleBindings::collect(root, source, imports);
+    match modules.framework() {
+        Some(framework @ ("express" | "koa" | "hono" | "fastify")) => {
+            extract_router_patterns(root, source, framework, &modules)
+        }
+        Some("nestjs") => extract_nestjs_patterns(root, source),
+        _ => extract_elysia_patterns(root, source),
+    }
+}
+
+/// Local names bound to modules, from imports and `require` calls.
+pub(super) struct ModuleBindings {
+    /// Local name to module specifier
+    pub(super) locals: HashMap<String, String>,
+    sources: Vec<String>,
+}
+
+impl ModuleBindings {
+    pub(super) fn collect(root: Node, source: &str, imports: &[Import]) -> Self {
+        let mut locals = HashMap::new();
+        let mut sources = Vec::new();
+        for import in imports {
+            let local = match import.alias.as_deref() {
+                Some("default") | None => import.name.clone(),
+                Some(alias) => alias.to_string(),
+            };
+            locals.insert(local, import.source.clone());
+            sources.push(import.source.clone());
+        }
+        walk(root, &mut |node| {
+            // const users = require('./routes/users')
+            if node.kind() != "call_expression" {
+                return;
+            }
+            let Some(function) = node.child_by_field_name("function") else {
+                return;
+            };
+            if text_for_node(function, source) != "require" {
+                return;
+            }
+            let Some(module) = node
+                .child_by_field_name("arguments")
+                .and_then(|args| named_children(args).into_iter().next())
+                .filter(|arg| arg.kind() == "string")
+                .map(|arg| extract_string_value(arg, source))
+            else {
+                return;
+            };
+            let declarator = node
+                .parent()
+                .filter(|parent| parent.kind() == "variable_declarator");
+            if let Some(name) = declarator.and_then(|d| d.child_by_field_name("name")) {
+                if name.kind() == "identifier" {
+                    locals.insert(text_for_node(name, source), module.clone());
+                }
+            }
+            sources.push(module);
+        });
+        Self { locals, sources }
+    }
+
+    fn framework(&self) -> Option<&'static str> {
+        self.sources.iter().find_map(|module| {
+            Some(match module.as_str() {
+                "express" => "express",
+                "koa" | "koa-router" | "@koa/router" => "koa",
+                "fastify" => "fastify",
+                "elysia" => "elysia",
+                "@nestjs/common" => "nestjs",
+                m if m == "hono" || m.starts_with("hono/") => "hono",
+                _ => return None,
+            })
+        })
+    }
+
+    /// The module `name` is imported from, when that is a file of the repository.
+    fn relative_source(&self, name: &str) -> Option<&str> {
+        self.locals
+            .get(name)
+            .map(String::as_str)
+            .filter(|module| module.starts_with('.'))
+    }
+}
+
+/// A route or mount found in the file, before prefixes are applied.
+pub(super) struct Registration {
+    pub line: u32,
+    pub column: u32,
+    pub kind: FrameworkPatternKind,
+    pub http_method: Option<String>,
+    pub path: String,
+    /// Mounted router, for mounts
+    pub name: Option<String>,
+    pub handler: Option<String>,
+    /// Module the mounted router (or the handler) is imported from
+    pub arguments: Option<String>,
+    /// App or router the registration is made on
+    pub parent_chain: Option<String>,
+}
+
+fn extract_router_patterns(
+    root: Node,
+    source: &str,
+    framework: &str,
+    modules: &ModuleBindings,
+) -> Vec<ExtractedFrameworkPattern> {
+    let mut base_prefixes: HashMap<String, String> = HashMap::new();
+    let mut registrations: Vec<Registration> = Vec::new();
+    walk(root, &mut |node| match node.kind() {
+        "variable_declarator" => {
+            let name = node.child_by_field_name("name");
+            let value = node.child_by_field_name("value");
+            if let (Some(name), Some(value)) = (name, value) {
+                if let Some(prefix) = constructed_prefix(value, source) {
+                    base_prefixes.insert(text_for_node(name, source), prefix);
+                }
+            }
+        }
+        "call_expression" => registrations.extend(registrations_of_call(node, source)),
+        _ => {}
+    });
+
+    for registration in &mut registrations {
+        registration.arguments = registration
+            .name
+            .as_deref()
+            .and_then(|name| modules.relative_source(name))
+            .map(str::to_string);
+    }
+    apply_prefixes(framework, registrations, &base_prefixes)
+}
+
+/// Patterns of the routes and mounts of a file, with the prefixes of the routers
+/// they are registered on: the router's own (`base_prefixes`), then the paths it is
+/// mounted at in the file.
+pub(super) fn apply_prefixes(
+    framework: &str,
+    registrations: Vec<Registration>,
+    base_prefixes: &HashMap<String, String>,
+) -> Vec<ExtractedFrameworkPattern> {
+    // Prefix of each router: where it is mounted in this file, then its own prefix
+    let mounted_on: HashMap<&str, (&str, &str)> = registrations
+        .iter()
+        .filter(|r| r.kind == FrameworkPatternKind::Mount)
+        .filter_map(|r| {
+            Some((
+                r.name.as_deref()?,
+                (r.parent_chain.as_deref()?, r.path.as_str()),
+            ))
+        })
+        .collect();
+    let prefix_of = |router: Option<&str>| -> String {
+        let mut prefix = String::new();
+        let mut current = router;
+        for _ in 0..MAX_MOUNT_DEPTH {
+            let Some(name) = current else {
+                break;
+            };
+            prefix = join_paths(base_prefixes.get(name).map_or("", String::as_str), &prefix);
+            let Some(&(parent, mount_path)) = mounted_on.get(name) else {
+                break;
+            };
+            prefix = join_paths(mount_path, &prefix);
+            current = Some(parent);
+        }
+        prefix
+    };
+
+    let mut patterns: Vec<ExtractedFrameworkPattern> = registrations
+        .iter()
+        .map(|r| {
+            let prefix = prefix_of(r.parent_chain.as_deref());
+            ExtractedFrameworkPattern {
+                line: r.line,
+                column: r.column,
+                framework: framework.to_string(),
+                kind: r.kind.clone(),
+                http_method: r.http_method.clone(),
+                path: Some(join_paths(&prefix, &r.path)),
+                name: r.name.clone(),
+                handler: r.handler.clone(),
+                arguments: r.arguments.clone(),
+                parent_chain: r.parent_chain.clone(),
+            }
+        })
+        .collect();
+    patterns.sort_by_key(|p| (p.line, p.column));
+    patterns
+}
+
+/// The routes and mounts a call registers.
+fn registrations_of_call(node: Node, source: &str) -> Vec<Registration> {
+    let Some(function) = node
+        .child_by_field_name("function")
+        .filter(|f| f.kind() == "member_expression")
+    else {
+        return Vec::new();
+    };
+    let (Some(property), Some(object), Some(args)) = (
+        function.child_by_field_name("property"),
+        function.child_by_field_name("object"),
+        node.child_by_field_name("arguments"),
+    ) else {
+        return Vec::new();
+    };
+    let method = text_for_node(property, source);
+    let args = named_children(args);
+    let position = property.start_position();
+    let registration =
+        |kind, http_method: Option<String>, path: String, name, handler| Registration {
+            line: position.row as u32 + 1,
+            column: position.column as u32,
+            kind,
+            http_method,
+            path,
+            name,
+            handler,
+            arguments: None,
+            parent_chain: find_chain_root(function, source),
+        };
+    let string_at = |index: usize| {
+        args.get(index)
+            .filter(|arg| matches!(arg.kind(), "string" | "template_string"))
+            .map(|arg| extract_string_value(*arg, source))
+    };
+
+    let lower = method.to_ascii_lowercase();
+    if ROUTE_METHODS.contains(&lower.as_str()) {
+        // router.get('/path', ...handlers), Koa's router.get('name', '/path', ...), or
+        // Express's router.route('/path').get(handler)
+        let (path, handlers_from) = match (string_at(0), string_at(1)) {
+            (Some(_), Some(path)) if is_route_path(&path) => (Some(path), 2),
+            (Some(path), _) if args.len() >= 2 => (Some(path), 1),
+            _ => (express_route_path(object, source), 0),
+        };
+        let Some(path) = path.filter(|p| is_route_path(p)) else {
+            return Vec::new();
+        };
+        let handler = args
+            .get(handlers_from..)
+            .and_then(|h| h.last())
+            .and_then(|h| handler_name(*h, source));
+        return vec![registration(
+            FrameworkPatternKind::Route,
+            Some(lower.to_ascii_uppercase()),
+            path,
+            None,
+            handler,
+        )];
+    }
+
+    match method.as_str() {
+        // fastify.route({ method: ['GET', 'HEAD'], url: '/path', handler })
+        "route" if args.len() == 1 && args[0].kind() == "object" => {
+            let options = object_pairs(args[0], source);
+            let Some(path) = options
+                .get("url")
+                .or_else(|| options.get("path"))
+                .and_then(|value| string_value(*value, source))
+            else {
+                return Vec::new();
+            };
+            let handler = options
+                .get("handler")
+                .and_then(|h| handler_name(*h, source));
+            let methods: Vec<String> = match options.get("method") {
+                Some(value) if value.kind() == "array" => named_children(*value)
+                    .into_iter()
+                    .filter_map(|m| string_value(m, source))
+                    .collect(),
+                Some(value) => string_value(*value, source).into_iter().collect(),
+                None => Vec::new(),
+            };
+            methods
+                .into_iter()
+                .map(|m| {
+                    registration(
+                        FrameworkPatternKind::Route,
+                        Some(m.to_ascii_uppercase()),
+                        path.clone(),
+                        None,
+                        handler.clone(),
+                    )
+                })
+                .collect()
+        }
+        // app.use('/api', router), app.use('/api', router.routes()), app.use(router),
+        // and Hono's app.route('/api', sub)
+        "use" | "route" => {
+            let (path, mounted) = match string_at(0) {
+                Some(path) => (path, args.get(1)),
+                None if method == "use" => (String::new(), args.first()),
+                None => return Vec::new(),
+            };
+            let Some(mounted) = mounted.and_then(|m| mounted_router(*m, source)) else {
+                return Vec::new();
+            };
+            vec![registration(
+                FrameworkPatternKind::Mount,
+                None,
+                path,
+                Some(mounted),
+                None,
+            )]
+        }
+        // fastify.register(plugin, { prefix: '/api' })
+        "register" => {
+            let Some(plugin) = args.first().and_then(|p| mounted_router(*p, source)) else {
+                return Vec::new();
+            };
+            let prefix = args
+                .get(1)
+                .filter(|options| options.kind() == "object")
+                .and_then(|options| object_pairs(*options, source).get("prefix").copied())
+                .and_then(|prefix| string_value(prefix, source))
+                .unwrap_or_default();
+            vec![registration(
+                FrameworkPatternKind::Mount,
+                None,
+                prefix,
+                Some(plugin),
+                None,
+            )]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The prefix a router is constructed with: `new Router({ prefix: '/api' })` or
+/// `new Hono().basePath('/api')`.
+fn constructed_prefix(value: Node, source: &str) -> Option<String> {
+    match value.kind() {
+        "new_expression" => {
+            let args = value.child_by_field_name("arguments")?;
+            let options = named_children(args).into_iter().next()?;
+            if options.kind() != "object" {
+                return None;
+            }
+            string_value(*object_pairs(options, source).get("prefix")?, source)
+        }
+        "call_expression" => {
+            let function = value.child_by_field_name("function")?;
+            let property = function.child_by_field_name("property")?;
+            if text_for_node(property, source) != "basePath" {
+                return None;
+            }
+            let args = value.child_by_field_name("arguments")?;
+            string_value(named_children(args).into_iter().next()?, source)
+        }
+        _ => None,
+    }
+}
+
+/// The path of `router.route('/path')` when `object` is that call, or a route
+/// method chained on it.
+fn express_route_path(object: Node, source: &str) -> Option<String> {
+    if object.kind() != "call_expression" {
+        return None;
+    }
+    let function = object.child_by_field_name("function")?;
+    let property = function.child_by_field_name("property")?;
+    let method = text_for_node(property, source).to_ascii_lowercase();
+    // router.route('/path').get(list).post(create): past the earlier methods
+    if ROUTE_METHODS.contains(&method.as_str()) {
+        return express_route_path(function.child_by_field_name("object")?, source);
+    }
+    if method != "route" {
+        return None;
+    }
+    let args = named_children(object.child_by_field_name("arguments")?);
+    match args.as_slice() {
+        [path] => string_value(*path, source),
+        _ => None,
+    }
+}
+
+/// The router a mount argument names: `router`, `router.routes()`, `import('./x')`
+/// is not followed.
+fn mounted_router(node: Node, source: &str) -> Option<String> {
+    match node.kind() {
+        "identifier" => Some(text_for_node(node, source)),
+        // Koa's router.routes() and router.middleware()
+        "call_expression" => {
+            let function = node.child_by_field_name("function")?;
+            let property = function.child_by_field_name("property")?;
+            if !matches!(
+                text_for_node(property, source).as_str(),
+                "routes" | "middleware"
+            ) {
+                return None;
+            }
+            let object = function.child_by_field_name("object")?;
+            (object.kind() == "identifier").then(|| text_for_node(object, source))
+        }
+        _ => None,
+    }
+}
+
+fn handler_name(node: Node, source: &str) -> Option<String> {
+    match node.kind() {
+        "identifier" | "member_expression" => Some(text_for_node(node, source)),
+        "arrow_function" | "function_expression" | "function" => Some("<anonymous>".to_string()),
+        // A wrapper such as asyncHandler(listUsers): the wrapped handler
+        "call_expression" => {
+            let args = named_children(node.child_by_field_name("arguments")?);
+            match args.last() {
+                Some(last) => handler_name(*last, source),
+                None => Some(text_for_node(node.child_by_field_name("function")?, source)),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Paths as routes are written; other first arguments (`cache.get('key', ...)`,
+/// `app.get('env')`) are not routes.
+fn is_route_path(path: &str) -> bool {
+    path.starts_with('/') || path == "*"
+}
+
+/// Join a prefix and a path, with one `/` between them and none at the end.
+pub fn join_paths(prefix: &str, path: &str) -> String {
+    let joined = format!(
+        "{}/{}",
+        prefix.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+    let trimmed = joined.trim_end_matches('/');
+    if trimmed.is_empty() {
+        if prefix.is_empty() && path.is_empty() {
+            String::new()
+        } else {
+            "/".to_string()
+        }
+    } else if trimmed.starts_with('/') {
+        trimmed.to_string()
+    } else {
+        format!("/{trimmed}")
+    }
+}
+
+fn string_value(node: Node, source: &str) -> Option<String> {
+    matches!(node.kind(), "string" | "template_string").then(|| extract_string_value(node, source))
+}
+
+/// The values of an object literal's `key: value` pairs and shorthand properties.
+fn object_pairs<'a>(object: Node<'a>, source: &str) -> HashMap<String, Node<'a>> {
+    let mut pairs = HashMap::new();
+    for child in named_children(object) {
+        match child.kind() {
+            "pair" => {
+                let (Some(key), Some(value)) = (
+                    child.child_by_field_name("key"),
+                    child.child_by_field_name("value"),
+                ) else {
+                    continue;
+                };
+                let key = text_for_node(key, source)
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .to_string();
+                pairs.insert(key, value);
+            }
+            "shorthand_property_identifier" => {
+                pairs.insert(text_for_node(child, source), child);
+            }
+            _ => {}
+        }
+    }
+    pairs
+}
+
+fn named_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).collect()
+}
+
+fn walk<'a>(node: Node<'a>, f: &mut impl FnMut(Node<'a>)) {
+    f(node);
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk(child, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::extract::typescript::extract_typescript_symbols;
+    use crate::indexer::parser::{parser_for_id, LanguageId};
+
+    fn extract(source: &str) -> Vec<ExtractedFrameworkPattern> {
+        let imports = extract_typescript_symbols(LanguageId::Typescript, source)
+            .unwrap()
+            .imports;
+        let mut parser = parser_for_id(LanguageId::Typescript).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        extract_web_patterns(tree.root_node(), source, &imports)
+    }
+
+    fn routes(patterns: &[ExtractedFrameworkPattern]) -> Vec<(String, String, Option<String>)> {
+        patterns
+            .iter()
+            .filter(|p| p.kind == FrameworkPatternKind::Route)
+            .map(|p| {
+                (
+                    p.http_method.clone().unwrap(),
+                    p.path.clone().unwrap(),
+                    p.handler.clone(),
+                )
+            })
+            .collect()
+    }
+
+    fn route(method: &str, path: &str, handler: &str) -> (String, String, Option<String>) {
+        (method.into(), path.into(), Some(handler.into()))
+    }
+
+    #[test]
+    fn express_routes_resolve_in_file_mounts() {
+        let source = r#"
+const express = require('express');
+const users = require('./routes/users');
+const app = express();
+const admin = express.Router();
+
+admin.post('/users/:id/ban', requireAdmin, banUser);
+admin.route('/reports').get(listReports).delete(clearReports);
+app.use('/api/admin', admin);
+app.use('/api/users', users);
+app.get('/health', (req, res) => res.send('ok'));
+app.get('env');
+"#;
+        let patterns = extract(source);
+        assert!(patterns.iter().all(|p| p.framework == "express"));
+        assert_eq!(
+            routes(&patterns),
+            vec![
+                route("POST", "/api/admin/users/:id/ban", "banUser"),
+                route("GET", "/api/admin/reports", "listReports"),
+                route("DELETE", "/api/admin/reports", "clearReports"),
+                route("GET", "/health", "<anonymous>"),
+            ]
+        );
+        let mounts: Vec<_> = patterns
+            .iter()
+            .filter(|p| p.kind == FrameworkPatternKind::Mount)
+            .map(|p| (p.path.as_deref(), p.name.as_deref(), p.arguments.as_deref()))
+            .collect();
+        assert_eq!(
+            mounts,
+            vec![
+                (Some("/api/admin"), Some("admin"), None),
+                (Some("/api/users"), Some("users"), Some("./routes/users")),
+            ]
+        );
+    }
+
+    #[test]
+    fn koa_hono_and_fastify_prefixes() {
+        let koa = r#"
+import Router from '@koa/router';
+const router = new Router({ prefix: '/v1' });
+router.get('user', '/users/:id', getUser);
+"#;
+        assert_eq!(
+            routes(&extract(koa)),
+            vec![route("GET", "/v1/users/:id", "getUser")]
+        );
+
+        let hono = r#"
+import { Hono } from 'hono';
+const books = new Hono();
+books.get('/:id', (c) => c.json({}));
+const app = new Hono().basePath('/api');
+app.route('/books', books);
+"#;
+        assert_eq!(
+            routes(&extract(hono)),
+            vec![route("GET", "/api/books/:id", "<anonymous>")]
+        );
+
+        let fastify = r#"
+import Fastify from 'fastify';
+const server = Fastify();
+server.route({ method: ['GET', 'HEAD'], url: '/items', handler: listItems });
+server.register(itemsPlugin, { prefix: '/v2' });
+"#;
+        let patterns = extract(fastify);
+        assert_eq!(
+            routes(&patterns),
+            vec![
+                route("GET", "/items", "listItems"),
+                route("HEAD", "/items", "listItems")
+            ]
+        );
+        assert_eq!(patterns[2].kind, FrameworkPatternKind::Mount);
+        assert_eq!(patterns[2].path.as_deref(), Some("/v2"));
+    }
+
+    #[test]
+    fn joins_paths() {
+        assert_eq!(join_paths("/api/", "/users/"), "/api/users");
+        assert_eq!(join_paths("/api", "/"), "/api");
+        assert_eq!(join_paths("", "/"), "/");
+        assert_eq!(join_paths("", "users"), "/users");
+        assert_eq!(join_paths("", ""), "");
+    }
+}
@@ -0,0 +1,496 @@
+//! Ruby symbol extraction
+//!
+//! Classes, modules, methods and constants are read from the tree-sitter syntax
+//! tree. Method bodies are not descended into; other statements (conditionals,
+//! blocks) are, since Ruby can define methods and constants inside them. A bare
+//! `private`/`protected` makes the methods after it in the same body private, as
+//! does naming them in `private :name`.
+
+use anyhow::{anyhow, Result};
+use tree_sitter::Node;
+
+use super::symbol::{ByteSpan, ExtractedFile, ExtractedSymbol, Import, LineSpan, SymbolKind};
+use crate::indexer::parser::{parser_for_id, LanguageId};
+
+pub fn extract_ruby_symbols(source: &str) -> Result<ExtractedFile> {
+    let parsed = parse(source)?;
+    Ok(ExtractedFile {
+        symbols: parsed.symbols,
+        imports: parsed.imports,
+        type_edges: Vec::new(),
+        dataflow_edges: Vec::new(),
+        todos: Vec::new(),
+        jsdoc_entries: Vec::new(),
+        decorators: Vec::new(),
+        framework_patterns: Vec::new(),
+    })
+}
+
+/// `(extends, mixins, aliases)` of the class or module whose text is `text`, in the
+/// shape of `parse_type_relations`. The superclass is the `extends` target;
+/// `include`/`extend`/`prepend` in the body are reported as mixins.
+pub fn ruby_type_relations(text: &str) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let mut extends = Vec::new();
+    let mut mixins = Vec::new();
+    let Ok(parsed) = parse(text) else {
+        return (extends, mixins, Vec::new());
+    };
+    for relation in parsed.relations.iter().filter(|r| r.symbol == 0) {
+        match relation.kind {
+            RelationKind::Superclass => extends.push(relation.target.clone()),
+            RelationKind::Mixin => mixins.push(relation.target.clone()),
+        }
+    }
+    (extends, mixins, Vec::new())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelationKind {
+    Superclass,
+    Mixin,
+}
+
+struct Relation {
+    symbol: usize,
+    kind: RelationKind,
+    target: String,
+}
+
+#[derive(Default)]
+struct ParsedRuby {
+    symbols: Vec<ExtractedSymbol>,
+    imports: Vec<Import>,
+    relations: Vec<Relation>,
+    /// `private :a, :b` – names made private after their definition, with the
+    /// class or module whose methods they are (`None` for the whole file)
+    private_names: Vec<(String, Option<usize>)>,
+}
+
+fn parse(source: &str) -> Result<ParsedRuby> {
+    let mut parser = parser_for_id(LanguageId::Ruby)?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| anyhow!("Failed to parse source"))?;
+    let mut out = ParsedRuby::default();
+    read_body(source, tree.root_node(), None, &mut out);
+
+    for (name, owner) in std::mem::take(&mut out.private_names) {
+        let owner_span = owner.map(|o| out.symbols[o].bytes);
+        for symbol in out.symbols.iter_mut() {
+            let inside = owner_span.as_ref().is_none_or(|span| {
+                span.start <= symbol.bytes.start && symbol.bytes.end <= span.end
+            });
+            if symbol.kind == SymbolKind::Function && symbol.name == name && inside {
+                symbol.exported = false;
+            }
+        }
+    }
+
+    out.symbols.sort_by_key(|s| s.bytes.start);
+    Ok(out)
+}
+
+/// The statements of a file, class or module body. `owner` is the class or module.
+fn read_body(source: &str, node: Node, owner: Option<usize>, out: &mut ParsedRuby) {
+    let mut private = false;
+    let mut cursor = node.walk();
+    for statement in node.named_children(&mut cursor) {
+        match statement.kind() {
+            // A bare `private`/`protected`/`public`
+            "identifier" => match text(source, statement) {
+                "private" | "protected" => private = true,
+                "public" => private = false,
+                _ => {}
+            },
+            _ => read_statement(source, statement, owner, private, out),
+        }
+    }
+}
+
+fn read_statement(
+    source: &str,
+    node: Node,
+    owner: Option<usize>,
+    private: bool,
+    out: &mut ParsedRuby,
+) {
+    match node.kind() {
+        "class" | "module" => {
+            let Some(name) = node.child_by_field_name("name") else {
+                return;
+            };
+            let kind = if node.kind() == "class" {
+                SymbolKind::Class
+            } else {
+                SymbolKind::Module
+            };
+            let symbol = push_symbol(out, node, constant_name(source, name), kind, true);
+            let superclass = node
+                .child_by_field_name("superclass")
+                .and_then(|superclass| superclass.named_child(0))
+                .filter(|parent| matches!(parent.kind(), "constant" | "scope_resolution"));
+            if let Some(parent) = superclass {
+                out.relations.push(Relation {
+                    symbol,
+                    kind: RelationKind::Superclass,
+                    target: constant_name(source, parent),
+                });
+            }
+            if let Some(body) = node.child_by_field_name("body") {
+                read_body(source, body, Some(symbol), out);
+            }
+        }
+        // `class << self`: its methods belong to no class symbol of their own
+        "singleton_class" => {
+            if let Some(body) = node.child_by_field_name("body") {
+                read_body(source, body, None, out);
+            }
+        }
+        "method" | "singleton_method" => {
+            if let Some(name) = node.child_by_field_name("name") {
+                let name = text(source, name).to_string();
+                push_symbol(out, node, name, SymbolKind::Function, !private);
+            }
+        }
+        "assignment"
+            if node
+                .child_by_field_name("left")
+                .is_some_and(|left| left.kind() == "constant") =>
+        {
+            let name = text(source, node.child_by_field_name("left").unwrap()).to_string();
+            push_symbol(out, node, name, SymbolKind::Const, true);
+        }
+        "call" if node.child_by_field_name("receiver").is_none() => {
+            read_call(source, node, owner, private, out)
+        }
+        "comment" => {}
+        // Conditionals, `begin` blocks, blocks passed to calls and so on
+        _ => read_nested(source, node, owner, private, out),
+    }
+}
+
+fn read_nested(
+    source: &str,
+    node: Node,
+    owner: Option<usize>,
+    private: bool,
+    out: &mut ParsedRuby,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        read_statement(source, child, owner, private, out);
+    }
+}
+
+/// A call without a receiver: visibility, mixins and requires.
+fn read_call(source: &str, node: Node, owner: Option<usize>, private: bool, out: &mut ParsedRuby) {
+    let method = node
+        .child_by_field_name("method")
+        .map_or("", |method| text(source, method));
+    let arguments: Vec<Node> = node
+        .child_by_field_name("arguments")
+        .map(|arguments| arguments.named_children(&mut arguments.walk()).collect())
+        .unwrap_or_default();
+    match method {
+        "private" | "protected" | "public" => {
+            let private = method != "public";
+            for argument in arguments {
+                match argument.kind() {
+                    // `private def helper`
+                    "method" | "singleton_method" => {
+                        read_statement(source, argument, owner, private, out)
+                    }
+                    _ if private => {
+                        if let Some(name) = string_value(source, argument) {
+                            out.private_names.push((name.to_string(), owner));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        "include" | "extend" | "prepend" => {
+            let Some(owner) = owner else {
+                return;
+            };
+            for argument in arguments {
+                if matches!(argument.kind(), "constant" | "scope_resolution") {
+                    out.relations.push(Relation {
+                        symbol: owner,
+                        kind: RelationKind::Mixin,
+                        target: constant_name(source, argument),
+                    });
+                }
+            }
+        }
+        "require" | "require_relative" | "load" | "autoload" => {
+            if let Some(import) = import_for(source, method, &arguments) {
+                out.imports.push(import);
+            }
+        }
+        _ => read_nested(source, node, owner, private, out),
+    }
+}
+
+fn push_symbol(
+    out: &mut ParsedRuby,
+    node: Node,
+    name: String,
+    kind: SymbolKind,
+    exported: bool,
+) -> usize {
+    out.symbols.push(ExtractedSymbol {
+        name,
+        kind,
+        exported,
+        bytes: ByteSpan {
+            start: node.start_byte(),
+            end: node.end_byte(),
+        },
+        lines: LineSpan {
+            start: node.start_position().row as u32 + 1,
+            end: node.end_position().row as u32 + 1,
+        },
+    });
+    out.symbols.len() - 1
+}
+
+fn text<'s>(source: &'s str, node: Node) -> &'s str {
+    &source[node.byte_range()]
+}
+
+/// The last segment of a constant path: `Base::Record` is `Record`.
+fn constant_name(source: &str, node: Node) -> String {
+    let name = node.child_by_field_name("name").unwrap_or(node);
+    text(source, name).to_string()
+}
+
+/// The contents of a plain string or symbol literal.
+fn string_value<'s>(source: &'s str, node: Node) -> Option<&'s str> {
+    match node.kind() {
+        "simple_symbol" => Some(text(source, node).trim_start_matches(':')),
+        "string" => {
+            let mut cursor = node.walk();
+            let mut parts = node.named_children(&mut cursor);
+            match (parts.next(), parts.next()) {
+                (None, _) => Some(""),
+                (Some(content), None) if content.kind() == "string_content" => {
+                    Some(text(source, content))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Only `require_relative` names a file of this project; `require`, `load` and
+/// `autoload` go through the load path, so they are kept as written.
+fn import_for(source: &str, method: &str, arguments: &[Node]) -> Option<Import> {
+    let mut arguments = arguments.iter();
+    if method == "autoload" {
+        // autoload :Name, "path"
+        let name = string_value(source, *arguments.next()?)?;
+        let path = string_value(source, *arguments.next()?)?;
+        return (!path.is_empty()).then(|| Import {
+            name: name.to_string(),
+            source: path.to_string(),
+            alias: None,
+        });
+    }
+    let path = string_value(source, *arguments.next()?)?;
+    if path.is_empty() {
+        return None;
+    }
+    if method != "require_relative" {
+        return Some(Import {
+            name: path.to_string(),
+            source: path.to_string(),
+            alias: None,
+        });
+    }
+    let relative = if path.starts_with('.') {
+        path.to_string()
+    } else {
+        format!("./{path}")
+    };
+    let source = if relative.ends_with(".rb") {
+        relative
+    } else {
+        format!("{relative}.rb")
+    };
+    let stem = path.rsplit('/').next().unwrap_or(path);
+    Some(Import {
+        name: camelize(stem.strip_suffix(".rb").unwrap_or(stem)),
+        source,
+        alias: None,
+    })
+}
+
+/// `user_profile` -> `UserProfile`, the constant a file conventionally defines.
+fn camelize(stem: &str) -> String {
+    stem.split(['_', '-'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(file: &'a ExtractedFile, name: &str) -> &'a ExtractedSymbol {
+        file.symbols
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("missing symbol {name}: {:?}", file.symbols))
+    }
+
+    #[test]
+    fn extracts_classes_modules_methods_and_constants() {
+        let source = r#"require "json"
+require_relative "../models/user_profile"
+
+module Billing
+  TAX_RATE = 0.2
+  LIMITS = {
+    daily: 10,
+  }.freeze
+
+  class Invoice < Base::Record
+    include Comparable
+    extend Forwardable, Enumerable
+
+    def self.build(attrs) = new(attrs)
+
+    def total
+      return 0 if lines.empty?
+      sum = lines.sum do |line|
+        line.amount
+      end
+      sum * (1 + TAX_RATE) # end
+    end
+
+    def <=>(other)
+      total <=> other.total
+    end
+
+    def paid?
+      !!@paid_at unless draft?
+    end
+
+    private
+
+    def lines
+      @lines ||= <<~SQL.split("\n")
+        SELECT * FROM lines WHERE state = 'end'
+      SQL
+    end
+  end
+end
+"#;
+        let file = extract_ruby_symbols(source).unwrap();
+        let names: Vec<&str> = file.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "Billing", "TAX_RATE", "LIMITS", "Invoice", "build", "total", "<=>", "paid?",
+                "lines"
+            ]
+        );
+
+        let billing = find(&file, "Billing");
+        assert_eq!(billing.kind, SymbolKind::Module);
+        assert_eq!((billing.lines.start, billing.lines.end), (4, 40));
+        assert_eq!(&source[billing.bytes.end - 3..billing.bytes.end], "end");
+
+        let limits = find(&file, "LIMITS");
+        assert_eq!(limits.kind, SymbolKind::Const);
+        assert_eq!((limits.lines.start, limits.lines.end), (6, 8));
+
+        let invoice = find(&file, "Invoice");
+        assert_eq!(invoice.kind, SymbolKind::Class);
+        assert_eq!((invoice.lines.start, invoice.lines.end), (10, 39));
+
+        let build = find(&file, "build");
+        assert_eq!((build.lines.start, build.lines.end), (14, 14));
+
+        let total = find(&file, "total");
+        assert_eq!((total.lines.start, total.lines.end), (16, 22));
+        assert!(total.exported);
+
+        assert_eq!(find(&file, "paid?").lines.end, 30);
+        let lines = find(&file, "lines");
+        assert!(!lines.exported);
+        assert_eq!((lines.lines.start, lines.lines.end), (34, 38));
+
+        assert_eq!(file.imports.len(), 2);
+        // A library on the load path, not a file next to this one
+        assert_eq!(file.imports[0].name, "json");
+        assert_eq!(file.imports[0].source, "json");
+        assert_eq!(file.imports[1].name, "UserProfile");
+        assert_eq!(file.imports[1].source, "../models/user_profile.rb");
+    }
+
+    #[test]
+    fn class_relations_cover_superclass_and_mixins() {
+        let text = "class Invoice < Base::Record\n  include Comparable\n  extend Forwardable, Enumerable\n  class Line\n    include Nested\n  end\nend";
+        let (extends, mixins, aliases) = ruby_type_relations(text);
+        assert_eq!(extends, ["Record"]);
+        assert_eq!(mixins, ["Comparable", "Forwardable", "Enumerable"]);
+        assert!(aliases.is_empty());
+    }
+
+    #[test]
+    fn private_symbols_and_literals_do_not_confuse_nesting() {
+        let source = r#"class Api
+  ROUTES = %w[if end do]
+  PATTERN = %r{/users/(\d+)}i
+
+  def call(env)
+    while running? do
+      step
+    end
+    for x in items
+      x
+    end
+    path = env["PATH"] =~ /\/end$/ ? :end : :other
+    { if: 1, end: 2 }
+  end
+
+  def helper; end
+  private :helper
+
+  class << self
+    def instance
+      @instance ||= new
+    end
+  end
+=begin
+def not_a_method
+end
+=end
+  def last = :ok
+end
+__END__
+def after_end
+"#;
+        let file = extract_ruby_symbols(source).unwrap();
+        let names: Vec<&str> = file.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Api", "ROUTES", "PATTERN", "call", "helper", "instance", "last"]
+        );
+        assert_eq!(find(&file, "call").lines.end, 14);
+        assert!(!find(&file, "helper").exported);
+        assert!(find(&file, "instance").exported);
+        assert_eq!(find(&file, "Api").lines.end, 29);
+        assert_eq!(find(&file, "last").lines.start, 28);
+    }
+}
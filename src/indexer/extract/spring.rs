@@ -0,0 +1,278 @@
+//! Spring MVC route extraction
+//!
+//! A Spring controller declares its routes with annotations: `@RequestMapping("/users")`
+//! on the class gives the prefix, and `@GetMapping("/{id}")`, `@PostMapping` and the
+//! other mapping annotations on its methods give each route's method and path.
+//! `@RequestMapping(value = "/x", method = RequestMethod.POST)` on a method names its
+//! methods explicitly, and matches any method when it names none.
+
+use tree_sitter::Node;
+
+use super::routers::join_paths;
+use super::symbol::{ExtractedFrameworkPattern, FrameworkPatternKind};
+
+/// Method annotations and the HTTP method of the routes they declare.
+const MAPPING_ANNOTATIONS: &[(&str, &str)] = &[
+    ("GetMapping", "GET"),
+    ("PostMapping", "POST"),
+    ("PutMapping", "PUT"),
+    ("DeleteMapping", "DELETE"),
+    ("PatchMapping", "PATCH"),
+];
+
+/// Class annotations of interfaces that call routes rather than serve them.
+const CLIENT_ANNOTATIONS: &[&str] = &["FeignClient", "HttpExchange"];
+
+/// Extract the routes of the Spring controllers in a Java AST
+pub fn extract_spring_routes(root: Node, source: &str) -> Vec<ExtractedFrameworkPattern> {
+    let mut patterns = Vec::new();
+    collect_controllers(root, source, &mut patterns);
+    patterns.sort_by_key(|p| (p.line, p.column));
+    patterns
+}
+
+fn collect_controllers(node: Node, source: &str, patterns: &mut Vec<ExtractedFrameworkPattern>) {
+    if node.kind() == "class_declaration" {
+        let annotations = annotations_of(node);
+        let is_client = annotations
+            .iter()
+            .any(|a| CLIENT_ANNOTATIONS.contains(&annotation_name(*a, source).as_str()));
+        if let (false, Some(class_name), Some(body)) = (
+            is_client,
+            node.child_by_field_name("name"),
+            node.child_by_field_name("body"),
+        ) {
+            // A class mapped to several paths serves its routes under each
+            let prefixes = annotations
+                .iter()
+                .find(|a| annotation_name(**a, source) == "RequestMapping")
+                .map(|a| mapping_paths(*a, source))
+                .unwrap_or_else(|| vec![String::new()]);
+            let class_name = text(class_name, source);
+            collect_routes(body, source, &class_name, &prefixes, patterns);
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_controllers(child, source, patterns);
+    }
+}
+
+fn collect_routes(
+    body: Node,
+    source: &str,
+    class_name: &str,
+    prefixes: &[String],
+    patterns: &mut Vec<ExtractedFrameworkPattern>,
+) {
+    let mut cursor = body.walk();
+    for member in body.named_children(&mut cursor) {
+        if member.kind() != "method_declaration" {
+            continue;
+        }
+        let Some(method_name) = member.child_by_field_name("name") else {
+            continue;
+        };
+        let handler = format!("{class_name}.{}", text(method_name, source));
+        for annotation in annotations_of(member) {
+            let name = annotation_name(annotation, source);
+            let http_methods = match MAPPING_ANNOTATIONS.iter().find(|(a, _)| *a == name) {
+                Some(&(_, http_method)) => vec![http_method.to_string()],
+                None if name == "RequestMapping" => request_methods(annotation, source),
+                None => continue,
+            };
+            let position = annotation.start_position();
+            for prefix in prefixes {
+                for path in mapping_paths(annotation, source) {
+                    for http_method in &http_methods {
+                        patterns.push(ExtractedFrameworkPattern {
+                            line: position.row as u32 + 1,
+                            column: position.column as u32,
+                            framework: "spring".to_string(),
+                            kind: FrameworkPatternKind::Route,
+                            http_method: Some(http_method.clone()),
+                            path: Some(join_paths(&join_paths("/", prefix), &path)),
+                            name: None,
+                            handler: Some(handler.clone()),
+                            arguments: None,
+                            parent_chain: Some(class_name.to_string()),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn annotations_of(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    let Some(modifiers) = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "modifiers")
+    else {
+        return Vec::new();
+    };
+    let mut cursor = modifiers.walk();
+    modifiers
+        .named_children(&mut cursor)
+        .filter(|child| matches!(child.kind(), "annotation" | "marker_annotation"))
+        .collect()
+}
+
+/// The simple name of an annotation: `GetMapping` for `@org.springframework...GetMapping`.
+fn annotation_name(annotation: Node, source: &str) -> String {
+    annotation
+        .child_by_field_name("name")
+        .map(|name| {
+            let name = text(name, source);
+            name.rsplit('.').next().unwrap_or(&name).to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// The paths a mapping annotation names, from its single value or its `value` or
+/// `path` element. A mapping naming none maps the empty path.
+fn mapping_paths(annotation: Node, source: &str) -> Vec<String> {
+    let value = arguments_of(annotation).into_iter().find_map(|arg| {
+        if arg.kind() != "element_value_pair" {
+            return Some(arg);
+        }
+        let key = text(arg.child_by_field_name("key")?, source);
+        matches!(key.as_str(), "value" | "path")
+            .then(|| arg.child_by_field_name("value"))
+            .flatten()
+    });
+    let paths: Vec<String> = value
+        .map(|value| {
+            elements(value)
+                .into_iter()
+                .filter(|element| element.kind() == "string_literal")
+                .map(|element| text(element, source).trim_matches('"').to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    if paths.is_empty() {
+        vec![String::new()]
+    } else {
+        paths
+    }
+}
+
+/// The methods of a `@RequestMapping`: `method = RequestMethod.GET` or
+/// `method = {RequestMethod.GET, RequestMethod.HEAD}`, and any method without one.
+fn request_methods(annotation: Node, source: &str) -> Vec<String> {
+    let methods: Vec<String> = arguments_of(annotation)
+        .into_iter()
+        .filter(|arg| arg.kind() == "element_value_pair")
+        .filter(|arg| {
+            arg.child_by_field_name("key")
+                .is_some_and(|key| text(key, source) == "method")
+        })
+        .filter_map(|arg| arg.child_by_field_name("value"))
+        .flat_map(elements)
+        .map(|element| {
+            let method = text(element, source);
+            method.rsplit('.').next().unwrap_or(&method).to_string()
+        })
+        .collect();
+    if methods.is_empty() {
+        vec!["ALL".to_string()]
+    } else {
+        methods
+    }
+}
+
+fn arguments_of(annotation: Node) -> Vec<Node> {
+    let Some(arguments) = annotation.child_by_field_name("arguments") else {
+        return Vec::new();
+    };
+    let mut cursor = arguments.walk();
+    arguments
+        .named_children(&mut cursor)
+        .filter(|arg| arg.kind() != "comment")
+        .collect()
+}
+
+/// The elements of an array value, or the value itself.
+fn elements(value: Node) -> Vec<Node> {
+    if value.kind() != "element_value_array_initializer" {
+        return vec![value];
+    }
+    let mut cursor = value.walk();
+    value
+        .named_children(&mut cursor)
+        .filter(|element| element.kind() != "comment")
+        .collect()
+}
+
+fn text(node: Node, source: &str) -> String {
+    source[node.start_byte()..node.end_byte()].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::parser::{parser_for_id, LanguageId};
+
+    #[test]
+    fn extracts_controller_routes_under_the_class_mapping() {
+        let source = r#"
+package com.example.users;
+
+import org.springframework.web.bind.annotation.*;
+
+@RestController
+@RequestMapping("/api/users")
+public class UserController {
+    @GetMapping
+    public List<User> list() { return service.list(); }
+
+    @GetMapping("/{id}")
+    public User get(@PathVariable long id) { return service.get(id); }
+
+    @RequestMapping(value = "/{id}", method = {RequestMethod.PUT, RequestMethod.PATCH})
+    public User update(@PathVariable long id, @RequestBody User user) { return user; }
+
+    @PostMapping(path = {"", "/bulk"}, consumes = "application/json")
+    public void create(@RequestBody User user) {}
+
+    @RequestMapping("/legacy")
+    public void legacy() {}
+
+    private void helper() {}
+}
+
+@FeignClient(name = "billing")
+interface BillingClient {
+    @GetMapping("/invoices")
+    List<Invoice> invoices();
+}
+"#;
+        let mut parser = parser_for_id(LanguageId::Java).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let routes: Vec<_> = extract_spring_routes(tree.root_node(), source)
+            .into_iter()
+            .map(|p| {
+                assert_eq!(p.framework, "spring");
+                assert_eq!(p.parent_chain.as_deref(), Some("UserController"));
+                (p.http_method.unwrap(), p.path.unwrap(), p.handler.unwrap())
+            })
+            .collect();
+        let route = |method: &str, path: &str, handler: &str| {
+            (method.to_string(), path.to_string(), handler.to_string())
+        };
+        assert_eq!(
+            routes,
+            vec![
+                route("GET", "/api/users", "UserController.list"),
+                route("GET", "/api/users/{id}", "UserController.get"),
+                route("PUT", "/api/users/{id}", "UserController.update"),
+                route("PATCH", "/api/users/{id}", "UserController.update"),
+                route("POST", "/api/users", "UserController.create"),
+                route("POST", "/api/users/bulk", "UserController.create"),
+                route("ALL", "/api/users/legacy", "UserController.legacy"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! SQL schema extraction
+//!
+//! Migrations and schema dumps are scanned for `CREATE` statements: tables and views
+//! become `table` symbols with a `column` for each column definition, functions and
+//! procedures become `function`s. Names are unquoted and stripped of their schema
+//! (`"public"."users"` is `users`), since that is how code refers to them. The lexer
+//! skips comments and string, quoted-identifier and dollar-quoted literals so that
+//! nothing inside them is taken for a statement.
+
+use anyhow::Result;
+
+use super::symbol::{ExtractedFile, LineIndex, SymbolKind};
+
+pub fn extract_sql_symbols(source: &str) -> Result<ExtractedFile> {
+    let lines = LineIndex::new(source);
+    let tokens = tokenize(source);
+    let mut symbols = Vec::new();
+
+    for statement in tokens.split(|t| t.kind == TokenKind::Punct && t.text == ";") {
+        let Some((first, last)) = statement.first().zip(statement.last()) else {
+            continue;
+        };
+        let Some(created) = created_object(statement) else {
+            continue;
+        };
+        let kind = match created.keyword.as_str() {
+            "TABLE" | "VIEW" => SymbolKind::Table,
+            _ => SymbolKind::Function,
+        };
+        symbols.push(lines.symbol(source, created.name, kind, first.start, last.end));
+        if created.keyword != "TABLE" {
+            continue;
+        }
+
+        // Column definitions are the top-level items of the parenthesized list that
+        // follows the name (`CREATE TABLE t AS SELECT ...` has none)
+        if !statement
+            .get(created.after)
+            .is_some_and(|t| t.is_punct("("))
+        {
+            continue;
+        }
+        let mut depth = 0usize;
+        let mut item: Vec<&Token> = Vec::new();
+        for token in &statement[created.after + 1..] {
+            let closes_list = depth == 0 && token.is_punct(")");
+            if (depth == 0 && token.is_punct(",")) || closes_list {
+                if let Some(column) = column_name(&item) {
+                    let (start, end) = (item[0].start, item[item.len() - 1].end);
+                    symbols.push(lines.symbol(source, column, SymbolKind::Column, start, end));
+                }
+                item.clear();
+                if closes_list {
+                    break;
+                }
+                continue;
+            }
+            if token.is_punct("(") {
+                depth += 1;
+            } else if token.is_punct(")") {
+                depth -= 1;
+            }
+            item.push(token);
+        }
+    }
+    Ok(ExtractedFile::with_symbols(symbols, Vec::new()))
+}
+
+/// Keywords opening a table constraint rather than a column definition.
+const CONSTRAINT_KEYWORDS: &[&str] = &[
+    "CONSTRAINT",
+    "PRIMARY",
+    "FOREIGN",
+    "UNIQUE",
+    "CHECK",
+    "INDEX",
+    "KEY",
+    "EXCLUDE",
+    "LIKE",
+    "FULLTEXT",
+    "SPATIAL",
+    "PERIOD",
+];
+
+/// Words that may stand between `CREATE` and the kind of object created.
+const CREATE_MODIFIERS: &[&str] = &[
+    "OR",
+    "REPLACE",
+    "TEMP",
+    "TEMPORARY",
+    "UNLOGGED",
+    "GLOBAL",
+    "LOCAL",
+    "MATERIALIZED",
+    "RECURSIVE",
+    "VIRTUAL",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    /// `"name"`, `` `name` `` or `[name]`
+    QuotedName,
+    /// String and dollar-quoted literals
+    Str,
+    Punct,
+}
+
+#[derive(Debug)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl Token<'_> {
+    fn is_punct(&self, text: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == text
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn name(&self) -> Option<&str> {
+        match self.kind {
+            TokenKind::Word => Some(self.text),
+            TokenKind::QuotedName => self.text.get(1..self.text.len() - 1),
+            _ => None,
+        }
+    }
+}
+
+struct Created {
+    /// `TABLE`, `VIEW`, `FUNCTION` or `PROCEDURE`
+    keyword: String,
+    name: String,
+    /// Index of the first token after the name
+    after: usize,
+}
+
+fn created_object(statement: &[Token]) -> Option<Created> {
+    if !statement.first()?.is_keyword("CREATE") {
+        return None;
+    }
+    let mut i = 1;
+    while statement
+        .get(i)
+        .is_some_and(|t| CREATE_MODIFIERS.iter().any(|m| t.is_keyword(m)))
+    {
+        i += 1;
+    }
+    let keyword = statement.get(i)?.text.to_ascii_uppercase();
+    if !matches!(
+        keyword.as_str(),
+        "TABLE" | "VIEW" | "FUNCTION" | "PROCEDURE"
+    ) {
+        return None;
+    }
+    i += 1;
+    if statement.get(i).is_some_and(|t| t.is_keyword("IF")) {
+        // IF NOT EXISTS
+        i += 3;
+    }
+    // Schema-qualified names keep their last part
+    let mut name = statement.get(i)?.name()?;
+    i += 1;
+    while statement.get(i).is_some_and(|t| t.is_punct(".")) {
+        name = statement.get(i + 1)?.name()?;
+        i += 2;
+    }
+    Some(Created {
+        keyword,
+        name: name.to_string(),
+        after: i,
+    })
+}
+
+fn column_name(item: &[&Token]) -> Option<String> {
+    let first = item.first()?;
+    if first.kind == TokenKind::Word
+        && CONSTRAINT_KEYWORDS
+            .iter()
+            .any(|k| first.text.eq_ignore_ascii_case(k))
+    {
+        return None;
+    }
+    // A name followed by its type
+    (item.len() > 1).then(|| first.name().map(str::to_string))?
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let start = i;
+        let kind = match b {
+            _ if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = source[i..].find('\n').map_or(bytes.len(), |at| i + at);
+                continue;
+            }
+            b'#' => {
+                // MySQL line comment
+                i = source[i..].find('\n').map_or(bytes.len(), |at| i + at);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = source[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |at| i + 2 + at + 2);
+                continue;
+            }
+            b'\'' | b'"' | b'`' | b'[' => {
+                let close = if b == b'[' { b']' } else { b };
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == close {
+                        // A doubled quote escapes itself
+                        if bytes.get(i + 1) == Some(&close) && close != b']' {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    if bytes[i] == b'\\' && b == b'\'' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(bytes.len());
+                if b == b'\'' {
+                    TokenKind::Str
+                } else {
+                    TokenKind::QuotedName
+                }
+            }
+            b'$' => match dollar_quote_tag(&source[i..]) {
+                Some(tag) => {
+                    let body = i + tag.len();
+                    i = source[body..]
+                        .find(tag)
+                        .map_or(bytes.len(), |at| body + at + tag.len());
+                    TokenKind::Str
+                }
+                None => {
+                    i += 1;
+                    TokenKind::Punct
+                }
+            },
+            _ if b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80 => {
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric()
+                        || bytes[i] == b'_'
+                        || bytes[i] == b'$'
+                        || bytes[i] >= 0x80)
+                {
+                    i += 1;
+                }
+                TokenKind::Word
+            }
+            _ => {
+                i += 1;
+                TokenKind::Punct
+            }
+        };
+        out.push(Token {
+            kind,
+            text: &source[start..i],
+            start,
+            end: i,
+        });
+    }
+    out
+}
+
+/// The `$tag$` opening a dollar-quoted literal at the start of `text`.
+fn dollar_quote_tag(text: &str) -> Option<&str> {
+    let rest = &text[1..];
+    let len = rest.find('$')?;
+    let tag = &rest[..len];
+    let valid = tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !tag.starts_with(|c: char| c.is_ascii_digit());
+    valid.then(|| &text[..len + 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_tables_columns_views_and_functions() {
+        let source = r#"-- CREATE TABLE commented_out (id int);
+CREATE TABLE IF NOT EXISTS "public"."users" (
+  id BIGSERIAL PRIMARY KEY,
+  "email" TEXT NOT NULL DEFAULT 'a;b',
+  price NUMERIC(10, 2),
+  CONSTRAINT users_email_key UNIQUE (email)
+);
+
+CREATE OR REPLACE VIEW active_users AS SELECT * FROM users WHERE id > 0;
+
+CREATE FUNCTION touch() RETURNS trigger AS $$
+BEGIN
+  NEW.updated_at = now(); -- CREATE TABLE nope (x int);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE INDEX users_email ON users (email);
+"#;
+        let file = extract_sql_symbols(source).unwrap();
+        let names: Vec<(&str, SymbolKind)> = file
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("users", SymbolKind::Table),
+                ("id", SymbolKind::Column),
+                ("email", SymbolKind::Column),
+                ("price", SymbolKind::Column),
+                ("active_users", SymbolKind::Table),
+                ("touch", SymbolKind::Function),
+            ]
+        );
+        let users = &file.symbols[0];
+        assert_eq!((users.lines.start, users.lines.end), (2, 7));
+        let price = &file.symbols[3];
+        assert_eq!(
+            &source[price.bytes.start..price.bytes.end],
+            "price NUMERIC(10, 2)"
+        );
+        assert!(price.bytes.end <= users.bytes.end);
+        let touch = &file.symbols[5];
+        assert_eq!((touch.lines.start, touch.lines.end), (11, 16));
+    }
+}
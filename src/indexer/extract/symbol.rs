@@ -13,6 +13,24 @@ pub enum SymbolKind {
     Trait,
     Impl,
     Module,
+    /// C# properties and indexers
+    Property,
+    /// Markdown heading and the text up to the next heading of the same or a higher level
+    Section,
+    /// Key of a YAML, TOML or JSON config file (top-level keys and their children)
+    ConfigKey,
+    /// SQL table or view
+    Table,
+    /// Column of a SQL table
+    Column,
+    /// Protobuf message
+    Message,
+    /// Protobuf service
+    Service,
+    /// Method of a protobuf service
+    Rpc,
+    /// Operation of an OpenAPI spec, named by its `operationId` when it has one
+    Operation,
 }
 
 /// TODO/FIXME comment kind for technical debt tracking (LANG-03)
@@ -94,7 +112,7 @@ pub enum DecoratorType {
     Unknown,
 }
 
-/// Framework pattern kind for Elysia/Hono/Express
+/// Framework pattern kind for Elysia, Express, Koa, Hono, Fastify and NestJS
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FrameworkPatternKind {
@@ -109,6 +127,10 @@ pub enum FrameworkPatternKind {
     Guard,
     Group,
     Listen,
+    /// A router or sub-application mounted under a path prefix
+    Mount,
+    /// A request sent to a route by an HTTP client
+    HttpCall,
 }
 
 impl std::fmt::Display for FrameworkPatternKind {
@@ -125,6 +147,8 @@ impl std::fmt::Display for FrameworkPatternKind {
             Self::Guard => write!(f, "guard"),
             Self::Group => write!(f, "group"),
             Self::Listen => write!(f, "listen"),
+            Self::Mount => write!(f, "mount"),
+            Self::HttpCall => write!(f, "http_call"),
         }
     }
 }
@@ -207,6 +231,64 @@ pub struct ExtractedFile {
     pub jsdoc_entries: Vec<JSDocEntry>,
     /// Decorators extracted from this file (LANG-02)
     pub decorators: Vec<DecoratorEntry>,
-    /// Framework patterns extracted from this file (Elysia/Hono/Express)
+    /// Framework patterns extracted from this file (web framework routes, OpenAPI operations)
     pub framework_patterns: Vec<ExtractedFrameworkPattern>,
 }
+
+impl ExtractedFile {
+    /// A file with only symbols and imports, as the document and config extractors
+    /// produce.
+    pub fn with_symbols(symbols: Vec<ExtractedSymbol>, imports: Vec<Import>) -> Self {
+        Self {
+            symbols,
+            imports,
+            type_edges: Vec::new(),
+            dataflow_edges: Vec::new(),
+            todos: Vec::new(),
+            jsdoc_entries: Vec::new(),
+            decorators: Vec::new(),
+            framework_patterns: Vec::new(),
+        }
+    }
+}
+
+/// 1-based line numbers of the byte offsets of one source text.
+pub struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(at, _)| at + 1))
+            .collect();
+        Self { starts }
+    }
+
+    pub fn line(&self, byte: usize) -> u32 {
+        self.starts.partition_point(|&start| start <= byte) as u32
+    }
+
+    /// A symbol spanning `start..end` of the source, with its end trimmed of
+    /// trailing whitespace.
+    pub fn symbol(
+        &self,
+        source: &str,
+        name: impl Into<String>,
+        kind: SymbolKind,
+        start: usize,
+        end: usize,
+    ) -> ExtractedSymbol {
+        let end = start + source[start..end].trim_end().len();
+        ExtractedSymbol {
+            name: name.into(),
+            kind,
+            exported: true,
+            bytes: ByteSpan { start, end },
+            lines: LineSpan {
+                start: self.line(start),
+                end: self.line(end.saturating_sub(1).max(start)),
+            },
+        }
+    }
+}
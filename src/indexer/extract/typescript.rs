@@ -2,7 +2,8 @@ use crate::indexer::parser::{parser_for_id, LanguageId};
 use anyhow::{anyhow, Result};
 use tree_sitter::{Node, Parser, TreeCursor};
 
-use super::elysia::extract_elysia_patterns;
+use super::http_clients::extract_js_http_calls;
+use super::routers::extract_web_patterns;
 use super::symbol::{
     ByteSpan, DataFlowEdge, DataFlowType, DecoratorEntry, DecoratorType, ExtractedFile,
     ExtractedSymbol, Import, JSDocEntry, JSDocParam, LineSpan, SymbolKind, TodoEntry, TodoKind,
@@ -129,11 +130,9 @@ fn extract_symbols_with_parser(
             "import_statement" => {
                 extract_imports(node, source, &mut imports);
             }
-            "export_statement" => {
-                // handle export ... from ...
-                if node.child_by_field_name("source").is_some() {
-                    extract_imports(node, source, &mut imports);
-                }
+            // handle export ... from ...
+            "export_statement" if node.child_by_field_name("source").is_some() => {
+                extract_imports(node, source, &mut imports);
             }
             _ => {}
         }
@@ -154,8 +153,10 @@ fn extract_symbols_with_parser(
     let cursor = root.walk();
     let decorators = extract_decorators_for_symbols(&symbols, source, cursor);
 
-    // Extract Elysia framework patterns
-    let framework_patterns = extract_elysia_patterns(root, source);
+    // Extract web framework routes
+    let mut framework_patterns = extract_web_patterns(root, source, &imports);
+    framework_patterns.extend(extract_js_http_calls(root, source, &imports));
+    framework_patterns.sort_by_key(|p| (p.line, p.column));
 
     Ok(ExtractedFile {
         symbols,
@@ -490,20 +491,23 @@ fn extract_imports(node: Node<'_>, source: &str, out: &mut Vec<Import>) {
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
         if child.kind() == "import_clause" {
+            // The clause parts are plain children, not named fields
+            let mut clause_cursor = child.walk();
+            let parts = child.named_children(&mut clause_cursor).collect::<Vec<_>>();
             // default import: import A from "..."
-            if let Some(name_node) = child.child_by_field_name("name") {
+            if let Some(name_node) = parts.iter().find(|n| n.kind() == "identifier") {
                 out.push(Import {
-                    name: text_for_node(name_node, source),
+                    name: text_for_node(*name_node, source),
                     source: source_path.clone(),
                     alias: Some("default".to_string()), // It imports the 'default' export
                 });
             }
             // named imports
-            if let Some(named) = child.child_by_field_name("named_imports") {
-                extract_import_specifiers(named, source, &source_path, out);
+            if let Some(named) = parts.iter().find(|n| n.kind() == "named_imports") {
+                extract_import_specifiers(*named, source, &source_path, out);
             }
             // namespace import
-            if let Some(ns) = child.child_by_field_name("namespace_import") {
+            if let Some(ns) = parts.iter().find(|n| n.kind() == "namespace_import") {
                 // import * as ns from ...
                 // The symbol * is imported as ns
                 // This is tricky. We import "everything".
@@ -1408,6 +1412,27 @@ mod tests {
         &source[sym.bytes.start..sym.bytes.end]
     }
 
+    #[test]
+    fn extracts_default_named_and_namespace_imports() {
+        let source = r#"
+import React from "react";
+import { helper, other as renamed } from "./util";
+import * as path from "path";
+"#;
+
+        let extracted = extract_typescript_symbols(LanguageId::Typescript, source).unwrap();
+        let imports: Vec<_> = extracted
+            .imports
+            .iter()
+            .map(|i| (i.name.as_str(), i.source.as_str(), i.alias.as_deref()))
+            .collect();
+
+        assert!(imports.contains(&("React", "react", Some("default"))));
+        assert!(imports.contains(&("helper", "./util", None)));
+        assert!(imports.contains(&("other", "./util", Some("renamed"))));
+        assert!(imports.contains(&("*", "path", Some("path"))));
+    }
+
     #[test]
     fn extracts_declarations_and_const_initializers() {
         let source = r#"
@@ -5,6 +5,8 @@
 use crate::config::Config;
 use crate::path::{Utf8Path, Utf8PathBuf};
 use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
 
 /// Known manifest filenames for various package ecosystems.
 pub const MANIFEST_FILENAMES: &[&str] = &[
@@ -12,10 +14,39 @@ pub const MANIFEST_FILENAMES: &[&str] = &[
     "Cargo.toml",
     "go.mod",
     "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "settings.gradle",
+    "settings.gradle.kts",
     "pyproject.toml",
     "requirements.txt",
+    "Gemfile",
+    "Directory.Packages.props",
 ];
 
+/// Whether `file_name` is a package manifest. Gemspecs and .NET project files are
+/// named after their package (`<name>.gemspec`, `<name>.csproj`), so they are
+/// matched by extension.
+pub fn is_manifest_filename(file_name: &str) -> bool {
+    MANIFEST_FILENAMES.contains(&file_name)
+        || file_name.ends_with(".gemspec")
+        || file_name.ends_with(".csproj")
+}
+
+/// Whether `dir` is .NET intermediate build output: an `obj` directory next to a
+/// `.csproj`. Elsewhere `obj` is an ordinary directory that may hold sources.
+pub fn is_dotnet_obj_dir(dir: &Path) -> bool {
+    dir.file_name().is_some_and(|name| name == "obj")
+        && dir
+            .parent()
+            .and_then(|parent| fs::read_dir(parent).ok())
+            .is_some_and(|entries| {
+                entries
+                    .flatten()
+                    .any(|e| e.path().extension().is_some_and(|ext| ext == "csproj"))
+            })
+}
+
 /// Vendor directories to skip during manifest discovery.
 ///
 /// These directories contain external dependencies that should not be
@@ -120,7 +151,7 @@ fn walk_dir(config: &Config, dir: &Utf8Path, manifests: &mut BTreeSet<Utf8PathBu
             }
             // Recurse into subdirectory
             walk_dir(config, &utf8_path, manifests)?;
-        } else if MANIFEST_FILENAMES.contains(&file_name) {
+        } else if is_manifest_filename(file_name) {
             // Found a manifest file - check it's not in an excluded path
             if !is_excluded_path(config, &utf8_path) {
                 manifests.insert(utf8_path);
@@ -147,7 +178,7 @@ fn walk_dir(config: &Config, dir: &Utf8Path, manifests: &mut BTreeSet<Utf8PathBu
 /// `true` if the directory should be skipped, `false` otherwise.
 pub fn should_skip_package_dir(config: &Config, path: &Utf8Path, name: &str) -> bool {
     // Check built-in vendor directory list
-    if VENDOR_DIRS.contains(&name) {
+    if VENDOR_DIRS.contains(&name) || is_dotnet_obj_dir(path.as_std_path()) {
         return true;
     }
 
@@ -276,11 +307,14 @@ mod tests {
             rank_test_penalty: 0.1,
             rank_popularity_weight: 0.05,
             rank_popularity_cap: 50,
+            rank_history_weight: 0.05,
+            history_max_commits: 1000,
             index_patterns: vec!["**/*.ts".to_string(), "**/*.rs".to_string()],
             exclude_patterns: vec![],
             watch_mode: true,
             watch_debounce_ms: 250,
             watch_min_index_interval_ms: 5000,
+            watch_backend: crate::config::WatchBackend::Auto,
             max_context_bytes: 200_000,
             index_node_modules: false,
             repo_roots: vec![],
@@ -311,6 +345,9 @@ mod tests {
             metrics_enabled: true,
             metrics_port: 9090,
             package_detection_enabled: true,
+            mcp_transport: crate::config::McpTransport::Stdio,
+            http_bind_addr: "127.0.0.1:8765".to_string(),
+            http_auth_token: None,
         }
     }
 
@@ -322,6 +359,17 @@ mod tests {
         assert!(MANIFEST_FILENAMES.contains(&"pom.xml"));
         assert!(MANIFEST_FILENAMES.contains(&"pyproject.toml"));
         assert!(MANIFEST_FILENAMES.contains(&"requirements.txt"));
+        assert!(MANIFEST_FILENAMES.contains(&"Gemfile"));
+        assert!(MANIFEST_FILENAMES.contains(&"Directory.Packages.props"));
+    }
+
+    #[test]
+    fn test_is_manifest_filename_matches_named_manifests() {
+        assert!(is_manifest_filename("Cargo.toml"));
+        assert!(is_manifest_filename("billing.gemspec"));
+        assert!(!is_manifest_filename("billing.rb"));
+        assert!(is_manifest_filename("Billing.Api.csproj"));
+        assert!(!is_manifest_filename("Billing.sln"));
     }
 
     #[test]
@@ -410,6 +458,22 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_obj_dirs_are_skipped_only_next_to_a_csproj() {
+        let config = test_config();
+        let dir = tempfile::tempdir().unwrap();
+        let dotnet = dir.path().join("Billing");
+        let other = dir.path().join("renderer");
+        std::fs::create_dir_all(dotnet.join("obj")).unwrap();
+        std::fs::create_dir_all(other.join("obj")).unwrap();
+        std::fs::write(dotnet.join("Billing.csproj"), "<Project />").unwrap();
+
+        let dotnet_obj = Utf8PathBuf::from_path_buf(dotnet.join("obj")).unwrap();
+        let other_obj = Utf8PathBuf::from_path_buf(other.join("obj")).unwrap();
+        assert!(should_skip_package_dir(&config, &dotnet_obj, "obj"));
+        assert!(!should_skip_package_dir(&config, &other_obj, "obj"));
+    }
+
     #[test]
     fn test_pattern_matches_path() {
         // Exact match (pattern, path)
@@ -37,6 +37,12 @@ pub enum PackageType {
     Python,
     /// Java/Maven (pom.xml)
     Maven,
+    /// JVM/Gradle (build.gradle(.kts), settings.gradle(.kts))
+    Gradle,
+    /// Ruby (Gemfile, *.gemspec)
+    Ruby,
+    /// .NET (*.csproj, Directory.Packages.props)
+    Dotnet,
     /// Unknown package type
     Unknown,
 }
@@ -49,6 +55,9 @@ impl fmt::Display for PackageType {
             PackageType::Go => write!(f, "go"),
             PackageType::Python => write!(f, "python"),
             PackageType::Maven => write!(f, "maven"),
+            PackageType::Gradle => write!(f, "gradle"),
+            PackageType::Ruby => write!(f, "ruby"),
+            PackageType::Dotnet => write!(f, "dotnet"),
             PackageType::Unknown => write!(f, "unknown"),
         }
     }
@@ -63,6 +72,13 @@ impl PackageType {
             "go.mod" => PackageType::Go,
             "pyproject.toml" | "requirements.txt" => PackageType::Python,
             "pom.xml" => PackageType::Maven,
+            "build.gradle" | "build.gradle.kts" | "settings.gradle" | "settings.gradle.kts" => {
+                PackageType::Gradle
+            }
+            "Gemfile" => PackageType::Ruby,
+            name if name.ends_with(".gemspec") => PackageType::Ruby,
+            "Directory.Packages.props" => PackageType::Dotnet,
+            name if name.ends_with(".csproj") => PackageType::Dotnet,
             _ => PackageType::Unknown,
         }
     }
@@ -75,6 +91,9 @@ impl PackageType {
             PackageType::Go => Some("go.mod"),
             PackageType::Python => Some("pyproject.toml"), // Also requirements.txt
             PackageType::Maven => Some("pom.xml"),
+            PackageType::Gradle => Some("build.gradle"), // Also the .kts and settings scripts
+            PackageType::Ruby => Some("Gemfile"), // Gems also have <name>.gemspec
+            PackageType::Dotnet => Some("Directory.Packages.props"), // Projects are <name>.csproj
             PackageType::Unknown => None,
         }
     }
@@ -97,6 +116,9 @@ pub struct PackageInfo {
     pub package_type: PackageType,
     /// ID of the repository containing this package
     pub repository_id: Option<String>,
+    /// Names of the packages this one depends on, as they appear in the manifest
+    #[serde(default)]
+    pub dependencies: Vec<String>,
 }
 
 impl PackageInfo {
@@ -117,6 +139,7 @@ impl PackageInfo {
             root_path,
             package_type,
             repository_id: None,
+            dependencies: Vec::new(),
         }
     }
 
@@ -149,6 +172,12 @@ impl PackageInfo {
         self
     }
 
+    /// Set the names of the packages this one depends on.
+    pub fn with_dependencies(mut self, dependencies: Vec<String>) -> Self {
+        self.dependencies = dependencies;
+        self
+    }
+
     /// Set the repository ID.
     pub fn with_repository_id(mut self, repository_id: String) -> Self {
         self.repository_id = Some(repository_id);
@@ -276,6 +305,9 @@ pub fn discover_packages(
         }
     }
 
+    // A root Gradle project is registered through its settings script
+    manifest_paths.retain(|path| !parsers::gradle::is_root_build_script(path));
+
     // Parse each manifest to extract package metadata
     for manifest_path in manifest_paths {
         match parse_manifest(&manifest_path) {
@@ -407,6 +439,14 @@ mod tests {
             PackageType::Python
         );
         assert_eq!(PackageType::from_filename("pom.xml"), PackageType::Maven);
+        assert_eq!(
+            PackageType::from_filename("build.gradle.kts"),
+            PackageType::Gradle
+        );
+        assert_eq!(
+            PackageType::from_filename("settings.gradle"),
+            PackageType::Gradle
+        );
         assert_eq!(
             PackageType::from_filename("unknown.txt"),
             PackageType::Unknown
@@ -423,6 +463,14 @@ mod tests {
             Some("pyproject.toml")
         );
         assert_eq!(PackageType::Maven.manifest_filename(), Some("pom.xml"));
+        assert_eq!(
+            PackageType::Gradle.manifest_filename(),
+            Some("build.gradle")
+        );
+        assert_eq!(
+            PackageType::Dotnet.manifest_filename(),
+            Some("Directory.Packages.props")
+        );
         assert_eq!(PackageType::Unknown.manifest_filename(), None);
     }
 
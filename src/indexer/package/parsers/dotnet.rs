@@ -0,0 +1,197 @@
+//! .csproj and Directory.Packages.props parser.
+//!
+//! Every project file is a package. A `Directory.Packages.props` (central package
+//! management) marks the solution root; it is named after the solution file next
+//! to it, if any.
+
+use crate::indexer::package::{PackageInfo, PackageType};
+use crate::path::{Utf8Path, Utf8PathBuf};
+use anyhow::Result;
+use regex::Regex;
+
+/// Parse a `.csproj` file and extract package information.
+///
+/// The name is `<PackageId>`, else `<AssemblyName>`, else the project file name.
+/// The version is `<Version>` or `<VersionPrefix>`, from the project or else from
+/// the nearest `Directory.Build.props` above it (which MSBuild imports).
+///
+/// # Arguments
+///
+/// * `path` - Path to the .csproj file
+///
+/// # Returns
+///
+/// * `Ok(PackageInfo)` - Package information with extracted metadata
+/// * `Err(anyhow::Error)` - If the file cannot be read
+///
+/// # Examples
+///
+/// ```no_run
+/// use code_intelligence_mcp_server::indexer::package::parsers::dotnet::parse_csproj;
+/// use code_intelligence_mcp_server::path::Utf8Path;
+///
+/// let manifest = Utf8Path::new("/path/to/Billing.csproj");
+/// let info = parse_csproj(manifest)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn parse_csproj(path: &Utf8Path) -> Result<PackageInfo> {
+    let content = std::fs::read_to_string(path)?;
+
+    let manifest_path = path.to_string();
+    let root_path = path
+        .parent()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| manifest_path.clone());
+
+    let name = property(&content, "PackageId")
+        .or_else(|| property(&content, "AssemblyName"))
+        .or_else(|| path.file_stem().map(str::to_string));
+
+    let version = project_version(&content).or_else(|| {
+        let props = find_upwards(Utf8Path::new(&root_path), "Directory.Build.props")?;
+        project_version(&std::fs::read_to_string(props).ok()?)
+    });
+
+    let info = PackageInfo::new(manifest_path, root_path, PackageType::Dotnet, name, version);
+
+    Ok(info)
+}
+
+/// Parse a `Directory.Packages.props` file and extract package information.
+///
+/// # Arguments
+///
+/// * `path` - Path to the Directory.Packages.props file
+///
+/// # Returns
+///
+/// * `Ok(PackageInfo)` - Package information; the name is the stem of the `.sln`
+///   in the same directory, and there is no version
+/// * `Err(anyhow::Error)` - If the directory cannot be determined
+pub fn parse_directory_packages_props(path: &Utf8Path) -> Result<PackageInfo> {
+    let manifest_path = path.to_string();
+    let root_path = path
+        .parent()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| manifest_path.clone());
+
+    let name = find_solution(Utf8Path::new(&root_path))
+        .and_then(|sln| sln.file_stem().map(str::to_string));
+
+    let info = PackageInfo::new(manifest_path, root_path, PackageType::Dotnet, name, None);
+
+    Ok(info)
+}
+
+/// The first `.sln` (or `.slnx`) file in `dir`, by name.
+pub fn find_solution(dir: &Utf8Path) -> Option<Utf8PathBuf> {
+    let mut solutions: Vec<Utf8PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.path()).ok())
+        .filter(|p| matches!(p.extension(), Some("sln" | "slnx")) && p.is_file())
+        .collect();
+    solutions.sort();
+    solutions.into_iter().next()
+}
+
+/// The literal value of an MSBuild property (`<Name>value</Name>`). Values that
+/// reference other properties (`$(...)`) are not evaluated and count as missing.
+fn property(content: &str, name: &str) -> Option<String> {
+    let re = Regex::new(&format!(r"<{name}(?:\s[^>]*)?>\s*([^<]*?)\s*</{name}>")).ok()?;
+    let value = re
+        .captures_iter(content)
+        .filter_map(|c| c.get(1))
+        .map(|m| m.as_str())
+        .find(|v| !v.is_empty() && !v.contains("$("))
+        .map(str::to_string);
+    value
+}
+
+fn project_version(content: &str) -> Option<String> {
+    property(content, "Version").or_else(|| property(content, "VersionPrefix"))
+}
+
+/// `file_name` in `dir` or the nearest ancestor directory.
+fn find_upwards(dir: &Utf8Path, file_name: &str) -> Option<Utf8PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, rel: &str, content: &str) -> Utf8PathBuf {
+        let path = Utf8PathBuf::from_path_buf(dir.path().join(rel)).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_csproj_with_package_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let csproj = write(
+            &temp_dir,
+            "src/Billing/Billing.csproj",
+            r#"<Project Sdk="Microsoft.NET.Sdk">
+  <PropertyGroup>
+    <TargetFramework>net8.0</TargetFramework>
+    <PackageId>Acme.Billing</PackageId>
+    <Version>3.1.0</Version>
+  </PropertyGroup>
+  <ItemGroup>
+    <PackageReference Include="Newtonsoft.Json" Version="13.0.3" />
+  </ItemGroup>
+</Project>
+"#,
+        );
+
+        let info = parse_csproj(&csproj).unwrap();
+
+        assert_eq!(info.package_type, PackageType::Dotnet);
+        assert_eq!(info.name, Some("Acme.Billing".to_string()));
+        assert_eq!(info.version, Some("3.1.0".to_string()));
+        assert!(info.root_path.ends_with("Billing"));
+    }
+
+    #[test]
+    fn test_parse_csproj_falls_back_to_file_name_and_build_props() {
+        let temp_dir = TempDir::new().unwrap();
+        write(
+            &temp_dir,
+            "Directory.Build.props",
+            "<Project>\n  <PropertyGroup>\n    <VersionPrefix>2.4.0</VersionPrefix>\n  </PropertyGroup>\n</Project>\n",
+        );
+        let csproj = write(
+            &temp_dir,
+            "src/Api/Api.csproj",
+            "<Project Sdk=\"Microsoft.NET.Sdk.Web\">\n  <PropertyGroup>\n    <AssemblyName>$(RootNamespace)</AssemblyName>\n  </PropertyGroup>\n</Project>\n",
+        );
+
+        let info = parse_csproj(&csproj).unwrap();
+
+        assert_eq!(info.name, Some("Api".to_string()));
+        assert_eq!(info.version, Some("2.4.0".to_string()));
+    }
+
+    #[test]
+    fn test_parse_directory_packages_props_uses_solution_name() {
+        let temp_dir = TempDir::new().unwrap();
+        write(&temp_dir, "Shop.sln", "Microsoft Visual Studio Solution File\n");
+        let props = write(
+            &temp_dir,
+            "Directory.Packages.props",
+            "<Project>\n  <ItemGroup>\n    <PackageVersion Include=\"Serilog\" Version=\"3.1.1\" />\n  </ItemGroup>\n</Project>\n",
+        );
+
+        let info = parse_directory_packages_props(&props).unwrap();
+
+        assert_eq!(info.package_type, PackageType::Dotnet);
+        assert_eq!(info.name, Some("Shop".to_string()));
+        assert_eq!(info.version, None);
+    }
+}
@@ -0,0 +1,275 @@
+//! build.gradle(.kts) and settings.gradle(.kts) parser.
+//!
+//! Every Gradle project with a build script is a package named after the project:
+//! `rootProject.name` for the root (from the settings script), the directory name
+//! for subprojects. The root project is registered through its settings script,
+//! which stands for the whole build; a build script sitting next to one describes
+//! the same project. Inter-project dependencies are the `project(":path")`
+//! references of a build script.
+
+use crate::indexer::package::{PackageInfo, PackageType};
+use crate::path::{Utf8Path, Utf8PathBuf};
+use anyhow::{Context, Result};
+use regex::Regex;
+
+const SETTINGS_SCRIPTS: &[&str] = &["settings.gradle", "settings.gradle.kts"];
+const BUILD_SCRIPTS: &[&str] = &["build.gradle", "build.gradle.kts"];
+
+/// Parse a `build.gradle` or `build.gradle.kts` file and extract package information.
+///
+/// The version is the script's `version = ...`, else the root project's (from its
+/// build script or `gradle.properties`), which `allprojects` blocks usually set.
+///
+/// # Arguments
+///
+/// * `path` - Path to the build script
+///
+/// # Returns
+///
+/// * `Ok(PackageInfo)` - Package information; the dependencies are the names of
+///   the projects referenced with `project(...)`
+/// * `Err(anyhow::Error)` - If the file cannot be read
+///
+/// # Examples
+///
+/// ```no_run
+/// use code_intelligence_mcp_server::indexer::package::parsers::gradle::parse_build_gradle;
+/// use code_intelligence_mcp_server::path::Utf8Path;
+///
+/// let manifest = Utf8Path::new("/path/to/core/build.gradle.kts");
+/// let info = parse_build_gradle(manifest)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn parse_build_gradle(path: &Utf8Path) -> Result<PackageInfo> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+
+    let manifest_path = path.to_string();
+    let root_path = path
+        .parent()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| manifest_path.clone());
+    let dir = Utf8Path::new(&root_path);
+
+    let name = match find_script(dir, SETTINGS_SCRIPTS) {
+        Some(settings) => root_project_name(&settings),
+        None => dir.file_name().map(str::to_string),
+    };
+
+    let version = script_version(&content).or_else(|| {
+        let root = dir
+            .ancestors()
+            .find(|d| find_script(d, SETTINGS_SCRIPTS).is_some())?;
+        build_version(root)
+    });
+
+    let info = PackageInfo::new(manifest_path, root_path, PackageType::Gradle, name, version)
+        .with_dependencies(project_dependencies(&content));
+
+    Ok(info)
+}
+
+/// Parse a `settings.gradle` or `settings.gradle.kts` file and extract package
+/// information for the root project.
+///
+/// # Arguments
+///
+/// * `path` - Path to the settings script
+///
+/// # Returns
+///
+/// * `Ok(PackageInfo)` - Package information; the name is `rootProject.name` (or
+///   the directory name), version and dependencies come from the root build script
+/// * `Err(anyhow::Error)` - If the file cannot be read
+pub fn parse_settings_gradle(path: &Utf8Path) -> Result<PackageInfo> {
+    let manifest_path = path.to_string();
+    let root_path = path
+        .parent()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| manifest_path.clone());
+    let dir = Utf8Path::new(&root_path);
+
+    let name = root_project_name(path);
+    let version = build_version(dir);
+    let dependencies = find_script(dir, BUILD_SCRIPTS)
+        .and_then(|script| std::fs::read_to_string(script).ok())
+        .map(|content| project_dependencies(&content))
+        .unwrap_or_default();
+
+    let info = PackageInfo::new(manifest_path, root_path, PackageType::Gradle, name, version)
+        .with_dependencies(dependencies);
+
+    Ok(info)
+}
+
+/// Whether `path` is the build script of a root project, which is registered
+/// through the settings script next to it instead.
+pub fn is_root_build_script(path: &Utf8Path) -> bool {
+    let is_build_script = path
+        .file_name()
+        .is_some_and(|name| BUILD_SCRIPTS.contains(&name));
+    is_build_script
+        && path
+            .parent()
+            .is_some_and(|dir| find_script(dir, SETTINGS_SCRIPTS).is_some())
+}
+
+fn find_script(dir: &Utf8Path, names: &[&str]) -> Option<Utf8PathBuf> {
+    names
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// `rootProject.name` from a settings script, else the name of its directory.
+fn root_project_name(settings: &Utf8Path) -> Option<String> {
+    let name_re = Regex::new(r#"rootProject\.name\s*=\s*["']([^"']+)["']"#).ok()?;
+    std::fs::read_to_string(settings)
+        .ok()
+        .and_then(|content| {
+            name_re
+                .captures(&content)
+                .and_then(|c| c.get(1))
+                .map(|m| m.as_str().to_string())
+        })
+        .or_else(|| settings.parent()?.file_name().map(str::to_string))
+}
+
+/// The version of the build rooted at `dir`: the root build script's, else the
+/// `version` in `gradle.properties`.
+fn build_version(dir: &Utf8Path) -> Option<String> {
+    let from_script = find_script(dir, BUILD_SCRIPTS)
+        .and_then(|script| std::fs::read_to_string(script).ok())
+        .and_then(|content| script_version(&content));
+    from_script.or_else(|| {
+        let properties = std::fs::read_to_string(dir.join("gradle.properties")).ok()?;
+        let version_re = Regex::new(r"(?m)^\s*version\s*[=:]\s*(\S+)\s*$").ok()?;
+        version_re
+            .captures(&properties)
+            .and_then(|c| c.get(1))
+            .map(|m| m.as_str().to_string())
+    })
+}
+
+/// A literal `version = "..."` (or Groovy's `version '...'`) in a build script.
+fn script_version(content: &str) -> Option<String> {
+    let version_re =
+        Regex::new(r#"(?m)^\s*(?:project\.)?version\s*=?\s*["']([^"'$]+)["']"#).ok()?;
+    version_re
+        .captures(content)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
+/// Names of the projects a build script depends on through a configuration
+/// (`implementation(project(":libs:core"))`, `api project(path: ':model')`). A
+/// project's name is the last segment of its path.
+fn project_dependencies(content: &str) -> Vec<String> {
+    let Ok(project_re) = Regex::new(
+        r#"\b[A-Za-z]\w*[ \t]*\(?[ \t]*project\s*\(\s*(?:path\s*[:=]\s*)?["']([^"']+)["']"#,
+    ) else {
+        return Vec::new();
+    };
+
+    let mut out: Vec<String> = Vec::new();
+    for caps in project_re.captures_iter(content) {
+        let Some(name) = caps[1].rsplit(':').next().filter(|n| !n.is_empty()) else {
+            continue;
+        };
+        if !out.iter().any(|existing| existing == name) {
+            out.push(name.to_string());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, rel: &str, content: &str) -> Utf8PathBuf {
+        let path = Utf8PathBuf::from_path_buf(dir.path().join(rel)).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_settings_gradle_for_root_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings = write(
+            &temp_dir,
+            "settings.gradle",
+            "rootProject.name = 'shop'\ninclude ':core', ':api'\n",
+        );
+        let build = write(
+            &temp_dir,
+            "build.gradle",
+            "allprojects {\n    group = 'com.acme'\n    version = '1.4.0'\n}\n\ndependencies {\n    implementation project(':api')\n}\n\nproject(':core') {\n    apply plugin: 'java'\n}\n",
+        );
+
+        let info = parse_settings_gradle(&settings).unwrap();
+
+        assert_eq!(info.package_type, PackageType::Gradle);
+        assert_eq!(info.name, Some("shop".to_string()));
+        assert_eq!(info.version, Some("1.4.0".to_string()));
+        // `project(':core') { ... }` configures a project; it is not a dependency
+        assert_eq!(info.dependencies, vec!["api".to_string()]);
+        assert!(is_root_build_script(&build));
+        assert!(!is_root_build_script(&settings));
+    }
+
+    #[test]
+    fn test_parse_build_gradle_kts_subproject() {
+        let temp_dir = TempDir::new().unwrap();
+        write(
+            &temp_dir,
+            "settings.gradle.kts",
+            "rootProject.name = \"shop\"\ninclude(\"libs:core\", \"app\")\n",
+        );
+        write(
+            &temp_dir,
+            "gradle.properties",
+            "org.gradle.jvmargs=-Xmx2g\nversion=0.9.1\n",
+        );
+        let app = write(
+            &temp_dir,
+            "app/build.gradle.kts",
+            r#"plugins {
+    id("org.springframework.boot") version "3.2.0"
+}
+
+dependencies {
+    implementation(project(":libs:core"))
+    testImplementation(project(path = ":libs:core", configuration = "testFixtures"))
+    implementation("com.google.guava:guava:33.0.0-jre")
+}
+"#,
+        );
+
+        let info = parse_build_gradle(&app).unwrap();
+
+        assert_eq!(info.package_type, PackageType::Gradle);
+        assert_eq!(info.name, Some("app".to_string()));
+        assert_eq!(info.version, Some("0.9.1".to_string()));
+        assert_eq!(info.dependencies, vec!["core".to_string()]);
+        assert!(!is_root_build_script(&app));
+    }
+
+    #[test]
+    fn test_parse_build_gradle_standalone_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let build = write(
+            &temp_dir,
+            "tool/build.gradle",
+            "plugins { id 'application' }\n\nversion '2.0.0'\n",
+        );
+
+        let info = parse_build_gradle(&build).unwrap();
+
+        assert_eq!(info.name, Some("tool".to_string()));
+        assert_eq!(info.version, Some("2.0.0".to_string()));
+        assert!(info.dependencies.is_empty());
+    }
+}
@@ -0,0 +1,401 @@
+//! pom.xml parser.
+//!
+//! Every POM is a package named by its `groupId:artifactId` coordinates. Modules of
+//! a multi-module reactor inherit `groupId`, `version` and `<properties>` from the
+//! POM named in `<parent>` (found through `relativePath`, `../pom.xml` by default),
+//! so `${revision}`-style versions resolve. Declared dependencies are recorded by
+//! coordinates; the ones naming other modules of the workspace are the
+//! inter-module dependencies.
+
+use crate::indexer::package::{PackageInfo, PackageType};
+use crate::path::Utf8Path;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+
+/// How many `<parent>` POMs are followed when inheriting coordinates.
+const MAX_PARENT_DEPTH: usize = 8;
+
+/// Parse a `pom.xml` file and extract package information.
+///
+/// # Arguments
+///
+/// * `path` - Path to the pom.xml file
+///
+/// # Returns
+///
+/// * `Ok(PackageInfo)` - Package information; the name is `groupId:artifactId`
+///   and the dependencies are the coordinates of the project's `<dependencies>`
+/// * `Err(anyhow::Error)` - If the file cannot be read
+///
+/// # Examples
+///
+/// ```no_run
+/// use code_intelligence_mcp_server::indexer::package::parsers::maven::parse_pom_xml;
+/// use code_intelligence_mcp_server::path::Utf8Path;
+///
+/// let manifest = Utf8Path::new("/path/to/pom.xml");
+/// let info = parse_pom_xml(manifest)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn parse_pom_xml(path: &Utf8Path) -> Result<PackageInfo> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+
+    let manifest_path = path.to_string();
+    let root_path = path
+        .parent()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| manifest_path.clone());
+
+    let pom = Pom::resolve(path, &content, 0);
+
+    let name = pom
+        .artifact_id
+        .as_ref()
+        .map(|artifact| match &pom.group_id {
+            Some(group) => format!("{group}:{artifact}"),
+            None => artifact.clone(),
+        });
+
+    let mut dependencies: Vec<String> = Vec::new();
+    for (group, artifact) in &pom.dependencies {
+        let (Some(group), Some(artifact)) = (pom.interpolate(group), pom.interpolate(artifact))
+        else {
+            continue;
+        };
+        let coordinates = format!("{group}:{artifact}");
+        if Some(&coordinates) != name.as_ref() && !dependencies.contains(&coordinates) {
+            dependencies.push(coordinates);
+        }
+    }
+
+    let info = PackageInfo::new(
+        manifest_path,
+        root_path,
+        PackageType::Maven,
+        name,
+        pom.version,
+    )
+    .with_dependencies(dependencies);
+
+    Ok(info)
+}
+
+/// The parts of a POM the package index needs, with inheritance applied.
+#[derive(Debug, Default)]
+struct Pom {
+    group_id: Option<String>,
+    artifact_id: Option<String>,
+    version: Option<String>,
+    /// `<properties>` of this POM and its parents; the nearest definition wins.
+    properties: HashMap<String, String>,
+    /// `(groupId, artifactId)` of each `<dependency>`, uninterpolated.
+    dependencies: Vec<(String, String)>,
+}
+
+impl Pom {
+    /// Read `content` (the POM at `path`) and fill in whatever it inherits from its
+    /// parents.
+    fn resolve(path: &Utf8Path, content: &str, depth: usize) -> Pom {
+        let content = strip_comments(content);
+        let project = elements(&content)
+            .into_iter()
+            .find(|(name, _)| name == "project")
+            .map(|(_, inner)| elements(inner))
+            .unwrap_or_default();
+        let text = |name: &str| child_text(&project, name);
+
+        let parent = project
+            .iter()
+            .find(|(name, _)| name == "parent")
+            .map(|(_, inner)| elements(inner))
+            .unwrap_or_default();
+        let parent_text = |name: &str| child_text(&parent, name);
+
+        let parent_pom = (!parent.is_empty() && depth < MAX_PARENT_DEPTH)
+            .then(|| {
+                let dir = path.parent()?;
+                let relative = parent_text("relativePath").unwrap_or_else(|| "../pom.xml".into());
+                if relative.is_empty() {
+                    return None;
+                }
+                let mut parent_path = dir.join(&relative);
+                if !relative.ends_with(".xml") {
+                    parent_path = parent_path.join("pom.xml");
+                }
+                let parent_content = std::fs::read_to_string(&parent_path).ok()?;
+                Some(Pom::resolve(&parent_path, &parent_content, depth + 1))
+            })
+            .flatten();
+
+        let mut properties = parent_pom
+            .as_ref()
+            .map(|p| p.properties.clone())
+            .unwrap_or_default();
+        if let Some((_, inner)) = project.iter().find(|(name, _)| name == "properties") {
+            for (name, value) in elements(inner) {
+                properties.insert(name, value.trim().to_string());
+            }
+        }
+
+        let inherited = |own: Option<String>, key: &str, from_parent: Option<&String>| {
+            own.or_else(|| parent_text(key))
+                .or_else(|| from_parent.cloned())
+        };
+        let mut pom = Pom {
+            group_id: inherited(
+                text("groupId"),
+                "groupId",
+                parent_pom.as_ref().and_then(|p| p.group_id.as_ref()),
+            ),
+            artifact_id: text("artifactId"),
+            version: inherited(
+                text("version"),
+                "version",
+                parent_pom.as_ref().and_then(|p| p.version.as_ref()),
+            ),
+            properties,
+            dependencies: Vec::new(),
+        };
+
+        for (builtin, value) in [
+            ("project.parent.groupId", parent_text("groupId")),
+            ("project.parent.version", parent_text("version")),
+        ] {
+            if let Some(value) = value {
+                pom.properties.insert(builtin.to_string(), value);
+            }
+        }
+        pom.group_id = pom.group_id.as_deref().and_then(|g| pom.interpolate(g));
+        pom.artifact_id = pom.artifact_id.as_deref().and_then(|a| pom.interpolate(a));
+        pom.version = pom.version.as_deref().and_then(|v| pom.interpolate(v));
+        for (builtin, value) in [
+            ("project.groupId", pom.group_id.clone()),
+            ("project.artifactId", pom.artifact_id.clone()),
+            ("project.version", pom.version.clone()),
+        ] {
+            if let Some(value) = value {
+                pom.properties.insert(builtin.to_string(), value);
+            }
+        }
+
+        // Only the project's own <dependencies>; <dependencyManagement>, <build> and
+        // <profiles> are other direct children and so are not searched
+        if let Some((_, inner)) = project.iter().find(|(name, _)| name == "dependencies") {
+            for (name, dependency) in elements(inner) {
+                if name != "dependency" {
+                    continue;
+                }
+                let fields = elements(dependency);
+                if let (Some(group), Some(artifact)) = (
+                    child_text(&fields, "groupId"),
+                    child_text(&fields, "artifactId"),
+                ) {
+                    pom.dependencies.push((group, artifact));
+                }
+            }
+        }
+
+        pom
+    }
+
+    /// `value` with `${name}` references replaced from the properties, or `None`
+    /// when one of them is not defined.
+    fn interpolate(&self, value: &str) -> Option<String> {
+        let mut out = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("${") {
+            let end = rest[start..].find('}')? + start;
+            out.push_str(&rest[..start]);
+            out.push_str(self.properties.get(&rest[start + 2..end])?);
+            rest = &rest[end + 1..];
+        }
+        out.push_str(rest);
+        Some(out)
+    }
+}
+
+/// The top-level elements of an XML fragment, as `(name, inner xml)` pairs.
+fn elements(xml: &str) -> Vec<(String, &str)> {
+    let tag_re = Regex::new(r"<(/)?([A-Za-z_][\w.:\-]*)[^>]*?(/)?>").expect("valid regex");
+    let mut out = Vec::new();
+    let mut depth = 0usize;
+    let mut open: Option<(String, usize)> = None;
+
+    for caps in tag_re.captures_iter(xml) {
+        let whole = caps.get(0).expect("match");
+        let name = &caps[2];
+        if caps.get(1).is_some() {
+            depth = depth.saturating_sub(1);
+            if depth == 0 {
+                if let Some((open_name, start)) = open.take() {
+                    out.push((open_name, &xml[start..whole.start()]));
+                }
+            }
+        } else if caps.get(3).is_some() {
+            if depth == 0 {
+                out.push((name.to_string(), ""));
+            }
+        } else {
+            if depth == 0 {
+                open = Some((name.to_string(), whole.end()));
+            }
+            depth += 1;
+        }
+    }
+
+    out
+}
+
+/// The trimmed text of the first child element called `name`.
+fn child_text(children: &[(String, &str)], name: &str) -> Option<String> {
+    children
+        .iter()
+        .find(|(child, _)| child == name)
+        .map(|(_, inner)| inner.trim())
+        .filter(|text| !text.contains('<'))
+        .map(str::to_string)
+}
+
+fn strip_comments(content: &str) -> String {
+    let comment_re = Regex::new(r"(?s)<!--.*?-->").expect("valid regex");
+    comment_re.replace_all(content, "").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::Utf8PathBuf;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, rel: &str, content: &str) -> Utf8PathBuf {
+        let path = Utf8PathBuf::from_path_buf(dir.path().join(rel)).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    const AGGREGATOR: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <modelVersion>4.0.0</modelVersion>
+  <!-- <artifactId>commented-out</artifactId> -->
+  <groupId>com.acme</groupId>
+  <artifactId>shop-parent</artifactId>
+  <version>${revision}</version>
+  <packaging>pom</packaging>
+  <properties>
+    <revision>2.3.0</revision>
+  </properties>
+  <modules>
+    <module>core</module>
+    <module>api</module>
+  </modules>
+  <dependencyManagement>
+    <dependencies>
+      <dependency>
+        <groupId>com.acme</groupId>
+        <artifactId>shop-core</artifactId>
+        <version>${project.version}</version>
+      </dependency>
+    </dependencies>
+  </dependencyManagement>
+</project>
+"#;
+
+    #[test]
+    fn test_parse_pom_xml_aggregator() {
+        let temp_dir = TempDir::new().unwrap();
+        let pom = write(&temp_dir, "pom.xml", AGGREGATOR);
+
+        let info = parse_pom_xml(&pom).unwrap();
+
+        assert_eq!(info.package_type, PackageType::Maven);
+        assert_eq!(info.name, Some("com.acme:shop-parent".to_string()));
+        assert_eq!(info.version, Some("2.3.0".to_string()));
+        // Managed dependencies are not dependencies of the aggregator itself
+        assert!(info.dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_parse_pom_xml_module_inherits_from_parent() {
+        let temp_dir = TempDir::new().unwrap();
+        write(&temp_dir, "pom.xml", AGGREGATOR);
+        let api = write(
+            &temp_dir,
+            "api/pom.xml",
+            r#"<project>
+  <parent>
+    <groupId>com.acme</groupId>
+    <artifactId>shop-parent</artifactId>
+    <version>${revision}</version>
+  </parent>
+  <artifactId>shop-api</artifactId>
+  <dependencies>
+    <dependency>
+      <groupId>${project.groupId}</groupId>
+      <artifactId>shop-core</artifactId>
+    </dependency>
+    <dependency>
+      <groupId>org.slf4j</groupId>
+      <artifactId>slf4j-api</artifactId>
+      <version>2.0.9</version>
+    </dependency>
+  </dependencies>
+  <build>
+    <plugins>
+      <plugin>
+        <artifactId>maven-jar-plugin</artifactId>
+        <dependencies>
+          <dependency>
+            <groupId>com.acme</groupId>
+            <artifactId>build-tools</artifactId>
+          </dependency>
+        </dependencies>
+      </plugin>
+    </plugins>
+  </build>
+</project>
+"#,
+        );
+
+        let info = parse_pom_xml(&api).unwrap();
+
+        assert_eq!(info.name, Some("com.acme:shop-api".to_string()));
+        assert_eq!(info.version, Some("2.3.0".to_string()));
+        assert_eq!(
+            info.dependencies,
+            vec![
+                "com.acme:shop-core".to_string(),
+                "org.slf4j:slf4j-api".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_pom_xml_without_parent_pom_on_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let pom = write(
+            &temp_dir,
+            "service/pom.xml",
+            r#"<project>
+  <parent>
+    <groupId>org.springframework.boot</groupId>
+    <artifactId>spring-boot-starter-parent</artifactId>
+    <version>3.2.0</version>
+    <relativePath/>
+  </parent>
+  <groupId>com.acme</groupId>
+  <artifactId>billing</artifactId>
+  <version>${billing.version}</version>
+</project>
+"#,
+        );
+
+        let info = parse_pom_xml(&pom).unwrap();
+
+        assert_eq!(info.name, Some("com.acme:billing".to_string()));
+        // The property is not defined anywhere we can see
+        assert_eq!(info.version, None);
+    }
+}
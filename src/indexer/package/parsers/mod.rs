@@ -5,11 +5,19 @@
 //! - Rust/Cargo (Cargo.toml)
 //! - Go (go.mod)
 //! - Python (pyproject.toml)
+//! - Maven (pom.xml)
+//! - Gradle (build.gradle(.kts), settings.gradle(.kts))
+//! - Ruby (Gemfile, *.gemspec)
+//! - .NET (*.csproj, Directory.Packages.props)
 
 pub mod cargo;
+pub mod dotnet;
 pub mod go;
+pub mod gradle;
+pub mod maven;
 pub mod npm;
 pub mod python;
+pub mod ruby;
 
 // Re-export types from parent module
 pub use crate::indexer::package::{PackageInfo, PackageType};
@@ -17,9 +25,13 @@ use crate::path::Utf8Path;
 
 // Re-export parser functions for public use
 pub use cargo::parse_cargo_toml;
+pub use dotnet::{parse_csproj, parse_directory_packages_props};
 pub use go::parse_go_mod;
+pub use gradle::{parse_build_gradle, parse_settings_gradle};
+pub use maven::parse_pom_xml;
 pub use npm::parse_package_json;
 pub use python::parse_pyproject_toml;
+pub use ruby::{parse_gemfile, parse_gemspec};
 
 /// Parse a package manifest file and return package information.
 ///
@@ -58,6 +70,13 @@ pub fn parse_manifest(path: &Utf8Path) -> anyhow::Result<PackageInfo> {
         "Cargo.toml" => parse_cargo_toml(path)?,
         "go.mod" => parse_go_mod(path)?,
         "pyproject.toml" => parse_pyproject_toml(path)?,
+        "pom.xml" => parse_pom_xml(path)?,
+        "build.gradle" | "build.gradle.kts" => parse_build_gradle(path)?,
+        "settings.gradle" | "settings.gradle.kts" => parse_settings_gradle(path)?,
+        "Gemfile" => parse_gemfile(path)?,
+        name if name.ends_with(".gemspec") => parse_gemspec(path)?,
+        "Directory.Packages.props" => parse_directory_packages_props(path)?,
+        name if name.ends_with(".csproj") => parse_csproj(path)?,
         _ => {
             // Unknown manifest type - return basic PackageInfo
             tracing::debug!("Unknown manifest type: {}", file_name);
@@ -83,6 +102,27 @@ mod tests {
             PackageType::Python
         );
         assert_eq!(PackageType::from_filename("pom.xml"), PackageType::Maven);
+        assert_eq!(
+            PackageType::from_filename("build.gradle"),
+            PackageType::Gradle
+        );
+        assert_eq!(
+            PackageType::from_filename("settings.gradle.kts"),
+            PackageType::Gradle
+        );
+        assert_eq!(PackageType::from_filename("Gemfile"), PackageType::Ruby);
+        assert_eq!(
+            PackageType::from_filename("billing.gemspec"),
+            PackageType::Ruby
+        );
+        assert_eq!(
+            PackageType::from_filename("Billing.csproj"),
+            PackageType::Dotnet
+        );
+        assert_eq!(
+            PackageType::from_filename("Directory.Packages.props"),
+            PackageType::Dotnet
+        );
         assert_eq!(
             PackageType::from_filename("unknown.txt"),
             PackageType::Unknown
@@ -0,0 +1,235 @@
+//! Gemfile and .gemspec parser.
+//!
+//! A gem is described by its `<name>.gemspec`; an application only has a Gemfile,
+//! which carries no name or version of its own. A Gemfile with a `gemspec`
+//! directive takes its metadata from the gemspec next to it.
+
+use crate::indexer::package::{PackageInfo, PackageType};
+use crate::path::{Utf8Path, Utf8PathBuf};
+use anyhow::Result;
+use regex::Regex;
+
+/// Parse a `.gemspec` file and extract package information.
+///
+/// Reads `spec.name` and `spec.version`. When the version is a constant (the usual
+/// `MyGem::VERSION`), it is read from `lib/<gem path>/version.rb`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the .gemspec file
+///
+/// # Returns
+///
+/// * `Ok(PackageInfo)` - Package information with extracted metadata
+/// * `Err(anyhow::Error)` - If the file cannot be read
+///
+/// # Examples
+///
+/// ```no_run
+/// use code_intelligence_mcp_server::indexer::package::parsers::ruby::parse_gemspec;
+/// use code_intelligence_mcp_server::path::Utf8Path;
+///
+/// let manifest = Utf8Path::new("/path/to/my_gem.gemspec");
+/// let info = parse_gemspec(manifest)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn parse_gemspec(path: &Utf8Path) -> Result<PackageInfo> {
+    let content = std::fs::read_to_string(path)?;
+
+    let manifest_path = path.to_string();
+    let root_path = path
+        .parent()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| manifest_path.clone());
+
+    // spec.name = "my_gem"
+    let name_re = Regex::new(r#"(?m)^\s*\w+\.name\s*=\s*["']([^"']+)["']"#)?;
+    let name = name_re
+        .captures(&content)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string());
+
+    // spec.version = "1.2.3" or spec.version = MyGem::VERSION
+    let version_re = Regex::new(r"(?m)^\s*\w+\.version\s*=\s*(.+?)\s*$")?;
+    let version = version_re
+        .captures(&content)
+        .and_then(|c| c.get(1))
+        .and_then(|m| {
+            let expr = m.as_str();
+            quoted(expr).map(str::to_string).or_else(|| {
+                let root = Utf8Path::new(&root_path);
+                name.as_deref()
+                    .and_then(|name| version_from_version_rb(root, name))
+            })
+        });
+
+    let info = PackageInfo::new(manifest_path, root_path, PackageType::Ruby, name, version);
+
+    Ok(info)
+}
+
+/// Parse a Gemfile and extract package information.
+///
+/// # Arguments
+///
+/// * `path` - Path to the Gemfile
+///
+/// # Returns
+///
+/// * `Ok(PackageInfo)` - Package information; name and version come from the
+///   adjacent gemspec when the Gemfile declares `gemspec`
+/// * `Err(anyhow::Error)` - If the file cannot be read
+pub fn parse_gemfile(path: &Utf8Path) -> Result<PackageInfo> {
+    let content = std::fs::read_to_string(path)?;
+
+    let manifest_path = path.to_string();
+    let root_path = path
+        .parent()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| manifest_path.clone());
+
+    let gemspec_re = Regex::new(r"(?m)^\s*gemspec\b")?;
+    let (name, version) = if gemspec_re.is_match(&content) {
+        match find_gemspec(Utf8Path::new(&root_path)) {
+            Some(gemspec) => {
+                let spec = parse_gemspec(&gemspec)?;
+                (spec.name, spec.version)
+            }
+            None => (None, None),
+        }
+    } else {
+        (None, None)
+    };
+
+    let info = PackageInfo::new(manifest_path, root_path, PackageType::Ruby, name, version);
+
+    Ok(info)
+}
+
+/// The first `.gemspec` file in `dir`, by name.
+pub fn find_gemspec(dir: &Utf8Path) -> Option<Utf8PathBuf> {
+    let mut specs: Vec<Utf8PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.path()).ok())
+        .filter(|p| p.as_str().ends_with(".gemspec") && p.is_file())
+        .collect();
+    specs.sort();
+    specs.into_iter().next()
+}
+
+fn quoted(expr: &str) -> Option<&str> {
+    let expr = expr.trim_end_matches(".freeze");
+    ['"', '\'']
+        .iter()
+        .find_map(|q| expr.strip_prefix(*q)?.strip_suffix(*q))
+}
+
+/// `VERSION = "x.y.z"` from `lib/<name>/version.rb`, trying both the gem name and
+/// its dashed-path form (`foo-bar` -> `lib/foo/bar/version.rb`).
+fn version_from_version_rb(root: &Utf8Path, name: &str) -> Option<String> {
+    let version_re = Regex::new(r#"(?m)^\s*VERSION\s*=\s*["']([^"']+)["']"#).ok()?;
+    [name.to_string(), name.replace('-', "/")]
+        .iter()
+        .map(|dir| root.join("lib").join(dir).join("version.rb"))
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .find_map(|content| {
+            version_re
+                .captures(&content)
+                .and_then(|c| c.get(1))
+                .map(|m| m.as_str().to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, rel: &str, content: &str) -> Utf8PathBuf {
+        let path = Utf8PathBuf::from_path_buf(dir.path().join(rel)).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_gemspec_with_literal_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let gemspec = write(
+            &temp_dir,
+            "billing.gemspec",
+            r#"Gem::Specification.new do |spec|
+  spec.name          = "billing"
+  spec.version       = "0.4.1"
+  spec.add_dependency "money", "~> 6.0"
+end
+"#,
+        );
+
+        let info = parse_gemspec(&gemspec).unwrap();
+
+        assert_eq!(info.package_type, PackageType::Ruby);
+        assert_eq!(info.name, Some("billing".to_string()));
+        assert_eq!(info.version, Some("0.4.1".to_string()));
+        assert!(info.manifest_path.ends_with("billing.gemspec"));
+    }
+
+    #[test]
+    fn test_parse_gemspec_reads_version_constant() {
+        let temp_dir = TempDir::new().unwrap();
+        write(
+            &temp_dir,
+            "lib/acme/client/version.rb",
+            "module Acme\n  module Client\n    VERSION = \"2.0.0\".freeze\n  end\nend\n",
+        );
+        let gemspec = write(
+            &temp_dir,
+            "acme-client.gemspec",
+            "Gem::Specification.new do |s|\n  s.name = 'acme-client'\n  s.version = Acme::Client::VERSION\nend\n",
+        );
+
+        let info = parse_gemspec(&gemspec).unwrap();
+
+        assert_eq!(info.name, Some("acme-client".to_string()));
+        assert_eq!(info.version, Some("2.0.0".to_string()));
+    }
+
+    #[test]
+    fn test_parse_gemfile_defers_to_gemspec() {
+        let temp_dir = TempDir::new().unwrap();
+        write(
+            &temp_dir,
+            "tool.gemspec",
+            "Gem::Specification.new do |s|\n  s.name = \"tool\"\n  s.version = \"1.0.0\"\nend\n",
+        );
+        let gemfile = write(
+            &temp_dir,
+            "Gemfile",
+            "source \"https://rubygems.org\"\n\ngemspec\n",
+        );
+
+        let info = parse_gemfile(&gemfile).unwrap();
+
+        assert_eq!(info.package_type, PackageType::Ruby);
+        assert_eq!(info.name, Some("tool".to_string()));
+        assert_eq!(info.version, Some("1.0.0".to_string()));
+        assert!(info.manifest_path.ends_with("Gemfile"));
+    }
+
+    #[test]
+    fn test_parse_gemfile_for_application() {
+        let temp_dir = TempDir::new().unwrap();
+        let gemfile = write(
+            &temp_dir,
+            "Gemfile",
+            "source \"https://rubygems.org\"\ngem \"rails\", \"~> 7.1\"\n",
+        );
+
+        let info = parse_gemfile(&gemfile).unwrap();
+
+        assert_eq!(info.package_type, PackageType::Ruby);
+        assert_eq!(info.name, None);
+        assert_eq!(info.version, None);
+    }
+}
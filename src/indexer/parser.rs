@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::path::Path;
 use tree_sitter::{Language, Parser};
 
@@ -13,6 +13,21 @@ pub enum LanguageId {
     Javascript,
     C,
     Cpp,
+    Ruby,
+    CSharp,
+    /// Extracted by a hand-written lexer; there is no tree-sitter grammar
+    Kotlin,
+    /// Extracted by a hand-written lexer; there is no tree-sitter grammar
+    Scala,
+    /// Documentation, extracted as sections by heading
+    Markdown,
+    /// Config files, extracted as their keys
+    Yaml,
+    Toml,
+    Json,
+    /// Schemas and migrations, extracted as tables, columns and functions
+    Sql,
+    Protobuf,
 }
 
 pub fn language_id_for_path(path: &Path) -> Option<LanguageId> {
@@ -26,12 +41,23 @@ pub fn language_id_for_path(path: &Path) -> Option<LanguageId> {
         Some("js") | Some("jsx") => Some(LanguageId::Javascript),
         Some("c") | Some("h") => Some(LanguageId::C),
         Some("cpp") | Some("cc") | Some("cxx") | Some("hpp") => Some(LanguageId::Cpp),
+        Some("rb") | Some("rake") => Some(LanguageId::Ruby),
+        Some("cs") => Some(LanguageId::CSharp),
+        Some("kt") | Some("kts") => Some(LanguageId::Kotlin),
+        Some("scala") | Some("sc") => Some(LanguageId::Scala),
+        Some("md") | Some("markdown") => Some(LanguageId::Markdown),
+        Some("yml") | Some("yaml") => Some(LanguageId::Yaml),
+        Some("toml") => Some(LanguageId::Toml),
+        Some("json") => Some(LanguageId::Json),
+        Some("sql") => Some(LanguageId::Sql),
+        Some("proto") => Some(LanguageId::Protobuf),
         _ => None,
     }
 }
 
-pub fn language_for_id(id: LanguageId) -> Language {
-    match id {
+/// The tree-sitter grammar for `id`, or `None` for languages without one.
+pub fn language_for_id(id: LanguageId) -> Option<Language> {
+    let language = match id {
         LanguageId::Typescript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
         LanguageId::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
         LanguageId::Rust => tree_sitter_rust::LANGUAGE.into(),
@@ -41,12 +67,25 @@ pub fn language_for_id(id: LanguageId) -> Language {
         LanguageId::Javascript => tree_sitter_javascript::LANGUAGE.into(),
         LanguageId::C => tree_sitter_c::LANGUAGE.into(),
         LanguageId::Cpp => tree_sitter_cpp::LANGUAGE.into(),
-    }
+        LanguageId::Ruby => tree_sitter_ruby::LANGUAGE.into(),
+        LanguageId::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
+        LanguageId::Kotlin
+        | LanguageId::Scala
+        | LanguageId::Markdown
+        | LanguageId::Yaml
+        | LanguageId::Toml
+        | LanguageId::Json
+        | LanguageId::Sql
+        | LanguageId::Protobuf => return None,
+    };
+    Some(language)
 }
 
 pub fn parser_for_id(id: LanguageId) -> Result<Parser> {
+    let language =
+        language_for_id(id).ok_or_else(|| anyhow!("No tree-sitter grammar for {id:?}"))?;
     let mut parser = Parser::new();
-    parser.set_language(&language_for_id(id))?;
+    parser.set_language(&language)?;
     Ok(parser)
 }
 
@@ -65,6 +104,12 @@ mod tests {
         let _ = parser_for_id(LanguageId::Javascript).unwrap();
         let _ = parser_for_id(LanguageId::C).unwrap();
         let _ = parser_for_id(LanguageId::Cpp).unwrap();
+        let _ = parser_for_id(LanguageId::Ruby).unwrap();
+        let _ = parser_for_id(LanguageId::CSharp).unwrap();
+        assert!(parser_for_id(LanguageId::Kotlin).is_err());
+        assert!(parser_for_id(LanguageId::Scala).is_err());
+        assert!(parser_for_id(LanguageId::Markdown).is_err());
+        assert!(parser_for_id(LanguageId::Protobuf).is_err());
     }
 
     #[test]
@@ -102,6 +147,38 @@ mod tests {
             language_id_for_path(Path::new("x.cpp")),
             Some(LanguageId::Cpp)
         );
-        assert_eq!(language_id_for_path(Path::new("x.rb")), None);
+        assert_eq!(
+            language_id_for_path(Path::new("x.rb")),
+            Some(LanguageId::Ruby)
+        );
+        assert_eq!(
+            language_id_for_path(Path::new("x.cs")),
+            Some(LanguageId::CSharp)
+        );
+        assert_eq!(
+            language_id_for_path(Path::new("build.gradle.kts")),
+            Some(LanguageId::Kotlin)
+        );
+        assert_eq!(
+            language_id_for_path(Path::new("x.scala")),
+            Some(LanguageId::Scala)
+        );
+        assert_eq!(
+            language_id_for_path(Path::new("docs/adr/0001.md")),
+            Some(LanguageId::Markdown)
+        );
+        assert_eq!(
+            language_id_for_path(Path::new("docker-compose.yml")),
+            Some(LanguageId::Yaml)
+        );
+        assert_eq!(
+            language_id_for_path(Path::new("migrations/001_init.sql")),
+            Some(LanguageId::Sql)
+        );
+        assert_eq!(
+            language_id_for_path(Path::new("api/billing.proto")),
+            Some(LanguageId::Protobuf)
+        );
+        assert_eq!(language_id_for_path(Path::new("x.php")), None);
     }
 }
@@ -1,6 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::indexer::extract::csharp::csharp_type_relations;
+use crate::indexer::extract::java::WILDCARD_IMPORT;
+use crate::indexer::extract::jvm::{kotlin_type_relations, scala_type_relations};
+use crate::indexer::extract::ruby::ruby_type_relations;
 use crate::indexer::extract::symbol::{DataFlowEdge, DataFlowType, Import};
+use crate::path::Utf8Path;
 use crate::storage::sqlite::{EdgeEvidenceRow, EdgeRow, SqliteStore, SymbolRow};
 
 use super::parsing::{
@@ -9,6 +14,9 @@ use super::parsing::{
 use super::utils::{build_import_map, resolve_imported_symbol_id, resolve_imported_symbol_id_with_db};
 
 pub fn upsert_name_mapping(name_to_id: &mut HashMap<String, String>, row: &SymbolRow) {
+    if is_resource_language(&row.language) {
+        return;
+    }
     if let Some(existing) = name_to_id.get(&row.name) {
         if row.exported && existing != &row.id {
             name_to_id.insert(row.name.clone(), row.id.clone());
@@ -18,6 +26,113 @@ pub fn upsert_name_mapping(name_to_id: &mut HashMap<String, String>, row: &Symbo
     name_to_id.insert(row.name.clone(), row.id.clone());
 }
 
+/// Languages whose files see the types of their whole namespace or package without
+/// importing them, grouped by which languages can call into each other.
+pub fn package_scope(language: &str) -> Option<&'static str> {
+    match language {
+        "csharp" => Some("dotnet"),
+        "java" | "kotlin" | "scala" => Some("jvm"),
+        _ => None,
+    }
+}
+
+/// Languages of a `package_scope`.
+fn package_scope_languages(scope: &str) -> &'static [&'static str] {
+    match scope {
+        "dotnet" => &["csharp"],
+        "jvm" => &["java", "kotlin", "scala"],
+        _ => &[],
+    }
+}
+
+/// The package or namespace `row` is declared in: the qualified name of its
+/// innermost enclosing `module` (a C# namespace), or else the package of its file,
+/// `""` where the file declares none.
+fn declaring_package(
+    row: &SymbolRow,
+    id_to_symbol: &HashMap<String, &SymbolRow>,
+) -> Option<String> {
+    let mut current = row;
+    loop {
+        match current.kind.as_str() {
+            "module" => return Some(current.qualified_name.clone()),
+            // A file's qualified name is its package, or its path when it has none
+            "file" if current.qualified_name == current.name => return Some(String::new()),
+            "file" => return Some(current.qualified_name.clone()),
+            _ => current = id_to_symbol.get(current.parent_id.as_deref()?)?,
+        }
+    }
+}
+
+/// Packages and namespaces whose symbols `row` sees without importing them by
+/// name: its own (and, in C#, the namespaces enclosing it), and those named by
+/// wildcard imports or `using` directives. Its own package comes first.
+fn visible_packages(
+    row: &SymbolRow,
+    scope: &str,
+    id_to_symbol: &HashMap<String, &SymbolRow>,
+    imports: &[Import],
+) -> Vec<String> {
+    let mut packages: Vec<String> = Vec::new();
+    if let Some(own) = declaring_package(row, id_to_symbol) {
+        if scope == "dotnet" {
+            let mut outer = own.as_str();
+            while let Some((parent, _)) = outer.rsplit_once('.') {
+                packages.push(parent.to_string());
+                outer = parent;
+            }
+            if !own.is_empty() {
+                packages.push(String::new());
+            }
+        }
+        packages.insert(0, own);
+    }
+    for imp in imports {
+        let names_package = match scope {
+            "dotnet" => imp.alias.is_none(),
+            _ => imp.name == WILDCARD_IMPORT,
+        };
+        if names_package && !packages.contains(&imp.source) {
+            packages.push(imp.source.clone());
+        }
+    }
+    packages
+}
+
+/// Languages of documentation, config and schema files. Their symbols take no part
+/// in name resolution, in either direction: code reaches them through the names in
+/// its strings and queries, which `resources::link_resource_references` matches.
+/// Languages of documents and data files rather than code.
+pub const RESOURCE_LANGUAGES: &[&str] = &["markdown", "yaml", "toml", "json", "sql", "protobuf"];
+
+pub fn is_resource_language(language: &str) -> bool {
+    RESOURCE_LANGUAGES.contains(&language)
+}
+
+/// `(extends, implements, aliases)` named by a type symbol, using its language's parser.
+/// Ruby mixins (include/extend/prepend) are recorded as `implements` so they show up
+/// in the type graph.
+pub fn type_relations_for(row: &SymbolRow) -> Option<(Vec<String>, Vec<String>, Vec<String>)> {
+    let is_type = |kinds: &[&str]| kinds.contains(&row.kind.as_str());
+    match row.language.as_str() {
+        "ruby" if is_type(&["class", "module"]) => Some(ruby_type_relations(&row.text)),
+        "csharp" if is_type(&["class", "interface", "struct"]) => {
+            Some(csharp_type_relations(&row.text))
+        }
+        "kotlin" if is_type(&["class", "interface", "enum"]) => {
+            Some(kotlin_type_relations(&row.text))
+        }
+        "scala" if is_type(&["class", "interface", "enum"]) => {
+            Some(scala_type_relations(&row.text))
+        }
+        "ruby" | "csharp" | "kotlin" | "scala" => None,
+        _ if is_type(&["class", "interface", "type_alias"]) => {
+            Some(parse_type_relations(&row.text))
+        }
+        _ => None,
+    }
+}
+
 /// Resolution context for edge creation
 struct ResolutionContext<'a> {
     from_file_path: &'a str,
@@ -25,16 +140,27 @@ struct ResolutionContext<'a> {
     row_name: &'a str,
     get_package_fn: Option<&'a PackageLookupFn>,
     id_to_symbol: &'a HashMap<String, &'a SymbolRow>,
+    sqlite: Option<&'a SqliteStore>,
 }
 
 /// Compute resolution for an edge to a target symbol
 fn compute_resolution_for_target(ctx: &ResolutionContext, to_id: &str, was_import: bool) -> String {
-    if let Some(to_symbol) = ctx.id_to_symbol.get(to_id) {
-        let to_package_id = get_package_for_symbol(ctx.get_package_fn, &to_symbol.file_path);
+    // Targets outside the current batch (resolved through imports or by name) are
+    // looked up among the indexed symbols
+    let to_symbol = match ctx.id_to_symbol.get(to_id) {
+        Some(symbol) => Some((symbol.file_path.clone(), symbol.name.clone())),
+        None => ctx
+            .sqlite
+            .and_then(|db| db.get_symbol_by_id(to_id).ok().flatten())
+            .map(|symbol| (symbol.file_path, symbol.name)),
+    };
+
+    if let Some((to_file_path, to_name)) = to_symbol {
+        let to_package_id = get_package_for_symbol(ctx.get_package_fn, &to_file_path);
 
         let resolution = determine_edge_resolution(
             ctx.from_file_path,
-            &to_symbol.file_path,
+            &to_file_path,
             &ctx.from_package_id,
             &to_package_id,
             was_import,
@@ -45,11 +171,11 @@ fn compute_resolution_for_target(ctx: &ResolutionContext, to_id: &str, was_impor
             if let (Some(from_pkg), Some(to_pkg)) = (&ctx.from_package_id, &to_package_id) {
                 tracing::debug!(
                     from = %ctx.row_name,
-                    to = %to_symbol.name,
+                    to = %to_name,
                     from_package = %from_pkg,
                     to_package = %to_pkg,
                     from_file = %ctx.from_file_path,
-                    to_file = %to_symbol.file_path,
+                    to_file = %to_file_path,
                     resolution = %resolution,
                     "Cross-package edge detected"
                 );
@@ -58,7 +184,7 @@ fn compute_resolution_for_target(ctx: &ResolutionContext, to_id: &str, was_impor
 
         resolution
     } else {
-        // Target symbol not in current batch (external import)
+        // Target symbol not indexed (external import)
         if was_import {
             "import".to_string()
         } else {
@@ -73,6 +199,19 @@ fn compute_resolution_for_target(ctx: &ResolutionContext, to_id: &str, was_impor
 /// in the closure for package lookup during edge extraction.
 pub type PackageLookupFn = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
 
+/// Package lookup backed by the `packages` table of the database at `db_path`.
+pub fn db_package_lookup(db_path: &Utf8Path) -> PackageLookupFn {
+    let db_path = db_path.to_path_buf();
+    Box::new(move |file_path: &str| -> Option<String> {
+        if let Ok(sqlite) = SqliteStore::open(&db_path) {
+            if let Ok(Some(pkg)) = sqlite.get_package_for_file(file_path) {
+                return Some(pkg.id);
+            }
+        }
+        None
+    })
+}
+
 /// Helper to create None package lookup
 pub fn no_package_lookup(_: &str) -> Option<String> {
     None
@@ -169,6 +308,9 @@ pub fn extract_edges_for_symbol(
     sqlite: Option<&SqliteStore>,
 ) -> Vec<(EdgeRow, Vec<EdgeEvidenceRow>)> {
     let mut out: Vec<(EdgeRow, Vec<EdgeEvidenceRow>)> = Vec::new();
+    if is_resource_language(&row.language) {
+        return out;
+    }
     let mut used_edges: HashSet<(String, String)> = HashSet::new();
     let confidence_for = |edge_type: &str| match edge_type {
         "call" => 1.0,
@@ -183,6 +325,54 @@ pub fn extract_edges_for_symbol(
     // Map import alias/name to Import struct for fast lookup
     let import_map = build_import_map(imports);
 
+    // A member of an enclosing class, impl or module is what an unqualified name means
+    // there, before anything else of that name in the file (`get` inside
+    // `OrderService` is `OrderService.get`, not `UserService.get`)
+    let member_id_for = |name: &str| -> Option<&String> {
+        let mut container = id_to_symbol.get(row.parent_id.as_deref()?)?;
+        while container.kind != "file" {
+            let member = id_to_symbol
+                .values()
+                .filter(|s| s.parent_id.as_deref() == Some(container.id.as_str()) && s.name == name)
+                .min_by_key(|s| s.start_byte);
+            if let Some(member) = member {
+                return Some(&member.id);
+            }
+            container = id_to_symbol.get(container.parent_id.as_deref()?)?;
+        }
+        None
+    };
+
+    // `name_to_id` may also hold names from previously indexed files; those must not
+    // shadow what this file actually imports
+    let local_id_for = |name: &str| {
+        member_id_for(name).or_else(|| {
+            name_to_id.get(name).filter(|id| {
+                id_to_symbol.contains_key(id.as_str()) || !import_map.contains_key(name)
+            })
+        })
+    };
+
+    // C# and JVM code sees everything in its namespace or package (and behind
+    // wildcard imports or `using` directives, which name namespaces rather than
+    // types), so names that are neither local nor imported are looked up among the
+    // indexed symbols of languages in the same scope declared in one of those,
+    // preferring the caller's own
+    let scope = package_scope(&row.language);
+    let packages = scope
+        .map(|scope| visible_packages(row, scope, id_to_symbol, imports))
+        .unwrap_or_default();
+    let package_id_for = |name: &str| -> Option<String> {
+        let languages = package_scope_languages(scope?);
+        sqlite?
+            .search_symbols_in_packages(name, languages, &packages, 10)
+            .ok()?
+            .into_iter()
+            .filter(|(id, _)| id != &row.id)
+            .min_by_key(|(_, package)| Some(package) != packages.first())
+            .map(|(id, _)| id)
+    };
+
     // Get package for source symbol
     let from_package_id = get_package_for_symbol(get_package_fn, &row.file_path);
 
@@ -193,6 +383,7 @@ pub fn extract_edges_for_symbol(
         row_name: &row.name,
         get_package_fn,
         id_to_symbol,
+        sqlite,
     };
 
     // Helper to resolve import with DB or path-based fallback
@@ -205,7 +396,7 @@ pub fn extract_edges_for_symbol(
     };
 
     for callee in extract_callee_names(&row.text) {
-        let (to_id, was_import) = if let Some(local_id) = name_to_id.get(&callee) {
+        let (to_id, was_import) = if let Some(local_id) = local_id_for(&callee) {
             if local_id == &row.id {
                 continue;
             }
@@ -214,7 +405,7 @@ pub fn extract_edges_for_symbol(
             // Resolve import using DB when available, path-based otherwise
             (resolve_import(&row.file_path, imp), true)
         } else {
-            (None, false)
+            (package_id_for(&callee), false)
         };
 
         let Some(to_id) = to_id else {
@@ -253,11 +444,10 @@ pub fn extract_edges_for_symbol(
     }
 
     // Handle extends/implements
-    if row.kind == "class" || row.kind == "interface" || row.kind == "type_alias" {
-        let (extends, implements, aliases) = parse_type_relations(&row.text);
+    if let Some((extends, implements, aliases)) = type_relations_for(row) {
 
         for name in extends {
-            let (to_id, was_import) = if let Some(local_id) = name_to_id.get(&name) {
+            let (to_id, was_import) = if let Some(local_id) = local_id_for(&name) {
                 if local_id == &row.id {
                     continue;
                 }
@@ -265,7 +455,7 @@ pub fn extract_edges_for_symbol(
             } else if let Some(imp) = import_map.get(name.as_str()) {
                 (resolve_import(&row.file_path, imp), true)
             } else {
-                (None, false)
+                (package_id_for(&name), false)
             };
 
             if let Some(id) = to_id {
@@ -301,7 +491,7 @@ pub fn extract_edges_for_symbol(
         }
 
         for name in implements {
-            let (to_id, was_import) = if let Some(local_id) = name_to_id.get(&name) {
+            let (to_id, was_import) = if let Some(local_id) = local_id_for(&name) {
                 if local_id == &row.id {
                     continue;
                 }
@@ -309,7 +499,7 @@ pub fn extract_edges_for_symbol(
             } else if let Some(imp) = import_map.get(name.as_str()) {
                 (resolve_import(&row.file_path, imp), true)
             } else {
-                (None, false)
+                (package_id_for(&name), false)
             };
 
             if let Some(id) = to_id {
@@ -345,7 +535,7 @@ pub fn extract_edges_for_symbol(
         }
 
         for name in aliases {
-            let (to_id, was_import) = if let Some(local_id) = name_to_id.get(&name) {
+            let (to_id, was_import) = if let Some(local_id) = local_id_for(&name) {
                 if local_id == &row.id {
                     continue;
                 }
@@ -399,7 +589,7 @@ pub fn extract_edges_for_symbol(
             continue;
         }
 
-        let (to_id, was_import) = if let Some(local_id) = name_to_id.get(&ident) {
+        let (to_id, was_import) = if let Some(local_id) = local_id_for(&ident) {
             if local_id == &row.id {
                 continue;
             }
@@ -446,7 +636,7 @@ pub fn extract_edges_for_symbol(
     for (parent_name, type_name) in type_edges {
         if parent_name == &row.name {
             // Resolve type_name
-            let (to_id, was_import) = if let Some(local_id) = name_to_id.get(type_name) {
+            let (to_id, was_import) = if let Some(local_id) = local_id_for(type_name) {
                 if local_id == &row.id {
                     continue;
                 }
@@ -493,7 +683,7 @@ pub fn extract_edges_for_symbol(
     // Handle data flow edges
     for dfe in dataflow_edges {
         // Resolve from_symbol to actual symbol ID
-        let (to_id, was_import) = if let Some(local_id) = name_to_id.get(&dfe.from_symbol) {
+        let (to_id, was_import) = if let Some(local_id) = local_id_for(&dfe.from_symbol) {
             if local_id == &row.id {
                 continue;
             }
@@ -556,6 +746,8 @@ mod tests {
             start_line: 1,
             end_line: 1,
             text: text.to_string(),
+            qualified_name: name.to_string(),
+            parent_id: None,
         }
     }
 
@@ -609,6 +801,44 @@ mod tests {
             .any(|(e, _)| { e.edge_type == "reference" && e.to_symbol_id == expected_b_id }));
     }
 
+    #[test]
+    fn imports_take_precedence_over_names_from_other_files() {
+        let row = symbol(
+            "id_a",
+            "a",
+            "function",
+            "import { b } from './b';\nexport function a(){ b(); }",
+            "src/a.ts",
+        );
+        // An unrelated `b` indexed earlier in the same run
+        let mut name_to_id = HashMap::new();
+        name_to_id.insert("b".to_string(), "id_other_b".to_string());
+        let id_to_symbol: HashMap<String, &SymbolRow> = HashMap::new();
+
+        let imports = vec![Import {
+            name: "b".to_string(),
+            source: "./b".to_string(),
+            alias: None,
+        }];
+
+        let edges = extract_edges_for_symbol(
+            &row,
+            &name_to_id,
+            &id_to_symbol,
+            &imports,
+            &[],
+            &[],
+            None,
+            None,
+        );
+
+        let expected_b_id = stable_symbol_id("src/b.ts", "b", 0);
+        assert!(edges
+            .iter()
+            .any(|(e, _)| e.edge_type == "call" && e.to_symbol_id == expected_b_id));
+        assert!(!edges.iter().any(|(e, _)| e.to_symbol_id == "id_other_b"));
+    }
+
     #[test]
     fn test_cross_package_edge_resolution() {
         // Create symbols in different packages
@@ -790,4 +1020,49 @@ mod tests {
         // Cross-package (local reference, different packages) should be "cross-package"
         assert_eq!(edge_to_b.unwrap().0.resolution, "cross-package");
     }
+
+    #[test]
+    fn unqualified_calls_prefer_members_of_the_enclosing_container() {
+        let child = |id: &str, name: &str, kind: &str, text: &str, parent: &str| SymbolRow {
+            parent_id: Some(parent.to_string()),
+            ..symbol(id, name, kind, text, "src/services.ts")
+        };
+        let file = symbol("file", "src/services.ts", "file", "", "src/services.ts");
+        let users = child("users", "UserService", "class", "class UserService {}", "file");
+        let users_get = child("users_get", "get", "method", "get() {}", "users");
+        let orders = child("orders", "OrderService", "class", "class OrderService {}", "file");
+        let orders_get = child("orders_get", "get", "method", "get() {}", "orders");
+        let orders_list = child(
+            "orders_list",
+            "list",
+            "method",
+            "list() { return get(); }",
+            "orders",
+        );
+
+        let rows = [&file, &users, &users_get, &orders, &orders_get, &orders_list];
+        let id_to_symbol: HashMap<String, &SymbolRow> =
+            rows.iter().map(|r| (r.id.clone(), *r)).collect();
+        // The file-wide mapping knows only one `get`
+        let mut name_to_id = HashMap::new();
+        name_to_id.insert("get".to_string(), "users_get".to_string());
+
+        let edges = extract_edges_for_symbol(
+            &orders_list,
+            &name_to_id,
+            &id_to_symbol,
+            &[],
+            &[],
+            &[],
+            None,
+            None,
+        );
+
+        let calls: Vec<&str> = edges
+            .iter()
+            .filter(|(e, _)| e.edge_type == "call")
+            .map(|(e, _)| e.to_symbol_id.as_str())
+            .collect();
+        assert_eq!(calls, vec!["orders_get"]);
+    }
 }
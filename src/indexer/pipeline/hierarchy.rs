@@ -0,0 +1,227 @@
+//! Container hierarchy and qualified names of a file's symbols.
+//!
+//! A symbol's container is the innermost symbol whose byte range strictly contains
+//! it; top-level symbols belong to the file's `file` row. The qualified name joins
+//! the module path of the file (where the language has one) with the names of the
+//! containers and the symbol itself, separated by `.` whatever the language
+//! (`crate::a::B::c` is stored as `a.B.c`). An `impl` block contributes the type it
+//! implements, so a Rust method reads `Type.method`.
+
+use crate::storage::sqlite::SymbolRow;
+
+/// Index of each row's innermost strictly containing row; `None` for top-level
+/// symbols and for the `file` row, which contains everything.
+pub fn parent_indices(rows: &[SymbolRow]) -> Vec<Option<usize>> {
+    let mut order: Vec<usize> = (0..rows.len())
+        .filter(|&i| rows[i].kind != "file")
+        .collect();
+    order.sort_by_key(|&i| (rows[i].start_byte, std::cmp::Reverse(rows[i].end_byte)));
+
+    let mut parents = vec![None; rows.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    for &i in &order {
+        let row = &rows[i];
+        while let Some(&top) = stack.last() {
+            let outer = &rows[top];
+            let contains = outer.start_byte <= row.start_byte
+                && row.end_byte <= outer.end_byte
+                && (outer.start_byte, outer.end_byte) != (row.start_byte, row.end_byte);
+            if contains {
+                break;
+            }
+            stack.pop();
+        }
+        parents[i] = stack.last().copied();
+        stack.push(i);
+    }
+    parents
+}
+
+/// Set `parent_id` and `qualified_name` of every row of one file. Ids must already
+/// be assigned; `source` is the file's content.
+pub fn assign_hierarchy(rows: &mut [SymbolRow], source: &str) {
+    let parents = parent_indices(rows);
+    let file_row = rows.iter().position(|r| r.kind == "file");
+    let module = rows
+        .first()
+        .and_then(|r| module_path(&r.language, &r.file_path, source));
+
+    // Containers start before what they contain, so they are named first
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by_key(|&i| (rows[i].start_byte, std::cmp::Reverse(rows[i].end_byte)));
+
+    let mut qualified = vec![String::new(); rows.len()];
+    for i in order {
+        if Some(i) == file_row {
+            qualified[i] = module.clone().unwrap_or_else(|| rows[i].name.clone());
+            continue;
+        }
+        let segment = qualified_segment(&rows[i]);
+        qualified[i] = match (parents[i], &module) {
+            (Some(p), _) => format!("{}.{segment}", qualified[p]),
+            (None, Some(module)) => format!("{module}.{segment}"),
+            (None, None) => segment,
+        };
+    }
+
+    let ids: Vec<String> = rows.iter().map(|r| r.id.clone()).collect();
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.parent_id = match parents[i] {
+            Some(p) => Some(ids[p].clone()),
+            None if Some(i) != file_row => file_row.map(|f| ids[f].clone()),
+            None => None,
+        };
+        row.qualified_name = std::mem::take(&mut qualified[i]);
+    }
+}
+
+/// `name` with `::` separators turned into `.`, as qualified names are stored.
+fn normalize_qualified_name(name: &str) -> String {
+    name.trim().replace("::", ".")
+}
+
+/// The name a symbol contributes to the qualified names of its members.
+fn qualified_segment(row: &SymbolRow) -> String {
+    if row.kind != "impl" {
+        return normalize_qualified_name(&row.name);
+    }
+    // `impl Trait for Type` and `impl Type` are named after the type, without
+    // generic arguments
+    let name = row.name.trim_start_matches("impl").trim();
+    let target = name.rsplit_once(" for ").map_or(name, |(_, ty)| ty);
+    let target = target.split('<').next().unwrap_or(target).trim();
+    normalize_qualified_name(target)
+}
+
+/// The module, package or namespace a file's top-level symbols live in, for
+/// languages where that is not itself a symbol: Java, Kotlin and Scala packages and
+/// Go packages from their declaration, Python and Rust modules from the file path.
+/// C# namespaces and Rust inline modules are symbols and show up as containers.
+pub fn module_path(language: &str, file_path: &str, source: &str) -> Option<String> {
+    match language {
+        "java" | "kotlin" | "scala" | "go" | "protobuf" => package_declaration(source),
+        "python" => {
+            let stem = file_path.strip_suffix(".py")?;
+            let stem = stem.strip_prefix("src/").unwrap_or(stem);
+            let stem = stem.strip_suffix("/__init__").unwrap_or(stem);
+            (!stem.is_empty() && stem != "__init__").then(|| stem.replace('/', "."))
+        }
+        "rust" => {
+            // Relative to the crate's `src` directory; lib.rs, main.rs and mod.rs
+            // name their directory
+            let stem = file_path.strip_suffix(".rs")?;
+            let stem = match stem.rfind("src/") {
+                Some(at) if at == 0 || stem[..at].ends_with('/') => &stem[at + 4..],
+                _ => stem,
+            };
+            let stem = ["lib", "main", "mod"]
+                .iter()
+                .find_map(|file| {
+                    (stem == *file)
+                        .then_some("")
+                        .or_else(|| stem.strip_suffix(&format!("/{file}")))
+                })
+                .unwrap_or(stem);
+            (!stem.is_empty()).then(|| stem.replace('/', "."))
+        }
+        _ => None,
+    }
+}
+
+/// The name in the first `package` declaration of a file.
+fn package_declaration(source: &str) -> Option<String> {
+    source.lines().find_map(|line| {
+        let rest = line.trim_start().strip_prefix("package")?;
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let name: String = rest
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+            .collect();
+        // `package object billing` is a member of the package, not its name
+        (!name.is_empty() && name != "object").then_some(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, name: &str, kind: &str, start: u32, end: u32) -> SymbolRow {
+        SymbolRow {
+            id: id.to_string(),
+            file_path: "src/billing/invoice.rs".to_string(),
+            language: "rust".to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+            exported: false,
+            start_byte: start,
+            end_byte: end,
+            start_line: 1,
+            end_line: 1,
+            text: String::new(),
+            qualified_name: String::new(),
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn assigns_parents_and_qualified_names() {
+        let mut rows = vec![
+            row("f", "src/billing/invoice.rs", "file", 0, 300),
+            row("s", "Invoice", "struct", 0, 40),
+            row("i", "impl fmt::Display for Invoice<T>", "impl", 50, 200),
+            row("m", "fmt", "function", 80, 190),
+            row("t", "tests", "module", 210, 300),
+            row("h", "helper", "function", 230, 290),
+        ];
+        assign_hierarchy(&mut rows, "");
+
+        let get = |id: &str| rows.iter().find(|r| r.id == id).unwrap();
+        assert_eq!(get("f").qualified_name, "billing.invoice");
+        assert_eq!(get("f").parent_id, None);
+        assert_eq!(get("s").qualified_name, "billing.invoice.Invoice");
+        assert_eq!(get("s").parent_id.as_deref(), Some("f"));
+        assert_eq!(get("m").qualified_name, "billing.invoice.Invoice.fmt");
+        assert_eq!(get("m").parent_id.as_deref(), Some("i"));
+        assert_eq!(get("h").qualified_name, "billing.invoice.tests.helper");
+        assert_eq!(get("h").parent_id.as_deref(), Some("t"));
+    }
+
+    #[test]
+    fn module_paths_follow_each_language() {
+        assert_eq!(
+            module_path(
+                "java",
+                "src/main/java/Billing.java",
+                "// x\npackage com.acme.billing;\n"
+            ),
+            Some("com.acme.billing".to_string())
+        );
+        assert_eq!(
+            module_path("scala", "a.scala", "package object billing {\n}\n"),
+            None
+        );
+        assert_eq!(
+            module_path("go", "main.go", "package main\n"),
+            Some("main".to_string())
+        );
+        assert_eq!(
+            module_path("python", "src/shop/cart/__init__.py", ""),
+            Some("shop.cart".to_string())
+        );
+        assert_eq!(module_path("rust", "src/lib.rs", ""), None);
+        assert_eq!(
+            module_path("rust", "crates/core/src/graph/mod.rs", ""),
+            Some("graph".to_string())
+        );
+        assert_eq!(module_path("typescript", "src/a.ts", ""), None);
+        assert_eq!(
+            module_path("protobuf", "api/billing.proto", "syntax = \"proto3\";\npackage acme.billing.v1;\n"),
+            Some("acme.billing.v1".to_string())
+        );
+        assert_eq!(normalize_qualified_name("Foo::bar"), "Foo.bar");
+    }
+}
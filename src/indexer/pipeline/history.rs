@@ -0,0 +1,222 @@
+//! Git history of the indexed files.
+//!
+//! Each time `HEAD` moves, the history behind it is mined again: which files every
+//! commit changed, by whom and when, and which of their current lines it touched.
+//! Those lines are then matched to the symbols holding them now, through whatever
+//! uncommitted edits the working tree has, so churn can be told per symbol as well as
+//! per file. Files re-indexed without `HEAD` moving only have their symbols matched
+//! again, since their symbols and lines are what changed.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::git::history::{mine_history, working_tree_maps};
+use crate::git::open_repository;
+use crate::storage::sqlite::{GitCommitRow, GitFileChangeRow, SqliteStore, SymbolRow};
+
+/// Commits changing more files than this say little about which files belong
+/// together, so they do not count as changing them together.
+pub const MAX_CO_CHANGE_FILES: usize = 50;
+
+/// Mine the history again if `HEAD` moved since it was last mined, and match the
+/// changes to the symbols of every indexed file, or else only to those of
+/// `changed_files`. Returns how many files were matched, or `None` when the base
+/// directory is not in a git repository or mining is turned off.
+pub fn refresh_history(
+    sqlite: &SqliteStore,
+    config: &Config,
+    changed_files: &[String],
+) -> Result<Option<usize>> {
+    if config.history_max_commits == 0 {
+        return Ok(None);
+    }
+    let Ok(repo) = open_repository(config) else {
+        return Ok(None);
+    };
+    let Some(head) = repo.repo.head().ok().and_then(|head| head.target()) else {
+        // No commits yet
+        return Ok(None);
+    };
+    let head = head.to_string();
+
+    let files = if sqlite.get_history_head()?.as_deref() != Some(head.as_str()) {
+        let commits = mine_history(&repo.repo, config.history_max_commits, |path| {
+            repo.indexed_path(config, path)
+        })?;
+        let mut commit_rows = Vec::with_capacity(commits.len());
+        let mut change_rows = Vec::new();
+        for commit in commits {
+            commit_rows.push(GitCommitRow {
+                id: commit.id.clone(),
+                author: commit.author,
+                email: commit.email,
+                committed_at: commit.time,
+                summary: commit.summary,
+                file_count: commit.files.len() as u32,
+            });
+            change_rows.extend(commit.files.into_iter().map(|file| GitFileChangeRow {
+                file_path: file.path,
+                commit_id: commit.id.clone(),
+                lines_added: file.added as u32,
+                lines_deleted: file.deleted as u32,
+                head_lines: format_lines(&file.lines),
+            }));
+        }
+        sqlite.replace_git_history(&head, &commit_rows, &change_rows)?;
+        tracing::debug!(
+            head = %head,
+            commits = commit_rows.len(),
+            changes = change_rows.len(),
+            "Mined git history"
+        );
+        sqlite.list_content_files()?
+    } else {
+        changed_files.to_vec()
+    };
+
+    let repo_paths: HashMap<String, String> = files
+        .iter()
+        .filter_map(|file| Some((file.clone(), repo.repo_path(config, file)?)))
+        .collect();
+    let maps = working_tree_maps(
+        &repo.repo,
+        &repo_paths.values().cloned().collect::<Vec<_>>(),
+    )?;
+    for file in &files {
+        let changes = sqlite.list_file_changes(file)?;
+        let symbols = sqlite.list_symbols_by_file(file)?;
+        let content = match sqlite.get_file_content(file)? {
+            Some(content) => Some(content),
+            None => std::fs::read_to_string(config.base_dir.join(file)).ok(),
+        };
+        let map = repo_paths.get(file).and_then(|path| maps.get(path));
+        let mut symbol_changes = Vec::new();
+        if let Some(content) = content {
+            for change in &changes {
+                let lines = parse_lines(&change.head_lines);
+                let lines = match map {
+                    Some(map) => lines
+                        .iter()
+                        .flat_map(|&(start, end)| map.map_range(start, end))
+                        .collect(),
+                    None => lines,
+                };
+                for id in symbols_on_lines(&symbols, &content, &lines) {
+                    symbol_changes.push((id.to_string(), change.commit_id.clone()));
+                }
+            }
+        }
+        sqlite.replace_symbol_changes(file, &symbol_changes)?;
+    }
+    Ok(Some(files.len()))
+}
+
+/// Line ranges as stored: `start-end` joined with `,`.
+fn format_lines(lines: &[(u32, u32)]) -> String {
+    lines
+        .iter()
+        .map(|(start, end)| format!("{start}-{end}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_lines(lines: &str) -> Vec<(u32, u32)> {
+    lines
+        .split(',')
+        .filter_map(|range| {
+            let (start, end) = range.split_once('-')?;
+            Some((start.parse().ok()?, end.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Ids of the symbols, other than the file itself, holding any of `lines` (1-based,
+/// half-open ranges) of `content`, which their byte spans refer to.
+fn symbols_on_lines<'a>(
+    symbols: &'a [SymbolRow],
+    content: &str,
+    lines: &[(u32, u32)],
+) -> Vec<&'a str> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_start = |line: u32| {
+        line_starts
+            .get(line.saturating_sub(1) as usize)
+            .copied()
+            .unwrap_or(content.len())
+    };
+    let bytes: Vec<(usize, usize)> = lines
+        .iter()
+        .map(|&(start, end)| (line_start(start), line_start(end)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    symbols
+        .iter()
+        .filter(|s| s.kind != "file")
+        .filter(|s| {
+            bytes
+                .iter()
+                .any(|&(start, end)| (s.start_byte as usize) < end && start < s.end_byte as usize)
+        })
+        .map(|s| s.id.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(id: &str, kind: &str, content: &str, text: &str) -> SymbolRow {
+        let start = content.find(text).unwrap();
+        SymbolRow {
+            id: id.to_string(),
+            file_path: "src/orders.ts".to_string(),
+            language: "typescript".to_string(),
+            kind: kind.to_string(),
+            name: id.to_string(),
+            exported: true,
+            start_byte: start as u32,
+            end_byte: (start + text.len()) as u32,
+            start_line: 0,
+            end_line: 0,
+            text: text.to_string(),
+            qualified_name: id.to_string(),
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn changed_lines_go_to_every_symbol_holding_them() {
+        let content = "import { db } from './db';\n\
+                       export class Orders {\n\
+                       \x20 list() {\n\
+                       \x20   return db.all();\n\
+                       \x20 }\n\
+                       }\n\
+                       export const LIMIT = 10;\n";
+        let class_text = &content
+            [content.find("export class").unwrap()..content.find("}\nexport const").unwrap() + 1];
+        let symbols = vec![
+            symbol("file", "file", content, content),
+            symbol("Orders", "class", content, class_text),
+            symbol(
+                "list",
+                "method",
+                content,
+                "list() {\n    return db.all();\n  }",
+            ),
+            symbol("LIMIT", "variable", content, "export const LIMIT = 10;"),
+        ];
+        let on_lines = |lines: &str| symbols_on_lines(&symbols, content, &parse_lines(lines));
+
+        assert_eq!(on_lines("4-5"), vec!["Orders", "list"]);
+        assert_eq!(on_lines("1-2,7-8"), vec!["LIMIT"]);
+        assert_eq!(on_lines("6-7"), vec!["Orders"]);
+        assert!(on_lines("1-2").is_empty());
+        assert!(on_lines("9-12").is_empty());
+        assert_eq!(format_lines(&parse_lines("1-2,7-8")), "1-2,7-8");
+    }
+}
@@ -0,0 +1,273 @@
+//! HTTP calls from one service to another.
+//!
+//! Client code requests a path (`fetch('/api/users/' + id)`) that a route of some
+//! service serves (`router.get('/users/:id', getUser)`, mounted under `/api`). After
+//! each index run the `http_call` patterns the extractors record are matched against
+//! the routes by method and path, and the symbol making each call gets an `http_call`
+//! edge to the route's handler. Code calling an API through a client generated from
+//! its contract names an OpenAPI operation or protobuf rpc instead, and gets an edge to
+//! the handlers implementing it. A full scan rebuilds the edges as a whole; other runs
+//! rebuild the ones found in or pointing into the files they changed, so the edges
+//! follow both the clients and the services as either changes.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::Result;
+
+use crate::indexer::extract::openapi::OPENAPI_FRAMEWORK;
+use crate::storage::sqlite::{FrameworkPatternRow, SqliteStore, SymbolHeaderRow};
+
+use super::resources::{
+    edge_ends, path_segments, route_handler, EdgeSet, IMPLEMENTS_CONTRACT_EDGE, RESOURCE_RESOLUTION,
+};
+
+/// Edge from the code sending a request to the handler of the route serving it.
+pub const HTTP_CALL_EDGE: &str = "http_call";
+
+/// Resolution of `http_call` edges.
+pub const HTTP_RESOLUTION: &str = "http";
+
+/// Confidence of a call whose method and whole path match a route.
+const PATH_CONFIDENCE: f32 = 0.7;
+
+/// Confidence of a call matching the end of a route's path, or the other way round.
+const SUFFIX_CONFIDENCE: f32 = 0.5;
+
+/// Confidence of a call through a client generated from a contract.
+const CONTRACT_CONFIDENCE: f32 = 0.6;
+
+/// Most references to one operation or rpc looked through for its clients.
+const MAX_CONTRACT_CLIENTS: usize = 500;
+
+/// Rebuild the `http_call` edges from client calls to the handlers of the routes
+/// they request. With `changed`, only the edges found in or pointing into those files
+/// are. Returns the ends of the edges removed or added, whose PageRank may have moved.
+pub fn link_http_calls(
+    sqlite: &SqliteStore,
+    changed: Option<&BTreeSet<String>>,
+) -> Result<Vec<String>> {
+    let calls = sqlite.list_framework_patterns_by_kind("http_call")?;
+    let routes: Vec<FrameworkPatternRow> = sqlite
+        .list_framework_patterns_by_kind("route")?
+        .into_iter()
+        .filter(|route| route.framework != OPENAPI_FRAMEWORK)
+        .filter(|route| route.http_method.is_some() && route.path.is_some())
+        .collect();
+    let touches = |file: &str| changed.is_none_or(|changed| changed.contains(file));
+
+    let mut headers = HeaderCache::default();
+    let mut handlers: HashMap<String, Option<(String, String)>> = HashMap::new();
+    let mut edges = EdgeSet::new(HTTP_RESOLUTION);
+    for call in &calls {
+        let matching: Vec<(&FrameworkPatternRow, f32)> = routes
+            .iter()
+            .filter_map(|route| call_confidence(call, route).map(|c| (route, c)))
+            .collect();
+        // Routes matching the whole path rule out those matching only its end
+        let best = matching.iter().map(|(_, c)| *c).fold(0.0, f32::max);
+        let mut targets = Vec::new();
+        for (route, confidence) in matching {
+            if confidence < best {
+                continue;
+            }
+            if !handlers.contains_key(&route.id) {
+                let handler = route_handler_of(sqlite, &mut headers, route)?;
+                handlers.insert(route.id.clone(), handler);
+            }
+            if let Some((handler, file)) = &handlers[&route.id] {
+                let touched = touches(&route.file_path) || touches(file);
+                targets.push((handler.clone(), confidence, touched));
+            }
+        }
+        if !touches(&call.file_path) && !targets.iter().any(|(_, _, touched)| *touched) {
+            continue;
+        }
+        // The innermost symbol around the call
+        let Some(caller) = route_handler(headers.get(sqlite, &call.file_path)?, call)
+            .map(|symbol| symbol.id.clone())
+        else {
+            continue;
+        };
+        for (handler, confidence, _) in targets {
+            if handler != caller {
+                edges.add(
+                    &caller,
+                    &handler,
+                    HTTP_CALL_EDGE,
+                    &call.file_path,
+                    call.line,
+                    confidence,
+                );
+            }
+        }
+    }
+    link_contract_clients(sqlite, &touches, &mut edges)?;
+
+    let edges = edges.into_rows();
+    let files: Option<Vec<String>> = changed.map(|changed| changed.iter().cloned().collect());
+    let removed = sqlite.replace_edges_by_resolution(HTTP_RESOLUTION, files.as_deref(), &edges)?;
+    Ok(edge_ends(&edges, removed))
+}
+
+/// Symbol headers of files, read once each.
+#[derive(Default)]
+struct HeaderCache(HashMap<String, Vec<SymbolHeaderRow>>);
+
+impl HeaderCache {
+    fn get(&mut self, sqlite: &SqliteStore, file: &str) -> Result<&[SymbolHeaderRow]> {
+        if !self.0.contains_key(file) {
+            let headers = sqlite.list_symbol_headers_by_file(file, false)?;
+            self.0.insert(file.to_string(), headers);
+        }
+        Ok(&self.0[file])
+    }
+}
+
+/// The symbol handling `route`, and its file: the one the extractor or the
+/// resolution of imported handlers found, when it is still indexed, or else the one
+/// `route_handler` finds.
+fn route_handler_of(
+    sqlite: &SqliteStore,
+    headers: &mut HeaderCache,
+    route: &FrameworkPatternRow,
+) -> Result<Option<(String, String)>> {
+    if let Some(id) = route.symbol_id.as_deref() {
+        if let Some(symbol) = sqlite.get_symbol_by_id(id)? {
+            return Ok(Some((symbol.id, symbol.file_path)));
+        }
+    }
+    Ok(route_handler(headers.get(sqlite, &route.file_path)?, route)
+        .map(|h| (h.id.clone(), h.file_path.clone())))
+}
+
+/// How confident it is that `call` requests `route`: they need the same method,
+/// unless either takes any, and paths whose segments agree, a parameter on either
+/// side agreeing with any segment. Paths of different lengths are aligned at their
+/// ends, as a call may go through a gateway adding a prefix or a base URL may carry
+/// the router's prefix; that is less certain.
+fn call_confidence(call: &FrameworkPatternRow, route: &FrameworkPatternRow) -> Option<f32> {
+    let (Some(call_method), Some(route_method)) = (&call.http_method, &route.http_method) else {
+        return None;
+    };
+    let any_method = call_method == "ALL" || route_method == "ALL";
+    if !any_method && !call_method.eq_ignore_ascii_case(route_method) {
+        return None;
+    }
+    let call_path = path_segments(call.path.as_deref().unwrap_or_default());
+    let route_path = path_segments(route.path.as_deref().unwrap_or_default());
+    let (shorter, longer) = if call_path.len() <= route_path.len() {
+        (&call_path, &route_path)
+    } else {
+        (&route_path, &call_path)
+    };
+    let aligned = &longer[longer.len() - shorter.len()..];
+    let agree = shorter
+        .iter()
+        .zip(aligned)
+        .all(|(a, b)| a == b || a == "{}" || b == "{}");
+    if !agree || shorter.iter().all(|segment| segment == "{}") {
+        return None;
+    }
+    Some(if call_path.len() == route_path.len() {
+        PATH_CONFIDENCE
+    } else {
+        SUFFIX_CONFIDENCE
+    })
+}
+
+/// Give code referencing an operation or rpc an edge to each handler implementing it,
+/// where the code or the handler is in a file `touches` accepts.
+fn link_contract_clients(
+    sqlite: &SqliteStore,
+    touches: &dyn Fn(&str) -> bool,
+    edges: &mut EdgeSet,
+) -> Result<()> {
+    let mut implementers: HashMap<String, Vec<String>> = HashMap::new();
+    for edge in sqlite.list_edges_by_type(IMPLEMENTS_CONTRACT_EDGE)? {
+        implementers
+            .entry(edge.to_symbol_id)
+            .or_default()
+            .push(edge.from_symbol_id);
+    }
+    let handler_ids: Vec<String> = implementers.values().flatten().cloned().collect();
+    let handler_files: HashMap<String, String> = sqlite
+        .list_symbol_headers_by_ids(&handler_ids)?
+        .into_iter()
+        .map(|header| (header.id, header.file_path))
+        .collect();
+    for (contract, handlers) in &implementers {
+        for reference in sqlite.list_edges_to(contract, MAX_CONTRACT_CLIENTS)? {
+            let is_client = reference.edge_type == "reference"
+                && reference.resolution == RESOURCE_RESOLUTION
+                && !handlers.contains(&reference.from_symbol_id);
+            if !is_client {
+                continue;
+            }
+            let client_file = reference.at_file.as_deref().unwrap_or_default();
+            for handler in handlers {
+                let touched = touches(client_file)
+                    || handler_files.get(handler).is_some_and(|file| touches(file));
+                if !touched {
+                    continue;
+                }
+                edges.add(
+                    &reference.from_symbol_id,
+                    handler,
+                    HTTP_CALL_EDGE,
+                    client_file,
+                    reference.at_line.unwrap_or_default(),
+                    CONTRACT_CONFIDENCE,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(kind: &str, method: &str, path: &str) -> FrameworkPatternRow {
+        FrameworkPatternRow {
+            id: path.to_string(),
+            file_path: "x".to_string(),
+            line: 1,
+            framework: "express".to_string(),
+            kind: kind.to_string(),
+            http_method: Some(method.to_string()),
+            path: Some(path.to_string()),
+            local_path: Some(path.to_string()),
+            name: None,
+            handler: None,
+            symbol_id: None,
+            arguments: None,
+            parent_chain: None,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn calls_match_routes_by_method_and_path_template() {
+        let call = pattern("http_call", "GET", "/api/users/{id}");
+        let confidence =
+            |method: &str, path: &str| call_confidence(&call, &pattern("route", method, path));
+        assert_eq!(
+            confidence("GET", "/api/users/:userId"),
+            Some(PATH_CONFIDENCE)
+        );
+        assert_eq!(
+            confidence("ALL", "/api/users/<int:pk>"),
+            Some(PATH_CONFIDENCE)
+        );
+        assert_eq!(confidence("GET", "/api/users/me"), Some(PATH_CONFIDENCE));
+        assert_eq!(confidence("GET", "/users/{id}"), Some(SUFFIX_CONFIDENCE));
+        assert_eq!(
+            confidence("GET", "/v1/api/users/{id}"),
+            Some(SUFFIX_CONFIDENCE)
+        );
+        assert_eq!(confidence("POST", "/api/users/:id"), None);
+        assert_eq!(confidence("GET", "/api/orders/:id"), None);
+        assert_eq!(confidence("GET", "/:slug"), None);
+    }
+}
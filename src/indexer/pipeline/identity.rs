@@ -0,0 +1,324 @@
+//! Edit-stable symbol identities.
+//!
+//! A symbol's id is derived from its file and its qualified name (the names of the
+//! symbols enclosing it, then its own), not from its position. Edits elsewhere in
+//! the file leave the id alone, and so do the rows keyed by it: query selections,
+//! metrics, docstrings, decorators and edges from other files. The first symbol of a
+//! qualified name keeps the bare name; later ones sharing it are told apart by kind
+//! and parameter list (overloads), then by order of appearance.
+//!
+//! The file part is the file's identity path: its path when it was first indexed. A
+//! file that appears under a new path in the same run in which a similar file
+//! disappears is taken to be a rename, and it keeps the old identity and ids.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::storage::sqlite::SymbolRow;
+
+use super::hierarchy::parent_indices;
+use super::utils::stable_symbol_id;
+
+/// Share of distinct lines two files must have in common to count as a rename.
+pub const RENAME_SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// Above this many deleted x added pairs, only files with the same name are compared.
+const MAX_RENAME_COMPARISONS: usize = 100_000;
+
+/// Set the id of every row of one file. `rows` are the file's symbols, including its
+/// `file` row; `identity_path` is the file's identity path.
+pub fn assign_symbol_ids(identity_path: &str, rows: &mut [SymbolRow]) {
+    let keys = identity_keys(rows);
+    for (row, key) in rows.iter_mut().zip(keys) {
+        row.id = stable_symbol_id(identity_path, &key, 0);
+    }
+}
+
+/// The per-file identity key of each row: its qualified name for the first symbol of
+/// that name, and the name with kind, parameter list and, for exact repeats, an
+/// ordinal appended for later ones.
+fn identity_keys(rows: &[SymbolRow]) -> Vec<String> {
+    let mut order: Vec<usize> = (0..rows.len())
+        .filter(|&i| rows[i].kind != "file")
+        .collect();
+    order.sort_by_key(|&i| (rows[i].start_byte, std::cmp::Reverse(rows[i].end_byte)));
+
+    // Names of the enclosing symbols, which come first in `order`
+    let parents = parent_indices(rows);
+    let mut qualified = vec![String::new(); rows.len()];
+    for &i in &order {
+        qualified[i] = match parents[i] {
+            Some(outer) => format!("{}.{}", qualified[outer], rows[i].name),
+            None => rows[i].name.clone(),
+        };
+    }
+
+    let mut keys = vec![String::new(); rows.len()];
+    for (i, row) in rows.iter().enumerate() {
+        if row.kind == "file" {
+            keys[i] = "FILE_ROOT".to_string();
+        }
+    }
+
+    // The first symbol of a name keeps the bare name, so adding a same-named sibling
+    // later never changes an existing id
+    let mut taken: HashSet<&str> = HashSet::new();
+    let mut ordinals: HashMap<String, usize> = HashMap::new();
+    for &i in &order {
+        let name = qualified[i].as_str();
+        if taken.insert(name) {
+            keys[i] = name.to_string();
+            continue;
+        }
+        let overload = format!("{name}#{}{}", rows[i].kind, parameter_list(&rows[i].text));
+        let n = ordinals.entry(overload.clone()).or_insert(0);
+        keys[i] = if *n == 0 {
+            overload
+        } else {
+            format!("{overload}@{n}")
+        };
+        *n += 1;
+    }
+
+    keys
+}
+
+/// The first parenthesized list of `text` with whitespace collapsed, e.g. `(a: i32, b: i32)`.
+fn parameter_list(text: &str) -> String {
+    let Some(open) = text.find('(') else {
+        return String::new();
+    };
+    let mut depth = 0usize;
+    let mut close = text.len();
+    for (offset, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = open + offset + 1;
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    text[open..close]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Jaccard similarity of the sets of non-blank, trimmed lines of `a` and `b`.
+pub fn content_similarity(a: &str, b: &str) -> f64 {
+    let lines = |s: &'_ str| -> HashSet<String> {
+        s.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let (a, b) = (lines(a), lines(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Pair deleted files with added files they were most likely renamed to.
+///
+/// Both lists hold `(file path, content)`. Only files with the same extension are
+/// paired, each file at most once, best match first; pairs below
+/// [`RENAME_SIMILARITY_THRESHOLD`] are not renames. Returns `(old path, new path)`.
+pub fn detect_renames(
+    deleted: &[(String, String)],
+    added: &[(String, String)],
+) -> Vec<(String, String)> {
+    let extension = |path: &str| path.rsplit_once('.').map(|(_, ext)| ext.to_string());
+    let file_name = |path: &str| path.rsplit('/').next().unwrap_or(path).to_string();
+    let same_name_only = deleted.len().saturating_mul(added.len()) > MAX_RENAME_COMPARISONS;
+
+    let mut candidates: Vec<(f64, usize, usize)> = Vec::new();
+    for (d, (old_path, old_content)) in deleted.iter().enumerate() {
+        for (a, (new_path, new_content)) in added.iter().enumerate() {
+            if extension(old_path) != extension(new_path)
+                || (same_name_only && file_name(old_path) != file_name(new_path))
+            {
+                continue;
+            }
+            let score = content_similarity(old_content, new_content);
+            if score >= RENAME_SIMILARITY_THRESHOLD {
+                candidates.push((score, d, a));
+            }
+        }
+    }
+    candidates.sort_by(|x, y| y.0.total_cmp(&x.0).then(x.1.cmp(&y.1)).then(x.2.cmp(&y.2)));
+
+    let mut used_deleted = HashSet::new();
+    let mut used_added = HashSet::new();
+    let mut out = Vec::new();
+    for (_, d, a) in candidates {
+        if used_deleted.contains(&d) || used_added.contains(&a) {
+            continue;
+        }
+        used_deleted.insert(d);
+        used_added.insert(a);
+        out.push((deleted[d].0.clone(), added[a].0.clone()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(name: &str, kind: &str, start: u32, end: u32, text: &str) -> SymbolRow {
+        SymbolRow {
+            id: String::new(),
+            file_path: "src/shapes.ts".to_string(),
+            language: "typescript".to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+            exported: false,
+            start_byte: start,
+            end_byte: end,
+            start_line: 1,
+            end_line: 1,
+            text: text.to_string(),
+            qualified_name: String::new(),
+            parent_id: None,
+        }
+    }
+
+    fn ids(rows: &[SymbolRow]) -> HashMap<String, String> {
+        let mut rows = rows.to_vec();
+        assign_symbol_ids("src/shapes.ts", &mut rows);
+        rows.into_iter()
+            .map(|r| (format!("{}:{}:{}", r.kind, r.name, r.start_byte), r.id))
+            .collect()
+    }
+
+    #[test]
+    fn ids_survive_code_movement() {
+        let before = vec![
+            row("src/shapes.ts", "file", 0, 200, ""),
+            row("Circle", "class", 0, 100, "class Circle {}"),
+            row("area", "method", 20, 60, "area() {}"),
+            row("helper", "function", 120, 150, "function helper() {}"),
+        ];
+        // A comment added at the top shifts everything down
+        let after = vec![
+            row("src/shapes.ts", "file", 0, 230, ""),
+            row("Circle", "class", 30, 130, "class Circle {}"),
+            row("area", "method", 50, 90, "area() {}"),
+            row("helper", "function", 150, 180, "function helper() {}"),
+        ];
+
+        let before = ids(&before);
+        let after = ids(&after);
+        assert_eq!(before["class:Circle:0"], after["class:Circle:30"]);
+        assert_eq!(before["method:area:20"], after["method:area:50"]);
+        assert_eq!(before["function:helper:120"], after["function:helper:150"]);
+        assert_eq!(
+            before["file:src/shapes.ts:0"],
+            after["file:src/shapes.ts:0"]
+        );
+        // The top-level key is the bare name, as for import resolution of unindexed files
+        assert_eq!(
+            after["function:helper:150"],
+            stable_symbol_id("src/shapes.ts", "helper", 0)
+        );
+    }
+
+    #[test]
+    fn later_same_names_are_told_apart_by_container_kind_and_parameters() {
+        let rows = vec![
+            row("Shape", "interface", 0, 20, "interface Shape {}"),
+            row("Shape", "class", 30, 200, "class Shape {}"),
+            row("scale", "method", 40, 80, "scale(factor: number) {}"),
+            row(
+                "scale",
+                "method",
+                90,
+                140,
+                "scale(x: number,\n      y: number) {}",
+            ),
+            row("scale", "function", 210, 260, "function scale(s: Shape) {}"),
+        ];
+        let keys = identity_keys(&rows);
+
+        assert_eq!(
+            keys,
+            vec![
+                "Shape",
+                "Shape#class",
+                "Shape.scale",
+                "Shape.scale#method(x: number, y: number)",
+                "scale",
+            ]
+        );
+        let unique: HashSet<_> = keys.iter().collect();
+        assert_eq!(unique.len(), keys.len());
+    }
+
+    #[test]
+    fn identical_duplicates_get_ordinals() {
+        let rows = vec![
+            row("init", "function", 0, 10, "init() {}"),
+            row("init", "function", 20, 30, "init() {}"),
+        ];
+        assert_eq!(identity_keys(&rows), vec!["init", "init#function()"]);
+        let rows = [rows, vec![row("init", "function", 40, 50, "init() {}")]].concat();
+        assert_eq!(
+            identity_keys(&rows),
+            vec!["init", "init#function()", "init#function()@1"]
+        );
+    }
+
+    #[test]
+    fn adding_an_overload_keeps_the_existing_id() {
+        let before = vec![
+            row("src/shapes.ts", "file", 0, 200, ""),
+            row("scale", "function", 0, 40, "function scale(s: Shape) {}"),
+        ];
+        let mut after = before.clone();
+        after.push(row(
+            "scale",
+            "function",
+            50,
+            100,
+            "function scale(s: Shape, by: number) {}",
+        ));
+
+        let before = ids(&before);
+        let after = ids(&after);
+        assert_eq!(before["function:scale:0"], after["function:scale:0"]);
+        assert_ne!(after["function:scale:0"], after["function:scale:50"]);
+    }
+
+    #[test]
+    fn renames_are_paired_by_content() {
+        let body = "export function total(items) {\n  return items.length;\n}\n";
+        let deleted = vec![
+            ("src/old.ts".to_string(), body.to_string()),
+            (
+                "src/gone.ts".to_string(),
+                "export const x = 1;\n".to_string(),
+            ),
+        ];
+        let added = vec![
+            ("src/new.ts".to_string(), format!("// moved\n{body}")),
+            ("src/new.py".to_string(), body.to_string()),
+            (
+                "src/other.ts".to_string(),
+                "export const y = 2;\n".to_string(),
+            ),
+        ];
+
+        assert_eq!(
+            detect_renames(&deleted, &added),
+            vec![("src/old.ts".to_string(), "src/new.ts".to_string())]
+        );
+        assert!(content_similarity(body, "unrelated\n") < RENAME_SIMILARITY_THRESHOLD);
+    }
+}
@@ -0,0 +1,361 @@
+//! Dependency-aware incremental re-indexing.
+//!
+//! Re-indexing a file keeps the symbols whose identity survived the edit (see
+//! `identity`), but deletes the rest, which (through `ON DELETE CASCADE`) also drops
+//! every edge other files had into them, and new symbols may now be what other files
+//! meant. Files that import the changed file, or import a name it defines, are
+//! therefore looked up in the `file_dependencies` table and have their edges
+//! re-resolved against the fresh symbols.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::config::Config;
+use crate::indexer::extract::java::WILDCARD_IMPORT;
+use crate::indexer::extract::symbol::Import;
+use crate::indexer::parser::language_id_for_path;
+use crate::path::Utf8Path;
+use crate::storage::sqlite::{SqliteStore, SymbolRow};
+
+use super::edges::{
+    db_package_lookup, extract_edges_for_symbol, package_scope, type_relations_for,
+    upsert_name_mapping,
+};
+use super::http_calls::HTTP_RESOLUTION;
+use super::parsing::{extract_callee_names, extract_symbols_for_language};
+use super::resources::RESOURCE_RESOLUTION;
+use super::utils::{
+    alternative_import_paths, file_fingerprint, file_key_path, language_string, resolve_path,
+};
+
+/// What an index run changed, captured before the old rows are deleted and completed
+/// once the new rows are written.
+#[derive(Debug, Default)]
+pub struct ChangeImpact {
+    /// File keys that are re-indexed or deleted in this run
+    changed_files: BTreeSet<String>,
+    /// Subset of `changed_files` that no longer exist
+    deleted_files: BTreeSet<String>,
+    /// Symbol names defined by changed files, before and after the run
+    names: BTreeSet<String>,
+    /// Symbols whose PageRank may have moved
+    seeds: BTreeSet<String>,
+}
+
+impl ChangeImpact {
+    /// Record the current state of every file in `files` whose fingerprint differs
+    /// from the stored one, plus every file in `deleted`.
+    pub fn capture(
+        sqlite: &SqliteStore,
+        config: &Config,
+        files: &[PathBuf],
+        deleted: &[String],
+    ) -> Result<Self> {
+        let mut impact = Self::default();
+
+        for file in files {
+            let rel = file_key_path(config, file);
+            let Ok(fp) = file_fingerprint(file) else {
+                continue;
+            };
+            let unchanged = sqlite.get_file_fingerprint(&rel)?.is_some_and(|existing| {
+                existing.mtime_ns == fp.mtime_ns && existing.size_bytes == fp.size_bytes
+            });
+            if !unchanged {
+                impact.snapshot_file(sqlite, &rel)?;
+            }
+        }
+
+        for rel in deleted {
+            impact.snapshot_file(sqlite, rel)?;
+            impact.deleted_files.insert(rel.clone());
+        }
+
+        Ok(impact)
+    }
+
+    fn snapshot_file(&mut self, sqlite: &SqliteStore, rel: &str) -> Result<()> {
+        self.changed_files.insert(rel.to_string());
+        for (id, name) in sqlite.list_symbol_ids_and_names_by_file(rel)? {
+            self.names.insert(name);
+            self.seeds.insert(id);
+        }
+        // Losing (or gaining) out-edges shifts rank into every target
+        self.seeds.extend(sqlite.list_edge_targets_from_file(rel)?);
+        Ok(())
+    }
+
+    /// Add the post-index symbols and edge targets of the changed files.
+    pub fn finish(&mut self, sqlite: &SqliteStore) -> Result<()> {
+        for rel in self.changed_files.difference(&self.deleted_files) {
+            for (id, name) in sqlite.list_symbol_ids_and_names_by_file(rel)? {
+                self.names.insert(name);
+                self.seeds.insert(id);
+            }
+            self.seeds.extend(sqlite.list_edge_targets_from_file(rel)?);
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed_files.is_empty()
+    }
+
+    /// Files re-indexed or deleted in this run.
+    pub fn changed_files(&self) -> &BTreeSet<String> {
+        &self.changed_files
+    }
+
+    /// Changed files that still exist.
+    pub fn reindexed_files(&self) -> Vec<String> {
+        self.changed_files
+            .difference(&self.deleted_files)
+            .cloned()
+            .collect()
+    }
+
+    /// Files whose edges must be re-resolved: everything that depends on a changed
+    /// file or imports one of its names. Changed files are included when they depend
+    /// on another changed file, since their edges into it were dropped when it was
+    /// re-indexed after them.
+    pub fn dependent_files(&self, sqlite: &SqliteStore) -> Result<Vec<String>> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let files = self.changed_files.iter().cloned().collect::<Vec<_>>();
+        let names = self.names.iter().cloned().collect::<Vec<_>>();
+        Ok(sqlite
+            .list_dependent_files(&files, &names)?
+            .into_iter()
+            .filter(|f| !self.deleted_files.contains(f))
+            .collect())
+    }
+
+    pub fn add_seeds(&mut self, ids: impl IntoIterator<Item = String>) {
+        self.seeds.extend(ids);
+    }
+
+    pub fn seeds(&self) -> Vec<String> {
+        self.seeds.iter().cloned().collect()
+    }
+}
+
+/// Record which files and names `rel` depends on, based on its imports and the
+/// edges already stored for it.
+///
+/// Files of package-scoped languages (C#, Java, Kotlin, Scala) reach the rest of their
+/// package without imports, so the names their symbols call or derive from count too.
+pub fn record_file_dependencies(sqlite: &SqliteStore, rel: &str, imports: &[Import]) -> Result<()> {
+    let mut files = BTreeSet::new();
+    let mut names = BTreeSet::new();
+    for imp in imports {
+        if imp.name != WILDCARD_IMPORT {
+            names.insert(imp.name.clone());
+        }
+        if let Some(target) = resolve_path(rel, &imp.source) {
+            files.extend(alternative_import_paths(&target));
+            files.insert(target);
+        }
+    }
+    let package_scoped = language_id_for_path(Path::new(rel))
+        .is_some_and(|id| package_scope(language_string(id)).is_some());
+    if package_scoped {
+        for row in sqlite.list_symbols_by_file(rel)? {
+            names.extend(extract_callee_names(&row.text));
+            if let Some((extends, implements, _)) = type_relations_for(&row) {
+                names.extend(extends);
+                names.extend(implements);
+            }
+        }
+    }
+    let files = files.into_iter().collect::<Vec<_>>();
+    let names = names.into_iter().collect::<Vec<_>>();
+    sqlite
+        .replace_file_dependencies(rel, &files, &names)
+        .with_context(|| format!("Failed to record dependencies for {rel}"))
+}
+
+/// Re-resolve the outgoing edges of an already indexed file without touching its
+/// symbols, embeddings or search documents.
+///
+/// Returns the symbols the file's edges pointed at before or point at now, whose
+/// PageRank may have moved.
+pub fn reresolve_file_edges(
+    sqlite: &SqliteStore,
+    config: &Config,
+    db_path: &Utf8Path,
+    rel: &str,
+) -> Result<Vec<String>> {
+    let path = if Path::new(rel).is_absolute() {
+        PathBuf::from(rel)
+    } else {
+        config.base_dir.join(rel).into_std_path_buf()
+    };
+    let language_id = language_id_for_path(&path)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported language for file: {rel}"))?;
+    let source = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let extracted = extract_symbols_for_language(language_id, &source, rel)
+        .with_context(|| format!("Failed to extract symbols from: {rel}"))?;
+
+    let symbol_rows: Vec<SymbolRow> = sqlite.list_symbols_by_file(rel)?;
+    let mut name_to_id: HashMap<String, String> = HashMap::new();
+    for row in &symbol_rows {
+        upsert_name_mapping(&mut name_to_id, row);
+    }
+    let id_to_symbol: HashMap<String, &SymbolRow> =
+        symbol_rows.iter().map(|r| (r.id.clone(), r)).collect();
+
+    // Edges out of the file are extracted again below: one that resolved to another
+    // symbol before (a name fallback taken while the imported file was not indexed
+    // yet) would otherwise stay. Resource and HTTP links come from their own passes.
+    let mut targets =
+        sqlite.delete_edges_from_file(rel, &[RESOURCE_RESOLUTION, HTTP_RESOLUTION])?;
+
+    let package_lookup_fn = db_package_lookup(db_path);
+    for row in &symbol_rows {
+        let edges = extract_edges_for_symbol(
+            row,
+            &name_to_id,
+            &id_to_symbol,
+            &extracted.imports,
+            &extracted.type_edges,
+            &extracted.dataflow_edges,
+            Some(&package_lookup_fn),
+            Some(sqlite),
+        );
+        for (edge, evidence) in edges {
+            let _ = sqlite.upsert_edge(&edge);
+            for ev in evidence {
+                let _ = sqlite.upsert_edge_evidence(&ev);
+            }
+        }
+    }
+
+    record_file_dependencies(sqlite, rel, &extracted.imports)?;
+    targets.extend(sqlite.list_edge_targets_from_file(rel)?);
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn store() -> SqliteStore {
+        let sqlite = SqliteStore::from_connection(Connection::open_in_memory().unwrap());
+        sqlite.init().unwrap();
+        sqlite
+    }
+
+    #[test]
+    fn dependents_are_found_by_file_and_by_name() {
+        let sqlite = store();
+        let imports = vec![
+            Import {
+                name: "helper".to_string(),
+                source: "./util".to_string(),
+                alias: None,
+            },
+            Import {
+                name: "Widget".to_string(),
+                source: "@acme/widgets".to_string(),
+                alias: None,
+            },
+        ];
+        record_file_dependencies(&sqlite, "src/app.ts", &imports).unwrap();
+
+        let by_file = sqlite
+            .list_dependent_files(&["src/util.ts".to_string()], &[])
+            .unwrap();
+        assert_eq!(by_file, vec!["src/app.ts".to_string()]);
+
+        let by_index_file = sqlite
+            .list_dependent_files(&["src/util/index.ts".to_string()], &[])
+            .unwrap();
+        assert_eq!(by_index_file, vec!["src/app.ts".to_string()]);
+
+        let by_name = sqlite
+            .list_dependent_files(&[], &["Widget".to_string()])
+            .unwrap();
+        assert_eq!(by_name, vec!["src/app.ts".to_string()]);
+
+        sqlite.delete_file_dependencies("src/app.ts").unwrap();
+        assert!(sqlite
+            .list_dependent_files(&["src/util.ts".to_string()], &["Widget".to_string()])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn stale_edges_are_dropped_but_linked_ones_kept() {
+        use crate::storage::sqlite::schema::{EdgeEvidenceRow, EdgeRow};
+
+        let sqlite = store();
+        for (id, file_path, name) in [
+            ("run", "src/app.ts", "run"),
+            ("helper", "src/other.ts", "helper"),
+            ("orders", "db/schema.sql", "orders"),
+        ] {
+            sqlite
+                .upsert_symbol(&SymbolRow {
+                    id: id.to_string(),
+                    file_path: file_path.to_string(),
+                    language: "typescript".to_string(),
+                    kind: "function".to_string(),
+                    name: name.to_string(),
+                    exported: true,
+                    start_byte: 0,
+                    end_byte: 10,
+                    start_line: 1,
+                    end_line: 1,
+                    text: String::new(),
+                    qualified_name: name.to_string(),
+                    parent_id: None,
+                })
+                .unwrap();
+        }
+        let edge = |to: &str, edge_type: &str, resolution: &str| EdgeRow {
+            from_symbol_id: "run".to_string(),
+            to_symbol_id: to.to_string(),
+            edge_type: edge_type.to_string(),
+            at_file: Some("src/app.ts".to_string()),
+            at_line: Some(1),
+            confidence: 1.0,
+            evidence_count: 1,
+            resolution: resolution.to_string(),
+        };
+        sqlite
+            .upsert_edge(&edge("helper", "call", "import"))
+            .unwrap();
+        sqlite
+            .upsert_edge(&edge("orders", "reference", RESOURCE_RESOLUTION))
+            .unwrap();
+        sqlite
+            .upsert_edge_evidence(&EdgeEvidenceRow {
+                from_symbol_id: "run".to_string(),
+                to_symbol_id: "helper".to_string(),
+                edge_type: "call".to_string(),
+                at_file: "src/app.ts".to_string(),
+                at_line: 1,
+                count: 1,
+            })
+            .unwrap();
+
+        let removed = sqlite
+            .delete_edges_from_file("src/app.ts", &[RESOURCE_RESOLUTION, HTTP_RESOLUTION])
+            .unwrap();
+        assert_eq!(removed, vec!["helper".to_string()]);
+
+        let remaining = sqlite.list_edges_from("run", 10).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].to_symbol_id, "orders");
+        assert!(sqlite
+            .list_edge_evidence("run", "helper", "call", 10)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,34 +1,33 @@
 pub mod edges;
+pub mod hierarchy;
+pub mod history;
+pub mod http_calls;
+pub mod identity;
+pub mod incremental;
 pub mod parallel;
 pub mod parsing;
+pub mod resources;
+pub mod routes;
 pub mod scan;
 pub mod stats;
 pub mod usage;
 pub mod utils;
+pub mod watch;
 
 use crate::indexer::package;
 
 use crate::{
-    config::Config,
+    config::{Config, WatchBackend},
     embeddings::Embedder,
     graph::pagerank,
-    indexer::{
-        extract::c::extract_c_symbols,
-        extract::cpp::extract_cpp_symbols,
-        extract::go::extract_go_symbols,
-        extract::java::extract_java_symbols,
-        extract::javascript::extract_javascript_symbols,
-        extract::python::extract_python_symbols,
-        extract::rust::extract_rust_symbols,
-        extract::typescript::extract_typescript_symbols_with_path,
-        parser::{language_id_for_path, LanguageId},
-    },
+    indexer::parser::language_id_for_path,
     metrics::MetricsRegistry,
     path::Utf8PathBuf,
     storage::{
         cache::EmbeddingCache,
         sqlite::{SimilarityClusterRow, SqliteStore, SymbolRow},
         tantivy::TantivyIndex,
+        trigram::content_trigrams,
         vector::{LanceVectorTable, VectorRecord},
     },
 };
@@ -44,14 +43,15 @@ use tokio::sync::Mutex;
 use tokio::time::sleep;
 
 use self::edges::{extract_edges_for_symbol, upsert_name_mapping};
+use self::incremental::{record_file_dependencies, reresolve_file_edges, ChangeImpact};
 use self::parallel::index_files_parallel;
-use self::parsing::symbol_kind_to_string;
+use self::parsing::{extract_symbols_for_language, symbol_kind_to_string};
 use self::scan::{scan_files, should_index_file};
 use self::stats::IndexRunStats;
 use self::usage::extract_usage_examples_for_file;
 use self::utils::{
-    cluster_key_from_vector, file_fingerprint, file_key_path, language_string, stable_symbol_id,
-    unix_now_s,
+    assign_extracted_symbol_ids, cluster_key_from_vector, file_fingerprint, file_key_path,
+    handler_symbol_id, is_same_or_descendant, language_string, unix_now_s,
 };
 
 #[derive(Clone)]
@@ -110,6 +110,21 @@ impl IndexPipeline {
         }
     }
 
+    /// Use another pipeline's embedding cache, so symbol texts it has embedded are
+    /// not embedded again.
+    pub fn with_embedding_cache(mut self, cache: Arc<EmbeddingCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn embedding_cache(&self) -> &Arc<EmbeddingCache> {
+        &self.cache
+    }
+
+    pub fn embedder(&self) -> &Arc<Mutex<Box<dyn Embedder + Send>>> {
+        &self.embedder
+    }
+
     pub async fn index_all(&self) -> Result<IndexRunStats> {
         let _timer = self.metrics.index_duration.start_timer();
 
@@ -117,21 +132,13 @@ impl IndexPipeline {
         let started_at_unix_s = unix_now_s();
 
         // Discover and store packages if enabled
-        if self.config.package_detection_enabled {
-            if let Err(e) = self.index_packages_and_repositories() {
-                tracing::warn!(
-                    repo = %self.repo_name(),
-                    error = %e,
-                    "Package detection failed, continuing with indexing"
-                );
-            }
-        }
+        self.refresh_packages();
 
         let mut files = Vec::new();
         for root in &self.config.repo_roots {
             files.extend(scan_files(&self.config, root.as_std_path())?);
         }
-        let stats = self.index_files(files, true).await?;
+        let stats = self.index_files(files, true, &[]).await?;
 
         // Record Prometheus metrics
         self.metrics
@@ -191,6 +198,20 @@ impl IndexPipeline {
             .sum())
     }
 
+    /// Re-run package detection when enabled; failures are logged, not fatal.
+    fn refresh_packages(&self) {
+        if !self.config.package_detection_enabled {
+            return;
+        }
+        if let Err(e) = self.index_packages_and_repositories() {
+            tracing::warn!(
+                repo = %self.repo_name(),
+                error = %e,
+                "Package detection failed, continuing with indexing"
+            );
+        }
+    }
+
     /// Discover packages and repositories and store them in SQLite.
     ///
     /// This function:
@@ -241,6 +262,15 @@ impl IndexPipeline {
             } else {
                 pkg.manifest_path.clone()
             };
+            // Packages are looked up by prefix of symbol file_paths, so store the package
+            // directory relative to base_dir, with a trailing slash ("" for the root)
+            let root_path = match file_key_path(&self.config, &PathBuf::from(&pkg.root_path))
+                .as_str()
+            {
+                "" | "." => String::new(),
+                dir => format!("{}/", dir.trim_end_matches('/')),
+            };
+            let dependencies = pkg.dependencies;
 
             let pkg_row = crate::storage::sqlite::schema::PackageRow {
                 id: pkg.id,
@@ -255,10 +285,12 @@ impl IndexPipeline {
                 }),
                 version: pkg.version,
                 manifest_path,
+                root_path: Some(root_path),
                 package_type: pkg.package_type.to_string(),
                 created_at,
             };
             sqlite.upsert_package(&pkg_row)?;
+            sqlite.replace_package_dependencies(&pkg_row.id, &dependencies)?;
         }
 
         // Log summary
@@ -278,25 +310,38 @@ impl IndexPipeline {
     pub async fn index_paths(&self, paths: &[Utf8PathBuf]) -> Result<IndexRunStats> {
         let started_at = Instant::now();
         let started_at_unix_s = unix_now_s();
+
+        // A touched manifest can add, rename or re-root packages
+        if paths.iter().any(|p| {
+            p.file_name()
+                .is_some_and(package::detector::is_manifest_filename)
+        }) {
+            self.refresh_packages();
+        }
+
         let mut files = Vec::new();
+        let mut removed = Vec::new();
         for p in paths {
             let std_path = p.as_std_path();
             if std_path.is_dir() {
                 files.extend(scan_files(&self.config, std_path)?);
             } else if std_path.is_file() && should_index_file(&self.config, std_path) {
                 files.push(std_path.to_path_buf());
+            } else if !std_path.exists() {
+                // Deleted file, or the old side of a rename/directory move
+                removed.push(file_key_path(&self.config, std_path));
             }
         }
-        let stats = self.index_files(files, false).await?;
+        let stats = self.index_files(files, false, &removed).await?;
         self.persist_index_run_metrics(started_at_unix_s, started_at.elapsed(), &stats)?;
         Ok(stats)
     }
 
-    /// Check if any files in the workspace have changed since last indexing
+    /// Collect files in the workspace that changed since last indexing
     ///
-    /// Returns Ok(true) if changes are detected, Ok(false) if no changes,
-    /// or Err() if checking fails.
-    fn check_for_changes(&self) -> Result<bool> {
+    /// Returns new or modified files plus the paths of indexed files that no longer
+    /// exist; an empty list means nothing changed. Errors if checking fails.
+    fn check_for_changes(&self) -> Result<Vec<Utf8PathBuf>> {
         let sqlite = SqliteStore::open(&self.db_path)?;
         sqlite.init()?;
 
@@ -306,123 +351,262 @@ impl IndexPipeline {
             files.extend(scan_files(&self.config, root.as_std_path())?);
         }
 
-        // Check if any files have changed by comparing fingerprints
+        let mut changed = Vec::new();
+        let mut scanned_rel: HashSet<String> = HashSet::new();
+
+        // Compare fingerprints of every scanned file with the indexed ones
         for file in &files {
             let rel = file_key_path(&self.config, file);
             let fp = file_fingerprint(file)?;
 
-            // Check if file is already indexed and unchanged
-            if let Ok(Some(existing)) = sqlite.get_file_fingerprint(&rel) {
-                // File exists in index - check if it changed
-                if existing.mtime_ns != fp.mtime_ns || existing.size_bytes != fp.size_bytes {
-                    // File changed - need to re-index
-                    return Ok(true);
+            let is_unchanged = matches!(
+                sqlite.get_file_fingerprint(&rel),
+                Ok(Some(existing))
+                    if existing.mtime_ns == fp.mtime_ns && existing.size_bytes == fp.size_bytes
+            );
+            if !is_unchanged {
+                // New or modified file - needs (re-)indexing
+                if let Ok(path) = Utf8PathBuf::from_path_buf(file.clone()) {
+                    changed.push(path);
                 }
-            } else {
-                // File not in index yet - need to index
-                return Ok(true);
             }
+            scanned_rel.insert(rel);
         }
 
         // Check for deleted files
-        let scanned_rel: HashSet<String> = files
-            .iter()
-            .map(|f| file_key_path(&self.config, f))
-            .collect();
-
         let existing = sqlite.list_all_file_fingerprints(1_000_000)?;
         for fp in existing {
             if !scanned_rel.contains(&fp.file_path) {
-                // File was deleted - need to re-index
-                return Ok(true);
+                let path = Utf8PathBuf::from(&fp.file_path);
+                changed.push(if path.is_absolute() {
+                    path
+                } else {
+                    self.config.base_dir.join(path)
+                });
             }
         }
 
-        // No changes detected
-        Ok(false)
+        Ok(changed)
     }
 
+    /// Start watch mode in the background.
+    ///
+    /// Depending on `WATCH_BACKEND`, changes are picked up from filesystem events
+    /// (only touched paths are re-indexed) or by periodically re-scanning every repo
+    /// root. In `auto` mode the polling loop takes over if the event watcher cannot be
+    /// set up or its event stream ends.
     pub fn spawn_watch_loop(&self) -> tokio::task::JoinHandle<()> {
         let pipeline = self.clone();
         tokio::spawn(async move {
-            let interval_ms = pipeline.config.watch_debounce_ms.max(50);
-            let min_index_interval = pipeline.config.watch_min_index_interval_ms;
-            let mut consecutive_failures = 0;
-            let max_backoff_ms = 5000; // Max 5 seconds backoff
-            let mut last_index_time: Option<Instant> = None;
-
-            // Get repository name for logging
-            let repo_name = pipeline
-                .config
-                .base_dir
-                .file_name()
-                .unwrap_or("unknown");
-
-            loop {
-                sleep(Duration::from_millis(interval_ms)).await;
-
-                // Only re-index if files have actually changed
-                match pipeline.check_for_changes() {
-                    Ok(true) => {
-                        // Check rate limiting: ensure minimum time between index runs
-                        if let Some(last_time) = last_index_time {
-                            let elapsed = last_time.elapsed().as_millis() as u64;
-                            if elapsed < min_index_interval {
-                                tracing::debug!(
-                                    repo = %repo_name,
-                                    elapsed_ms = elapsed,
-                                    min_interval_ms = min_index_interval,
-                                    "Rate limiting: skipping index, too soon since last run"
-                                );
-                                continue;
-                            }
-                        }
-
-                        // Changes detected - proceed with indexing
-                        tracing::info!(
-                            repo = %repo_name,
-                            "Changes detected, starting index run"
-                        );
-
-                        match pipeline.index_all().await {
-                            Ok(_) => {
-                                last_index_time = Some(Instant::now());
-                                consecutive_failures = 0; // Reset on success
-                            }
-                            Err(err) => {
-                                consecutive_failures += 1;
-                                let backoff_ms =
-                                    (interval_ms * (1 << consecutive_failures.min(8))).min(max_backoff_ms);
-                                tracing::warn!(
-                                    repo = %repo_name,
-                                    error = %err,
-                                    consecutive_failures = consecutive_failures,
-                                    backoff_ms = backoff_ms,
-                                    "Watch index run failed, backing off"
-                                );
-                                sleep(Duration::from_millis(backoff_ms)).await;
-                            }
-                        }
-                    }
-                    Ok(false) => {
-                        // No changes - skip indexing this cycle
-                        tracing::trace!(
-                            repo = %repo_name,
-                            "No changes detected, skipping index cycle"
-                        );
+            let backend = pipeline.config.watch_backend;
+            if backend != WatchBackend::Poll {
+                match watch::EventWatcher::start(pipeline.config.clone()) {
+                    Ok(watcher) => {
+                        tracing::info!(repo = %pipeline.repo_name(), "Watching for filesystem events");
+                        pipeline.run_event_watch_loop(watcher).await;
+                        tracing::warn!(repo = %pipeline.repo_name(), "Filesystem event stream closed");
                     }
                     Err(err) => {
                         tracing::warn!(
-                            repo = %repo_name,
+                            repo = %pipeline.repo_name(),
                             error = %err,
-                            "Failed to check for changes, skipping this cycle"
+                            "Failed to start filesystem watcher"
                         );
                     }
                 }
+                if backend == WatchBackend::Events {
+                    return;
+                }
+                tracing::info!(repo = %pipeline.repo_name(), "Falling back to polling watch mode");
             }
+            pipeline.run_poll_watch_loop().await;
         })
     }
 
+    /// Re-index coalesced filesystem events until the event stream closes.
+    async fn run_event_watch_loop(&self, mut watcher: watch::EventWatcher) {
+        let quiet = Duration::from_millis(self.config.watch_debounce_ms.max(50));
+        let min_index_interval = Duration::from_millis(self.config.watch_min_index_interval_ms);
+
+        let index = |changes: watch::ChangeSet| async move {
+            if changes.needs_rescan {
+                self.index_all().await?;
+            } else {
+                let paths = changes
+                    .paths
+                    .into_iter()
+                    .filter_map(|p| Utf8PathBuf::from_path_buf(p).ok())
+                    .collect::<Vec<_>>();
+                self.index_paths(&paths).await?;
+            }
+            Ok(())
+        };
+        watcher
+            .run_index_loop(quiet, min_index_interval, self.repo_name(), index)
+            .await;
+    }
+
+    /// Polling fallback: re-scan all repo roots every `watch_debounce_ms` and re-index
+    /// the files whose fingerprints differ.
+    async fn run_poll_watch_loop(&self) {
+        let interval_ms = self.config.watch_debounce_ms.max(50);
+        let min_index_interval = self.config.watch_min_index_interval_ms;
+        let mut consecutive_failures = 0;
+        let max_backoff_ms = 5000; // Max 5 seconds backoff
+        let mut last_index_time: Option<Instant> = None;
+
+        // Get repository name for logging
+        let repo_name = self
+            .config
+            .base_dir
+            .file_name()
+            .unwrap_or("unknown");
+
+        loop {
+            sleep(Duration::from_millis(interval_ms)).await;
+
+            // Only re-index if files have actually changed
+            match self.check_for_changes() {
+                Ok(changed) if !changed.is_empty() => {
+                    // Check rate limiting: ensure minimum time between index runs
+                    if let Some(last_time) = last_index_time {
+                        let elapsed = last_time.elapsed().as_millis() as u64;
+                        if elapsed < min_index_interval {
+                            tracing::debug!(
+                                repo = %repo_name,
+                                elapsed_ms = elapsed,
+                                min_interval_ms = min_index_interval,
+                                "Rate limiting: skipping index, too soon since last run"
+                            );
+                            continue;
+                        }
+                    }
+
+                    // Changes detected - proceed with indexing
+                    tracing::info!(
+                        repo = %repo_name,
+                        paths = changed.len(),
+                        "Changes detected, starting index run"
+                    );
+
+                    match self.index_paths(&changed).await {
+                        Ok(_) => {
+                            last_index_time = Some(Instant::now());
+                            consecutive_failures = 0; // Reset on success
+                        }
+                        Err(err) => {
+                            consecutive_failures += 1;
+                            let backoff_ms =
+                                (interval_ms * (1 << consecutive_failures.min(8))).min(max_backoff_ms);
+                            tracing::warn!(
+                                repo = %repo_name,
+                                error = %err,
+                                consecutive_failures = consecutive_failures,
+                                backoff_ms = backoff_ms,
+                                "Watch index run failed, backing off"
+                            );
+                            sleep(Duration::from_millis(backoff_ms)).await;
+                        }
+                    }
+                }
+                Ok(_) => {
+                    // No changes - skip indexing this cycle
+                    tracing::trace!(
+                        repo = %repo_name,
+                        "No changes detected, skipping index cycle"
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        repo = %repo_name,
+                        error = %err,
+                        "Failed to check for changes, skipping this cycle"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Remove every trace of an indexed file from SQLite, Tantivy and LanceDB.
+    ///
+    /// The caller is responsible for committing Tantivy.
+    async fn delete_file_data(&self, file_path: &str) -> Result<()> {
+        {
+            let sqlite = SqliteStore::open(&self.db_path)?;
+            sqlite.init()?;
+
+            // Delete symbols first - test_links have ON DELETE CASCADE, so they auto-delete
+            sqlite.delete_symbols_by_file(file_path)?;
+            sqlite.delete_usage_examples_by_file(file_path)?;
+            sqlite.delete_todos_by_file(file_path)?;
+            sqlite.delete_docstrings_by_file(file_path)?;
+            sqlite.delete_decorators_by_file(file_path)?;
+            sqlite.delete_framework_patterns_by_file(file_path)?;
+            sqlite.delete_file_dependencies(file_path)?;
+            sqlite.delete_file_fingerprint(file_path)?;
+            sqlite.delete_file_identity(file_path)?;
+            sqlite.delete_file_trigrams(file_path)?;
+        }
+
+        self.tantivy.delete_symbols_by_file(file_path)?;
+        self.vectors.delete_records_by_file_path(file_path).await?;
+        Ok(())
+    }
+
+    /// Record the trigrams of indexed files that have none, such as files indexed
+    /// before the content index existed and unchanged since.
+    fn backfill_content_trigrams(&self) -> Result<()> {
+        let sqlite = SqliteStore::open(&self.db_path)?;
+        sqlite.init()?;
+        for file_path in sqlite.list_files_without_trigrams()? {
+            if let Some(content) = sqlite.get_file_content(&file_path)? {
+                sqlite.replace_file_trigrams(&file_path, &content_trigrams(&content))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pair deleted files with never-indexed files of similar content and move each
+    /// deleted file's identity and symbols to its new path.
+    fn carry_over_renames(&self, files: &[PathBuf], deleted: &[String]) -> Result<()> {
+        let sqlite = SqliteStore::open(&self.db_path)?;
+        sqlite.init()?;
+
+        let mut old_files = Vec::new();
+        for file_path in deleted {
+            let content = sqlite
+                .list_symbols_by_file(file_path)?
+                .into_iter()
+                .find(|row| row.kind == "file")
+                .map(|row| row.text);
+            if let Some(content) = content {
+                old_files.push((file_path.clone(), content));
+            }
+        }
+        if old_files.is_empty() {
+            return Ok(());
+        }
+
+        let mut new_files = Vec::new();
+        for file in files {
+            let rel = file_key_path(&self.config, file);
+            if sqlite.get_file_fingerprint(&rel)?.is_some() || sqlite.has_file_identity(&rel)? {
+                continue;
+            }
+            if let Ok(content) = fs::read_to_string(file) {
+                new_files.push((rel, content));
+            }
+        }
+
+        for (from, to) in identity::detect_renames(&old_files, &new_files) {
+            tracing::debug!(repo = %self.repo_name(), from = %from, to = %to, "Detected file rename");
+            sqlite
+                .rename_file(&from, &to)
+                .with_context(|| format!("Failed to carry {from} over to {to}"))?;
+        }
+        Ok(())
+    }
+
     fn persist_index_run_metrics(
         &self,
         started_at_unix_s: i64,
@@ -445,10 +629,16 @@ impl IndexPipeline {
         Ok(())
     }
 
+    /// Index `files`, then purge index data for files that are gone.
+    ///
+    /// With `cleanup_deleted`, every indexed file missing from `files` is purged (full
+    /// scans). `removed` lists relative paths known to be gone; indexed files at or
+    /// below them are purged, which covers deletes as well as moved directories.
     async fn index_files(
         &self,
         files: Vec<PathBuf>,
         cleanup_deleted: bool,
+        removed: &[String],
     ) -> Result<IndexRunStats> {
         let mut seen = HashSet::new();
         let mut uniq = Vec::new();
@@ -464,8 +654,9 @@ impl IndexPipeline {
             ..Default::default()
         };
 
-        // Cleanup deleted files first
-        if cleanup_deleted {
+        // Files that are gone: everything not scanned on a full run, otherwise
+        // whatever sits at or below an explicitly removed path
+        let to_delete = if cleanup_deleted || !removed.is_empty() {
             let mut scanned_rel: HashSet<String> = HashSet::new();
             for file in &uniq {
                 scanned_rel.insert(file_key_path(&self.config, file));
@@ -477,37 +668,41 @@ impl IndexPipeline {
                 sqlite.list_all_file_fingerprints(1_000_000)?
             };
 
-            let to_delete = existing
+            existing
                 .into_iter()
                 .filter(|fp| !scanned_rel.contains(&fp.file_path))
+                .filter(|fp| {
+                    cleanup_deleted
+                        || removed
+                            .iter()
+                            .any(|r| is_same_or_descendant(&fp.file_path, r))
+                })
                 .map(|fp| fp.file_path)
-                .collect::<Vec<_>>();
-
-            let mut any = false;
-            for file_path in to_delete {
-                {
-                    let sqlite = SqliteStore::open(&self.db_path)?;
-                    sqlite.init()?;
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
 
-                    // Delete symbols first - test_links have ON DELETE CASCADE, so they auto-delete
-                    sqlite.delete_symbols_by_file(&file_path)?;
-                    sqlite.delete_usage_examples_by_file(&file_path)?;
-                    sqlite.delete_todos_by_file(&file_path)?;
-                    sqlite.delete_docstrings_by_file(&file_path)?;
-                    sqlite.delete_decorators_by_file(&file_path)?;
-                    sqlite.delete_file_fingerprint(&file_path)?;
-                }
+        // Snapshot what is about to change so dependents and PageRank can follow
+        let mut impact = {
+            let sqlite = SqliteStore::open(&self.db_path)?;
+            sqlite.init()?;
+            ChangeImpact::capture(&sqlite, &self.config, &uniq, &to_delete)?
+        };
 
-                self.tantivy.delete_symbols_by_file(&file_path)?;
-                self.vectors.delete_records_by_file_path(&file_path).await?;
+        // A deleted file that reappears elsewhere keeps its symbol ids
+        if !to_delete.is_empty() {
+            self.carry_over_renames(&uniq, &to_delete)?;
+        }
 
-                stats.files_deleted += 1;
-                any = true;
-            }
+        // Cleanup deleted files first
+        for file_path in &to_delete {
+            self.delete_file_data(file_path).await?;
+            stats.files_deleted += 1;
+        }
 
-            if any {
-                self.tantivy.commit()?;
-            }
+        if !to_delete.is_empty() {
+            self.tantivy.commit()?;
         }
 
         // Choose parallel or sequential indexing based on config
@@ -536,18 +731,137 @@ impl IndexPipeline {
         stats.files_unchanged = indexing_stats.files_unchanged;
         stats.symbols_indexed = indexing_stats.symbols_indexed;
 
+        if let Err(err) = self.backfill_content_trigrams() {
+            tracing::warn!(repo = %self.repo_name(), error = %err, "Failed to backfill content index");
+        }
+
+        // Re-resolve edges of files that depend on what changed
+        if !impact.is_empty() {
+            let sqlite = SqliteStore::open(&self.db_path)?;
+            sqlite.init()?;
+            impact.finish(&sqlite)?;
+            for rel in impact.dependent_files(&sqlite)? {
+                match reresolve_file_edges(&sqlite, &self.config, &self.db_path, &rel) {
+                    Ok(targets) => {
+                        impact.add_seeds(targets);
+                        stats.files_reresolved += 1;
+                    }
+                    Err(err) => {
+                        tracing::warn!(
+                            repo = %self.repo_name(),
+                            file = %rel,
+                            error = %err,
+                            "Failed to re-resolve edges of dependent file"
+                        );
+                    }
+                }
+            }
+        }
+
+        // Files whose links to resources and services are rebuilt below, unless a full
+        // scan rebuilds them all: the changed ones, and those whose routes moved
+        let mut linked_files = impact.changed_files().clone();
+
+        // Routes are matched against contracts below, so give them their full paths
+        // and handlers first: both may come from other files
+        if stats.files_indexed > 0 || stats.files_deleted > 0 {
+            let resolved = SqliteStore::open(&self.db_path)
+                .and_then(|sqlite| routes::resolve_mounted_routes(&sqlite));
+            match resolved {
+                Ok(files) => {
+                    tracing::debug!(files = files.len(), "Resolved mounted routes");
+                    linked_files.extend(files);
+                }
+                Err(err) => tracing::warn!(
+                    repo = %self.repo_name(),
+                    error = %err,
+                    "Failed to resolve mounted routes"
+                ),
+            }
+        }
+
+        // Links from code to resources follow changes on either side, so rebuild them
+        // before PageRank takes them into account
+        if stats.files_indexed > 0 || stats.files_deleted > 0 {
+            let scope = (!cleanup_deleted).then_some(&linked_files);
+            let linked = SqliteStore::open(&self.db_path).and_then(|sqlite| {
+                let touched = resources::link_resource_references(&sqlite, scope)?;
+                let files: Vec<String> = sqlite
+                    .list_symbol_headers_by_ids(&touched)?
+                    .into_iter()
+                    .map(|header| header.file_path)
+                    .collect();
+                Ok((touched, files))
+            });
+            match linked {
+                Ok((touched, files)) => {
+                    tracing::debug!(symbols = touched.len(), "Linked resource references");
+                    impact.add_seeds(touched);
+                    // Clients of a contract follow their links to it
+                    linked_files.extend(files);
+                }
+                Err(err) => tracing::warn!(
+                    repo = %self.repo_name(),
+                    error = %err,
+                    "Failed to link resource references"
+                ),
+            }
+        }
+
+        // Calls between services go by the routes and contract links set above
+        if stats.files_indexed > 0 || stats.files_deleted > 0 {
+            let scope = (!cleanup_deleted).then_some(&linked_files);
+            let linked = SqliteStore::open(&self.db_path)
+                .and_then(|sqlite| http_calls::link_http_calls(&sqlite, scope));
+            match linked {
+                Ok(touched) => {
+                    tracing::debug!(symbols = touched.len(), "Linked HTTP calls");
+                    impact.add_seeds(touched);
+                }
+                Err(err) => tracing::warn!(
+                    repo = %self.repo_name(),
+                    error = %err,
+                    "Failed to link HTTP calls"
+                ),
+            }
+        }
+
+        // History is mined again only when HEAD moved, which an index run may follow
+        // without any file changing
+        {
+            let changed = impact.reindexed_files();
+            let refreshed = SqliteStore::open(&self.db_path)
+                .and_then(|sqlite| history::refresh_history(&sqlite, &self.config, &changed));
+            match refreshed {
+                Ok(files) => tracing::debug!(files = ?files, "Refreshed git history"),
+                Err(err) => tracing::warn!(
+                    repo = %self.repo_name(),
+                    error = %err,
+                    "Failed to refresh git history"
+                ),
+            }
+        }
+
         // Compute PageRank scores after all indexing is complete
-        // Only run if the graph structure changed (files indexed or deleted)
+        // Only run if the graph structure changed (files indexed or deleted). Full scans
+        // recompute from scratch; incremental runs only propagate from what changed.
         if stats.files_indexed > 0 || stats.files_deleted > 0 {
             let sqlite = SqliteStore::open(&self.db_path)?;
             sqlite.init()?;
-            pagerank::compute_and_store_pagerank(&sqlite, &self.config)
-                .with_context(|| {
-                    format!(
-                        "Failed to compute PageRank scores: files_indexed={}, files_deleted={}",
-                        stats.files_indexed, stats.files_deleted
-                    )
-                })?;
+            let result = if cleanup_deleted {
+                pagerank::compute_and_store_pagerank(&sqlite, &self.config)
+            } else {
+                pagerank::update_pagerank_incremental(&sqlite, &self.config, &impact.seeds())
+                    .map(|updated| {
+                        tracing::debug!(updated = updated, "Incremental PageRank update complete");
+                    })
+            };
+            result.with_context(|| {
+                format!(
+                    "Failed to compute PageRank scores: files_indexed={}, files_deleted={}",
+                    stats.files_indexed, stats.files_deleted
+                )
+            })?;
         } else {
             tracing::debug!("Skipping PageRank computation (no files indexed or deleted)");
         }
@@ -653,20 +967,7 @@ impl IndexPipeline {
                 }
             };
 
-            let extracted = match language_id {
-                LanguageId::Typescript | LanguageId::Tsx => {
-                    extract_typescript_symbols_with_path(language_id, &source, &rel)
-                }
-                LanguageId::Rust => extract_rust_symbols(&source),
-                LanguageId::Python => extract_python_symbols(&source),
-                LanguageId::Go => extract_go_symbols(&source),
-                LanguageId::C => extract_c_symbols(&source),
-                LanguageId::Cpp => extract_cpp_symbols(&source),
-                LanguageId::Java => extract_java_symbols(&source),
-                LanguageId::Javascript => extract_javascript_symbols(&source),
-            };
-
-            let extracted = match extracted {
+            let mut extracted = match extract_symbols_for_language(language_id, &source, &rel) {
                 Ok(syms) => syms,
                 Err(err) => {
                     tracing::warn!(
@@ -684,46 +985,12 @@ impl IndexPipeline {
                 .await
                 .with_context(|| format!("Failed to delete old vectors for {rel}"))?;
 
-            {
-                let sqlite = SqliteStore::open(&self.db_path)?;
-                sqlite.init()?;
-
-                // Delete symbols first - test_links have ON DELETE CASCADE, so they auto-delete
-                if let Err(err) = sqlite.delete_symbols_by_file(&rel) {
-                    tracing::error!(
-                        file = %rel,
-                        error = %err,
-                        error_chain = %err.chain().map(|e| e.to_string()).collect::<Vec<_>>().join(" -> "),
-                        "Failed to delete old symbols (full error chain)"
-                    );
-                    return Err(err)
-                        .with_context(|| format!("Failed to delete old symbols for {rel}"));
-                }
-                sqlite
-                    .delete_usage_examples_by_file(&rel)
-                    .with_context(|| format!("Failed to delete old usage examples for {rel}"))?;
-                sqlite
-                    .delete_todos_by_file(&rel)
-                    .with_context(|| format!("Failed to delete old todos for {rel}"))?;
-                sqlite
-                    .delete_docstrings_by_file(&rel)
-                    .with_context(|| format!("Failed to delete old docstrings for {rel}"))?;
-                sqlite
-                    .delete_decorators_by_file(&rel)
-                    .with_context(|| format!("Failed to delete old decorators for {rel}"))?;
-                sqlite
-                    .delete_framework_patterns_by_file(&rel)
-                    .with_context(|| format!("Failed to delete old framework patterns for {rel}"))?;
-                // Note: test_links auto-delete via ON DELETE CASCADE when symbols are deleted
-            }
-
             let mut symbol_rows = Vec::new();
 
             // 1. Add File-Level Symbol (Document Indexing)
             // We index the file itself as a symbol to allow retrieval of the "whole file" concept.
-            let file_symbol_id = stable_symbol_id(&rel, "FILE_ROOT", 0);
             symbol_rows.push(SymbolRow {
-                id: file_symbol_id,
+                id: String::new(),
                 file_path: rel.clone(),
                 language: language_string(language_id).to_string(),
                 kind: "file".to_string(),
@@ -734,6 +1001,8 @@ impl IndexPipeline {
                 start_line: 1,
                 end_line: source.lines().count() as u32,
                 text: source.clone(),
+                qualified_name: String::new(),
+                parent_id: None,
             });
 
             for sym in extracted.symbols {
@@ -746,28 +1015,69 @@ impl IndexPipeline {
                     continue;
                 }
 
-                let start_byte_for_id = if sym.exported {
-                    0
-                } else {
-                    sym.bytes.start as u32
-                };
-                let id = stable_symbol_id(&rel, &sym.name, start_byte_for_id);
                 symbol_rows.push(SymbolRow {
-                    id,
+                    id: String::new(),
                     file_path: rel.clone(),
                     language: language_string(language_id).to_string(),
                     kind: symbol_kind_to_string(sym.kind),
                     name: sym.name,
                     exported: sym.exported,
                     start_byte: sym.bytes.start as u32,
-
                     end_byte: sym.bytes.end as u32,
                     start_line: sym.lines.start,
                     end_line: sym.lines.end,
                     text,
+                    qualified_name: String::new(),
+                    parent_id: None,
                 });
             }
 
+            {
+                let sqlite = SqliteStore::open(&self.db_path)?;
+                sqlite.init()?;
+
+                let identity_path = sqlite
+                    .get_or_assign_file_identity(&rel)
+                    .with_context(|| format!("Failed to get file identity for {rel}"))?;
+                identity::assign_symbol_ids(&identity_path, &mut symbol_rows);
+                hierarchy::assign_hierarchy(&mut symbol_rows, &source);
+
+                // Symbols that are gone are deleted (test_links cascade with them); the
+                // rest keep their ids and whatever is keyed by them
+                let ids: Vec<String> = symbol_rows.iter().map(|r| r.id.clone()).collect();
+                if let Err(err) = sqlite.retain_symbols_by_file(&rel, &ids) {
+                    tracing::error!(
+                        file = %rel,
+                        error = %err,
+                        error_chain = %err.chain().map(|e| e.to_string()).collect::<Vec<_>>().join(" -> "),
+                        "Failed to delete old symbols (full error chain)"
+                    );
+                    return Err(err)
+                        .with_context(|| format!("Failed to delete old symbols for {rel}"));
+                }
+                sqlite
+                    .delete_usage_examples_by_file(&rel)
+                    .with_context(|| format!("Failed to delete old usage examples for {rel}"))?;
+                sqlite
+                    .delete_todos_by_file(&rel)
+                    .with_context(|| format!("Failed to delete old todos for {rel}"))?;
+                sqlite
+                    .delete_docstrings_by_file(&rel)
+                    .with_context(|| format!("Failed to delete old docstrings for {rel}"))?;
+                sqlite
+                    .delete_decorators_by_file(&rel)
+                    .with_context(|| format!("Failed to delete old decorators for {rel}"))?;
+                sqlite
+                    .delete_framework_patterns_by_file(&rel)
+                    .with_context(|| format!("Failed to delete old framework patterns for {rel}"))?;
+            }
+
+            assign_extracted_symbol_ids(
+                &mut extracted.jsdoc_entries,
+                &mut extracted.decorators,
+                &symbol_rows,
+            );
+
             if !symbol_rows.is_empty() {
                 let vectors = self
                     .embed_and_build_vector_records(&symbol_rows)
@@ -793,18 +1103,12 @@ impl IndexPipeline {
                     for row in &symbol_rows {
                         sqlite.upsert_symbol(row)?;
                     }
+                    sqlite
+                        .replace_file_trigrams(&rel, &content_trigrams(&source))
+                        .with_context(|| format!("Failed to index content of {rel}"))?;
 
                     // Create package lookup function for cross-package edge resolution
-                    let db_path_for_lookup = self.db_path.clone();
-                    let package_lookup_fn: edges::PackageLookupFn =
-                        Box::new(move |file_path: &str| -> Option<String> {
-                            if let Ok(sqlite) = SqliteStore::open(&db_path_for_lookup) {
-                                if let Ok(Some(pkg)) = sqlite.get_package_for_file(file_path) {
-                                    return Some(pkg.id);
-                                }
-                            }
-                            None
-                        });
+                    let package_lookup_fn = edges::db_package_lookup(&self.db_path);
 
                     // Use a reference to the package lookup function for multiple calls
                     let package_lookup_ref: Option<&edges::PackageLookupFn> =
@@ -829,6 +1133,12 @@ impl IndexPipeline {
                         }
                     }
 
+                    if let Err(err) =
+                        record_file_dependencies(&sqlite, &rel, &extracted.imports)
+                    {
+                        tracing::warn!(file = %rel, error = %err, "Failed to record file dependencies");
+                    }
+
                     let examples = extract_usage_examples_for_file(
                         &rel,
                         &source,
@@ -900,8 +1210,13 @@ impl IndexPipeline {
                                     kind: p.kind.to_string(),
                                     http_method: p.http_method.clone(),
                                     path: p.path.clone(),
+                                    local_path: p.path.clone(),
                                     name: p.name.clone(),
                                     handler: p.handler.clone(),
+                                    symbol_id: handler_symbol_id(
+                                        p.handler.as_deref(),
+                                        &symbol_rows,
+                                    ),
                                     arguments: p.arguments.clone(),
                                     parent_chain: p.parent_chain.clone(),
                                     updated_at: 0,
@@ -1012,6 +1327,8 @@ impl IndexPipeline {
                 start_line: sym.start_line,
                 end_line: sym.end_line,
                 text: sym.text,
+                qualified_name: sym.qualified_name,
+                parent_id: sym.parent_id,
             });
         }
 
@@ -1,26 +1,26 @@
 use crate::{
     config::Config,
     indexer::{
-        extract::go::extract_go_symbols,
-        extract::java::extract_java_symbols,
-        extract::javascript::extract_javascript_symbols,
-        extract::python::extract_python_symbols,
-        extract::rust::extract_rust_symbols,
-        extract::typescript::extract_typescript_symbols_with_path,
-        extract::{c::extract_c_symbols, cpp::extract_cpp_symbols},
-        parser::{language_id_for_path, LanguageId},
+        parser::language_id_for_path,
         pipeline::{
-            edges::{extract_edges_for_symbol, upsert_name_mapping},
-            parsing::symbol_kind_to_string,
+            edges::{db_package_lookup, extract_edges_for_symbol, upsert_name_mapping},
+            hierarchy::assign_hierarchy,
+            identity::assign_symbol_ids,
+            incremental::record_file_dependencies,
+            parsing::{extract_symbols_for_language, symbol_kind_to_string},
             stats::IndexRunStats,
             usage::extract_usage_examples_for_file,
-            utils::{file_fingerprint, file_key_path, language_string, stable_symbol_id},
+            utils::{
+                assign_extracted_symbol_ids, file_fingerprint, file_key_path, handler_symbol_id,
+                language_string,
+            },
         },
     },
     path::Utf8PathBuf,
     storage::{
         sqlite::{schema::{DecoratorRow, FrameworkPatternRow}, SqliteStore, SymbolRow},
         tantivy::TantivyIndex,
+        trigram::content_trigrams,
         vector::LanceVectorTable,
     },
 };
@@ -184,18 +184,7 @@ fn index_file_single(
     let source = fs::read_to_string(file)
         .with_context(|| format!("Failed to read file: {}", file.display()))?;
 
-    let extracted = match language_id {
-        LanguageId::Typescript | LanguageId::Tsx => {
-            extract_typescript_symbols_with_path(language_id, &source, &rel)
-        }
-        LanguageId::Rust => extract_rust_symbols(&source),
-        LanguageId::Python => extract_python_symbols(&source),
-        LanguageId::Go => extract_go_symbols(&source),
-        LanguageId::C => extract_c_symbols(&source),
-        LanguageId::Cpp => extract_cpp_symbols(&source),
-        LanguageId::Java => extract_java_symbols(&source),
-        LanguageId::Javascript => extract_javascript_symbols(&source),
-    }
+    let mut extracted = extract_symbols_for_language(language_id, &source, &rel)
     .with_context(|| format!("Failed to extract symbols from: {}", file.display()))?;
 
     // Delete old data
@@ -203,22 +192,18 @@ fn index_file_single(
     // Note: We skip vector deletion here to avoid async in sync context
     // Vectors will be updated in batch later
 
-    // Delete symbols first - test_links have ON DELETE CASCADE, so they auto-delete
-    sqlite.delete_symbols_by_file(&rel)?;
     sqlite.delete_usage_examples_by_file(&rel)?;
     sqlite.delete_todos_by_file(&rel)?;
     sqlite.delete_docstrings_by_file(&rel)?;
     sqlite.delete_decorators_by_file(&rel)?;
     sqlite.delete_framework_patterns_by_file(&rel)?;
-    // Note: test_links auto-delete via ON DELETE CASCADE when symbols are deleted
 
     let mut name_to_id: HashMap<String, String> = HashMap::new();
     let mut symbol_rows = Vec::new();
 
     // Add file-level symbol
-    let file_symbol_id = stable_symbol_id(&rel, "FILE_ROOT", 0);
     symbol_rows.push(SymbolRow {
-        id: file_symbol_id,
+        id: String::new(),
         file_path: rel.clone(),
         language: language_string(language_id).to_string(),
         kind: "file".to_string(),
@@ -229,6 +214,8 @@ fn index_file_single(
         start_line: 1,
         end_line: source.lines().count() as u32,
         text: source.clone(),
+        qualified_name: String::new(),
+        parent_id: None,
     });
 
     for sym in extracted.symbols {
@@ -241,14 +228,8 @@ fn index_file_single(
             continue;
         }
 
-        let start_byte_for_id = if sym.exported {
-            0
-        } else {
-            sym.bytes.start as u32
-        };
-        let id = stable_symbol_id(&rel, &sym.name, start_byte_for_id);
         symbol_rows.push(SymbolRow {
-            id,
+            id: String::new(),
             file_path: rel.clone(),
             language: language_string(language_id).to_string(),
             kind: symbol_kind_to_string(sym.kind),
@@ -259,9 +240,26 @@ fn index_file_single(
             start_line: sym.lines.start,
             end_line: sym.lines.end,
             text,
+            qualified_name: String::new(),
+            parent_id: None,
         });
     }
 
+    let identity_path = sqlite.get_or_assign_file_identity(&rel)?;
+    assign_symbol_ids(&identity_path, &mut symbol_rows);
+    assign_hierarchy(&mut symbol_rows, &source);
+
+    // Symbols that are gone are deleted (test_links cascade with them); the rest keep
+    // their ids and whatever is keyed by them
+    let ids: Vec<String> = symbol_rows.iter().map(|r| r.id.clone()).collect();
+    sqlite.retain_symbols_by_file(&rel, &ids)?;
+
+    assign_extracted_symbol_ids(
+        &mut extracted.jsdoc_entries,
+        &mut extracted.decorators,
+        &symbol_rows,
+    );
+
     if symbol_rows.is_empty() {
         sqlite.upsert_file_fingerprint(&rel, fp.mtime_ns, fp.size_bytes)?;
         return Ok(IndexFileResult::Processed { symbols_count: 0 });
@@ -282,23 +280,13 @@ fn index_file_single(
     for row in &symbol_rows {
         sqlite.upsert_symbol(row)?;
     }
+    sqlite.replace_file_trigrams(&rel, &content_trigrams(&source))?;
 
-    for row in &symbol_rows {
-        // Create package lookup function for cross-package edge resolution
-        let db_path_for_lookup = db_path.to_path_buf();
-        let package_lookup_fn: super::edges::PackageLookupFn =
-            Box::new(move |file_path: &str| -> Option<String> {
-                if let Ok(sqlite) = SqliteStore::open(&db_path_for_lookup) {
-                    if let Ok(Some(pkg)) = sqlite.get_package_for_file(file_path) {
-                        return Some(pkg.id);
-                    }
-                }
-                None
-            });
-
-        // Use a reference to the package lookup function
-        let package_lookup_ref: Option<&super::edges::PackageLookupFn> = Some(&package_lookup_fn);
+    // Create package lookup function for cross-package edge resolution
+    let package_lookup_fn = db_package_lookup(db_path);
+    let package_lookup_ref: Option<&super::edges::PackageLookupFn> = Some(&package_lookup_fn);
 
+    for row in &symbol_rows {
         let edges = extract_edges_for_symbol(
             row,
             &name_to_id,
@@ -317,6 +305,10 @@ fn index_file_single(
         }
     }
 
+    if let Err(err) = record_file_dependencies(&sqlite, &rel, &extracted.imports) {
+        tracing::warn!(file = %rel, error = %err, "Failed to record file dependencies");
+    }
+
     let examples = extract_usage_examples_for_file(
         &rel,
         &source,
@@ -374,8 +366,10 @@ fn index_file_single(
                     kind: p.kind.to_string(),
                     http_method: p.http_method.clone(),
                     path: p.path.clone(),
+                    local_path: p.path.clone(),
                     name: p.name.clone(),
                     handler: p.handler.clone(),
+                    symbol_id: handler_symbol_id(p.handler.as_deref(), &symbol_rows),
                     arguments: p.arguments.clone(),
                     parent_chain: p.parent_chain.clone(),
                     updated_at: 0,
@@ -1,6 +1,55 @@
-use crate::indexer::extract::symbol::SymbolKind;
+use crate::indexer::{
+    extract::c::extract_c_symbols,
+    extract::config::{extract_json_symbols, extract_toml_symbols, extract_yaml_symbols},
+    extract::cpp::extract_cpp_symbols,
+    extract::csharp::extract_csharp_symbols,
+    extract::go::extract_go_symbols,
+    extract::java::extract_java_symbols,
+    extract::javascript::extract_javascript_symbols,
+    extract::jvm::{extract_kotlin_symbols, extract_scala_symbols},
+    extract::markdown::extract_markdown_symbols,
+    extract::protobuf::extract_protobuf_symbols,
+    extract::python::extract_python_symbols,
+    extract::ruby::extract_ruby_symbols,
+    extract::rust::extract_rust_symbols,
+    extract::sql::extract_sql_symbols,
+    extract::symbol::{ExtractedFile, SymbolKind},
+    extract::typescript::extract_typescript_symbols_with_path,
+    parser::LanguageId,
+};
+use anyhow::Result;
 use std::collections::HashSet;
 
+/// Run the extractor for `language_id` over `source` (`rel` is the file key).
+pub fn extract_symbols_for_language(
+    language_id: LanguageId,
+    source: &str,
+    rel: &str,
+) -> Result<ExtractedFile> {
+    match language_id {
+        LanguageId::Typescript | LanguageId::Tsx => {
+            extract_typescript_symbols_with_path(language_id, source, rel)
+        }
+        LanguageId::Rust => extract_rust_symbols(source),
+        LanguageId::Python => extract_python_symbols(source),
+        LanguageId::Go => extract_go_symbols(source),
+        LanguageId::C => extract_c_symbols(source),
+        LanguageId::Cpp => extract_cpp_symbols(source),
+        LanguageId::Java => extract_java_symbols(source),
+        LanguageId::Javascript => extract_javascript_symbols(source),
+        LanguageId::Ruby => extract_ruby_symbols(source),
+        LanguageId::CSharp => extract_csharp_symbols(source),
+        LanguageId::Kotlin => extract_kotlin_symbols(source),
+        LanguageId::Scala => extract_scala_symbols(source),
+        LanguageId::Markdown => extract_markdown_symbols(source),
+        LanguageId::Yaml => extract_yaml_symbols(source),
+        LanguageId::Toml => extract_toml_symbols(source),
+        LanguageId::Json => extract_json_symbols(source),
+        LanguageId::Sql => extract_sql_symbols(source),
+        LanguageId::Protobuf => extract_protobuf_symbols(source),
+    }
+}
+
 pub fn symbol_kind_to_string(kind: SymbolKind) -> String {
     match kind {
         SymbolKind::Function => "function",
@@ -13,6 +62,15 @@ pub fn symbol_kind_to_string(kind: SymbolKind) -> String {
         SymbolKind::Trait => "trait",
         SymbolKind::Impl => "impl",
         SymbolKind::Module => "module",
+        SymbolKind::Property => "property",
+        SymbolKind::Section => "section",
+        SymbolKind::ConfigKey => "config_key",
+        SymbolKind::Table => "table",
+        SymbolKind::Column => "column",
+        SymbolKind::Message => "message",
+        SymbolKind::Service => "service",
+        SymbolKind::Rpc => "rpc",
+        SymbolKind::Operation => "operation",
     }
     .to_string()
 }
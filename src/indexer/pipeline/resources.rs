@@ -0,0 +1,590 @@
+//! Links from code to the documents, config, schemas and contracts it mentions.
+//!
+//! Code names a table in its queries, a config key in a lookup string and a protobuf
+//! message through the types generated from it; none of that resolves through
+//! imports. After each index run the resource symbols are looked up in the content
+//! index instead, and each code symbol mentioning one gets an edge to it with the
+//! `resource` resolution. A full scan rebuilds those edges as a whole; other runs
+//! rebuild the ones found in or pointing into the files they changed, so the edges
+//! follow both the code and the resources as either changes.
+//!
+//! Mentions of an API contract (protobuf services, rpcs and messages, OpenAPI
+//! operations) are told apart by where they occur. Code in generated stub files is
+//! `generated_from` the contract, and a handler named after an rpc or operation
+//! `implements_contract` it; every other mention, typically a client call, stays a
+//! `reference`. Handlers are also found by matching the routes the code registers
+//! against the method and path of each OpenAPI operation.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::indexer::extract::openapi::OPENAPI_FRAMEWORK;
+use crate::indexer::parser::language_id_for_path;
+use crate::storage::sqlite::{EdgeRow, FrameworkPatternRow, SqliteStore, SymbolHeaderRow};
+use crate::storage::trigram::content_trigrams;
+
+use super::edges::{is_resource_language, RESOURCE_LANGUAGES};
+use super::utils::language_string;
+
+/// Resolution of the edges from code to resource symbols.
+pub const RESOURCE_RESOLUTION: &str = "resource";
+
+/// Edge from generated stub code to the contract it was generated from.
+pub const GENERATED_FROM_EDGE: &str = "generated_from";
+
+/// Edge from a handler to the rpc or OpenAPI operation it serves.
+pub const IMPLEMENTS_CONTRACT_EDGE: &str = "implements_contract";
+
+/// Shortest name looked for; shorter ones turn up everywhere.
+const MIN_NAME_LEN: usize = 4;
+
+/// Confidence of a mention: a name match, not a resolved reference.
+const MENTION_CONFIDENCE: f32 = 0.6;
+
+/// Confidence of a route whose method and path match an operation.
+const ROUTE_CONFIDENCE: f32 = 0.8;
+
+/// Rebuild the edges from code symbols to the resource symbols they mention. With
+/// `changed`, only the edges found in or pointing into those files are: changed code
+/// is matched against every resource, and the resources of changed files against
+/// every code file. Returns the ends of the edges removed or added, whose PageRank
+/// may have moved.
+pub fn link_resource_references(
+    sqlite: &SqliteStore,
+    changed: Option<&BTreeSet<String>>,
+) -> Result<Vec<String>> {
+    let resources: Vec<(SymbolHeaderRow, Regex)> = sqlite
+        .list_symbol_headers_by_languages(RESOURCE_LANGUAGES)?
+        .into_iter()
+        .filter_map(|resource| {
+            let pattern = mention_pattern(&resource)?;
+            Some(Regex::new(&pattern).map(|regex| (resource, regex)))
+        })
+        .collect::<std::result::Result<_, _>>()
+        .context("Failed to build resource patterns")?;
+    let touches = |file: &str| changed.is_none_or(|changed| changed.contains(file));
+
+    // Which resources each code file may mention, going by the content index
+    let mut by_file: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+    for (index, (resource, _)) in resources.iter().enumerate() {
+        if !touches(&resource.file_path) {
+            continue;
+        }
+        for file in sqlite.list_files_with_all_trigrams(&content_trigrams(&resource.name))? {
+            if is_code_file(&file) {
+                by_file.entry(file).or_default().insert(index);
+            }
+        }
+    }
+    // and which resources changed code may mention, going by its own trigrams
+    for file in changed.into_iter().flatten().filter(|f| is_code_file(f)) {
+        let Some(content) = sqlite.get_file_content(file)? else {
+            continue;
+        };
+        let trigrams = content_trigrams(&content);
+        for (index, (resource, _)) in resources.iter().enumerate() {
+            let mentioned = content_trigrams(&resource.name)
+                .iter()
+                .all(|t| trigrams.binary_search(t).is_ok());
+            if mentioned {
+                by_file.entry(file.clone()).or_default().insert(index);
+            }
+        }
+    }
+
+    let mut edges = EdgeSet::new(RESOURCE_RESOLUTION);
+    for (file, candidates) in by_file {
+        let Some(content) = sqlite.get_file_content(&file)? else {
+            continue;
+        };
+        let headers = sqlite.list_symbol_headers_by_file(&file, false)?;
+        for index in candidates {
+            let (resource, regex) = &resources[index];
+            for found in regex.find_iter(&content) {
+                let Some(from) = enclosing_symbol(&headers, found.start()) else {
+                    continue;
+                };
+                let line = content[..found.start()].matches('\n').count() as u32 + 1;
+                let edge_type = mention_edge_type(from, resource, &file, &content);
+                edges.add(
+                    &from.id,
+                    &resource.id,
+                    edge_type,
+                    &file,
+                    line,
+                    MENTION_CONFIDENCE,
+                );
+            }
+        }
+    }
+    link_operation_routes(sqlite, &touches, &mut edges)?;
+
+    let edges = edges.into_rows();
+    let files: Option<Vec<String>> = changed.map(|changed| changed.iter().cloned().collect());
+    let removed =
+        sqlite.replace_edges_by_resolution(RESOURCE_RESOLUTION, files.as_deref(), &edges)?;
+    Ok(edge_ends(&edges, removed))
+}
+
+/// The distinct ends of `edges` and of the `removed` ones.
+pub(super) fn edge_ends(edges: &[EdgeRow], removed: Vec<String>) -> Vec<String> {
+    let ends: BTreeSet<String> = edges
+        .iter()
+        .flat_map(|edge| [edge.from_symbol_id.clone(), edge.to_symbol_id.clone()])
+        .chain(removed)
+        .collect();
+    ends.into_iter().collect()
+}
+
+/// Edges of one resolution keyed by their ends and type, counting the evidence for
+/// each.
+pub(super) struct EdgeSet {
+    resolution: &'static str,
+    edges: HashMap<(String, String, &'static str), EdgeRow>,
+}
+
+impl EdgeSet {
+    pub(super) fn new(resolution: &'static str) -> Self {
+        Self {
+            resolution,
+            edges: HashMap::new(),
+        }
+    }
+
+    pub(super) fn add(
+        &mut self,
+        from: &str,
+        to: &str,
+        edge_type: &'static str,
+        file: &str,
+        line: u32,
+        confidence: f32,
+    ) {
+        self.edges
+            .entry((from.to_string(), to.to_string(), edge_type))
+            .and_modify(|edge| edge.evidence_count += 1)
+            .or_insert_with(|| EdgeRow {
+                from_symbol_id: from.to_string(),
+                to_symbol_id: to.to_string(),
+                edge_type: edge_type.to_string(),
+                at_file: Some(file.to_string()),
+                at_line: Some(line),
+                confidence,
+                evidence_count: 1,
+                resolution: self.resolution.to_string(),
+            });
+    }
+
+    pub(super) fn into_rows(self) -> Vec<EdgeRow> {
+        let mut rows: Vec<EdgeRow> = self.edges.into_values().collect();
+        rows.sort_by(|a, b| {
+            (&a.from_symbol_id, &a.to_symbol_id, &a.edge_type).cmp(&(
+                &b.from_symbol_id,
+                &b.to_symbol_id,
+                &b.edge_type,
+            ))
+        });
+        rows
+    }
+}
+
+/// The regex code mentions `resource` with, or `None` when it is not looked for.
+fn mention_pattern(resource: &SymbolHeaderRow) -> Option<String> {
+    let name = resource.name.as_str();
+    if name.len() < MIN_NAME_LEN {
+        return None;
+    }
+    let escaped = regex::escape(name);
+    match (resource.language.as_str(), resource.kind.as_str()) {
+        // Named in a statement, or quoted as query builders and ORMs take it
+        ("sql", "table") => Some(format!(
+            r#"(?i)(?:\b(?:from|join|into|update|table)\s+["`\[]?(?:\w+\.)?{escaped}\b|["'`]{escaped}["'`])"#
+        )),
+        // Only distinctive top-level keys, and only as string literals: `name` or
+        // `version` would match every file
+        (_, "config_key") if resource.qualified_name == name && is_distinctive_key(name) => {
+            Some(format!(r#"["'`]{escaped}["'`]"#))
+        }
+        ("protobuf", "message" | "enum" | "service") => Some(format!(r"\b{escaped}\b")),
+        // Generated code may lower the first letter (`getInvoice`). Operations
+        // without an `operationId` are named by method and path, which code does not
+        // spell out; routes find their handlers instead
+        ("protobuf", "rpc") | (_, "operation") if !name.contains(' ') => {
+            let lowered = regex::escape(&lower_first(name));
+            Some(format!(r"\b(?:{escaped}|{lowered})\b"))
+        }
+        _ => None,
+    }
+}
+
+/// What a mention of `resource` by `from`, in `file`, makes `from` of it.
+fn mention_edge_type(
+    from: &SymbolHeaderRow,
+    resource: &SymbolHeaderRow,
+    file: &str,
+    content: &str,
+) -> &'static str {
+    let is_contract = resource.language == "protobuf" || resource.kind == "operation";
+    if !is_contract {
+        return "reference";
+    }
+    if is_generated_stub(file) {
+        return GENERATED_FROM_EDGE;
+    }
+    let named_after = matches!(from.kind.as_str(), "function" | "method")
+        && (from.name == resource.name || from.name == lower_first(&resource.name));
+    let implements = match resource.kind.as_str() {
+        "operation" => named_after,
+        // A method named after an rpc, in code that deals with the rpc's service (by
+        // embedding, extending or registering its generated server)
+        "rpc" => {
+            named_after && service_name(resource).is_some_and(|service| content.contains(service))
+        }
+        _ => false,
+    };
+    if implements {
+        IMPLEMENTS_CONTRACT_EDGE
+    } else {
+        "reference"
+    }
+}
+
+/// Link the handlers of routes to the OpenAPI operations with the same method and
+/// path, for the routes where the route, its handler or an operation is in a file
+/// `touches` accepts.
+fn link_operation_routes(
+    sqlite: &SqliteStore,
+    touches: &dyn Fn(&str) -> bool,
+    edges: &mut EdgeSet,
+) -> Result<()> {
+    let (operations, routes): (Vec<FrameworkPatternRow>, Vec<FrameworkPatternRow>) = sqlite
+        .list_framework_patterns_by_kind("route")?
+        .into_iter()
+        .filter(|route| route.http_method.is_some() && route.path.is_some())
+        .partition(|route| route.framework == OPENAPI_FRAMEWORK);
+    if operations.is_empty() {
+        return Ok(());
+    }
+
+    let mut headers: HashMap<String, Vec<SymbolHeaderRow>> = HashMap::new();
+    for route in &routes {
+        let matching: Vec<&FrameworkPatternRow> = operations
+            .iter()
+            .filter(|operation| routes_match(operation, route))
+            .collect();
+        if matching.is_empty() {
+            continue;
+        }
+        // The handler's symbol, when the extractor found it, may be in another file
+        let handler_file = match route.symbol_id.as_deref() {
+            Some(id) => sqlite.get_symbol_by_id(id)?.map(|symbol| symbol.file_path),
+            None => None,
+        };
+        let touched = touches(&route.file_path)
+            || handler_file.as_deref().is_some_and(touches)
+            || matching
+                .iter()
+                .any(|operation| touches(&operation.file_path));
+        if !touched {
+            continue;
+        }
+        for file in matching
+            .iter()
+            .map(|operation| &operation.file_path)
+            .chain([&route.file_path])
+            .chain(handler_file.as_ref())
+        {
+            if !headers.contains_key(file) {
+                headers.insert(
+                    file.clone(),
+                    sqlite.list_symbol_headers_by_file(file, false)?,
+                );
+            }
+        }
+        let known = handler_file.as_ref().and_then(|file| {
+            headers[file]
+                .iter()
+                .find(|h| Some(h.id.as_str()) == route.symbol_id.as_deref())
+        });
+        let Some(handler) = known.or_else(|| route_handler(&headers[&route.file_path], route))
+        else {
+            continue;
+        };
+        for operation in matching {
+            let symbol = headers[&operation.file_path]
+                .iter()
+                .find(|h| h.kind == "operation" && h.start_line == operation.line);
+            if let Some(symbol) = symbol {
+                edges.add(
+                    &handler.id,
+                    &symbol.id,
+                    IMPLEMENTS_CONTRACT_EDGE,
+                    &route.file_path,
+                    route.line,
+                    ROUTE_CONFIDENCE,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `route` serves `operation`: the same method, and paths equal up to
+/// parameter names once the shorter one is aligned with the end of the longer, which
+/// leaves room for a server base path or a router mounted under a prefix.
+fn routes_match(operation: &FrameworkPatternRow, route: &FrameworkPatternRow) -> bool {
+    let (Some(operation_method), Some(route_method)) = (&operation.http_method, &route.http_method)
+    else {
+        return false;
+    };
+    if !route_method.eq_ignore_ascii_case(operation_method) && route_method != "ALL" {
+        return false;
+    }
+    let operation_path = path_segments(operation.path.as_deref().unwrap_or_default());
+    let route_path = path_segments(route.path.as_deref().unwrap_or_default());
+    let (shorter, longer) = if operation_path.len() <= route_path.len() {
+        (&operation_path, &route_path)
+    } else {
+        (&route_path, &operation_path)
+    };
+    shorter.iter().any(|segment| segment != "{}") && longer.ends_with(shorter)
+}
+
+/// The segments of a route template, with every parameter (`{id}`, `:id`, `<id>`,
+/// `<int:id>`, `*`) written `{}`.
+pub(super) fn path_segments(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            if segment.starts_with([':', '{', '<', '*']) {
+                "{}".to_string()
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect()
+}
+
+/// The symbol handling `route` when the extractor did not find it: the function it
+/// names when that is defined in the same file, or else the innermost symbol around
+/// it.
+pub(super) fn route_handler<'a>(
+    headers: &'a [SymbolHeaderRow],
+    route: &FrameworkPatternRow,
+) -> Option<&'a SymbolHeaderRow> {
+    let named = route
+        .handler
+        .as_deref()
+        .filter(|handler| *handler != "<anonymous>")
+        .map(|handler| handler.rsplit(['.', ':']).next().unwrap_or(handler));
+    if let Some(name) = named {
+        let defined = headers
+            .iter()
+            .find(|h| h.name == name && matches!(h.kind.as_str(), "function" | "method"));
+        if defined.is_some() {
+            return defined;
+        }
+    }
+    headers
+        .iter()
+        .filter(|h| h.kind != "file" && h.start_line <= route.line && route.line <= h.end_line)
+        .min_by_key(|h| h.end_byte - h.start_byte)
+        .or_else(|| headers.iter().find(|h| h.kind == "file"))
+}
+
+/// The service an rpc belongs to, from its qualified name.
+fn service_name(rpc: &SymbolHeaderRow) -> Option<&str> {
+    let mut segments = rpc.qualified_name.rsplit('.');
+    segments.next()?;
+    segments.next()
+}
+
+/// Files generated from protobuf or OpenAPI contracts, by the naming conventions of
+/// the common generators.
+fn is_generated_stub(file_path: &str) -> bool {
+    let path = file_path.replace('\\', "/");
+    let name = path.rsplit('/').next().unwrap_or(&path);
+    let stem = name.split('.').next().unwrap_or(name);
+    name.contains(".pb.")
+        || stem.ends_with("_pb2")
+        || stem.ends_with("_pb2_grpc")
+        || stem.ends_with("_pb")
+        || stem.ends_with("_grpc_pb")
+        || stem.ends_with("Grpc")
+        || path.contains("/generated/")
+        || path.contains("__generated__/")
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}", first.to_lowercase(), chars.as_str()),
+        None => String::new(),
+    }
+}
+
+/// Keys made of several words (`DATABASE_URL`, `max-connections`, `compilerOptions`).
+fn is_distinctive_key(name: &str) -> bool {
+    name.contains(['_', '-', '.'])
+        || name.chars().skip(1).any(|c| c.is_uppercase()) && name.chars().any(|c| c.is_lowercase())
+}
+
+fn is_code_file(file_path: &str) -> bool {
+    language_id_for_path(Path::new(file_path))
+        .is_some_and(|id| !is_resource_language(language_string(id)))
+}
+
+/// The innermost symbol other than the file containing `byte`, or else the file.
+fn enclosing_symbol(headers: &[SymbolHeaderRow], byte: usize) -> Option<&SymbolHeaderRow> {
+    headers
+        .iter()
+        .filter(|h| {
+            h.kind != "file" && (h.start_byte as usize) <= byte && byte < h.end_byte as usize
+        })
+        .min_by_key(|h| h.end_byte - h.start_byte)
+        .or_else(|| headers.iter().find(|h| h.kind == "file"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(language: &str, kind: &str, name: &str, qualified_name: &str) -> SymbolHeaderRow {
+        SymbolHeaderRow {
+            id: format!("{language}:{qualified_name}"),
+            file_path: "x".to_string(),
+            language: language.to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+            exported: true,
+            start_byte: 0,
+            end_byte: 1,
+            start_line: 1,
+            end_line: 1,
+            qualified_name: qualified_name.to_string(),
+            parent_id: None,
+        }
+    }
+
+    fn mentions(resource: &SymbolHeaderRow, text: &str) -> bool {
+        mention_pattern(resource).is_some_and(|p| Regex::new(&p).unwrap().is_match(text))
+    }
+
+    fn route(framework: &str, method: &str, path: &str) -> FrameworkPatternRow {
+        FrameworkPatternRow {
+            id: path.to_string(),
+            file_path: "x".to_string(),
+            line: 1,
+            framework: framework.to_string(),
+            kind: "route".to_string(),
+            http_method: Some(method.to_string()),
+            path: Some(path.to_string()),
+            local_path: Some(path.to_string()),
+            name: None,
+            handler: None,
+            symbol_id: None,
+            arguments: None,
+            parent_chain: None,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn mentions_follow_the_kind_of_resource() {
+        let table = resource("sql", "table", "invoices", "invoices");
+        assert!(mentions(
+            &table,
+            "SELECT * FROM public.invoices WHERE id = $1"
+        ));
+        assert!(mentions(&table, "db.table('invoices').insert(row)"));
+        assert!(!mentions(&table, "const invoices = await load();"));
+
+        let key = resource("yaml", "config_key", "DATABASE_URL", "DATABASE_URL");
+        assert!(mentions(&key, "process.env[\"DATABASE_URL\"]"));
+        assert!(!mentions(&key, "// DATABASE_URL is read at startup"));
+        let plain = resource("json", "config_key", "version", "version");
+        assert!(mention_pattern(&plain).is_none());
+        let nested = resource("yaml", "config_key", "max_retries", "http.max_retries");
+        assert!(mention_pattern(&nested).is_none());
+
+        let rpc = resource(
+            "protobuf",
+            "rpc",
+            "GetInvoice",
+            "acme.InvoiceService.GetInvoice",
+        );
+        assert!(mentions(&rpc, "client.getInvoice(req)"));
+        assert!(mentions(&rpc, "c.GetInvoice(ctx, req)"));
+        assert!(!mentions(&rpc, "client.getInvoices(req)"));
+
+        let operation = resource("yaml", "operation", "listUsers", "paths./users.listUsers");
+        assert!(mentions(&operation, "api.listUsers()"));
+        let unnamed = resource("yaml", "operation", "GET /users", "paths./users.GET /users");
+        assert!(mention_pattern(&unnamed).is_none());
+
+        let section = resource("markdown", "section", "Installation", "Installation");
+        assert!(mention_pattern(&section).is_none());
+    }
+
+    #[test]
+    fn contract_mentions_are_told_apart_by_where_they_occur() {
+        let rpc = resource(
+            "protobuf",
+            "rpc",
+            "GetInvoice",
+            "acme.InvoiceService.GetInvoice",
+        );
+        let method = resource("go", "method", "GetInvoice", "server.GetInvoice");
+        let caller = resource("go", "function", "main", "main");
+        let registers = "pb.RegisterInvoiceServiceServer(s, &server{})";
+
+        assert_eq!(
+            mention_edge_type(&caller, &rpc, "gen/billing_grpc.pb.go", ""),
+            GENERATED_FROM_EDGE
+        );
+        assert_eq!(
+            mention_edge_type(&method, &rpc, "server/main.go", registers),
+            IMPLEMENTS_CONTRACT_EDGE
+        );
+        assert_eq!(
+            mention_edge_type(&method, &rpc, "other/main.go", "unrelated"),
+            "reference"
+        );
+        assert_eq!(
+            mention_edge_type(&caller, &rpc, "server/main.go", registers),
+            "reference"
+        );
+        assert!(is_generated_stub("py/billing_pb2_grpc.py"));
+        assert!(is_generated_stub("java/InvoiceServiceGrpc.java"));
+        assert!(!is_generated_stub("src/grpc/server.ts"));
+    }
+
+    #[test]
+    fn routes_match_operations_up_to_parameter_names_and_prefixes() {
+        let operation = route(OPENAPI_FRAMEWORK, "GET", "/users/{id}");
+        assert!(routes_match(
+            &operation,
+            &route("express", "GET", "/users/:id")
+        ));
+        assert!(routes_match(
+            &operation,
+            &route("express", "GET", "/api/v1/users/:userId")
+        ));
+        assert!(routes_match(
+            &operation,
+            &route("flask", "ALL", "/users/<int:id>")
+        ));
+        assert!(!routes_match(
+            &operation,
+            &route("express", "POST", "/users/:id")
+        ));
+        assert!(!routes_match(
+            &operation,
+            &route("express", "GET", "/users")
+        ));
+        assert!(!routes_match(&operation, &route("express", "GET", "/:id")));
+    }
+}
@@ -0,0 +1,278 @@
+//! Routes carried across files.
+//!
+//! A router is often defined in one file and mounted in another: `app.use('/api/users',
+//! usersRouter)` with `usersRouter` imported from `./routes/users`, or
+//! `path("blog/", include("blog.urls"))` in a Django URLconf. The extractor only
+//! sees one file at a time, so it records each route with the prefixes known in its
+//! file (`local_path`) and each mount with the module the mounted router comes from.
+//! After each index run those mounts are followed from file to file and every route
+//! gets its full `path`.
+//!
+//! Views are imported the same way (`path("", views.index)`), so routes whose handler
+//! is not defined in their own file get its symbol from the module it is imported
+//! from.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::indexer::extract::routers::join_paths;
+use crate::storage::sqlite::{FrameworkPatternRow, SqliteStore};
+
+use super::utils::resolve_path;
+
+/// Frameworks whose routers are mounted across files.
+const ROUTER_FRAMEWORKS: &[&str] = &[
+    "express", "koa", "hono", "fastify", "fastapi", "flask", "django",
+];
+
+/// Extensions a module specifier may leave out.
+const MODULE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Most files a chain of mounts is followed through.
+const MAX_MOUNT_DEPTH: usize = 8;
+
+/// Most symbols of one name looked through for an imported handler.
+const MAX_HANDLER_CANDIDATES: usize = 50;
+
+/// Set the full path of every route from the prefixes its file is mounted under, and
+/// the symbol of handlers imported from other files. Returns the files whose patterns
+/// changed.
+pub fn resolve_mounted_routes(sqlite: &SqliteStore) -> Result<BTreeSet<String>> {
+    let mut patterns = sqlite.list_framework_patterns_by_kind("route")?;
+    patterns.extend(sqlite.list_framework_patterns_by_kind("mount")?);
+    patterns.retain(|p| ROUTER_FRAMEWORKS.contains(&p.framework.as_str()));
+
+    let files: HashSet<&str> = patterns.iter().map(|p| p.file_path.as_str()).collect();
+    // Mounted file to the file mounting it and the path it is mounted at
+    let mut mounted_in: BTreeMap<&str, (&str, &str)> = BTreeMap::new();
+    for mount in patterns.iter().filter(|p| p.kind == "mount") {
+        let Some(target) = mount
+            .arguments
+            .as_deref()
+            .and_then(|module| resolve_module(&mount.file_path, module, &files))
+        else {
+            continue;
+        };
+        if target != mount.file_path {
+            let path = mount.local_path.as_deref().unwrap_or_default();
+            mounted_in
+                .entry(target)
+                .or_insert((mount.file_path.as_str(), path));
+        }
+    }
+
+    let mut prefixes: HashMap<&str, String> = HashMap::new();
+    for &file in &files {
+        let mut prefix = String::new();
+        let mut current = file;
+        for _ in 0..MAX_MOUNT_DEPTH {
+            let Some(&(parent, path)) = mounted_in.get(current) else {
+                break;
+            };
+            prefix = join_paths(path, &prefix);
+            current = parent;
+        }
+        prefixes.insert(file, prefix);
+    }
+
+    let mut changed = BTreeSet::new();
+    let paths: Vec<(String, String)> = patterns
+        .iter()
+        .filter_map(|pattern| {
+            let full = full_path(pattern, &prefixes[pattern.file_path.as_str()])?;
+            let moved = pattern.path.as_deref() != Some(full.as_str());
+            if moved {
+                changed.insert(pattern.file_path.clone());
+            }
+            moved.then(|| (pattern.id.clone(), full))
+        })
+        .collect();
+    if !paths.is_empty() {
+        sqlite.update_framework_pattern_paths(&paths)?;
+    }
+
+    // Imported handlers are looked up again each run, as their module may have changed
+    let mut symbols = Vec::new();
+    for route in patterns.iter().filter(|p| p.kind == "route") {
+        if let Some(symbol_id) = imported_handler(sqlite, route)? {
+            if route.symbol_id.as_ref() != Some(&symbol_id) {
+                changed.insert(route.file_path.clone());
+                symbols.push((route.id.clone(), symbol_id));
+            }
+        }
+    }
+    if !symbols.is_empty() {
+        sqlite.update_framework_pattern_symbols(&symbols)?;
+    }
+    Ok(changed)
+}
+
+fn full_path(pattern: &FrameworkPatternRow, prefix: &str) -> Option<String> {
+    let local = pattern.local_path.as_deref()?;
+    Some(if prefix.is_empty() {
+        local.to_string()
+    } else {
+        join_paths(prefix, local)
+    })
+}
+
+/// The symbol of a route's handler in the module it is imported from.
+fn imported_handler(sqlite: &SqliteStore, route: &FrameworkPatternRow) -> Result<Option<String>> {
+    let (Some(handler), Some(module)) = (route.handler.as_deref(), route.arguments.as_deref())
+    else {
+        return Ok(None);
+    };
+    let name = handler.rsplit('.').next().unwrap_or(handler);
+    let candidates: Vec<_> = sqlite
+        .search_symbols_by_exact_name(name, None, MAX_HANDLER_CANDIDATES)?
+        .into_iter()
+        .filter(|s| matches!(s.kind.as_str(), "function" | "method" | "class"))
+        .collect();
+    let files: HashSet<&str> = candidates.iter().map(|s| s.file_path.as_str()).collect();
+    let Some(file) = resolve_module(&route.file_path, module, &files) else {
+        return Ok(None);
+    };
+    Ok(candidates
+        .iter()
+        .find(|s| s.file_path == file)
+        .map(|s| s.id.clone()))
+}
+
+/// The file among `files` a module specifier of `from` names: a relative
+/// JavaScript specifier, or a Python module, relative (`.views`) or absolute
+/// (`blog.urls`, found under any source root).
+fn resolve_module<'a>(from: &str, module: &str, files: &HashSet<&'a str>) -> Option<&'a str> {
+    if from.ends_with(".py") {
+        let absolute = !module.starts_with('.');
+        return python_module_paths(from, module)
+            .into_iter()
+            .find_map(|candidate| {
+                if let Some(file) = files.get(candidate.as_str()) {
+                    return Some(*file);
+                }
+                let suffix = format!("/{candidate}");
+                files
+                    .iter()
+                    .filter(|file| absolute && file.ends_with(&suffix))
+                    .min()
+                    .copied()
+            });
+    }
+
+    // `resolve_path` assumes TypeScript when the specifier has no extension
+    let resolved = resolve_path(from, module)?;
+    let base = match module.rsplit('/').next() {
+        Some(last) if last.ends_with(".ts") || last.ends_with(".tsx") => resolved.clone(),
+        _ => resolved
+            .strip_suffix(".ts")
+            .unwrap_or(&resolved)
+            .to_string(),
+    };
+    let stem = MODULE_EXTENSIONS
+        .iter()
+        .find_map(|ext| base.strip_suffix(&format!(".{ext}")))
+        .unwrap_or(&base);
+    std::iter::once(base.clone())
+        .chain(MODULE_EXTENSIONS.iter().map(|ext| format!("{stem}.{ext}")))
+        .chain(
+            MODULE_EXTENSIONS
+                .iter()
+                .map(|ext| format!("{base}/index.{ext}")),
+        )
+        .find_map(|candidate| files.get(candidate.as_str()).copied())
+}
+
+/// Files a Python module may be, most specific first. What `from a import b` binds
+/// may be the submodule `a.b` or an attribute of `a`, so the parent module is tried
+/// after the module itself.
+fn python_module_paths(from: &str, module: &str) -> Vec<String> {
+    let relative = module.len() - module.trim_start_matches('.').len();
+    let mut segments: Vec<&str> = Vec::new();
+    if relative > 0 {
+        segments.extend(from.split('/'));
+        // The importing file, then one package up per extra dot
+        for _ in 0..relative {
+            segments.pop();
+        }
+    }
+    let base_len = segments.len();
+    segments.extend(module[relative..].split('.').filter(|s| !s.is_empty()));
+
+    let mut paths = Vec::new();
+    for end in (base_len..=segments.len()).rev().take(2) {
+        if end == 0 {
+            continue;
+        }
+        let base = segments[..end].join("/");
+        paths.push(format!("{base}.py"));
+        paths.push(format!("{base}/__init__.py"));
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_module_specifiers_to_indexed_files() {
+        let files: HashSet<&str> = [
+            "src/routes/users.js",
+            "src/routes/admin/index.ts",
+            "src/app.ts",
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            resolve_module("src/app.ts", "./routes/users", &files),
+            Some("src/routes/users.js")
+        );
+        assert_eq!(
+            resolve_module("src/app.ts", "./routes/users.js", &files),
+            Some("src/routes/users.js")
+        );
+        assert_eq!(
+            resolve_module("src/app.ts", "./routes/admin", &files),
+            Some("src/routes/admin/index.ts")
+        );
+        assert_eq!(
+            resolve_module("src/routes/users.js", "../app", &files),
+            Some("src/app.ts")
+        );
+        assert_eq!(resolve_module("src/app.ts", "./missing", &files), None);
+    }
+
+    #[test]
+    fn resolves_python_modules_to_indexed_files() {
+        let files: HashSet<&str> = [
+            "site/blog/urls.py",
+            "site/blog/views.py",
+            "site/api/routers/__init__.py",
+            "site/api/routers/users.py",
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            resolve_module("site/mysite/urls.py", "blog.urls", &files),
+            Some("site/blog/urls.py")
+        );
+        assert_eq!(
+            resolve_module("site/blog/urls.py", ".views", &files),
+            Some("site/blog/views.py")
+        );
+        assert_eq!(
+            resolve_module("site/blog/urls.py", ".views.ArchiveView", &files),
+            Some("site/blog/views.py")
+        );
+        assert_eq!(
+            resolve_module("site/api/main.py", ".routers.users", &files),
+            Some("site/api/routers/users.py")
+        );
+        assert_eq!(
+            resolve_module("site/api/routers/users.py", "..routers", &files),
+            Some("site/api/routers/__init__.py")
+        );
+        assert_eq!(resolve_module("site/blog/urls.py", ".models", &files), None);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::Config;
+use crate::indexer::package::detector::is_dotnet_obj_dir;
 use crate::indexer::parser::{language_id_for_path, LanguageId};
 use anyhow::Result;
 use std::{
@@ -59,14 +60,25 @@ pub fn should_skip_dir(config: &Config, path: &Path) -> bool {
     if name == ".git" || name == "dist" || name == "build" || name == "target" {
         return true;
     }
+    // .NET intermediate output, including generated .cs files
+    if is_dotnet_obj_dir(path) {
+        return true;
+    }
     if !config.index_node_modules && name == "node_modules" {
         return true;
     }
+    // The index's own storage when kept inside the repo; its metadata is JSON
+    if path == config.tantivy_index_path.as_std_path()
+        || path == config.vector_db_path.as_std_path()
+        || path == config.snapshot_dir().as_std_path()
+    {
+        return true;
+    }
     false
 }
 
 pub fn should_index_file(config: &Config, path: &Path) -> bool {
-    if is_excluded(config, path) {
+    if is_excluded(config, path) || is_lock_file(path) {
         return false;
     }
     matches!(
@@ -81,10 +93,28 @@ pub fn should_index_file(config: &Config, path: &Path) -> bool {
                 | LanguageId::Javascript
                 | LanguageId::C
                 | LanguageId::Cpp
+                | LanguageId::Ruby
+                | LanguageId::CSharp
+                | LanguageId::Kotlin
+                | LanguageId::Scala
+                | LanguageId::Markdown
+                | LanguageId::Yaml
+                | LanguageId::Toml
+                | LanguageId::Json
+                | LanguageId::Sql
+                | LanguageId::Protobuf
         )
     )
 }
 
+/// Generated dependency lock files (`package-lock.json`, `pnpm-lock.yaml`), which are
+/// large and say nothing about the code.
+fn is_lock_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|name| name.ends_with("-lock.json") || name.ends_with("-lock.yaml"))
+}
+
 fn is_excluded(config: &Config, path: &Path) -> bool {
     let s = path.to_string_lossy().replace('\\', "/");
     if !config.index_node_modules && s.contains("/node_modules/") {
@@ -6,4 +6,6 @@ pub struct IndexRunStats {
     pub files_skipped: usize,
     pub files_unchanged: usize,
     pub files_deleted: usize,
+    /// Files whose edges were re-resolved because a file they depend on changed
+    pub files_reresolved: usize,
 }
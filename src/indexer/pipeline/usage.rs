@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use crate::indexer::extract::symbol::Import;
 use crate::storage::sqlite::{SymbolRow, UsageExampleRow};
 
+use super::edges::is_resource_language;
 use super::parsing::{extract_callee_names, extract_identifiers, extract_usage_line, trim_snippet};
 use super::utils::{build_import_map, resolve_imported_symbol_id};
 
@@ -19,7 +20,10 @@ pub fn extract_usage_examples_for_file(
     // Map import alias/name to Import struct
     let import_map = build_import_map(imports);
 
-    for row in symbol_rows {
+    for row in symbol_rows
+        .iter()
+        .filter(|row| !is_resource_language(&row.language))
+    {
         for callee in extract_callee_names(&row.text) {
             let to_id = if let Some(local_id) = name_to_id.get(&callee) {
                 if local_id == &row.id {
@@ -1,7 +1,7 @@
 use crate::config::Config;
-use crate::indexer::extract::symbol::Import;
+use crate::indexer::extract::symbol::{DecoratorEntry, Import, JSDocEntry};
 use crate::indexer::parser::LanguageId;
-use crate::storage::sqlite::SqliteStore;
+use crate::storage::sqlite::{SqliteStore, SymbolRow};
 use anyhow::{Context, Result};
 use std::{
     collections::HashMap,
@@ -53,6 +53,17 @@ pub fn file_key_path(config: &Config, path: &Path) -> String {
     file_key(config, &utf8_path)
 }
 
+/// Whether the file key `path` equals `prefix` or lives below it (`prefix` being a
+/// file or directory key, as produced by [`file_key`]).
+pub fn is_same_or_descendant(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    prefix == "."
+        || path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
 pub fn fnv1a_64(data: &[u8]) -> u64 {
     const OFFSET: u64 = 0xcbf29ce484222325;
     const PRIME: u64 = 0x00000100000001b3;
@@ -74,6 +85,63 @@ pub fn stable_symbol_id(file_path: &str, name: &str, start_byte: u32) -> String
     format!("{:016x}", fnv1a_64(&data))
 }
 
+/// Extractors key docstrings and decorators by a provisional `...:<start line>:<name>`
+/// id since they don't know the stored ids; point them at the indexed symbols. Entries
+/// whose symbol was not indexed are dropped.
+pub fn assign_extracted_symbol_ids(
+    jsdoc_entries: &mut Vec<JSDocEntry>,
+    decorators: &mut Vec<DecoratorEntry>,
+    symbol_rows: &[SymbolRow],
+) {
+    let by_position: HashMap<(u32, &str), &str> = symbol_rows
+        .iter()
+        .map(|r| ((r.start_line, r.name.as_str()), r.id.as_str()))
+        .collect();
+    let resolve = |provisional: &mut String| {
+        let mut parts = provisional.rsplitn(3, ':');
+        let name = parts.next();
+        let line = parts.next().and_then(|l| l.parse::<u32>().ok());
+        let id = match (line, name) {
+            (Some(line), Some(name)) => by_position.get(&(line, name)).copied(),
+            _ => None,
+        };
+        match id {
+            Some(id) => {
+                *provisional = id.to_string();
+                true
+            }
+            None => false,
+        }
+    };
+    jsdoc_entries.retain_mut(|e| resolve(&mut e.symbol_id));
+    decorators.retain_mut(|d| resolve(&mut d.symbol_id));
+}
+
+/// The indexed symbol a framework pattern's handler names in its own file:
+/// `UsersController.create` by qualified name, with or without the file's package or
+/// module, `read_item` by name. Handlers qualified by something else, such as an
+/// imported module, are not looked up here.
+pub fn handler_symbol_id(handler: Option<&str>, symbol_rows: &[SymbolRow]) -> Option<String> {
+    let handler = handler?;
+    let is_handler =
+        |row: &&SymbolRow| matches!(row.kind.as_str(), "function" | "method" | "class");
+    let qualified_suffix = format!(".{handler}");
+    symbol_rows
+        .iter()
+        .filter(is_handler)
+        .find(|row| {
+            row.qualified_name == handler
+                || (handler.contains('.') && row.qualified_name.ends_with(&qualified_suffix))
+        })
+        .or_else(|| {
+            symbol_rows
+                .iter()
+                .filter(is_handler)
+                .find(|row| !handler.contains('.') && row.name == handler)
+        })
+        .map(|row| row.id.clone())
+}
+
 pub fn language_string(language_id: LanguageId) -> &'static str {
     match language_id {
         LanguageId::Typescript => "typescript",
@@ -85,6 +153,16 @@ pub fn language_string(language_id: LanguageId) -> &'static str {
         LanguageId::Javascript => "javascript",
         LanguageId::C => "c",
         LanguageId::Cpp => "cpp",
+        LanguageId::Ruby => "ruby",
+        LanguageId::CSharp => "csharp",
+        LanguageId::Kotlin => "kotlin",
+        LanguageId::Scala => "scala",
+        LanguageId::Markdown => "markdown",
+        LanguageId::Yaml => "yaml",
+        LanguageId::Toml => "toml",
+        LanguageId::Json => "json",
+        LanguageId::Sql => "sql",
+        LanguageId::Protobuf => "protobuf",
     }
 }
 
@@ -108,7 +186,8 @@ pub fn resolve_imported_symbol_id(current_file_path: &str, imp: &Import) -> Opti
     // For now, we use the file-level ID as fallback since we don't have SqliteStore access here
     // TODO: Pass SqliteStore when available for symbol-level lookup
 
-    // The ID is stable_symbol_id(target_path, imp.name, 0) for exported symbols
+    // A top-level symbol whose name is unique in its file has the ID
+    // stable_symbol_id(target_path, imp.name, 0) (see `identity`)
     Some(stable_symbol_id(&target_path, &imp.name, 0))
 }
 
@@ -155,7 +234,7 @@ pub fn resolve_imported_symbol_id_with_db(
 }
 
 /// Generate alternative import paths to try when the default resolution fails
-fn alternative_import_paths(base_path: &str) -> Vec<String> {
+pub fn alternative_import_paths(base_path: &str) -> Vec<String> {
     let mut alternatives = Vec::new();
 
     // If path ends with .ts, try .tsx
@@ -218,7 +297,7 @@ pub fn resolve_path(current: &str, source: &str) -> Option<String> {
     let mut s = stack.join("/");
 
     // Quick hack: just append .ts if missing extension
-    if !s.ends_with(".ts") && !s.ends_with(".tsx") && !s.ends_with(".rs") {
+    if !s.ends_with(".ts") && !s.ends_with(".tsx") && !s.ends_with(".rs") && !s.ends_with(".rb") && !s.ends_with(".cs") {
         s.push_str(".ts"); // Bias towards TS
     }
 
@@ -253,6 +332,16 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn is_same_or_descendant_matches_whole_components() {
+        assert!(is_same_or_descendant("src/a.ts", "src/a.ts"));
+        assert!(is_same_or_descendant("src/feature/a.ts", "src/feature"));
+        assert!(is_same_or_descendant("src/feature/a.ts", "src/feature/"));
+        assert!(is_same_or_descendant("src/a.ts", "."));
+        assert!(!is_same_or_descendant("src/feature2/a.ts", "src/feature"));
+        assert!(!is_same_or_descendant("src/a.tsx", "src/a.ts"));
+    }
+
     #[test]
     fn file_key_is_relative_under_base_and_absolute_outside() {
         let base0 = tmp_dir();
@@ -292,11 +381,14 @@ mod tests {
             rank_test_penalty: 0.0,
             rank_popularity_weight: 0.0,
             rank_popularity_cap: 0,
+            rank_history_weight: 0.0,
+            history_max_commits: 0,
             index_patterns: vec![],
             exclude_patterns: vec![],
             watch_mode: false,
             watch_debounce_ms: 100,
             watch_min_index_interval_ms: 50,
+            watch_backend: crate::config::WatchBackend::Auto,
             max_context_bytes: 10_000,
             index_node_modules: false,
             repo_roots: vec![base_utf8.clone()],
@@ -336,6 +428,9 @@ mod tests {
             metrics_enabled: true,
             metrics_port: 9090,
             package_detection_enabled: true,
+            mcp_transport: crate::config::McpTransport::Stdio,
+            http_bind_addr: "127.0.0.1:8765".to_string(),
+            http_auth_token: None,
         };
 
         let k1 = file_key_path(&config, &inner);
@@ -371,6 +466,8 @@ mod tests {
             start_line: 1,
             end_line: 10,
             text: "export function helper() {}".to_string(),
+            qualified_name: "helper".to_string(),
+            parent_id: None,
         };
         sqlite.upsert_symbol(&target_symbol).unwrap();
 
@@ -527,7 +624,7 @@ mod utils_proptest {
             start_byte in start_byte_strategy(),
         ) {
             // Single-bit change in name should produce different ID
-            if name.len() > 0 {
+            if !name.is_empty() {
                 let mut modified_name = name.clone();
                 modified_name.pop();
                 if let Some(c) = modified_name.chars().last() {
@@ -610,7 +707,7 @@ mod utils_proptest {
                 let seeded_idx = (seed.wrapping_add(i as u64)) as usize;
 
                 // Generate file path from seed
-                let depth = (seeded_idx % 4) as usize;
+                let depth = seeded_idx % 4;
                 let dirs: Vec<_> = (0..depth).map(|d| format!("dir{}", (seeded_idx + d) % 100)).collect();
                 let file_path = if dirs.is_empty() {
                     format!("file{}.rs", (seeded_idx % 50))
@@ -0,0 +1,546 @@
+//! Event-driven file watching for watch mode.
+//!
+//! Instead of re-walking every repo root on a timer, [`EventWatcher`] subscribes to
+//! filesystem notifications (inotify on Linux, FSEvents on macOS) and coalesces them
+//! into a [`ChangeSet`] of touched paths. The pipeline then re-indexes only those
+//! paths via `IndexPipeline::index_paths`, which scans directories that appeared,
+//! indexes files that changed and purges paths that no longer exist (deletes, the
+//! source side of renames and directory moves).
+
+use crate::config::Config;
+use crate::indexer::package::detector::is_manifest_filename;
+use crate::indexer::parser::language_id_for_path;
+use crate::indexer::pipeline::scan::{should_index_file, should_skip_dir};
+use anyhow::{Context, Result};
+use notify::event::{EventKind, ModifyKind};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, timeout_at, Instant};
+
+/// A coalesced batch of filesystem changes.
+#[derive(Debug, Default, Clone)]
+pub struct ChangeSet {
+    /// Paths that were created, modified, removed or renamed (either side).
+    pub paths: BTreeSet<PathBuf>,
+    /// Set when the backend dropped events (e.g. inotify queue overflow), in which
+    /// case only a full re-index is guaranteed to be consistent.
+    pub needs_rescan: bool,
+}
+
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && !self.needs_rescan
+    }
+
+    /// Fold another batch into this one.
+    pub fn merge(&mut self, other: ChangeSet) {
+        self.paths.extend(other.paths);
+        self.needs_rescan |= other.needs_rescan;
+    }
+
+    /// Fold a single notification into the change set.
+    pub fn record(&mut self, config: &Config, event: &Event) {
+        if event.need_rescan() {
+            self.needs_rescan = true;
+        }
+
+        match event.kind {
+            // Reads and permission/timestamp changes never alter indexed content
+            EventKind::Access(_) | EventKind::Modify(ModifyKind::Metadata(_)) => return,
+            _ => {}
+        }
+
+        for path in &event.paths {
+            if is_relevant_path(config, path) {
+                self.paths.insert(path.clone());
+            }
+        }
+    }
+}
+
+/// Decide whether a path reported by the watcher can affect the index.
+///
+/// Package manifests always count, since they change package detection. Other
+/// existing files must pass `should_index_file`. Directories are kept so that moved-in
+/// trees get scanned. Missing paths are kept unless their extension shows they were
+/// never indexable (editor swap files, build artifacts), so deletes and moved-out
+/// directories can be purged.
+pub fn is_relevant_path(config: &Config, path: &Path) -> bool {
+    if is_storage_path(config, path) {
+        return false;
+    }
+    // Only directories below the repo root count; the root itself may live under `build/`
+    let root = config
+        .repo_roots
+        .iter()
+        .map(|root| root.as_std_path())
+        .find(|root| path.starts_with(root));
+    if path
+        .ancestors()
+        .take_while(|p| root.is_none_or(|root| p != &root))
+        .any(|p| should_skip_dir(config, p))
+    {
+        return false;
+    }
+
+    if path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(is_manifest_filename)
+    {
+        return true;
+    }
+
+    match fs::metadata(path) {
+        Ok(meta) if meta.is_file() => should_index_file(config, path),
+        Ok(meta) => meta.is_dir(),
+        Err(_) => path.extension().is_none() || language_id_for_path(path).is_some(),
+    }
+}
+
+/// Index files live under BASE_DIR when configured locally; their churn must not
+/// trigger index runs.
+fn is_storage_path(config: &Config, path: &Path) -> bool {
+    let db = config.db_path.as_std_path();
+    let db_sidecar = path
+        .to_str()
+        .zip(db.to_str())
+        .is_some_and(|(p, d)| p.starts_with(d));
+
+    db_sidecar
+        || path.starts_with(config.tantivy_index_path.as_std_path())
+        || path.starts_with(config.vector_db_path.as_std_path())
+        || path.starts_with(config.snapshot_dir().as_std_path())
+}
+
+/// Filesystem event source backed by the platform's native notification API.
+pub struct EventWatcher {
+    config: Arc<Config>,
+    watcher: RecommendedWatcher,
+    rx: mpsc::UnboundedReceiver<notify::Result<Event>>,
+}
+
+impl EventWatcher {
+    /// Register watches on every repo root.
+    ///
+    /// Directories are watched non-recursively one by one so that skipped trees
+    /// (`node_modules`, `target`, `.git`, ...) never consume watch descriptors.
+    pub fn start(config: Arc<Config>) -> Result<Self> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })
+        .context("Failed to create filesystem watcher")?;
+
+        let mut this = Self {
+            config,
+            watcher,
+            rx,
+        };
+
+        let roots = this.config.repo_roots.clone();
+        for root in &roots {
+            let watched = this
+                .watch_tree(root.as_std_path())
+                .with_context(|| format!("Failed to watch repo root: {root}"))?;
+            tracing::debug!(root = %root, directories = watched, "Registered filesystem watches");
+        }
+
+        Ok(this)
+    }
+
+    /// Hand coalesced batches to `index` until the event stream closes.
+    ///
+    /// Runs are at least `min_index_interval` apart; events arriving in between are
+    /// merged into the pending batch. A failed batch is retried after an exponential
+    /// backoff, together with whatever changed while backing off.
+    pub async fn run_index_loop<F, Fut>(
+        &mut self,
+        quiet: Duration,
+        min_index_interval: Duration,
+        repo_name: &str,
+        mut index: F,
+    ) where
+        F: FnMut(ChangeSet) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let max_backoff_ms = 5000; // Max 5 seconds backoff
+        let mut consecutive_failures = 0;
+        let mut last_index_time: Option<Instant> = None;
+        let mut failed: Option<ChangeSet> = None;
+
+        loop {
+            let mut changes = match failed.take() {
+                Some(mut changes) => {
+                    self.collect_for(&mut changes, Duration::ZERO).await;
+                    changes
+                }
+                None => match self.next_change_set(quiet).await {
+                    Some(changes) => changes,
+                    None => break,
+                },
+            };
+
+            // Rate limiting: hold the batch back and keep merging events into it
+            if let Some(last_time) = last_index_time {
+                let elapsed = last_time.elapsed();
+                if elapsed < min_index_interval {
+                    self.collect_for(&mut changes, min_index_interval - elapsed)
+                        .await;
+                }
+            }
+
+            tracing::info!(
+                repo = %repo_name,
+                paths = changes.paths.len(),
+                rescan = changes.needs_rescan,
+                "Changes detected, starting index run"
+            );
+
+            match index(changes.clone()).await {
+                Ok(()) => {
+                    last_index_time = Some(Instant::now());
+                    consecutive_failures = 0;
+                }
+                Err(err) => {
+                    consecutive_failures += 1;
+                    let backoff_ms = (quiet.as_millis() as u64
+                        * (1 << consecutive_failures.min(8)))
+                    .min(max_backoff_ms);
+                    tracing::warn!(
+                        repo = %repo_name,
+                        error = %err,
+                        consecutive_failures = consecutive_failures,
+                        backoff_ms = backoff_ms,
+                        "Watch index run failed, backing off"
+                    );
+                    tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+                    failed = Some(changes);
+                }
+            }
+        }
+    }
+
+    /// Wait for the next batch of changes.
+    ///
+    /// Blocks until the first relevant event arrives, then keeps collecting until no
+    /// event has been seen for `quiet`. Returns `None` once the event stream has
+    /// closed, which means the backend is gone and the caller should fall back.
+    pub async fn next_change_set(&mut self, quiet: Duration) -> Option<ChangeSet> {
+        let mut changes = ChangeSet::default();
+        loop {
+            let first = self.rx.recv().await?;
+            self.record(&mut changes, first);
+            self.collect_until_quiet(&mut changes, quiet).await;
+            if !changes.is_empty() {
+                self.watch_new_directories(&changes);
+                return Some(changes);
+            }
+        }
+    }
+
+    /// Keep folding events into `changes` for a fixed amount of time.
+    ///
+    /// Used for rate limiting: events arriving while a run is held back are merged
+    /// into the pending batch instead of being dropped.
+    pub async fn collect_for(&mut self, changes: &mut ChangeSet, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        while let Ok(Some(res)) = timeout_at(deadline, self.rx.recv()).await {
+            self.record(changes, res);
+        }
+        self.watch_new_directories(changes);
+    }
+
+    async fn collect_until_quiet(&mut self, changes: &mut ChangeSet, quiet: Duration) {
+        while let Ok(Some(res)) = timeout(quiet, self.rx.recv()).await {
+            self.record(changes, res);
+        }
+    }
+
+    fn record(&self, changes: &mut ChangeSet, res: notify::Result<Event>) {
+        match res {
+            Ok(event) => changes.record(&self.config, &event),
+            Err(err) => {
+                tracing::warn!(error = %err, "Filesystem watcher error, scheduling full rescan");
+                changes.needs_rescan = true;
+            }
+        }
+    }
+
+    /// Directories created or moved into the tree need their own watches.
+    fn watch_new_directories(&mut self, changes: &ChangeSet) {
+        for path in &changes.paths {
+            if path.is_dir() {
+                if let Err(err) = self.watch_tree(path) {
+                    tracing::warn!(
+                        dir = %path.display(),
+                        error = %err,
+                        "Failed to watch new directory"
+                    );
+                }
+            }
+        }
+    }
+
+    fn watch_tree(&mut self, root: &Path) -> Result<usize> {
+        let mut count = 0;
+        let mut stack = vec![root.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            if is_storage_path(&self.config, &dir) {
+                continue;
+            }
+            self.watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Failed to watch directory: {}", dir.display()))?;
+            count += 1;
+
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if entry.file_type().is_ok_and(|ft| ft.is_dir()) && !should_skip_dir(&self.config, &path)
+                {
+                    stack.push(path);
+                }
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::pipeline::scan::scan_files;
+    use notify::event::{CreateKind, DataChange, RemoveKind, RenameMode};
+
+    fn test_config(base: &Path) -> Config {
+        let base = crate::path::Utf8PathBuf::from_path_buf(base.to_path_buf()).unwrap();
+        Config {
+            base_dir: base.clone(),
+            db_path: base.join(".cimcp/code-intelligence.db"),
+            vector_db_path: base.join(".cimcp/vectors"),
+            tantivy_index_path: base.join(".cimcp/tantivy-index"),
+            embeddings_backend: crate::config::EmbeddingsBackend::Hash,
+            embeddings_model_dir: None,
+            embeddings_model_url: None,
+            embeddings_model_sha256: None,
+            embeddings_auto_download: false,
+            embeddings_model_repo: None,
+            embeddings_model_revision: None,
+            embeddings_model_hf_token: None,
+            embeddings_device: crate::config::EmbeddingsDevice::Cpu,
+            embedding_batch_size: 32,
+            hash_embedding_dim: 64,
+            vector_search_limit: 20,
+            hybrid_alpha: 0.7,
+            rank_vector_weight: 0.7,
+            rank_keyword_weight: 0.3,
+            rank_exported_boost: 0.1,
+            rank_index_file_boost: 0.05,
+            rank_test_penalty: 0.1,
+            rank_popularity_weight: 0.05,
+            rank_popularity_cap: 50,
+            rank_history_weight: 0.05,
+            history_max_commits: 1000,
+            index_patterns: vec!["**/*.ts".to_string()],
+            exclude_patterns: vec!["**/node_modules/**".to_string()],
+            watch_mode: false,
+            watch_debounce_ms: 250,
+            watch_min_index_interval_ms: 5000,
+            watch_backend: crate::config::WatchBackend::Auto,
+            max_context_bytes: 200_000,
+            index_node_modules: false,
+            repo_roots: vec![base],
+            reranker_model_path: None,
+            reranker_top_k: 20,
+            reranker_cache_dir: None,
+            learning_enabled: false,
+            learning_selection_boost: 0.1,
+            learning_file_affinity_boost: 0.05,
+            max_context_tokens: 8192,
+            token_encoding: "o200k_base".to_string(),
+            parallel_workers: 1,
+            embedding_cache_enabled: true,
+            embedding_max_threads: 0,
+            pagerank_damping: 0.85,
+            pagerank_iterations: 20,
+            synonym_expansion_enabled: true,
+            acronym_expansion_enabled: true,
+            rrf_enabled: true,
+            rrf_k: 60.0,
+            rrf_keyword_weight: 1.0,
+            rrf_vector_weight: 1.0,
+            rrf_graph_weight: 0.5,
+            hyde_enabled: false,
+            hyde_llm_backend: "openai".to_string(),
+            hyde_api_key: None,
+            hyde_max_tokens: 512,
+            metrics_enabled: true,
+            metrics_port: 9090,
+            package_detection_enabled: true,
+            mcp_transport: crate::config::McpTransport::Stdio,
+            http_bind_addr: "127.0.0.1:8765".to_string(),
+            http_auth_token: None,
+        }
+    }
+
+    fn event(kind: EventKind, paths: &[&Path]) -> Event {
+        let mut ev = Event::new(kind);
+        for p in paths {
+            ev = ev.add_path(p.to_path_buf());
+        }
+        ev
+    }
+
+    #[test]
+    fn change_set_coalesces_events_and_keeps_rename_sides() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(dir.path());
+        let kept = dir.path().join("a.ts");
+        let renamed_to = dir.path().join("b.ts");
+        std::fs::write(&kept, "export const a = 1;").unwrap();
+        std::fs::write(&renamed_to, "export const b = 1;").unwrap();
+        let renamed_from = dir.path().join("old.ts");
+
+        let mut changes = ChangeSet::default();
+        changes.record(&config, &event(EventKind::Create(CreateKind::File), &[&kept]));
+        changes.record(
+            &config,
+            &event(EventKind::Modify(ModifyKind::Data(DataChange::Content)), &[&kept]),
+        );
+        changes.record(
+            &config,
+            &event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &[&renamed_from, &renamed_to],
+            ),
+        );
+
+        assert!(!changes.needs_rescan);
+        assert_eq!(
+            changes.paths.into_iter().collect::<Vec<_>>(),
+            vec![kept, renamed_to, renamed_from]
+        );
+    }
+
+    #[test]
+    fn change_set_ignores_irrelevant_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(dir.path());
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, "hello").unwrap();
+        let git_file = dir.path().join(".git").join("index");
+        let node_file = dir.path().join("node_modules").join("x").join("index.ts");
+        let swap_file = dir.path().join(".a.ts.swp");
+
+        let mut changes = ChangeSet::default();
+        changes.record(&config, &event(EventKind::Create(CreateKind::File), &[&notes]));
+        changes.record(&config, &event(EventKind::Remove(RemoveKind::File), &[&git_file]));
+        changes.record(&config, &event(EventKind::Remove(RemoveKind::File), &[&node_file]));
+        changes.record(&config, &event(EventKind::Remove(RemoveKind::File), &[&swap_file]));
+
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn change_set_keeps_manifests_that_are_not_indexed() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(dir.path());
+        let manifest = dir.path().join("go.mod");
+        std::fs::write(&manifest, "module example.com/shop\n").unwrap();
+
+        let mut changes = ChangeSet::default();
+        changes.record(
+            &config,
+            &event(EventKind::Create(CreateKind::File), &[&manifest]),
+        );
+
+        assert!(changes.paths.contains(&manifest));
+    }
+
+    #[test]
+    fn obj_dirs_hold_sources_unless_next_to_a_csproj() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(dir.path());
+        config.index_patterns.push("**/*.cs".to_string());
+        let source = dir.path().join("renderer/obj/mesh.ts");
+        let generated = dir.path().join("Billing/obj/AssemblyInfo.cs");
+        for file in [&source, &generated] {
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, "// contents\n").unwrap();
+        }
+        std::fs::write(dir.path().join("Billing/Billing.csproj"), "<Project />").unwrap();
+
+        let scanned = scan_files(&config, dir.path()).unwrap();
+        assert_eq!(scanned, vec![source.clone()]);
+        assert!(is_relevant_path(&config, &source));
+        assert!(!is_relevant_path(&config, &generated));
+    }
+
+    #[tokio::test]
+    async fn failed_run_is_retried_with_changes_from_the_backoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a.ts");
+        let second = dir.path().join("b.ts");
+        std::fs::write(&first, "export const a = 1;").unwrap();
+        std::fs::write(&second, "export const b = 1;").unwrap();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher = EventWatcher {
+            config: Arc::new(test_config(dir.path())),
+            watcher: notify::recommended_watcher(|_| {}).unwrap(),
+            rx,
+        };
+        tx.send(Ok(event(EventKind::Create(CreateKind::File), &[&first])))
+            .unwrap();
+
+        let mut tx = Some(tx);
+        let mut runs = Vec::new();
+        let index = |changes: ChangeSet| {
+            runs.push(changes.paths.into_iter().collect::<Vec<_>>());
+            let result = if runs.len() == 1 {
+                // Lands while the loop backs off from the failure below
+                let sender = tx.as_ref().unwrap();
+                sender
+                    .send(Ok(event(EventKind::Create(CreateKind::File), &[&second])))
+                    .unwrap();
+                Err(anyhow::anyhow!("injected index failure"))
+            } else {
+                // Closing the stream ends the loop once the retry succeeded
+                tx = None;
+                Ok(())
+            };
+            std::future::ready(result)
+        };
+        watcher
+            .run_index_loop(Duration::from_millis(10), Duration::ZERO, "test", index)
+            .await;
+
+        assert_eq!(runs, vec![vec![first.clone()], vec![first, second]]);
+    }
+
+    #[test]
+    fn change_set_keeps_removed_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(dir.path());
+        let moved_dir = dir.path().join("src").join("feature");
+
+        let mut changes = ChangeSet::default();
+        changes.record(
+            &config,
+            &event(EventKind::Modify(ModifyKind::Name(RenameMode::From)), &[&moved_dir]),
+        );
+
+        assert!(changes.paths.contains(&moved_dir));
+    }
+}
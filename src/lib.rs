@@ -1,8 +1,10 @@
 pub mod config;
 pub mod embeddings;
+pub mod git;
 pub mod graph;
 pub mod handlers;
 pub mod indexer;
+pub mod lsp;
 pub mod metrics;
 pub mod path;
 pub mod reranker;
@@ -0,0 +1,541 @@
+//! LSP request handlers, implemented on top of the MCP tool handlers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::position::{Position, Range, TextDocument};
+use super::{LspError, LspServer};
+use crate::graph::build_call_hierarchy;
+use crate::handlers::{handle_find_references, handle_get_definition, handle_get_file_symbols};
+use crate::path::Utf8PathBuf;
+use crate::storage::sqlite::{SymbolHeaderRow, SymbolRow};
+use crate::tools::{FindReferencesTool, GetDefinitionTool, GetFileSymbolsTool};
+
+const DEFINITION_LIMIT: u32 = 50;
+const REFERENCE_LIMIT: u32 = 1000;
+const WORKSPACE_SYMBOL_LIMIT: usize = 200;
+const CALL_HIERARCHY_LIMIT: usize = 500;
+
+pub(super) fn initialize_result() -> Value {
+    json!({
+        "capabilities": {
+            "positionEncoding": "utf-16",
+            "textDocumentSync": { "openClose": true, "change": 1 },
+            "definitionProvider": true,
+            "referencesProvider": true,
+            "documentSymbolProvider": true,
+            "workspaceSymbolProvider": true,
+            "callHierarchyProvider": true,
+        },
+        "serverInfo": {
+            "name": "code-intelligence",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TextDocumentIdentifier {
+    uri: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TextDocumentPositionParams {
+    text_document: TextDocumentIdentifier,
+    position: Position,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReferenceContext {
+    include_declaration: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReferenceParams {
+    #[serde(flatten)]
+    position: TextDocumentPositionParams,
+    context: Option<ReferenceContext>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentSymbolParams {
+    text_document: TextDocumentIdentifier,
+}
+
+#[derive(Deserialize)]
+struct WorkspaceSymbolParams {
+    query: String,
+}
+
+#[derive(Deserialize)]
+struct CallHierarchyCallsParams {
+    item: Value,
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, LspError> {
+    serde_json::from_value(params).map_err(LspError::invalid_params)
+}
+
+/// Map an indexed symbol kind to an LSP `SymbolKind`.
+fn lsp_symbol_kind(kind: &str) -> u32 {
+    match kind {
+        "file" => 1,
+        "module" => 2,
+        "section" => 3,
+        "class" => 5,
+        "method" | "rpc" | "operation" => 6,
+        "property" => 7,
+        "column" => 8,
+        "enum" => 10,
+        "interface" | "trait" | "service" => 11,
+        "function" => 12,
+        "const" => 14,
+        "config_key" => 20,
+        "struct" | "impl" | "table" | "message" => 23,
+        "type_alias" => 26,
+        _ => 13,
+    }
+}
+
+/// Disk contents of indexed files, loaded once per request. Symbol byte spans refer
+/// to the indexed (on-disk) text, not to unsaved editor buffers.
+#[derive(Default)]
+struct FileCache {
+    files: HashMap<String, Option<TextDocument>>,
+}
+
+impl FileCache {
+    fn get(&mut self, server: &LspServer, file_key: &str) -> Option<&TextDocument> {
+        self.files
+            .entry(file_key.to_string())
+            .or_insert_with(|| {
+                std::fs::read_to_string(server.absolute_path(file_key))
+                    .ok()
+                    .map(TextDocument::new)
+            })
+            .as_ref()
+    }
+}
+
+impl LspServer {
+    fn absolute_path(&self, file_key: &str) -> PathBuf {
+        let path = PathBuf::from(file_key);
+        if path.is_absolute() {
+            path
+        } else {
+            self.state
+                .config
+                .base_dir
+                .join(file_key)
+                .into_std_path_buf()
+        }
+    }
+
+    fn uri_for(&self, file_key: &str) -> String {
+        url::Url::from_file_path(self.absolute_path(file_key))
+            .map(String::from)
+            .unwrap_or_else(|_| format!("file://{file_key}"))
+    }
+
+    /// The index's key for a document URI (path relative to `BASE_DIR`).
+    fn file_key(&self, uri: &str) -> Result<String, LspError> {
+        let path = url::Url::parse(uri)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+            .ok_or_else(|| LspError::invalid_params(format!("Not a file URI: {uri}")))?;
+        let path = Utf8PathBuf::from_path_buf(path)
+            .map_err(|p| LspError::invalid_params(format!("Non UTF-8 path: {}", p.display())))?;
+        Ok(self
+            .state
+            .config
+            .path_relative_to_base(&path)
+            .unwrap_or_else(|_| path.to_string()))
+    }
+
+    /// The identifier under the cursor, read from the open buffer when there is one.
+    fn identifier_at(
+        &self,
+        params: &TextDocumentPositionParams,
+    ) -> Result<Option<(String, String)>, LspError> {
+        let uri = &params.text_document.uri;
+        let file_key = self.file_key(uri)?;
+        let disk;
+        let doc = match self.documents.get(uri) {
+            Some(doc) => doc,
+            None => {
+                let text = std::fs::read_to_string(self.absolute_path(&file_key))
+                    .map_err(|err| anyhow!("Failed to read {file_key}: {err}"))?;
+                disk = TextDocument::new(text);
+                &disk
+            }
+        };
+        let offset = doc.offset(params.position);
+        Ok(doc
+            .identifier_at(offset)
+            .map(|(_, _, ident)| (file_key, ident.to_string())))
+    }
+
+    /// Definitions of `name`, those in `file_key` first.
+    async fn definitions(&self, name: &str, file_key: &str) -> Result<Vec<SymbolRow>, LspError> {
+        let result = handle_get_definition(
+            &self.state,
+            GetDefinitionTool {
+                symbol_name: name.to_string(),
+                file: None,
+                limit: Some(DEFINITION_LIMIT),
+                revision: None,
+            },
+        )
+        .await?;
+        let mut rows: Vec<SymbolRow> =
+            serde_json::from_value(result["definitions"].clone()).map_err(anyhow::Error::from)?;
+        rows.sort_by_key(|row| row.file_path != file_key);
+        Ok(rows)
+    }
+
+    fn symbol_location(
+        &self,
+        cache: &mut FileCache,
+        file_key: &str,
+        start_byte: u32,
+        end_byte: u32,
+        name: &str,
+    ) -> Option<(Range, Range)> {
+        let doc = cache.get(self, file_key)?;
+        let (start, end) = (start_byte as usize, end_byte as usize);
+        Some((doc.range(start, end), doc.name_range(start, end, name)))
+    }
+
+    fn location(&self, cache: &mut FileCache, row: &SymbolRow) -> Option<Value> {
+        let (_, selection) = self.symbol_location(
+            cache,
+            &row.file_path,
+            row.start_byte,
+            row.end_byte,
+            &row.name,
+        )?;
+        Some(json!({ "uri": self.uri_for(&row.file_path), "range": selection }))
+    }
+
+    fn call_hierarchy_item(&self, cache: &mut FileCache, row: &SymbolRow) -> Option<Value> {
+        let (range, selection) = self.symbol_location(
+            cache,
+            &row.file_path,
+            row.start_byte,
+            row.end_byte,
+            &row.name,
+        )?;
+        Some(json!({
+            "name": row.name,
+            "kind": lsp_symbol_kind(&row.kind),
+            "detail": row.file_path,
+            "uri": self.uri_for(&row.file_path),
+            "range": range,
+            "selectionRange": selection,
+            "data": { "symbol_id": row.id },
+        }))
+    }
+
+    pub(super) async fn definition(&self, params: Value) -> Result<Value, LspError> {
+        let params: TextDocumentPositionParams = parse_params(params)?;
+        let Some((file_key, name)) = self.identifier_at(&params)? else {
+            return Ok(Value::Null);
+        };
+        let rows = self.definitions(&name, &file_key).await?;
+        let mut cache = FileCache::default();
+        let locations: Vec<Value> = rows
+            .iter()
+            .filter(|row| row.kind != "file")
+            .filter_map(|row| self.location(&mut cache, row))
+            .collect();
+        Ok(Value::Array(locations))
+    }
+
+    pub(super) async fn references(&self, params: Value) -> Result<Value, LspError> {
+        let params: ReferenceParams = parse_params(params)?;
+        let Some((file_key, name)) = self.identifier_at(&params.position)? else {
+            return Ok(Value::Null);
+        };
+        let include_declaration = params.context.is_some_and(|c| c.include_declaration);
+
+        let result = handle_find_references(
+            &self.state,
+            FindReferencesTool {
+                symbol_name: name.clone(),
+                file: None,
+                reference_type: None,
+                limit: Some(REFERENCE_LIMIT),
+                revision: None,
+            },
+        )?;
+
+        let mut cache = FileCache::default();
+        let mut locations = Vec::new();
+        if include_declaration {
+            for row in self.definitions(&name, &file_key).await? {
+                if row.kind != "file" {
+                    locations.extend(self.location(&mut cache, &row));
+                }
+            }
+        }
+
+        // One location per (file, line); several edge types often share a line
+        let mut seen = std::collections::HashSet::new();
+        for reference in result["references"].as_array().into_iter().flatten() {
+            let (Some(file), Some(line)) =
+                (reference["at_file"].as_str(), reference["at_line"].as_u64())
+            else {
+                continue;
+            };
+            if !seen.insert((file.to_string(), line)) {
+                continue;
+            }
+            if let Some(doc) = cache.get(self, file) {
+                let range = doc.name_range_on_line(line as u32, &name);
+                locations.push(json!({ "uri": self.uri_for(file), "range": range }));
+            }
+        }
+        Ok(Value::Array(locations))
+    }
+
+    pub(super) fn document_symbols(&self, params: Value) -> Result<Value, LspError> {
+        let params: DocumentSymbolParams = parse_params(params)?;
+        let file_key = self.file_key(&params.text_document.uri)?;
+        let result = handle_get_file_symbols(
+            &self.state,
+            GetFileSymbolsTool {
+                file_path: file_key.clone(),
+                exported_only: Some(false),
+                tree: None,
+                revision: None,
+            },
+        )?;
+        let mut rows: Vec<SymbolHeaderRow> =
+            serde_json::from_value(result["symbols"].clone()).map_err(anyhow::Error::from)?;
+        rows.retain(|row| row.kind != "file");
+
+        let mut cache = FileCache::default();
+        let Some(doc) = cache.get(self, &file_key) else {
+            return Ok(Value::Array(Vec::new()));
+        };
+        Ok(Value::Array(nest_document_symbols(doc, rows)))
+    }
+
+    pub(super) fn workspace_symbols(&self, params: Value) -> Result<Value, LspError> {
+        let params: WorkspaceSymbolParams = parse_params(params)?;
+        let query = params.query.trim();
+        if query.is_empty() {
+            return Ok(Value::Array(Vec::new()));
+        }
+        let rows = self
+            .state
+            .sqlite
+            .search_symbols_by_name_substr(query, WORKSPACE_SYMBOL_LIMIT)?;
+        let mut cache = FileCache::default();
+        let symbols = rows
+            .iter()
+            .filter(|row| row.kind != "file")
+            .filter_map(|row| {
+                let location = self.location(&mut cache, row)?;
+                Some(json!({
+                    "name": row.name,
+                    "kind": lsp_symbol_kind(&row.kind),
+                    "location": location,
+                    "containerName": row.file_path,
+                }))
+            })
+            .collect();
+        Ok(Value::Array(symbols))
+    }
+
+    pub(super) async fn prepare_call_hierarchy(&self, params: Value) -> Result<Value, LspError> {
+        let params: TextDocumentPositionParams = parse_params(params)?;
+        let Some((file_key, name)) = self.identifier_at(&params)? else {
+            return Ok(Value::Null);
+        };
+        let rows = self.definitions(&name, &file_key).await?;
+        let mut cache = FileCache::default();
+        let items: Vec<Value> = rows
+            .iter()
+            .filter(|row| row.kind != "file")
+            .filter_map(|row| self.call_hierarchy_item(&mut cache, row))
+            .collect();
+        Ok(if items.is_empty() {
+            Value::Null
+        } else {
+            Value::Array(items)
+        })
+    }
+
+    /// `callHierarchy/incomingCalls` (`direction = "callers"`) and
+    /// `callHierarchy/outgoingCalls` (`direction = "callees"`).
+    pub(super) fn call_hierarchy_calls(
+        &self,
+        params: Value,
+        direction: &str,
+    ) -> Result<Value, LspError> {
+        let params: CallHierarchyCallsParams = parse_params(params)?;
+        let symbol_id = params
+            .item
+            .pointer("/data/symbol_id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| LspError::invalid_params("Call hierarchy item has no symbol_id"))?;
+        let sqlite = &self.state.sqlite;
+        let Some(root) = sqlite.get_symbol_by_id(symbol_id)? else {
+            return Ok(Value::Array(Vec::new()));
+        };
+
+        let graph = build_call_hierarchy(sqlite, &root, direction, 1, CALL_HIERARCHY_LIMIT)?;
+        let incoming = direction == "callers";
+
+        // Other end of each call edge -> lines (in the caller's file) of the call sites
+        let mut calls: BTreeMap<String, Vec<(String, u32)>> = BTreeMap::new();
+        for edge in graph["edges"].as_array().into_iter().flatten() {
+            let other = if incoming { &edge["from"] } else { &edge["to"] };
+            let Some(other) = other.as_str() else {
+                continue;
+            };
+            let sites = calls.entry(other.to_string()).or_default();
+            let evidence = edge["evidence"].as_array().into_iter().flatten();
+            for site in std::iter::once(edge).chain(evidence) {
+                if let (Some(file), Some(line)) =
+                    (site["at_file"].as_str(), site["at_line"].as_u64())
+                {
+                    let site = (file.to_string(), line as u32);
+                    if !sites.contains(&site) {
+                        sites.push(site);
+                    }
+                }
+            }
+        }
+
+        let mut cache = FileCache::default();
+        let mut out = Vec::new();
+        for (other_id, sites) in calls {
+            let Some(other) = sqlite.get_symbol_by_id(&other_id)? else {
+                continue;
+            };
+            let Some(item) = self.call_hierarchy_item(&mut cache, &other) else {
+                continue;
+            };
+            // The call sites name the callee
+            let callee = if incoming { &root.name } else { &other.name };
+            let from_ranges: Vec<Range> = sites
+                .iter()
+                .filter_map(|(file, line)| {
+                    cache
+                        .get(self, file)
+                        .map(|doc| doc.name_range_on_line(*line, callee))
+                })
+                .collect();
+            let key = if incoming { "from" } else { "to" };
+            out.push(json!({ key: item, "fromRanges": from_ranges }));
+        }
+        Ok(Value::Array(out))
+    }
+}
+
+/// Build a `DocumentSymbol` tree, nesting symbols whose span lies inside another's.
+fn nest_document_symbols(doc: &TextDocument, mut rows: Vec<SymbolHeaderRow>) -> Vec<Value> {
+    // Outer symbols first: by start, then longest span
+    rows.sort_by_key(|row| (row.start_byte, std::cmp::Reverse(row.end_byte)));
+
+    struct Node {
+        value: Value,
+        end_byte: u32,
+        children: Vec<Value>,
+    }
+
+    fn close(mut node: Node) -> Value {
+        node.value["children"] = Value::Array(node.children);
+        node.value
+    }
+
+    let mut roots = Vec::new();
+    let mut stack: Vec<Node> = Vec::new();
+    for row in rows {
+        while stack.last().is_some_and(|top| row.end_byte > top.end_byte) {
+            let done = close(stack.pop().unwrap());
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(done),
+                None => roots.push(done),
+            }
+        }
+        let (start, end) = (row.start_byte as usize, row.end_byte as usize);
+        stack.push(Node {
+            value: json!({
+                "name": row.name,
+                "kind": lsp_symbol_kind(&row.kind),
+                "range": doc.range(start, end),
+                "selectionRange": doc.name_range(start, end, &row.name),
+            }),
+            end_byte: row.end_byte,
+            children: Vec::new(),
+        });
+    }
+    while let Some(node) = stack.pop() {
+        let done = close(node);
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(done),
+            None => roots.push(done),
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(name: &str, kind: &str, start_byte: u32, end_byte: u32) -> SymbolHeaderRow {
+        SymbolHeaderRow {
+            id: name.to_string(),
+            file_path: "a.ts".to_string(),
+            language: "typescript".to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+            exported: false,
+            start_byte,
+            end_byte,
+            start_line: 1,
+            end_line: 1,
+            qualified_name: name.to_string(),
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn document_symbols_nest_by_span() {
+        let text = "class A {\n  m() {}\n}\nfunction f() {}\n";
+        let doc = TextDocument::new(text.to_string());
+        let class_end = text.find("}\nfunction").unwrap() as u32 + 1;
+        let m_start = text.find("m()").unwrap() as u32;
+        let f_start = text.find("function").unwrap() as u32;
+        let rows = vec![
+            header("f", "function", f_start, text.len() as u32 - 1),
+            header("m", "method", m_start, m_start + 6),
+            header("A", "class", 0, class_end),
+        ];
+
+        let symbols = nest_document_symbols(&doc, rows);
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0]["name"], "A");
+        assert_eq!(symbols[0]["kind"], 5);
+        assert_eq!(symbols[0]["children"][0]["name"], "m");
+        assert_eq!(
+            symbols[0]["children"][0]["selectionRange"]["start"],
+            json!({ "line": 1, "character": 2 })
+        );
+        assert_eq!(symbols[1]["name"], "f");
+        assert_eq!(symbols[1]["children"], json!([]));
+    }
+}
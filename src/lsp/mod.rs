@@ -0,0 +1,266 @@
+//! Language Server Protocol front-end
+//!
+//! Serves go-to-definition, references, document/workspace symbols and call
+//! hierarchy from the same index the MCP tools use, over JSON-RPC on stdio.
+
+mod handlers;
+pub mod position;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+
+use crate::handlers::AppState;
+use position::TextDocument;
+
+// JSON-RPC error codes used by LSP
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// A failed request, reported to the client as a JSON-RPC error.
+#[derive(Debug)]
+pub struct LspError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl LspError {
+    fn invalid_params(err: impl std::fmt::Display) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for LspError {
+    fn from(err: anyhow::Error) -> Self {
+        Self {
+            code: INTERNAL_ERROR,
+            message: format!("{err:#}"),
+        }
+    }
+}
+
+/// Per-connection server state: the shared index plus the editor's open buffers.
+pub struct LspServer {
+    state: Arc<AppState>,
+    /// Open documents by URI, as last synced by the client
+    documents: HashMap<String, TextDocument>,
+    shutdown_requested: bool,
+}
+
+impl LspServer {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            documents: HashMap::new(),
+            shutdown_requested: false,
+        }
+    }
+
+    /// Handle one incoming message. Returns the response to send (for requests) and
+    /// whether the connection should close.
+    pub async fn handle_message(&mut self, message: Value) -> (Option<Value>, bool) {
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let Some(method) = method else {
+            // A response to a server-initiated request; we never send any
+            return (None, false);
+        };
+
+        let Some(id) = message.get("id").cloned() else {
+            let exit = self.handle_notification(&method, params);
+            return (None, exit);
+        };
+
+        let result = if self.shutdown_requested {
+            Err(LspError {
+                code: INVALID_REQUEST,
+                message: "Server is shutting down".to_string(),
+            })
+        } else {
+            self.handle_request(&method, params).await
+        };
+
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => {
+                if err.code == INTERNAL_ERROR {
+                    tracing::warn!(method = %method, error = %err.message, "LSP request failed");
+                }
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": err.code, "message": err.message },
+                })
+            }
+        };
+        (Some(response), false)
+    }
+
+    async fn handle_request(&mut self, method: &str, params: Value) -> Result<Value, LspError> {
+        match method {
+            "initialize" => Ok(handlers::initialize_result()),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            "textDocument/definition" => self.definition(params).await,
+            "textDocument/references" => self.references(params).await,
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            "workspace/symbol" => self.workspace_symbols(params),
+            "textDocument/prepareCallHierarchy" => self.prepare_call_hierarchy(params).await,
+            "callHierarchy/incomingCalls" => self.call_hierarchy_calls(params, "callers"),
+            "callHierarchy/outgoingCalls" => self.call_hierarchy_calls(params, "callees"),
+            other => Err(LspError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Unhandled method: {other}"),
+            }),
+        }
+    }
+
+    /// Returns true on `exit`.
+    fn handle_notification(&mut self, method: &str, params: Value) -> bool {
+        match method {
+            "exit" => return true,
+            "textDocument/didOpen" => {
+                if let (Some(uri), Some(text)) = (
+                    params.pointer("/textDocument/uri").and_then(Value::as_str),
+                    params.pointer("/textDocument/text").and_then(Value::as_str),
+                ) {
+                    self.documents
+                        .insert(uri.to_string(), TextDocument::new(text.to_string()));
+                }
+            }
+            "textDocument/didChange" => {
+                // We advertise full sync, so the last change carries the whole text
+                let uri = params.pointer("/textDocument/uri").and_then(Value::as_str);
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.documents
+                        .insert(uri.to_string(), TextDocument::new(text.to_string()));
+                }
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params.pointer("/textDocument/uri").and_then(Value::as_str) {
+                    self.documents.remove(uri);
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Run the language server over stdin/stdout until the client exits.
+pub async fn serve_stdio(state: Arc<AppState>) -> Result<()> {
+    serve(
+        state,
+        BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+    )
+    .await
+}
+
+/// Run the language server over any byte stream pair.
+pub async fn serve<R, W>(state: Arc<AppState>, mut reader: R, mut writer: W) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut server = LspServer::new(state);
+    while let Some(message) = read_message(&mut reader).await? {
+        let (response, exit) = server.handle_message(message).await;
+        if let Some(response) = response {
+            write_message(&mut writer, &response).await?;
+        }
+        if exit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Read one `Content-Length`-framed message. Returns `None` at end of stream.
+pub async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .with_context(|| format!("Invalid Content-Length: {value}"))?,
+                );
+            }
+        }
+    }
+
+    let length = content_length.ok_or_else(|| anyhow!("Missing Content-Length header"))?;
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).await?;
+    let message = serde_json::from_slice(&body).context("Invalid JSON-RPC message")?;
+    Ok(Some(message))
+}
+
+pub async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn messages_round_trip_through_content_length_framing() {
+        let first =
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "é": "😀" } });
+        let second = json!({ "jsonrpc": "2.0", "method": "exit" });
+        let mut buf = Vec::new();
+        write_message(&mut buf, &first).await.unwrap();
+        write_message(&mut buf, &second).await.unwrap();
+
+        let mut reader = BufReader::new(buf.as_slice());
+        assert_eq!(read_message(&mut reader).await.unwrap(), Some(first));
+        assert_eq!(read_message(&mut reader).await.unwrap(), Some(second));
+        assert_eq!(read_message(&mut reader).await.unwrap(), None);
+
+        let mut bad = BufReader::new(&b"Content-Length: abc\r\n\r\n{}"[..]);
+        assert!(read_message(&mut bad).await.is_err());
+    }
+}
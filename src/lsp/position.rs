@@ -0,0 +1,213 @@
+//! Mapping between byte offsets (as stored in `SymbolRow`) and LSP positions
+//! (zero-based line, UTF-16 code unit column).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A file's text with its line starts, for offset <-> position conversion.
+#[derive(Debug, Clone)]
+pub struct TextDocument {
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl TextDocument {
+    pub fn new(text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Text of a zero-based line, without the line terminator.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line)?;
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.text.len());
+        Some(self.text[start..end].trim_end_matches(['\n', '\r']))
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = floor_char_boundary(&self.text, offset.min(self.text.len()));
+        let line = self.line_starts.partition_point(|&s| s <= offset) - 1;
+        let start = self.line_starts[line];
+        Position {
+            line: line as u32,
+            character: utf16_len(&self.text[start..offset]),
+        }
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(line) = self.line(position.line as usize) else {
+            return self.text.len();
+        };
+        let start = self.line_starts[position.line as usize];
+        let mut units = 0u32;
+        for (i, c) in line.char_indices() {
+            if units >= position.character {
+                return start + i;
+            }
+            units += c.len_utf16() as u32;
+        }
+        start + line.len()
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Range {
+        Range {
+            start: self.position(start),
+            end: self.position(end.max(start)),
+        }
+    }
+
+    /// Range of the first occurrence of `name` within the byte span, falling back to
+    /// the start of the span. Used for `selectionRange`.
+    pub fn name_range(&self, start: usize, end: usize, name: &str) -> Range {
+        let start = floor_char_boundary(&self.text, start.min(self.text.len()));
+        let end = floor_char_boundary(&self.text, end.clamp(start, self.text.len()));
+        match find_identifier(&self.text[start..end], name) {
+            Some(i) => self.range(start + i, start + i + name.len()),
+            None => self.range(start, start),
+        }
+    }
+
+    /// Range of `name` on a one-based line, or the whole line when the name is not
+    /// found there.
+    pub fn name_range_on_line(&self, line: u32, name: &str) -> Range {
+        let line0 = line.saturating_sub(1) as usize;
+        let Some(text) = self.line(line0) else {
+            let end = self.position(self.text.len());
+            return Range { start: end, end };
+        };
+        let start = self.line_starts[line0];
+        match find_identifier(text, name) {
+            Some(i) => self.range(start + i, start + i + name.len()),
+            None => self.range(start, start + text.len()),
+        }
+    }
+
+    /// The identifier touching `offset`, with its byte span.
+    pub fn identifier_at(&self, offset: usize) -> Option<(usize, usize, &str)> {
+        let offset = floor_char_boundary(&self.text, offset.min(self.text.len()));
+        let start = self.text[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_ident_char(*c))
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(offset);
+        let end = self.text[offset..]
+            .char_indices()
+            .find(|(_, c)| !is_ident_char(*c))
+            .map(|(i, _)| offset + i)
+            .unwrap_or(self.text.len());
+        let ident = &self.text[start..end];
+        (!ident.is_empty() && !ident.starts_with(|c: char| c.is_ascii_digit()))
+            .then_some((start, end, ident))
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// First occurrence of `name` in `haystack` as a whole identifier.
+fn find_identifier(haystack: &str, name: &str) -> Option<usize> {
+    if name.is_empty() {
+        return None;
+    }
+    haystack.match_indices(name).map(|(i, _)| i).find(|&i| {
+        let before = haystack[..i].chars().next_back();
+        let after = haystack[i + name.len()..].chars().next();
+        !before.is_some_and(is_ident_char) && !after.is_some_and(is_ident_char)
+    })
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.chars().map(|c| c.len_utf16() as u32).sum()
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    while index > 0 && !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_round_trip_through_utf16_positions() {
+        let doc = TextDocument::new("let a = 1;\nconst s = \"é😀\"; foo\r\nbar".to_string());
+        let foo = doc.text().find("foo").unwrap();
+        let pos = doc.position(foo);
+        // é is one UTF-16 unit, 😀 is two
+        assert_eq!(
+            pos,
+            Position {
+                line: 1,
+                character: 17
+            }
+        );
+        assert_eq!(doc.offset(pos), foo);
+        assert_eq!(doc.line(1), Some("const s = \"é😀\"; foo"));
+        assert_eq!(doc.position(doc.text().len()).line, 2);
+    }
+
+    #[test]
+    fn identifier_and_name_ranges() {
+        let doc =
+            TextDocument::new("function helper() {\n  return helperX + helper;\n}".to_string());
+        let (_, _, ident) = doc
+            .identifier_at(doc.text().find("elper").unwrap())
+            .unwrap();
+        assert_eq!(ident, "helper");
+
+        let r = doc.name_range_on_line(2, "helper");
+        assert_eq!(
+            r.start,
+            Position {
+                line: 1,
+                character: 19
+            }
+        );
+        assert_eq!(
+            r.end,
+            Position {
+                line: 1,
+                character: 25
+            }
+        );
+
+        let r = doc.name_range(0, doc.text().len(), "helper");
+        assert_eq!(
+            r.start,
+            Position {
+                line: 0,
+                character: 9
+            }
+        );
+        assert!(doc
+            .identifier_at(doc.text().find('{').unwrap() + 1)
+            .is_none());
+    }
+}
@@ -5,31 +5,29 @@
 use rust_mcp_sdk::{
     error::{McpSdkError, SdkResult},
     mcp_server::{server_runtime, McpServerOptions, ToMcpServerHandler},
-    schema::{
-        Implementation, InitializeResult, ProtocolVersion, ServerCapabilities,
-        ServerCapabilitiesTools,
-    },
     McpServer, StdioTransport, TransportOptions,
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
-use code_intelligence_mcp_server::config::Config;
+use code_intelligence_mcp_server::config::{Config, McpTransport};
 use code_intelligence_mcp_server::embeddings::{create_embedder, Embedder};
 use code_intelligence_mcp_server::handlers::AppState;
 use code_intelligence_mcp_server::indexer::pipeline::IndexPipeline;
+use code_intelligence_mcp_server::lsp;
 use code_intelligence_mcp_server::metrics::{spawn_metrics_server, MetricsRegistry};
 use code_intelligence_mcp_server::reranker::create_reranker;
 use code_intelligence_mcp_server::retrieval::hyde::HypotheticalCodeGenerator;
 use code_intelligence_mcp_server::retrieval::Retriever;
-use code_intelligence_mcp_server::server::CodeIntelligenceHandler;
+use code_intelligence_mcp_server::server::{http::create_http_server, server_details, CodeIntelligenceHandler};
 use code_intelligence_mcp_server::storage::sqlite::SqliteStore;
 use code_intelligence_mcp_server::storage::tantivy::TantivyIndex;
 use code_intelligence_mcp_server::storage::vector::LanceDbStore;
 
 mod cli;
+mod commands;
 
 #[cfg(feature = "web-ui")]
 mod web_ui;
@@ -53,6 +51,13 @@ async fn main() -> SdkResult<()> {
         cli::print_version();
         return Ok(());
     }
+    let invocation = match cli::parse_command(&args) {
+        Ok(invocation) => invocation,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(2);
+        }
+    };
 
     // Set up file logging to global ~/.cimcp/logs directory
     let global_dir = code_intelligence_mcp_server::config::get_global_cimcp_dir();
@@ -67,8 +72,11 @@ async fn main() -> SdkResult<()> {
     let file_appender = tracing_appender::rolling::daily(&logs_dir, "server.log");
     let (non_blocking_file, _guard) = tracing_appender::non_blocking(file_appender);
 
-    // Set up layered subscriber with both stderr and file output
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    // Set up layered subscriber with both stderr and file output.
+    // Subcommands keep stderr quiet by default so their output stays readable.
+    let default_level = if invocation.is_some() { "warn" } else { "info" };
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
 
     tracing_subscriber::registry()
         .with(env_filter)
@@ -94,14 +102,14 @@ async fn main() -> SdkResult<()> {
         "Starting code-intelligence-mcp-server"
     );
 
-    if let Err(err) = run().await {
+    if let Err(err) = run(invocation).await {
         error!(error = %err, "Server exited with error");
         return Err(err);
     }
     Ok(())
 }
 
-async fn run() -> SdkResult<()> {
+async fn run(invocation: Option<cli::Invocation>) -> SdkResult<()> {
     let config = Config::from_env().map_err(|err| McpSdkError::Internal {
         description: err.to_string(),
     })?;
@@ -166,18 +174,6 @@ async fn run() -> SdkResult<()> {
         description: format!("Failed to create metrics registry: {}", err),
     })?);
 
-    // Spawn metrics server if enabled
-    let _metrics_handle = if config.metrics_enabled {
-        let handle = spawn_metrics_server(Arc::clone(&metrics), config.metrics_port)
-            .await
-            .map_err(|err| McpSdkError::Internal {
-                description: format!("Failed to spawn metrics server: {}", err),
-            })?;
-        Some(handle)
-    } else {
-        None
-    };
-
     // Create reranker if model path is configured
     let reranker = create_reranker(
         config.reranker_model_path.as_deref(),
@@ -221,8 +217,49 @@ async fn run() -> SdkResult<()> {
         indexer,
         retriever,
         sqlite: Arc::new(sqlite),
+        snapshots: Default::default(),
     });
 
+    // Subcommands, the LSP server included, never rebuild embeddings on their own
+    if invocation.is_some() && needs_reindex {
+        warn!("Vector table was migrated to a new embedding dimension; run `index` to rebuild embeddings");
+    }
+
+    if invocation
+        .as_ref()
+        .is_some_and(|inv| inv.command == cli::Command::Lsp)
+    {
+        if state.config.watch_mode {
+            state.indexer.spawn_watch_loop();
+        }
+        info!("Starting LSP server on stdio");
+        return lsp::serve_stdio(state)
+            .await
+            .map_err(|err| McpSdkError::Internal {
+                description: err.to_string(),
+            });
+    }
+
+    if let Some(invocation) = invocation {
+        if let Err(err) = commands::run(&state, invocation).await {
+            eprintln!("error: {err:#}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Spawn metrics server if enabled
+    let _metrics_handle = if config.metrics_enabled {
+        let handle = spawn_metrics_server(Arc::clone(&metrics), config.metrics_port)
+            .await
+            .map_err(|err| McpSdkError::Internal {
+                description: format!("Failed to spawn metrics server: {}", err),
+            })?;
+        Some(handle)
+    } else {
+        None
+    };
+
     // Trigger automatic re-index if vector dimension migration occurred
     if needs_reindex {
         tracing::info!(
@@ -286,12 +323,18 @@ async fn run() -> SdkResult<()> {
         rank_test_penalty = config.rank_test_penalty,
         rank_popularity_weight = config.rank_popularity_weight,
         rank_popularity_cap = config.rank_popularity_cap,
+        rank_history_weight = config.rank_history_weight,
+        history_max_commits = config.history_max_commits,
         watch_mode = config.watch_mode,
         watch_debounce_ms = config.watch_debounce_ms,
         watch_min_index_interval_ms = config.watch_min_index_interval_ms,
+        watch_backend = ?config.watch_backend,
         max_context_bytes = config.max_context_bytes,
         index_node_modules = config.index_node_modules,
         repo_roots = ?config.repo_roots,
+        mcp_transport = ?config.mcp_transport,
+        http_bind_addr = %config.http_bind_addr,
+        http_auth = config.http_auth_token.is_some(),
         "Loaded config"
     );
 
@@ -301,31 +344,25 @@ async fn run() -> SdkResult<()> {
         "Initialized components"
     );
 
-    let server_details = InitializeResult {
-        server_info: Implementation {
-            name: "code-intelligence".into(),
-            version: env!("CARGO_PKG_VERSION").into(),
-            title: Some("Code Intelligence MCP".into()),
-            description: Some("Local code intelligence MCP server".into()),
-            icons: vec![],
-            website_url: None,
-        },
-        capabilities: ServerCapabilities {
-            tools: Some(ServerCapabilitiesTools { list_changed: None }),
-            ..Default::default()
-        },
-        protocol_version: ProtocolVersion::V2025_11_25.into(),
-        instructions: None,
-        meta: None,
-    };
+    let handler = CodeIntelligenceHandler { state };
 
-    let transport = StdioTransport::new(TransportOptions::default())?;
-    let handler = CodeIntelligenceHandler { state }.to_mcp_server_handler();
+    if config.mcp_transport == McpTransport::Http {
+        let server = create_http_server(&config, handler).map_err(|err| McpSdkError::Internal {
+            description: err.to_string(),
+        })?;
+        info!(
+            bind_addr = %config.http_bind_addr,
+            auth = config.http_auth_token.is_some(),
+            "Starting MCP HTTP server"
+        );
+        return server.start().await;
+    }
 
+    let transport = StdioTransport::new(TransportOptions::default())?;
     let server = server_runtime::create_server(McpServerOptions {
-        server_details,
+        server_details: server_details(),
         transport,
-        handler,
+        handler: handler.to_mcp_server_handler(),
         task_store: None,
         client_task_store: None,
     });
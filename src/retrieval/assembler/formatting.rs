@@ -99,6 +99,8 @@ pub fn symbol_row_from_usage_example(
         start_line: line,
         end_line: line,
         text: ex.snippet.clone(),
+        qualified_name: root.qualified_name.clone(),
+        parent_id: None,
     }
 }
 
@@ -548,6 +550,8 @@ mod tests {
             start_line: 1,
             end_line: 5,
             text: "fn test_func() {}".to_string(),
+            qualified_name: "test_func".to_string(),
+            parent_id: None,
         };
         let result = format_symbol_section(&sym, "fn test_func() {}", "root");
         assert!(result.contains("### test.rs:1-5"));
@@ -571,6 +575,8 @@ mod tests {
             start_line: 1,
             end_line: 5,
             text: "fn definition() {}".to_string(),
+            qualified_name: "definition".to_string(),
+            parent_id: None,
         };
         let ex_sym = SymbolRow {
             id: "ex".to_string(),
@@ -584,6 +590,8 @@ mod tests {
             start_line: 10,
             end_line: 10,
             text: "definition();".to_string(),
+            qualified_name: "example".to_string(),
+            parent_id: None,
         };
         let rel_sym = SymbolRow {
             id: "rel".to_string(),
@@ -597,6 +605,8 @@ mod tests {
             start_line: 1,
             end_line: 3,
             text: "type Related = ();".to_string(),
+            qualified_name: "related_type".to_string(),
+            parent_id: None,
         };
 
         let definitions = vec![(def_sym, "fn definition() {}".to_string())];
@@ -630,6 +640,8 @@ mod tests {
             start_line: 1,
             end_line: 5,
             text: "fn definition() {}".to_string(),
+            qualified_name: "definition".to_string(),
+            parent_id: None,
         };
 
         let definitions = vec![(def_sym, "fn definition() {}".to_string())];
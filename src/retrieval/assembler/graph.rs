@@ -224,6 +224,8 @@ mod tests {
             start_line: 1,
             end_line: 5,
             text: "fn test_func() -> Result<()> { Ok(()) }".to_string(),
+            qualified_name: "test_func".to_string(),
+            parent_id: None,
         };
         store.upsert_symbol(&root).unwrap();
 
@@ -254,6 +256,8 @@ mod tests {
             start_line: 1,
             end_line: 5,
             text: "fn test_func() -> Result<()> { Ok(()) }".to_string(),
+            qualified_name: "test_func".to_string(),
+            parent_id: None,
         };
         let dep = SymbolRow {
             id: "dep".to_string(),
@@ -267,6 +271,8 @@ mod tests {
             start_line: 1,
             end_line: 3,
             text: "type Result<T> = ...".to_string(),
+            qualified_name: "Result".to_string(),
+            parent_id: None,
         };
 
         store.upsert_symbol(&root).unwrap();
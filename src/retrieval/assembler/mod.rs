@@ -350,11 +350,14 @@ mod tests {
             rank_test_penalty: 0.0,
             rank_popularity_weight: 0.0,
             rank_popularity_cap: 0,
+            rank_history_weight: 0.0,
+            history_max_commits: 0,
             index_patterns: vec![],
             exclude_patterns: vec![],
             watch_mode: false,
             watch_debounce_ms: 100,
             watch_min_index_interval_ms: 5000,
+            watch_backend: crate::config::WatchBackend::Auto,
             max_context_bytes: max_bytes,
             index_node_modules: false,
             repo_roots: vec![],
@@ -394,6 +397,9 @@ mod tests {
             metrics_enabled: true,
             metrics_port: 9090,
             package_detection_enabled: true,
+            mcp_transport: crate::config::McpTransport::Stdio,
+            http_bind_addr: "127.0.0.1:8765".to_string(),
+            http_auth_token: None,
         })
     }
 
@@ -418,6 +424,8 @@ mod tests {
             start_line: 1,
             end_line: 4,
             text: format!("function smallFunc() {}", small_body),
+            qualified_name: "smallFunc".to_string(),
+            parent_id: None,
         };
 
         // 2. Huge function (should be truncated)
@@ -438,6 +446,8 @@ mod tests {
             start_line: 1,
             end_line: 202,
             text: format!("function hugeFunc() {{\n{}\n}}", huge_body),
+            qualified_name: "hugeFunc".to_string(),
+            parent_id: None,
         };
 
         let (output, items) = assembler
@@ -489,6 +499,8 @@ mod tests {
             start_line: 1,
             end_line: 1,
             text: "export function same() { return 1 }".to_string(),
+            qualified_name: id.to_string(),
+            parent_id: None,
         };
 
         let expanded = vec![mk("a"), mk("b")];
@@ -520,6 +532,8 @@ mod tests {
             start_line: 1,
             end_line: 5,
             text: "fn test_func() {\n    let x = 1;\n    return x;\n}".to_string(),
+            qualified_name: "test_func".to_string(),
+            parent_id: None,
         };
 
         // Test with query - should work without panicking
@@ -571,6 +585,8 @@ mod tests {
             start_line: 1,
             end_line: lines.len() as u32,
             text: lines.join("\n"),
+            qualified_name: "large_function".to_string(),
+            parent_id: None,
         };
 
         // Both should produce some output and not panic
@@ -613,6 +629,8 @@ mod tests {
             start_line: 1,
             end_line: 5,
             text: "fn small_func() {\n    let x = 1;\n    return x;\n}".to_string(),
+            qualified_name: "small_func".to_string(),
+            parent_id: None,
         };
 
         let (output, items) = assembler
@@ -0,0 +1,210 @@
+//! Literal and regex search over the content of indexed files.
+//!
+//! The trigram index narrows the files down to those that can hold a match; the
+//! regex then runs over each candidate's content. Matches are reported once per
+//! line, with the innermost symbol enclosing them.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::{anyhow, bail, Context, Result};
+use regex::RegexBuilder;
+use serde::Serialize;
+
+use super::filter::PathPattern;
+use crate::storage::{
+    sqlite::{SqliteStore, SymbolHeaderRow},
+    trigram::TrigramQuery,
+};
+
+/// Longest line text returned with a match, in characters.
+const MAX_LINE_CHARS: usize = 240;
+
+/// A content search request.
+#[derive(Debug, Clone)]
+pub struct ContentSearch<'a> {
+    pub pattern: &'a str,
+    /// Match `pattern` as plain text rather than as a regex
+    pub literal: bool,
+    pub case_insensitive: bool,
+    /// `path:` filter of search_code: a substring, or a glob
+    pub path: Option<&'a str>,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentMatch {
+    pub file_path: String,
+    /// 1-based line of the match start
+    pub line: u32,
+    /// 1-based character column of the match start
+    pub column: u32,
+    pub line_text: String,
+    /// Innermost symbol (other than the file itself) containing the match
+    pub symbol: Option<EnclosingSymbol>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnclosingSymbol {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub qualified_name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentSearchResult {
+    pub matches: Vec<ContentMatch>,
+    /// Files the trigram index could not rule out
+    pub candidate_files: usize,
+    /// Whether matches beyond `limit` were left out
+    pub truncated: bool,
+}
+
+/// Run a content search over the files indexed in `sqlite`.
+pub fn search_content(sqlite: &SqliteStore, search: &ContentSearch) -> Result<ContentSearchResult> {
+    if search.pattern.is_empty() {
+        bail!("pattern is empty");
+    }
+    let pattern = if search.literal {
+        regex::escape(search.pattern)
+    } else {
+        search.pattern.to_string()
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(search.case_insensitive)
+        .multi_line(true)
+        .build()
+        .with_context(|| format!("Invalid regex: {}", search.pattern))?;
+    let query = TrigramQuery::from_regex(&pattern, search.case_insensitive)?;
+    let path = search
+        .path
+        .map(|p| PathPattern::path(p).map_err(|e| anyhow!("path {p}: {e}")))
+        .transpose()?;
+
+    let mut postings = HashMap::new();
+    let candidates: Vec<String> = match candidate_files(sqlite, &query, &mut postings)? {
+        Some(file_ids) => {
+            sqlite.list_content_file_paths(&file_ids.into_iter().collect::<Vec<_>>())?
+        }
+        None => sqlite.list_content_files()?,
+    };
+    let candidates: Vec<String> = candidates
+        .into_iter()
+        .filter(|file| path.as_ref().is_none_or(|p| p.matches(file)))
+        .collect();
+
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    'files: for file_path in &candidates {
+        let Some(content) = sqlite.get_file_content(file_path)? else {
+            continue;
+        };
+        let mut headers: Option<Vec<SymbolHeaderRow>> = None;
+        let (mut line, mut line_start) = (1u32, 0usize);
+        let mut last_line = 0u32;
+        for found in regex.find_iter(&content) {
+            // Advance the line cursor to the match
+            let from = line_start;
+            for (offset, _) in content[from..found.start()].match_indices('\n') {
+                line += 1;
+                line_start = from + offset + 1;
+            }
+            if line == last_line {
+                continue;
+            }
+            if matches.len() == search.limit {
+                truncated = true;
+                break 'files;
+            }
+            last_line = line;
+
+            let line_end = content[line_start..]
+                .find('\n')
+                .map_or(content.len(), |at| line_start + at);
+            let line_text: String = content[line_start..line_end]
+                .trim_end()
+                .chars()
+                .take(MAX_LINE_CHARS)
+                .collect();
+            let column = content[line_start..found.start()].chars().count() as u32 + 1;
+
+            let headers = match &mut headers {
+                Some(headers) => headers,
+                None => headers.insert(sqlite.list_symbol_headers_by_file(file_path, false)?),
+            };
+            let symbol = headers
+                .iter()
+                .filter(|h| {
+                    h.kind != "file"
+                        && (h.start_byte as usize) <= found.start()
+                        && found.start() < h.end_byte as usize
+                })
+                .min_by_key(|h| h.end_byte - h.start_byte)
+                .map(|h| EnclosingSymbol {
+                    id: h.id.clone(),
+                    name: h.name.clone(),
+                    kind: h.kind.clone(),
+                    qualified_name: h.qualified_name.clone(),
+                });
+
+            matches.push(ContentMatch {
+                file_path: file_path.clone(),
+                line,
+                column,
+                line_text,
+                symbol,
+            });
+        }
+    }
+
+    Ok(ContentSearchResult {
+        matches,
+        candidate_files: candidates.len(),
+        truncated,
+    })
+}
+
+/// Ids of the files satisfying `query`, or `None` when every file does.
+fn candidate_files(
+    sqlite: &SqliteStore,
+    query: &TrigramQuery,
+    postings: &mut HashMap<u32, BTreeSet<i64>>,
+) -> Result<Option<BTreeSet<i64>>> {
+    match query {
+        TrigramQuery::All => Ok(None),
+        TrigramQuery::Trigram(trigram) => {
+            if !postings.contains_key(trigram) {
+                let file_ids = sqlite.list_file_ids_with_trigram(*trigram)?;
+                postings.insert(*trigram, file_ids.into_iter().collect());
+            }
+            Ok(postings.get(trigram).cloned())
+        }
+        TrigramQuery::And(parts) => {
+            let mut out: Option<BTreeSet<i64>> = None;
+            for part in parts {
+                let Some(files) = candidate_files(sqlite, part, postings)? else {
+                    continue;
+                };
+                let files = match out {
+                    Some(so_far) => so_far.intersection(&files).cloned().collect(),
+                    None => files,
+                };
+                if files.is_empty() {
+                    return Ok(Some(files));
+                }
+                out = Some(files);
+            }
+            Ok(out)
+        }
+        TrigramQuery::Or(parts) => {
+            let mut out = BTreeSet::new();
+            for part in parts {
+                match candidate_files(sqlite, part, postings)? {
+                    Some(files) => out.extend(files),
+                    None => return Ok(None),
+                }
+            }
+            Ok(Some(out))
+        }
+    }
+}
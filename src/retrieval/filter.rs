@@ -0,0 +1,930 @@
+//! Filter terms of search queries.
+//!
+//! Besides free text, a query may hold `key:value` filter terms. Adjacent terms must
+//! all hold. `OR` between two terms keeps symbols matching either and binds tighter
+//! than adjacency, so `lang:rust kind:function OR kind:method` keeps Rust functions
+//! and methods. A term is negated with a leading `-` or `NOT`, and terms are grouped
+//! with parentheses: `-(path:tests/ OR path:*_test.go)`.
+//!
+//! - `kind:function,method`: the symbol kind is one of the list
+//! - `lang:ts`: the language
+//! - `path:src/api/`: the file path contains the text; with `*`, `**`, `?` or `[...]`
+//!   it is a glob over the whole path, or over the file name when it has no `/`
+//! - `file:*.ts`: the file path ends with (`*x`), starts with (`x*`) or contains the text
+//! - `exported:true`
+//! - `calls:parseConfig`: the symbol calls a symbol of that name (or qualified name)
+//! - `implements:Repository`: the symbol implements or extends it
+//! - `decorated:Controller`: the symbol carries that decorator
+//! - `modified:<7d`: its file changed within the last 7 days (`>7d`: not since);
+//!   units are `m`, `h`, `d` and `w`
+//!
+//! Paths and kinds match regardless of case. [`Filter::matches`] is the one definition
+//! of what a filter keeps, judged on symbol rows and graph facts read from SQLite.
+//! Tantivy, LanceDB and the SQLite scan of filter-only queries get relaxed forms of
+//! the filter ([`Filter::tantivy_query`], [`Filter::lance_predicate`],
+//! [`Filter::sqlite_condition`]) that never exclude a symbol it keeps; they only keep
+//! symbols the filter drops from crowding out the candidate pools.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use regex::Regex;
+use tantivy::query::{
+    AllQuery, BooleanQuery, ConstScoreQuery, Occur, Query, RegexQuery, TermQuery,
+};
+use tantivy::schema::{IndexRecordOption, Schema};
+use tantivy::Term;
+
+use crate::storage::sqlite::{SqliteStore, SymbolHeaderRow};
+
+/// Keys of filter terms; other `key:value` tokens are free text.
+const FILTER_KEYS: &[&str] = &[
+    "kind",
+    "lang",
+    "language",
+    "path",
+    "file",
+    "exported",
+    "calls",
+    "implements",
+    "decorated",
+    "modified",
+];
+
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Term(Predicate),
+    Not(Box<Filter>),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Predicate {
+    /// Lowercase kinds, any of which matches
+    Kind(Vec<String>),
+    /// Language names as stored, any of which matches
+    Lang(Vec<String>),
+    Path(PathPattern),
+    Exported(bool),
+    Calls(String),
+    Implements(String),
+    Decorated(String),
+    /// The file changed within `seconds` (or, when `within` is false, did not)
+    Modified {
+        within: bool,
+        seconds: i64,
+    },
+}
+
+/// A case-insensitive pattern over file paths.
+#[derive(Debug, Clone)]
+pub struct PathPattern {
+    /// Regex matching whole paths, without anchors or flags, as Tantivy takes it
+    body: String,
+    regex: Regex,
+    /// `LIKE` pattern over lowercased paths, matching at least the same paths
+    like: String,
+    /// Whether `like` matches exactly the same paths
+    like_exact: bool,
+}
+
+/// Graph and file facts the predicates of one filter need, read from SQLite.
+#[derive(Debug, Default)]
+pub struct FilterFacts {
+    /// Ids of the symbols a `calls:`, `implements:` or `decorated:` term holds for
+    symbols: HashMap<String, HashSet<String>>,
+    /// Files modified within each age of a `modified:` term, in seconds
+    recent_files: HashMap<i64, HashSet<String>>,
+}
+
+/// The filter of a query, its parse errors, and the plain top-level terms.
+#[derive(Debug, Default)]
+pub struct ParsedFilter {
+    pub filter: Option<Filter>,
+    pub errors: Vec<String>,
+    /// `(key, value)` of terms that are neither negated nor part of an `OR` or a group
+    pub plain_terms: Vec<(String, String)>,
+    /// Indices of the tokens that are free text
+    pub text_tokens: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    Open,
+    Close,
+    Or,
+    Not,
+    Term {
+        key: String,
+        value: String,
+        predicate: Result<Predicate, String>,
+    },
+}
+
+/// Parse the filter terms among whitespace-separated query `tokens`.
+pub fn parse_filter(tokens: &[&str]) -> ParsedFilter {
+    enum Token {
+        Text,
+        Operator(Item),
+        Filter(Vec<Item>),
+    }
+    let lexed: Vec<Token> = tokens
+        .iter()
+        .map(|&token| match token {
+            "OR" => Token::Operator(Item::Or),
+            "NOT" => Token::Operator(Item::Not),
+            _ => lex(token).map_or(Token::Text, Token::Filter),
+        })
+        .collect();
+
+    // `OR` and `NOT` are operators only next to filter terms; "read OR write" is text
+    let is_filter = |i: usize| matches!(lexed.get(i), Some(Token::Filter(_)));
+    let is_operator = |i: usize| match &lexed[i] {
+        Token::Operator(Item::Not) => is_filter(i + 1),
+        Token::Operator(_) => {
+            i > 0
+                && is_filter(i - 1)
+                && (is_filter(i + 1)
+                    || (matches!(lexed.get(i + 1), Some(Token::Operator(Item::Not)))
+                        && is_filter(i + 2)))
+        }
+        _ => false,
+    };
+
+    let mut parsed = ParsedFilter::default();
+    let mut items = Vec::new();
+    for (i, token) in lexed.iter().enumerate() {
+        match token {
+            Token::Filter(lexed_items) => items.extend(lexed_items.iter().cloned()),
+            Token::Operator(op) if is_operator(i) => items.push(op.clone()),
+            _ => parsed.text_tokens.push(i),
+        }
+    }
+
+    let mut parser = Parser {
+        items,
+        pos: 0,
+        errors: Vec::new(),
+    };
+    let (filter, plain_terms) = parser.sequence(false);
+    parsed.filter = filter;
+    parsed.plain_terms = plain_terms;
+    parsed.errors = parser.errors;
+    parsed
+}
+
+/// The items of one token, or `None` when it is free text. Parentheses around a
+/// term are split off it.
+fn lex(token: &str) -> Option<Vec<Item>> {
+    let mut items = Vec::new();
+    let mut rest = token;
+    loop {
+        if let Some(after) = rest.strip_prefix('(') {
+            items.push(Item::Open);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("-(").or_else(|| rest.strip_prefix("!(")) {
+            items.extend([Item::Not, Item::Open]);
+            rest = after;
+        } else {
+            break;
+        }
+    }
+    let core = rest.trim_end_matches(')');
+    let closes = rest.len() - core.len();
+    if core.is_empty() {
+        // A bare `(`, `-(` or `)`, but not `()`
+        return (items.is_empty() != (closes == 0)).then(|| {
+            items.extend((0..closes).map(|_| Item::Close));
+            items
+        });
+    }
+
+    let (negated, term) = match core.strip_prefix('-').or_else(|| core.strip_prefix('!')) {
+        Some(after) => (true, after),
+        None => (false, core),
+    };
+    let (key, value) = term.split_once(':')?;
+    let key = key.to_lowercase();
+    let value = value.trim_matches('"').trim_matches('\'');
+    if !FILTER_KEYS.contains(&key.as_str()) || value.is_empty() {
+        return None;
+    }
+
+    if negated {
+        items.push(Item::Not);
+    }
+    items.push(Item::Term {
+        predicate: parse_predicate(&key, value).map_err(|e| format!("{key}:{value}: {e}")),
+        key,
+        value: value.to_string(),
+    });
+    items.extend((0..closes).map(|_| Item::Close));
+    Some(items)
+}
+
+fn parse_predicate(key: &str, value: &str) -> Result<Predicate, String> {
+    let name = || {
+        let name = value.trim_start_matches('@').replace("::", ".");
+        if name.is_empty() {
+            Err("expected a symbol name".to_string())
+        } else {
+            Ok(name)
+        }
+    };
+    match key {
+        "kind" => {
+            let kinds: Vec<String> = value
+                .split(',')
+                .map(|k| k.trim().to_lowercase())
+                .filter(|k| !k.is_empty())
+                .collect();
+            if kinds.is_empty() {
+                return Err("expected a kind such as function or class".to_string());
+            }
+            Ok(Predicate::Kind(kinds))
+        }
+        "lang" | "language" => Ok(Predicate::Lang(language_names(value))),
+        "path" => PathPattern::path(value).map(Predicate::Path),
+        "file" => PathPattern::file(value).map(Predicate::Path),
+        "exported" => match value.to_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok(Predicate::Exported(true)),
+            "false" | "no" | "0" => Ok(Predicate::Exported(false)),
+            _ => Err("expected true or false".to_string()),
+        },
+        "calls" => name().map(Predicate::Calls),
+        "implements" => name().map(Predicate::Implements),
+        "decorated" => name().map(Predicate::Decorated),
+        "modified" => parse_age(value),
+        _ => Err("unknown filter".to_string()),
+    }
+}
+
+/// `<7d`, `7d` (within the last 7 days) or `>7d` (not within them).
+fn parse_age(value: &str) -> Result<Predicate, String> {
+    let error = || "expected an age such as <7d or >12h (units m, h, d, w)".to_string();
+    let (within, age) = match value.as_bytes().first() {
+        Some(b'<') => (true, &value[1..]),
+        Some(b'>') => (false, &value[1..]),
+        _ => (true, value),
+    };
+    let age = age.trim_start_matches('=');
+    let split = age.find(|c: char| !c.is_ascii_digit()).ok_or_else(error)?;
+    let amount: i64 = age[..split].parse().map_err(|_| error())?;
+    let unit = match &age[split..] {
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return Err(error()),
+    };
+    Ok(Predicate::Modified {
+        within,
+        seconds: amount.saturating_mul(unit),
+    })
+}
+
+/// The stored language names a `lang:` value stands for.
+fn language_names(value: &str) -> Vec<String> {
+    let names: &[&str] = match value.trim().to_lowercase().as_str() {
+        "ts" | "typescript" => &["typescript", "tsx"],
+        "js" | "jsx" | "javascript" => &["javascript"],
+        "py" => &["python"],
+        "rs" => &["rust"],
+        "golang" => &["go"],
+        "c++" | "cc" => &["cpp"],
+        "c#" | "cs" => &["csharp"],
+        "kt" => &["kotlin"],
+        "rb" => &["ruby"],
+        "md" => &["markdown"],
+        "yml" => &["yaml"],
+        "proto" => &["protobuf"],
+        other => return vec![other.to_string()],
+    };
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+struct Parser {
+    items: Vec<Item>,
+    pos: usize,
+    errors: Vec<String>,
+}
+
+type Operand = (Option<Filter>, Option<(String, String)>);
+
+impl Parser {
+    fn peek(&self) -> Option<&Item> {
+        self.items.get(self.pos)
+    }
+
+    /// Adjacent operands, up to the end or, when `nested`, the closing parenthesis.
+    fn sequence(&mut self, nested: bool) -> (Option<Filter>, Vec<(String, String)>) {
+        let mut operands = Vec::new();
+        let mut plain = Vec::new();
+        loop {
+            match self.peek() {
+                None => {
+                    if nested {
+                        self.errors.push("missing ')'".to_string());
+                    }
+                    break;
+                }
+                Some(Item::Close) => {
+                    self.pos += 1;
+                    if nested {
+                        break;
+                    }
+                    self.errors.push("unmatched ')'".to_string());
+                    continue;
+                }
+                Some(Item::Or) => {
+                    self.pos += 1;
+                    self.errors
+                        .push("OR needs a filter term on each side".to_string());
+                    continue;
+                }
+                _ => {}
+            }
+            let (operand, term) = self.alternatives();
+            operands.extend(operand);
+            plain.extend(term);
+        }
+        (combine(operands, Filter::And), plain)
+    }
+
+    fn alternatives(&mut self) -> Operand {
+        let first = self.unary();
+        if !matches!(self.peek(), Some(Item::Or)) {
+            return first;
+        }
+        let mut alternatives: Vec<Filter> = first.0.into_iter().collect();
+        while matches!(self.peek(), Some(Item::Or)) {
+            self.pos += 1;
+            if matches!(self.peek(), None | Some(Item::Close) | Some(Item::Or)) {
+                self.errors
+                    .push("OR needs a filter term on each side".to_string());
+                break;
+            }
+            alternatives.extend(self.unary().0);
+        }
+        (combine(alternatives, Filter::Or), None)
+    }
+
+    fn unary(&mut self) -> Operand {
+        let item = self.peek().cloned();
+        self.pos += 1;
+        match item {
+            Some(Item::Not) => {
+                if matches!(self.peek(), None | Some(Item::Close) | Some(Item::Or)) {
+                    self.errors
+                        .push("NOT needs a filter term after it".to_string());
+                    return (None, None);
+                }
+                let (inner, _) = self.unary();
+                (inner.map(|f| Filter::Not(Box::new(f))), None)
+            }
+            Some(Item::Open) => (self.sequence(true).0, None),
+            Some(Item::Term {
+                key,
+                value,
+                predicate,
+            }) => match predicate {
+                Ok(predicate) => (Some(Filter::Term(predicate)), Some((key, value))),
+                Err(e) => {
+                    self.errors.push(e);
+                    (None, None)
+                }
+            },
+            _ => (None, None),
+        }
+    }
+}
+
+fn combine<T>(mut parts: Vec<T>, join: fn(Vec<T>) -> T) -> Option<T> {
+    match parts.len() {
+        0 => None,
+        1 => parts.pop(),
+        _ => Some(join(parts)),
+    }
+}
+
+impl PathPattern {
+    /// `path:` semantics: a substring, or a glob when the text has glob syntax.
+    pub(super) fn path(source: &str) -> Result<Self, String> {
+        if source.contains(['*', '?', '[']) {
+            return Self::glob(source.trim_start_matches('/'));
+        }
+        let literal = regex::escape(source);
+        Self::new(
+            format!(".*{literal}.*"),
+            format!("%{}%", source.to_lowercase()),
+        )
+    }
+
+    /// `file:` semantics: a leading `*` matches the end of the path, a trailing one its
+    /// start, and otherwise the text is a substring.
+    fn file(source: &str) -> Result<Self, String> {
+        let text = source.trim_matches('*');
+        let (literal, lower) = (regex::escape(text), text.to_lowercase());
+        let (body, like) = match (source.starts_with('*'), source.ends_with('*')) {
+            (true, false) => (format!(".*{literal}"), format!("%{lower}")),
+            (false, true) => (format!("{literal}.*"), format!("{lower}%")),
+            _ => (format!(".*{literal}.*"), format!("%{lower}%")),
+        };
+        Self::new(body, like)
+    }
+
+    /// A glob where `*` and `?` stay within a path segment and `**` spans segments.
+    fn glob(glob: &str) -> Result<Self, String> {
+        let chars: Vec<char> = glob.chars().collect();
+        let mut body = String::new();
+        let mut like = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    i += 2;
+                    if chars.get(i) == Some(&'/') {
+                        body.push_str("(?:.*/)?");
+                        i += 1;
+                    } else {
+                        body.push_str(".*");
+                    }
+                    like.push('%');
+                    continue;
+                }
+                '*' => {
+                    body.push_str("[^/]*");
+                    like.push('%');
+                }
+                '?' => {
+                    body.push_str("[^/]");
+                    like.push('_');
+                }
+                '[' => {
+                    let close = chars[i + 1..]
+                        .iter()
+                        .position(|&c| c == ']')
+                        .map(|at| i + 1 + at)
+                        .ok_or_else(|| "unclosed '[' in glob".to_string())?;
+                    let class: String = chars[i + 1..close].iter().collect();
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{rest}"),
+                        None => class,
+                    };
+                    if class.is_empty() || class == "^" {
+                        return Err("empty '[]' in glob".to_string());
+                    }
+                    body.push('[');
+                    body.push_str(&class.replace('\\', "\\\\"));
+                    body.push(']');
+                    like.push('_');
+                    i = close + 1;
+                    continue;
+                }
+                c => {
+                    body.push_str(&regex::escape(c.encode_utf8(&mut [0; 4])));
+                    like.extend(c.to_lowercase());
+                }
+            }
+            i += 1;
+        }
+        if !glob.contains('/') {
+            body = format!("(?:.*/)?{body}");
+            like = format!("%{like}");
+        }
+        let mut pattern = Self::new(body, like)?;
+        pattern.like_exact = false;
+        Ok(pattern)
+    }
+
+    fn new(body: String, like: String) -> Result<Self, String> {
+        let regex = Regex::new(&format!("(?i)^(?:{body})$"))
+            .map_err(|e| format!("invalid pattern: {e}"))?;
+        // `_` and `%` of the path itself are wildcards to LIKE
+        let like_exact = !like.trim_matches('%').contains(['%', '_']);
+        Ok(Self {
+            body,
+            regex,
+            like,
+            like_exact,
+        })
+    }
+
+    pub(super) fn matches(&self, file_path: &str) -> bool {
+        self.regex.is_match(file_path)
+    }
+}
+
+impl Predicate {
+    fn matches(&self, symbol: &SymbolHeaderRow, facts: &FilterFacts) -> bool {
+        match self {
+            Predicate::Kind(kinds) => kinds.iter().any(|k| symbol.kind.eq_ignore_ascii_case(k)),
+            Predicate::Lang(names) => names
+                .iter()
+                .any(|n| symbol.language.eq_ignore_ascii_case(n)),
+            Predicate::Path(pattern) => pattern.matches(&symbol.file_path),
+            Predicate::Exported(exported) => symbol.exported == *exported,
+            Predicate::Calls(_) | Predicate::Implements(_) | Predicate::Decorated(_) => facts
+                .symbols
+                .get(&self.fact_key())
+                .is_some_and(|ids| ids.contains(&symbol.id)),
+            Predicate::Modified { within, seconds } => {
+                let recent = facts
+                    .recent_files
+                    .get(seconds)
+                    .is_some_and(|files| files.contains(&symbol.file_path));
+                recent == *within
+            }
+        }
+    }
+
+    fn fact_key(&self) -> String {
+        match self {
+            Predicate::Calls(name) => format!("calls:{name}"),
+            Predicate::Implements(name) => format!("implements:{name}"),
+            Predicate::Decorated(name) => format!("decorated:{name}"),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Filter {
+    /// Whether the filter keeps `symbol`; `facts` must be loaded for this filter.
+    pub fn matches(&self, symbol: &SymbolHeaderRow, facts: &FilterFacts) -> bool {
+        match self {
+            Filter::Term(predicate) => predicate.matches(symbol, facts),
+            Filter::Not(inner) => !inner.matches(symbol, facts),
+            Filter::And(parts) => parts.iter().all(|f| f.matches(symbol, facts)),
+            Filter::Or(parts) => parts.iter().any(|f| f.matches(symbol, facts)),
+        }
+    }
+
+    fn predicates(&self) -> Vec<&Predicate> {
+        match self {
+            Filter::Term(predicate) => vec![predicate],
+            Filter::Not(inner) => inner.predicates(),
+            Filter::And(parts) | Filter::Or(parts) => {
+                parts.iter().flat_map(|f| f.predicates()).collect()
+            }
+        }
+    }
+
+    /// A Tantivy query that matches at least every document the filter keeps, scoring
+    /// nothing; `None` when it cannot narrow anything down. Kinds, paths and the
+    /// exported flag are indexed; other predicates are left to [`Filter::matches`].
+    pub fn tantivy_query(&self, schema: &Schema) -> Option<Box<dyn Query>> {
+        let kind = schema.get_field("kind").ok()?;
+        let file_path = schema.get_field("file_path").ok()?;
+        let exported = schema.get_field("exported").ok()?;
+
+        let leaf = |predicate: &Predicate, negated: bool| -> Option<Box<dyn Query>> {
+            let query: Box<dyn Query> = match predicate {
+                Predicate::Kind(kinds) => {
+                    let alternatives: Vec<String> =
+                        kinds.iter().map(|k| regex::escape(k)).collect();
+                    let pattern = format!("(?i)(?:{})", alternatives.join("|"));
+                    Box::new(RegexQuery::from_pattern(&pattern, kind).ok()?)
+                }
+                Predicate::Path(pattern) => Box::new(
+                    RegexQuery::from_pattern(&format!("(?i){}", pattern.body), file_path).ok()?,
+                ),
+                Predicate::Exported(value) => Box::new(TermQuery::new(
+                    Term::from_field_u64(exported, u64::from(*value)),
+                    IndexRecordOption::Basic,
+                )),
+                _ => return None,
+            };
+            Some(if negated {
+                Box::new(BooleanQuery::new(vec![
+                    (Occur::Must, Box::new(AllQuery)),
+                    (Occur::MustNot, query),
+                ]))
+            } else {
+                query
+            })
+        };
+        let query = self.relax(
+            false,
+            &leaf,
+            |parts| {
+                Box::new(BooleanQuery::new(
+                    parts.into_iter().map(|q| (Occur::Must, q)).collect(),
+                ))
+            },
+            |parts| {
+                Box::new(BooleanQuery::new(
+                    parts.into_iter().map(|q| (Occur::Should, q)).collect(),
+                ))
+            },
+        )?;
+        Some(Box::new(ConstScoreQuery::new(query, 0.0)))
+    }
+
+    /// A LanceDB `only_if` predicate that holds for at least every row the filter
+    /// keeps; `None` when it cannot narrow anything down.
+    pub fn lance_predicate(&self) -> Option<String> {
+        let literal = |s: &str| format!("'{}'", s.replace('\'', "''"));
+        let list = |items: &[String]| {
+            items
+                .iter()
+                .map(|s| literal(s))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let leaf = |predicate: &Predicate, negated: bool| -> Option<String> {
+            let (sql, exact) = match predicate {
+                Predicate::Kind(kinds) => (format!("lower(kind) IN ({})", list(kinds)), true),
+                Predicate::Lang(names) => (format!("lower(language) IN ({})", list(names)), true),
+                Predicate::Path(pattern) => (
+                    format!("lower(file_path) LIKE {}", literal(&pattern.like)),
+                    pattern.like_exact,
+                ),
+                Predicate::Exported(value) => (format!("exported = {value}"), true),
+                _ => return None,
+            };
+            match (negated, exact) {
+                (false, _) => Some(sql),
+                (true, true) => Some(format!("NOT ({sql})")),
+                // The negation of a superset is a subset
+                (true, false) => None,
+            }
+        };
+        self.relax(
+            false,
+            &leaf,
+            |parts| format!("({})", parts.join(" AND ")),
+            |parts| format!("({})", parts.join(" OR ")),
+        )
+    }
+
+    /// A SQLite `WHERE` condition over `symbols` columns, with its `?` parameters, that
+    /// holds for at least every row the filter keeps; `None` when it cannot narrow
+    /// anything down.
+    pub fn sqlite_condition(&self) -> Option<(String, Vec<String>)> {
+        let list = |items: &[String]| vec!["?"; items.len()].join(", ");
+        let leaf = |predicate: &Predicate, negated: bool| -> Option<(String, Vec<String>)> {
+            let (sql, params, exact) = match predicate {
+                Predicate::Kind(kinds) => (
+                    format!("lower(kind) IN ({})", list(kinds)),
+                    kinds.clone(),
+                    true,
+                ),
+                Predicate::Lang(names) => (
+                    format!("lower(language) IN ({})", list(names)),
+                    names.clone(),
+                    true,
+                ),
+                Predicate::Path(pattern) => (
+                    "lower(file_path) LIKE ?".to_string(),
+                    vec![pattern.like.clone()],
+                    pattern.like_exact,
+                ),
+                Predicate::Exported(value) => (
+                    format!("exported = {}", i64::from(*value)),
+                    Vec::new(),
+                    true,
+                ),
+                _ => return None,
+            };
+            match (negated, exact) {
+                (false, _) => Some((sql, params)),
+                (true, true) => Some((format!("NOT ({sql})"), params)),
+                // The negation of a superset is a subset
+                (true, false) => None,
+            }
+        };
+        fn join(parts: Vec<(String, Vec<String>)>, op: &str) -> (String, Vec<String>) {
+            let (sql, params): (Vec<String>, Vec<Vec<String>>) = parts.into_iter().unzip();
+            (format!("({})", sql.join(op)), params.concat())
+        }
+        self.relax(
+            false,
+            &leaf,
+            |parts| join(parts, " AND "),
+            |parts| join(parts, " OR "),
+        )
+    }
+
+    /// Whether [`Filter::sqlite_condition`] keeps exactly the rows the filter keeps,
+    /// so that no row it returns is dropped by [`Filter::matches`].
+    pub fn sqlite_exact(&self) -> bool {
+        self.predicates().iter().all(|predicate| match predicate {
+            Predicate::Kind(_) | Predicate::Lang(_) | Predicate::Exported(_) => true,
+            Predicate::Path(pattern) => pattern.like_exact,
+            _ => false,
+        })
+    }
+
+    /// Rewrite the filter, with negations pushed down to the predicates, for a backend
+    /// that can express some predicates (`leaf`, given the predicate and whether it is
+    /// negated). Inexpressible predicates are relaxed to "anything", so the result
+    /// holds for at least what the filter keeps. `None` stands for "anything".
+    fn relax<T>(
+        &self,
+        negated: bool,
+        leaf: &dyn Fn(&Predicate, bool) -> Option<T>,
+        all: fn(Vec<T>) -> T,
+        any: fn(Vec<T>) -> T,
+    ) -> Option<T> {
+        match self {
+            Filter::Term(predicate) => leaf(predicate, negated),
+            Filter::Not(inner) => inner.relax(!negated, leaf, all, any),
+            Filter::And(parts) | Filter::Or(parts) => {
+                let conjunction = matches!(self, Filter::And(_)) != negated;
+                let parts = parts.iter().map(|f| f.relax(negated, leaf, all, any));
+                if conjunction {
+                    combine(parts.flatten().collect(), all)
+                } else {
+                    combine(parts.collect::<Option<Vec<T>>>()?, any)
+                }
+            }
+        }
+    }
+}
+
+impl FilterFacts {
+    /// Read the facts the predicates of `filter` need.
+    pub fn load(sqlite: &SqliteStore, filter: &Filter, now_unix_s: i64) -> Result<Self> {
+        let mut facts = Self::default();
+        for predicate in filter.predicates() {
+            let ids = match predicate {
+                Predicate::Calls(name) => sqlite.list_symbol_ids_with_edge_to(&["call"], name)?,
+                Predicate::Implements(name) => {
+                    sqlite.list_symbol_ids_with_edge_to(&["implements", "extends"], name)?
+                }
+                Predicate::Decorated(name) => sqlite.list_symbol_ids_with_decorator(name)?,
+                Predicate::Modified { seconds, .. } => {
+                    if !facts.recent_files.contains_key(seconds) {
+                        let files = sqlite.list_files_modified_since(now_unix_s - seconds)?;
+                        facts
+                            .recent_files
+                            .insert(*seconds, files.into_iter().collect());
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            facts
+                .symbols
+                .insert(predicate.fact_key(), ids.into_iter().collect());
+        }
+        Ok(facts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> ParsedFilter {
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+        parse_filter(&tokens)
+    }
+
+    fn symbol(kind: &str, file_path: &str, exported: bool) -> SymbolHeaderRow {
+        SymbolHeaderRow {
+            id: format!("{file_path}#{kind}"),
+            file_path: file_path.to_string(),
+            language: "typescript".to_string(),
+            kind: kind.to_string(),
+            name: "x".to_string(),
+            exported,
+            start_byte: 0,
+            end_byte: 1,
+            start_line: 1,
+            end_line: 1,
+            qualified_name: "x".to_string(),
+            parent_id: None,
+        }
+    }
+
+    fn keeps(query: &str, symbol: &SymbolHeaderRow) -> bool {
+        let parsed = parse(query);
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        parsed
+            .filter
+            .unwrap()
+            .matches(symbol, &FilterFacts::default())
+    }
+
+    #[test]
+    fn or_binds_tighter_than_adjacent_terms() {
+        let query = "lang:ts kind:function OR kind:method -path:tests/";
+        let method = symbol("method", "src/api/users.ts", false);
+        assert!(keeps(query, &method));
+        assert!(!keeps(query, &symbol("class", "src/api/users.ts", false)));
+        assert!(!keeps(query, &symbol("method", "tests/users.ts", false)));
+
+        let parsed = parse("find users kind:function OR kind:method");
+        assert_eq!(parsed.text_tokens, vec![0, 1]);
+        assert!(parsed.plain_terms.is_empty());
+        // Between free text, OR and NOT are text
+        assert_eq!(parse("read OR write kind:class").text_tokens, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn groups_negation_and_globs() {
+        let query = "-(path:**/*_test.go OR path:testdata/) exported:true";
+        assert!(keeps(
+            query,
+            &symbol("function", "pkg/server/handler.go", true)
+        ));
+        assert!(!keeps(
+            query,
+            &symbol("function", "pkg/server/handler.go", false)
+        ));
+        assert!(!keeps(
+            query,
+            &symbol("function", "pkg/server/handler_test.go", true)
+        ));
+        assert!(!keeps(
+            query,
+            &symbol("function", "pkg/testdata/x.go", true)
+        ));
+
+        assert!(keeps(
+            "path:src/*.rs",
+            &symbol("struct", "src/Lib.rs", false)
+        ));
+        assert!(!keeps(
+            "path:src/*.rs",
+            &symbol("struct", "src/a/lib.rs", false)
+        ));
+        assert!(keeps("path:*.rs", &symbol("struct", "src/a/lib.rs", false)));
+        assert!(keeps(
+            "NOT file:*.md",
+            &symbol("struct", "src/a/lib.rs", false)
+        ));
+        assert_eq!(
+            parse("kind:class file:src/*").plain_terms,
+            vec![
+                ("kind".to_string(), "class".to_string()),
+                ("file".to_string(), "src/*".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let parsed = parse("exported:maybe modified:soon (kind:class path:[a");
+        assert_eq!(parsed.errors.len(), 4, "{:?}", parsed.errors);
+        assert!(parsed.errors[0].starts_with("exported:maybe:"));
+        assert!(parsed.errors.iter().any(|e| e == "missing ')'"));
+        // The valid term still filters
+        assert!(parsed.filter.is_some());
+
+        assert_eq!(parse("(kind:class OR ) kind:method").errors.len(), 1);
+        assert_eq!(parse("kind:class)").errors, vec!["unmatched ')'"]);
+        assert!(matches!(
+            parse("modified:>2w").filter,
+            Some(Filter::Term(Predicate::Modified {
+                within: false,
+                seconds: 1_209_600
+            }))
+        ));
+    }
+
+    #[test]
+    fn backend_pushdowns_relax_what_they_cannot_express() {
+        let filter = parse("kind:function calls:parseConfig -path:**/*.test.ts")
+            .filter
+            .unwrap();
+        assert_eq!(
+            filter.lance_predicate().as_deref(),
+            Some("lower(kind) IN ('function')")
+        );
+        let filter = parse("calls:parseConfig OR kind:method").filter.unwrap();
+        assert_eq!(filter.lance_predicate(), None);
+        let filter = parse("-(path:src/ exported:false) lang:go").filter.unwrap();
+        assert_eq!(
+            filter.lance_predicate().as_deref(),
+            Some(
+                "((NOT (lower(file_path) LIKE '%src/%') OR NOT (exported = false)) AND lower(language) IN ('go'))"
+            )
+        );
+    }
+
+    #[test]
+    fn sqlite_condition_binds_values_and_knows_when_it_is_exact() {
+        let filter = parse("kind:function,method -path:tests/ exported:true")
+            .filter
+            .unwrap();
+        let (sql, params) = filter.sqlite_condition().unwrap();
+        assert_eq!(
+            sql,
+            "(lower(kind) IN (?, ?) AND NOT (lower(file_path) LIKE ?) AND exported = 1)"
+        );
+        assert_eq!(params, vec!["function", "method", "%tests/%"]);
+        assert!(filter.sqlite_exact());
+
+        let filter = parse("lang:rust calls:parseConfig").filter.unwrap();
+        assert_eq!(filter.sqlite_condition().unwrap().1, vec!["rust"]);
+        assert!(!filter.sqlite_exact());
+
+        let filter = parse("path:src/*_test.go").filter.unwrap();
+        assert!(filter.sqlite_condition().is_some());
+        assert!(!filter.sqlite_exact());
+    }
+}
@@ -2,6 +2,8 @@
 
 pub mod assembler;
 mod cache;
+pub mod content;
+mod filter;
 pub mod hyde;
 mod query;
 mod ranking;
@@ -15,23 +17,25 @@ use crate::{
     reranker::Reranker,
     retrieval::assembler::{ContextAssembler, ContextItem},
     storage::{
-        sqlite::{SqliteStore, SymbolRow},
+        sqlite::{SqliteStore, SymbolHeaderRow, SymbolRow},
         tantivy::TantivyIndex,
-        vector::LanceVectorTable,
+        vector::{LanceVectorTable, VectorHit},
     },
 };
 use anyhow::{anyhow, Result};
 use cache::RetrieverCaches;
+use filter::{Filter, FilterFacts};
 use query::{
     contains_code_snippet, decompose_query, detect_intent, normalize_and_expand_query,
-    parse_query_controls, trim_query, Intent, QueryControls,
+    parse_query_controls, trim_query, Intent,
 };
 use ranking::{
     apply_docstring_boost_with_signals, apply_file_affinity_boost_with_signals,
-    apply_package_boost_with_signals, apply_popularity_boost_with_signals, apply_reranker_scores,
-    apply_selection_boost_with_signals, diversify_by_cluster, diversify_by_file, diversify_by_kind,
-    expand_with_edges, get_graph_ranked_hits, prepare_rerank_docs, rank_hits_with_signals,
-    reciprocal_rank_fusion, should_rerank,
+    apply_history_boost_with_signals, apply_package_boost_with_signals,
+    apply_popularity_boost_with_signals, apply_reranker_scores, apply_selection_boost_with_signals,
+    diversify_by_cluster, diversify_by_file, diversify_by_kind, expand_with_edges,
+    get_graph_ranked_hits, prepare_rerank_docs, rank_hits_with_signals, reciprocal_rank_fusion,
+    should_rerank,
 };
 use serde::Serialize;
 use std::{
@@ -71,6 +75,8 @@ pub struct SearchResponse {
 pub struct SearchResponseWithSignals {
     pub response: SearchResponse,
     pub hit_signals: HashMap<String, HitSignals>,
+    /// Why filter terms of the query were ignored, one message per problem
+    pub query_errors: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -86,6 +92,7 @@ pub struct HitSignals {
     pub affinity_boost: f32,
     pub docstring_boost: f32,
     pub package_boost: f32,
+    pub history_boost: f32,
 }
 
 #[derive(Clone)]
@@ -160,6 +167,8 @@ impl Retriever {
             .ok()
             .flatten()
             .map(|r| r.started_at_unix_s);
+        let (query_without_controls, controls) = parse_query_controls(query);
+
         let cache_key = format!(
             "v2|cfg={}|q={}|l={}|e={}",
             self.cache_config_key,
@@ -182,12 +191,11 @@ impl Retriever {
                 return Ok(SearchResponseWithSignals {
                     response: resp,
                     hit_signals: HashMap::new(),
+                    query_errors: controls.errors.clone(),
                 });
             }
         }
 
-        let (query_without_controls, controls) = parse_query_controls(query);
-
         if let Some(id) = &controls.id {
             if let Some(row) = sqlite.get_symbol_by_id(id)? {
                 if exported_only && !row.exported {
@@ -199,6 +207,7 @@ impl Retriever {
                             context: String::new(),
                         },
                         hit_signals: HashMap::new(),
+                        query_errors: controls.errors.clone(),
                     });
                 }
 
@@ -243,10 +252,19 @@ impl Retriever {
                 return Ok(SearchResponseWithSignals {
                     response: resp,
                     hit_signals: HashMap::new(),
+                    query_errors: controls.errors.clone(),
                 });
             }
         }
 
+        // Backends narrow their candidates with relaxed forms of the filter; the
+        // filter itself is applied to the merged hits
+        let tantivy_filter = controls
+            .filter
+            .as_ref()
+            .and_then(|f| f.tantivy_query(&self.tantivy.schema()));
+        let lance_filter = controls.filter.as_ref().and_then(Filter::lance_predicate);
+
         // Intent Detection
         let intent = detect_intent(&query_without_controls);
 
@@ -265,7 +283,7 @@ impl Retriever {
         let smart_truncation_query = if sub_queries.len() == 1 {
             Some(query_without_controls.as_str())
         } else {
-            Some(sub_queries[0].as_str())
+            sub_queries.first().map(String::as_str)
         };
 
         if let Some(Intent::Callers(name)) = &intent {
@@ -298,6 +316,7 @@ impl Retriever {
                     }
                 }
 
+                let mut hits = Self::filter_hits(&sqlite, hits, controls.filter.as_ref())?;
                 if !hits.is_empty() {
                     hits.truncate(limit);
                     let rows = hits
@@ -336,15 +355,40 @@ impl Retriever {
                     return Ok(SearchResponseWithSignals {
                         response: resp,
                         hit_signals: HashMap::new(),
+                        query_errors: controls.errors.clone(),
                     });
                 }
             }
         }
 
         // Conditional: single-query path vs multi-query path based on decomposition
-        // Single query preserves existing behavior; multi-query uses unified RRF
+        // Single query preserves existing behavior; multi-query uses unified RRF.
+        // A query of filter terms alone takes its candidates from SQLite.
         let (ranked, mut hit_signals): (Vec<RankedHit>, HashMap<String, HitSignals>) =
-            if sub_queries.len() == 1 {
+            if let (None, Some(filter)) = (sub_queries.first(), &controls.filter) {
+                let ranked = Self::filtered_symbols(&sqlite, filter, limit * 3)?;
+                let mut hit_signals = HashMap::new();
+                for hit in &ranked {
+                    hit_signals.insert(
+                        hit.id.clone(),
+                        HitSignals {
+                            keyword_score: 0.0,
+                            vector_score: 0.0,
+                            base_score: hit.score,
+                            structural_adjust: 0.0,
+                            intent_mult: 1.0,
+                            definition_bias: 0.0,
+                            popularity_boost: 0.0,
+                            learning_boost: 0.0,
+                            affinity_boost: 0.0,
+                            docstring_boost: 0.0,
+                            package_boost: 0.0,
+                            history_boost: 0.0,
+                        },
+                    );
+                }
+                (ranked, hit_signals)
+            } else if sub_queries.len() == 1 {
                 // SINGLE-QUERY PATH: Use existing logic unchanged
                 let search_query = &sub_queries[0];
 
@@ -356,7 +400,9 @@ impl Retriever {
                     self.config.vector_search_limit.max(limit * 3).max(40)
                 };
                 let keyword_t = Instant::now();
-                let keyword_hits = self.tantivy.search(search_query, k)?;
+                let keyword_hits =
+                    self.tantivy
+                        .search_filtered(search_query, k, tantivy_filter.as_deref())?;
                 let _keyword_ms = keyword_t.elapsed().as_millis().min(u64::MAX as u128) as u64;
 
                 let vector_t = Instant::now();
@@ -364,7 +410,7 @@ impl Retriever {
                 // Vector search with graceful degradation
                 let (vector_hits, _vector_degraded) = match self.get_query_vector_cached(search_query).await {
                     Ok(query_vector) => {
-                        match self.vectors.search(&query_vector, k).await {
+                        match self.vector_search(&query_vector, k, lance_filter.as_deref()).await {
                             Ok(mut hits) => {
                                 // HyDE: Add hypothetical document retrieval (best-effort)
                                 if self.config.hyde_enabled {
@@ -484,6 +530,7 @@ impl Retriever {
                                 affinity_boost: 0.0,
                                 docstring_boost: 0.0,
                                 package_boost: 0.0,
+                                history_boost: 0.0,
                             },
                         );
                     }
@@ -510,14 +557,16 @@ impl Retriever {
 
                 for sub_query in &sub_queries {
                     // Keyword search for this sub-query
-                    let sub_keyword_hits = self.tantivy.search(sub_query, k)?;
+                    let sub_keyword_hits =
+                        self.tantivy
+                            .search_filtered(sub_query, k, tantivy_filter.as_deref())?;
                     combined_keyword_hits.extend(sub_keyword_hits);
 
                     // Vector search for this sub-query with graceful degradation
                     // Each sub-query degrades independently - one failure doesn't affect others
                     let sub_vector_hits = match self.get_query_vector_cached(sub_query).await {
                         Ok(query_vector) => {
-                            match self.vectors.search(&query_vector, k).await {
+                            match self.vector_search(&query_vector, k, lance_filter.as_deref()).await {
                                 Ok(mut hits) => {
                                     // HyDE for this sub-query (best-effort)
                                     if self.config.hyde_enabled {
@@ -631,6 +680,7 @@ impl Retriever {
                             affinity_boost: 0.0,
                             docstring_boost: 0.0,
                             package_boost: 0.0,
+                            history_boost: 0.0,
                         },
                     );
                 }
@@ -660,7 +710,7 @@ impl Retriever {
             }
         }
 
-        let hits = Self::filter_hits_by_controls(uniq, &controls);
+        let hits = Self::filter_hits(&sqlite, uniq, controls.filter.as_ref())?;
         let hits = if exported_only {
             hits.into_iter().filter(|h| h.exported).collect::<Vec<_>>()
         } else {
@@ -670,6 +720,9 @@ impl Retriever {
         let hits =
             apply_popularity_boost_with_signals(&sqlite, hits, &mut hit_signals, &self.config)?;
 
+        let hits =
+            apply_history_boost_with_signals(&sqlite, hits, &mut hit_signals, &self.config)?;
+
         // Apply JSDoc documentation boost (1.5x for well-documented symbols)
         let hits = apply_docstring_boost_with_signals(&sqlite, hits, &mut hit_signals)?;
 
@@ -697,7 +750,7 @@ impl Retriever {
 
         // Apply cross-encoder reranking if available
         let mut hits = if let Some(reranker) = &self.reranker {
-            if should_rerank(hits.len(), 3) {
+            if should_rerank(hits.len(), 3) && !sub_queries.is_empty() {
                 // Collect symbol texts for reranking
                 let mut texts = HashMap::new();
                 for hit in &hits {
@@ -744,6 +797,9 @@ impl Retriever {
         let (context, _context_items) =
             self.assemble_context_cached(&sqlite, &roots, &extra, smart_truncation_query)?;
 
+        // Neighbours the filter drops still add context, but they are not hits
+        let hits = Self::filter_hits(&sqlite, hits, controls.filter.as_ref())?;
+
         let merge_ms = merge_t.elapsed().as_millis().min(u64::MAX as u128) as u64;
         let duration_ms = started.elapsed().as_millis().min(u64::MAX as u128) as u64;
 
@@ -775,6 +831,7 @@ impl Retriever {
         Ok(SearchResponseWithSignals {
             response: resp,
             hit_signals,
+            query_errors: controls.errors,
         })
     }
 
@@ -857,56 +914,77 @@ impl Retriever {
         Ok(v)
     }
 
-    fn filter_hits_by_controls(hits: Vec<RankedHit>, controls: &QueryControls) -> Vec<RankedHit> {
-        hits.into_iter()
-            .filter(|h| {
-                controls
-                    .lang
-                    .as_ref()
-                    .is_none_or(|l| h.language == l.as_str())
-            })
-            .filter(|h| {
-                controls
-                    .kind
-                    .as_ref()
-                    .is_none_or(|k| Self::kind_matches(&h.kind, k))
-            })
-            .filter(|h| {
-                controls
-                    .path
-                    .as_ref()
-                    .is_none_or(|p| Self::path_matches(&h.file_path, p))
-            })
-            .filter(|h| {
-                controls
-                    .file
-                    .as_ref()
-                    .is_none_or(|f| Self::file_matches(&h.file_path, f))
-            })
-            .collect()
-    }
-
-    fn kind_matches(kind: &str, control: &str) -> bool {
-        control
-            .split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .any(|k| kind.eq_ignore_ascii_case(k))
+    /// Keep the hits `filter` holds for, judged on their rows in SQLite.
+    fn filter_hits(
+        sqlite: &SqliteStore,
+        hits: Vec<RankedHit>,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<RankedHit>> {
+        let Some(filter) = filter else {
+            return Ok(hits);
+        };
+        let ids: Vec<String> = hits.iter().map(|h| h.id.clone()).collect();
+        let rows: HashMap<String, SymbolHeaderRow> = sqlite
+            .list_symbol_headers_by_ids(&ids)?
+            .into_iter()
+            .map(|row| (row.id.clone(), row))
+            .collect();
+        let facts = FilterFacts::load(sqlite, filter, unix_now_s())?;
+        Ok(hits
+            .into_iter()
+            .filter(|h| rows.get(&h.id).is_some_and(|row| filter.matches(row, &facts)))
+            .collect())
     }
 
-    fn path_matches(file_path: &str, control: &str) -> bool {
-        file_path.to_lowercase().contains(&control.to_lowercase())
+    /// Every symbol `filter` keeps, up to `limit`, for queries without free text. The
+    /// columns the filter tests are narrowed in SQLite, and the scan is cut at `limit`
+    /// there when nothing else is left to test.
+    fn filtered_symbols(
+        sqlite: &SqliteStore,
+        filter: &Filter,
+        limit: usize,
+    ) -> Result<Vec<RankedHit>> {
+        let facts = FilterFacts::load(sqlite, filter, unix_now_s())?;
+        let (condition, params) = filter
+            .sqlite_condition()
+            .unwrap_or_else(|| ("1".to_string(), Vec::new()));
+        let sql_limit = filter.sqlite_exact().then_some(limit);
+        Ok(sqlite
+            .list_symbol_headers_where(&condition, &params, sql_limit)?
+            .into_iter()
+            .filter(|row| filter.matches(row, &facts))
+            .take(limit)
+            .map(|row| RankedHit {
+                id: row.id,
+                score: 1.0,
+                name: row.name,
+                kind: row.kind,
+                file_path: row.file_path,
+                exported: row.exported,
+                language: row.language,
+            })
+            .collect())
     }
 
-    fn file_matches(file_path: &str, control: &str) -> bool {
-        let file_path = file_path.to_lowercase();
-        let control = control.to_lowercase();
-        match (control.starts_with('*'), control.ends_with('*')) {
-            (true, true) => file_path.contains(control.trim_matches('*')),
-            (true, false) => file_path.ends_with(control.trim_start_matches('*')),
-            (false, true) => file_path.starts_with(control.trim_end_matches('*')),
-            (false, false) => file_path.contains(&control),
+    /// Nearest neighbours of `vector`, narrowed by a LanceDB predicate when there is
+    /// one. A predicate LanceDB rejects is dropped rather than failing the search.
+    async fn vector_search(
+        &self,
+        vector: &[f32],
+        k: usize,
+        predicate: Option<&str>,
+    ) -> Result<Vec<VectorHit>> {
+        if let Some(predicate) = predicate {
+            match self.vectors.search_with_filter(vector, k, predicate).await {
+                Ok(hits) => return Ok(hits),
+                Err(e) => tracing::warn!(
+                    predicate = %predicate,
+                    error = %e,
+                    "LanceDB rejected the filter predicate, searching without it"
+                ),
+            }
         }
+        self.vectors.search(vector, k).await
     }
 
     /// Get reference to vector store for vector queries
@@ -1,15 +1,22 @@
 //! Query processing and normalization
 
+use super::filter::{parse_filter, Filter};
 use crate::text as text_module;
 
 #[derive(Debug, Clone, Default)]
 pub struct QueryControls {
     pub id: Option<String>,
+    /// Values of plain top-level `file:`, `path:`, `lang:` and `kind:` terms; what
+    /// the query keeps is decided by `filter`
     pub file: Option<String>,
     pub path: Option<String>,
     pub lang: Option<String>,
     pub kind: Option<String>,
     pub package: Option<String>,
+    /// All filter terms of the query, see [`super::filter`]
+    pub filter: Option<Filter>,
+    /// Why filter terms were ignored
+    pub errors: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -169,28 +176,33 @@ pub fn detect_intent(query: &str) -> Option<Intent> {
 /// Parse query controls (filters) from query string
 pub fn parse_query_controls(query: &str) -> (String, QueryControls) {
     let mut controls = QueryControls::default();
-    let mut kept = Vec::new();
+    let mut rest = Vec::new();
     for token in query.split_whitespace() {
         let Some((k, v)) = token.split_once(':') else {
-            kept.push(token);
+            rest.push(token);
             continue;
         };
-        let key = k.trim().to_lowercase();
         let value = v.trim().trim_matches('"').trim_matches('\'');
-        if value.is_empty() {
-            kept.push(token);
-            continue;
+        match k.trim().to_lowercase().as_str() {
+            "id" if !value.is_empty() => controls.id = Some(value.to_string()),
+            "package" | "pkg" if !value.is_empty() => controls.package = Some(value.to_string()),
+            _ => rest.push(token),
         }
+    }
+
+    let parsed = parse_filter(&rest);
+    for (key, value) in parsed.plain_terms {
         match key.as_str() {
-            "id" => controls.id = Some(value.to_string()),
-            "file" => controls.file = Some(value.to_string()),
-            "path" => controls.path = Some(value.to_string()),
-            "lang" | "language" => controls.lang = Some(normalize_lang(value)),
-            "kind" => controls.kind = Some(value.to_string()),
-            "package" | "pkg" => controls.package = Some(value.to_string()),
-            _ => kept.push(token),
+            "file" => controls.file = Some(value),
+            "path" => controls.path = Some(value),
+            "lang" | "language" => controls.lang = Some(normalize_lang(&value)),
+            "kind" => controls.kind = Some(value),
+            _ => {}
         }
     }
+    controls.filter = parsed.filter;
+    controls.errors = parsed.errors;
+    let kept: Vec<&str> = parsed.text_tokens.iter().map(|&i| rest[i]).collect();
     (kept.join(" "), controls)
 }
 
@@ -435,6 +447,25 @@ mod tests {
         assert_eq!(controls.file, Some("test.ts".to_string()));
     }
 
+    #[test]
+    fn parse_query_controls_builds_boolean_filters() {
+        let (query, controls) = parse_query_controls(
+            "user service kind:function OR kind:method -path:tests/ lang:ts modified:<soon",
+        );
+        assert_eq!(query, "user service");
+        assert!(controls.filter.is_some());
+        // Only plain top-level terms fill the single-valued fields
+        assert_eq!(controls.kind, None);
+        assert_eq!(controls.path, None);
+        assert_eq!(controls.lang, Some("typescript".to_string()));
+        assert_eq!(controls.errors.len(), 1);
+        assert!(controls.errors[0].starts_with("modified:<soon:"));
+        assert!(parse_query_controls("exported:true calls:parseConfig")
+            .1
+            .errors
+            .is_empty());
+    }
+
     #[test]
     fn detect_intent_recognizes_new_intents() {
         assert!(matches!(
@@ -12,7 +12,7 @@ pub use reranker::{apply_reranker_scores, prepare_rerank_docs, should_rerank};
 pub use rrf::{get_graph_ranked_hits, reciprocal_rank_fusion};
 pub use score::{
     apply_docstring_boost_with_signals, apply_file_affinity_boost_with_signals,
-    apply_popularity_boost_with_signals, apply_selection_boost_with_signals,
-    rank_hits_with_signals,
+    apply_history_boost_with_signals, apply_popularity_boost_with_signals,
+    apply_selection_boost_with_signals, rank_hits_with_signals,
 };
 
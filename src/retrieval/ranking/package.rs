@@ -110,6 +110,7 @@ pub fn apply_package_boost_with_signals(
                         affinity_boost: 0.0,
                         docstring_boost: 0.0,
                         package_boost: boost_amount,
+                        history_boost: 0.0,
                     });
             }
         }
@@ -187,11 +188,14 @@ mod tests {
             rank_test_penalty: 0.1,
             rank_popularity_weight: 0.1,
             rank_popularity_cap: 0,
+            rank_history_weight: 0.0,
+            history_max_commits: 0,
             index_patterns: vec!["**/*.ts".to_string()],
             exclude_patterns: vec!["**/node_modules/**".to_string()],
             watch_mode: false,
             watch_debounce_ms: 250,
             watch_min_index_interval_ms: 5000,
+            watch_backend: crate::config::WatchBackend::Auto,
             max_context_bytes: 200_000,
             index_node_modules: false,
             repo_roots: vec![],
@@ -222,6 +226,9 @@ mod tests {
             metrics_enabled: true,
             metrics_port: 9090,
             package_detection_enabled: true,
+            mcp_transport: crate::config::McpTransport::Stdio,
+            http_bind_addr: "127.0.0.1:8765".to_string(),
+            http_auth_token: None,
         }
     }
 
@@ -268,7 +275,8 @@ mod tests {
                 repository_id: "repo-123".to_string(),
                 name: "package-a".to_string(),
                 version: Some("1.0.0".to_string()),
-                manifest_path: "/path/to/repo/packages/a".to_string(),
+                manifest_path: "/path/to/repo/packages/a/package.json".to_string(),
+                root_path: Some("/path/to/repo/packages/a/".to_string()),
                 package_type: "npm".to_string(),
                 created_at: 1234567891,
             };
@@ -279,7 +287,8 @@ mod tests {
                 repository_id: "repo-123".to_string(),
                 name: "package-b".to_string(),
                 version: Some("1.0.0".to_string()),
-                manifest_path: "/path/to/repo/packages/b".to_string(),
+                manifest_path: "/path/to/repo/packages/b/package.json".to_string(),
+                root_path: Some("/path/to/repo/packages/b/".to_string()),
                 package_type: "npm".to_string(),
                 created_at: 1234567892,
             };
@@ -298,6 +307,8 @@ mod tests {
                 start_line: 1,
                 end_line: 10,
                 text: "export function helper() {}".to_string(),
+                qualified_name: "helper".to_string(),
+                parent_id: None,
             };
             sqlite.upsert_symbol(&symbol_a).unwrap();
 
@@ -313,6 +324,8 @@ mod tests {
                 start_line: 1,
                 end_line: 10,
                 text: "export function helper() {}".to_string(),
+                qualified_name: "helper".to_string(),
+                parent_id: None,
             };
             sqlite.upsert_symbol(&symbol_b).unwrap();
 
@@ -390,7 +403,8 @@ mod tests {
                 repository_id: "repo-123".to_string(),
                 name: "package-a".to_string(),
                 version: Some("1.0.0".to_string()),
-                manifest_path: "/path/to/repo/packages/a".to_string(),
+                manifest_path: "/path/to/repo/packages/a/package.json".to_string(),
+                root_path: Some("/path/to/repo/packages/a/".to_string()),
                 package_type: "npm".to_string(),
                 created_at: 1234567891,
             };
@@ -409,6 +423,8 @@ mod tests {
                 start_line: 1,
                 end_line: 10,
                 text: "export function helper() {}".to_string(),
+                qualified_name: "helper".to_string(),
+                parent_id: None,
             };
             sqlite.upsert_symbol(&symbol_a).unwrap();
 
@@ -472,7 +488,8 @@ mod tests {
                 repository_id: "repo-123".to_string(),
                 name: "package-a".to_string(),
                 version: Some("1.0.0".to_string()),
-                manifest_path: "/path/to/repo/packages/a".to_string(),
+                manifest_path: "/path/to/repo/packages/a/package.json".to_string(),
+                root_path: Some("/path/to/repo/packages/a/".to_string()),
                 package_type: "npm".to_string(),
                 created_at: 1234567891,
             };
@@ -491,6 +508,8 @@ mod tests {
                 start_line: 1,
                 end_line: 10,
                 text: "export function helper() {}".to_string(),
+                qualified_name: "helper".to_string(),
+                parent_id: None,
             };
             sqlite.upsert_symbol(&symbol_a).unwrap();
 
@@ -574,7 +593,8 @@ mod tests {
                 repository_id: "repo-123".to_string(),
                 name: "package-a".to_string(),
                 version: Some("1.0.0".to_string()),
-                manifest_path: "/path/to/repo/packages/a".to_string(),
+                manifest_path: "/path/to/repo/packages/a/package.json".to_string(),
+                root_path: Some("/path/to/repo/packages/a/".to_string()),
                 package_type: "npm".to_string(),
                 created_at: 1234567891,
             };
@@ -593,6 +613,8 @@ mod tests {
                 start_line: 1,
                 end_line: 10,
                 text: "export function helper() {}".to_string(),
+                qualified_name: "helper".to_string(),
+                parent_id: None,
             };
             sqlite.upsert_symbol(&symbol_a).unwrap();
 
@@ -608,6 +630,8 @@ mod tests {
                 start_line: 1,
                 end_line: 10,
                 text: "export function helper2() {}".to_string(),
+                qualified_name: "helper2".to_string(),
+                parent_id: None,
             };
             sqlite.upsert_symbol(&symbol_a2).unwrap();
 
@@ -72,6 +72,7 @@ pub fn apply_selection_boost_with_signals(
                     affinity_boost: 0.0,
                     docstring_boost: 0.0,
                     package_boost: 0.0,
+                    history_boost: 0.0,
                 });
         }
     }
@@ -166,6 +167,7 @@ pub fn apply_file_affinity_boost_with_signals(
                     affinity_boost: final_boost,
                     docstring_boost: 0.0,
                     package_boost: 0.0,
+                    history_boost: 0.0,
                 });
         }
     }
@@ -266,6 +268,7 @@ pub fn rank_hits_with_signals(
                 affinity_boost: 0.0,
                 docstring_boost: 0.0,
                 package_boost: 0.0,
+                history_boost: 0.0,
             },
         );
 
@@ -328,6 +331,7 @@ pub fn rank_hits_with_signals(
                 affinity_boost: 0.0,
                 docstring_boost: 0.0,
                 package_boost: 0.0,
+                history_boost: 0.0,
             },
         );
 
@@ -440,6 +444,110 @@ pub fn apply_popularity_boost_with_signals(
                 affinity_boost: 0.0,
                 docstring_boost: 0.0,
                 package_boost: 0.0,
+                history_boost: 0.0,
+            });
+    }
+
+    // Re-sort by score after applying boosts
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.exported.cmp(&a.exported))
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.kind.cmp(&b.kind))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(hits)
+}
+
+/// Days after which a change counts half as recent as one at the newest commit.
+const HISTORY_RECENCY_HALF_LIFE_DAYS: f64 = 90.0;
+
+/// Apply git history boost with signals tracking
+///
+/// Code that changed often and lately is where work is happening, so it is more
+/// likely what a query is after. Each hit gets `rank_history_weight` times the mean
+/// of its recency, halving every 90 days before the newest mined commit, and its
+/// churn, the log of its commit count relative to the most changed hit. Symbols are
+/// judged by the commits that touched their own lines, and fall back to those of
+/// their file when none did.
+pub fn apply_history_boost_with_signals(
+    sqlite: &SqliteStore,
+    mut hits: Vec<RankedHit>,
+    hit_signals: &mut HashMap<String, HitSignals>,
+    config: &Config,
+) -> Result<Vec<RankedHit>> {
+    if hits.is_empty() || config.rank_history_weight == 0.0 {
+        return Ok(hits);
+    }
+    // Nothing mined, as outside a git repository
+    let Some(newest) = sqlite.latest_commit_time().ok().flatten() else {
+        return Ok(hits);
+    };
+
+    let symbol_ids: Vec<&str> = hits.iter().map(|h| h.id.as_str()).collect();
+    let file_paths: Vec<&str> = hits.iter().map(|h| h.file_path.as_str()).collect();
+    let (symbol_counts, file_counts) = match (
+        sqlite.batch_get_symbol_change_counts(&symbol_ids),
+        sqlite.batch_get_file_change_counts(&file_paths),
+    ) {
+        (Ok(symbols), Ok(files)) => (symbols, files),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::warn!(
+                error = %e,
+                symbol_count = symbol_ids.len(),
+                "Change history lookup failed, skipping history boost"
+            );
+            return Ok(hits);
+        }
+    };
+
+    let changes: Vec<Option<(u32, i64)>> = hits
+        .iter()
+        .map(|h| {
+            symbol_counts
+                .get(&h.id)
+                .or_else(|| file_counts.get(&h.file_path))
+                .copied()
+        })
+        .collect();
+    let max_count = changes
+        .iter()
+        .flatten()
+        .map(|(count, _)| *count)
+        .max()
+        .unwrap_or(0);
+    if max_count == 0 {
+        return Ok(hits);
+    }
+
+    for (h, change) in hits.iter_mut().zip(changes) {
+        let Some((count, last_at)) = change else {
+            continue;
+        };
+        let age_days = (newest - last_at).max(0) as f64 / 86_400.0;
+        let recency = (-std::f64::consts::LN_2 * age_days / HISTORY_RECENCY_HALF_LIFE_DAYS).exp();
+        let churn = (1.0 + count as f64).ln() / (1.0 + max_count as f64).ln();
+        let boost = config.rank_history_weight * ((recency + churn) / 2.0) as f32;
+
+        h.score += boost;
+        hit_signals
+            .entry(h.id.clone())
+            .and_modify(|s| s.history_boost += boost)
+            .or_insert(HitSignals {
+                keyword_score: 0.0,
+                vector_score: 0.0,
+                base_score: 0.0,
+                structural_adjust: 0.0,
+                intent_mult: 1.0,
+                definition_bias: 0.0,
+                popularity_boost: 0.0,
+                learning_boost: 0.0,
+                affinity_boost: 0.0,
+                docstring_boost: 0.0,
+                package_boost: 0.0,
+                history_boost: boost,
             });
     }
 
@@ -490,6 +598,7 @@ pub fn apply_docstring_boost_with_signals(
                     affinity_boost: 0.0,
                     docstring_boost: DOCSTRING_BOOST,
                     package_boost: 0.0,
+                    history_boost: 0.0,
                 });
         }
     }
@@ -638,7 +747,10 @@ fn intent_adjustment(intent: &Option<Intent>, kind: &str, file_path: &str, expor
             let path = file_path.to_lowercase();
             if path.contains("schema") {
                 75.0
-            } else if path.contains("model") || path.contains("entity") || path.contains("entities")
+            } else if matches!(kind, "table" | "message")
+                || path.contains("model")
+                || path.contains("entity")
+                || path.contains("entities")
             {
                 50.0
             } else if path.contains("db/")
@@ -679,6 +791,8 @@ fn intent_adjustment(intent: &Option<Intent>, kind: &str, file_path: &str, expor
                 || path.contains("options")
             {
                 3.0
+            } else if kind == "config_key" {
+                2.0
             } else if matches!(kind, "const" | "variable") {
                 1.5
             } else {
@@ -740,7 +854,7 @@ fn intent_adjustment(intent: &Option<Intent>, kind: &str, file_path: &str, expor
             let path = file_path.to_lowercase();
             if path.contains("migration") || path.contains("migrate") {
                 5.0
-            } else if path.contains("schema") || path.contains("sql") {
+            } else if path.contains("schema") || path.contains("sql") || kind == "table" {
                 2.0
             } else {
                 0.5
@@ -787,11 +901,14 @@ mod tests {
             rank_test_penalty: 0.1,
             rank_popularity_weight: popularity_weight,
             rank_popularity_cap: 0, // No longer used
+            rank_history_weight: 0.0,
+            history_max_commits: 0,
             index_patterns: vec!["**/*.ts".to_string()],
             exclude_patterns: vec!["**/node_modules/**".to_string()],
             watch_mode: false,
             watch_debounce_ms: 250,
             watch_min_index_interval_ms: 5000,
+            watch_backend: crate::config::WatchBackend::Auto,
             max_context_bytes: 200_000,
             index_node_modules: false,
             repo_roots: vec![],
@@ -822,6 +939,9 @@ mod tests {
             metrics_enabled: true,
             metrics_port: 9090,
             package_detection_enabled: true,
+            mcp_transport: crate::config::McpTransport::Stdio,
+            http_bind_addr: "127.0.0.1:8765".to_string(),
+            http_auth_token: None,
         }
     }
 
@@ -839,6 +959,8 @@ mod tests {
             start_line: 1,
             end_line: 2,
             text: format!("fn {}() {{}}", name),
+            qualified_name: name.to_string(),
+            parent_id: None,
         };
         sqlite.upsert_symbol(&symbol).unwrap();
     }
@@ -0,0 +1,139 @@
+//! Streamable HTTP / SSE transport
+//!
+//! Serves one `CodeIntelligenceHandler` (and therefore one set of warm stores and one
+//! loaded embedding model) to any number of concurrent MCP clients. Streamable HTTP is
+//! served at `/mcp`, the legacy SSE transport at `/sse` + `/messages`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::http::{Request, Response, StatusCode};
+use rust_mcp_sdk::{
+    auth::{AuthInfo, AuthProvider, AuthenticationError, OauthEndpoint},
+    mcp_http::{GenericBody, GenericBodyExt, McpAppState},
+    mcp_server::{
+        error::TransportServerError, hyper_server, HyperServer, HyperServerOptions,
+        ToMcpServerHandler,
+    },
+};
+
+use super::{server_details, CodeIntelligenceHandler};
+use crate::config::{split_bind_addr, Config};
+
+/// Static tokens never expire, but the SDK's auth middleware requires an expiry.
+const TOKEN_LIFETIME: Duration = Duration::from_secs(10 * 365 * 24 * 60 * 60);
+
+/// Accepts requests carrying `Authorization: Bearer <token>` for a single shared token.
+pub struct BearerTokenAuth {
+    token: String,
+}
+
+impl BearerTokenAuth {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for BearerTokenAuth {
+    async fn verify_token(&self, access_token: String) -> Result<AuthInfo, AuthenticationError> {
+        if !constant_time_eq(access_token.as_bytes(), self.token.as_bytes()) {
+            return Err(AuthenticationError::InvalidToken {
+                description: "Invalid bearer token",
+            });
+        }
+        Ok(AuthInfo {
+            token_unique_id: "static-bearer-token".to_string(),
+            client_id: None,
+            user_id: None,
+            scopes: None,
+            expires_at: Some(SystemTime::now() + TOKEN_LIFETIME),
+            audience: None,
+            extra: None,
+        })
+    }
+
+    fn auth_endpoints(&self) -> Option<&HashMap<String, OauthEndpoint>> {
+        None
+    }
+
+    async fn handle_request(
+        &self,
+        _request: Request<&str>,
+        _state: Arc<McpAppState>,
+    ) -> Result<Response<GenericBody>, TransportServerError> {
+        // No OAuth endpoints are registered, so nothing is routed here
+        Ok(GenericBody::empty().into_response(StatusCode::NOT_FOUND, None))
+    }
+
+    fn protected_resource_metadata_url(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Compare without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Server options for `config.http_bind_addr`, with bearer-token auth when
+/// `config.http_auth_token` is set.
+pub fn http_server_options(config: &Config) -> Result<HyperServerOptions> {
+    let (host, port) = split_bind_addr(&config.http_bind_addr)?;
+    if config.http_auth_token.is_none()
+        && !matches!(host.as_str(), "127.0.0.1" | "::1" | "localhost")
+    {
+        tracing::warn!(
+            bind_addr = %config.http_bind_addr,
+            "HTTP transport is reachable beyond localhost without MCP_HTTP_AUTH_TOKEN"
+        );
+    }
+    let auth = config
+        .http_auth_token
+        .as_deref()
+        .map(|token| Arc::new(BearerTokenAuth::new(token)) as Arc<dyn AuthProvider>);
+    Ok(HyperServerOptions {
+        host,
+        port,
+        sse_support: true,
+        auth,
+        ..Default::default()
+    })
+}
+
+/// Build the HTTP server; call `start()` (or `start_runtime()`) to serve.
+pub fn create_http_server(
+    config: &Config,
+    handler: CodeIntelligenceHandler,
+) -> Result<HyperServer> {
+    let options = http_server_options(config)?;
+    Ok(hyper_server::create_server(
+        server_details(),
+        handler.to_mcp_server_handler(),
+        options,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bearer_token_auth_accepts_only_the_configured_token() {
+        let auth = BearerTokenAuth::new("s3cret");
+        let info = auth.verify_token("s3cret".to_string()).await.unwrap();
+        assert!(info.expires_at.unwrap() > SystemTime::now());
+
+        assert!(auth.verify_token("s3cre".to_string()).await.is_err());
+        assert!(auth.verify_token("s3creT".to_string()).await.is_err());
+        assert!(auth.verify_token(String::new()).await.is_err());
+    }
+}
@@ -6,13 +6,71 @@ use async_trait::async_trait;
 use rust_mcp_sdk::{
     mcp_server::ServerHandler,
     schema::{
-        CallToolError, CallToolRequestParams, CallToolResult, ListToolsResult,
-        PaginatedRequestParams, RpcError,
+        CallToolError, CallToolRequestParams, CallToolResult, Implementation, InitializeResult,
+        ListToolsResult, PaginatedRequestParams, ProtocolVersion, RpcError, ServerCapabilities,
+        ServerCapabilitiesTools, Tool,
     },
     McpServer,
 };
 use std::sync::Arc;
 
+pub mod http;
+
+/// Initialize result advertised to clients, shared by every transport.
+pub fn server_details() -> InitializeResult {
+    InitializeResult {
+        server_info: Implementation {
+            name: "code-intelligence".into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            title: Some("Code Intelligence MCP".into()),
+            description: Some("Local code intelligence MCP server".into()),
+            icons: vec![],
+            website_url: None,
+        },
+        capabilities: ServerCapabilities {
+            tools: Some(ServerCapabilitiesTools { list_changed: None }),
+            ..Default::default()
+        },
+        protocol_version: ProtocolVersion::V2025_11_25.into(),
+        instructions: None,
+        meta: None,
+    }
+}
+
+/// Every tool the server offers, in the order they are listed to clients.
+pub fn tool_list() -> Vec<Tool> {
+    vec![
+        SearchCodeTool::tool(),
+        SearchContentTool::tool(),
+        RefreshIndexTool::tool(),
+        GetDefinitionTool::tool(),
+        ResolvePositionTool::tool(),
+        FindReferencesTool::tool(),
+        GetFileSymbolsTool::tool(),
+        GetCallHierarchyTool::tool(),
+        ExploreDependencyGraphTool::tool(),
+        GetTypeGraphTool::tool(),
+        GetUsageExamplesTool::tool(),
+        GetIndexStatsTool::tool(),
+        HydrateSymbolsTool::tool(),
+        ReportSelectionTool::tool(),
+        ExplainSearchTool::tool(),
+        FindSimilarCodeTool::tool(),
+        SummarizeFileTool::tool(),
+        GetModuleSummaryTool::tool(),
+        TraceDataFlowTool::tool(),
+        FindAffectedCodeTool::tool(),
+        AnalyzeDiffTool::tool(),
+        CompareRevisionsTool::tool(),
+        CheckApiSurfaceTool::tool(),
+        GetChangeHistoryTool::tool(),
+        SearchTodosTool::tool(),
+        FindTestsForSymbolTool::tool(),
+        SearchDecoratorsTool::tool(),
+        SearchFrameworkPatternsTool::tool(),
+    ]
+}
+
 #[derive(Clone)]
 pub struct CodeIntelligenceHandler {
     pub state: Arc<AppState>,
@@ -26,30 +84,7 @@ impl ServerHandler for CodeIntelligenceHandler {
         _runtime: Arc<dyn McpServer>,
     ) -> std::result::Result<ListToolsResult, RpcError> {
         Ok(ListToolsResult {
-            tools: vec![
-                SearchCodeTool::tool(),
-                RefreshIndexTool::tool(),
-                GetDefinitionTool::tool(),
-                FindReferencesTool::tool(),
-                GetFileSymbolsTool::tool(),
-                GetCallHierarchyTool::tool(),
-                ExploreDependencyGraphTool::tool(),
-                GetTypeGraphTool::tool(),
-                GetUsageExamplesTool::tool(),
-                GetIndexStatsTool::tool(),
-                HydrateSymbolsTool::tool(),
-                ReportSelectionTool::tool(),
-                ExplainSearchTool::tool(),
-                FindSimilarCodeTool::tool(),
-                SummarizeFileTool::tool(),
-                GetModuleSummaryTool::tool(),
-                TraceDataFlowTool::tool(),
-                FindAffectedCodeTool::tool(),
-                SearchTodosTool::tool(),
-                FindTestsForSymbolTool::tool(),
-                SearchDecoratorsTool::tool(),
-                SearchFrameworkPatternsTool::tool(),
-            ],
+            tools: tool_list(),
             meta: None,
             next_cursor: None,
         })
@@ -60,244 +95,337 @@ impl ServerHandler for CodeIntelligenceHandler {
         params: CallToolRequestParams,
         _runtime: Arc<dyn McpServer>,
     ) -> std::result::Result<CallToolResult, CallToolError> {
-        match params.name.as_str() {
-            "refresh_index" => {
-                let tool: RefreshIndexTool = parse_tool_args(&params)?;
-                let result = handle_refresh_index(&self.state, tool)
-                    .await
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "search_code" => {
-                let tool: SearchCodeTool = parse_tool_args(&params)?;
-                let result = handle_search_code(&self.state.retriever, tool)
-                    .await
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "get_definition" => {
-                let tool: GetDefinitionTool = parse_tool_args(&params)?;
-                let result = handle_get_definition(&self.state, tool)
-                    .await
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "get_file_symbols" => {
-                let tool: GetFileSymbolsTool = parse_tool_args(&params)?;
-                let result = handle_get_file_symbols(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "get_index_stats" => {
-                let _tool: GetIndexStatsTool =
-                    parse_tool_args(&params).unwrap_or(GetIndexStatsTool {});
-                let result = handle_get_index_stats(&self.state).map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "hydrate_symbols" => {
-                let tool: HydrateSymbolsTool = parse_tool_args(&params)?;
-                let result =
-                    handle_hydrate_symbols(&self.state, tool).map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "explore_dependency_graph" => {
-                let tool: ExploreDependencyGraphTool = parse_tool_args(&params)?;
-                let result = handle_explore_dependency_graph(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "get_similarity_cluster" => {
-                let tool: GetSimilarityClusterTool = parse_tool_args(&params)?;
-                let result = handle_get_similarity_cluster(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "find_references" => {
-                let tool: FindReferencesTool = parse_tool_args(&params)?;
-                let result = handle_find_references(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "get_usage_examples" => {
-                let tool: GetUsageExamplesTool = parse_tool_args(&params)?;
-                let result = handle_get_usage_examples(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "get_call_hierarchy" => {
-                let tool: GetCallHierarchyTool = parse_tool_args(&params)?;
-                let result = handle_get_call_hierarchy(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "get_type_graph" => {
-                let tool: GetTypeGraphTool = parse_tool_args(&params)?;
-                let result = handle_get_type_graph(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "report_selection" => {
-                let tool: ReportSelectionTool = parse_tool_args(&params)?;
-                let result = handle_report_selection(&self.state, tool)
-                    .await
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "explain_search" => {
-                let tool: ExplainSearchTool = parse_tool_args(&params)?;
-                let result = handle_explain_search(&self.state.retriever, tool)
-                    .await
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "find_similar_code" => {
-                let tool: FindSimilarCodeTool = parse_tool_args(&params)?;
-                let result = handle_find_similar_code(&self.state, tool)
-                    .await
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "summarize_file" => {
-                let tool: SummarizeFileTool = parse_tool_args(&params)?;
-                let result = handle_summarize_file(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "get_module_summary" => {
-                let tool: GetModuleSummaryTool = parse_tool_args(&params)?;
-                let result = handle_get_module_summary(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{\"ok\":true}".to_string())
-                        .into(),
-                ]))
-            }
-            "trace_data_flow" => {
-                let tool: TraceDataFlowTool = parse_tool_args(&params)?;
-                let result = handle_trace_data_flow(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "find_affected_code" => {
-                let tool: FindAffectedCodeTool = parse_tool_args(&params)?;
-                let result = handle_find_affected_code(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "search_todos" => {
-                let tool: SearchTodosTool = parse_tool_args(&params)?;
-                let result = handle_search_todos(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "find_tests_for_symbol" => {
-                let tool: FindTestsForSymbolTool = parse_tool_args(&params)?;
-                let result = handle_find_tests_for_symbol(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "search_decorators" => {
-                let tool: SearchDecoratorsTool = parse_tool_args(&params)?;
-                let result = handle_search_decorators(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            "search_framework_patterns" => {
-                let tool: SearchFrameworkPatternsTool = parse_tool_args(&params)?;
-                let result = handle_search_framework_patterns(&self.state, tool)
-                    .map_err(tool_internal_error)?;
-                Ok(CallToolResult::text_content(vec![
-                    serde_json::to_string_pretty(&result)
-                        .unwrap_or_else(|_| "{}".to_string())
-                        .into(),
-                ]))
-            }
-            _ => Err(CallToolError::unknown_tool(params.name)),
+        call_tool(&self.state, params).await
+    }
+}
+
+/// Dispatch a tool call by name. Shared by every MCP transport and the CLI `call`
+/// subcommand. A tool given a `revision` answers from the snapshot of that revision.
+pub async fn call_tool(
+    state: &AppState,
+    params: CallToolRequestParams,
+) -> std::result::Result<CallToolResult, CallToolError> {
+    let revision = params
+        .arguments
+        .as_ref()
+        .and_then(|args| args.get("revision"))
+        .and_then(|revision| revision.as_str())
+        .map(str::trim)
+        .filter(|revision| !revision.is_empty())
+        .map(str::to_string);
+    match revision {
+        Some(revision) if takes_revision(&params.name) => {
+            let snapshot = state
+                .snapshots
+                .open(state, &revision)
+                .await
+                .map_err(tool_internal_error)?;
+            dispatch_tool(&snapshot, params).await
+        }
+        _ => dispatch_tool(state, params).await,
+    }
+}
+
+/// Whether a tool takes a `revision`; the others compare revisions themselves or
+/// deal with the working tree.
+fn takes_revision(tool: &str) -> bool {
+    !matches!(
+        tool,
+        "report_selection"
+            | "analyze_diff"
+            | "compare_revisions"
+            | "check_api_surface"
+            | "get_change_history"
+    )
+}
+
+async fn dispatch_tool(
+    state: &AppState,
+    params: CallToolRequestParams,
+) -> std::result::Result<CallToolResult, CallToolError> {
+    match params.name.as_str() {
+        "refresh_index" => {
+            let tool: RefreshIndexTool = parse_tool_args(&params)?;
+            let result = handle_refresh_index(state, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "search_code" => {
+            let tool: SearchCodeTool = parse_tool_args(&params)?;
+            let result = handle_search_code(&state.retriever, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "search_content" => {
+            let tool: SearchContentTool = parse_tool_args(&params)?;
+            let result = handle_search_content(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_definition" => {
+            let tool: GetDefinitionTool = parse_tool_args(&params)?;
+            let result = handle_get_definition(state, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "resolve_position" => {
+            let tool: ResolvePositionTool = parse_tool_args(&params)?;
+            let result = handle_resolve_position(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_file_symbols" => {
+            let tool: GetFileSymbolsTool = parse_tool_args(&params)?;
+            let result = handle_get_file_symbols(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_index_stats" => {
+            let _tool: GetIndexStatsTool =
+                parse_tool_args(&params).unwrap_or(GetIndexStatsTool { revision: None });
+            let result = handle_get_index_stats(state).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "hydrate_symbols" => {
+            let tool: HydrateSymbolsTool = parse_tool_args(&params)?;
+            let result = handle_hydrate_symbols(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "explore_dependency_graph" => {
+            let tool: ExploreDependencyGraphTool = parse_tool_args(&params)?;
+            let result =
+                handle_explore_dependency_graph(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_similarity_cluster" => {
+            let tool: GetSimilarityClusterTool = parse_tool_args(&params)?;
+            let result = handle_get_similarity_cluster(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "find_references" => {
+            let tool: FindReferencesTool = parse_tool_args(&params)?;
+            let result = handle_find_references(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_usage_examples" => {
+            let tool: GetUsageExamplesTool = parse_tool_args(&params)?;
+            let result = handle_get_usage_examples(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_call_hierarchy" => {
+            let tool: GetCallHierarchyTool = parse_tool_args(&params)?;
+            let result = handle_get_call_hierarchy(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_type_graph" => {
+            let tool: GetTypeGraphTool = parse_tool_args(&params)?;
+            let result = handle_get_type_graph(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "report_selection" => {
+            let tool: ReportSelectionTool = parse_tool_args(&params)?;
+            let result = handle_report_selection(state, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "explain_search" => {
+            let tool: ExplainSearchTool = parse_tool_args(&params)?;
+            let result = handle_explain_search(&state.retriever, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "find_similar_code" => {
+            let tool: FindSimilarCodeTool = parse_tool_args(&params)?;
+            let result = handle_find_similar_code(state, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "summarize_file" => {
+            let tool: SummarizeFileTool = parse_tool_args(&params)?;
+            let result = handle_summarize_file(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_module_summary" => {
+            let tool: GetModuleSummaryTool = parse_tool_args(&params)?;
+            let result = handle_get_module_summary(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{\"ok\":true}".to_string())
+                    .into(),
+            ]))
+        }
+        "trace_data_flow" => {
+            let tool: TraceDataFlowTool = parse_tool_args(&params)?;
+            let result = handle_trace_data_flow(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "find_affected_code" => {
+            let tool: FindAffectedCodeTool = parse_tool_args(&params)?;
+            let result = handle_find_affected_code(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "analyze_diff" => {
+            let tool: AnalyzeDiffTool = parse_tool_args(&params)?;
+            let result = handle_analyze_diff(state, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "compare_revisions" => {
+            let tool: CompareRevisionsTool = parse_tool_args(&params)?;
+            let result = handle_compare_revisions(state, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "check_api_surface" => {
+            let tool: CheckApiSurfaceTool = parse_tool_args(&params)?;
+            let result = handle_check_api_surface(state, tool)
+                .await
+                .map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "get_change_history" => {
+            let tool: GetChangeHistoryTool = parse_tool_args(&params)?;
+            let result = handle_get_change_history(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "search_todos" => {
+            let tool: SearchTodosTool = parse_tool_args(&params)?;
+            let result = handle_search_todos(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "find_tests_for_symbol" => {
+            let tool: FindTestsForSymbolTool = parse_tool_args(&params)?;
+            let result = handle_find_tests_for_symbol(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "search_decorators" => {
+            let tool: SearchDecoratorsTool = parse_tool_args(&params)?;
+            let result = handle_search_decorators(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
+        }
+        "search_framework_patterns" => {
+            let tool: SearchFrameworkPatternsTool = parse_tool_args(&params)?;
+            let result =
+                handle_search_framework_patterns(state, tool).map_err(tool_internal_error)?;
+            Ok(CallToolResult::text_content(vec![
+                serde_json::to_string_pretty(&result)
+                    .unwrap_or_else(|_| "{}".to_string())
+                    .into(),
+            ]))
         }
+        _ => Err(CallToolError::unknown_tool(params.name)),
     }
 }
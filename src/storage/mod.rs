@@ -1,4 +1,5 @@
 pub mod cache;
 pub mod sqlite;
 pub mod tantivy;
+pub mod trigram;
 pub mod vector;
@@ -21,6 +21,11 @@ impl SqliteStore {
         queries::symbols::delete_symbols_by_file(&conn, file_path)
     }
 
+    pub fn retain_symbols_by_file(&self, file_path: &str, keep_ids: &[String]) -> Result<()> {
+        let conn = self.write()?;
+        queries::symbols::retain_symbols_by_file(&conn, file_path, keep_ids)
+    }
+
     pub fn count_symbols(&self) -> Result<u64> {
         let conn = self.read()?;
         queries::symbols::count_symbols(&conn)
@@ -41,6 +46,17 @@ impl SqliteStore {
         queries::symbols::search_symbols_by_exact_name(&conn, name, file_path, limit)
     }
 
+    pub fn search_symbols_in_packages(
+        &self,
+        name: &str,
+        languages: &[&str],
+        packages: &[String],
+        limit: usize,
+    ) -> Result<Vec<(String, String)>> {
+        let conn = self.read()?;
+        queries::symbols::search_symbols_in_packages(&conn, name, languages, packages, limit)
+    }
+
     pub fn search_symbols_by_text_substr(
         &self,
         needle: &str,
@@ -64,6 +80,34 @@ impl SqliteStore {
         queries::symbols::list_symbol_headers_by_file(&conn, file_path, exported_only)
     }
 
+    pub fn list_symbol_headers_by_ids(&self, ids: &[String]) -> Result<Vec<SymbolHeaderRow>> {
+        let conn = self.read()?;
+        queries::symbols::list_symbol_headers_by_ids(&conn, ids)
+    }
+
+    pub fn list_all_symbol_headers(&self) -> Result<Vec<SymbolHeaderRow>> {
+        let conn = self.read()?;
+        queries::symbols::list_all_symbol_headers(&conn)
+    }
+
+    pub fn list_symbol_headers_where(
+        &self,
+        condition: &str,
+        params: &[String],
+        limit: Option<usize>,
+    ) -> Result<Vec<SymbolHeaderRow>> {
+        let conn = self.read()?;
+        queries::symbols::list_symbol_headers_where(&conn, condition, params, limit)
+    }
+
+    pub fn list_symbol_headers_by_languages(
+        &self,
+        languages: &[&str],
+    ) -> Result<Vec<SymbolHeaderRow>> {
+        let conn = self.read()?;
+        queries::symbols::list_symbol_headers_by_languages(&conn, languages)
+    }
+
     pub fn list_symbol_id_name_pairs(&self) -> Result<Vec<(String, String)>> {
         let conn = self.read()?;
         queries::symbols::list_symbol_id_name_pairs(&conn)
@@ -74,6 +118,15 @@ impl SqliteStore {
         queries::symbols::list_symbols_by_file(&conn, file_path)
     }
 
+    pub fn list_exported_declarations(
+        &self,
+        exclude_languages: &[&str],
+        span: usize,
+    ) -> Result<Vec<SymbolRow>> {
+        let conn = self.read()?;
+        queries::symbols::list_exported_declarations(&conn, exclude_languages, span)
+    }
+
     pub fn search_symbols_by_name_prefix(
         &self,
         prefix: &str,
@@ -129,6 +182,21 @@ impl SqliteStore {
         queries::edges::list_edges_to(&conn, to_symbol_id, limit)
     }
 
+    pub fn list_edges_by_type(&self, edge_type: &str) -> Result<Vec<EdgeRow>> {
+        let conn = self.read()?;
+        queries::edges::list_edges_by_type(&conn, edge_type)
+    }
+
+    pub fn replace_edges_by_resolution(
+        &self,
+        resolution: &str,
+        files: Option<&[String]>,
+        edges: &[EdgeRow],
+    ) -> Result<Vec<String>> {
+        let conn = self.write()?;
+        queries::edges::replace_edges_by_resolution(&conn, resolution, files, edges)
+    }
+
     pub fn count_incoming_edges(&self, to_symbol_id: &str) -> Result<u64> {
         let conn = self.read()?;
         queries::edges::count_incoming_edges(&conn, to_symbol_id)
@@ -144,11 +212,72 @@ impl SqliteStore {
         queries::edges::list_all_edges(&conn)
     }
 
+    pub fn list_all_edge_rows(&self) -> Result<Vec<EdgeRow>> {
+        let conn = self.read()?;
+        queries::edges::list_all_edge_rows(&conn)
+    }
+
+    pub fn list_edge_targets_by_types(
+        &self,
+        edge_types: &[&str],
+    ) -> Result<Vec<(String, String, String)>> {
+        let conn = self.read()?;
+        queries::edges::list_edge_targets_by_types(&conn, edge_types)
+    }
+
     pub fn list_all_symbol_ids(&self) -> Result<Vec<(String, String)>> {
         let conn = self.read()?;
         queries::edges::list_all_symbol_ids(&conn)
     }
 
+    pub fn list_symbol_ids_with_edge_to(
+        &self,
+        edge_types: &[&str],
+        name: &str,
+    ) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::edges::list_symbol_ids_with_edge_to(&conn, edge_types, name)
+    }
+
+    pub fn list_symbol_ids_and_names_by_file(
+        &self,
+        file_path: &str,
+    ) -> Result<Vec<(String, String)>> {
+        let conn = self.read()?;
+        queries::dependencies::list_symbol_ids_and_names_by_file(&conn, file_path)
+    }
+
+    pub fn list_edge_targets_from_file(&self, file_path: &str) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::dependencies::list_edge_targets_from_file(&conn, file_path)
+    }
+
+    pub fn delete_edges_from_file(&self, file_path: &str, keep: &[&str]) -> Result<Vec<String>> {
+        let conn = self.write()?;
+        queries::dependencies::delete_edges_from_file(&conn, file_path, keep)
+    }
+
+    // File dependency operations (incremental re-indexing)
+    pub fn replace_file_dependencies(
+        &self,
+        file_path: &str,
+        files: &[String],
+        names: &[String],
+    ) -> Result<()> {
+        let conn = self.write()?;
+        queries::dependencies::replace_file_dependencies(&conn, file_path, files, names)
+    }
+
+    pub fn delete_file_dependencies(&self, file_path: &str) -> Result<()> {
+        let conn = self.write()?;
+        queries::dependencies::delete_file_dependencies(&conn, file_path)
+    }
+
+    pub fn list_dependent_files(&self, files: &[String], names: &[String]) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::dependencies::list_dependent_files(&conn, files, names)
+    }
+
     pub fn get_file_fingerprint(&self, file_path: &str) -> Result<Option<FileFingerprintRow>> {
         let conn = self.read()?;
         queries::files::get_file_fingerprint(&conn, file_path)
@@ -174,6 +303,71 @@ impl SqliteStore {
         queries::files::list_all_file_fingerprints(&conn, limit)
     }
 
+    pub fn list_files_modified_since(&self, since_unix_s: i64) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::files::list_files_modified_since(&conn, since_unix_s)
+    }
+
+    pub fn get_or_assign_file_identity(&self, file_path: &str) -> Result<String> {
+        let conn = self.write()?;
+        queries::files::get_or_assign_file_identity(&conn, file_path)
+    }
+
+    pub fn has_file_identity(&self, file_path: &str) -> Result<bool> {
+        let conn = self.read()?;
+        queries::files::has_file_identity(&conn, file_path)
+    }
+
+    pub fn delete_file_identity(&self, file_path: &str) -> Result<()> {
+        let conn = self.write()?;
+        queries::files::delete_file_identity(&conn, file_path)
+    }
+
+    pub fn rename_file(&self, from: &str, to: &str) -> Result<()> {
+        let conn = self.write()?;
+        queries::files::rename_file(&conn, from, to)
+    }
+
+    pub fn replace_file_trigrams(&self, file_path: &str, trigrams: &[u32]) -> Result<()> {
+        let conn = self.write()?;
+        queries::contents::replace_file_trigrams(&conn, file_path, trigrams)
+    }
+
+    pub fn delete_file_trigrams(&self, file_path: &str) -> Result<()> {
+        let conn = self.write()?;
+        queries::contents::delete_file_trigrams(&conn, file_path)
+    }
+
+    pub fn list_file_ids_with_trigram(&self, trigram: u32) -> Result<Vec<i64>> {
+        let conn = self.read()?;
+        queries::contents::list_file_ids_with_trigram(&conn, trigram)
+    }
+
+    pub fn list_content_file_paths(&self, file_ids: &[i64]) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::contents::list_content_file_paths(&conn, file_ids)
+    }
+
+    pub fn list_files_with_all_trigrams(&self, trigrams: &[u32]) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::contents::list_files_with_all_trigrams(&conn, trigrams)
+    }
+
+    pub fn list_content_files(&self) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::contents::list_content_files(&conn)
+    }
+
+    pub fn list_files_without_trigrams(&self) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::contents::list_files_without_trigrams(&conn)
+    }
+
+    pub fn get_file_content(&self, file_path: &str) -> Result<Option<String>> {
+        let conn = self.read()?;
+        queries::contents::get_file_content(&conn, file_path)
+    }
+
     pub fn insert_index_run(&self, run: &IndexRunRow) -> Result<()> {
         let conn = self.write()?;
         queries::stats::insert_index_run(&conn, run)
@@ -258,6 +452,31 @@ impl SqliteStore {
         queries::metrics::get_symbol_metrics(&conn, symbol_id)
     }
 
+    pub fn get_top_symbols_by_pagerank(&self, limit: usize) -> Result<Vec<SymbolMetricsRow>> {
+        let conn = self.read()?;
+        queries::metrics::get_top_symbols_by_pagerank(&conn, limit)
+    }
+
+    pub fn count_rankable_symbols(&self) -> Result<u64> {
+        let conn = self.read()?;
+        queries::metrics::count_rankable_symbols(&conn)
+    }
+
+    pub fn list_rankable_predecessors(&self, to_symbol_id: &str) -> Result<Vec<(String, u64)>> {
+        let conn = self.read()?;
+        queries::metrics::list_rankable_predecessors(&conn, to_symbol_id)
+    }
+
+    pub fn list_rankable_successors(&self, from_symbol_id: &str) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::metrics::list_rankable_successors(&conn, from_symbol_id)
+    }
+
+    pub fn is_rankable_symbol(&self, symbol_id: &str) -> Result<bool> {
+        let conn = self.read()?;
+        queries::metrics::is_rankable_symbol(&conn, symbol_id)
+    }
+
     pub fn insert_query_selection(
         &self,
         query_text: &str,
@@ -275,6 +494,11 @@ impl SqliteStore {
         )
     }
 
+    pub fn get_symbol_selection_count(&self, symbol_id: &str) -> Result<u64> {
+        let conn = self.read()?;
+        queries::selections::get_symbol_selection_count(&conn, symbol_id)
+    }
+
     pub fn batch_get_selection_boosts(
         &self,
         pairs: &[(String, String)],
@@ -340,6 +564,11 @@ impl SqliteStore {
         queries::decorators::delete_decorators_by_file(&conn, file_path)
     }
 
+    pub fn list_symbol_ids_with_decorator(&self, name: &str) -> Result<Vec<String>> {
+        let conn = self.read()?;
+        queries::decorators::list_symbol_ids_with_decorator(&conn, name)
+    }
+
     pub fn search_decorators_by_name(
         &self,
         name: Option<&str>,
@@ -419,6 +648,20 @@ impl SqliteStore {
         queries::packages::upsert_package(&conn, pkg)
     }
 
+    pub fn replace_package_dependencies(
+        &self,
+        package_id: &str,
+        dependency_names: &[String],
+    ) -> Result<()> {
+        let conn = self.write()?;
+        queries::packages::replace_package_dependencies(&conn, package_id, dependency_names)
+    }
+
+    pub fn list_package_dependencies(&self, package_id: &str) -> Result<Vec<PackageRow>> {
+        let conn = self.read()?;
+        queries::packages::list_package_dependencies(&conn, package_id)
+    }
+
     pub fn get_package_for_file(&self, file_path: &str) -> Result<Option<PackageRow>> {
         let conn = self.read()?;
         queries::packages::get_package_for_file(&conn, file_path)
@@ -482,6 +725,21 @@ impl SqliteStore {
         queries::framework::delete_framework_patterns_by_file(&conn, file_path)
     }
 
+    pub fn update_framework_pattern_paths(&self, paths: &[(String, String)]) -> Result<()> {
+        let conn = self.write()?;
+        queries::framework::update_framework_pattern_paths(&conn, paths)
+    }
+
+    pub fn update_framework_pattern_symbols(&self, symbols: &[(String, String)]) -> Result<()> {
+        let conn = self.write()?;
+        queries::framework::update_framework_pattern_symbols(&conn, symbols)
+    }
+
+    pub fn list_framework_patterns_by_kind(&self, kind: &str) -> Result<Vec<FrameworkPatternRow>> {
+        let conn = self.read()?;
+        queries::framework::list_framework_patterns_by_kind(&conn, kind)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn search_framework_patterns(
         &self,
@@ -498,4 +756,88 @@ impl SqliteStore {
             &conn, framework, kind, http_method, path, name, file_path, limit,
         )
     }
+
+    pub fn replace_git_history(
+        &self,
+        head: &str,
+        commits: &[GitCommitRow],
+        changes: &[GitFileChangeRow],
+    ) -> Result<()> {
+        let conn = self.write()?;
+        queries::history::replace_git_history(&conn, head, commits, changes)
+    }
+
+    pub fn get_history_head(&self) -> Result<Option<String>> {
+        let conn = self.read()?;
+        queries::history::get_history_head(&conn)
+    }
+
+    pub fn latest_commit_time(&self) -> Result<Option<i64>> {
+        let conn = self.read()?;
+        queries::history::latest_commit_time(&conn)
+    }
+
+    pub fn list_file_changes(&self, file_path: &str) -> Result<Vec<GitFileChangeRow>> {
+        let conn = self.read()?;
+        queries::history::list_file_changes(&conn, file_path)
+    }
+
+    pub fn replace_symbol_changes(
+        &self,
+        file_path: &str,
+        changes: &[(String, String)],
+    ) -> Result<()> {
+        let conn = self.write()?;
+        queries::history::replace_symbol_changes(&conn, file_path, changes)
+    }
+
+    pub fn get_change_stats(&self, target: HistoryTarget) -> Result<Option<ChangeStatsRow>> {
+        let conn = self.read()?;
+        queries::history::get_change_stats(&conn, target)
+    }
+
+    pub fn list_authors(&self, target: HistoryTarget, limit: usize) -> Result<Vec<AuthorStatsRow>> {
+        let conn = self.read()?;
+        queries::history::list_authors(&conn, target, limit)
+    }
+
+    pub fn list_commits(&self, target: HistoryTarget, limit: usize) -> Result<Vec<GitCommitRow>> {
+        let conn = self.read()?;
+        queries::history::list_commits(&conn, target, limit)
+    }
+
+    pub fn list_co_changed_files(
+        &self,
+        file_path: &str,
+        max_commit_files: usize,
+        limit: usize,
+    ) -> Result<Vec<CoChangeRow>> {
+        let conn = self.read()?;
+        queries::history::list_co_changed_files(&conn, file_path, max_commit_files, limit)
+    }
+
+    pub fn list_symbols_by_churn(
+        &self,
+        file_path: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, u32, i64)>> {
+        let conn = self.read()?;
+        queries::history::list_symbols_by_churn(&conn, file_path, limit)
+    }
+
+    pub fn batch_get_symbol_change_counts(
+        &self,
+        symbol_ids: &[&str],
+    ) -> Result<std::collections::HashMap<String, (u32, i64)>> {
+        let conn = self.read()?;
+        queries::history::batch_get_symbol_change_counts(&conn, symbol_ids)
+    }
+
+    pub fn batch_get_file_change_counts(
+        &self,
+        file_paths: &[&str],
+    ) -> Result<std::collections::HashMap<String, (u32, i64)>> {
+        let conn = self.read()?;
+        queries::history::batch_get_file_change_counts(&conn, file_paths)
+    }
 }
@@ -84,6 +84,10 @@ impl SqliteStore {
             // Write lock needed for migration functions that modify schema
             #[allow(clippy::readonly_write_lock)]
             let conn = self.write()?;
+            // Runs first: the schema indexes the postings by file id
+            migrate_key_content_trigrams_by_file_id(&conn).with_context(|| {
+                "Failed to run migration: migrate_key_content_trigrams_by_file_id"
+            })?;
             conn.execute_batch(SCHEMA_SQL)
                 .context("Failed to initialize sqlite schema: execute_batch SCHEMA_SQL")?;
 
@@ -95,6 +99,17 @@ impl SqliteStore {
                 .with_context(|| "Failed to run migration: migrate_add_edges_evidence_count_column")?;
             migrate_add_edges_resolution_columns(&conn)
                 .with_context(|| "Failed to run migration: migrate_add_edges_resolution_columns")?;
+            migrate_add_symbols_hierarchy_columns(&conn)
+                .with_context(|| "Failed to run migration: migrate_add_symbols_hierarchy_columns")?;
+            migrate_add_framework_patterns_local_path_column(&conn).with_context(|| {
+                "Failed to run migration: migrate_add_framework_patterns_local_path_column"
+            })?;
+            migrate_add_framework_patterns_symbol_id_column(&conn).with_context(|| {
+                "Failed to run migration: migrate_add_framework_patterns_symbol_id_column"
+            })?;
+            migrate_add_packages_root_path_column(&conn).with_context(|| {
+                "Failed to run migration: migrate_add_packages_root_path_column"
+            })?;
         }
         Ok(())
     }
@@ -117,6 +132,9 @@ DELETE FROM user_file_affinity;
 DELETE FROM docstrings;
 DELETE FROM packages;
 DELETE FROM repositories;
+DELETE FROM file_dependencies;
+DELETE FROM content_trigrams;
+DELETE FROM content_files;
 "#,
             )
             .context("Failed to clear sqlite index: execute_batch DELETE FROM all tables")?;
@@ -136,6 +154,10 @@ DELETE FROM repositories;
 fn migrate_add_edges_location_columns(conn: &Connection) -> Result<()> {
     let _ = conn.execute("ALTER TABLE edges ADD COLUMN at_file TEXT", []);
     let _ = conn.execute("ALTER TABLE edges ADD COLUMN at_line INTEGER", []);
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_edges_at_file ON edges(at_file)",
+        [],
+    )?;
     Ok(())
 }
 
@@ -166,3 +188,51 @@ fn migrate_add_edges_resolution_columns(conn: &Connection) -> Result<()> {
     );
     Ok(())
 }
+
+fn migrate_add_symbols_hierarchy_columns(conn: &Connection) -> Result<()> {
+    let _ = conn.execute(
+        "ALTER TABLE symbols ADD COLUMN qualified_name TEXT NOT NULL DEFAULT ''",
+        [],
+    );
+    let _ = conn.execute("ALTER TABLE symbols ADD COLUMN parent_id TEXT", []);
+    conn.execute_batch(
+        r#"
+CREATE INDEX IF NOT EXISTS idx_symbols_qualified_name ON symbols(qualified_name);
+CREATE INDEX IF NOT EXISTS idx_symbols_parent_id ON symbols(parent_id);
+"#,
+    )?;
+    Ok(())
+}
+
+fn migrate_add_framework_patterns_local_path_column(conn: &Connection) -> Result<()> {
+    let _ = conn.execute(
+        "ALTER TABLE framework_patterns ADD COLUMN local_path TEXT",
+        [],
+    );
+    Ok(())
+}
+
+fn migrate_add_framework_patterns_symbol_id_column(conn: &Connection) -> Result<()> {
+    let _ = conn.execute(
+        "ALTER TABLE framework_patterns ADD COLUMN symbol_id TEXT",
+        [],
+    );
+    Ok(())
+}
+
+/// Postings used to repeat the file path. They are dropped, and the next index run
+/// records them again for every file without trigrams.
+fn migrate_key_content_trigrams_by_file_id(conn: &Connection) -> Result<()> {
+    if conn
+        .prepare("SELECT file_path FROM content_trigrams LIMIT 0")
+        .is_ok()
+    {
+        conn.execute("DROP TABLE content_trigrams", [])?;
+    }
+    Ok(())
+}
+
+fn migrate_add_packages_root_path_column(conn: &Connection) -> Result<()> {
+    let _ = conn.execute("ALTER TABLE packages ADD COLUMN root_path TEXT", []);
+    Ok(())
+}
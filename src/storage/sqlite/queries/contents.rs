@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Replace the trigrams recorded for a file, numbering the file on first sight.
+pub fn replace_file_trigrams(conn: &Connection, file_path: &str, trigrams: &[u32]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT OR IGNORE INTO content_files(file_path) VALUES (?1)",
+        params![file_path],
+    )
+    .with_context(|| format!("Failed to record content file {file_path}"))?;
+    let file_id: i64 = tx
+        .query_row(
+            "SELECT id FROM content_files WHERE file_path = ?1",
+            params![file_path],
+            |row| row.get(0),
+        )
+        .with_context(|| format!("Failed to look up content file {file_path}"))?;
+    tx.execute(
+        "DELETE FROM content_trigrams WHERE file_id = ?1",
+        params![file_id],
+    )
+    .with_context(|| format!("Failed to delete old trigrams for {file_path}"))?;
+    {
+        let mut stmt = tx
+            .prepare("INSERT OR IGNORE INTO content_trigrams(trigram, file_id) VALUES (?1, ?2)")
+            .context("Failed to prepare replace_file_trigrams")?;
+        for trigram in trigrams {
+            stmt.execute(params![trigram, file_id])
+                .with_context(|| format!("Failed to insert trigrams for {file_path}"))?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn delete_file_trigrams(conn: &Connection, file_path: &str) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM content_trigrams WHERE file_id IN (SELECT id FROM content_files WHERE file_path = ?1)",
+        params![file_path],
+    )
+    .with_context(|| format!("Failed to delete trigrams for {file_path}"))?;
+    tx.execute(
+        "DELETE FROM content_files WHERE file_path = ?1",
+        params![file_path],
+    )
+    .with_context(|| format!("Failed to delete content file {file_path}"))?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Ids of the files containing `trigram`.
+pub fn list_file_ids_with_trigram(conn: &Connection, trigram: u32) -> Result<Vec<i64>> {
+    let mut stmt = conn
+        .prepare("SELECT file_id FROM content_trigrams WHERE trigram = ?1")
+        .context("Failed to prepare list_file_ids_with_trigram")?;
+    let rows = stmt.query_map(params![trigram], |row| row.get(0))?;
+    rows.collect::<std::result::Result<Vec<i64>, _>>()
+        .context("Failed to list files with trigram")
+}
+
+/// Paths of the files with the given ids, sorted.
+pub fn list_content_file_paths(conn: &Connection, file_ids: &[i64]) -> Result<Vec<String>> {
+    let mut out = Vec::with_capacity(file_ids.len());
+    // Stay well below SQLite's bound-parameter limit
+    for chunk in file_ids.chunks(500) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT file_path FROM content_files WHERE id IN ({placeholders})"
+            ))
+            .context("Failed to prepare list_content_file_paths")?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(chunk), |row| row.get(0))?;
+        for row in rows {
+            out.push(row.context("Failed to list content file paths")?);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// Paths of the files containing every one of `trigrams`, sorted.
+pub fn list_files_with_all_trigrams(conn: &Connection, trigrams: &[u32]) -> Result<Vec<String>> {
+    let placeholders = vec!["?"; trigrams.len()].join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+SELECT f.file_path
+FROM content_trigrams t
+JOIN content_files f ON f.id = t.file_id
+WHERE t.trigram IN ({placeholders})
+GROUP BY t.file_id
+HAVING COUNT(*) = {}
+ORDER BY f.file_path
+"#,
+            trigrams.len()
+        ))
+        .context("Failed to prepare list_files_with_all_trigrams")?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(trigrams), |row| row.get(0))?;
+    rows.collect::<std::result::Result<Vec<String>, _>>()
+        .context("Failed to list files with trigrams")
+}
+
+/// Paths of all indexed files, sorted.
+pub fn list_content_files(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare("SELECT file_path FROM symbols WHERE kind = 'file' ORDER BY file_path")
+        .context("Failed to prepare list_content_files")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect::<std::result::Result<Vec<String>, _>>()
+        .context("Failed to list indexed files")
+}
+
+/// Indexed files with no trigrams recorded, such as those indexed before the content
+/// index existed (or too short to hold a trigram).
+pub fn list_files_without_trigrams(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT s.file_path
+FROM symbols s
+WHERE s.kind = 'file'
+  AND NOT EXISTS (
+    SELECT 1
+    FROM content_files f
+    JOIN content_trigrams t ON t.file_id = f.id
+    WHERE f.file_path = s.file_path
+  )
+ORDER BY s.file_path
+"#,
+        )
+        .context("Failed to prepare list_files_without_trigrams")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect::<std::result::Result<Vec<String>, _>>()
+        .context("Failed to list files without trigrams")
+}
+
+/// The content of an indexed file, as stored on its `file` symbol.
+pub fn get_file_content(conn: &Connection, file_path: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT text FROM symbols WHERE file_path = ?1 AND kind = 'file' LIMIT 1",
+        params![file_path],
+        |row| row.get(0),
+    )
+    .optional()
+    .with_context(|| format!("Failed to get content of {file_path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::sqlite::SqliteStore;
+
+    /// Create an in-memory SQLite database with the schema.
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::storage::sqlite::schema::SCHEMA_SQL)
+            .unwrap();
+        conn
+    }
+
+    #[test]
+    fn postings_refer_to_numbered_files() {
+        let conn = setup_test_db();
+        replace_file_trigrams(&conn, "src/b.ts", &[1, 2]).unwrap();
+        replace_file_trigrams(&conn, "src/a.ts", &[2, 3]).unwrap();
+        // Re-indexing a file keeps its id and replaces its postings
+        replace_file_trigrams(&conn, "src/b.ts", &[2, 4]).unwrap();
+
+        let with_two = list_file_ids_with_trigram(&conn, 2).unwrap();
+        assert_eq!(with_two.len(), 2);
+        assert_eq!(
+            list_content_file_paths(&conn, &with_two).unwrap(),
+            vec!["src/a.ts".to_string(), "src/b.ts".to_string()]
+        );
+        assert!(list_file_ids_with_trigram(&conn, 1).unwrap().is_empty());
+        assert_eq!(
+            list_files_with_all_trigrams(&conn, &[2, 4]).unwrap(),
+            vec!["src/b.ts".to_string()]
+        );
+
+        delete_file_trigrams(&conn, "src/b.ts").unwrap();
+        assert_eq!(list_file_ids_with_trigram(&conn, 2).unwrap().len(), 1);
+        let files: i64 = conn
+            .query_row("SELECT COUNT(*) FROM content_files", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(files, 1);
+    }
+
+    #[test]
+    fn postings_keyed_by_path_are_dropped_on_init() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+CREATE TABLE content_trigrams (
+  trigram INTEGER NOT NULL,
+  file_path TEXT NOT NULL,
+  PRIMARY KEY (trigram, file_path)
+) WITHOUT ROWID;
+CREATE INDEX idx_content_trigrams_file ON content_trigrams(file_path);
+INSERT INTO content_trigrams VALUES (1, 'src/a.ts');
+"#,
+        )
+        .unwrap();
+        let sqlite = SqliteStore::from_connection(conn);
+        sqlite.init().unwrap();
+
+        assert!(sqlite.list_file_ids_with_trigram(1).unwrap().is_empty());
+        sqlite.replace_file_trigrams("src/a.ts", &[1]).unwrap();
+        assert_eq!(sqlite.list_file_ids_with_trigram(1).unwrap().len(), 1);
+    }
+}
@@ -101,6 +101,18 @@ LIMIT ?2
     Ok(out)
 }
 
+/// Ids of the symbols carrying a decorator named `name`, with or without its `@`.
+pub fn list_symbol_ids_with_decorator(conn: &Connection, name: &str) -> Result<Vec<String>> {
+    let name = name.trim_start_matches('@');
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT symbol_id FROM decorators WHERE name = ?1 OR name = '@' || ?1")
+        .context("Failed to prepare list_symbol_ids_with_decorator")?;
+    let ids = stmt
+        .query_map(params![name], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(ids)
+}
+
 /// Delete all decorators for symbols in a specific file.
 pub fn delete_decorators_by_file(conn: &Connection, file_path: &str) -> Result<()> {
     conn.execute(
@@ -0,0 +1,190 @@
+//! Reverse-dependency index (file_dependencies table) and per-file lookups
+//! used by incremental re-indexing.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, params_from_iter, Connection};
+
+/// Replace the recorded dependencies of `file_path`.
+///
+/// `files` are import targets resolved to file paths, `names` are imported symbol names
+/// (used when the import source cannot be mapped to a file). Files referenced by the
+/// file's current outgoing edges are added as well, so edges resolved by name lookup
+/// are covered.
+pub fn replace_file_dependencies(
+    conn: &Connection,
+    file_path: &str,
+    files: &[String],
+    names: &[String],
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM file_dependencies WHERE file_path = ?1",
+        params![file_path],
+    )
+    .context("Failed to clear file dependencies")?;
+
+    {
+        let mut stmt = tx.prepare(
+            r#"
+INSERT OR IGNORE INTO file_dependencies (file_path, dependency_kind, target)
+VALUES (?1, ?2, ?3)
+"#,
+        )?;
+        for target in files.iter().filter(|t| t.as_str() != file_path) {
+            stmt.execute(params![file_path, "file", target])?;
+        }
+        for name in names {
+            stmt.execute(params![file_path, "name", name])?;
+        }
+    }
+
+    tx.execute(
+        r#"
+INSERT OR IGNORE INTO file_dependencies (file_path, dependency_kind, target)
+SELECT DISTINCT ?1, 'file', t.file_path
+FROM edges e
+JOIN symbols f ON f.id = e.from_symbol_id
+JOIN symbols t ON t.id = e.to_symbol_id
+WHERE f.file_path = ?1 AND t.file_path <> ?1
+"#,
+        params![file_path],
+    )
+    .context("Failed to record edge-derived file dependencies")?;
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Drop all dependencies recorded for `file_path` (used when the file is deleted).
+pub fn delete_file_dependencies(conn: &Connection, file_path: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM file_dependencies WHERE file_path = ?1",
+        params![file_path],
+    )
+    .context("Failed to delete file dependencies")?;
+    Ok(())
+}
+
+/// Files that depend on any of `files` or import any of `names`.
+pub fn list_dependent_files(
+    conn: &Connection,
+    files: &[String],
+    names: &[String],
+) -> Result<Vec<String>> {
+    let mut out = std::collections::BTreeSet::new();
+
+    for (kind, targets) in [("file", files), ("name", names)] {
+        // Stay well below SQLite's bound-parameter limit
+        for chunk in targets.chunks(500) {
+            let placeholders = (0..chunk.len())
+                .map(|i| format!("?{}", i + 2))
+                .collect::<Vec<_>>()
+                .join(",");
+            let sql = format!(
+                "SELECT DISTINCT file_path FROM file_dependencies WHERE dependency_kind = ?1 AND target IN ({placeholders})"
+            );
+            let mut stmt = conn
+                .prepare(&sql)
+                .context("Failed to prepare list_dependent_files")?;
+            let params = std::iter::once(kind).chain(chunk.iter().map(|s| s.as_str()));
+            let mut rows = stmt.query(params_from_iter(params))?;
+            while let Some(row) = rows.next()? {
+                out.insert(row.get::<_, String>(0)?);
+            }
+        }
+    }
+
+    Ok(out.into_iter().collect())
+}
+
+/// `(id, name)` of every symbol in a file.
+pub fn list_symbol_ids_and_names_by_file(
+    conn: &Connection,
+    file_path: &str,
+) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn
+        .prepare("SELECT id, name FROM symbols WHERE file_path = ?1")
+        .context("Failed to prepare list_symbol_ids_and_names_by_file")?;
+    let mut rows = stmt.query(params![file_path])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push((row.get(0)?, row.get(1)?));
+    }
+    Ok(out)
+}
+
+/// Distinct targets of edges that originate in a file.
+pub fn list_edge_targets_from_file(conn: &Connection, file_path: &str) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT DISTINCT e.to_symbol_id
+FROM edges e
+JOIN symbols f ON f.id = e.from_symbol_id
+WHERE f.file_path = ?1
+"#,
+        )
+        .context("Failed to prepare list_edge_targets_from_file")?;
+    let mut rows = stmt.query(params![file_path])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(row.get(0)?);
+    }
+    Ok(out)
+}
+
+/// Delete the edges that originate in a file, and their evidence, except those of the
+/// `keep` resolutions. Returns the distinct targets of the edges deleted.
+pub fn delete_edges_from_file(
+    conn: &Connection,
+    file_path: &str,
+    keep: &[&str],
+) -> Result<Vec<String>> {
+    let placeholders = (2..keep.len() + 2)
+        .map(|n| format!("?{n}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let condition = format!(
+        "from_symbol_id IN (SELECT id FROM symbols WHERE file_path = ?1) \
+         AND resolution NOT IN ({placeholders})"
+    );
+    let params: Vec<&str> = std::iter::once(file_path)
+        .chain(keep.iter().copied())
+        .collect();
+
+    let tx = conn.unchecked_transaction()?;
+    let targets = {
+        let mut stmt = tx
+            .prepare(&format!(
+                "SELECT DISTINCT to_symbol_id FROM edges WHERE {condition}"
+            ))
+            .context("Failed to prepare delete_edges_from_file")?;
+        let targets = stmt
+            .query_map(rusqlite::params_from_iter(&params), |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        targets
+    };
+    tx.execute(
+        &format!("DELETE FROM edges WHERE {condition}"),
+        rusqlite::params_from_iter(&params),
+    )
+    .with_context(|| format!("Failed to delete edges from file: {file_path}"))?;
+    tx.execute(
+        r#"
+DELETE FROM edge_evidence
+WHERE from_symbol_id IN (SELECT id FROM symbols WHERE file_path = ?1)
+  AND NOT EXISTS (
+    SELECT 1 FROM edges e
+    WHERE e.from_symbol_id = edge_evidence.from_symbol_id
+      AND e.to_symbol_id = edge_evidence.to_symbol_id
+      AND e.edge_type = edge_evidence.edge_type
+  )
+"#,
+        params![file_path],
+    )
+    .with_context(|| format!("Failed to delete edge evidence from file: {file_path}"))?;
+    tx.commit()?;
+    Ok(targets)
+}
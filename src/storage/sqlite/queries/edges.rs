@@ -180,6 +180,94 @@ LIMIT ?2
     Ok(out)
 }
 
+pub fn list_edges_by_type(conn: &Connection, edge_type: &str) -> Result<Vec<EdgeRow>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT
+  from_symbol_id, to_symbol_id, edge_type, at_file, at_line, confidence, evidence_count, resolution
+FROM edges
+WHERE edge_type = ?1
+ORDER BY from_symbol_id ASC, to_symbol_id ASC
+"#,
+        )
+        .context("Failed to prepare list_edges_by_type")?;
+
+    let mut rows = stmt.query(params![edge_type])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(EdgeRow {
+            from_symbol_id: row.get(0)?,
+            to_symbol_id: row.get(1)?,
+            edge_type: row.get(2)?,
+            at_file: row.get(3)?,
+            at_line: row
+                .get::<_, Option<i64>>(4)?
+                .and_then(|v| u32::try_from(v).ok()),
+            confidence: row.get::<_, f64>(5)? as f32,
+            evidence_count: u32::try_from(row.get::<_, i64>(6)?).unwrap_or(1),
+            resolution: row.get(7)?,
+        });
+    }
+    Ok(out)
+}
+
+pub fn list_all_edge_rows(conn: &Connection) -> Result<Vec<EdgeRow>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT
+  from_symbol_id, to_symbol_id, edge_type, at_file, at_line, confidence, evidence_count, resolution
+FROM edges
+ORDER BY from_symbol_id ASC, to_symbol_id ASC
+"#,
+        )
+        .context("Failed to prepare list_all_edge_rows")?;
+
+    let mut rows = stmt.query([])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(EdgeRow {
+            from_symbol_id: row.get(0)?,
+            to_symbol_id: row.get(1)?,
+            edge_type: row.get(2)?,
+            at_file: row.get(3)?,
+            at_line: row
+                .get::<_, Option<i64>>(4)?
+                .and_then(|v| u32::try_from(v).ok()),
+            confidence: row.get::<_, f64>(5)? as f32,
+            evidence_count: u32::try_from(row.get::<_, i64>(6)?).unwrap_or(1),
+            resolution: row.get(7)?,
+        });
+    }
+    Ok(out)
+}
+
+/// `(from_symbol_id, edge_type, qualified name of the target)` of the edges of the
+/// given types.
+pub fn list_edge_targets_by_types(
+    conn: &Connection,
+    edge_types: &[&str],
+) -> Result<Vec<(String, String, String)>> {
+    let placeholders = vec!["?"; edge_types.len()].join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+SELECT e.from_symbol_id, e.edge_type, t.qualified_name
+FROM edges e JOIN symbols t ON t.id = e.to_symbol_id
+WHERE e.edge_type IN ({placeholders})
+"#
+        ))
+        .context("Failed to prepare list_edge_targets_by_types")?;
+
+    let mut rows = stmt.query(rusqlite::params_from_iter(edge_types))?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push((row.get(0)?, row.get(1)?, row.get(2)?));
+    }
+    Ok(out)
+}
+
 pub fn count_incoming_edges(conn: &Connection, to_symbol_id: &str) -> Result<u64> {
     let count: i64 = conn
         .query_row(
@@ -233,6 +321,100 @@ SELECT id, kind FROM symbols
     Ok(out)
 }
 
+/// Ids of the symbols with an edge of one of `edge_types` to a symbol named `name`.
+/// A qualified name (`Repo.save`) matches symbols whose qualified name ends with it.
+pub fn list_symbol_ids_with_edge_to(
+    conn: &Connection,
+    edge_types: &[&str],
+    name: &str,
+) -> Result<Vec<String>> {
+    let qualified = name.replace("::", ".");
+    let (last, qualified) = match qualified.rsplit_once('.') {
+        Some((_, last)) => (last.to_string(), qualified.clone()),
+        None => (qualified, String::new()),
+    };
+    let placeholders = (0..edge_types.len())
+        .map(|i| format!("?{}", i + 3))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        r#"
+SELECT DISTINCT e.from_symbol_id
+FROM edges e
+JOIN symbols t ON t.id = e.to_symbol_id
+WHERE t.name = ?1
+  AND (?2 = '' OR t.qualified_name = ?2 OR substr(t.qualified_name, -length(?2) - 1) = '.' || ?2)
+  AND e.edge_type IN ({placeholders})
+"#
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .context("Failed to prepare list_symbol_ids_with_edge_to")?;
+
+    let mut values: Vec<&dyn rusqlite::ToSql> = vec![&last, &qualified];
+    values.extend(edge_types.iter().map(|t| t as &dyn rusqlite::ToSql));
+    let ids = stmt
+        .query_map(values.as_slice(), |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(ids)
+}
+
+/// Replace the edges of `resolution` with `edges`. With `files`, only the edges found
+/// in one of them or going from or to one of their symbols are replaced. Returns the
+/// ends of the edges deleted.
+pub fn replace_edges_by_resolution(
+    conn: &Connection,
+    resolution: &str,
+    files: Option<&[String]>,
+    edges: &[EdgeRow],
+) -> Result<Vec<String>> {
+    let condition = match files {
+        None => String::new(),
+        Some(files) => {
+            let placeholders = (2..files.len() + 2)
+                .map(|n| format!("?{n}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                " AND (at_file IN ({placeholders}) \
+                 OR from_symbol_id IN (SELECT id FROM symbols WHERE file_path IN ({placeholders})) \
+                 OR to_symbol_id IN (SELECT id FROM symbols WHERE file_path IN ({placeholders})))"
+            )
+        }
+    };
+    let params: Vec<&str> = std::iter::once(resolution)
+        .chain(files.unwrap_or_default().iter().map(String::as_str))
+        .collect();
+
+    let tx = conn.unchecked_transaction()?;
+    let mut ends = Vec::new();
+    {
+        let mut stmt = tx
+            .prepare(&format!(
+                "SELECT from_symbol_id, to_symbol_id FROM edges WHERE resolution = ?1{condition}"
+            ))
+            .context("Failed to prepare replaced edges query")?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(&params), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (from, to) = row?;
+            ends.push(from);
+            ends.push(to);
+        }
+    }
+    tx.execute(
+        &format!("DELETE FROM edges WHERE resolution = ?1{condition}"),
+        rusqlite::params_from_iter(&params),
+    )
+    .with_context(|| format!("Failed to delete {resolution} edges"))?;
+    for edge in edges {
+        upsert_edge(&tx, edge)?;
+    }
+    tx.commit()?;
+    Ok(ends)
+}
+
 fn edge_resolution_rank(resolution: &str) -> i64 {
     match resolution {
         "local" => 3,
@@ -56,6 +56,19 @@ pub fn delete_file_fingerprint(conn: &Connection, file_path: &str) -> Result<()>
     Ok(())
 }
 
+/// Paths of the files whose recorded modification time is at or after `since_unix_s`.
+pub fn list_files_modified_since(conn: &Connection, since_unix_s: i64) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare("SELECT file_path FROM file_fingerprints WHERE mtime_ns >= ?1")
+        .context("Failed to prepare list_files_modified_since")?;
+    let paths = stmt
+        .query_map(params![since_unix_s.saturating_mul(1_000_000_000)], |row| {
+            row.get::<_, String>(0)
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(paths)
+}
+
 pub fn list_all_file_fingerprints(
     conn: &Connection,
     limit: usize,
@@ -82,3 +95,86 @@ LIMIT ?1
     }
     Ok(out)
 }
+
+/// The identity path of `file_path`, assigning one if the file has none yet.
+///
+/// A new file's identity is its own path, unless a renamed file already carries that
+/// identity, in which case a `#<n>` suffix keeps the two apart.
+pub fn get_or_assign_file_identity(conn: &Connection, file_path: &str) -> Result<String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT identity_path FROM file_identities WHERE file_path = ?1",
+            params![file_path],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to query file identity")?;
+    if let Some(identity) = existing {
+        return Ok(identity);
+    }
+
+    for n in 0u32.. {
+        let candidate = if n == 0 {
+            file_path.to_string()
+        } else {
+            format!("{file_path}#{n}")
+        };
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO file_identities (file_path, identity_path) VALUES (?1, ?2)",
+                params![file_path, candidate],
+            )
+            .with_context(|| format!("Failed to assign identity for {file_path}"))?;
+        if inserted == 1 {
+            return Ok(candidate);
+        }
+    }
+    unreachable!("identity suffixes are unbounded")
+}
+
+/// Whether `file_path` has an identity, i.e. has been indexed before.
+pub fn has_file_identity(conn: &Connection, file_path: &str) -> Result<bool> {
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM file_identities WHERE file_path = ?1",
+            params![file_path],
+            |row| row.get(0),
+        )
+        .context("Failed to query file identity")?;
+    Ok(count > 0)
+}
+
+pub fn delete_file_identity(conn: &Connection, file_path: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM file_identities WHERE file_path = ?1",
+        params![file_path],
+    )
+    .with_context(|| format!("Failed to delete file identity for {file_path}"))?;
+    Ok(())
+}
+
+/// Carry a renamed file's identity, symbols and affinity over to its new path.
+///
+/// The symbols keep their ids, so nothing keyed by them is lost; re-indexing the new
+/// path then updates them in place.
+pub fn rename_file(conn: &Connection, from: &str, to: &str) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM file_identities WHERE file_path = ?1", params![to])?;
+    tx.execute(
+        "UPDATE file_identities SET file_path = ?2 WHERE file_path = ?1",
+        params![from, to],
+    )
+    .context("Failed to move file identity")?;
+    tx.execute(
+        "UPDATE symbols SET file_path = ?2 WHERE file_path = ?1",
+        params![from, to],
+    )
+    .context("Failed to move symbols to renamed file")?;
+    tx.execute(
+        "UPDATE OR IGNORE user_file_affinity SET file_path = ?2 WHERE file_path = ?1",
+        params![from, to],
+    )
+    .context("Failed to move file affinity")?;
+    tx.commit()?;
+    Ok(())
+}
@@ -8,9 +8,10 @@ pub fn upsert_framework_pattern(conn: &Connection, pattern: &FrameworkPatternRow
     conn.execute(
         r#"
 INSERT INTO framework_patterns (
-    id, file_path, line, framework, kind, http_method, path, name, handler, arguments, parent_chain, updated_at
+    id, file_path, line, framework, kind, http_method, path, local_path, name, handler, symbol_id,
+    arguments, parent_chain, updated_at
 )
-VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, unixepoch())
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, unixepoch())
 ON CONFLICT(id) DO UPDATE SET
     file_path=excluded.file_path,
     line=excluded.line,
@@ -18,8 +19,10 @@ ON CONFLICT(id) DO UPDATE SET
     kind=excluded.kind,
     http_method=excluded.http_method,
     path=excluded.path,
+    local_path=excluded.local_path,
     name=excluded.name,
     handler=excluded.handler,
+    symbol_id=excluded.symbol_id,
     arguments=excluded.arguments,
     parent_chain=excluded.parent_chain,
     updated_at=unixepoch()
@@ -32,8 +35,10 @@ ON CONFLICT(id) DO UPDATE SET
             pattern.kind,
             pattern.http_method,
             pattern.path,
+            pattern.local_path,
             pattern.name,
             pattern.handler,
+            pattern.symbol_id,
             pattern.arguments,
             pattern.parent_chain,
         ],
@@ -65,6 +70,43 @@ pub fn delete_framework_patterns_by_file(conn: &Connection, file_path: &str) ->
     Ok(())
 }
 
+/// Set the full path of framework patterns, by id, once the prefixes their files are
+/// mounted under are known.
+pub fn update_framework_pattern_paths(conn: &Connection, paths: &[(String, String)]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx
+            .prepare("UPDATE framework_patterns SET path = ?2 WHERE id = ?1")
+            .context("Failed to prepare framework pattern path update")?;
+        for (id, path) in paths {
+            stmt.execute(params![id, path])
+                .context("Failed to update framework pattern path")?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Set the handler symbol of framework patterns, by id, for handlers defined in
+/// other files.
+pub fn update_framework_pattern_symbols(
+    conn: &Connection,
+    symbols: &[(String, String)],
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx
+            .prepare("UPDATE framework_patterns SET symbol_id = ?2 WHERE id = ?1")
+            .context("Failed to prepare framework pattern symbol update")?;
+        for (id, symbol_id) in symbols {
+            stmt.execute(params![id, symbol_id])
+                .context("Failed to update framework pattern symbol")?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
 /// Search framework patterns with optional filters.
 #[allow(clippy::too_many_arguments)]
 pub fn search_framework_patterns(
@@ -93,8 +135,14 @@ pub fn search_framework_patterns(
         param_values.push(Box::new(m.to_uppercase()));
     }
     if let Some(p) = path {
+        // `/api/*` matches every path under `/api`; without a wildcard, any path
+        // containing `p` matches
         conditions.push("path LIKE ?");
-        param_values.push(Box::new(format!("%{}%", p)));
+        if p.contains('*') {
+            param_values.push(Box::new(p.replace('*', "%")));
+        } else {
+            param_values.push(Box::new(format!("%{}%", p)));
+        }
     }
     if let Some(n) = name {
         conditions.push("name LIKE ?");
@@ -113,7 +161,8 @@ pub fn search_framework_patterns(
 
     let sql = format!(
         r#"
-SELECT id, file_path, line, framework, kind, http_method, path, name, handler, arguments, parent_chain, updated_at
+SELECT id, file_path, line, framework, kind, http_method, path, COALESCE(local_path, path), name,
+    handler, symbol_id, arguments, parent_chain, updated_at
 FROM framework_patterns
 {}
 ORDER BY file_path ASC, line ASC
@@ -129,25 +178,47 @@ LIMIT ?
     let limit_i64 = limit as i64;
     param_refs.push(&limit_i64);
 
-    let mut rows = stmt.query(param_refs.as_slice())?;
-    let mut results = Vec::new();
+    let rows = stmt.query_map(param_refs.as_slice(), pattern_from_row)?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to read framework patterns")
+}
 
-    while let Some(row) = rows.next()? {
-        results.push(FrameworkPatternRow {
-            id: row.get(0)?,
-            file_path: row.get(1)?,
-            line: row.get::<_, i64>(2)? as u32,
-            framework: row.get(3)?,
-            kind: row.get(4)?,
-            http_method: row.get(5)?,
-            path: row.get(6)?,
-            name: row.get(7)?,
-            handler: row.get(8)?,
-            arguments: row.get(9)?,
-            parent_chain: row.get(10)?,
-            updated_at: row.get(11)?,
-        });
-    }
+/// All framework patterns of one kind (`route`, `plugin`, ...), in file order.
+pub fn list_framework_patterns_by_kind(
+    conn: &Connection,
+    kind: &str,
+) -> Result<Vec<FrameworkPatternRow>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT id, file_path, line, framework, kind, http_method, path, COALESCE(local_path, path), name,
+    handler, symbol_id, arguments, parent_chain, updated_at
+FROM framework_patterns
+WHERE kind = ?1
+ORDER BY file_path ASC, line ASC
+"#,
+        )
+        .context("Failed to prepare framework pattern listing")?;
+    let rows = stmt.query_map(params![kind], pattern_from_row)?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to read framework patterns")
+}
 
-    Ok(results)
+fn pattern_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<FrameworkPatternRow> {
+    Ok(FrameworkPatternRow {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        line: row.get::<_, i64>(2)? as u32,
+        framework: row.get(3)?,
+        kind: row.get(4)?,
+        http_method: row.get(5)?,
+        path: row.get(6)?,
+        local_path: row.get(7)?,
+        name: row.get(8)?,
+        handler: row.get(9)?,
+        symbol_id: row.get(10)?,
+        arguments: row.get(11)?,
+        parent_chain: row.get(12)?,
+        updated_at: row.get(13)?,
+    })
 }
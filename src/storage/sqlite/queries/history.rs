@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::storage::sqlite::schema::{
+    AuthorStatsRow, ChangeStatsRow, CoChangeRow, GitCommitRow, GitFileChangeRow, HistoryTarget,
+};
+
+/// Replace the mined history with the commits up to `head`, dropping what was
+/// recorded for symbols.
+pub fn replace_git_history(
+    conn: &Connection,
+    head: &str,
+    commits: &[GitCommitRow],
+    changes: &[GitFileChangeRow],
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        "DELETE FROM git_symbol_changes; DELETE FROM git_file_changes; DELETE FROM git_commits;",
+    )
+    .context("Failed to clear git history")?;
+    {
+        let mut stmt = tx
+            .prepare(
+                r#"
+INSERT OR REPLACE INTO git_commits (id, author, email, committed_at, summary, file_count)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+"#,
+            )
+            .context("Failed to prepare git commit insert")?;
+        for c in commits {
+            stmt.execute(params![
+                c.id,
+                c.author,
+                c.email,
+                c.committed_at,
+                c.summary,
+                c.file_count
+            ])
+            .with_context(|| format!("Failed to insert commit {}", c.id))?;
+        }
+
+        let mut stmt = tx
+            .prepare(
+                r#"
+INSERT OR REPLACE INTO git_file_changes (file_path, commit_id, lines_added, lines_deleted, head_lines)
+VALUES (?1, ?2, ?3, ?4, ?5)
+"#,
+            )
+            .context("Failed to prepare git file change insert")?;
+        for c in changes {
+            stmt.execute(params![
+                c.file_path,
+                c.commit_id,
+                c.lines_added,
+                c.lines_deleted,
+                c.head_lines
+            ])
+            .with_context(|| format!("Failed to insert change to {}", c.file_path))?;
+        }
+    }
+    tx.execute(
+        r#"
+INSERT INTO git_history_state (id, head, mined_at) VALUES (1, ?1, unixepoch())
+ON CONFLICT(id) DO UPDATE SET head=excluded.head, mined_at=excluded.mined_at
+"#,
+        params![head],
+    )
+    .context("Failed to record mined head")?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// The HEAD the history was last mined at.
+pub fn get_history_head(conn: &Connection) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT head FROM git_history_state WHERE id = 1",
+        [],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to get mined head")
+}
+
+/// Commit time of the newest mined commit.
+pub fn latest_commit_time(conn: &Connection) -> Result<Option<i64>> {
+    conn.query_row("SELECT MAX(committed_at) FROM git_commits", [], |row| {
+        row.get(0)
+    })
+    .context("Failed to get latest commit time")
+}
+
+/// Changes to a file, newest first.
+pub fn list_file_changes(conn: &Connection, file_path: &str) -> Result<Vec<GitFileChangeRow>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT c.file_path, c.commit_id, c.lines_added, c.lines_deleted, c.head_lines
+FROM git_file_changes c
+JOIN git_commits g ON g.id = c.commit_id
+WHERE c.file_path = ?1
+ORDER BY g.committed_at DESC
+"#,
+        )
+        .context("Failed to prepare list_file_changes")?;
+    let rows = stmt.query_map(params![file_path], |row| {
+        Ok(GitFileChangeRow {
+            file_path: row.get(0)?,
+            commit_id: row.get(1)?,
+            lines_added: row.get(2)?,
+            lines_deleted: row.get(3)?,
+            head_lines: row.get(4)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to list changes to {file_path}"))
+}
+
+/// Replace the commits recorded as changing the symbols of a file.
+pub fn replace_symbol_changes(
+    conn: &Connection,
+    file_path: &str,
+    changes: &[(String, String)],
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM git_symbol_changes WHERE file_path = ?1",
+        params![file_path],
+    )
+    .with_context(|| format!("Failed to delete symbol changes for {file_path}"))?;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT OR IGNORE INTO git_symbol_changes (symbol_id, commit_id, file_path) VALUES (?1, ?2, ?3)",
+            )
+            .context("Failed to prepare replace_symbol_changes")?;
+        for (symbol_id, commit_id) in changes {
+            stmt.execute(params![symbol_id, commit_id, file_path])
+                .with_context(|| format!("Failed to insert symbol changes for {file_path}"))?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// The table holding a target's changes and the column naming it.
+fn target_source(target: HistoryTarget<'_>) -> (&'static str, &'static str, &str) {
+    match target {
+        HistoryTarget::File(path) => ("git_file_changes", "file_path", path),
+        HistoryTarget::Symbol(id) => ("git_symbol_changes", "symbol_id", id),
+    }
+}
+
+/// How often and when a file or symbol changed, if it ever did.
+pub fn get_change_stats(
+    conn: &Connection,
+    target: HistoryTarget,
+) -> Result<Option<ChangeStatsRow>> {
+    let (table, column, value) = target_source(target);
+    let lines = match target {
+        HistoryTarget::File(_) => "SUM(c.lines_added), SUM(c.lines_deleted)",
+        HistoryTarget::Symbol(_) => "NULL, NULL",
+    };
+    let stats = conn
+        .query_row(
+            &format!(
+                r#"
+SELECT COUNT(*), {lines}, MIN(g.committed_at), MAX(g.committed_at)
+FROM {table} c
+JOIN git_commits g ON g.id = c.commit_id
+WHERE c.{column} = ?1
+"#
+            ),
+            params![value],
+            |row| {
+                Ok(ChangeStatsRow {
+                    commit_count: row.get(0)?,
+                    lines_added: row.get(1)?,
+                    lines_deleted: row.get(2)?,
+                    first_commit_at: row.get::<_, Option<i64>>(3)?.unwrap_or_default(),
+                    last_commit_at: row.get::<_, Option<i64>>(4)?.unwrap_or_default(),
+                })
+            },
+        )
+        .with_context(|| format!("Failed to get change stats for {value}"))?;
+    Ok((stats.commit_count > 0).then_some(stats))
+}
+
+/// Authors of the commits changing a file or symbol, most commits first.
+pub fn list_authors(
+    conn: &Connection,
+    target: HistoryTarget,
+    limit: usize,
+) -> Result<Vec<AuthorStatsRow>> {
+    let (table, column, value) = target_source(target);
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+SELECT g.author, g.email, COUNT(*) AS commits, MAX(g.committed_at)
+FROM {table} c
+JOIN git_commits g ON g.id = c.commit_id
+WHERE c.{column} = ?1
+GROUP BY g.email, g.author
+ORDER BY commits DESC, MAX(g.committed_at) DESC
+LIMIT ?2
+"#
+        ))
+        .context("Failed to prepare list_authors")?;
+    let rows = stmt.query_map(params![value, limit as i64], |row| {
+        Ok(AuthorStatsRow {
+            author: row.get(0)?,
+            email: row.get(1)?,
+            commit_count: row.get(2)?,
+            last_commit_at: row.get(3)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to list authors of {value}"))
+}
+
+/// Commits changing a file or symbol, newest first.
+pub fn list_commits(
+    conn: &Connection,
+    target: HistoryTarget,
+    limit: usize,
+) -> Result<Vec<GitCommitRow>> {
+    let (table, column, value) = target_source(target);
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+SELECT g.id, g.author, g.email, g.committed_at, g.summary, g.file_count
+FROM {table} c
+JOIN git_commits g ON g.id = c.commit_id
+WHERE c.{column} = ?1
+ORDER BY g.committed_at DESC
+LIMIT ?2
+"#
+        ))
+        .context("Failed to prepare list_commits")?;
+    let rows = stmt.query_map(params![value, limit as i64], |row| {
+        Ok(GitCommitRow {
+            id: row.get(0)?,
+            author: row.get(1)?,
+            email: row.get(2)?,
+            committed_at: row.get(3)?,
+            summary: row.get(4)?,
+            file_count: row.get(5)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to list commits changing {value}"))
+}
+
+/// Files changed in the same commits as `file_path`, most shared commits first.
+/// Commits changing more than `max_commit_files` files say little about which belong
+/// together and are left out.
+pub fn list_co_changed_files(
+    conn: &Connection,
+    file_path: &str,
+    max_commit_files: usize,
+    limit: usize,
+) -> Result<Vec<CoChangeRow>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT other.file_path, COUNT(*) AS shared
+FROM git_file_changes mine
+JOIN git_commits g ON g.id = mine.commit_id
+JOIN git_file_changes other ON other.commit_id = mine.commit_id
+WHERE mine.file_path = ?1
+  AND other.file_path != ?1
+  AND g.file_count <= ?2
+GROUP BY other.file_path
+ORDER BY shared DESC, other.file_path ASC
+LIMIT ?3
+"#,
+        )
+        .context("Failed to prepare list_co_changed_files")?;
+    let rows = stmt.query_map(
+        params![file_path, max_commit_files as i64, limit as i64],
+        |row| {
+            Ok(CoChangeRow {
+                file_path: row.get(0)?,
+                commit_count: row.get(1)?,
+            })
+        },
+    )?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to list files changed with {file_path}"))
+}
+
+/// Symbols of a file by how many commits changed them, with the time of the last.
+pub fn list_symbols_by_churn(
+    conn: &Connection,
+    file_path: &str,
+    limit: usize,
+) -> Result<Vec<(String, u32, i64)>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT c.symbol_id, COUNT(*) AS commits, MAX(g.committed_at)
+FROM git_symbol_changes c
+JOIN git_commits g ON g.id = c.commit_id
+WHERE c.file_path = ?1
+GROUP BY c.symbol_id
+ORDER BY commits DESC, MAX(g.committed_at) DESC
+LIMIT ?2
+"#,
+        )
+        .context("Failed to prepare list_symbols_by_churn")?;
+    let rows = stmt.query_map(params![file_path, limit as i64], |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to list symbols of {file_path} by churn"))
+}
+
+/// Commit count and last commit time of each of `symbol_ids` that changed.
+pub fn batch_get_symbol_change_counts(
+    conn: &Connection,
+    symbol_ids: &[&str],
+) -> Result<HashMap<String, (u32, i64)>> {
+    batch_get_change_counts(conn, HistoryTarget::Symbol(""), symbol_ids)
+}
+
+/// Commit count and last commit time of each of `file_paths` that changed.
+pub fn batch_get_file_change_counts(
+    conn: &Connection,
+    file_paths: &[&str],
+) -> Result<HashMap<String, (u32, i64)>> {
+    batch_get_change_counts(conn, HistoryTarget::File(""), file_paths)
+}
+
+fn batch_get_change_counts(
+    conn: &Connection,
+    kind: HistoryTarget,
+    keys: &[&str],
+) -> Result<HashMap<String, (u32, i64)>> {
+    if keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let (table, column, _) = target_source(kind);
+    let placeholders = keys
+        .iter()
+        .enumerate()
+        .map(|(i, _)| format!("?{}", i + 1))
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+SELECT c.{column}, COUNT(*), MAX(g.committed_at)
+FROM {table} c
+JOIN git_commits g ON g.id = c.commit_id
+WHERE c.{column} IN ({placeholders})
+GROUP BY c.{column}
+"#
+        ))
+        .context("Failed to prepare batch_get_change_counts")?;
+    let mut rows = stmt.query(rusqlite::params_from_iter(keys))?;
+    let mut out = HashMap::new();
+    while let Some(row) = rows.next()? {
+        out.insert(row.get(0)?, (row.get(1)?, row.get(2)?));
+    }
+    Ok(out)
+}
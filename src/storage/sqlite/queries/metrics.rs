@@ -126,6 +126,80 @@ pub fn batch_get_symbol_metrics(
     Ok(out)
 }
 
+/// Number of symbols that participate in PageRank (everything except FILE_ROOT symbols).
+pub fn count_rankable_symbols(conn: &Connection) -> Result<u64> {
+    let n: i64 = conn
+        .query_row("SELECT COUNT(*) FROM symbols WHERE kind <> 'file'", [], |row| {
+            row.get(0)
+        })
+        .context("Failed to count rankable symbols")?;
+    Ok(n as u64)
+}
+
+/// Incoming PageRank inputs of a symbol: one `(from_symbol_id, out_degree)` row per edge,
+/// where both ends are rankable and `out_degree` counts the source's rankable targets.
+pub fn list_rankable_predecessors(
+    conn: &Connection,
+    to_symbol_id: &str,
+) -> Result<Vec<(String, u64)>> {
+    let mut stmt = conn
+        .prepare_cached(
+            r#"
+SELECT e.from_symbol_id,
+       (SELECT COUNT(*) FROM edges e2
+        JOIN symbols t2 ON t2.id = e2.to_symbol_id
+        WHERE e2.from_symbol_id = e.from_symbol_id AND t2.kind <> 'file')
+FROM edges e
+JOIN symbols f ON f.id = e.from_symbol_id
+JOIN symbols t ON t.id = e.to_symbol_id
+WHERE e.to_symbol_id = ?1 AND f.kind <> 'file' AND t.kind <> 'file'
+"#,
+        )
+        .context("Failed to prepare list_rankable_predecessors")?;
+
+    let mut rows = stmt.query(params![to_symbol_id])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push((row.get(0)?, row.get::<_, i64>(1)? as u64));
+    }
+    Ok(out)
+}
+
+/// Distinct rankable targets of a rankable symbol's outgoing edges.
+pub fn list_rankable_successors(conn: &Connection, from_symbol_id: &str) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare_cached(
+            r#"
+SELECT DISTINCT e.to_symbol_id
+FROM edges e
+JOIN symbols f ON f.id = e.from_symbol_id
+JOIN symbols t ON t.id = e.to_symbol_id
+WHERE e.from_symbol_id = ?1 AND f.kind <> 'file' AND t.kind <> 'file'
+"#,
+        )
+        .context("Failed to prepare list_rankable_successors")?;
+
+    let mut rows = stmt.query(params![from_symbol_id])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(row.get(0)?);
+    }
+    Ok(out)
+}
+
+/// Whether a symbol exists and participates in PageRank.
+pub fn is_rankable_symbol(conn: &Connection, symbol_id: &str) -> Result<bool> {
+    let found: Option<i64> = conn
+        .query_row(
+            "SELECT 1 FROM symbols WHERE id = ?1 AND kind <> 'file'",
+            params![symbol_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to check rankable symbol")?;
+    Ok(found.is_some())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,7 +131,7 @@ pub fn list_symbols_without_similarity_clusters(
             r#"
 SELECT
   s.id, s.file_path, s.language, s.kind, s.name, s.exported,
-  s.start_byte, s.end_byte, s.start_line, s.end_line, s.text
+  s.start_byte, s.end_byte, s.start_line, s.end_line, s.text, s.qualified_name, s.parent_id
 FROM symbols s
 LEFT JOIN similarity_clusters c ON s.id = c.symbol_id
 WHERE c.symbol_id IS NULL
@@ -156,6 +156,8 @@ LIMIT ?1
             start_line: row.get(8)?,
             end_line: row.get(9)?,
             text: row.get(10)?,
+            qualified_name: row.get(11)?,
+            parent_id: row.get(12)?,
         });
     }
     Ok(out)
@@ -1,10 +1,13 @@
 pub mod affinity;
 pub mod cache;
+pub mod contents;
 pub mod decorators;
+pub mod dependencies;
 pub mod docstrings;
 pub mod edges;
 pub mod files;
 pub mod framework;
+pub mod history;
 pub mod metrics;
 pub mod misc;
 pub mod packages;
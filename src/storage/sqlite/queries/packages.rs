@@ -57,8 +57,8 @@ VALUES (?1, ?2, ?3, ?4, ?5, ?6)
 pub fn upsert_package(conn: &Connection, pkg: &PackageRow) -> Result<()> {
     conn.execute(
         r#"
-INSERT OR REPLACE INTO packages (id, repository_id, name, version, manifest_path, package_type, created_at)
-VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+INSERT OR REPLACE INTO packages (id, repository_id, name, version, manifest_path, package_type, created_at, root_path)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
 "#,
         params![
             pkg.id,
@@ -68,6 +68,7 @@ VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             pkg.manifest_path,
             pkg.package_type,
             pkg.created_at,
+            pkg.root_path,
         ],
     )
     .context("Failed to upsert package")?;
@@ -76,7 +77,7 @@ VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
 
 /// Get the package that contains a given file path.
 ///
-/// This function finds the deepest (most specific) package whose manifest_path
+/// This function finds the deepest (most specific) package whose root_path
 /// is a prefix of the given file_path. This allows finding which package
 /// contains any given source file.
 ///
@@ -91,14 +92,14 @@ VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
 /// * `Ok(None)` - No package found containing the file
 /// * `Err(anyhow::Error)` - Database operation failed
 pub fn get_package_for_file(conn: &Connection, file_path: &str) -> Result<Option<PackageRow>> {
-    // Find the deepest package whose manifest_path is a prefix of file_path
-    // ORDER BY LENGTH(manifest_path) DESC ensures we get the most specific match
+    // Find the deepest package whose root_path is a prefix of file_path
+    // ORDER BY LENGTH(root_path) DESC ensures we get the most specific match
     conn.query_row(
         r#"
-SELECT id, repository_id, name, version, manifest_path, package_type, created_at
+SELECT id, repository_id, name, version, manifest_path, package_type, created_at, root_path
 FROM packages
-WHERE ?1 LIKE manifest_path || '%'
-ORDER BY LENGTH(manifest_path) DESC
+WHERE root_path IS NOT NULL AND substr(?1, 1, length(root_path)) = root_path
+ORDER BY LENGTH(root_path) DESC
 LIMIT 1
 "#,
         params![file_path],
@@ -111,6 +112,7 @@ LIMIT 1
                 manifest_path: row.get(4)?,
                 package_type: row.get(5)?,
                 created_at: row.get(6)?,
+                root_path: row.get(7)?,
             })
         },
     )
@@ -132,7 +134,7 @@ pub fn list_all_packages(conn: &Connection) -> Result<Vec<PackageRow>> {
     let mut stmt = conn
         .prepare(
             r#"
-SELECT id, repository_id, name, version, manifest_path, package_type, created_at
+SELECT id, repository_id, name, version, manifest_path, package_type, created_at, root_path
 FROM packages
 ORDER BY manifest_path
 "#,
@@ -151,6 +153,7 @@ ORDER BY manifest_path
             manifest_path: row.get(4)?,
             package_type: row.get(5)?,
             created_at: row.get(6)?,
+            root_path: row.get(7)?,
         });
     }
 
@@ -245,7 +248,7 @@ WHERE id = ?1
 pub fn get_package_by_id(conn: &Connection, id: &str) -> Result<Option<PackageRow>> {
     conn.query_row(
         r#"
-SELECT id, repository_id, name, version, manifest_path, package_type, created_at
+SELECT id, repository_id, name, version, manifest_path, package_type, created_at, root_path
 FROM packages
 WHERE id = ?1
 "#,
@@ -259,6 +262,7 @@ WHERE id = ?1
                 manifest_path: row.get(4)?,
                 package_type: row.get(5)?,
                 created_at: row.get(6)?,
+                root_path: row.get(7)?,
             })
         },
     )
@@ -292,7 +296,7 @@ pub fn count_packages_in_repository(conn: &Connection, repository_id: &str) -> R
 ///
 /// This function efficiently looks up which package each symbol belongs to
 /// by joining the symbols table with the packages table. For each symbol,
-/// it finds the package whose manifest_path is a prefix of the symbol's file_path.
+/// it finds the package whose root_path is a prefix of the symbol's file_path.
 ///
 /// # Arguments
 ///
@@ -329,14 +333,17 @@ pub fn batch_get_symbol_packages(
         .collect::<Vec<_>>()
         .join(",");
 
-    // Query joins symbols with packages using LIKE to find containing package
-    // Uses LENGTH(manifest_path) DESC to find the deepest (most specific) package
+    // Query joins symbols with every package whose root_path prefixes the file path
+    // Ordered by LENGTH(root_path) so the deepest (most specific) package is inserted last
     let query = format!(
         r#"
 SELECT s.id, p.id
 FROM symbols s
-JOIN packages p ON s.file_path LIKE p.manifest_path || '%'
+JOIN packages p
+  ON p.root_path IS NOT NULL
+ AND substr(s.file_path, 1, length(p.root_path)) = p.root_path
 WHERE s.id IN ({})
+ORDER BY LENGTH(p.root_path)
 "#,
         placeholders
     );
@@ -383,8 +390,8 @@ pub fn get_package_id_for_file(conn: &Connection, file_path: &str) -> Result<Opt
         r#"
 SELECT id
 FROM packages
-WHERE ?1 LIKE manifest_path || '%'
-ORDER BY LENGTH(manifest_path) DESC
+WHERE root_path IS NOT NULL AND substr(?1, 1, length(root_path)) = root_path
+ORDER BY LENGTH(root_path) DESC
 LIMIT 1
 "#,
         params![file_path],
@@ -394,6 +401,91 @@ LIMIT 1
     .context("Failed to query package_id for file")
 }
 
+/// Replace the dependencies recorded for a package.
+///
+/// # Arguments
+///
+/// * `conn` - SQLite connection
+/// * `package_id` - Package whose dependencies are replaced
+/// * `dependency_names` - Names of the packages it depends on, as declared in its manifest
+///
+/// # Returns
+///
+/// * `Ok(())` - Dependencies were replaced
+/// * `Err(anyhow::Error)` - Database operation failed
+pub fn replace_package_dependencies(
+    conn: &Connection,
+    package_id: &str,
+    dependency_names: &[String],
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM package_dependencies WHERE package_id = ?1",
+        params![package_id],
+    )
+    .context("Failed to clear package dependencies")?;
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT OR IGNORE INTO package_dependencies (package_id, dependency_name) VALUES (?1, ?2)",
+        )?;
+        for name in dependency_names {
+            stmt.execute(params![package_id, name])
+                .context("Failed to insert package dependency")?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// List the indexed packages a package depends on.
+///
+/// Declared dependencies are matched to packages by name, so dependencies on
+/// packages outside the workspace are not returned.
+///
+/// # Arguments
+///
+/// * `conn` - SQLite connection
+/// * `package_id` - Package whose dependencies are listed
+///
+/// # Returns
+///
+/// * `Ok(Vec<PackageRow>)` - Packages depended on, ordered by name
+/// * `Err(anyhow::Error)` - Database operation failed
+pub fn list_package_dependencies(conn: &Connection, package_id: &str) -> Result<Vec<PackageRow>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT DISTINCT p.id, p.repository_id, p.name, p.version, p.manifest_path, p.package_type, p.created_at,
+       p.root_path
+FROM package_dependencies d
+JOIN packages p ON p.name = d.dependency_name
+WHERE d.package_id = ?1 AND p.id <> ?1
+ORDER BY p.name, p.manifest_path
+"#,
+        )
+        .context("Failed to prepare list_package_dependencies statement")?;
+
+    let mut rows = stmt.query(params![package_id])?;
+    let mut out = Vec::new();
+
+    while let Some(row) = rows.next()? {
+        out.push(PackageRow {
+            id: row.get(0)?,
+            repository_id: row.get(1)?,
+            name: row.get(2)?,
+            version: row.get(3)?,
+            manifest_path: row.get(4)?,
+            package_type: row.get(5)?,
+            created_at: row.get(6)?,
+            root_path: row.get(7)?,
+        });
+    }
+
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -486,6 +578,7 @@ mod tests {
             name: "test-package".to_string(),
             version: Some("1.0.0".to_string()),
             manifest_path: "/path/to/repo/package.json".to_string(),
+            root_path: Some("/path/to/repo/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567891,
         };
@@ -516,13 +609,14 @@ mod tests {
         };
         upsert_repository(&conn, &repo).unwrap();
 
-        // Create a package at a specific manifest path
+        // Create a package rooted at a specific directory
         let pkg = PackageRow {
             id: "pkg-456".to_string(),
             repository_id: "repo-123".to_string(),
             name: "test-package".to_string(),
             version: Some("1.0.0".to_string()),
-            manifest_path: "/path/to/repo/packages/subpackage".to_string(),
+            manifest_path: "/path/to/repo/packages/subpackage/package.json".to_string(),
+            root_path: Some("/path/to/repo/packages/subpackage/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567891,
         };
@@ -556,7 +650,8 @@ mod tests {
             repository_id: "repo-123".to_string(),
             name: "root-package".to_string(),
             version: Some("1.0.0".to_string()),
-            manifest_path: "/path/to/repo".to_string(),
+            manifest_path: "/path/to/repo/package.json".to_string(),
+            root_path: Some("/path/to/repo/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567891,
         };
@@ -567,7 +662,8 @@ mod tests {
             repository_id: "repo-123".to_string(),
             name: "nested-package".to_string(),
             version: Some("1.0.0".to_string()),
-            manifest_path: "/path/to/repo/packages/nested".to_string(),
+            manifest_path: "/path/to/repo/packages/nested/package.json".to_string(),
+            root_path: Some("/path/to/repo/packages/nested/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567892,
         };
@@ -589,6 +685,44 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_get_package_for_file_matches_root_path_literally() {
+        let conn = setup_test_db();
+
+        let repo = RepositoryRow {
+            id: "repo-123".to_string(),
+            name: "test-repo".to_string(),
+            root_path: "/path/to/repo".to_string(),
+            vcs_type: Some("git".to_string()),
+            remote_url: None,
+            created_at: 1234567890,
+        };
+        upsert_repository(&conn, &repo).unwrap();
+
+        // `_` is a LIKE wildcard, and a row without root_path predates the column
+        for (id, root_path) in [("pkg-underscore", Some("my_pkg/")), ("pkg-legacy", None)] {
+            let pkg = PackageRow {
+                id: id.to_string(),
+                repository_id: "repo-123".to_string(),
+                name: id.to_string(),
+                version: None,
+                manifest_path: "my_pkg/package.json".to_string(),
+                root_path: root_path.map(str::to_string),
+                package_type: "npm".to_string(),
+                created_at: 1234567891,
+            };
+            upsert_package(&conn, &pkg).unwrap();
+        }
+
+        assert!(get_package_for_file(&conn, "myxpkg/index.ts")
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            get_package_id_for_file(&conn, "my_pkg/index.ts").unwrap(),
+            Some("pkg-underscore".to_string())
+        );
+    }
+
     #[test]
     fn test_list_all_packages() {
         let conn = setup_test_db();
@@ -610,7 +744,8 @@ mod tests {
             repository_id: "repo-123".to_string(),
             name: "package-a".to_string(),
             version: Some("1.0.0".to_string()),
-            manifest_path: "/path/to/repo/packages/a".to_string(),
+            manifest_path: "/path/to/repo/packages/a/package.json".to_string(),
+            root_path: Some("/path/to/repo/packages/a/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567891,
         };
@@ -619,7 +754,8 @@ mod tests {
             repository_id: "repo-123".to_string(),
             name: "package-b".to_string(),
             version: Some("2.0.0".to_string()),
-            manifest_path: "/path/to/repo/packages/b".to_string(),
+            manifest_path: "/path/to/repo/packages/b/package.json".to_string(),
+            root_path: Some("/path/to/repo/packages/b/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567892,
         };
@@ -693,7 +829,8 @@ mod tests {
                 repository_id: "repo-123".to_string(),
                 name: format!("package-{}", i),
                 version: Some("1.0.0".to_string()),
-                manifest_path: format!("/path/to/repo/pkg{}", i),
+                manifest_path: format!("/path/to/repo/pkg{}/package.json", i),
+                root_path: Some(format!("/path/to/repo/pkg{}/", i)),
                 package_type: "npm".to_string(),
                 created_at: 1234567890 + i as i64,
             };
@@ -724,7 +861,8 @@ mod tests {
             repository_id: "repo-123".to_string(),
             name: "test-package".to_string(),
             version: Some("1.0.0".to_string()),
-            manifest_path: "/path/to/repo".to_string(),
+            manifest_path: "/path/to/repo/package.json".to_string(),
+            root_path: Some("/path/to/repo/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567891,
         };
@@ -808,7 +946,8 @@ mod tests {
             repository_id: "repo-123".to_string(),
             name: "test-package".to_string(),
             version: Some("1.0.0".to_string()),
-            manifest_path: "/path/to/repo/packages/subpackage".to_string(),
+            manifest_path: "/path/to/repo/packages/subpackage/package.json".to_string(),
+            root_path: Some("/path/to/repo/packages/subpackage/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567891,
         };
@@ -850,7 +989,8 @@ mod tests {
             repository_id: "repo-123".to_string(),
             name: "root-package".to_string(),
             version: Some("1.0.0".to_string()),
-            manifest_path: "/path/to/repo".to_string(),
+            manifest_path: "/path/to/repo/package.json".to_string(),
+            root_path: Some("/path/to/repo/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567891,
         };
@@ -861,7 +1001,8 @@ mod tests {
             repository_id: "repo-123".to_string(),
             name: "nested-package".to_string(),
             version: Some("1.0.0".to_string()),
-            manifest_path: "/path/to/repo/packages/nested".to_string(),
+            manifest_path: "/path/to/repo/packages/nested/package.json".to_string(),
+            root_path: Some("/path/to/repo/packages/nested/".to_string()),
             package_type: "npm".to_string(),
             created_at: 1234567892,
         };
@@ -872,4 +1013,59 @@ mod tests {
         let result = get_package_id_for_file(&conn, file_path).unwrap();
         assert_eq!(result, Some("pkg-nested".to_string()));
     }
+
+    #[test]
+    fn test_list_package_dependencies_matches_workspace_packages() {
+        let conn = setup_test_db();
+
+        let repo = RepositoryRow {
+            id: "repo-123".to_string(),
+            name: "test-repo".to_string(),
+            root_path: "/path/to/repo".to_string(),
+            vcs_type: Some("git".to_string()),
+            remote_url: None,
+            created_at: 1234567890,
+        };
+        upsert_repository(&conn, &repo).unwrap();
+
+        for (id, name, dir) in [
+            ("pkg-api", "com.acme:shop-api", "api/"),
+            ("pkg-core", "com.acme:shop-core", "core/"),
+        ] {
+            let pkg = PackageRow {
+                id: id.to_string(),
+                repository_id: "repo-123".to_string(),
+                name: name.to_string(),
+                version: Some("1.0.0".to_string()),
+                manifest_path: format!("{dir}pom.xml"),
+                root_path: Some(dir.to_string()),
+                package_type: "maven".to_string(),
+                created_at: 1234567891,
+            };
+            upsert_package(&conn, &pkg).unwrap();
+        }
+
+        replace_package_dependencies(
+            &conn,
+            "pkg-api",
+            &[
+                "com.acme:shop-core".to_string(),
+                "org.slf4j:slf4j-api".to_string(),
+            ],
+        )
+        .unwrap();
+
+        let deps = list_package_dependencies(&conn, "pkg-api").unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].id, "pkg-core");
+        assert!(list_package_dependencies(&conn, "pkg-core")
+            .unwrap()
+            .is_empty());
+
+        // Replacing drops what was recorded before
+        replace_package_dependencies(&conn, "pkg-api", &[]).unwrap();
+        assert!(list_package_dependencies(&conn, "pkg-api")
+            .unwrap()
+            .is_empty());
+    }
 }
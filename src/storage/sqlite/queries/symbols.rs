@@ -8,9 +8,9 @@ pub fn upsert_symbol(conn: &Connection, symbol: &SymbolRow) -> Result<()> {
         r#"
 INSERT INTO symbols (
   id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line, text, updated_at
+  start_byte, end_byte, start_line, end_line, text, qualified_name, parent_id, updated_at
 )
-VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, unixepoch())
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, unixepoch())
 ON CONFLICT(id) DO UPDATE SET
   file_path=excluded.file_path,
   language=excluded.language,
@@ -22,6 +22,8 @@ ON CONFLICT(id) DO UPDATE SET
   start_line=excluded.start_line,
   end_line=excluded.end_line,
   text=excluded.text,
+  qualified_name=excluded.qualified_name,
+  parent_id=excluded.parent_id,
   updated_at=unixepoch()
 "#,
         params![
@@ -35,7 +37,9 @@ ON CONFLICT(id) DO UPDATE SET
             symbol.end_byte,
             symbol.start_line,
             symbol.end_line,
-            symbol.text
+            symbol.text,
+            symbol.qualified_name,
+            symbol.parent_id
         ],
     )
     .with_context(|| {
@@ -56,6 +60,43 @@ pub fn delete_symbols_by_file(conn: &Connection, file_path: &str) -> Result<()>
     Ok(())
 }
 
+/// Prepare `file_path` for re-indexing without disturbing symbols that survive.
+///
+/// Symbols whose id is not in `keep_ids` are deleted (cascading to everything keyed
+/// by them); the rest stay, so rows keyed by their ids are kept. Edges out of the
+/// file are dropped either way, since they are extracted again.
+pub fn retain_symbols_by_file(conn: &Connection, file_path: &str, keep_ids: &[String]) -> Result<()> {
+    let keep: std::collections::HashSet<&str> = keep_ids.iter().map(String::as_str).collect();
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM edge_evidence WHERE from_symbol_id IN (SELECT id FROM symbols WHERE file_path = ?1)",
+        params![file_path],
+    )
+    .with_context(|| format!("Failed to delete edge evidence from file: {file_path}"))?;
+    tx.execute(
+        "DELETE FROM edges WHERE from_symbol_id IN (SELECT id FROM symbols WHERE file_path = ?1)",
+        params![file_path],
+    )
+    .with_context(|| format!("Failed to delete edges from file: {file_path}"))?;
+
+    let existing: Vec<String> = {
+        let mut stmt = tx.prepare("SELECT id FROM symbols WHERE file_path = ?1")?;
+        let ids = stmt
+            .query_map(params![file_path], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        ids
+    };
+    {
+        let mut stmt = tx.prepare("DELETE FROM symbols WHERE id = ?1")?;
+        for id in existing.iter().filter(|id| !keep.contains(id.as_str())) {
+            stmt.execute(params![id])
+                .with_context(|| format!("Failed to delete symbol {id} of file: {file_path}"))?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
 pub fn count_symbols(conn: &Connection) -> Result<u64> {
     let count: i64 = conn
         .query_row("SELECT COUNT(*) FROM symbols", [], |row| row.get(0))
@@ -72,77 +113,61 @@ pub fn most_recent_symbol_update(conn: &Connection) -> Result<Option<i64>> {
     Ok(ts)
 }
 
+/// Symbols named `name`, or whose qualified name is or ends with `name` when it is
+/// qualified (`Foo.bar`, `Foo::bar`, `pkg.Foo.bar`); exact qualified matches first.
 pub fn search_symbols_by_exact_name(
     conn: &Connection,
     name: &str,
     file_path: Option<&str>,
     limit: usize,
 ) -> Result<Vec<SymbolRow>> {
-    let mut out = Vec::new();
+    // Qualified names are stored with `.` separators; the symbol itself is named
+    // after the last segment, which keeps the lookup on the name index
+    let qualified = name.trim().replace("::", ".");
+    let (qualified, last_segment) = match qualified.rsplit_once('.') {
+        Some((_, last)) if !last.is_empty() => (qualified.clone(), last.to_string()),
+        _ => (String::new(), String::new()),
+    };
 
-    match file_path {
-        Some(fp) => {
-            let mut stmt = conn
-                .prepare(
-                    r#"
+    let mut stmt = conn
+        .prepare(
+            r#"
 SELECT
   id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line, text
+  start_byte, end_byte, start_line, end_line, text, qualified_name, parent_id
 FROM symbols
-WHERE name = ?1 AND file_path = ?2
-ORDER BY exported DESC, start_byte ASC
-LIMIT ?3
+WHERE (
+    name = ?1
+    OR (name = ?2 AND (
+      qualified_name = ?3
+      OR substr(qualified_name, -length(?3) - 1) = '.' || ?3
+    ))
+  )
+  AND (?4 IS NULL OR file_path = ?4)
+ORDER BY (?3 != '' AND qualified_name = ?3) DESC, exported DESC, file_path ASC, start_byte ASC
+LIMIT ?5
 "#,
-                )
-                .context("Failed to prepare search_symbols_by_exact_name (file)")?;
-            let mut rows = stmt.query(params![name, fp, limit as i64])?;
-            while let Some(row) = rows.next()? {
-                out.push(SymbolRow {
-                    id: row.get(0)?,
-                    file_path: row.get(1)?,
-                    language: row.get(2)?,
-                    kind: row.get(3)?,
-                    name: row.get(4)?,
-                    exported: row.get::<_, i64>(5)? != 0,
-                    start_byte: row.get::<_, i64>(6)? as u32,
-                    end_byte: row.get::<_, i64>(7)? as u32,
-                    start_line: row.get::<_, i64>(8)? as u32,
-                    end_line: row.get::<_, i64>(9)? as u32,
-                    text: row.get(10)?,
-                });
-            }
-        }
-        None => {
-            let mut stmt = conn
-                .prepare(
-                    r#"
-SELECT
-  id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line, text
-FROM symbols
-WHERE name = ?1
-ORDER BY exported DESC, file_path ASC, start_byte ASC
-LIMIT ?2
-"#,
-                )
-                .context("Failed to prepare search_symbols_by_exact_name")?;
-            let mut rows = stmt.query(params![name, limit as i64])?;
-            while let Some(row) = rows.next()? {
-                out.push(SymbolRow {
-                    id: row.get(0)?,
-                    file_path: row.get(1)?,
-                    language: row.get(2)?,
-                    kind: row.get(3)?,
-                    name: row.get(4)?,
-                    exported: row.get::<_, i64>(5)? != 0,
-                    start_byte: row.get::<_, i64>(6)? as u32,
-                    end_byte: row.get::<_, i64>(7)? as u32,
-                    start_line: row.get::<_, i64>(8)? as u32,
-                    end_line: row.get::<_, i64>(9)? as u32,
-                    text: row.get(10)?,
-                });
-            }
-        }
+        )
+        .context("Failed to prepare search_symbols_by_exact_name")?;
+
+    let mut rows = stmt.query(params![name, last_segment, qualified, file_path, limit as i64])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(SymbolRow {
+            id: row.get(0)?,
+            file_path: row.get(1)?,
+            language: row.get(2)?,
+            kind: row.get(3)?,
+            name: row.get(4)?,
+            exported: row.get::<_, i64>(5)? != 0,
+            start_byte: row.get::<_, i64>(6)? as u32,
+            end_byte: row.get::<_, i64>(7)? as u32,
+            start_line: row.get::<_, i64>(8)? as u32,
+            end_line: row.get::<_, i64>(9)? as u32,
+            text: row.get(10)?,
+            qualified_name: row.get(11)?,
+            parent_id: row.get(12)?,
+        });
     }
 
     Ok(out)
@@ -158,7 +183,7 @@ pub fn search_symbols_by_text_substr(
             r#"
 SELECT
   id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line, text
+  start_byte, end_byte, start_line, end_line, text, qualified_name, parent_id
 FROM symbols
 WHERE instr(text, ?1) > 0
 ORDER BY exported DESC, file_path ASC, start_byte ASC
@@ -182,6 +207,8 @@ LIMIT ?2
             start_line: row.get::<_, i64>(8)? as u32,
             end_line: row.get::<_, i64>(9)? as u32,
             text: row.get(10)?,
+            qualified_name: row.get(11)?,
+            parent_id: row.get(12)?,
         });
     }
     Ok(out)
@@ -192,7 +219,7 @@ pub fn get_symbol_by_id(conn: &Connection, id: &str) -> Result<Option<SymbolRow>
         r#"
 SELECT
   id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line, text
+  start_byte, end_byte, start_line, end_line, text, qualified_name, parent_id
 FROM symbols
 WHERE id = ?1
 "#,
@@ -210,6 +237,8 @@ WHERE id = ?1
                 start_line: row.get::<_, i64>(8)? as u32,
                 end_line: row.get::<_, i64>(9)? as u32,
                 text: row.get(10)?,
+                qualified_name: row.get(11)?,
+                parent_id: row.get(12)?,
             })
         },
     )
@@ -227,7 +256,7 @@ pub fn list_symbol_headers_by_file(
             r#"
 SELECT
   id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line
+  start_byte, end_byte, start_line, end_line, qualified_name, parent_id
 FROM symbols
 WHERE file_path = ?1 AND (?2 = 0 OR exported = ?2)
 ORDER BY start_byte ASC
@@ -238,18 +267,7 @@ ORDER BY start_byte ASC
     let mut rows = stmt.query(params![file_path, if exported_only { 1 } else { 0 }])?;
     let mut out = Vec::new();
     while let Some(row) = rows.next()? {
-        out.push(SymbolHeaderRow {
-            id: row.get(0)?,
-            file_path: row.get(1)?,
-            language: row.get(2)?,
-            kind: row.get(3)?,
-            name: row.get(4)?,
-            exported: row.get::<_, i64>(5)? != 0,
-            start_byte: row.get::<_, i64>(6)? as u32,
-            end_byte: row.get::<_, i64>(7)? as u32,
-            start_line: row.get::<_, i64>(8)? as u32,
-            end_line: row.get::<_, i64>(9)? as u32,
-        });
+        out.push(symbol_header_from_row(row)?);
     }
 
     // Log diagnostic info for empty results
@@ -294,6 +312,131 @@ ORDER BY start_byte ASC
     Ok(out)
 }
 
+/// Headers of the symbols with the given ids, in no particular order; unknown ids
+/// are skipped.
+pub fn list_symbol_headers_by_ids(conn: &Connection, ids: &[String]) -> Result<Vec<SymbolHeaderRow>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT
+  id, file_path, language, kind, name, exported,
+  start_byte, end_byte, start_line, end_line, qualified_name, parent_id
+FROM symbols
+WHERE id = ?1
+"#,
+        )
+        .context("Failed to prepare list_symbol_headers_by_ids")?;
+
+    let mut out = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(header) = stmt
+            .query_row(params![id], symbol_header_from_row)
+            .optional()?
+        {
+            out.push(header);
+        }
+    }
+    Ok(out)
+}
+
+/// Headers of all symbols except `file` rows, exported ones first.
+pub fn list_all_symbol_headers(conn: &Connection) -> Result<Vec<SymbolHeaderRow>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+SELECT
+  id, file_path, language, kind, name, exported,
+  start_byte, end_byte, start_line, end_line, qualified_name, parent_id
+FROM symbols
+WHERE kind != 'file'
+ORDER BY exported DESC, file_path ASC, start_byte ASC
+"#,
+        )
+        .context("Failed to prepare list_all_symbol_headers")?;
+
+    let mut rows = stmt.query([])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(symbol_header_from_row(row)?);
+    }
+    Ok(out)
+}
+
+/// Headers of the symbols, except `file` rows, for which `condition` (a `WHERE`
+/// clause over `symbols` columns with `?` parameters) holds, exported ones first.
+pub fn list_symbol_headers_where(
+    conn: &Connection,
+    condition: &str,
+    params: &[String],
+    limit: Option<usize>,
+) -> Result<Vec<SymbolHeaderRow>> {
+    let limit = limit.map(|n| format!("LIMIT {n}")).unwrap_or_default();
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+SELECT
+  id, file_path, language, kind, name, exported,
+  start_byte, end_byte, start_line, end_line, qualified_name, parent_id
+FROM symbols
+WHERE kind != 'file' AND ({condition})
+ORDER BY exported DESC, file_path ASC, start_byte ASC
+{limit}
+"#
+        ))
+        .context("Failed to prepare list_symbol_headers_where")?;
+
+    let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(symbol_header_from_row(row)?);
+    }
+    Ok(out)
+}
+
+/// Headers of the symbols of the given languages, except `file` rows.
+pub fn list_symbol_headers_by_languages(
+    conn: &Connection,
+    languages: &[&str],
+) -> Result<Vec<SymbolHeaderRow>> {
+    let placeholders = vec!["?"; languages.len()].join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+SELECT
+  id, file_path, language, kind, name, exported,
+  start_byte, end_byte, start_line, end_line, qualified_name, parent_id
+FROM symbols
+WHERE kind != 'file' AND language IN ({placeholders})
+ORDER BY file_path ASC, start_byte ASC
+"#
+        ))
+        .context("Failed to prepare list_symbol_headers_by_languages")?;
+
+    let mut rows = stmt.query(rusqlite::params_from_iter(languages))?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(symbol_header_from_row(row)?);
+    }
+    Ok(out)
+}
+
+fn symbol_header_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SymbolHeaderRow> {
+    Ok(SymbolHeaderRow {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        language: row.get(2)?,
+        kind: row.get(3)?,
+        name: row.get(4)?,
+        exported: row.get::<_, i64>(5)? != 0,
+        start_byte: row.get::<_, i64>(6)? as u32,
+        end_byte: row.get::<_, i64>(7)? as u32,
+        start_line: row.get::<_, i64>(8)? as u32,
+        end_line: row.get::<_, i64>(9)? as u32,
+        qualified_name: row.get(10)?,
+        parent_id: row.get(11)?,
+    })
+}
+
 pub fn list_symbol_id_name_pairs(conn: &Connection) -> Result<Vec<(String, String)>> {
     let mut stmt = conn
         .prepare("SELECT id, name FROM symbols ORDER BY name ASC")
@@ -313,7 +456,7 @@ pub fn list_symbols_by_file(conn: &Connection, file_path: &str) -> Result<Vec<Sy
             r#"
 SELECT
   id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line, text
+  start_byte, end_byte, start_line, end_line, text, qualified_name, parent_id
 FROM symbols
 WHERE file_path = ?1
 ORDER BY start_byte ASC
@@ -336,11 +479,115 @@ ORDER BY start_byte ASC
             start_line: row.get::<_, i64>(8)? as u32,
             end_line: row.get::<_, i64>(9)? as u32,
             text: row.get(10)?,
+            qualified_name: row.get(11)?,
+            parent_id: row.get(12)?,
+        });
+    }
+    Ok(out)
+}
+
+/// Exported symbols outside the given languages, and every member nested in one,
+/// except `file` rows. `text` holds only the first `span` characters of each.
+pub fn list_exported_declarations(
+    conn: &Connection,
+    exclude_languages: &[&str],
+    span: usize,
+) -> Result<Vec<SymbolRow>> {
+    let placeholders = vec!["?"; exclude_languages.len()].join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+WITH RECURSIVE declared(id) AS (
+  SELECT id FROM symbols
+  WHERE exported = 1 AND kind != 'file' AND language NOT IN ({placeholders})
+  UNION
+  SELECT s.id FROM symbols s JOIN declared d ON s.parent_id = d.id
+)
+SELECT
+  s.id, s.file_path, s.language, s.kind, s.name, s.exported,
+  s.start_byte, s.end_byte, s.start_line, s.end_line, substr(s.text, 1, {span}),
+  s.qualified_name, s.parent_id
+FROM symbols s JOIN declared d ON s.id = d.id
+WHERE s.kind != 'file'
+ORDER BY s.file_path ASC, s.start_byte ASC
+"#
+        ))
+        .context("Failed to prepare list_exported_declarations")?;
+
+    let mut rows = stmt.query(rusqlite::params_from_iter(exclude_languages))?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(SymbolRow {
+            id: row.get(0)?,
+            file_path: row.get(1)?,
+            language: row.get(2)?,
+            kind: row.get(3)?,
+            name: row.get(4)?,
+            exported: row.get::<_, i64>(5)? != 0,
+            start_byte: row.get::<_, i64>(6)? as u32,
+            end_byte: row.get::<_, i64>(7)? as u32,
+            start_line: row.get::<_, i64>(8)? as u32,
+            end_line: row.get::<_, i64>(9)? as u32,
+            text: row.get(10)?,
+            qualified_name: row.get(11)?,
+            parent_id: row.get(12)?,
         });
     }
     Ok(out)
 }
 
+/// Ids of the exported symbols named `name` in the given languages that are declared
+/// in one of `packages`, with the package each is declared in: the qualified name of
+/// its innermost enclosing `module` (a C# namespace), or else the package of its
+/// file, `""` where the file declares none.
+pub fn search_symbols_in_packages(
+    conn: &Connection,
+    name: &str,
+    languages: &[&str],
+    packages: &[String],
+    limit: usize,
+) -> Result<Vec<(String, String)>> {
+    if languages.is_empty() || packages.is_empty() {
+        return Ok(Vec::new());
+    }
+    let language_placeholders = vec!["?"; languages.len()].join(", ");
+    let package_placeholders = vec!["?"; packages.len()].join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+WITH RECURSIVE chain(id, ancestor_id) AS (
+  SELECT id, parent_id FROM symbols
+  WHERE name = ? AND exported = 1 AND language IN ({language_placeholders})
+  UNION ALL
+  SELECT c.id, a.parent_id FROM chain c JOIN symbols a ON a.id = c.ancestor_id
+  WHERE a.kind NOT IN ('module', 'file')
+),
+declared(id, package) AS (
+  SELECT c.id,
+    CASE WHEN a.kind = 'file' AND a.qualified_name = a.name THEN '' ELSE a.qualified_name END
+  FROM chain c JOIN symbols a ON a.id = c.ancestor_id
+  WHERE a.kind IN ('module', 'file')
+)
+SELECT d.id, d.package
+FROM declared d JOIN symbols s ON s.id = d.id
+WHERE d.package IN ({package_placeholders})
+ORDER BY s.file_path ASC, s.start_byte ASC
+LIMIT {limit}
+"#
+        ))
+        .context("Failed to prepare search_symbols_in_packages")?;
+
+    let params = std::iter::once(name)
+        .chain(languages.iter().copied())
+        .chain(packages.iter().map(String::as_str));
+    let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push((row.get(0)?, row.get(1)?));
+    }
+    Ok(out)
+}
+
 pub fn search_symbols_by_name_prefix(
     conn: &Connection,
     prefix: &str,
@@ -351,7 +598,7 @@ pub fn search_symbols_by_name_prefix(
             r#"
 SELECT
   id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line, text
+  start_byte, end_byte, start_line, end_line, text, qualified_name, parent_id
 FROM symbols
 WHERE name LIKE (?1 || '%')
 ORDER BY name ASC
@@ -375,6 +622,8 @@ LIMIT ?2
             start_line: row.get::<_, i64>(8)? as u32,
             end_line: row.get::<_, i64>(9)? as u32,
             text: row.get(10)?,
+            qualified_name: row.get(11)?,
+            parent_id: row.get(12)?,
         });
     }
     Ok(out)
@@ -390,7 +639,7 @@ pub fn search_symbols_by_name_substr(
             r#"
 SELECT
   id, file_path, language, kind, name, exported,
-  start_byte, end_byte, start_line, end_line, text
+  start_byte, end_byte, start_line, end_line, text, qualified_name, parent_id
 FROM symbols
 WHERE instr(name, ?1) > 0
 ORDER BY name ASC
@@ -414,6 +663,8 @@ LIMIT ?2
             start_line: row.get::<_, i64>(8)? as u32,
             end_line: row.get::<_, i64>(9)? as u32,
             text: row.get(10)?,
+            qualified_name: row.get(11)?,
+            parent_id: row.get(12)?,
         });
     }
     Ok(out)
@@ -13,6 +13,12 @@ pub struct SymbolRow {
     pub start_line: u32,
     pub end_line: u32,
     pub text: String,
+    /// Module/namespace, containers and name, joined with `.`
+    #[serde(default)]
+    pub qualified_name: String,
+    /// Innermost containing symbol; the file's `file` row for top-level symbols
+    #[serde(default)]
+    pub parent_id: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -49,6 +55,8 @@ pub struct SymbolHeaderRow {
     pub end_byte: u32,
     pub start_line: u32,
     pub end_line: u32,
+    pub qualified_name: String,
+    pub parent_id: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -144,6 +152,10 @@ pub struct PackageRow {
     pub name: String,
     pub version: Option<String>,
     pub manifest_path: String,
+    /// Package directory relative to the base dir, with a trailing slash ("" for the
+    /// root). Files are matched to packages by this prefix. `None` for rows written
+    /// before the column existed, until packages are detected again.
+    pub root_path: Option<String>,
     pub package_type: String,
     pub created_at: i64,
 }
@@ -191,7 +203,7 @@ pub struct DecoratorRow {
     pub updated_at: i64,
 }
 
-/// Framework pattern row for Elysia/Hono/Express route and plugin metadata
+/// Framework pattern row for web framework route, mount and plugin metadata
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FrameworkPatternRow {
     pub id: String,
@@ -201,13 +213,75 @@ pub struct FrameworkPatternRow {
     pub kind: String,
     pub http_method: Option<String>,
     pub path: Option<String>,
+    /// Path as registered in the file, before prefixes mounted from other files
+    pub local_path: Option<String>,
     pub name: Option<String>,
     pub handler: Option<String>,
+    /// Symbol of the handler, when it is indexed
+    pub symbol_id: Option<String>,
     pub arguments: Option<String>,
     pub parent_chain: Option<String>,
     pub updated_at: i64,
 }
 
+/// Commit mined from the git history of the indexed repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitCommitRow {
+    pub id: String,
+    pub author: String,
+    pub email: String,
+    pub committed_at: i64,
+    pub summary: String,
+    /// Files the commit changed
+    pub file_count: u32,
+}
+
+/// File a mined commit changed, under its current path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitFileChangeRow {
+    pub file_path: String,
+    pub commit_id: String,
+    pub lines_added: u32,
+    pub lines_deleted: u32,
+    /// Where the lines the change touched are at HEAD: half-open `start-end` ranges
+    /// joined with `,`
+    pub head_lines: String,
+}
+
+/// What history is asked about: a file or a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryTarget<'a> {
+    File(&'a str),
+    Symbol(&'a str),
+}
+
+/// How often and when a file or symbol changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeStatsRow {
+    pub commit_count: u32,
+    /// Lines added and removed, for files only
+    pub lines_added: Option<u64>,
+    pub lines_deleted: Option<u64>,
+    pub first_commit_at: i64,
+    pub last_commit_at: i64,
+}
+
+/// An author of the commits changing a file or symbol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorStatsRow {
+    pub author: String,
+    pub email: String,
+    pub commit_count: u32,
+    pub last_commit_at: i64,
+}
+
+/// A file changed in the same commits as another, and in how many
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoChangeRow {
+    pub file_path: String,
+    pub commit_count: u32,
+}
+
 pub const SCHEMA_SQL: &str = r#"
 PRAGMA foreign_keys = ON;
 
@@ -223,6 +297,8 @@ CREATE TABLE IF NOT EXISTS symbols (
   start_line INTEGER NOT NULL,
   end_line INTEGER NOT NULL,
   text TEXT NOT NULL,
+  qualified_name TEXT NOT NULL DEFAULT '',
+  parent_id TEXT,
   created_at INTEGER NOT NULL DEFAULT (unixepoch()),
   updated_at INTEGER NOT NULL DEFAULT (unixepoch())
 );
@@ -281,6 +357,27 @@ CREATE TABLE IF NOT EXISTS file_fingerprints (
 
 CREATE INDEX IF NOT EXISTS idx_file_fingerprints_updated_at ON file_fingerprints(updated_at);
 
+-- Files of the content index, numbered so that trigram postings stay small
+CREATE TABLE IF NOT EXISTS content_files (
+  id INTEGER PRIMARY KEY,
+  file_path TEXT NOT NULL UNIQUE
+);
+
+-- Trigrams of each indexed file's content (ASCII folded to lowercase), for regex search
+CREATE TABLE IF NOT EXISTS content_trigrams (
+  trigram INTEGER NOT NULL,
+  file_id INTEGER NOT NULL,
+  PRIMARY KEY (trigram, file_id),
+  FOREIGN KEY(file_id) REFERENCES content_files(id) ON DELETE CASCADE
+) WITHOUT ROWID;
+CREATE INDEX IF NOT EXISTS idx_content_trigrams_file ON content_trigrams(file_id);
+
+-- Path each file's symbol ids are derived from; a renamed file keeps its old one
+CREATE TABLE IF NOT EXISTS file_identities (
+  file_path TEXT PRIMARY KEY NOT NULL,
+  identity_path TEXT NOT NULL UNIQUE
+);
+
 CREATE TABLE IF NOT EXISTS usage_examples (
   id INTEGER PRIMARY KEY AUTOINCREMENT,
   to_symbol_id TEXT NOT NULL,
@@ -384,12 +481,22 @@ CREATE TABLE IF NOT EXISTS packages (
   name TEXT NOT NULL,
   version TEXT,
   manifest_path TEXT NOT NULL,
+  root_path TEXT,
   package_type TEXT NOT NULL,
   created_at INTEGER NOT NULL DEFAULT (unixepoch()),
   FOREIGN KEY(repository_id) REFERENCES repositories(id) ON DELETE CASCADE
 );
 CREATE INDEX IF NOT EXISTS idx_packages_repo ON packages(repository_id);
 
+-- Dependencies declared in package manifests, by the name of the package depended on
+CREATE TABLE IF NOT EXISTS package_dependencies (
+  package_id TEXT NOT NULL,
+  dependency_name TEXT NOT NULL,
+  PRIMARY KEY(package_id, dependency_name),
+  FOREIGN KEY(package_id) REFERENCES packages(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_package_dependencies_name ON package_dependencies(dependency_name);
+
 -- Docstrings for documentation extraction (FNDN-13)
 CREATE TABLE IF NOT EXISTS docstrings (
   symbol_id TEXT PRIMARY KEY NOT NULL,
@@ -455,7 +562,7 @@ CREATE TABLE IF NOT EXISTS embedding_cache (
 CREATE INDEX IF NOT EXISTS idx_embedding_cache_model ON embedding_cache(model_name);
 CREATE INDEX IF NOT EXISTS idx_embedding_cache_accessed ON embedding_cache(last_accessed_at);
 
--- Framework patterns for Elysia/Hono/Express route metadata
+-- Framework patterns for web framework route metadata
 CREATE TABLE IF NOT EXISTS framework_patterns (
     id TEXT PRIMARY KEY NOT NULL,
     file_path TEXT NOT NULL,
@@ -464,8 +571,10 @@ CREATE TABLE IF NOT EXISTS framework_patterns (
     kind TEXT NOT NULL,
     http_method TEXT,
     path TEXT,
+    local_path TEXT,
     name TEXT,
     handler TEXT,
+    symbol_id TEXT,
     arguments TEXT,
     parent_chain TEXT,
     updated_at INTEGER NOT NULL DEFAULT (unixepoch())
@@ -475,4 +584,55 @@ CREATE INDEX IF NOT EXISTS idx_fp_framework ON framework_patterns(framework);
 CREATE INDEX IF NOT EXISTS idx_fp_kind ON framework_patterns(kind);
 CREATE INDEX IF NOT EXISTS idx_fp_http_method ON framework_patterns(http_method);
 CREATE INDEX IF NOT EXISTS idx_fp_path ON framework_patterns(path);
+
+-- Reverse dependencies for incremental re-indexing: which files import which files/names
+CREATE TABLE IF NOT EXISTS file_dependencies (
+    file_path TEXT NOT NULL,
+    dependency_kind TEXT NOT NULL,
+    target TEXT NOT NULL,
+    PRIMARY KEY (file_path, dependency_kind, target)
+);
+CREATE INDEX IF NOT EXISTS idx_file_dependencies_target ON file_dependencies(dependency_kind, target);
+
+-- Commits mined from the git history along first parents, newest ones up to a limit
+CREATE TABLE IF NOT EXISTS git_commits (
+  id TEXT PRIMARY KEY NOT NULL,
+  author TEXT NOT NULL,
+  email TEXT NOT NULL,
+  committed_at INTEGER NOT NULL,
+  summary TEXT NOT NULL,
+  file_count INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_git_commits_committed_at ON git_commits(committed_at);
+
+-- Files each mined commit changed, under their current paths
+CREATE TABLE IF NOT EXISTS git_file_changes (
+  file_path TEXT NOT NULL,
+  commit_id TEXT NOT NULL,
+  lines_added INTEGER NOT NULL,
+  lines_deleted INTEGER NOT NULL,
+  head_lines TEXT NOT NULL,
+  PRIMARY KEY (file_path, commit_id),
+  FOREIGN KEY(commit_id) REFERENCES git_commits(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_git_file_changes_commit ON git_file_changes(commit_id);
+
+-- Commits that changed the current lines of each symbol
+CREATE TABLE IF NOT EXISTS git_symbol_changes (
+  symbol_id TEXT NOT NULL,
+  commit_id TEXT NOT NULL,
+  file_path TEXT NOT NULL,
+  PRIMARY KEY (symbol_id, commit_id),
+  FOREIGN KEY(symbol_id) REFERENCES symbols(id) ON DELETE CASCADE,
+  FOREIGN KEY(commit_id) REFERENCES git_commits(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_git_symbol_changes_file ON git_symbol_changes(file_path);
+CREATE INDEX IF NOT EXISTS idx_git_symbol_changes_commit ON git_symbol_changes(commit_id);
+
+-- The HEAD the history was mined at
+CREATE TABLE IF NOT EXISTS git_history_state (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  head TEXT NOT NULL,
+  mined_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
 "#;
@@ -6,7 +6,7 @@ use std::{path::Path, sync::Mutex};
 use tantivy::{
     collector::TopDocs,
     doc,
-    query::QueryParser,
+    query::{BooleanQuery, Occur, Query, QueryParser},
     schema::TantivyDocument,
     schema::{
         Field, IndexRecordOption, TextFieldIndexing, TextOptions, Value, INDEXED, STORED, STRING,
@@ -338,7 +338,22 @@ impl TantivyIndex {
         Ok(fresh)
     }
 
+    pub fn schema(&self) -> tantivy::schema::Schema {
+        self.index.schema()
+    }
+
     pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        self.search_filtered(query, limit, None)
+    }
+
+    /// Search, keeping only documents that also match `filter`, which should not
+    /// contribute to scores.
+    pub fn search_filtered(
+        &self,
+        query: &str,
+        limit: usize,
+        filter: Option<&dyn Query>,
+    ) -> Result<Vec<SearchHit>> {
         let searcher = self.reader.searcher();
 
         // For multi-word NL queries, boost text field over name to reduce
@@ -358,6 +373,7 @@ impl TantivyIndex {
             1.0,
             &[self.fields.name, self.fields.text],
             field_boosts,
+            filter,
         )?;
 
         if out.len() < limit && !query.contains('"') && looks_like_partial(query) {
@@ -375,6 +391,7 @@ impl TantivyIndex {
                     0.35,
                     &[self.fields.name_ngram, self.fields.text_ngram],
                     ngram_boosts,
+                    filter,
                 )?;
                 let mut seen: std::collections::HashSet<String> =
                     out.iter().map(|h| h.id.clone()).collect();
@@ -392,6 +409,7 @@ impl TantivyIndex {
         Ok(out)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn search_in_fields(
         &self,
         searcher: &tantivy::Searcher,
@@ -400,6 +418,7 @@ impl TantivyIndex {
         score_multiplier: f32,
         fields: &[Field],
         field_boosts: &[(Field, f32)],
+        filter: Option<&dyn Query>,
     ) -> Result<Vec<SearchHit>> {
         let mut query_parser = QueryParser::for_index(&self.index, fields.to_vec());
         for &(field, boost) in field_boosts {
@@ -408,6 +427,13 @@ impl TantivyIndex {
         let parsed_query = query_parser
             .parse_query(query)
             .with_context(|| format!("Failed to parse tantivy query: {query}"))?;
+        let parsed_query: Box<dyn Query> = match filter {
+            Some(filter) => Box::new(BooleanQuery::new(vec![
+                (Occur::Must, parsed_query),
+                (Occur::Must, filter.box_clone()),
+            ])),
+            None => parsed_query,
+        };
 
         let top_docs = searcher
             .search(&parsed_query, &TopDocs::with_limit(limit))
@@ -567,6 +593,8 @@ mod tests {
             start_line: 1,
             end_line: 1,
             text: text.to_string(),
+            qualified_name: name.to_string(),
+            parent_id: None,
         }
     }
 
@@ -0,0 +1,329 @@
+//! Trigram index over file contents, for literal and regex search.
+//!
+//! Every indexed file is recorded under each distinct trigram (three consecutive
+//! bytes, ASCII letters folded to lowercase) of its content. A regex is turned into a
+//! [`TrigramQuery`]: a boolean combination of trigrams that every file holding a
+//! match must contain. The query only narrows the candidates; the regex itself is
+//! then run over each candidate's content to find the matches.
+
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use regex_syntax::hir::{Class, Hir, HirKind};
+use regex_syntax::ParserBuilder;
+
+/// Most strings an exact set may hold before it is turned into trigrams.
+const MAX_EXACT_STRINGS: usize = 16;
+
+/// Most characters a class may hold to be expanded into its strings.
+const MAX_CLASS_CHARS: u32 = 4;
+
+/// Trigrams a file must contain to possibly hold a match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrigramQuery {
+    /// Every file qualifies
+    All,
+    Trigram(u32),
+    And(Vec<TrigramQuery>),
+    /// Any of the alternatives; no file qualifies when there are none
+    Or(Vec<TrigramQuery>),
+}
+
+/// The distinct trigrams of `text`, sorted.
+pub fn content_trigrams(text: &str) -> Vec<u32> {
+    let bytes = text.as_bytes();
+    let mut out: Vec<u32> = bytes.windows(3).map(trigram).collect();
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+fn trigram(window: &[u8]) -> u32 {
+    let b = |i: usize| window[i].to_ascii_lowercase() as u32;
+    (b(0) << 16) | (b(1) << 8) | b(2)
+}
+
+impl TrigramQuery {
+    /// The query for `pattern`, parsed with the flags the search compiles it with.
+    pub fn from_regex(pattern: &str, case_insensitive: bool) -> Result<Self> {
+        let hir = ParserBuilder::new()
+            .case_insensitive(case_insensitive)
+            .multi_line(true)
+            .build()
+            .parse(pattern)
+            .with_context(|| format!("Invalid regex: {pattern}"))?;
+        Ok(analyze(&hir).into_query())
+    }
+
+    fn and(parts: Vec<TrigramQuery>) -> Self {
+        let mut out = Vec::new();
+        for part in parts {
+            match part {
+                TrigramQuery::All => {}
+                TrigramQuery::And(inner) => out.extend(inner),
+                other => out.push(other),
+            }
+        }
+        out.dedup();
+        match out.len() {
+            0 => TrigramQuery::All,
+            1 => out.pop().unwrap_or(TrigramQuery::All),
+            _ => TrigramQuery::And(out),
+        }
+    }
+
+    fn or(parts: Vec<TrigramQuery>) -> Self {
+        let mut out = Vec::new();
+        for part in parts {
+            match part {
+                TrigramQuery::All => return TrigramQuery::All,
+                TrigramQuery::Or(inner) => out.extend(inner),
+                other => out.push(other),
+            }
+        }
+        out.dedup();
+        match out.len() {
+            1 => out.pop().unwrap_or(TrigramQuery::All),
+            _ => TrigramQuery::Or(out),
+        }
+    }
+
+    /// Files holding one of `strings` (ASCII already folded); a string shorter than
+    /// a trigram can be anywhere.
+    fn any_of(strings: &BTreeSet<Vec<u8>>) -> Self {
+        Self::or(
+            strings
+                .iter()
+                .map(|s| match s.len() {
+                    0..=2 => TrigramQuery::All,
+                    _ => Self::and(s.windows(3).map(|w| Self::Trigram(trigram(w))).collect()),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// What is known about the text a regex fragment matches: the exact set of strings
+/// it can match when that set is small, and a query its matches satisfy.
+struct Info {
+    exact: Option<BTreeSet<Vec<u8>>>,
+    query: TrigramQuery,
+}
+
+impl Info {
+    fn exact(strings: BTreeSet<Vec<u8>>) -> Self {
+        Self {
+            exact: Some(strings),
+            query: TrigramQuery::All,
+        }
+    }
+
+    fn anything() -> Self {
+        Self {
+            exact: None,
+            query: TrigramQuery::All,
+        }
+    }
+
+    fn into_query(self) -> TrigramQuery {
+        match self.exact {
+            Some(exact) => TrigramQuery::and(vec![self.query, TrigramQuery::any_of(&exact)]),
+            None => self.query,
+        }
+    }
+}
+
+fn empty_string() -> BTreeSet<Vec<u8>> {
+    BTreeSet::from([Vec::new()])
+}
+
+fn analyze(hir: &Hir) -> Info {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => Info::exact(empty_string()),
+        HirKind::Literal(literal) => Info::exact(BTreeSet::from([literal.0.to_ascii_lowercase()])),
+        HirKind::Class(class) => class_strings(class).map_or_else(Info::anything, Info::exact),
+        HirKind::Capture(capture) => analyze(&capture.sub),
+        HirKind::Repetition(repetition) => {
+            let sub = analyze(&repetition.sub);
+            match (repetition.min, repetition.max) {
+                (1, Some(1)) => sub,
+                // `x?` is `x` or nothing
+                (0, Some(1)) if sub.query == TrigramQuery::All => match sub.exact {
+                    Some(mut exact) if exact.len() < MAX_EXACT_STRINGS => {
+                        exact.insert(Vec::new());
+                        Info::exact(exact)
+                    }
+                    _ => Info::anything(),
+                },
+                (0, _) => Info::anything(),
+                _ => Info {
+                    exact: None,
+                    query: sub.into_query(),
+                },
+            }
+        }
+        HirKind::Concat(subs) => {
+            // `run` holds the strings of the exact fragments since the last one that
+            // was not; each run must appear in a match as a whole
+            let mut parts = Vec::new();
+            let mut run = empty_string();
+            let mut all_exact = true;
+            for sub in subs {
+                let info = analyze(sub);
+                parts.push(info.query);
+                match info.exact {
+                    Some(exact) if run.len() * exact.len() <= MAX_EXACT_STRINGS => {
+                        run = run
+                            .iter()
+                            .flat_map(|a| exact.iter().map(move |b| [a.as_slice(), b].concat()))
+                            .collect();
+                    }
+                    Some(exact) => {
+                        parts.push(TrigramQuery::any_of(&run));
+                        run = exact;
+                        all_exact = false;
+                    }
+                    None => {
+                        parts.push(TrigramQuery::any_of(&run));
+                        run = empty_string();
+                        all_exact = false;
+                    }
+                }
+            }
+            if all_exact {
+                return Info {
+                    exact: Some(run),
+                    query: TrigramQuery::and(parts),
+                };
+            }
+            parts.push(TrigramQuery::any_of(&run));
+            Info {
+                exact: None,
+                query: TrigramQuery::and(parts),
+            }
+        }
+        HirKind::Alternation(subs) => {
+            let infos: Vec<Info> = subs.iter().map(analyze).collect();
+            let simple = infos
+                .iter()
+                .all(|info| info.exact.is_some() && info.query == TrigramQuery::All);
+            if simple {
+                let union: BTreeSet<Vec<u8>> = infos
+                    .iter()
+                    .flat_map(|info| info.exact.iter().flatten().cloned())
+                    .collect();
+                if union.len() <= MAX_EXACT_STRINGS {
+                    return Info::exact(union);
+                }
+            }
+            Info {
+                exact: None,
+                query: TrigramQuery::or(infos.into_iter().map(Info::into_query).collect()),
+            }
+        }
+    }
+}
+
+/// The strings of a small class, ASCII folded.
+fn class_strings(class: &Class) -> Option<BTreeSet<Vec<u8>>> {
+    match class {
+        Class::Unicode(class) => {
+            let size: u32 = class
+                .ranges()
+                .iter()
+                .map(|r| r.end() as u32 - r.start() as u32 + 1)
+                .sum();
+            if size > MAX_CLASS_CHARS {
+                return None;
+            }
+            Some(
+                class
+                    .iter()
+                    .flat_map(|r| r.start()..=r.end())
+                    .map(|c| c.to_string().to_ascii_lowercase().into_bytes())
+                    .collect(),
+            )
+        }
+        Class::Bytes(class) => {
+            let size: u32 = class
+                .ranges()
+                .iter()
+                .map(|r| r.end() as u32 - r.start() as u32 + 1)
+                .sum();
+            if size > MAX_CLASS_CHARS {
+                return None;
+            }
+            Some(
+                class
+                    .iter()
+                    .flat_map(|r| r.start()..=r.end())
+                    .map(|b| vec![b.to_ascii_lowercase()])
+                    .collect(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(s: &str) -> TrigramQuery {
+        TrigramQuery::Trigram(trigram(s.as_bytes()))
+    }
+
+    #[test]
+    fn trigrams_are_distinct_and_case_folded() {
+        let trigrams = content_trigrams("AbcABC");
+        assert_eq!(
+            trigrams,
+            vec![trigram(b"abc"), trigram(b"bca"), trigram(b"cab")]
+        );
+        assert!(content_trigrams("ab").is_empty());
+    }
+
+    #[test]
+    fn literals_need_all_their_trigrams() {
+        assert_eq!(
+            TrigramQuery::from_regex("X-Request", false).unwrap(),
+            TrigramQuery::And(vec![
+                t("x-r"),
+                t("-re"),
+                t("req"),
+                t("equ"),
+                t("que"),
+                t("ues"),
+                t("est")
+            ])
+        );
+        // Case-insensitive classes fold back to the same trigrams
+        assert_eq!(
+            TrigramQuery::from_regex("abcd", true).unwrap(),
+            TrigramQuery::from_regex("ABCD", false).unwrap()
+        );
+    }
+
+    #[test]
+    fn regex_structure_shapes_the_query() {
+        assert_eq!(
+            TrigramQuery::from_regex("foo|bar", false).unwrap(),
+            TrigramQuery::Or(vec![t("bar"), t("foo")])
+        );
+        assert_eq!(
+            TrigramQuery::from_regex(r"get\w+Config", false).unwrap(),
+            TrigramQuery::And(vec![t("get"), t("con"), t("onf"), t("nfi"), t("fig")])
+        );
+        assert_eq!(
+            TrigramQuery::from_regex("colou?r", false).unwrap(),
+            TrigramQuery::Or(vec![
+                TrigramQuery::And(vec![t("col"), t("olo"), t("lor")]),
+                TrigramQuery::And(vec![t("col"), t("olo"), t("lou"), t("our")]),
+            ])
+        );
+        assert_eq!(
+            TrigramQuery::from_regex("a.*b", false).unwrap(),
+            TrigramQuery::All
+        );
+        assert!(TrigramQuery::from_regex("(unclosed", false).is_err());
+    }
+}
@@ -5,13 +5,35 @@ use serde::{Deserialize, Serialize};
 
 #[macros::mcp_tool(
     name = "search_code",
-    description = "Search codebase for symbols and return assembled context."
+    description = "Search codebase for symbols and return assembled context. Besides free text, the query may hold filter terms: kind:function,method, lang:ts, path:src/api/ (or a glob such as path:src/**/*.rs), file:*.ts, exported:true, calls:parseConfig, implements:Repository, decorated:Controller and modified:<7d. Negate a term with - or NOT, combine terms with OR and group them with parentheses, e.g. \"user lookup kind:function OR kind:method -path:tests/\"."
 )]
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
 pub struct SearchCodeTool {
     pub query: String,
     pub limit: Option<u32>,
     pub exported_only: Option<bool>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
+}
+
+#[macros::mcp_tool(
+    name = "search_content",
+    description = "Search the full text of indexed files for a regex or literal string (string literals, comments, config values), e.g. \"X-Request-Id\". Returns file:line matches with the line text and the innermost symbol enclosing each match."
+)]
+#[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
+pub struct SearchContentTool {
+    /// Regex (Rust syntax, `^`/`$` match at line boundaries) or, with literal=true, plain text
+    pub pattern: String,
+    /// Match the pattern as plain text (default: false)
+    pub literal: Option<bool>,
+    /// Ignore case (default: false)
+    pub case_insensitive: Option<bool>,
+    /// Only search files whose path contains this text, or matches it as a glob (e.g. "src/**/*.ts")
+    pub path: Option<String>,
+    /// Maximum number of matching lines to return (default: 100)
+    pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -21,20 +43,41 @@ pub struct SearchCodeTool {
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
 pub struct RefreshIndexTool {
     pub files: Option<Vec<String>>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
     name = "get_definition",
-    description = "Get full definition(s) for a symbol by name. When multiple symbols share the same name, use the 'file' parameter to disambiguate (e.g., file: \"src/auth.ts\")."
+    description = "Get full definition(s) for a symbol by name. Qualified names (e.g., \"UserService.get\" or \"UserService::get\") pick the member of a specific container. When multiple symbols share the same name, use the 'file' parameter to disambiguate (e.g., file: \"src/auth.ts\")."
 )]
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
 pub struct GetDefinitionTool {
-    /// The symbol name to look up
+    /// The symbol name to look up; a qualified name (`Foo.bar`, `Foo::bar`) matches
+    /// symbols whose qualified name ends with it
     pub symbol_name: String,
     /// Optional file path to disambiguate when multiple symbols share the same name
     pub file: Option<String>,
     /// Maximum number of definitions to return (default: 10)
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
+}
+
+#[macros::mcp_tool(
+    name = "resolve_position",
+    description = "Resolve a cursor position in a file: returns the innermost enclosing symbol, the identifier under the cursor, and the symbol that identifier refers to (resolved through the call/reference graph, not by name alone). Symbol ids in the result can be passed to hydrate_symbols."
+)]
+#[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
+pub struct ResolvePositionTool {
+    /// File path (absolute or relative to the indexed base directory)
+    pub file_path: String,
+    /// 1-based line number
+    pub line: u32,
+    /// 1-based column (character offset within the line, in UTF-16 code units as in LSP)
+    pub column: u32,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -47,25 +90,31 @@ pub struct FindReferencesTool {
     pub symbol_name: String,
     /// Optional file path to disambiguate when multiple symbols share the same name
     pub file: Option<String>,
-    /// Filter by reference type: "call", "import", "reference", "extends", "implements", or "all" (default)
+    /// Filter by reference type: "call", "import", "reference", "extends", "implements", "implements_contract" (handlers of an rpc or API operation), "generated_from" (stubs generated from a contract), or "all" (default)
     pub reference_type: Option<String>,
     /// Maximum number of references to return (default: 200)
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
     name = "get_file_symbols",
-    description = "List symbols defined in a file (no full definitions)."
+    description = "List symbols defined in a file (no full definitions), with qualified names and parent ids. Set tree=true to nest members under their containers."
 )]
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
 pub struct GetFileSymbolsTool {
     pub file_path: String,
     pub exported_only: Option<bool>,
+    /// Return top-level symbols with their members nested under `children` (default: false)
+    pub tree: Option<bool>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
     name = "get_call_hierarchy",
-    description = "Return a best-effort call hierarchy rooted at a symbol."
+    description = "Return a best-effort call hierarchy rooted at a symbol. Follows HTTP calls from client code to the handlers of the routes they request, across services."
 )]
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
 pub struct GetCallHierarchyTool {
@@ -73,6 +122,8 @@ pub struct GetCallHierarchyTool {
     pub direction: Option<String>,
     pub depth: Option<u32>,
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -84,6 +135,8 @@ pub struct GetTypeGraphTool {
     pub symbol_name: String,
     pub depth: Option<u32>,
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -94,6 +147,8 @@ pub struct GetTypeGraphTool {
 pub struct GetUsageExamplesTool {
     pub symbol_name: String,
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -101,7 +156,10 @@ pub struct GetUsageExamplesTool {
     description = "Return index statistics (files, symbols, edges, last updated)."
 )]
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
-pub struct GetIndexStatsTool {}
+pub struct GetIndexStatsTool {
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
+}
 
 #[macros::mcp_tool(
     name = "explore_dependency_graph",
@@ -113,6 +171,8 @@ pub struct ExploreDependencyGraphTool {
     pub direction: Option<String>,
     pub depth: Option<u32>,
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -123,6 +183,8 @@ pub struct ExploreDependencyGraphTool {
 pub struct GetSimilarityClusterTool {
     pub symbol_name: String,
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -133,6 +195,8 @@ pub struct GetSimilarityClusterTool {
 pub struct HydrateSymbolsTool {
     pub ids: Vec<String>,
     pub mode: Option<String>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -148,7 +212,7 @@ pub struct ReportSelectionTool {
 
 #[macros::mcp_tool(
     name = "explain_search",
-    description = "Return detailed scoring breakdown for search results to understand why results ranked the way they did."
+    description = "Return detailed scoring breakdown for search results to understand why results ranked the way they did. Filter terms of the query that could not be parsed are listed in query_errors."
 )]
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
 pub struct ExplainSearchTool {
@@ -156,6 +220,8 @@ pub struct ExplainSearchTool {
     pub limit: Option<u32>,
     pub exported_only: Option<bool>,
     pub verbose: Option<bool>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -169,6 +235,8 @@ pub struct FindSimilarCodeTool {
     pub file_path: Option<String>,
     pub limit: Option<u32>,
     pub threshold: Option<f32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -182,6 +250,8 @@ pub struct TraceDataFlowTool {
     pub direction: Option<String>,
     pub depth: Option<u32>,
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -193,11 +263,13 @@ pub struct SummarizeFileTool {
     pub file_path: String,
     pub include_signatures: Option<bool>,
     pub verbose: Option<bool>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
     name = "find_affected_code",
-    description = "Find code that would be affected if the given symbol changes (reverse dependencies)."
+    description = "Find code that would be affected if the given symbol changes (reverse dependencies), including client code in other services calling a route it handles."
 )]
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
 pub struct FindAffectedCodeTool {
@@ -206,6 +278,72 @@ pub struct FindAffectedCodeTool {
     pub depth: Option<u32>,
     pub limit: Option<u32>,
     pub include_tests: Option<bool>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
+}
+
+#[macros::mcp_tool(
+    name = "analyze_diff",
+    description = "Analyze the impact of a git diff: the symbols its hunks change, the code calling them transitively (with the depth at which each is reached), the exported symbols among both, and the test files linked to the changed and affected files. Compares a revision range ('main..feature', 'main...feature', a single revision against the working tree) or, without one, HEAD against the working tree including uncommitted and untracked changes. When the new side of a range is not what is checked out, it is indexed from the git object database without a checkout."
+)]
+#[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
+pub struct AnalyzeDiffTool {
+    /// Revision range as `git diff` takes it (e.g., 'HEAD~3..HEAD', 'main...feature', 'v1.2.0'). If not specified, compares HEAD with the working tree.
+    pub range: Option<String>,
+    /// How many edges to follow upstream from the changed symbols (default: 3)
+    pub depth: Option<u32>,
+    /// Maximum number of affected symbols to return (default: 200)
+    pub limit: Option<u32>,
+}
+
+#[macros::mcp_tool(
+    name = "compare_revisions",
+    description = "Compare two git revisions symbol by symbol: added, removed and renamed symbols, signature changes with the old and new declaration, symbols that became exported or stopped being, new and broken edges (calls, references, inheritance) and moved files. Each revision is indexed from the git object database without a checkout; without 'head', the base is compared with the working tree."
+)]
+#[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
+pub struct CompareRevisionsTool {
+    /// Revision to compare from (branch, tag or commit, e.g. 'v1.2.0', 'main', 'HEAD~5')
+    pub base: String,
+    /// Revision to compare to. If not specified, the working tree is used.
+    pub head: Option<String>,
+    /// Only report changes in files whose path contains this text (e.g. 'src/api/')
+    pub path: Option<String>,
+    /// Maximum number of entries to return for each kind of change (default: 50)
+    pub limit: Option<u32>,
+}
+
+#[macros::mcp_tool(
+    name = "check_api_surface",
+    description = "Get the public API surface of each package (exported symbols and the public members of exported types, with their signatures and the types they extend or implement) and check it for breaking changes against a git revision ('base') or against surface files written earlier ('baseline'). Each change is classified as breaking (removed export, new required or narrowed parameter, changed return type, removed base type) or not. 'write_to' writes one canonical surface file per package, to commit as a baseline."
+)]
+#[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
+pub struct CheckApiSurfaceTool {
+    /// Only check the package with this name
+    pub package: Option<String>,
+    /// Revision to check against (branch, tag or commit, e.g. 'main', 'v2.0.0')
+    pub base: Option<String>,
+    /// Directory of surface files written earlier to check against, relative to the base directory
+    pub baseline: Option<String>,
+    /// Revision whose surface is checked. If not specified, the working tree is used.
+    pub head: Option<String>,
+    /// Directory to write the surface files to, one per package, relative to the base directory
+    pub write_to: Option<String>,
+    /// Maximum number of changes or symbols to list (default: 100)
+    pub limit: Option<u32>,
+}
+
+#[macros::mcp_tool(
+    name = "get_change_history",
+    description = "Get the git history of a file or symbol: how many commits changed it and when, lines added and removed, its top authors, and its recent commits. For a file, also lists the files most often changed in the same commits and its most frequently changed symbols. A symbol's history covers the commits that touched its current lines."
+)]
+#[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
+pub struct GetChangeHistoryTool {
+    /// File to get the history of, or to look the symbol up in
+    pub file_path: Option<String>,
+    /// Symbol to get the history of; without it, the history of the file is returned
+    pub symbol_name: Option<String>,
+    /// Maximum number of commits, authors and related files to return (default: 10)
+    pub limit: Option<u32>,
 }
 
 #[macros::mcp_tool(
@@ -216,6 +354,8 @@ pub struct FindAffectedCodeTool {
 pub struct GetModuleSummaryTool {
     pub file_path: String,
     pub group_by_kind: Option<bool>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -232,6 +372,8 @@ pub struct SearchTodosTool {
     pub kind: Option<String>,
     /// Maximum number of results to return
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -246,6 +388,8 @@ pub struct FindTestsForSymbolTool {
     pub file_path: Option<String>,
     /// Maximum number of test files to return
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
@@ -260,22 +404,26 @@ pub struct SearchDecoratorsTool {
     pub decorator_type: Option<String>,
     /// Maximum number of results to return (default: 50)
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
 
 #[macros::mcp_tool(
     name = "search_framework_patterns",
-    description = "Search for framework-specific patterns in the codebase (e.g., Elysia routes, WebSocket handlers, middleware). Returns pattern metadata including file path, line, framework, kind, HTTP method, and route path."
+    description = "Search for framework-specific patterns in the codebase (e.g., Express, Koa, Hono, Fastify, NestJS, Elysia, FastAPI, Flask, Django, net/http, chi, gin and Spring routes, mounted routers, HTTP client calls, WebSocket handlers, middleware). Returns pattern metadata including file path, line, framework, kind, HTTP method, full route path (with router prefixes resolved across files), handler and the handler's symbol id."
 )]
 #[derive(Debug, Clone, Deserialize, Serialize, macros::JsonSchema)]
 pub struct SearchFrameworkPatternsTool {
-    /// Framework to filter by (e.g., 'elysia'). If not specified, returns patterns from all frameworks.
+    /// Framework to filter by (e.g., 'express', 'koa', 'hono', 'fastify', 'nestjs', 'elysia', 'fastapi', 'flask', 'django', 'net/http', 'chi', 'gin', 'spring', 'openapi'). If not specified, returns patterns from all frameworks.
     pub framework: Option<String>,
-    /// Pattern kind to filter by (e.g., 'route', 'websocket', 'plugin', 'middleware')
+    /// Pattern kind to filter by (e.g., 'route', 'mount', 'http_call', 'websocket', 'plugin', 'middleware')
     pub kind: Option<String>,
     /// HTTP method to filter by (e.g., 'GET', 'POST', 'PUT', 'DELETE')
     pub http_method: Option<String>,
-    /// Route path pattern to search for (e.g., '/api/users')
+    /// Route path to search for (e.g., '/api/users' matches any path containing it; '/api/*' matches every path under '/api')
     pub path: Option<String>,
     /// Maximum number of results to return (default: 50)
     pub limit: Option<u32>,
+    /// Git revision (branch, tag or commit) to answer as of, from an index built for it. If not specified, the working tree is used.
+    pub revision: Option<String>,
 }
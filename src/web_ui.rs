@@ -222,11 +222,14 @@ mod tests {
             rank_test_penalty: 0.1,
             rank_popularity_weight: 0.05,
             rank_popularity_cap: 50,
+            rank_history_weight: 0.05,
+            history_max_commits: 1000,
             index_patterns: vec![],
             exclude_patterns: vec![],
             watch_mode: false,
             watch_debounce_ms: 50,
             watch_min_index_interval_ms: 50,
+            watch_backend: crate::config::WatchBackend::Auto,
             max_context_bytes: 200_000,
             index_node_modules: false,
             repo_roots: vec![base_utf8],
@@ -267,6 +270,9 @@ mod tests {
             metrics_port: 9090,
             // Package detection config (09-04)
             package_detection_enabled: true,
+            mcp_transport: crate::config::McpTransport::Stdio,
+            http_bind_addr: "127.0.0.1:8765".to_string(),
+            http_auth_token: None,
         }
     }
 
@@ -1,5 +1,5 @@
 use code_intelligence_mcp_server::{
-    config::{Config, EmbeddingsBackend, EmbeddingsDevice},
+    config::{Config, EmbeddingsBackend, EmbeddingsDevice, McpTransport, WatchBackend},
     embeddings::hash::HashEmbedder,
     indexer::pipeline::IndexPipeline,
     metrics::MetricsRegistry,
@@ -59,6 +59,8 @@ fn test_config(base_dir: &std::path::Path) -> Config {
         rank_test_penalty: 0.1,
         rank_popularity_weight: 0.05,
         rank_popularity_cap: 50,
+        rank_history_weight: 0.05,
+        history_max_commits: 1000,
         index_patterns: vec![
             "**/*.ts".to_string(),
             "**/*.tsx".to_string(),
@@ -68,6 +70,7 @@ fn test_config(base_dir: &std::path::Path) -> Config {
         watch_mode: false,
         watch_debounce_ms: 100,
         watch_min_index_interval_ms: 50, // Small interval for tests
+        watch_backend: WatchBackend::Auto,
         max_context_bytes: 200_000,
         index_node_modules: false,
         repo_roots: vec![base_dir_utf8],
@@ -107,6 +110,9 @@ fn test_config(base_dir: &std::path::Path) -> Config {
         metrics_enabled: false,
         metrics_port: 9090,
         package_detection_enabled: false,
+        mcp_transport: McpTransport::Stdio,
+        http_bind_addr: "127.0.0.1:8765".to_string(),
+        http_auth_token: None,
     }
 }
 
@@ -348,6 +354,185 @@ export function beta() { return alpha() }
     assert!(found);
 }
 
+#[tokio::test]
+async fn index_paths_purges_deleted_files_and_moved_directories() {
+    let dir = tmp_dir();
+    std::fs::create_dir_all(dir.join("feature")).unwrap();
+    std::fs::write(dir.join("a.ts"), "export function alpha() { return 1 }\n").unwrap();
+    std::fs::write(
+        dir.join("feature/b.ts"),
+        "export function beta() { return 2 }\n",
+    )
+    .unwrap();
+
+    let config = Arc::new(test_config(&dir));
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+
+    let stats1 = indexer.index_all().await.unwrap();
+    assert_eq!(stats1.files_indexed, 2);
+
+    std::fs::remove_file(dir.join("a.ts")).unwrap();
+    std::fs::rename(dir.join("feature"), dir.join("moved")).unwrap();
+
+    let paths = ["a.ts", "feature", "moved"]
+        .iter()
+        .map(|p| config.base_dir.join(p))
+        .collect::<Vec<_>>();
+    let stats2 = indexer.index_paths(&paths).await.unwrap();
+    assert_eq!(stats2.files_deleted, 2);
+    assert_eq!(stats2.files_indexed, 1);
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+    assert!(sqlite.get_file_fingerprint("a.ts").unwrap().is_none());
+    assert!(sqlite.get_file_fingerprint("feature/b.ts").unwrap().is_none());
+    assert!(sqlite.list_symbols_by_file("feature/b.ts").unwrap().is_empty());
+    assert!(!sqlite.list_symbols_by_file("moved/b.ts").unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn reindexing_a_file_re_resolves_edges_of_its_dependents() {
+    let dir = tmp_dir();
+    std::fs::write(dir.join("util.ts"), "export function helper() { return 1 }\n").unwrap();
+    std::fs::write(
+        dir.join("app.ts"),
+        r#"import { helper } from "./util";
+import { Widget } from "@acme/widgets";
+
+export function run() { return helper() }
+export function render() { return Widget() }
+"#,
+    )
+    .unwrap();
+
+    let config = Arc::new(test_config(&dir));
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    indexer.index_all().await.unwrap();
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+    let symbol_id = |name: &str, file: &str| {
+        sqlite
+            .search_symbols_by_exact_name(name, Some(file), 1)
+            .unwrap()
+            .first()
+            .map(|s| s.id.clone())
+            .unwrap_or_else(|| panic!("{name} should be indexed in {file}"))
+    };
+    let has_edge = |from: &str, to: &str| {
+        sqlite
+            .list_edges_from(from, 100)
+            .unwrap()
+            .iter()
+            .any(|e| e.to_symbol_id == to)
+    };
+
+    let run = symbol_id("run", "app.ts");
+    let render = symbol_id("render", "app.ts");
+    assert!(has_edge(&run, &symbol_id("helper", "util.ts")));
+
+    // Re-indexing util.ts drops app.ts's edges into it; they must come back
+    sleep(Duration::from_millis(20)).await;
+    std::fs::write(
+        dir.join("util.ts"),
+        "export function helper() { return 42 }\n",
+    )
+    .unwrap();
+    let stats = indexer
+        .index_paths(&[config.base_dir.join("util.ts")])
+        .await
+        .unwrap();
+    assert_eq!(stats.files_indexed, 1);
+    assert_eq!(stats.files_reresolved, 1);
+    let helper = symbol_id("helper", "util.ts");
+    assert!(has_edge(&run, &helper));
+    assert!(sqlite.get_symbol_metrics(&helper).unwrap().is_some());
+
+    // A new file defining an imported name gets linked through the name dependency
+    std::fs::write(dir.join("widgets.ts"), "export function Widget() { return 0 }\n").unwrap();
+    let stats = indexer
+        .index_paths(&[config.base_dir.join("widgets.ts")])
+        .await
+        .unwrap();
+    assert_eq!(stats.files_reresolved, 1);
+    let widget = symbol_id("Widget", "widgets.ts");
+    assert!(has_edge(&render, &widget));
+    assert!(sqlite.get_symbol_metrics(&widget).unwrap().is_some());
+}
+
+#[tokio::test]
+async fn event_watch_reindexes_renamed_files() {
+    let dir = tmp_dir();
+    std::fs::write(dir.join("a.ts"), "export function alpha() { return 1 }\n").unwrap();
+
+    let mut config = test_config(&dir);
+    config.watch_debounce_ms = 50;
+    config.watch_min_index_interval_ms = 0;
+    config.watch_backend = WatchBackend::Events;
+    let config = Arc::new(config);
+
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    indexer.index_all().await.unwrap();
+
+    let handle = indexer.spawn_watch_loop();
+    sleep(Duration::from_millis(150)).await;
+
+    std::fs::rename(dir.join("a.ts"), dir.join("b.ts")).unwrap();
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+
+    let mut moved = false;
+    for _ in 0..40 {
+        let old = sqlite.list_symbols_by_file("a.ts").unwrap();
+        let new = sqlite.list_symbols_by_file("b.ts").unwrap();
+        if old.is_empty() && !new.is_empty() {
+            moved = true;
+            break;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+
+    handle.abort();
+    assert!(moved);
+}
+
 #[tokio::test]
 async fn multi_root_indexes_additional_repo_roots() {
     let dir = tmp_dir();
@@ -581,11 +766,10 @@ export const myConstant = 123;
         sqlite.upsert_repository(&repo_row).unwrap();
         eprintln!("Created repository: {:?}", repo_row);
 
-        // Then create the package - use relative manifest path without the filename
-        // The manifest_path should be the directory containing package.json for LIKE query to work
-        // get_package_for_file uses: WHERE file_path LIKE manifest_path || '%'
-        // So for file "mypackage/utils.ts", manifest_path should be "mypackage/"
-        let manifest_path = "mypackage/".to_string();
+        // Then create the package - use relative manifest path
+        // get_package_for_file matches files by the root_path prefix, the directory
+        // containing package.json: for file "mypackage/utils.ts", root_path is "mypackage/"
+        let manifest_path = "mypackage/package.json".to_string();
         eprintln!("Manifest path: {}", manifest_path);
 
         let mut hasher2 = DefaultHasher::new();
@@ -596,6 +780,7 @@ export const myConstant = 123;
             name: "mypackage".to_string(),
             version: Some("1.0.0".to_string()),
             manifest_path: manifest_path.clone(),
+            root_path: Some("mypackage/".to_string()),
             package_type: "npm".to_string(),
             created_at: 0,
         };
@@ -618,6 +803,760 @@ export const myConstant = 123;
 
     let pkg = package.unwrap();
     assert_eq!(pkg.name, "mypackage");
-    // manifest_path is the directory prefix for file matching, not the full package.json path
+    // root_path is the directory prefix for file matching, manifest_path the package.json path
     assert!(pkg.manifest_path.contains("mypackage"));
+    assert_eq!(pkg.root_path.as_deref(), Some("mypackage/"));
+}
+
+#[tokio::test]
+async fn ruby_classes_link_to_superclasses_and_mixins() {
+    let dir = tmp_dir();
+    let lib = dir.join("lib");
+    std::fs::create_dir_all(&lib).unwrap();
+    std::fs::write(lib.join("base.rb"), "class Base\n  def save; end\nend\n").unwrap();
+    std::fs::write(
+        lib.join("auditable.rb"),
+        "module Auditable\n  def audit!; end\nend\n",
+    )
+    .unwrap();
+    std::fs::write(
+        lib.join("invoice.rb"),
+        r#"require_relative "base"
+require_relative "auditable"
+
+class Invoice < Base
+  include Auditable
+
+  def total
+    tax(100)
+  end
+
+  def tax(amount)
+    amount / 5
+  end
+end
+"#,
+    )
+    .unwrap();
+
+    let config = Arc::new(test_config(&dir));
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    let stats = indexer.index_all().await.unwrap();
+    assert_eq!(stats.files_indexed, 3);
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+    let symbol = |name: &str, file: &str| {
+        sqlite
+            .search_symbols_by_exact_name(name, Some(file), 1)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| panic!("{name} should be indexed in {file}"))
+    };
+    let edge_type = |from: &str, to: &str| {
+        sqlite
+            .list_edges_from(from, 100)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.to_symbol_id == to)
+            .map(|e| e.edge_type)
+    };
+
+    let invoice = symbol("Invoice", "lib/invoice.rb");
+    assert_eq!(invoice.kind, "class");
+    assert_eq!(invoice.language, "ruby");
+    let total = symbol("total", "lib/invoice.rb");
+    assert_eq!(total.kind, "function");
+
+    let base = symbol("Base", "lib/base.rb");
+    let auditable = symbol("Auditable", "lib/auditable.rb");
+    assert_eq!(auditable.kind, "module");
+    assert_eq!(edge_type(&invoice.id, &base.id).as_deref(), Some("extends"));
+    assert_eq!(
+        edge_type(&invoice.id, &auditable.id).as_deref(),
+        Some("implements")
+    );
+    let tax = symbol("tax", "lib/invoice.rb");
+    assert_eq!(edge_type(&total.id, &tax.id).as_deref(), Some("call"));
+}
+
+#[tokio::test]
+async fn csharp_types_attributes_and_docs_are_indexed() {
+    let dir = tmp_dir();
+    let src = dir.join("src");
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(
+        src.join("Entity.cs"),
+        "namespace Shop;\n\npublic abstract class Entity\n{\n    public int Id { get; init; }\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        src.join("Order.cs"),
+        r#"using System;
+
+namespace Shop;
+
+/// <summary>A customer order.</summary>
+[Serializable]
+public class Order : Entity, IComparable<Order>
+{
+    /// <summary>Sum of all lines.</summary>
+    /// <returns>The order total.</returns>
+    public decimal Total()
+    {
+        return Round(0m);
+    }
+
+    private static decimal Round(decimal value) => value;
+
+    public int CompareTo(Order other) => 0;
+}
+"#,
+    )
+    .unwrap();
+
+    let config = Arc::new(test_config(&dir));
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    let stats = indexer.index_all().await.unwrap();
+    assert_eq!(stats.files_indexed, 2);
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+    let symbol = |name: &str, file: &str| {
+        sqlite
+            .search_symbols_by_exact_name(name, Some(file), 1)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| panic!("{name} should be indexed in {file}"))
+    };
+    let edge_type = |from: &str, to: &str| {
+        sqlite
+            .list_edges_from(from, 100)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.to_symbol_id == to)
+            .map(|e| e.edge_type)
+    };
+
+    let order = symbol("Order", "src/Order.cs");
+    assert_eq!(order.kind, "class");
+    assert_eq!(order.language, "csharp");
+    let entity = symbol("Entity", "src/Entity.cs");
+    assert_eq!(edge_type(&order.id, &entity.id).as_deref(), Some("extends"));
+    assert_eq!(symbol("Id", "src/Entity.cs").kind, "property");
+
+    let total = symbol("Total", "src/Order.cs");
+    let round = symbol("Round", "src/Order.cs");
+    assert!(!round.exported);
+    assert_eq!(edge_type(&total.id, &round.id).as_deref(), Some("call"));
+
+    // Attributes and XML docs are stored against the indexed symbols
+    let attributes = sqlite
+        .search_decorators_by_name(Some("Serializable"), None, 10)
+        .unwrap();
+    assert_eq!(attributes.len(), 1);
+    assert_eq!(attributes[0].symbol_id, order.id);
+    let doc = sqlite.get_docstring_by_symbol(&total.id).unwrap().unwrap();
+    assert_eq!(doc.summary.as_deref(), Some("Sum of all lines."));
+    assert_eq!(doc.returns_text.as_deref(), Some("The order total."));
+    assert!(sqlite.has_docstring(&order.id).unwrap());
+}
+
+#[tokio::test]
+async fn jvm_calls_and_supertypes_cross_language_boundaries() {
+    let dir = tmp_dir();
+    let java = dir.join("src/main/java/com/acme");
+    let kotlin = dir.join("src/main/kotlin/com/acme");
+    let scala = dir.join("src/main/scala/com/acme");
+    for path in [&java, &kotlin, &scala] {
+        std::fs::create_dir_all(path).unwrap();
+    }
+    // Java is indexed first, so its call into Kotlin resolves only once the Kotlin
+    // file has been indexed too
+    std::fs::write(
+        java.join("Pricing.java"),
+        r#"package com.acme;
+
+public class Pricing {
+    public static int applyDiscount(int total) {
+        return total - 10;
+    }
+
+    public static int quote(Checkout checkout) {
+        return checkout.total(java.util.List.of(1, 2));
+    }
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        kotlin.join("Checkout.kt"),
+        r#"package com.acme
+
+open class Checkout {
+    fun total(items: List<Int>): Int {
+        return Pricing.applyDiscount(items.sum())
+    }
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        scala.join("ExpressCheckout.scala"),
+        r#"package com.acme
+
+class ExpressCheckout extends Checkout {
+  def charge(items: List[Int]): Int = total(items) + 5
+}
+"#,
+    )
+    .unwrap();
+
+    let config = Arc::new(test_config(&dir));
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    let stats = indexer.index_all().await.unwrap();
+    assert_eq!(stats.files_indexed, 3);
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+    let symbol = |name: &str, file: &str| {
+        sqlite
+            .search_symbols_by_exact_name(name, Some(file), 1)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| panic!("{name} should be indexed in {file}"))
+    };
+    let edge_type = |from: &str, to: &str| {
+        sqlite
+            .list_edges_from(from, 100)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.to_symbol_id == to)
+            .map(|e| e.edge_type)
+    };
+
+    let checkout = symbol("Checkout", "src/main/kotlin/com/acme/Checkout.kt");
+    assert_eq!(checkout.kind, "class");
+    assert_eq!(checkout.language, "kotlin");
+    let total = symbol("total", "src/main/kotlin/com/acme/Checkout.kt");
+    assert_eq!(total.kind, "function");
+    let express = symbol("ExpressCheckout", "src/main/scala/com/acme/ExpressCheckout.scala");
+    assert_eq!(express.language, "scala");
+    let charge = symbol("charge", "src/main/scala/com/acme/ExpressCheckout.scala");
+    let apply_discount = symbol("applyDiscount", "src/main/java/com/acme/Pricing.java");
+    let quote = symbol("quote", "src/main/java/com/acme/Pricing.java");
+
+    // Kotlin -> Java, Java -> Kotlin, Scala -> Kotlin
+    assert_eq!(edge_type(&total.id, &apply_discount.id).as_deref(), Some("call"));
+    assert_eq!(edge_type(&quote.id, &total.id).as_deref(), Some("call"));
+    assert_eq!(edge_type(&charge.id, &total.id).as_deref(), Some("call"));
+    assert_eq!(edge_type(&express.id, &checkout.id).as_deref(), Some("extends"));
+}
+
+#[tokio::test]
+async fn package_scoped_names_resolve_only_within_visible_packages() {
+    let dir = tmp_dir();
+    let files = [
+        (
+            "src/main/java/com/acme/billing/Ledger.java",
+            r#"package com.acme.billing;
+
+public class Ledger {
+    public static int round(int cents) {
+        return cents / 100;
+    }
+}
+"#,
+        ),
+        (
+            "src/main/java/com/acme/shipping/Ledger.java",
+            r#"package com.acme.shipping;
+
+public class Ledger {
+    public static int round(int grams) {
+        return grams / 1000;
+    }
+}
+"#,
+        ),
+        (
+            "src/main/kotlin/com/acme/shipping/Rates.kt",
+            r#"package com.acme.shipping
+
+class Rates {
+    fun quote(grams: Int): Int {
+        return Ledger.round(grams)
+    }
+}
+"#,
+        ),
+        (
+            "src/main/scala/com/acme/reports/Summary.scala",
+            r#"package com.acme.reports
+
+import com.acme.billing._
+
+class Summary {
+  def total(cents: Int): Int = Ledger.round(cents)
+}
+"#,
+        ),
+        (
+            "src/main/java/com/acme/audit/Audit.java",
+            r#"package com.acme.audit;
+
+public class Audit {
+    public static int check(int cents) {
+        return Ledger.round(cents);
+    }
+}
+"#,
+        ),
+        (
+            "src/Billing/Invoice.cs",
+            r#"namespace Acme.Billing
+{
+    public class Invoice
+    {
+        public static int Total(int cents) { return cents; }
+    }
+}
+"#,
+        ),
+        (
+            "src/Shipping/Invoice.cs",
+            r#"namespace Acme.Shipping
+{
+    public class Invoice
+    {
+        public static int Total(int grams) { return grams; }
+    }
+}
+"#,
+        ),
+        (
+            "src/Reports/Statement.cs",
+            r#"using Acme.Shipping;
+
+namespace Acme.Reports
+{
+    public class Statement
+    {
+        public int Amount(int grams) { return Invoice.Total(grams); }
+    }
+}
+"#,
+        ),
+        (
+            "src/Audit/Check.cs",
+            r#"namespace Acme.Audit
+{
+    public class Check
+    {
+        public int Amount(int cents) { return Invoice.Total(cents); }
+    }
+}
+"#,
+        ),
+    ];
+    for (path, source) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+
+    let config = Arc::new(test_config(&dir));
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    let stats = indexer.index_all().await.unwrap();
+    assert_eq!(stats.files_indexed, files.len());
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+    let symbol_id = |name: &str, file: &str| {
+        sqlite
+            .search_symbols_by_exact_name(name, Some(file), 1)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| panic!("{name} should be indexed in {file}"))
+            .id
+    };
+    let callees = |from: &str| -> Vec<String> {
+        sqlite
+            .list_edges_from(from, 100)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.edge_type == "call")
+            .map(|e| e.to_symbol_id)
+            .collect()
+    };
+
+    let billing_round = symbol_id("round", "src/main/java/com/acme/billing/Ledger.java");
+    let shipping_round = symbol_id("round", "src/main/java/com/acme/shipping/Ledger.java");
+    // Its own package, though another package defining the same name sorts first
+    let quote = callees(&symbol_id(
+        "quote",
+        "src/main/kotlin/com/acme/shipping/Rates.kt",
+    ));
+    assert!(quote.contains(&shipping_round), "{quote:?}");
+    assert!(!quote.contains(&billing_round), "{quote:?}");
+    // A wildcard import
+    let total = callees(&symbol_id(
+        "total",
+        "src/main/scala/com/acme/reports/Summary.scala",
+    ));
+    assert!(total.contains(&billing_round), "{total:?}");
+    assert!(!total.contains(&shipping_round), "{total:?}");
+    // Neither package is visible
+    let check = callees(&symbol_id(
+        "check",
+        "src/main/java/com/acme/audit/Audit.java",
+    ));
+    assert!(!check.contains(&billing_round), "{check:?}");
+    assert!(!check.contains(&shipping_round), "{check:?}");
+
+    let billing_total = symbol_id("Total", "src/Billing/Invoice.cs");
+    let shipping_total = symbol_id("Total", "src/Shipping/Invoice.cs");
+    // A `using` directive
+    let statement = callees(&symbol_id("Amount", "src/Reports/Statement.cs"));
+    assert!(statement.contains(&shipping_total), "{statement:?}");
+    assert!(!statement.contains(&billing_total), "{statement:?}");
+    let audit = callees(&symbol_id("Amount", "src/Audit/Check.cs"));
+    assert!(!audit.contains(&billing_total), "{audit:?}");
+    assert!(!audit.contains(&shipping_total), "{audit:?}");
+}
+
+#[tokio::test]
+async fn maven_modules_are_packages_with_cross_package_edges() {
+    let dir = tmp_dir();
+    let git_init = std::process::Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(&dir)
+        .status();
+    if !git_init.is_ok_and(|s| s.success()) {
+        eprintln!("git is not available, skipping");
+        return;
+    }
+
+    std::fs::write(
+        dir.join("pom.xml"),
+        r#"<project>
+  <groupId>com.acme</groupId>
+  <artifactId>shop-parent</artifactId>
+  <version>${revision}</version>
+  <packaging>pom</packaging>
+  <properties><revision>1.2.0</revision></properties>
+  <modules>
+    <module>core</module>
+    <module>api</module>
+  </modules>
+</project>
+"#,
+    )
+    .unwrap();
+    for (module, dependency) in [("core", ""), ("api", "shop-core")] {
+        let dependencies = if dependency.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<dependencies><dependency><groupId>${{project.groupId}}</groupId><artifactId>{dependency}</artifactId></dependency></dependencies>"
+            )
+        };
+        std::fs::create_dir_all(dir.join(module)).unwrap();
+        std::fs::write(
+            dir.join(module).join("pom.xml"),
+            format!(
+                "<project>\n  <parent><groupId>com.acme</groupId><artifactId>shop-parent</artifactId><version>${{revision}}</version></parent>\n  <artifactId>shop-{module}</artifactId>\n  {dependencies}\n</project>\n"
+            ),
+        )
+        .unwrap();
+    }
+    let core_src = dir.join("core/src/main/java/com/acme/core");
+    let api_src = dir.join("api/src/main/java/com/acme/api");
+    std::fs::create_dir_all(&core_src).unwrap();
+    std::fs::create_dir_all(&api_src).unwrap();
+    std::fs::write(
+        core_src.join("Pricing.java"),
+        r#"package com.acme.core;
+
+public class Pricing {
+    public static int applyDiscount(int total) {
+        return total - 10;
+    }
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        api_src.join("Checkout.java"),
+        r#"package com.acme.api;
+
+import com.acme.core.Pricing;
+
+public class Checkout {
+    public int total(int amount) {
+        return Pricing.applyDiscount(amount);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let mut config = test_config(&dir);
+    config.package_detection_enabled = true;
+    let config = Arc::new(config);
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    indexer.index_all().await.unwrap();
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+
+    let packages = sqlite.list_all_packages().unwrap();
+    let package = |name: &str| {
+        packages
+            .iter()
+            .find(|p| p.name == name)
+            .unwrap_or_else(|| panic!("{name} should be registered, got {packages:?}"))
+    };
+    let api = package("com.acme:shop-api");
+    let core = package("com.acme:shop-core");
+    assert_eq!(package("com.acme:shop-parent").package_type, "maven");
+    assert_eq!(api.version.as_deref(), Some("1.2.0"));
+    assert_eq!(api.manifest_path, "api/pom.xml");
+    assert_eq!(api.root_path.as_deref(), Some("api/"));
+
+    let api_dependencies = sqlite.list_package_dependencies(&api.id).unwrap();
+    assert_eq!(api_dependencies.len(), 1);
+    assert_eq!(api_dependencies[0].id, core.id);
+
+    let checkout_file = "api/src/main/java/com/acme/api/Checkout.java";
+    assert_eq!(
+        sqlite.get_package_for_file(checkout_file).unwrap().map(|p| p.id),
+        Some(api.id.clone())
+    );
+
+    let total = sqlite
+        .search_symbols_by_exact_name("total", Some(checkout_file), 1)
+        .unwrap()
+        .into_iter()
+        .next()
+        .expect("total should be indexed");
+    let edges = sqlite.list_edges_from(&total.id, 100).unwrap();
+    assert!(
+        edges
+            .iter()
+            .any(|e| e.resolution.starts_with("cross-package")),
+        "expected a cross-package edge from total, got {edges:?}"
+    );
+}
+
+#[tokio::test]
+async fn symbol_ids_survive_edits_and_renames() {
+    let dir = tmp_dir();
+    let source = r#"export class Cart {
+  total(items: number[]) { return items.length }
+}
+
+export function checkout(cart: Cart) { return cart.total([]) }
+"#;
+    std::fs::write(dir.join("cart.ts"), source).unwrap();
+
+    let mut config = test_config(&dir);
+    config.parallel_workers = 1;
+    let config = Arc::new(config);
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    indexer.index_all().await.unwrap();
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+    let symbol_id = |name: &str, file: &str| {
+        sqlite
+            .search_symbols_by_exact_name(name, Some(file), 1)
+            .unwrap()
+            .first()
+            .map(|s| s.id.clone())
+            .unwrap_or_else(|| panic!("{name} should be indexed in {file}"))
+    };
+    let total = symbol_id("total", "cart.ts");
+    let checkout = symbol_id("checkout", "cart.ts");
+    sqlite
+        .insert_query_selection("cart total", "cart total", &total, 0)
+        .unwrap();
+    assert_eq!(sqlite.get_symbol_selection_count(&total).unwrap(), 1);
+
+    // Code added above shifts every symbol; their ids and learned signals stay
+    sleep(Duration::from_millis(20)).await;
+    std::fs::write(
+        dir.join("cart.ts"),
+        format!("// Shopping cart\nexport const TAX = 0.2;\n\n{source}"),
+    )
+    .unwrap();
+    indexer
+        .index_paths(&[config.base_dir.join("cart.ts")])
+        .await
+        .unwrap();
+    assert_eq!(symbol_id("total", "cart.ts"), total);
+    assert_eq!(symbol_id("checkout", "cart.ts"), checkout);
+    assert_eq!(sqlite.get_symbol_selection_count(&total).unwrap(), 1);
+
+    // A rename detected by content keeps the ids too
+    std::fs::rename(dir.join("cart.ts"), dir.join("basket.ts")).unwrap();
+    let stats = indexer
+        .index_paths(&[
+            config.base_dir.join("cart.ts"),
+            config.base_dir.join("basket.ts"),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(stats.files_deleted, 1);
+    assert!(sqlite.list_symbols_by_file("cart.ts").unwrap().is_empty());
+    assert_eq!(symbol_id("total", "basket.ts"), total);
+    assert_eq!(symbol_id("checkout", "basket.ts"), checkout);
+    assert_eq!(sqlite.get_symbol_selection_count(&total).unwrap(), 1);
+
+    // A new file at the old path gets ids of its own
+    std::fs::write(dir.join("cart.ts"), "export function checkout() { return 0 }\n").unwrap();
+    indexer
+        .index_paths(&[config.base_dir.join("cart.ts")])
+        .await
+        .unwrap();
+    assert_ne!(symbol_id("checkout", "cart.ts"), checkout);
+}
+
+#[tokio::test]
+async fn index_paths_rediscovers_packages_when_a_manifest_changes() {
+    let dir = tmp_dir();
+    let git_init = std::process::Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(&dir)
+        .status();
+    if !git_init.is_ok_and(|s| s.success()) {
+        eprintln!("git is not available, skipping");
+        return;
+    }
+    std::fs::write(dir.join("package.json"), r#"{"name": "shop"}"#).unwrap();
+    std::fs::write(dir.join("a.ts"), "export function alpha() { return 1 }\n").unwrap();
+
+    let config = Arc::new(Config {
+        package_detection_enabled: true,
+        ..test_config(&dir)
+    });
+    let tantivy = Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
+    let embedder = Arc::new(Mutex::new(
+        Box::new(HashEmbedder::new(config.hash_embedding_dim)) as _,
+    ));
+    let lancedb = LanceDbStore::connect(&config.vector_db_path).await.unwrap();
+    let vectors = Arc::new(
+        lancedb
+            .open_or_create_table("symbols", config.hash_embedding_dim)
+            .await
+            .unwrap(),
+    );
+    let metrics = Arc::new(MetricsRegistry::new().unwrap());
+    let indexer = IndexPipeline::new(config.clone(), tantivy, vectors, embedder, metrics);
+    indexer.index_all().await.unwrap();
+
+    let sqlite = SqliteStore::open(config.db_path.as_path()).unwrap();
+    sqlite.init().unwrap();
+    let package_of = |file: &str| {
+        sqlite
+            .get_package_for_file(file)
+            .unwrap()
+            .map(|p| p.name)
+            .unwrap_or_default()
+    };
+    assert_eq!(package_of("cart/index.ts"), "shop");
+
+    // A new nested package shows up through its manifest alone
+    std::fs::create_dir_all(dir.join("cart")).unwrap();
+    std::fs::write(dir.join("cart/package.json"), r#"{"name": "@shop/cart"}"#).unwrap();
+    std::fs::write(
+        dir.join("cart/index.ts"),
+        "export function total() { return 2 }\n",
+    )
+    .unwrap();
+    indexer
+        .index_paths(&[
+            config.base_dir.join("cart/package.json"),
+            config.base_dir.join("cart/index.ts"),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(package_of("cart/index.ts"), "@shop/cart");
+    assert_eq!(package_of("a.ts"), "shop");
 }
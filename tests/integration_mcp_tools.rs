@@ -7,12 +7,12 @@
 mod support;
 
 use code_intelligence_mcp_server::{
-    config::{Config, EmbeddingsBackend, EmbeddingsDevice},
+    config::{Config, EmbeddingsBackend, EmbeddingsDevice, McpTransport, WatchBackend},
     embeddings::hash::HashEmbedder,
     handlers::{
-        handle_explain_search, handle_find_affected_code, handle_find_similar_code,
-        handle_get_module_summary, handle_report_selection, handle_summarize_file,
-        handle_trace_data_flow,
+        handle_explain_search, handle_find_affected_code, handle_find_references,
+        handle_find_similar_code, handle_get_definition, handle_get_file_symbols, handle_get_module_summary, handle_report_selection, handle_resolve_position,
+        handle_summarize_file, handle_trace_data_flow,
     },
     metrics::MetricsRegistry,
     path::Utf8PathBuf,
@@ -23,8 +23,10 @@ use code_intelligence_mcp_server::{
         vector::LanceDbStore,
     },
     tools::{
-        ExplainSearchTool, FindAffectedCodeTool, FindSimilarCodeTool, GetModuleSummaryTool,
-        ReportSelectionTool, SummarizeFileTool, TraceDataFlowTool,
+        ExplainSearchTool, FindAffectedCodeTool, FindReferencesTool, FindSimilarCodeTool,
+        GetDefinitionTool,
+        GetFileSymbolsTool, GetModuleSummaryTool,
+        ReportSelectionTool, ResolvePositionTool, SummarizeFileTool, TraceDataFlowTool,
     },
 };
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -76,6 +78,8 @@ fn create_test_symbol(
         start_line: 1,
         end_line: 10,
         text: format!("pub fn {}() {{}}", name),
+        qualified_name: name.to_string(),
+        parent_id: None,
     };
 
     sqlite.upsert_symbol(&symbol)?;
@@ -143,6 +147,7 @@ async fn create_app_state(db_path: &Path, suffix: &str) -> code_intelligence_mcp
         indexer,
         retriever,
         sqlite,
+        snapshots: Default::default(),
     }
 }
 
@@ -188,6 +193,7 @@ async fn test_summarize_file_tool() {
         file_path: "src/test.rs".to_string(),
         include_signatures: Some(false),
         verbose: Some(false),
+        revision: None,
     };
 
     let result = handle_summarize_file(&state, params).unwrap();
@@ -237,6 +243,7 @@ async fn test_summarize_file_with_signatures() {
         file_path: "src/module.ts".to_string(),
         include_signatures: Some(true),
         verbose: Some(false),
+        revision: None,
     };
 
     let result = handle_summarize_file(&state, params).unwrap();
@@ -293,6 +300,7 @@ async fn test_summarize_file_verbose_includes_internal() {
         file_path: "src/module.ts".to_string(),
         include_signatures: Some(true),
         verbose: Some(true), // verbose=true should include internal symbols
+        revision: None,
     };
 
     let result = handle_summarize_file(&state, params).unwrap();
@@ -316,6 +324,7 @@ async fn test_summarize_file_not_found() {
         file_path: "nonexistent.rs".to_string(),
         include_signatures: Some(false),
         verbose: Some(false),
+        revision: None,
     };
 
     let result = handle_summarize_file(&state, params).unwrap();
@@ -345,7 +354,14 @@ mod get_module_summary_tests {
 
     /// Helper to create AppState for async tests
     /// This must be called within async context to avoid runtime conflicts
-    async fn create_async_app_state() -> (code_intelligence_mcp_server::handlers::AppState, std::path::PathBuf) {
+    pub(crate) async fn create_async_app_state() -> (code_intelligence_mcp_server::handlers::AppState, std::path::PathBuf) {
+        create_async_app_state_with(|_| {}).await
+    }
+
+    /// Like `create_async_app_state`, with the test config adjusted first
+    pub(crate) async fn create_async_app_state_with(
+        configure: impl FnOnce(&mut code_intelligence_mcp_server::config::Config),
+    ) -> (code_intelligence_mcp_server::handlers::AppState, std::path::PathBuf) {
         use code_intelligence_mcp_server::handlers::AppState;
         use code_intelligence_mcp_server::indexer::pipeline::IndexPipeline;
         use code_intelligence_mcp_server::retrieval::Retriever;
@@ -366,7 +382,9 @@ mod get_module_summary_tests {
         ));
         std::fs::create_dir_all(&base_dir).unwrap();
 
-        let config = std::sync::Arc::new(super::test_config(&base_dir));
+        let mut config = super::test_config(&base_dir);
+        configure(&mut config);
+        let config = std::sync::Arc::new(config);
 
         // Create storage components async
         let tantivy = std::sync::Arc::new(TantivyIndex::open_or_create(&config.tantivy_index_path).unwrap());
@@ -411,6 +429,7 @@ mod get_module_summary_tests {
             indexer,
             retriever,
             sqlite,
+            snapshots: Default::default(),
         };
 
         (app_state, base_dir)
@@ -443,6 +462,7 @@ function internalFunc() {
         let params = GetModuleSummaryTool {
             file_path: "src/module.ts".to_string(),
             group_by_kind: Some(true),
+            revision: None,
         };
 
         let result = handle_get_module_summary(&app_state, params).unwrap();
@@ -487,6 +507,7 @@ export function myFunction() {
         let params = GetModuleSummaryTool {
             file_path: "src/api.ts".to_string(),
             group_by_kind: Some(false), // Flat output
+            revision: None,
         };
 
         let result = handle_get_module_summary(&app_state, params).unwrap();
@@ -532,6 +553,7 @@ function internalFunc() {
         let params = GetModuleSummaryTool {
             file_path: "src/internal.ts".to_string(),
             group_by_kind: Some(false),
+            revision: None,
         };
 
         let result = handle_get_module_summary(&app_state, params).unwrap();
@@ -564,6 +586,7 @@ export function myFunction(param: string): number {
         let params = GetModuleSummaryTool {
             file_path: "src/utils.ts".to_string(),
             group_by_kind: Some(false),
+            revision: None,
         };
 
         let result = handle_get_module_summary(&app_state, params).unwrap();
@@ -609,6 +632,7 @@ async fn test_trace_data_flow_tool() {
         direction: Some("both".to_string()),
         depth: Some(2),
         limit: Some(50),
+        revision: None,
     };
 
     let result = handle_trace_data_flow(&state, params).unwrap();
@@ -631,6 +655,7 @@ async fn test_trace_data_flow_not_found() {
         direction: Some("both".to_string()),
         depth: Some(2),
         limit: Some(50),
+        revision: None,
     };
 
     let result = handle_trace_data_flow(&state, params).unwrap();
@@ -667,6 +692,7 @@ async fn test_find_affected_code_tool() {
         depth: Some(2),
         limit: Some(50),
         include_tests: Some(false),
+        revision: None,
     };
 
     let result = handle_find_affected_code(&state, params).unwrap();
@@ -688,6 +714,7 @@ async fn test_find_affected_code_not_found() {
         depth: Some(2),
         limit: Some(50),
         include_tests: Some(false),
+        revision: None,
     };
 
     let result = handle_find_affected_code(&state, params).unwrap();
@@ -816,6 +843,8 @@ fn test_config(base_dir: &Path) -> Config {
         rank_test_penalty: 0.1,
         rank_popularity_weight: 0.05,
         rank_popularity_cap: 50,
+        rank_history_weight: 0.05,
+        history_max_commits: 1000,
         index_patterns: vec![
             "**/*.ts".to_string(),
             "**/*.tsx".to_string(),
@@ -825,6 +854,7 @@ fn test_config(base_dir: &Path) -> Config {
         watch_mode: false,
         watch_debounce_ms: 100,
         watch_min_index_interval_ms: 50,
+        watch_backend: WatchBackend::Auto,
         max_context_bytes: 200_000,
         index_node_modules: false,
         repo_roots: vec![base_dir_utf8],
@@ -855,6 +885,9 @@ fn test_config(base_dir: &Path) -> Config {
         metrics_enabled: false,
         metrics_port: 9090,
         package_detection_enabled: false,
+        mcp_transport: McpTransport::Stdio,
+        http_bind_addr: "127.0.0.1:8765".to_string(),
+        http_auth_token: None,
     }
 }
 
@@ -922,6 +955,7 @@ async fn test_explain_search_tool() {
         limit: Some(5),
         exported_only: Some(false),
         verbose: Some(false),
+        revision: None,
     };
 
     let result = handle_explain_search(&retriever, params).await.unwrap();
@@ -953,6 +987,7 @@ async fn test_explain_search_verbose() {
         limit: Some(10),
         exported_only: Some(true),
         verbose: Some(true),
+        revision: None,
     };
 
     let result = handle_explain_search(&retriever, params).await.unwrap();
@@ -985,6 +1020,7 @@ async fn test_find_similar_code_by_symbol_name() {
         file_path: Some(dir.join("search.ts").to_str().unwrap().to_string()),
         limit: Some(10),
         threshold: Some(0.1), // Low threshold for testing
+        revision: None,
     };
 
     // Create AppState - need to reconstruct the components
@@ -1025,6 +1061,7 @@ async fn test_find_similar_code_by_symbol_name() {
         indexer,
         retriever,
         sqlite: Arc::new(SqliteStore::open(config.db_path.as_path()).unwrap()),
+        snapshots: Default::default(),
     };
 
     // The handler might fail if embedding isn't found, so check both success and error cases
@@ -1063,6 +1100,7 @@ async fn test_find_similar_code_by_code_snippet() {
         file_path: None,
         limit: Some(5),
         threshold: Some(0.0), // Zero threshold to get any results
+        revision: None,
     };
 
     // Create AppState
@@ -1103,6 +1141,7 @@ async fn test_find_similar_code_by_code_snippet() {
         indexer,
         retriever,
         sqlite: Arc::new(SqliteStore::open(config.db_path.as_path()).unwrap()),
+        snapshots: Default::default(),
     };
 
     let result = handle_find_similar_code(&state, params).await.unwrap();
@@ -1121,6 +1160,7 @@ async fn test_find_similar_code_not_found() {
         file_path: Some(dir.join("search.ts").to_str().unwrap().to_string()),
         limit: Some(5),
         threshold: Some(0.5),
+        revision: None,
     };
 
     // Create AppState
@@ -1161,6 +1201,7 @@ async fn test_find_similar_code_not_found() {
         indexer,
         retriever,
         sqlite: Arc::new(SqliteStore::open(config.db_path.as_path()).unwrap()),
+        snapshots: Default::default(),
     };
 
     let result = handle_find_similar_code(&state, params).await.unwrap();
@@ -1171,6 +1212,1323 @@ async fn test_find_similar_code_not_found() {
     );
 }
 
+// ============================================================================
+// Tests for the LSP front-end
+// ============================================================================
+
+#[tokio::test]
+async fn test_resolve_position_follows_edges_past_name_collisions() {
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    std::fs::create_dir_all(base_dir.join("src")).unwrap();
+    std::fs::write(
+        base_dir.join("src/util.ts"),
+        "export function helper(x: number) {\n  return x + 1;\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("src/other.ts"),
+        "export function helper(s: string) {\n  return s;\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("src/app.ts"),
+        "import { helper } from \"./util\";\n\nexport function run() {\n  return helper(2);\n}\n",
+    )
+    .unwrap();
+    state.indexer.index_all().await.unwrap();
+
+    let resolve = |file_path: &str, line: u32, column: u32| {
+        handle_resolve_position(
+            &state,
+            ResolvePositionTool {
+                file_path: file_path.to_string(),
+                line,
+                column,
+                revision: None,
+            },
+        )
+        .unwrap()
+    };
+
+    // `helper(2)` inside run(): two symbols are named helper, the edge picks util.ts
+    let result = resolve("src/app.ts", 4, 12);
+    assert_eq!(result["identifier"]["name"], "helper", "{result}");
+    assert_eq!(result["identifier"]["start_column"], 10);
+    assert_eq!(result["enclosing_symbol"]["name"], "run");
+    let resolved = &result["resolved"];
+    assert_eq!(resolved["via"], "edge", "{result}");
+    assert_eq!(resolved["edge_type"], "call");
+    assert_eq!(resolved["at_cursor"], true);
+    assert_eq!(resolved["symbol"]["file_path"], "src/util.ts");
+
+    // The id is usable with the id-based tools
+    let id = resolved["symbol"]["id"].as_str().unwrap();
+    let row = state.sqlite.get_symbol_by_id(id).unwrap().unwrap();
+    assert_eq!(row.name, "helper");
+
+    // On a declaration's name the target is the declaration itself
+    let absolute = base_dir.join("src/other.ts");
+    let result = resolve(absolute.to_str().unwrap(), 1, 18);
+    assert_eq!(result["file_path_normalized"], "src/other.ts");
+    assert_eq!(result["resolved"]["via"], "declaration", "{result}");
+    assert_eq!(result["resolved"]["symbol"]["file_path"], "src/other.ts");
+
+    // Whitespace has no identifier and nothing to resolve
+    let result = resolve("src/app.ts", 4, 1);
+    assert!(result["identifier"].is_null());
+    assert!(result["resolved"].is_null());
+    assert_eq!(result["enclosing_symbol"]["name"], "run");
+
+    assert!(handle_resolve_position(
+        &state,
+        ResolvePositionTool {
+            file_path: "src/app.ts".to_string(),
+            line: 0,
+            column: 1,
+            revision: None,
+        },
+    )
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_qualified_lookups_and_symbol_tree() {
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    std::fs::create_dir_all(base_dir.join("src")).unwrap();
+    std::fs::write(
+        base_dir.join("src/services.ts"),
+        r#"export class UserService {
+  get(id: string) {
+    return id;
+  }
+}
+
+export class OrderService {
+  get(id: string) {
+    return id;
+  }
+
+  list() {
+    return this.get("all");
+  }
+}
+"#,
+    )
+    .unwrap();
+    state.indexer.index_all().await.unwrap();
+
+    let definitions = |symbol_name: &str| {
+        handle_get_definition(
+            &state,
+            GetDefinitionTool {
+                symbol_name: symbol_name.to_string(),
+                file: None,
+                limit: None,
+                revision: None,
+            },
+        )
+    };
+
+    let all = definitions("get").await.unwrap();
+    assert_eq!(all["count"], 2, "{all}");
+
+    for query in ["OrderService.get", "OrderService::get"] {
+        let result = definitions(query).await.unwrap();
+        assert_eq!(result["count"], 1, "{result}");
+        let definition = &result["definitions"][0];
+        assert_eq!(definition["qualified_name"], "OrderService.get");
+        assert_eq!(definition["start_line"], 8);
+
+        let parent_id = definition["parent_id"].as_str().unwrap();
+        let parent = state.sqlite.get_symbol_by_id(parent_id).unwrap().unwrap();
+        assert_eq!(parent.name, "OrderService");
+    }
+
+    // `this.get` inside OrderService calls its own get, not UserService's
+    let list = state
+        .sqlite
+        .search_symbols_by_exact_name("OrderService.list", None, 1)
+        .unwrap()
+        .remove(0);
+    let callees: Vec<String> = state
+        .sqlite
+        .list_edges_from(&list.id, 100)
+        .unwrap()
+        .into_iter()
+        .filter(|e| e.edge_type == "call")
+        .map(|e| e.to_symbol_id)
+        .collect();
+    let order_get = definitions("OrderService.get").await.unwrap()["definitions"][0]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(callees, vec![order_get]);
+
+    let tree = handle_get_file_symbols(
+        &state,
+        GetFileSymbolsTool {
+            file_path: "src/services.ts".to_string(),
+            exported_only: None,
+            tree: Some(true),
+            revision: None,
+        },
+    )
+    .unwrap();
+    let roots = tree["symbols"].as_array().unwrap();
+    let names = |nodes: &[serde_json::Value]| -> Vec<String> {
+        nodes
+            .iter()
+            .map(|n| n["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(names(roots), vec!["UserService", "OrderService"], "{tree}");
+    let members = roots[1]["children"].as_array().unwrap();
+    assert_eq!(names(members), vec!["get", "list"]);
+    assert_eq!(tree["count"], 5);
+}
+
+#[tokio::test]
+async fn test_resource_files_are_indexed_and_linked_from_code() {
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    for dir in ["docs", "db", "proto", "src"] {
+        std::fs::create_dir_all(base_dir.join(dir)).unwrap();
+    }
+    std::fs::write(
+        base_dir.join("docs/billing.md"),
+        "# Billing\n\nHow invoices work.\n\n## Refunds\n\nRefunds are manual.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("db/schema.sql"),
+        "CREATE TABLE invoices (\n  id BIGINT PRIMARY KEY,\n  total NUMERIC(10, 2)\n);\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("proto/billing.proto"),
+        "syntax = \"proto3\";\npackage acme.billing;\n\nmessage Invoice {\n  string id = 1;\n}\n\nservice InvoiceService {\n  rpc GetInvoice(Invoice) returns (Invoice);\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("app.yaml"),
+        "DATABASE_URL: postgres://localhost/billing\nhttp:\n  port: 8080\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("src/invoices.ts"),
+        r#"export function loadInvoice(db: any, id: string) {
+  const url = process.env["DATABASE_URL"];
+  return db.query("SELECT * FROM invoices WHERE id = $1", [id, url]);
+}
+
+export function fetchInvoice(client: any, id: string) {
+  return client.getInvoice({ id });
+}
+"#,
+    )
+    .unwrap();
+    state.indexer.index_all().await.unwrap();
+
+    let tree = handle_get_file_symbols(
+        &state,
+        GetFileSymbolsTool {
+            file_path: "docs/billing.md".to_string(),
+            exported_only: None,
+            tree: Some(true),
+            revision: None,
+        },
+    )
+    .unwrap();
+    let billing = &tree["symbols"][0];
+    assert_eq!(billing["name"], "Billing", "{tree}");
+    assert_eq!(billing["kind"], "section");
+    assert_eq!(billing["children"][0]["name"], "Refunds");
+
+    let find = |name: &str| {
+        state
+            .sqlite
+            .search_symbols_by_exact_name(name, None, 1)
+            .unwrap()
+            .remove(0)
+    };
+    let invoices = find("invoices");
+    assert_eq!(
+        (invoices.kind.as_str(), invoices.language.as_str()),
+        ("table", "sql")
+    );
+    assert_eq!(find("total").kind, "column");
+    assert_eq!(find("Invoice").qualified_name, "acme.billing.Invoice");
+    assert_eq!(find("DATABASE_URL").kind, "config_key");
+
+    let load = find("loadInvoice");
+    let fetch = find("fetchInvoice");
+    let linked_from = |resource: &str| -> Vec<String> {
+        let id = find(resource).id;
+        state
+            .sqlite
+            .list_edges_to(&id, 100)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.edge_type == "reference" && e.resolution == "resource")
+            .map(|e| e.from_symbol_id)
+            .collect()
+    };
+    assert_eq!(linked_from("invoices"), vec![load.id.clone()]);
+    assert_eq!(linked_from("DATABASE_URL"), vec![load.id]);
+    assert_eq!(linked_from("GetInvoice"), vec![fetch.id]);
+    // Prose never links, and resources are not resolved as call targets
+    assert!(linked_from("Refunds").is_empty());
+    assert!(state
+        .sqlite
+        .list_edges_from(&find("fetchInvoice").id, 100)
+        .unwrap()
+        .iter()
+        .all(|e| e.edge_type != "call" || e.to_symbol_id != find("GetInvoice").id));
+}
+
+#[tokio::test]
+async fn test_resource_links_follow_incremental_changes() {
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    for dir in ["db", "src"] {
+        std::fs::create_dir_all(base_dir.join(dir)).unwrap();
+    }
+    std::fs::write(
+        base_dir.join("db/schema.sql"),
+        "CREATE TABLE invoices (\n  id BIGINT PRIMARY KEY\n);\n",
+    )
+    .unwrap();
+    std::fs::write(base_dir.join("app.yaml"), "DATABASE_URL: postgres://db\n").unwrap();
+    std::fs::write(
+        base_dir.join("src/invoices.ts"),
+        "export function loadInvoice(db: any) {\n  return db.query(\"SELECT * FROM invoices\", [process.env[\"DATABASE_URL\"]]);\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("src/cache.ts"),
+        "export function cacheUrl() {\n  return process.env[\"CACHE_URL\"];\n}\n",
+    )
+    .unwrap();
+    state.indexer.index_all().await.unwrap();
+
+    let find = |name: &str| {
+        state
+            .sqlite
+            .search_symbols_by_exact_name(name, None, 1)
+            .unwrap()
+            .remove(0)
+    };
+    let linked_from = |resource: &str| -> Vec<String> {
+        let id = find(resource).id;
+        state
+            .sqlite
+            .list_edges_to(&id, 100)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.resolution == "resource")
+            .map(|e| e.from_symbol_id)
+            .collect()
+    };
+    let load = find("loadInvoice").id;
+    assert_eq!(linked_from("invoices"), vec![load.clone()]);
+
+    // A new key is linked from code that did not change
+    std::fs::write(
+        base_dir.join("app.yaml"),
+        "DATABASE_URL: postgres://db\nCACHE_URL: redis://cache\n",
+    )
+    .unwrap();
+    state
+        .indexer
+        .index_paths(&[state.config.base_dir.join("app.yaml")])
+        .await
+        .unwrap();
+    assert_eq!(linked_from("CACHE_URL"), vec![find("cacheUrl").id]);
+    assert_eq!(linked_from("DATABASE_URL"), vec![load.clone()]);
+    assert_eq!(linked_from("invoices"), vec![load.clone()]);
+
+    // Changed code loses the links it no longer makes and keeps the others
+    std::fs::write(
+        base_dir.join("src/invoices.ts"),
+        "export function loadInvoice(db: any) {\n  return db.connect(process.env[\"DATABASE_URL\"]);\n}\n",
+    )
+    .unwrap();
+    state
+        .indexer
+        .index_paths(&[state.config.base_dir.join("src/invoices.ts")])
+        .await
+        .unwrap();
+    assert!(linked_from("invoices").is_empty());
+    assert_eq!(linked_from("DATABASE_URL"), vec![load]);
+    assert_eq!(linked_from("CACHE_URL"), vec![find("cacheUrl").id]);
+}
+
+#[tokio::test]
+async fn test_contracts_link_to_stubs_handlers_and_clients() {
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    for dir in ["proto", "gen", "server", "web", "api"] {
+        std::fs::create_dir_all(base_dir.join(dir)).unwrap();
+    }
+    std::fs::write(
+        base_dir.join("proto/billing.proto"),
+        "syntax = \"proto3\";\npackage acme.billing;\n\nmessage Invoice {\n  string id = 1;\n}\n\nservice InvoiceService {\n  rpc GetInvoice(Invoice) returns (Invoice);\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("gen/billing_pb.ts"),
+        r#"export class InvoiceServiceClient {
+  getInvoice(request: unknown) {
+    return this.unary("/acme.billing.InvoiceService/GetInvoice", request);
+  }
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("server/invoices.ts"),
+        r#"import { InvoiceServiceServer } from "../gen/billing_pb";
+
+export class InvoiceHandlers implements InvoiceServiceServer {
+  getInvoice(call: unknown) {
+    return { id: String(call) };
+  }
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("web/client.ts"),
+        r#"export async function showInvoice(client: any) {
+  return client.getInvoice({ id: "1" });
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("api/openapi.yaml"),
+        "openapi: 3.0.3\ninfo:\n  title: Users\npaths:\n  /users/{id}:\n    get:\n      operationId: getUser\n      responses:\n        \"200\":\n          description: ok\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("server/app.ts"),
+        r#"import { Elysia } from "elysia";
+
+export function loadUser(ctx: any) {
+  return { id: ctx.params.id };
+}
+
+export const app = new Elysia().get("/api/users/:id", loadUser);
+"#,
+    )
+    .unwrap();
+    state.indexer.index_all().await.unwrap();
+
+    let references = |symbol_name: &str, file: &str| {
+        let result = handle_find_references(
+            &state,
+            FindReferencesTool {
+                symbol_name: symbol_name.to_string(),
+                file: Some(file.to_string()),
+                reference_type: None,
+                limit: None,
+                revision: None,
+            },
+        )
+        .unwrap();
+        let mut found: Vec<(String, String, String)> = result["references"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                (
+                    r["from_symbol_file"].as_str().unwrap().to_string(),
+                    r["from_symbol_name"].as_str().unwrap().to_string(),
+                    r["reference_type"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        found.sort();
+        found
+    };
+    let entry = |file: &str, name: &str, kind: &str| {
+        (file.to_string(), name.to_string(), kind.to_string())
+    };
+
+    assert_eq!(
+        references("GetInvoice", "proto/billing.proto"),
+        vec![
+            entry("gen/billing_pb.ts", "getInvoice", "generated_from"),
+            entry("server/invoices.ts", "getInvoice", "implements_contract"),
+            entry("web/client.ts", "showInvoice", "reference"),
+        ]
+    );
+
+    let operation = state
+        .sqlite
+        .search_symbols_by_exact_name("getUser", Some("api/openapi.yaml"), 1)
+        .unwrap()
+        .remove(0);
+    assert_eq!(operation.kind, "operation");
+    let routes = state
+        .sqlite
+        .search_framework_patterns(Some("openapi"), Some("route"), None, None, None, None, 10)
+        .unwrap();
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].path.as_deref(), Some("/users/{id}"));
+    assert_eq!(
+        references("getUser", "api/openapi.yaml"),
+        vec![entry("server/app.ts", "loadUser", "implements_contract")]
+    );
+}
+
+#[tokio::test]
+async fn test_routes_resolve_prefixes_of_routers_mounted_from_other_files() {
+    use code_intelligence_mcp_server::handlers::handle_search_framework_patterns;
+    use code_intelligence_mcp_server::tools::SearchFrameworkPatternsTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    std::fs::create_dir_all(base_dir.join("src/routes")).unwrap();
+    for (path, content) in [
+        (
+            "src/app.js",
+            "const express = require('express');\nconst users = require('./routes/users');\n\nconst app = express();\napp.use('/api/users', users);\napp.post('/login', login);\n",
+        ),
+        (
+            "src/routes/users.js",
+            "const { Router } = require('express');\n\nconst router = Router();\nrouter.get('/', listUsers);\nrouter.post('/:id/invite', auth, inviteUser);\n\nmodule.exports = router;\n",
+        ),
+        (
+            "src/orders.controller.ts",
+            "import { Controller, Post } from '@nestjs/common';\n\n@Controller('api/orders')\nexport class OrdersController {\n  @Post()\n  create() {}\n}\n",
+        ),
+    ] {
+        std::fs::write(base_dir.join(path), content).unwrap();
+    }
+    state.indexer.index_all().await.unwrap();
+
+    let search = |http_method: Option<&str>, path: &str| {
+        let result = handle_search_framework_patterns(
+            &state,
+            SearchFrameworkPatternsTool {
+                framework: None,
+                kind: Some("route".to_string()),
+                http_method: http_method.map(str::to_string),
+                path: Some(path.to_string()),
+                limit: None,
+                revision: None,
+            },
+        )
+        .unwrap();
+        let mut found: Vec<(String, String, String)> = result["patterns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                (
+                    p["http_method"].as_str().unwrap().to_string(),
+                    p["path"].as_str().unwrap().to_string(),
+                    p["handler"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        found.sort();
+        found
+    };
+    let route = |method: &str, path: &str, handler: &str| {
+        (method.to_string(), path.to_string(), handler.to_string())
+    };
+
+    assert_eq!(
+        search(Some("POST"), "/api/*"),
+        vec![
+            route("POST", "/api/orders", "OrdersController.create"),
+            route("POST", "/api/users/:id/invite", "inviteUser"),
+        ]
+    );
+    assert_eq!(
+        search(None, "/api/users*"),
+        vec![
+            route("GET", "/api/users", "listUsers"),
+            route("POST", "/api/users/:id/invite", "inviteUser"),
+        ]
+    );
+
+    // Unmounting the router leaves its routes at their own paths
+    std::fs::write(
+        base_dir.join("src/app.js"),
+        "const express = require('express');\n\nconst app = express();\napp.post('/login', login);\n",
+    )
+    .unwrap();
+    state.indexer.index_all().await.unwrap();
+    assert_eq!(
+        search(Some("GET"), "*"),
+        vec![route("GET", "/", "listUsers")]
+    );
+}
+
+#[tokio::test]
+async fn test_python_routes_resolve_included_urlconfs_and_imported_views() {
+    use code_intelligence_mcp_server::handlers::handle_search_framework_patterns;
+    use code_intelligence_mcp_server::tools::SearchFrameworkPatternsTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    for dir in ["mysite", "blog", "api/routers"] {
+        std::fs::create_dir_all(base_dir.join(dir)).unwrap();
+    }
+    for (path, content) in [
+        (
+            "mysite/urls.py",
+            "from django.urls import include, path\n\nurlpatterns = [\n    path(\"api/blog/\", include(\"blog.urls\")),\n]\n",
+        ),
+        (
+            "blog/urls.py",
+            "from django.urls import path\nfrom . import views\n\nurlpatterns = [\n    path(\"posts/<int:pk>/\", views.post_detail),\n]\n",
+        ),
+        (
+            "blog/views.py",
+            "def post_detail(request, pk):\n    return pk\n",
+        ),
+        (
+            "api/main.py",
+            "from fastapi import FastAPI\nfrom .routers import users\n\napp = FastAPI()\napp.include_router(users.router, prefix=\"/api/users\")\n",
+        ),
+        (
+            "api/routers/users.py",
+            "from fastapi import APIRouter\n\nrouter = APIRouter()\n\n@router.post(\"/{user_id}/invite\")\ndef invite_user(user_id: int):\n    return user_id\n",
+        ),
+    ] {
+        std::fs::write(base_dir.join(path), content).unwrap();
+    }
+    state.indexer.index_all().await.unwrap();
+
+    let result = handle_search_framework_patterns(
+        &state,
+        SearchFrameworkPatternsTool {
+            framework: None,
+            kind: Some("route".to_string()),
+            http_method: None,
+            path: Some("/api/*".to_string()),
+            limit: None,
+            revision: None,
+        },
+    )
+    .unwrap();
+    let symbol_of = |name: &str, file: &str| {
+        state
+            .sqlite
+            .search_symbols_by_exact_name(name, Some(file), 1)
+            .unwrap()
+            .remove(0)
+            .id
+    };
+    let mut found: Vec<(String, String, String)> = result["patterns"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| {
+            (
+                p["framework"].as_str().unwrap().to_string(),
+                p["path"].as_str().unwrap().to_string(),
+                p["symbol_id"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "django".to_string(),
+                "/api/blog/posts/<int:pk>".to_string(),
+                symbol_of("post_detail", "blog/views.py"),
+            ),
+            (
+                "fastapi".to_string(),
+                "/api/users/{user_id}/invite".to_string(),
+                symbol_of("invite_user", "api/routers/users.py"),
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_go_and_spring_routes_merge_prefixes_and_attach_handlers() {
+    use code_intelligence_mcp_server::handlers::handle_search_framework_patterns;
+    use code_intelligence_mcp_server::tools::SearchFrameworkPatternsTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    for (path, content) in [
+        (
+            "orders/router.go",
+            "package orders\n\nimport (\n\t\"net/http\"\n\n\t\"github.com/go-chi/chi/v5\"\n)\n\nfunc Router() http.Handler {\n\tr := chi.NewRouter()\n\tr.Route(\"/api/orders\", func(r chi.Router) {\n\t\tr.Get(\"/{id}\", getOrder)\n\t})\n\treturn r\n}\n\nfunc getOrder(w http.ResponseWriter, r *http.Request) {}\n",
+        ),
+        (
+            "users/UserController.java",
+            "package users;\n\n@RestController\n@RequestMapping(\"/api/users\")\npublic class UserController {\n    @PostMapping(\"/{id}/invite\")\n    public void invite(@PathVariable long id) {}\n}\n",
+        ),
+    ] {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    state.indexer.index_all().await.unwrap();
+
+    let result = handle_search_framework_patterns(
+        &state,
+        SearchFrameworkPatternsTool {
+            framework: None,
+            kind: Some("route".to_string()),
+            http_method: None,
+            path: Some("/api/*".to_string()),
+            limit: None,
+            revision: None,
+        },
+    )
+    .unwrap();
+    let symbol_of = |name: &str, file: &str| {
+        state
+            .sqlite
+            .search_symbols_by_exact_name(name, Some(file), 1)
+            .unwrap()
+            .remove(0)
+            .id
+    };
+    let mut found: Vec<(String, String, String, String)> = result["patterns"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| {
+            (
+                p["framework"].as_str().unwrap().to_string(),
+                p["http_method"].as_str().unwrap().to_string(),
+                p["path"].as_str().unwrap().to_string(),
+                p["symbol_id"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "chi".to_string(),
+                "GET".to_string(),
+                "/api/orders/{id}".to_string(),
+                symbol_of("getOrder", "orders/router.go"),
+            ),
+            (
+                "spring".to_string(),
+                "POST".to_string(),
+                "/api/users/{id}/invite".to_string(),
+                symbol_of("invite", "users/UserController.java"),
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_http_call_links_follow_incremental_changes() {
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    let write = |path: &str, content: &str| {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    write(
+        "web/src/users.ts",
+        "export async function loadUser(id: string) {\n  return fetch(`/api/users/${id}`);\n}\n",
+    );
+    write(
+        "web/src/orders.ts",
+        "export async function loadOrders() {\n  return fetch(\"/api/orders\");\n}\n",
+    );
+    write(
+        "services/main.py",
+        "from fastapi import FastAPI\n\napp = FastAPI()\n\n@app.get(\"/api/users/{user_id}\")\ndef read_user(user_id: str):\n    return {\"id\": user_id}\n",
+    );
+    state.indexer.index_all().await.unwrap();
+
+    let id = |name: &str| {
+        state
+            .sqlite
+            .search_symbols_by_exact_name(name, None, 1)
+            .unwrap()
+            .remove(0)
+            .id
+    };
+    let http_calls_from = |name: &str| -> Vec<String> {
+        state
+            .sqlite
+            .list_edges_from(&id(name), 100)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.edge_type == "http_call")
+            .map(|e| e.to_symbol_id)
+            .collect()
+    };
+    assert_eq!(http_calls_from("loadUser"), vec![id("read_user")]);
+    assert!(http_calls_from("loadOrders").is_empty());
+
+    // A new route is linked from clients that did not change
+    write(
+        "services/main.py",
+        "from fastapi import FastAPI\n\napp = FastAPI()\n\n@app.get(\"/api/users/{user_id}\")\ndef read_user(user_id: str):\n    return {\"id\": user_id}\n\n@app.get(\"/api/orders\")\ndef list_orders():\n    return []\n",
+    );
+    state
+        .indexer
+        .index_paths(&[state.config.base_dir.join("services/main.py")])
+        .await
+        .unwrap();
+    assert_eq!(http_calls_from("loadOrders"), vec![id("list_orders")]);
+    assert_eq!(http_calls_from("loadUser"), vec![id("read_user")]);
+
+    // A changed client loses the call it no longer makes
+    write(
+        "web/src/users.ts",
+        "export async function loadUser(id: string) {\n  return fetch(`/api/accounts/${id}`);\n}\n",
+    );
+    state
+        .indexer
+        .index_paths(&[state.config.base_dir.join("web/src/users.ts")])
+        .await
+        .unwrap();
+    assert!(http_calls_from("loadUser").is_empty());
+    assert_eq!(http_calls_from("loadOrders"), vec![id("list_orders")]);
+}
+
+#[tokio::test]
+async fn test_http_calls_link_frontend_code_to_backend_handlers() {
+    use code_intelligence_mcp_server::handlers::handle_get_call_hierarchy;
+    use code_intelligence_mcp_server::tools::{FindAffectedCodeTool, GetCallHierarchyTool};
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    for (path, content) in [
+        (
+            "web/src/api.ts",
+            "export async function loadUser(id: string) {\n  const response = await fetch(`${API_URL}/api/users/${id}`);\n  return response.json();\n}\n",
+        ),
+        (
+            "web/src/UserPage.ts",
+            "import { loadUser } from \"./api\";\n\nexport async function renderUserPage(id: string) {\n  return loadUser(id);\n}\n",
+        ),
+        (
+            "services/users/main.py",
+            "from fastapi import FastAPI\n\napp = FastAPI()\n\n@app.get(\"/api/users/{user_id}\")\ndef read_user(user_id: str):\n    return {\"id\": user_id}\n\n@app.delete(\"/api/users/{user_id}\")\ndef delete_user(user_id: str):\n    return None\n",
+        ),
+    ] {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    state.indexer.index_all().await.unwrap();
+
+    let hierarchy = handle_get_call_hierarchy(
+        &state,
+        GetCallHierarchyTool {
+            symbol_name: "loadUser".to_string(),
+            direction: Some("callees".to_string()),
+            depth: Some(1),
+            limit: None,
+            revision: None,
+        },
+    )
+    .unwrap();
+    let read_user = state
+        .sqlite
+        .search_symbols_by_exact_name("read_user", Some("services/users/main.py"), 1)
+        .unwrap()
+        .remove(0);
+    let http_calls: Vec<(&str, u64)> = hierarchy["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["edge_type"] == "http_call")
+        .map(|e| {
+            (
+                e["to"].as_str().unwrap(),
+                e["at_line"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(http_calls, vec![(read_user.id.as_str(), 2)]);
+
+    let affected = handle_find_affected_code(
+        &state,
+        FindAffectedCodeTool {
+            symbol_name: "read_user".to_string(),
+            file_path: Some("services/users/main.py".to_string()),
+            depth: None,
+            limit: None,
+            include_tests: None,
+            revision: None,
+        },
+    )
+    .unwrap();
+    let names: Vec<&str> = affected["affected"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["symbol_name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"loadUser"), "{names:?}");
+    assert!(names.contains(&"renderUserPage"), "{names:?}");
+    assert!(!names.contains(&"delete_user"), "{names:?}");
+}
+
+#[tokio::test]
+async fn test_search_filter_terms() {
+    use code_intelligence_mcp_server::handlers::handle_search_code;
+    use code_intelligence_mcp_server::tools::SearchCodeTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    for (path, content) in [
+        (
+            "src/config/loader.ts",
+            "export function parseConfig(raw: string) {\n  return raw.trim();\n}\n\nexport function loadConfig() {\n  return parseConfig(\"app\");\n}\n",
+        ),
+        (
+            "src/config/service.ts",
+            "import { parseConfig } from \"./loader\";\n\nexport interface Repository {\n  load(): string;\n}\n\nexport class ConfigService implements Repository {\n  load() {\n    return parseConfig(\"service\");\n  }\n}\n",
+        ),
+        (
+            "tests/config/fixtures.ts",
+            "import { parseConfig } from \"../../src/config/loader\";\n\nfunction parsesConfig() {\n  return parseConfig(\" x \");\n}\n",
+        ),
+    ] {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    state.indexer.index_all().await.unwrap();
+
+    let search = |query: &str| {
+        handle_search_code(
+            &state.retriever,
+            SearchCodeTool {
+                query: query.to_string(),
+                limit: Some(10),
+                exported_only: None,
+                revision: None,
+            },
+        )
+    };
+    // Enclosing classes and files of a call make it too; only declarations are listed
+    let names = |result: &serde_json::Value| -> Vec<String> {
+        let mut names: Vec<String> = result["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|h| h["kind"] != "file")
+            .map(|h| h["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+
+    // Filter terms alone are evaluated over the whole index
+    let callers = search("calls:parseConfig").await.unwrap();
+    assert_eq!(
+        names(&callers),
+        vec!["ConfigService", "load", "loadConfig", "parsesConfig"],
+        "{callers}"
+    );
+    let callers = search("calls:parseConfig -path:tests/").await.unwrap();
+    assert_eq!(
+        names(&callers),
+        vec!["ConfigService", "load", "loadConfig"],
+        "{callers}"
+    );
+    let callers = search("calls:parseConfig (kind:class OR path:tests/**/*.ts)")
+        .await
+        .unwrap();
+    assert_eq!(names(&callers), vec!["ConfigService", "parsesConfig"], "{callers}");
+    let implementers = search("implements:Repository").await.unwrap();
+    assert_eq!(names(&implementers), vec!["ConfigService"], "{implementers}");
+    let recent = search("modified:<1d exported:false -kind:file").await.unwrap();
+    let recent = names(&recent);
+    assert!(recent.contains(&"parsesConfig".to_string()), "{recent:?}");
+    assert!(!recent.contains(&"loadConfig".to_string()), "{recent:?}");
+    assert!(search("modified:>1d").await.unwrap()["hits"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    // With free text, every hit satisfies the filter
+    let hits = search("config kind:function OR kind:class -file:tests/*")
+        .await
+        .unwrap();
+    let hits = hits["hits"].as_array().unwrap();
+    assert!(!hits.is_empty());
+    for hit in hits {
+        assert!(["function", "class"].contains(&hit["kind"].as_str().unwrap()), "{hit}");
+        assert!(!hit["file_path"].as_str().unwrap().starts_with("tests/"), "{hit}");
+    }
+
+    let explained = handle_explain_search(
+        &state.retriever,
+        ExplainSearchTool {
+            query: "config exported:maybe (kind:class".to_string(),
+            limit: None,
+            exported_only: None,
+            verbose: None,
+            revision: None,
+        },
+    )
+    .await
+    .unwrap();
+    let errors = explained["query_errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 2, "{explained}");
+    assert!(errors[0].as_str().unwrap().starts_with("exported:maybe:"));
+    assert_eq!(errors[1], "missing ')'");
+}
+
+#[tokio::test]
+async fn test_search_content_tool() {
+    use code_intelligence_mcp_server::handlers::handle_search_content;
+    use code_intelligence_mcp_server::tools::SearchContentTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    let client = base_dir.join("src/http/client.ts");
+    std::fs::create_dir_all(client.parent().unwrap()).unwrap();
+    std::fs::write(
+        &client,
+        "// Every request carries an X-Request-Id header\nexport class HttpClient {\n  send(body: string) {\n    return post(body, { \"X-Request-Id\": newId() });\n  }\n}\n",
+    )
+    .unwrap();
+    let tracing = base_dir.join("src/tracing.ts");
+    std::fs::write(
+        &tracing,
+        "export function requestIdHeader() {\n  return \"x-request-id\";\n}\n",
+    )
+    .unwrap();
+    state.indexer.index_all().await.unwrap();
+
+    let search = |pattern: &str, literal: bool, case_insensitive: bool, path: Option<&str>| {
+        handle_search_content(
+            &state,
+            SearchContentTool {
+                pattern: pattern.to_string(),
+                literal: Some(literal),
+                case_insensitive: Some(case_insensitive),
+                path: path.map(str::to_string),
+                limit: None,
+                revision: None,
+            },
+        )
+        .unwrap()
+    };
+    let lines = |result: &serde_json::Value| -> Vec<String> {
+        result["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| format!("{}:{}", m["file_path"].as_str().unwrap(), m["line"]))
+            .collect()
+    };
+
+    // Comments and string literals are searchable; matches name their enclosing symbol
+    let found = search("\"X-Request-Id\"", true, false, None);
+    assert_eq!(lines(&found), vec!["src/http/client.ts:4"], "{found}");
+    let hit = &found["matches"][0];
+    assert_eq!(hit["column"], 25);
+    assert_eq!(hit["symbol"]["name"], "send");
+    assert_eq!(hit["symbol"]["qualified_name"], "HttpClient.send");
+    // The trigram index rules out files that cannot match
+    assert_eq!(search("HttpClient", true, false, None)["candidate_files"], 1);
+
+    let found = search("X-Request-Id", true, false, None);
+    assert_eq!(
+        lines(&found),
+        vec!["src/http/client.ts:1", "src/http/client.ts:4"],
+        "{found}"
+    );
+    assert!(found["matches"][0]["symbol"].is_null());
+
+    let found = search(r"x-request-(id|uuid)", false, true, None);
+    assert_eq!(
+        lines(&found),
+        vec![
+            "src/http/client.ts:1",
+            "src/http/client.ts:4",
+            "src/tracing.ts:2"
+        ],
+        "{found}"
+    );
+    let found = search(r"x-request-(id|uuid)", false, true, Some("src/*.ts"));
+    assert_eq!(lines(&found), vec!["src/tracing.ts:2"], "{found}");
+    let found = search(r"^\s*return", false, false, None);
+    assert_eq!(found["count"], 2, "{found}");
+    assert!(handle_search_content(
+        &state,
+        SearchContentTool {
+            pattern: "(unclosed".to_string(),
+            literal: None,
+            case_insensitive: None,
+            path: None,
+            limit: None,
+            revision: None,
+        },
+    )
+    .is_err());
+
+    // The index follows edits and deletions
+    std::fs::write(
+        &client,
+        "export class HttpClient {\n  send(body: string) {\n    return post(body, { \"X-Trace-Id\": newId() });\n  }\n}\n",
+    )
+    .unwrap();
+    std::fs::remove_file(&tracing).unwrap();
+    state.indexer.index_all().await.unwrap();
+    assert!(lines(&search("request-id", true, true, None)).is_empty());
+    assert_eq!(
+        lines(&search("X-Trace-Id", true, false, None)),
+        vec!["src/http/client.ts:3"]
+    );
+}
+
+#[tokio::test]
+async fn test_lsp_serves_definition_references_symbols_and_call_hierarchy() {
+    use code_intelligence_mcp_server::lsp::LspServer;
+    use serde_json::{json, Value};
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    std::fs::create_dir_all(base_dir.join("src")).unwrap();
+    std::fs::write(
+        base_dir.join("src/util.ts"),
+        "export function helper(x: number) {\n  return x + 1;\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        base_dir.join("src/app.ts"),
+        "import { helper } from \"./util\";\n\nexport function run() {\n  return helper(2);\n}\n",
+    )
+    .unwrap();
+    state.indexer.index_all().await.unwrap();
+
+    let uri = |rel: &str| {
+        url::Url::from_file_path(state.config.base_dir.join(rel).as_std_path())
+            .unwrap()
+            .to_string()
+    };
+    let app_uri = uri("src/app.ts");
+    let util_uri = uri("src/util.ts");
+    let mut server = LspServer::new(Arc::new(state.clone()));
+    let mut next_id = 0;
+    let mut request = |method: &str, params: Value| {
+        next_id += 1;
+        json!({ "jsonrpc": "2.0", "id": next_id, "method": method, "params": params })
+    };
+
+    let (init, _) = server
+        .handle_message(request("initialize", json!({ "capabilities": {} })))
+        .await;
+    assert_eq!(init.unwrap()["result"]["capabilities"]["definitionProvider"], true);
+
+    // `helper(2)` in app.ts, line 3 (zero-based)
+    let at_call = json!({ "textDocument": { "uri": app_uri }, "position": { "line": 3, "character": 11 } });
+    let (resp, _) = server
+        .handle_message(request("textDocument/definition", at_call.clone()))
+        .await;
+    let locations = resp.unwrap()["result"].clone();
+    assert_eq!(locations[0]["uri"], util_uri.as_str(), "definition: {locations}");
+    assert_eq!(
+        locations[0]["range"]["start"],
+        json!({ "line": 0, "character": 16 })
+    );
+
+    let mut params = at_call.clone();
+    params["context"] = json!({ "includeDeclaration": true });
+    let (resp, _) = server
+        .handle_message(request("textDocument/references", params))
+        .await;
+    let refs = resp.unwrap()["result"].clone();
+    let refs = refs.as_array().unwrap();
+    assert!(refs.iter().any(|r| r["uri"] == util_uri.as_str()), "references: {refs:?}");
+    assert!(
+        refs.iter().any(|r| r["uri"] == app_uri.as_str()
+            && r["range"]["start"] == json!({ "line": 3, "character": 9 })),
+        "references: {refs:?}"
+    );
+
+    let (resp, _) = server
+        .handle_message(request(
+            "textDocument/documentSymbol",
+            json!({ "textDocument": { "uri": app_uri } }),
+        ))
+        .await;
+    let symbols = resp.unwrap()["result"].clone();
+    assert!(
+        symbols.as_array().unwrap().iter().any(|s| s["name"] == "run" && s["kind"] == 12),
+        "documentSymbol: {symbols}"
+    );
+
+    let (resp, _) = server
+        .handle_message(request("workspace/symbol", json!({ "query": "help" })))
+        .await;
+    let symbols = resp.unwrap()["result"].clone();
+    assert_eq!(symbols[0]["name"], "helper", "workspace/symbol: {symbols}");
+
+    let (resp, _) = server
+        .handle_message(request("textDocument/prepareCallHierarchy", at_call))
+        .await;
+    let items = resp.unwrap()["result"].clone();
+    assert_eq!(items[0]["name"], "helper");
+    let (resp, _) = server
+        .handle_message(request(
+            "callHierarchy/incomingCalls",
+            json!({ "item": items[0].clone() }),
+        ))
+        .await;
+    let calls = resp.unwrap()["result"].clone();
+    assert!(
+        calls.as_array().unwrap().iter().any(|c| c["from"]["name"] == "run"),
+        "incomingCalls: {calls}"
+    );
+
+    let (resp, _) = server
+        .handle_message(request("textDocument/hover", json!({})))
+        .await;
+    assert_eq!(resp.unwrap()["error"]["code"], -32601);
+
+    let (resp, _) = server.handle_message(request("shutdown", Value::Null)).await;
+    assert!(resp.unwrap()["result"].is_null());
+    let (resp, exit) = server
+        .handle_message(json!({ "jsonrpc": "2.0", "method": "exit" }))
+        .await;
+    assert!(resp.is_none() && exit);
+}
+
+// ============================================================================
+// Tests for the HTTP transport
+// ============================================================================
+
+fn free_local_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+async fn post_mcp(
+    client: &reqwest::Client,
+    url: &str,
+    token: Option<&str>,
+    session_id: Option<&str>,
+    body: serde_json::Value,
+) -> reqwest::Response {
+    let mut req = client
+        .post(url)
+        .header("Accept", "application/json, text/event-stream")
+        .json(&body);
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+    if let Some(session_id) = session_id {
+        req = req.header("mcp-session-id", session_id);
+    }
+    // The server task may still be binding right after start
+    for _ in 0..50 {
+        match req.try_clone().unwrap().send().await {
+            Ok(resp) => return resp,
+            Err(err) if err.is_connect() => {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await
+            }
+            Err(err) => panic!("request failed: {err}"),
+        }
+    }
+    panic!("HTTP server did not start");
+}
+
+/// Read a streamed response until `needle` shows up (SSE streams may stay open).
+async fn read_until(mut resp: reqwest::Response, needle: &str) -> String {
+    let mut body = String::new();
+    let read = async {
+        while let Some(chunk) = resp.chunk().await.unwrap() {
+            body.push_str(&String::from_utf8_lossy(&chunk));
+            if body.contains(needle) {
+                break;
+            }
+        }
+    };
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(10), read).await;
+    body
+}
+
+#[tokio::test]
+async fn test_http_transport_requires_bearer_token_and_serves_tools() {
+    use code_intelligence_mcp_server::server::{http::create_http_server, CodeIntelligenceHandler};
+
+    let db_path = tmp_db_path();
+    let mut state = create_app_state(&db_path, "http-transport").await;
+    let port = free_local_port();
+    let mut config = (*state.config).clone();
+    config.mcp_transport = McpTransport::Http;
+    config.http_bind_addr = format!("127.0.0.1:{port}");
+    config.http_auth_token = Some("s3cret".to_string());
+    state.config = Arc::new(config.clone());
+
+    let handler = CodeIntelligenceHandler {
+        state: Arc::new(state),
+    };
+    let runtime = create_http_server(&config, handler)
+        .unwrap()
+        .start_runtime()
+        .await
+        .unwrap();
+
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{port}/mcp");
+    let initialize = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2025-06-18",
+            "capabilities": {},
+            "clientInfo": { "name": "http-test", "version": "0.0.0" }
+        }
+    });
+
+    let resp = post_mcp(&client, &url, None, None, initialize.clone()).await;
+    assert_eq!(resp.status(), reqwest::StatusCode::UNAUTHORIZED);
+    let resp = post_mcp(&client, &url, Some("wrong"), None, initialize.clone()).await;
+    assert_eq!(resp.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let resp = post_mcp(&client, &url, Some("s3cret"), None, initialize).await;
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    let session_id = resp
+        .headers()
+        .get("mcp-session-id")
+        .and_then(|v| v.to_str().ok())
+        .expect("session id header")
+        .to_string();
+    let body = read_until(resp, "code-intelligence").await;
+    assert!(body.contains("code-intelligence"), "initialize body: {body}");
+
+    let resp = post_mcp(
+        &client,
+        &url,
+        Some("s3cret"),
+        Some(&session_id),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+    )
+    .await;
+    assert!(resp.status().is_success());
+
+    let resp = post_mcp(
+        &client,
+        &url,
+        Some("s3cret"),
+        Some(&session_id),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+    )
+    .await;
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    let body = read_until(resp, "search_code").await;
+    assert!(body.contains("search_code"), "tools/list body: {body}");
+
+    runtime.graceful_shutdown(Some(std::time::Duration::from_secs(1)));
+}
+
 // ============================================================================
 // Fixture smoke tests
 // ============================================================================
@@ -1222,3 +2580,794 @@ mod fixture_tests {
         assert_eq!(count, 0); // Empty database
     }
 }
+
+#[tokio::test]
+async fn test_analyze_diff_maps_hunks_to_symbols_callers_and_tests() {
+    use code_intelligence_mcp_server::handlers::handle_analyze_diff;
+    use code_intelligence_mcp_server::tools::AnalyzeDiffTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    let write = |path: &str, content: &str| {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    write(
+        "src/store.ts",
+        "export function saveUser(name: string) {\n  return name;\n}\n\nexport function loadUser(id: string) {\n  return id;\n}\n",
+    );
+    write(
+        "src/service.ts",
+        "import { saveUser } from \"./store\";\n\nexport function registerUser(name: string) {\n  return saveUser(name);\n}\n",
+    );
+    write(
+        "src/service.spec.ts",
+        "import { registerUser } from \"./service\";\n\ntest(\"registers\", () => registerUser(\"ada\"));\n",
+    );
+
+    let repo = git2::Repository::init(&base_dir).unwrap();
+    let commit = |message: &str| {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["src"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    };
+    commit("first");
+
+    write(
+        "src/store.ts",
+        "export function saveUser(name: string) {\n  return name.trim();\n}\n\nexport function loadUser(id: string) {\n  return id;\n}\n",
+    );
+    state.indexer.index_all().await.unwrap();
+
+    let check = |result: serde_json::Value| {
+        let changed: Vec<&str> = result["changed_symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["symbol_name"].as_str().unwrap())
+            .collect();
+        assert_eq!(changed, vec!["saveUser"], "{result:#}");
+        let register = result["affected"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["symbol_name"] == "registerUser")
+            .unwrap_or_else(|| panic!("{result:#}"));
+        assert_eq!(register["depth"], 1);
+        let exports: Vec<&str> = result["affected_exports"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["symbol_name"].as_str().unwrap())
+            .collect();
+        assert!(exports.contains(&"saveUser"), "{exports:?}");
+        assert!(exports.contains(&"registerUser"), "{exports:?}");
+        assert!(!exports.contains(&"loadUser"), "{exports:?}");
+        let tests: Vec<&str> = result["tests"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["test_file"].as_str().unwrap())
+            .collect();
+        assert_eq!(tests, vec!["src/service.spec.ts"], "{result:#}");
+    };
+
+    // Uncommitted changes against HEAD
+    check(
+        handle_analyze_diff(
+            &state,
+            AnalyzeDiffTool {
+                range: None,
+                depth: None,
+                limit: None,
+            },
+        )
+        .await
+        .unwrap(),
+    );
+
+    // The same change once committed
+    commit("second");
+    check(
+        handle_analyze_diff(
+            &state,
+            AnalyzeDiffTool {
+                range: Some("HEAD~1..HEAD".to_string()),
+                depth: None,
+                limit: None,
+            },
+        )
+        .await
+        .unwrap(),
+    );
+}
+
+#[tokio::test]
+async fn test_analyze_diff_reads_a_range_from_its_new_side() {
+    use code_intelligence_mcp_server::handlers::handle_analyze_diff;
+    use code_intelligence_mcp_server::tools::AnalyzeDiffTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    let write = |path: &str, content: &str| {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    let store = "export function saveUser(name: string) {\n  return name;\n}\n\nexport function loadUser(id: string) {\n  return id;\n}\n";
+    write("src/store.ts", store);
+
+    let repo = git2::Repository::init(&base_dir).unwrap();
+    let commit = |message: &str| {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["src"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+    };
+    let first = commit("first");
+    let checkout = |branch: &str| {
+        repo.set_head(&format!("refs/heads/{branch}")).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+    };
+    let main = repo.head().unwrap().shorthand().unwrap().to_string();
+
+    // On a branch: a constant above saveUser shifts its lines, loadUser changes and
+    // a file is added
+    repo.branch("feature", &repo.find_commit(first).unwrap(), false)
+        .unwrap();
+    checkout("feature");
+    write(
+        "src/store.ts",
+        &format!(
+            "export const VERSION = 2;\n\n{}",
+            store.replace("return id;", "return id.trim();")
+        ),
+    );
+    write(
+        "src/audit.ts",
+        "export function auditUser(id: string) {\n  return id;\n}\n",
+    );
+    commit("feature");
+
+    // The checkout is back on the first commit, which the index describes
+    checkout(&main);
+    assert!(!base_dir.join("src/audit.ts").exists());
+    state.indexer.index_all().await.unwrap();
+
+    let result = handle_analyze_diff(
+        &state,
+        AnalyzeDiffTool {
+            range: Some(format!("{main}..feature")),
+            depth: None,
+            limit: None,
+        },
+    )
+    .await
+    .unwrap();
+    let mut changed: Vec<&str> = result["changed_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["symbol_name"].as_str().unwrap())
+        .collect();
+    changed.sort();
+    assert_eq!(
+        changed,
+        vec!["VERSION", "auditUser", "loadUser"],
+        "{result:#}"
+    );
+}
+
+#[tokio::test]
+async fn test_change_history_tracks_symbols_authors_and_co_changes() {
+    use code_intelligence_mcp_server::handlers::handle_get_change_history;
+    use code_intelligence_mcp_server::tools::GetChangeHistoryTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    let write = |path: &str, content: &str| {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    let store = |save: &str| {
+        format!(
+            "export function saveUser(name: string) {{\n  return {save};\n}}\n\nexport function loadUser(id: string) {{\n  return id;\n}}\n"
+        )
+    };
+    write("src/store.ts", &store("name"));
+    write(
+        "src/service.ts",
+        "import { saveUser } from \"./store\";\n\nexport function registerUser(name: string) {\n  return saveUser(name);\n}\n",
+    );
+    write("src/schema.ts", "export const USER_FIELDS = [\"name\"];\n");
+
+    let repo = git2::Repository::init(&base_dir).unwrap();
+    let commit = |author: &str, day: i64, message: &str| {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["src"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let time = git2::Time::new(1_700_000_000 + day * 86_400, 0);
+        let signature = git2::Signature::new(
+            author,
+            &format!("{}@example.com", author.to_lowercase()),
+            &time,
+        )
+        .unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    };
+    commit("Ada", 0, "Add user store");
+    write("src/store.ts", &store("name.trim()"));
+    write(
+        "src/schema.ts",
+        "export const USER_FIELDS = [\"name\", \"email\"];\n",
+    );
+    commit("Grace", 10, "Trim names");
+    write("src/store.ts", &store("name.trim().toLowerCase()"));
+    write(
+        "src/schema.ts",
+        "export const USER_FIELDS = [\"name\", \"email\", \"id\"];\n",
+    );
+    commit("Grace", 20, "Lowercase names");
+    state.indexer.index_all().await.unwrap();
+
+    let history = |file_path: Option<&str>, symbol_name: Option<&str>| {
+        handle_get_change_history(
+            &state,
+            GetChangeHistoryTool {
+                file_path: file_path.map(str::to_string),
+                symbol_name: symbol_name.map(str::to_string),
+                limit: None,
+            },
+        )
+        .unwrap()
+    };
+
+    // Symbols count the commits touching their own lines
+    let save = history(None, Some("saveUser"));
+    assert_eq!(save["stats"]["commit_count"], 3, "{save:#}");
+    assert_eq!(save["authors"][0]["author"], "Grace", "{save:#}");
+    assert_eq!(save["authors"][0]["commit_count"], 2);
+    assert_eq!(save["commits"][0]["summary"], "Lowercase names");
+    let load = history(Some("src/store.ts"), Some("loadUser"));
+    assert_eq!(load["stats"]["commit_count"], 1, "{load:#}");
+
+    let file = history(Some("src/store.ts"), None);
+    assert_eq!(file["stats"]["commit_count"], 3, "{file:#}");
+    assert_eq!(file["co_changed"][0]["file_path"], "src/schema.ts");
+    assert_eq!(file["co_changed"][0]["commit_count"], 3);
+    assert_eq!(file["hot_symbols"][0]["symbol_name"], "saveUser");
+    assert!(file["display"]
+        .as_str()
+        .unwrap()
+        .contains("## Usually Changed Together"));
+
+    // Changed together without depending on it
+    let affected = handle_find_affected_code(
+        &state,
+        FindAffectedCodeTool {
+            symbol_name: "saveUser".to_string(),
+            file_path: None,
+            depth: None,
+            limit: None,
+            include_tests: None,
+            revision: None,
+        },
+    )
+    .unwrap();
+    let co_changed: Vec<&str> = affected["co_changed_files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["file_path"].as_str().unwrap())
+        .collect();
+    assert_eq!(co_changed, vec!["src/schema.ts"], "{affected:#}");
+
+    // Recently and often changed code ranks higher
+    let explained = handle_explain_search(
+        &state.retriever,
+        ExplainSearchTool {
+            query: "saveUser".to_string(),
+            limit: None,
+            exported_only: None,
+            verbose: None,
+            revision: None,
+        },
+    )
+    .await
+    .unwrap();
+    let save_hit = explained["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["symbol_name"] == "saveUser")
+        .unwrap_or_else(|| panic!("{explained:#}"));
+    assert!(
+        save_hit["score_breakdown"]["history_boost"]
+            .as_f64()
+            .unwrap()
+            > 0.0,
+        "{save_hit:#}"
+    );
+}
+
+#[tokio::test]
+async fn test_tools_answer_as_of_a_revision_from_snapshots() {
+    use code_intelligence_mcp_server::server::call_tool;
+    use rust_mcp_sdk::schema::{CallToolRequestParams, ContentBlock};
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    let write = |path: &str, content: &str| {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    let auth = |params: &str| {
+        format!("export class AuthService {{\n  login({params}) {{\n    return user;\n  }}\n}}\n")
+    };
+    write("src/auth.ts", &auth("user: string"));
+    write(
+        "src/session.ts",
+        "export function startSession(token: string) {\n  return token;\n}\n",
+    );
+
+    let repo = git2::Repository::init(&base_dir).unwrap();
+    let commit = |message: &str| {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["src"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let id = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parent.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        repo.find_commit(id).unwrap()
+    };
+    let first = commit("first");
+    repo.branch("release", &first, false).unwrap();
+    write("src/auth.ts", &auth("user: string, password: string"));
+    commit("second");
+    write(
+        "src/auth.ts",
+        &auth("user: string, password: string, otp: string"),
+    );
+    state.indexer.index_all().await.unwrap();
+
+    let login = |revision: Option<&str>| {
+        let mut arguments = serde_json::Map::new();
+        arguments.insert("symbol_name".to_string(), serde_json::json!("login"));
+        if let Some(revision) = revision {
+            arguments.insert("revision".to_string(), serde_json::json!(revision));
+        }
+        let state = &state;
+        async move {
+            let result = call_tool(
+                state,
+                CallToolRequestParams {
+                    name: "get_definition".to_string(),
+                    arguments: Some(arguments),
+                    meta: None,
+                    task: None,
+                },
+            )
+            .await
+            .unwrap();
+            let ContentBlock::TextContent(text) = &result.content[0] else {
+                panic!("{result:?}");
+            };
+            let value: serde_json::Value = serde_json::from_str(&text.text).unwrap();
+            value["definitions"][0]["text"]
+                .as_str()
+                .unwrap_or_else(|| panic!("{value:#}"))
+                .to_string()
+        }
+    };
+
+    // The working tree, a branch, and a commit relative to HEAD
+    assert!(login(None).await.contains("otp: string"));
+    assert_eq!(
+        login(Some("release")).await,
+        "login(user: string) {\n    return user;\n  }"
+    );
+    let head = login(Some("HEAD")).await;
+    assert!(
+        head.contains("password: string") && !head.contains("otp"),
+        "{head}"
+    );
+    assert_eq!(login(Some("HEAD~1")).await, login(Some("release")).await);
+
+    // Read from the object database; the working tree is left as it was
+    assert!(std::fs::read_to_string(base_dir.join("src/auth.ts"))
+        .unwrap()
+        .contains("otp: string"));
+
+    // Unchanged files are one blob shared by every snapshot
+    let snapshot_file = |prefix: &str| {
+        std::fs::read_dir(state.config.snapshot_dir())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|dir| {
+                dir.file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .starts_with(prefix)
+            })
+            .unwrap()
+            .join("tree/src/session.ts")
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let inode = |path: PathBuf| std::fs::metadata(path).unwrap().ino();
+        assert_eq!(
+            inode(snapshot_file("release-")),
+            inode(snapshot_file("HEAD-"))
+        );
+    }
+
+    // A branch that moved on is brought up to date
+    let head_commit = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("release", &head_commit, true).unwrap();
+    assert_eq!(login(Some("release")).await, login(Some("HEAD")).await);
+
+    // Revisions that do not exist are reported
+    let error = call_tool(
+        &state,
+        CallToolRequestParams {
+            name: "get_file_symbols".to_string(),
+            arguments: Some(
+                serde_json::json!({ "file_path": "src/auth.ts", "revision": "no-such-branch" })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            ),
+            meta: None,
+            task: None,
+        },
+    )
+    .await;
+    assert!(
+        error.is_err() || error.unwrap().is_error == Some(true),
+        "unknown revision should fail"
+    );
+}
+
+#[tokio::test]
+async fn test_compare_revisions_reports_symbol_level_changes() {
+    use code_intelligence_mcp_server::server::call_tool;
+    use rust_mcp_sdk::schema::{CallToolRequestParams, ContentBlock};
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state().await;
+    let write = |path: &str, content: &str| {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    let format_name = "export function formatName(first: string, last: string) {\n  return `${first} ${last}`;\n}\n";
+    write(
+        "src/auth.ts",
+        "export class AuthService {\n  login(user: string) {\n    return user;\n  }\n\n  logout() {\n    return true;\n  }\n}\n\nexport function verify(token: string) {\n  const parts = token.split(\".\");\n  return parts.length === 3;\n}\n",
+    );
+    write(
+        "src/util.ts",
+        "export function helper() {\n  return 1;\n}\n\nfunction internal() {\n  return 2;\n}\n\nexport function legacyCheck() {\n  return internal();\n}\n",
+    );
+    write(
+        "src/session.ts",
+        "import { verify } from \"./auth\";\nimport { legacyCheck } from \"./util\";\n\nexport function startSession(token: string) {\n  return verify(token);\n}\n\nexport function endSession() {\n  return legacyCheck();\n}\n",
+    );
+    write("src/legacy/format.ts", format_name);
+
+    let repo = git2::Repository::init(&base_dir).unwrap();
+    let commit = |message: &str| {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["src"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.update_all(["src"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    };
+    commit("first");
+
+    write(
+        "src/auth.ts",
+        "export class AuthService {\n  login(user: string, password: string) {\n    return user;\n  }\n\n  refresh() {\n    return false;\n  }\n}\n\nexport function isValidToken(token: string) {\n  const parts = token.split(\".\");\n  return parts.length === 3;\n}\n",
+    );
+    write(
+        "src/util.ts",
+        "export function helper() {\n  return 1;\n}\n\nexport function internal() {\n  return 2;\n}\n",
+    );
+    write(
+        "src/session.ts",
+        "import { isValidToken } from \"./auth\";\nimport { helper } from \"./util\";\n\nexport function startSession(token: string) {\n  return isValidToken(token) && helper();\n}\n\nexport function endSession() {\n  return true;\n}\n",
+    );
+    std::fs::remove_file(base_dir.join("src/legacy/format.ts")).unwrap();
+    write("src/text/format.ts", format_name);
+    commit("second");
+    state.indexer.index_all().await.unwrap();
+
+    let compare = |arguments: serde_json::Value| {
+        let state = &state;
+        async move {
+            let result = call_tool(
+                state,
+                CallToolRequestParams {
+                    name: "compare_revisions".to_string(),
+                    arguments: Some(arguments.as_object().unwrap().clone()),
+                    meta: None,
+                    task: None,
+                },
+            )
+            .await
+            .unwrap();
+            let ContentBlock::TextContent(text) = &result.content[0] else {
+                panic!("{result:?}");
+            };
+            serde_json::from_str::<serde_json::Value>(&text.text).unwrap()
+        }
+    };
+    let names = |value: &serde_json::Value, key: &str| -> Vec<String> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item[key].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let result = compare(serde_json::json!({ "base": "HEAD~1", "head": "HEAD" })).await;
+    let renamed = &result["renamed"][0];
+    assert_eq!(
+        (renamed["old_name"].as_str(), renamed["new_name"].as_str()),
+        (Some("verify"), Some("isValidToken")),
+        "{result:#}"
+    );
+    let login = result["signature_changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["symbol_name"] == "AuthService.login")
+        .unwrap_or_else(|| panic!("{result:#}"));
+    assert_eq!(login["old_signature"], "login(user: string)");
+    assert_eq!(
+        login["new_signature"],
+        "login(user: string, password: string)"
+    );
+    let removed = names(&result["removed"], "qualified_name");
+    assert!(
+        removed.contains(&"AuthService.logout".to_string()),
+        "{removed:?}"
+    );
+    assert!(removed.contains(&"legacyCheck".to_string()), "{removed:?}");
+    assert_eq!(
+        names(&result["added"], "qualified_name"),
+        vec!["AuthService.refresh"]
+    );
+    assert_eq!(
+        names(&result["export_changes"], "symbol_name"),
+        vec!["internal"]
+    );
+    assert_eq!(result["export_changes"][0]["exported"], true);
+    assert_eq!(
+        result["moved_files"],
+        serde_json::json!([{ "old_path": "src/legacy/format.ts", "new_path": "src/text/format.ts", "identical": true }])
+    );
+
+    // Edges follow renames: startSession still calls the renamed function
+    let new_edges: Vec<(String, String)> = result["new_edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["from"].as_str().unwrap().to_string(),
+                e["to"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    assert!(
+        new_edges.contains(&("startSession".to_string(), "helper".to_string())),
+        "{new_edges:?}"
+    );
+    assert!(
+        !new_edges.iter().any(|(_, to)| to == "isValidToken"),
+        "{new_edges:?}"
+    );
+    let broken = result["broken_edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["from"] == "endSession" && e["to"] == "legacyCheck")
+        .unwrap_or_else(|| panic!("{result:#}"));
+    assert_eq!(broken["reason"], "target_removed");
+    assert!(result["display"]
+        .as_str()
+        .unwrap()
+        .contains("## Renamed (1)"));
+
+    // Against the working tree, which matches HEAD
+    let unchanged = compare(serde_json::json!({ "base": "HEAD" })).await;
+    assert_eq!(unchanged["head"], "working tree");
+    assert!(
+        unchanged["summary"]
+            .as_object()
+            .unwrap()
+            .values()
+            .all(|count| count == 0),
+        "{unchanged:#}"
+    );
+}
+
+#[tokio::test]
+async fn test_check_api_surface_classifies_breaking_changes() {
+    use code_intelligence_mcp_server::handlers::handle_check_api_surface;
+    use code_intelligence_mcp_server::tools::CheckApiSurfaceTool;
+
+    let (state, base_dir) = get_module_summary_tests::create_async_app_state_with(|config| {
+        config.package_detection_enabled = true;
+    })
+    .await;
+    let write = |path: &str, content: &str| {
+        let path = base_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    write(
+        "packages/core/package.json",
+        r#"{ "name": "@acme/core", "version": "1.0.0" }"#,
+    );
+    write(
+        "packages/core/src/index.ts",
+        "export class User {\n  name = \"\";\n}\n\nexport class Admin extends User {\n  grant(role: string): boolean {\n    return true;\n  }\n\n  private audit() {}\n}\n\nexport function parse(text: string, strict?: boolean): User | null {\n  return null;\n}\n\nexport function format(value: string) {\n  return value;\n}\n\nexport function legacy() {}\n\nfunction internal() {}\n",
+    );
+    let repo = git2::Repository::init(&base_dir).unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["packages"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "first", &tree, &[])
+        .unwrap();
+    state.indexer.index_all().await.unwrap();
+
+    let check = |base: Option<&str>, baseline: Option<&str>, write_to: Option<&str>| {
+        handle_check_api_surface(
+            &state,
+            CheckApiSurfaceTool {
+                package: None,
+                base: base.map(String::from),
+                baseline: baseline.map(String::from),
+                head: None,
+                write_to: write_to.map(String::from),
+                limit: None,
+            },
+        )
+    };
+
+    // The surface of the committed code, written as the baseline
+    let result = check(None, None, Some("api")).await.unwrap();
+    assert_eq!(result["packages"][0]["name"], "@acme/core", "{result:#}");
+    let file = base_dir.join("api/@acme_core.api.json");
+    let surface: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    assert_eq!(surface["path"], "packages/core/");
+    let names: Vec<&str> = surface["symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["Admin", "Admin.grant", "User", "format", "legacy", "parse"]
+    );
+    assert_eq!(
+        surface["symbols"][0]["relations"],
+        serde_json::json!(["extends User"])
+    );
+    assert_eq!(
+        surface["symbols"][1]["signature"],
+        "grant(role: string): boolean"
+    );
+
+    write(
+        "packages/core/src/index.ts",
+        "export class User {\n  name = \"\";\n}\n\nexport class Admin {\n  grant(role: string, scope: string): boolean {\n    return true;\n  }\n\n  revoke() {}\n\n  private audit() {}\n}\n\nexport function parse(text: string, strict?: boolean): User {\n  return new User();\n}\n\nexport function format(value: string | number) {\n  return String(value);\n}\n\nfunction internal() {}\n",
+    );
+    state.indexer.index_all().await.unwrap();
+
+    let expected = vec![
+        ("Admin", "relation_removed", true),
+        ("Admin.grant", "parameter_added", true),
+        ("Admin.revoke", "added", false),
+        ("format", "parameter_widened", false),
+        ("legacy", "removed", true),
+        ("parse", "return_type_narrowed", false),
+    ];
+    for (base, baseline) in [(Some("HEAD"), None), (None, Some("api"))] {
+        let result = check(base, baseline, None).await.unwrap();
+        let mut changes: Vec<(&str, &str, bool)> = result["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    c["symbol"].as_str().unwrap(),
+                    c["change"].as_str().unwrap(),
+                    c["breaking"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        changes.sort();
+        assert_eq!(changes, expected, "{result:#}");
+        assert_eq!(result["summary"]["breaking"], 3);
+        assert!(result["display"]
+            .as_str()
+            .unwrap()
+            .contains("## Breaking Changes (3)"));
+    }
+
+    assert!(check(Some("HEAD"), Some("api"), None).await.is_err());
+}
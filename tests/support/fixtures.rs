@@ -18,7 +18,7 @@
 //! ```
 
 use code_intelligence_mcp_server::{
-    config::{Config, EmbeddingsBackend, EmbeddingsDevice},
+    config::{Config, EmbeddingsBackend, EmbeddingsDevice, McpTransport, WatchBackend},
     embeddings::hash::HashEmbedder,
     handlers::AppState,
     indexer::pipeline::IndexPipeline,
@@ -102,6 +102,8 @@ pub fn test_config(tmp_dir: PathBuf) -> Config {
         rank_test_penalty: 0.1,
         rank_popularity_weight: 0.05,
         rank_popularity_cap: 50,
+        rank_history_weight: 0.05,
+        history_max_commits: 1000,
         index_patterns: vec![
             "**/*.ts".to_string(),
             "**/*.tsx".to_string(),
@@ -111,6 +113,7 @@ pub fn test_config(tmp_dir: PathBuf) -> Config {
         watch_mode: false,
         watch_debounce_ms: 100,
         watch_min_index_interval_ms: 50,
+        watch_backend: WatchBackend::Auto,
         max_context_bytes: 200_000,
         index_node_modules: false,
         repo_roots: vec![base_dir_utf8],
@@ -141,6 +144,9 @@ pub fn test_config(tmp_dir: PathBuf) -> Config {
         metrics_enabled: false,
         metrics_port: 9090,
         package_detection_enabled: false,
+        mcp_transport: McpTransport::Stdio,
+        http_bind_addr: "127.0.0.1:8765".to_string(),
+        http_auth_token: None,
     }
 }
 
@@ -269,6 +275,7 @@ pub fn app_state(
         indexer,
         retriever,
         sqlite,
+        snapshots: Default::default(),
     }
 }
 
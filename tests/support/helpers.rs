@@ -99,6 +99,8 @@ pub fn create_test_symbol(
         start_line: 1,
         end_line: 10,
         text: format!("pub fn {}() {{}}", name),
+        qualified_name: name.to_string(),
+        parent_id: None,
     };
 
     sqlite.upsert_symbol(&symbol)?;
@@ -150,6 +152,8 @@ pub fn create_test_symbol_with_text(
         start_line: 1,
         end_line: text.lines().count() as u32,
         text: text.to_string(),
+        qualified_name: name.to_string(),
+        parent_id: None,
     };
 
     sqlite.upsert_symbol(&symbol)?;
@@ -201,6 +205,8 @@ pub fn create_test_symbol_with_language(
         start_line: 1,
         end_line: 10,
         text: format!("// {} language\nexport function {}() {{}}", language, name),
+        qualified_name: name.to_string(),
+        parent_id: None,
     };
 
     sqlite.upsert_symbol(&symbol)?;